@@ -1,10 +1,24 @@
 use std::env;
 use std::fs;
+use std::io;
 use std::path::Path;
+use std::process::Command;
 
-const PACKAGE_NAME: &'static str = env!("CARGO_PKG_NAME");
+const PACKAGE_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// GLSL sources, compiled into OUT_DIR/shaders with the same file name plus a
+/// `.sprv` extension.
+const SHADER_SOURCE_DIR: &str = "shaders/src";
+
+/// Precompiled SPIR-V which is used when glslc isn't installed.
+const PRECOMPILED_SHADER_DIR: &str = "shaders/sprv";
 
 fn main() {
+    write_doc_index();
+    compile_shaders();
+}
+
+fn write_doc_index() {
     let doc_folder = Path::new("target").join("doc");
 
     fs::create_dir_all(&doc_folder).expect("unable to create the doc folder!");
@@ -30,3 +44,63 @@ fn main() {
     )
     .expect("unable to generate documentation index file!");
 }
+
+/// Compile every shader source with glslc, with the same default options
+/// used for the precompiled SPIR-V. The `GLSLC` environment variable can
+/// point at a specific compiler.
+///
+/// Without glslc the precompiled SPIR-V in `shaders/sprv` is copied instead,
+/// so the crate still builds on machines without the Vulkan SDK.
+fn compile_shaders() {
+    println!("cargo:rerun-if-changed={}", SHADER_SOURCE_DIR);
+    println!("cargo:rerun-if-changed={}", PRECOMPILED_SHADER_DIR);
+    println!("cargo:rerun-if-env-changed=GLSLC");
+
+    let out_dir = Path::new(&env::var("OUT_DIR").unwrap()).join("shaders");
+    fs::create_dir_all(&out_dir).expect("unable to create the shader folder!");
+
+    let glslc = env::var("GLSLC").unwrap_or_else(|_| "glslc".to_owned());
+    let mut sources: Vec<_> = fs::read_dir(SHADER_SOURCE_DIR)
+        .expect("unable to read the shader sources!")
+        .map(|entry| entry.expect("unable to read a shader source!").path())
+        .collect();
+    sources.sort();
+
+    let mut compiler_missing = false;
+    for source in sources {
+        let file_name = source.file_name().unwrap().to_str().unwrap();
+        println!("cargo:rerun-if-changed={}", source.display());
+        let output = out_dir.join(format!("{}.sprv", file_name));
+
+        if !compiler_missing {
+            let status = Command::new(&glslc)
+                .arg(&source)
+                .arg("-o")
+                .arg(&output)
+                .status();
+            match status {
+                Ok(status) if status.success() => continue,
+                Ok(status) => {
+                    panic!("glslc failed to compile {:?}: {}", source, status)
+                }
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                    println!(
+                        "cargo:warning={} was not found, using the \
+                         precompiled shaders in {}",
+                        glslc, PRECOMPILED_SHADER_DIR
+                    );
+                    compiler_missing = true;
+                }
+                Err(error) => {
+                    panic!("unable to run {}: {:?}", glslc, error)
+                }
+            }
+        }
+
+        let precompiled = Path::new(PRECOMPILED_SHADER_DIR)
+            .join(format!("{}.sprv", file_name));
+        fs::copy(&precompiled, &output).unwrap_or_else(|error| {
+            panic!("unable to copy {:?}: {:?}", precompiled, error)
+        });
+    }
+}
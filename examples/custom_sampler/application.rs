@@ -52,9 +52,11 @@ impl Application {
         let texture_handle_1 = self.graphics.add_texture(
             self.graphics.read_texture_file("assets/example.png")?,
         )?;
-        let texture_handle_2 = self.graphics.add_texture(
-            self.graphics.read_texture_file("assets/example.png")?,
-        )?;
+        // the atlas deduplicates files, so ask for a second copy explicitly
+        let texture_handle_2 =
+            self.graphics.texture_atlas.add_uncached_texture(
+                self.graphics.read_texture_file("assets/example.png")?,
+            )?;
 
         let sampler = unsafe {
             self.graphics.create_sampler(
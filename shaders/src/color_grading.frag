@@ -0,0 +1,54 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+layout(binding = 0) uniform sampler2D source;
+layout(binding = 1) uniform sampler3D lut;
+layout(binding = 2) uniform sampler2D noise;
+
+layout(location = 0) in vec2 vary_uv;
+
+layout(location = 0) out vec4 frag_color;
+
+layout(push_constant) uniform Grading {
+    float exposure;
+    float inverse_gamma;
+    uint tone_mapping;
+    float lut_strength;
+    float lut_scale;
+    float lut_offset;
+    float levels;
+    uint dither;
+    float inverse_noise_size;
+    float encode_gamma;
+} grading;
+
+void main() {
+    vec4 color = texture(source, vary_uv);
+    vec3 exposed = color.rgb * grading.exposure;
+    vec3 reinhard = exposed / (exposed + 1.0);
+    vec3 aces = clamp(
+        (exposed * (2.51 * exposed + 0.03))
+            / (exposed * (2.43 * exposed + 0.59) + 0.14),
+        0.0,
+        1.0
+    );
+    vec3 mapped = grading.tone_mapping == 1 ? reinhard
+        : (grading.tone_mapping == 2 ? aces : exposed);
+    vec3 corrected = pow(max(mapped, 0.0), vec3(grading.inverse_gamma));
+    vec3 lut_uv = clamp(corrected, 0.0, 1.0) * grading.lut_scale
+        + grading.lut_offset;
+    vec3 graded = texture(lut, lut_uv).rgb;
+    vec3 result = mix(corrected, graded, grading.lut_strength);
+
+    // quantize in the swapchain's encoding, dithering with the noise texel
+    // under this pixel
+    vec3 encoded = pow(max(result, 0.0), vec3(1.0 / grading.encode_gamma));
+    vec2 noise_uv = fract(gl_FragCoord.xy * grading.inverse_noise_size);
+    float threshold = grading.dither == 1
+        ? texture(noise, noise_uv).r
+        : 0.5;
+    vec3 quantized = floor(encoded * grading.levels + threshold)
+        / grading.levels;
+    vec3 decoded = pow(quantized, vec3(grading.encode_gamma));
+    frag_color = vec4(grading.levels > 0.0 ? decoded : result, color.a);
+}
@@ -0,0 +1,19 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+layout(location = 0) in float vary_distance;
+layout(location = 1) in vec4 vary_rgba;
+
+layout(location = 0) out vec4 frag_color;
+
+layout(push_constant) uniform PushConsts {
+    mat4 projection;
+    vec2 viewport_size;
+    float line_width;
+} pushConsts;
+
+void main() {
+    float edge = pushConsts.line_width * 0.5 + 0.5;
+    float coverage = clamp(edge - abs(vary_distance), 0.0, 1.0);
+    frag_color = vec4(vary_rgba.rgb, vary_rgba.a * coverage);
+}
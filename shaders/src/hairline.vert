@@ -0,0 +1,35 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+layout(location = 0) in vec2 pos;
+layout(location = 1) in vec2 other;
+layout(location = 2) in float side;
+layout(location = 3) in vec4 rgba;
+
+layout(location = 0) out float vary_distance;
+layout(location = 1) out vec4 vary_rgba;
+
+layout(push_constant) uniform PushConsts {
+    mat4 projection;
+    vec2 viewport_size;
+    float line_width;
+} pushConsts;
+
+void main() {
+    vec4 clip = pushConsts.projection * vec4(pos, 0.0, 1.0);
+    vec4 other_clip = pushConsts.projection * vec4(other, 0.0, 1.0);
+
+    // work in pixels so the width is the same at any zoom
+    vec2 half_viewport = pushConsts.viewport_size * 0.5;
+    vec2 dir = (other_clip.xy - clip.xy) * half_viewport;
+    dir = dir / max(length(dir), 0.000001);
+
+    // leave a pixel outside the line for the antialiased edge
+    float half_extent = pushConsts.line_width * 0.5 + 1.0;
+    float edge_distance = side * half_extent;
+    vec2 offset = vec2(-dir.y, dir.x) * edge_distance / half_viewport;
+
+    vary_distance = edge_distance;
+    vary_rgba = rgba;
+    gl_Position = clip + vec4(offset, 0.0, 0.0);
+}
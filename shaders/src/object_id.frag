@@ -0,0 +1,32 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+layout(constant_id = 0) const uint MAX_TEXTURES = 1;
+layout(constant_id = 1) const uint MAX_SAMPLERS = 1;
+layout(binding = 0) uniform texture2D textures[MAX_TEXTURES];
+layout(binding = 2) uniform sampler samplers[MAX_SAMPLERS];
+
+layout(location = 0) in vec2 vary_uv;
+layout(location = 1) in vec4 vary_rgba;
+
+layout(location = 0) out uint object_id;
+
+layout(push_constant) uniform PushConsts {
+    mat4 projection;
+    uint texture_index;
+    uint object_id;
+    uint sampler_index;
+} pushConsts;
+
+void main() {
+    vec4 sampled_value = texture(
+        sampler2D(
+            textures[pushConsts.texture_index],
+            samplers[pushConsts.sampler_index]),
+        vary_uv);
+    vec4 color = vary_rgba * sampled_value;
+    if (color.a < 0.01) {
+        discard;
+    }
+    object_id = pushConsts.object_id;
+}
@@ -0,0 +1,37 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+layout(constant_id = 0) const uint MAX_TEXTURES = 1;
+layout(constant_id = 1) const uint MAX_SAMPLERS = 1;
+layout(binding = 0) uniform texture2D textures[MAX_TEXTURES];
+layout(binding = 2) uniform sampler samplers[MAX_SAMPLERS];
+
+layout(location = 0) in vec2 vary_uv;
+layout(location = 1) in vec4 vary_rgba;
+
+layout(location = 0) out vec4 frag_color;
+
+layout(push_constant) uniform PushConsts {
+    mat4 projection;
+    uint texture_index;
+    uint palette_index;
+    uint sampler_index;
+} pushConsts;
+
+void main() {
+    float red = texture(
+        sampler2D(
+            textures[pushConsts.texture_index],
+            samplers[pushConsts.sampler_index]),
+        vary_uv).r;
+
+    // round to the nearest index, then sample the center of its palette texel
+    float index = round(red * 255.0);
+    vec2 palette_uv = vec2((index + 0.5) / 256.0, 0.5);
+    vec4 color = texture(
+        sampler2D(
+            textures[pushConsts.palette_index],
+            samplers[pushConsts.sampler_index]),
+        palette_uv);
+    frag_color = vary_rgba * color;
+}
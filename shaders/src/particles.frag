@@ -0,0 +1,14 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+layout(location = 0) in vec2 vary_corner;
+layout(location = 1) in vec4 vary_rgba;
+
+layout(location = 0) out vec4 out_color;
+
+void main() {
+    // round the quad off into a soft dot
+    float coverage = clamp(1.0 - length(vary_corner), 0.0, 1.0);
+    out_color = vary_rgba;
+    out_color.a = vary_rgba.a * coverage;
+}
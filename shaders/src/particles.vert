@@ -0,0 +1,44 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+struct Particle {
+    vec2 pos;
+    vec2 vel;
+    vec4 rgba;
+    float age;
+    float lifetime;
+    float size;
+    float padding;
+};
+
+layout(std430, set = 0, binding = 1) readonly buffer Alive {
+    Particle particles[];
+} alive;
+
+layout(location = 0) out vec2 vary_corner;
+layout(location = 1) out vec4 vary_rgba;
+
+layout(push_constant) uniform PushConsts {
+    mat4 projection;
+} pushConsts;
+
+void main() {
+    Particle particle = alive.particles[gl_InstanceIndex];
+
+    // two triangles per particle, each bit of 22 and 52 is one vertex's x
+    // and y
+    uint corner_bits = uint(gl_VertexIndex);
+    vec2 unit_corner = vec2(
+        float((22u >> corner_bits) & 1u),
+        float((52u >> corner_bits) & 1u));
+    vec2 corner = unit_corner * 2.0 - vec2(1.0, 1.0);
+
+    vec2 pos = particle.pos + corner * particle.size;
+    gl_Position = pushConsts.projection * vec4(pos, 0.0, 1.0);
+
+    // fade out over the particle's lifetime
+    float fade = 1.0 - particle.age / particle.lifetime;
+    vary_corner = corner;
+    vary_rgba = particle.rgba;
+    vary_rgba.a = particle.rgba.a * fade;
+}
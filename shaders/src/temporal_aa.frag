@@ -0,0 +1,20 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+layout(binding = 0) uniform sampler2D current;
+layout(binding = 1) uniform sampler2D history;
+
+layout(location = 0) in vec2 vary_uv;
+
+layout(location = 0) out vec4 frag_color;
+
+layout(push_constant) uniform Accumulation {
+    float blend;
+} accumulation;
+
+// Blend the jittered frame into the running average of earlier frames.
+void main() {
+    vec4 previous = texture(history, vary_uv);
+    vec4 latest = texture(current, vary_uv);
+    frag_color = mix(previous, latest, accumulation.blend);
+}
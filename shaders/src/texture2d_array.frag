@@ -0,0 +1,30 @@
+#version 450
+#extension GL_ARB_separate_shader_objects: enable
+
+// The same as texture2d.frag, but every texture is a 2d array texture and
+// the batch's layer picks which of its layers is drawn.
+layout(constant_id = 0) const uint MAX_TEXTURES = 1;
+layout(constant_id = 1) const uint MAX_SAMPLERS = 1;
+layout(binding = 0) uniform texture2DArray textures[MAX_TEXTURES];
+layout(binding = 2) uniform sampler samplers[MAX_SAMPLERS];
+
+layout(location = 0) in vec2 vary_uv;
+layout(location = 1) in vec4 vary_rgba;
+
+layout(location = 0) out vec4 frag_color;
+
+layout(push_constant) uniform PushConsts {
+    mat4 projection;
+    uint texture_index;
+    layout(offset = 72) uint sampler_index;
+    uint layer;
+} pushConsts;
+
+void main() {
+    vec4 sampled_value = texture(
+        sampler2DArray(
+            textures[pushConsts.texture_index],
+            samplers[pushConsts.sampler_index]),
+        vec3(vary_uv, float(pushConsts.layer)));
+    frag_color = vary_rgba * sampled_value;
+}
@@ -0,0 +1,28 @@
+use crate::graphics::vulkan::{ExtensionRequests, Instance};
+
+use anyhow::Result;
+use ash::extensions::khr::{AndroidSurface, Surface};
+use std::sync::Arc;
+
+impl Instance {
+    /// Create an instance with the extensions needed for Android window
+    /// surfaces.
+    pub fn for_android() -> Result<Arc<Self>> {
+        Self::for_android_with_extensions(&ExtensionRequests::new())
+    }
+
+    /// Create an instance with the extensions needed for Android window
+    /// surfaces, along with additional extensions.
+    pub fn for_android_with_extensions(
+        instance_extensions: &ExtensionRequests,
+    ) -> Result<Arc<Self>> {
+        let surface_extensions = ExtensionRequests {
+            required: vec![
+                Surface::name().to_str()?.to_owned(),
+                AndroidSurface::name().to_str()?.to_owned(),
+            ],
+            optional: vec![],
+        };
+        Self::with_extensions(&surface_extensions.merge(instance_extensions))
+    }
+}
@@ -0,0 +1,168 @@
+//! A window surface for Android applications.
+//!
+//! Android owns the application's window. It hands the app an
+//! `ANativeWindow` when the activity's surface is created, and takes it back
+//! whenever the activity is paused. With ndk-glue the window comes from
+//! `ndk_glue::native_window()` and the lifecycle arrives as
+//! `Event::WindowCreated` and `Event::WindowDestroyed`.
+//!
+//! The vulkan surface can't outlive the native window, so the application
+//! tears everything down in order when the window is destroyed:
+//!
+//! ```ignore
+//! graphics.suspend()?;
+//! android_window.suspend();
+//! ```
+//!
+//! and builds it back up when a new window is created:
+//!
+//! ```ignore
+//! unsafe { android_window.resume(native_window.ptr().as_ptr() as _)? };
+//! graphics.resume(&android_window)?;
+//! ```
+//!
+//! This crate doesn't depend on ndk-glue and doesn't hook into its event
+//! loop itself. ndk-glue provides the application's entry point with
+//! `#[ndk_glue::main]` and only one version of it can own the activity, so
+//! the application picks the version and forwards the window events. The
+//! window is passed in as a raw `ANativeWindow` pointer, which every version
+//! of ndk-glue can provide.
+
+mod android_instance;
+mod native_window;
+mod window_surface;
+
+use crate::graphics::vulkan::{ExtensionRequests, Instance};
+
+use anyhow::{Context, Result};
+use ash::{
+    extensions::khr::{AndroidSurface, Surface},
+    vk,
+};
+use std::{ptr::null_mut, sync::Arc};
+
+/// Resources required for rendering to an Android application's native
+/// window.
+pub struct AndroidWindow {
+    /// The native window the surface was created for, null while suspended.
+    /// A reference is held so the window can't be freed under the surface.
+    native_window: *mut vk::ANativeWindow,
+
+    /// The raw vulkan surface handle, null while suspended.
+    surface: vk::SurfaceKHR,
+
+    /// Extension functions for interacting with the surface
+    surface_loader: Surface,
+
+    /// Extension functions for creating surfaces from native windows
+    android_surface_loader: AndroidSurface,
+
+    /// The instance must not be destroyed before the WindowSurface
+    instance: Arc<Instance>,
+}
+
+impl AndroidWindow {
+    /// Create a vulkan surface for the application's native window.
+    ///
+    /// # Safety
+    ///
+    /// - `native_window` must be a valid `ANativeWindow` pointer, like the
+    ///   one held by ndk-glue between `WindowCreated` and `WindowDestroyed`
+    /// - the window must not be destroyed before `suspend` is called or this
+    ///   AndroidWindow is dropped
+    pub unsafe fn new(native_window: *mut vk::ANativeWindow) -> Result<Self> {
+        Self::with_instance_extensions(native_window, &ExtensionRequests::new())
+    }
+
+    /// Create a vulkan surface for the application's native window with
+    /// additional instance extensions.
+    ///
+    /// # Safety
+    ///
+    /// See `AndroidWindow::new`.
+    pub unsafe fn with_instance_extensions(
+        native_window: *mut vk::ANativeWindow,
+        instance_extensions: &ExtensionRequests,
+    ) -> Result<Self> {
+        let instance =
+            Instance::for_android_with_extensions(instance_extensions)?;
+        Self::with_instance(instance, native_window)
+    }
+
+    /// Create a vulkan surface for the application's native window with an
+    /// existing instance, see `Instance::for_android`.
+    ///
+    /// # Safety
+    ///
+    /// See `AndroidWindow::new`.
+    pub unsafe fn with_instance(
+        instance: Arc<Instance>,
+        native_window: *mut vk::ANativeWindow,
+    ) -> Result<Self> {
+        let mut android_window = Self {
+            native_window: null_mut(),
+            surface: vk::SurfaceKHR::null(),
+            surface_loader: instance.create_surface_loader(),
+            android_surface_loader: instance.create_android_surface_loader(),
+            instance,
+        };
+        android_window.resume(native_window)?;
+        Ok(android_window)
+    }
+
+    /// Destroy the vulkan surface and release the native window.
+    ///
+    /// Call this when Android destroys the window, after
+    /// `Graphics::suspend` has destroyed the swapchain. Nothing can be
+    /// rendered until `resume` is called with the next window.
+    pub fn suspend(&mut self) {
+        if self.is_suspended() {
+            return;
+        }
+        unsafe {
+            // SAFE: the application suspends graphics before the window
+            self.surface_loader.destroy_surface(self.surface, None);
+            native_window::release(self.native_window);
+        }
+        self.surface = vk::SurfaceKHR::null();
+        self.native_window = null_mut();
+    }
+
+    /// Create a vulkan surface for the native window Android provides when
+    /// the activity resumes. Follow with `Graphics::resume`.
+    ///
+    /// Any current surface is destroyed first, so the swapchain must
+    /// already be suspended.
+    ///
+    /// # Safety
+    ///
+    /// See `AndroidWindow::new`.
+    pub unsafe fn resume(
+        &mut self,
+        native_window: *mut vk::ANativeWindow,
+    ) -> Result<()> {
+        self.suspend();
+        let create_info = vk::AndroidSurfaceCreateInfoKHR {
+            window: native_window,
+            ..Default::default()
+        };
+        self.surface = self
+            .android_surface_loader
+            .create_android_surface(&create_info, None)
+            .context("unable to create the android vulkan surface")?;
+        native_window::acquire(native_window);
+        self.native_window = native_window;
+        Ok(())
+    }
+
+    /// True while there's no native window to render to.
+    pub fn is_suspended(&self) -> bool {
+        self.surface == vk::SurfaceKHR::null()
+    }
+}
+
+impl Drop for AndroidWindow {
+    fn drop(&mut self) {
+        self.suspend();
+    }
+}
@@ -0,0 +1,44 @@
+//! The parts of the NDK's `ANativeWindow` api used by `AndroidWindow`.
+
+use ash::vk;
+
+#[link(name = "android")]
+extern "C" {
+    fn ANativeWindow_acquire(window: *mut vk::ANativeWindow);
+    fn ANativeWindow_release(window: *mut vk::ANativeWindow);
+    fn ANativeWindow_getWidth(window: *mut vk::ANativeWindow) -> i32;
+    fn ANativeWindow_getHeight(window: *mut vk::ANativeWindow) -> i32;
+}
+
+/// Take a reference to the window so it isn't freed while in use.
+///
+/// # Safety
+///
+/// - `window` must be a valid `ANativeWindow` pointer
+pub unsafe fn acquire(window: *mut vk::ANativeWindow) {
+    ANativeWindow_acquire(window);
+}
+
+/// Give up a reference taken with `acquire`.
+///
+/// # Safety
+///
+/// - `window` must have been acquired and not yet released
+pub unsafe fn release(window: *mut vk::ANativeWindow) {
+    ANativeWindow_release(window);
+}
+
+/// The window's size in pixels, or zero when there is no window.
+pub fn size(window: *mut vk::ANativeWindow) -> (u32, u32) {
+    if window.is_null() {
+        return (0, 0);
+    }
+    // SAFE: the window is held by the AndroidWindow until it's suspended
+    let (width, height) = unsafe {
+        (
+            ANativeWindow_getWidth(window),
+            ANativeWindow_getHeight(window),
+        )
+    };
+    (width.max(0) as u32, height.max(0) as u32)
+}
@@ -0,0 +1,215 @@
+use super::{
+    fft, Analyzer, AnalyzerSettings, AudioAnalysis, BAND_COUNT, HOP_SIZE,
+    WINDOW_SIZE,
+};
+
+impl Default for AnalyzerSettings {
+    fn default() -> Self {
+        Self {
+            min_frequency: 40.0,
+            max_frequency: 16_000.0,
+            smoothing: 0.8,
+            beat_cutoff: 150.0,
+            beat_threshold: 1.5,
+            beat_decay: 0.01,
+        }
+    }
+}
+
+impl Analyzer {
+    /// Create an analyzer for audio at the given sample rate.
+    pub fn new(sample_rate: u32, settings: AnalyzerSettings) -> Self {
+        Self {
+            settings,
+            sample_rate: sample_rate as f32,
+            samples: vec![0.0; WINDOW_SIZE],
+            pending: 0,
+            energy_history: vec![],
+            analysis: AudioAnalysis::default(),
+        }
+    }
+
+    /// The most recent analysis.
+    pub fn analysis(&self) -> AudioAnalysis {
+        self.analysis
+    }
+
+    /// Add mono samples, analyzing again each time enough have arrived.
+    ///
+    /// Returns true when the analysis changed.
+    pub fn push_samples(&mut self, samples: &[f32]) -> bool {
+        let mut analyzed = false;
+        for chunk in samples.chunks(HOP_SIZE) {
+            let take = chunk.len().min(HOP_SIZE - self.pending);
+            self.append(&chunk[..take]);
+            if self.pending == HOP_SIZE {
+                self.analyze();
+                analyzed = true;
+            }
+            self.append(&chunk[take..]);
+        }
+        analyzed
+    }
+
+    /// Keep the most recent window of samples.
+    fn append(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        self.samples.drain(..samples.len());
+        self.samples.extend_from_slice(samples);
+        self.pending += samples.len();
+    }
+
+    /// Analyze the current window.
+    fn analyze(&mut self) {
+        self.pending = 0;
+        let hop_seconds = HOP_SIZE as f32 / self.sample_rate;
+        let spectrum = fft::amplitude_spectrum(&self.samples);
+        let bin_width = self.sample_rate / WINDOW_SIZE as f32;
+
+        // bands
+        let edges = self.band_edges();
+        for band in 0..BAND_COUNT {
+            let first = ((edges[band] / bin_width) as usize).max(1);
+            let last = ((edges[band + 1] / bin_width) as usize)
+                .max(first + 1)
+                .min(spectrum.len());
+            let amplitude = spectrum[first.min(last)..last]
+                .iter()
+                .cloned()
+                .fold(0.0, f32::max);
+            let previous = self.analysis.bands[band] * self.settings.smoothing;
+            self.analysis.bands[band] = amplitude.max(previous);
+        }
+
+        // level
+        let square_sum: f32 = self.samples.iter().map(|s| s * s).sum();
+        self.analysis.level = (square_sum / WINDOW_SIZE as f32).sqrt();
+
+        // beats
+        let cutoff = ((self.settings.beat_cutoff / bin_width) as usize)
+            .max(2)
+            .min(spectrum.len());
+        let energy: f32 = spectrum[1..cutoff].iter().map(|a| a * a).sum();
+        let history_length = (1.0 / hop_seconds).ceil() as usize;
+        let average = if self.energy_history.is_empty() {
+            0.0
+        } else {
+            self.energy_history.iter().sum::<f32>()
+                / self.energy_history.len() as f32
+        };
+        self.analysis.beat_energy = if average > f32::EPSILON {
+            energy / average
+        } else {
+            0.0
+        };
+
+        self.analysis.beat *= self.settings.beat_decay.powf(hop_seconds);
+        self.analysis.time_since_beat += hop_seconds;
+        let is_beat = self.energy_history.len() == history_length
+            && self.analysis.beat_energy > self.settings.beat_threshold
+            && self.analysis.time_since_beat > hop_seconds;
+        if is_beat {
+            self.analysis.beat = 1.0;
+            self.analysis.time_since_beat = 0.0;
+        }
+
+        self.energy_history.push(energy);
+        if self.energy_history.len() > history_length {
+            self.energy_history.remove(0);
+        }
+    }
+
+    /// The frequency at the edge of each band, spaced logarithmically.
+    fn band_edges(&self) -> [f32; BAND_COUNT + 1] {
+        let nyquist = self.sample_rate / 2.0;
+        let low = self.settings.min_frequency.max(1.0).min(nyquist);
+        let high = self.settings.max_frequency.min(nyquist).max(low);
+        let ratio = (high / low).powf(1.0 / BAND_COUNT as f32);
+        let mut edges = [low; BAND_COUNT + 1];
+        for i in 1..=BAND_COUNT {
+            edges[i] = edges[i - 1] * ratio;
+        }
+        edges
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 44_100;
+
+    fn tone(frequency: f32, amplitude: f32, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                amplitude * (2.0 * PI * frequency * t).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nothing_should_be_analyzed_until_a_hop_arrives() {
+        let mut analyzer = Analyzer::new(SAMPLE_RATE, Default::default());
+        assert!(!analyzer.push_samples(&[0.0; HOP_SIZE - 1]));
+        assert!(analyzer.push_samples(&[0.0; 1]));
+    }
+
+    #[test]
+    fn low_tones_should_fill_the_first_band() {
+        let mut analyzer = Analyzer::new(SAMPLE_RATE, Default::default());
+        analyzer.push_samples(&tone(60.0, 1.0, WINDOW_SIZE * 4));
+        let analysis = analyzer.analysis();
+        assert!(analysis.bands[0] > 0.5, "{:?}", analysis.bands);
+        assert!(
+            analysis.bands[BAND_COUNT - 1] < 0.01,
+            "{:?}",
+            analysis.bands
+        );
+    }
+
+    #[test]
+    fn high_tones_should_fill_the_last_band() {
+        let mut analyzer = Analyzer::new(SAMPLE_RATE, Default::default());
+        analyzer.push_samples(&tone(12_000.0, 1.0, WINDOW_SIZE * 4));
+        let analysis = analyzer.analysis();
+        assert!(analysis.bands[BAND_COUNT - 1] > 0.5, "{:?}", analysis.bands);
+        assert!(analysis.bands[0] < 0.01, "{:?}", analysis.bands);
+    }
+
+    #[test]
+    fn level_should_be_the_rms_amplitude() {
+        let mut analyzer = Analyzer::new(SAMPLE_RATE, Default::default());
+        analyzer.push_samples(&tone(1000.0, 0.5, WINDOW_SIZE * 2));
+        let expected = 0.5 / 2.0_f32.sqrt();
+        assert!((analyzer.analysis().level - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn bands_should_fall_off_smoothly() {
+        let mut analyzer = Analyzer::new(SAMPLE_RATE, Default::default());
+        analyzer.push_samples(&tone(60.0, 1.0, WINDOW_SIZE * 4));
+        let loud = analyzer.analysis().bands[0];
+        analyzer.push_samples(&[0.0; WINDOW_SIZE]);
+        let quiet = analyzer.analysis().bands[0];
+        assert!(quiet < loud);
+        assert!(quiet > 0.0);
+    }
+
+    #[test]
+    fn a_kick_after_quiet_bass_should_be_a_beat() {
+        let mut analyzer = Analyzer::new(SAMPLE_RATE, Default::default());
+        analyzer.push_samples(&tone(60.0, 0.1, SAMPLE_RATE as usize * 2));
+        assert_eq!(analyzer.analysis().beat, 0.0);
+
+        analyzer.push_samples(&tone(60.0, 1.0, WINDOW_SIZE));
+        let analysis = analyzer.analysis();
+        assert!(analysis.beat > 0.9, "{:?}", analysis);
+        assert!(analysis.beat_energy > 1.5);
+        assert!(analysis.time_since_beat < 0.05);
+    }
+}
@@ -0,0 +1,143 @@
+use super::{Analyzer, AnalyzerSettings, AudioAnalysis, AudioInput};
+
+use anyhow::{Context, Result};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Sample, SampleFormat,
+};
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+impl AudioInput {
+    /// Capture the host's default input device.
+    pub fn default_device() -> Result<Self> {
+        Self::with_settings(AnalyzerSettings::default())
+    }
+
+    /// Capture the host's default input device with custom analysis
+    /// settings.
+    pub fn with_settings(settings: AnalyzerSettings) -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_input_device()
+            .context("there is no default audio input device")?;
+        Self::from_device(&device, settings)
+    }
+
+    /// Capture a specific device, like one found with `cpal::available_hosts`.
+    pub fn from_device(
+        device: &cpal::Device,
+        settings: AnalyzerSettings,
+    ) -> Result<Self> {
+        let device_name = device
+            .name()
+            .unwrap_or_else(|_| "unknown input device".to_owned());
+        let supported_config = device
+            .default_input_config()
+            .with_context(|| format!("no input config for {}", device_name))?;
+        let sample_format = supported_config.sample_format();
+        let config: cpal::StreamConfig = supported_config.into();
+        let channels = config.channels as usize;
+        let sample_rate = config.sample_rate.0;
+
+        let (sender, receiver) = mpsc::channel::<Vec<f32>>();
+        let stream = match sample_format {
+            SampleFormat::F32 => {
+                build_stream::<f32>(device, &config, channels, sender)
+            }
+            SampleFormat::I16 => {
+                build_stream::<i16>(device, &config, channels, sender)
+            }
+            SampleFormat::U16 => {
+                build_stream::<u16>(device, &config, channels, sender)
+            }
+        }
+        .with_context(|| format!("unable to capture {}", device_name))?;
+
+        let latest = Arc::new(Mutex::new(AudioAnalysis::default()));
+        let thread_latest = latest.clone();
+        let analysis_thread = thread::Builder::new()
+            .name("draw2d audio analysis".to_owned())
+            .spawn(move || {
+                let mut analyzer = Analyzer::new(sample_rate, settings);
+                // the loop ends when the stream, which owns the sender, is
+                // dropped
+                for samples in receiver {
+                    if analyzer.push_samples(&samples) {
+                        *thread_latest.lock().unwrap() = analyzer.analysis();
+                    }
+                }
+            })?;
+
+        stream
+            .play()
+            .with_context(|| format!("unable to start {}", device_name))?;
+        log::info!("capturing audio from {} at {}hz", device_name, sample_rate);
+
+        Ok(Self {
+            stream: Some(stream),
+            latest,
+            analysis_thread: Some(analysis_thread),
+            device_name,
+            sample_rate,
+        })
+    }
+
+    /// The most recent analysis of the captured audio.
+    pub fn analysis(&self) -> AudioAnalysis {
+        *self.latest.lock().unwrap()
+    }
+
+    /// The name of the device being captured.
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    /// The sample rate of the device being captured, in hertz.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+impl Drop for AudioInput {
+    /// Stop capturing, then wait for the analysis thread to finish.
+    fn drop(&mut self) {
+        self.stream.take();
+        if let Some(analysis_thread) = self.analysis_thread.take() {
+            if analysis_thread.join().is_err() {
+                log::error!("the audio analysis thread panicked");
+            }
+        }
+    }
+}
+
+/// Build an input stream which mixes each callback's samples down to mono
+/// and sends them to the analysis thread.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    channels: usize,
+    sender: mpsc::Sender<Vec<f32>>,
+) -> Result<cpal::Stream>
+where
+    T: Sample,
+{
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let mono = data
+                .chunks(channels)
+                .map(|frame| {
+                    frame.iter().map(|sample| sample.to_f32()).sum::<f32>()
+                        / channels as f32
+                })
+                .collect();
+            // the analysis thread only stops after the stream is dropped
+            let _ = sender.send(mono);
+        },
+        |error| log::error!("audio input error: {:?}", error),
+    )?;
+    Ok(stream)
+}
@@ -0,0 +1,120 @@
+use std::f32::consts::PI;
+
+/// Compute the amplitude of each frequency bin for a window of samples.
+///
+/// A Hann window is applied first. Amplitudes are normalized so a full scale
+/// sine wave which lands exactly on a bin has an amplitude of 1. Only the
+/// first half of the spectrum is returned because the input is real.
+///
+/// The number of samples must be a power of two.
+pub fn amplitude_spectrum(samples: &[f32]) -> Vec<f32> {
+    let size = samples.len();
+    assert!(size.is_power_of_two(), "fft size must be a power of two");
+
+    let window: Vec<f32> = (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / size as f32).cos())
+        .collect();
+    let window_sum: f32 = window.iter().sum();
+
+    let mut real: Vec<f32> = samples
+        .iter()
+        .zip(&window)
+        .map(|(sample, weight)| sample * weight)
+        .collect();
+    let mut imaginary = vec![0.0; size];
+    fft_in_place(&mut real, &mut imaginary);
+
+    (0..size / 2)
+        .map(|bin| {
+            let magnitude = (real[bin] * real[bin]
+                + imaginary[bin] * imaginary[bin])
+                .sqrt();
+            2.0 * magnitude / window_sum
+        })
+        .collect()
+}
+
+/// An iterative radix-2 Cooley-Tukey transform.
+fn fft_in_place(real: &mut [f32], imaginary: &mut [f32]) {
+    let size = real.len();
+
+    // reorder the input by bit reversed index
+    let mut j = 0;
+    for i in 1..size {
+        let mut bit = size >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            real.swap(i, j);
+            imaginary.swap(i, j);
+        }
+    }
+
+    let mut length = 2;
+    while length <= size {
+        let angle = -2.0 * PI / length as f32;
+        for start in (0..size).step_by(length) {
+            for k in 0..length / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let even = start + k;
+                let odd = even + length / 2;
+                let odd_real = real[odd] * cos - imaginary[odd] * sin;
+                let odd_imaginary = real[odd] * sin + imaginary[odd] * cos;
+                real[odd] = real[even] - odd_real;
+                imaginary[odd] = imaginary[even] - odd_imaginary;
+                real[even] += odd_real;
+                imaginary[even] += odd_imaginary;
+            }
+        }
+        length <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sine(bin: usize, size: usize) -> Vec<f32> {
+        (0..size)
+            .map(|i| (2.0 * PI * bin as f32 * i as f32 / size as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn silence_should_have_no_amplitude() {
+        let spectrum = amplitude_spectrum(&[0.0; 64]);
+        assert_eq!(spectrum.len(), 32);
+        assert!(spectrum.iter().all(|amplitude| *amplitude == 0.0));
+    }
+
+    #[test]
+    fn sine_should_peak_at_its_bin() {
+        let spectrum = amplitude_spectrum(&sine(10, 256));
+        let (peak, amplitude) = spectrum
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+            .unwrap();
+        assert_eq!(peak, 10);
+        assert!((amplitude - 1.0).abs() < 1e-3, "{}", amplitude);
+    }
+
+    #[test]
+    fn sine_should_stay_near_its_bin() {
+        let spectrum = amplitude_spectrum(&sine(40, 512));
+        for (bin, amplitude) in spectrum.iter().enumerate() {
+            if (bin as i32 - 40).abs() > 1 {
+                assert!(*amplitude < 1e-3, "bin {} is {}", bin, amplitude);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn sizes_must_be_powers_of_two() {
+        amplitude_spectrum(&[0.0; 100]);
+    }
+}
@@ -0,0 +1,115 @@
+//! Analyze live audio for audio-reactive visuals.
+//!
+//! This module is only available with the `audio` feature. An `AudioInput`
+//! captures the default input device with `cpal`, mixes it down to mono, and
+//! analyzes it on a background thread. The most recent analysis can be read
+//! every frame and pushed directly to a shader because `AudioAnalysis` has a
+//! std430 compatible layout:
+//!
+//! ```glsl
+//! struct AudioAnalysis {
+//!     float bands[8];
+//!     float level;
+//!     float beat_energy;
+//!     float beat;
+//!     float time_since_beat;
+//! };
+//! ```
+
+mod analyzer;
+mod audio_input;
+mod fft;
+
+use std::{
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+/// The number of frequency bands in each analysis.
+pub const BAND_COUNT: usize = 8;
+
+/// The number of samples analyzed at once.
+pub const WINDOW_SIZE: usize = 1024;
+
+/// The number of new samples between each analysis. Windows overlap by half.
+pub const HOP_SIZE: usize = WINDOW_SIZE / 2;
+
+/// The result of analyzing the most recent window of audio.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct AudioAnalysis {
+    /// The smoothed amplitude of each frequency band, from bass to treble.
+    /// Bands are spaced logarithmically, a full scale sine wave reaches 1.
+    pub bands: [f32; BAND_COUNT],
+
+    /// The root mean square amplitude of the window.
+    pub level: f32,
+
+    /// The bass energy relative to its recent average. Values well above 1
+    /// are beats.
+    pub beat_energy: f32,
+
+    /// 1 when a beat is detected, then decays towards 0. Useful for pulsing
+    /// things in time with the music.
+    pub beat: f32,
+
+    /// Seconds since the last detected beat.
+    pub time_since_beat: f32,
+}
+
+/// Settings which control how audio is analyzed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AnalyzerSettings {
+    /// The lowest frequency, in hertz, included in the first band.
+    pub min_frequency: f32,
+
+    /// The highest frequency, in hertz, included in the last band. Clamped
+    /// to the nyquist frequency.
+    pub max_frequency: f32,
+
+    /// How much of the previous band values is kept when new values are
+    /// lower, in the range 0 to 1. Higher values fall off more slowly.
+    pub smoothing: f32,
+
+    /// Frequencies below this, in hertz, contribute to beat detection.
+    pub beat_cutoff: f32,
+
+    /// How far bass energy must rise above its recent average to be a beat.
+    pub beat_threshold: f32,
+
+    /// The fraction of the beat value which remains after a second.
+    pub beat_decay: f32,
+}
+
+/// Turns windows of mono samples into an `AudioAnalysis`.
+pub struct Analyzer {
+    settings: AnalyzerSettings,
+    sample_rate: f32,
+
+    /// The most recent samples, oldest first.
+    samples: Vec<f32>,
+
+    /// Samples received since the last analysis.
+    pending: usize,
+
+    /// Bass energy for roughly the last second of windows.
+    energy_history: Vec<f32>,
+
+    analysis: AudioAnalysis,
+}
+
+/// Captures an audio input device and analyzes it on a background thread.
+///
+/// The capture stops when this is dropped.
+pub struct AudioInput {
+    /// The device's stream, which sends samples to the analysis thread.
+    stream: Option<cpal::Stream>,
+
+    /// The most recent analysis, written by the analysis thread.
+    latest: Arc<Mutex<AudioAnalysis>>,
+
+    analysis_thread: Option<JoinHandle<()>>,
+
+    device_name: String,
+    sample_rate: u32,
+}
@@ -0,0 +1,229 @@
+use super::{CameraKeyframe, CameraPath, CameraPose, Easing, OrthoCamera};
+
+use nalgebra as na;
+
+impl CameraPose {
+    /// A pose at a position, with no zoom or rotation.
+    pub fn at(position: na::Point2<f32>) -> Self {
+        Self {
+            position,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// Set the pose's zoom.
+    pub fn with_zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// Set the pose's rotation in radians.
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// The pose part way from this pose to another, where `t` is already
+    /// eased.
+    fn interpolate(&self, other: &CameraPose, t: f32) -> CameraPose {
+        let zoom = if self.zoom > 0.0 && other.zoom > 0.0 {
+            self.zoom * (other.zoom / self.zoom).powf(t)
+        } else {
+            self.zoom + (other.zoom - self.zoom) * t
+        };
+        CameraPose {
+            position: self.position + (other.position - self.position) * t,
+            zoom,
+            rotation: self.rotation + (other.rotation - self.rotation) * t,
+        }
+    }
+}
+
+impl CameraPath {
+    /// Create an empty path. `viewport_height` is the height of the world
+    /// seen at a zoom of 1.
+    pub fn new(viewport_height: f32) -> Self {
+        Self {
+            keyframes: vec![],
+            viewport_height,
+        }
+    }
+
+    /// Add a keyframe, returning the path for chaining.
+    pub fn with_keyframe(
+        mut self,
+        time: f32,
+        pose: CameraPose,
+        easing: Easing,
+    ) -> Self {
+        self.add_keyframe(CameraKeyframe { time, pose, easing });
+        self
+    }
+
+    /// Add a keyframe. Keyframes can be added in any order, a keyframe at
+    /// the same time as an existing one is placed after it.
+    pub fn add_keyframe(&mut self, keyframe: CameraKeyframe) {
+        let index = self
+            .keyframes
+            .iter()
+            .position(|existing| existing.time > keyframe.time)
+            .unwrap_or(self.keyframes.len());
+        self.keyframes.insert(index, keyframe);
+    }
+
+    /// The path's keyframes, sorted by time.
+    pub fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    /// The time of the last keyframe.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map(|last| last.time).unwrap_or(0.0)
+    }
+
+    /// The height of the world seen at a zoom of 1.
+    pub fn viewport_height(&self) -> f32 {
+        self.viewport_height
+    }
+
+    /// The camera's pose at a time, or None when the path has no keyframes.
+    pub fn pose_at(&self, time: f32) -> Option<CameraPose> {
+        let first = self.keyframes.first()?;
+        if time <= first.time {
+            return Some(first.pose);
+        }
+        let next_index = match self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+        {
+            Some(index) => index,
+            None => return self.keyframes.last().map(|last| last.pose),
+        };
+        let from = &self.keyframes[next_index - 1];
+        let to = &self.keyframes[next_index];
+        let progress = (time - from.time) / (to.time - from.time);
+        Some(from.pose.interpolate(&to.pose, from.easing.apply(progress)))
+    }
+
+    /// Move a camera to its pose at a time. The camera's aspect ratio is
+    /// kept. Nothing changes when the path has no keyframes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use draw2d::camera::*;
+    /// # use approx::assert_relative_eq;
+    /// # use nalgebra as na;
+    /// #
+    /// let path = CameraPath::new(10.0)
+    ///     .with_keyframe(0.0, CameraPose::at(na::Point2::origin()), Easing::Linear)
+    ///     .with_keyframe(
+    ///         2.0,
+    ///         CameraPose::at(na::Point2::new(4.0, 0.0)).with_zoom(2.0),
+    ///         Easing::Linear,
+    ///     );
+    ///
+    /// // when exporting, time usually comes from the frame number
+    /// let mut camera = OrthoCamera::with_viewport(10.0, 16.0 / 9.0);
+    /// path.apply(2.0, &mut camera);
+    ///
+    /// assert_relative_eq!(camera.world_position(), na::Point2::new(4.0, 0.0));
+    /// assert_relative_eq!(camera.viewport_height(), 5.0);
+    /// ```
+    pub fn apply(&self, time: f32, camera: &mut OrthoCamera) {
+        if let Some(pose) = self.pose_at(time) {
+            camera.set_world_position(&pose.position);
+            camera.set_viewport_height(self.viewport_height / pose.zoom);
+            camera.set_rotation(pose.rotation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pose(x: f32) -> CameraPose {
+        CameraPose::at(na::Point2::new(x, 0.0))
+    }
+
+    #[test]
+    fn an_empty_path_should_have_no_pose() {
+        assert_eq!(CameraPath::new(1.0).pose_at(0.0), None);
+    }
+
+    #[test]
+    fn times_outside_the_path_should_hold_the_nearest_pose() {
+        let path = CameraPath::new(1.0)
+            .with_keyframe(1.0, pose(1.0), Easing::Linear)
+            .with_keyframe(2.0, pose(5.0), Easing::Linear);
+
+        assert_eq!(path.pose_at(0.0), Some(pose(1.0)));
+        assert_eq!(path.pose_at(3.0), Some(pose(5.0)));
+        assert_eq!(path.duration(), 2.0);
+    }
+
+    #[test]
+    fn keyframes_should_be_sorted_by_time() {
+        let path = CameraPath::new(1.0)
+            .with_keyframe(2.0, pose(2.0), Easing::Linear)
+            .with_keyframe(0.0, pose(0.0), Easing::Linear)
+            .with_keyframe(1.0, pose(1.0), Easing::Linear);
+
+        let times: Vec<f32> = path
+            .keyframes()
+            .iter()
+            .map(|keyframe| keyframe.time)
+            .collect();
+        assert_eq!(times, vec![0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn segments_should_use_the_first_keyframes_easing() {
+        let path = CameraPath::new(1.0)
+            .with_keyframe(0.0, pose(0.0), Easing::Step)
+            .with_keyframe(1.0, pose(1.0), Easing::Linear)
+            .with_keyframe(2.0, pose(2.0), Easing::Linear);
+
+        assert_eq!(path.pose_at(0.5).unwrap().position.x, 0.0);
+        assert_eq!(path.pose_at(1.5).unwrap().position.x, 1.5);
+    }
+
+    #[test]
+    fn zoom_should_interpolate_geometrically() {
+        let path = CameraPath::new(1.0)
+            .with_keyframe(0.0, pose(0.0), Easing::Linear)
+            .with_keyframe(1.0, pose(0.0).with_zoom(4.0), Easing::Linear);
+
+        let zoom = path.pose_at(0.5).unwrap().zoom;
+        assert!((zoom - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rotation_should_interpolate_linearly() {
+        let path = CameraPath::new(1.0)
+            .with_keyframe(0.0, pose(0.0), Easing::Linear)
+            .with_keyframe(1.0, pose(0.0).with_rotation(3.0), Easing::Linear);
+
+        assert_eq!(path.pose_at(0.5).unwrap().rotation, 1.5);
+    }
+
+    #[test]
+    fn apply_should_move_the_camera() {
+        let path = CameraPath::new(8.0).with_keyframe(
+            0.0,
+            pose(3.0).with_zoom(2.0).with_rotation(0.5),
+            Easing::Linear,
+        );
+        let mut camera = OrthoCamera::with_viewport(1.0, 2.0);
+
+        path.apply(0.0, &mut camera);
+
+        assert_eq!(camera.world_position(), na::Point2::new(3.0, 0.0));
+        assert_eq!(camera.viewport_height(), 4.0);
+        assert_eq!(camera.aspect_ratio(), 2.0);
+        assert_eq!(camera.rotation(), 0.5);
+    }
+}
@@ -0,0 +1,69 @@
+use super::Easing;
+
+impl Easing {
+    /// Map linear progress through a segment, from 0 to 1, to eased
+    /// progress. Progress outside of the segment is clamped.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => {
+                let inverse = 1.0 - t;
+                1.0 - inverse * inverse * inverse
+            }
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ALL: [Easing; 5] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Step,
+    ];
+
+    #[test]
+    fn every_easing_should_start_at_0_and_end_at_1() {
+        for easing in &ALL {
+            assert_eq!(easing.apply(0.0), 0.0, "{:?}", easing);
+            assert_eq!(easing.apply(1.0), 1.0, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn progress_should_be_clamped() {
+        for easing in &ALL {
+            assert_eq!(easing.apply(-1.0), 0.0, "{:?}", easing);
+            assert_eq!(easing.apply(2.0), 1.0, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn ease_in_out_should_be_symmetric() {
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        let early = Easing::EaseInOut.apply(0.25);
+        let late = Easing::EaseInOut.apply(0.75);
+        assert!((early + late - 1.0).abs() < 1e-6);
+        assert!(early < 0.25);
+    }
+
+    #[test]
+    fn ease_in_should_start_slower_than_ease_out() {
+        assert!(Easing::EaseIn.apply(0.5) < 0.5);
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    }
+}
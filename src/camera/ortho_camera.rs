@@ -19,6 +19,7 @@ impl OrthoCamera {
             view: na::Translation2::identity(),
             viewport_height,
             viewport_width,
+            rotation: 0.0,
         }
     }
 
@@ -26,10 +27,17 @@ impl OrthoCamera {
     /// shader for transformations.
     pub fn as_matrix(&self) -> na::Matrix4<f32> {
         let view_3d = na::Translation3::new(self.view.x, self.view.y, 0.0);
-        self.projection.as_matrix() * view_3d.to_homogeneous()
+        let rotation = na::Rotation3::from_axis_angle(
+            &na::Vector3::z_axis(),
+            -self.rotation,
+        );
+        self.projection.as_matrix()
+            * rotation.to_homogeneous()
+            * view_3d.to_homogeneous()
     }
 
-    /// The camera's bounds in world-space.
+    /// The camera's bounds in world-space. A rotated camera's bounds cover
+    /// everything it can see.
     ///
     /// # Example
     ///
@@ -47,18 +55,21 @@ impl OrthoCamera {
     /// assert_relative_eq!(bounds.bottom, -0.5);
     /// ```
     pub fn bounds(&self) -> Rect<f32> {
-        let viewport_top_left = na::Point2::new(
-            -self.viewport_width / 2.0,
-            self.viewport_height / 2.0,
-        );
-        let viewport_bottom_right = na::Point2::new(
+        let rotation = na::Rotation2::new(self.rotation);
+        let half_extent = na::Vector2::new(
             self.viewport_width / 2.0,
-            -self.viewport_height / 2.0,
+            self.viewport_height / 2.0,
         );
+        let corner = (rotation * half_extent).abs();
+        let other_corner =
+            (rotation * na::Vector2::new(half_extent.x, -half_extent.y)).abs();
+        let half_bounds = corner.sup(&other_corner);
+
         let inverse = self.view.inverse();
-        let world_top_left = inverse.transform_point(&viewport_top_left);
-        let world_bottom_right =
-            inverse.transform_point(&viewport_bottom_right);
+        let world_top_left = inverse
+            .transform_point(&na::Point2::new(-half_bounds.x, half_bounds.y));
+        let world_bottom_right = inverse
+            .transform_point(&na::Point2::new(half_bounds.x, -half_bounds.y));
         Rect {
             left: world_top_left.x,
             right: world_bottom_right.x,
@@ -113,6 +124,36 @@ impl OrthoCamera {
         na::Point2::new(-self.view.x, -self.view.y)
     }
 
+    /// Rotate the camera counter-clockwise by `radians` around its position.
+    /// The world appears to turn the other way.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use draw2d::camera::*;
+    /// # use approx::assert_relative_eq;
+    /// # use nalgebra as na;
+    /// #
+    /// let mut ortho = OrthoCamera::with_viewport(2.0, 1.0);
+    /// ortho.set_rotation(std::f32::consts::FRAC_PI_2);
+    ///
+    /// // the right edge of the screen now looks up the world's y axis
+    /// let right_ndc = na::Point2::new(1.0, 0.0);
+    /// assert_relative_eq!(
+    ///     ortho.unproject_point(&right_ndc),
+    ///     na::Point2::new(0.0, 1.0),
+    ///     epsilon = 1e-6
+    /// );
+    /// ```
+    pub fn set_rotation(&mut self, radians: f32) {
+        self.rotation = radians;
+    }
+
+    /// The camera's counter-clockwise rotation in radians.
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
     /// Resize the viewport's width such that the viewing rectangle has the
     /// desired aspect ratio.
     ///
@@ -255,7 +296,8 @@ impl OrthoCamera {
     /// );
     /// ```
     pub fn unproject_point(&self, ndc: &na::Point2<f32>) -> na::Point2<f32> {
-        let unprojected = self.unproject_vec(&ndc.coords);
+        let unprojected =
+            na::Rotation2::new(self.rotation) * self.unproject_vec(&ndc.coords);
         self.view
             .inverse_transform_point(&na::Point2::from(unprojected))
     }
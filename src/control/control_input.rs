@@ -0,0 +1,198 @@
+use super::{decode_packet, ControlInput, ParameterTable};
+
+use anyhow::{Context, Result};
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How often listener threads check whether they should stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The largest OSC packet which can be received.
+const MAX_PACKET_SIZE: usize = 65_536;
+
+impl ControlInput {
+    /// Create an input with no listeners.
+    pub fn new() -> Self {
+        Self {
+            parameters: Arc::new(Mutex::new(ParameterTable::new())),
+            stop: Arc::new(AtomicBool::new(false)),
+            osc_threads: vec![],
+            osc_addresses: vec![],
+            #[cfg(feature = "midi")]
+            midi_connections: vec![],
+        }
+    }
+
+    /// Listen for OSC packets on a UDP address, like "0.0.0.0:9000".
+    ///
+    /// Packets which can't be decoded are logged and skipped.
+    pub fn listen_osc(&mut self, address: impl ToSocketAddrs) -> Result<()> {
+        let socket =
+            UdpSocket::bind(address).context("unable to bind osc socket")?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        let local_address = socket.local_addr()?;
+        log::info!("listening for osc on {}", local_address);
+
+        let parameters = self.parameters.clone();
+        let stop = self.stop.clone();
+        let thread = thread::Builder::new()
+            .name(format!("draw2d osc {}", local_address))
+            .spawn(move || {
+                let mut buffer = vec![0; MAX_PACKET_SIZE];
+                while !stop.load(Ordering::SeqCst) {
+                    let size = match socket.recv(&mut buffer) {
+                        Ok(size) => size,
+                        // timeouts let the thread notice it should stop
+                        Err(_) => continue,
+                    };
+                    match decode_packet(&buffer[..size]) {
+                        Ok(messages) => {
+                            let mut parameters = parameters.lock().unwrap();
+                            for message in &messages {
+                                parameters.apply_osc(message);
+                            }
+                        }
+                        Err(error) => {
+                            log::warn!("skipped osc packet: {:?}", error)
+                        }
+                    }
+                }
+            })?;
+        self.osc_threads.push(thread);
+        self.osc_addresses.push(local_address);
+        Ok(())
+    }
+
+    /// The local address of every OSC listener. Useful when binding to port
+    /// 0 and letting the system choose.
+    pub fn osc_addresses(&self) -> &[SocketAddr] {
+        &self.osc_addresses
+    }
+
+    /// Listen for MIDI messages from every input port whose name contains
+    /// the given text. An empty string connects to every port.
+    ///
+    /// Returns the number of ports connected.
+    #[cfg(feature = "midi")]
+    pub fn connect_midi(&mut self, port_name: &str) -> Result<usize> {
+        use anyhow::anyhow;
+
+        let probe = midir::MidiInput::new("draw2d")?;
+        let ports = probe.ports();
+        let mut connected = 0;
+        for port in &ports {
+            let name = probe.port_name(port).unwrap_or_default();
+            if !name.contains(port_name) {
+                continue;
+            }
+            // each connection consumes its own input
+            let input = midir::MidiInput::new("draw2d")?;
+            let parameters = self.parameters.clone();
+            let connection = input
+                .connect(
+                    port,
+                    "draw2d control",
+                    move |_timestamp, message, _| {
+                        parameters.lock().unwrap().apply_midi(message);
+                    },
+                    (),
+                )
+                .map_err(|error| {
+                    anyhow!("unable to connect to {}: {}", name, error)
+                })?;
+            log::info!("listening for midi on {}", name);
+            self.midi_connections.push(connection);
+            connected += 1;
+        }
+        Ok(connected)
+    }
+
+    /// Also set `name` whenever the MIDI control change arrives. Channels
+    /// start at 0.
+    pub fn map_midi_cc(
+        &mut self,
+        channel: u8,
+        controller: u8,
+        name: impl Into<String>,
+    ) {
+        self.parameters
+            .lock()
+            .unwrap()
+            .map_midi_cc(channel, controller, name);
+    }
+
+    /// The parameter's most recent value, or None if no message set it.
+    pub fn parameter(&self, name: &str) -> Option<f32> {
+        self.parameters.lock().unwrap().get(name)
+    }
+
+    /// The parameter's most recent value, or the default if no message set
+    /// it.
+    pub fn parameter_or(&self, name: &str, default: f32) -> f32 {
+        self.parameter(name).unwrap_or(default)
+    }
+
+    /// Set a parameter directly. The next message for it replaces the value.
+    pub fn set_parameter(&self, name: impl Into<String>, value: f32) {
+        self.parameters.lock().unwrap().set(name, value);
+    }
+
+    /// A copy of every parameter, useful for debugging a controller's
+    /// layout.
+    pub fn parameters(&self) -> HashMap<String, f32> {
+        self.parameters.lock().unwrap().values().clone()
+    }
+}
+
+impl Default for ControlInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ControlInput {
+    /// Stop every listener and wait for their threads to finish.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        for thread in self.osc_threads.drain(..) {
+            if thread.join().is_err() {
+                log::error!("an osc listener thread panicked");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::time::Instant;
+
+    #[test]
+    fn osc_packets_should_update_parameters() -> Result<()> {
+        let mut control = ControlInput::new();
+        control.listen_osc("127.0.0.1:0")?;
+        let address = control.osc_addresses()[0];
+
+        let mut packet = b"/speed\0\0,f\0\0".to_vec();
+        packet.extend_from_slice(&0.75f32.to_be_bytes());
+        let sender = UdpSocket::bind("127.0.0.1:0")?;
+
+        let start = Instant::now();
+        while control.parameter("speed").is_none() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            sender.send_to(&packet, address)?;
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(control.parameter_or("speed", 0.0), 0.75);
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+//! Map OSC and MIDI messages from external controllers onto named
+//! parameters.
+//!
+//! This module is only available with the `control` feature. MIDI input also
+//! requires the `midi` feature.
+//!
+//! Listeners run on background threads and write into a shared table of
+//! parameters, which the application reads each frame:
+//!
+//! ```ignore
+//! let mut control = ControlInput::new();
+//! control.listen_osc("0.0.0.0:9000")?;
+//! control.map_midi_cc(0, 74, "speed");
+//!
+//! // every frame
+//! let speed = control.parameter_or("speed", 1.0);
+//! ```
+//!
+//! OSC messages set the parameter named by their address, without the
+//! leading slash, to their first numeric argument. Messages with more than
+//! one numeric argument, like an XY pad, also set `name/1`, `name/2`, and
+//! so on. MIDI control changes set `midi/<channel>/cc/<controller>`, scaled
+//! to the range 0 to 1, and any name mapped with `map_midi_cc`.
+
+mod control_input;
+mod osc;
+mod parameter_table;
+
+pub use self::osc::{decode_packet, OscArg, OscMessage};
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    thread::JoinHandle,
+};
+
+/// Named parameters and the rules for updating them from messages.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParameterTable {
+    values: HashMap<String, f32>,
+
+    /// Extra names for MIDI control changes, keyed by channel and controller.
+    midi_names: HashMap<(u8, u8), Vec<String>>,
+}
+
+/// Listens for control messages on background threads.
+///
+/// Listeners stop when this is dropped.
+pub struct ControlInput {
+    parameters: Arc<Mutex<ParameterTable>>,
+
+    /// Set when the listeners should stop.
+    stop: Arc<AtomicBool>,
+
+    osc_threads: Vec<JoinHandle<()>>,
+
+    /// The local address of each OSC socket.
+    osc_addresses: Vec<SocketAddr>,
+
+    #[cfg(feature = "midi")]
+    midi_connections: Vec<midir::MidiInputConnection<()>>,
+}
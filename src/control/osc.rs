@@ -0,0 +1,260 @@
+//! A decoder for the subset of OSC 1.0 used by controllers.
+
+use anyhow::{bail, Context, Result};
+use std::convert::TryInto;
+
+/// A single OSC message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+/// An argument to an OSC message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Double(f64),
+    Long(i64),
+    String(String),
+    Blob(Vec<u8>),
+    Bool(bool),
+    Nil,
+    Impulse,
+}
+
+impl OscArg {
+    /// The argument as a number, if it has a numeric meaning.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            OscArg::Int(value) => Some(*value as f32),
+            OscArg::Float(value) => Some(*value),
+            OscArg::Double(value) => Some(*value as f32),
+            OscArg::Long(value) => Some(*value as f32),
+            OscArg::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+            OscArg::Impulse => Some(1.0),
+            _ => None,
+        }
+    }
+}
+
+/// Decode every message in a packet, flattening bundles.
+pub fn decode_packet(packet: &[u8]) -> Result<Vec<OscMessage>> {
+    let mut messages = vec![];
+    decode_into(packet, &mut messages)?;
+    Ok(messages)
+}
+
+fn decode_into(packet: &[u8], messages: &mut Vec<OscMessage>) -> Result<()> {
+    if packet.starts_with(b"#bundle\0") {
+        // skip the tag and the time tag, elements are applied immediately
+        let mut reader = Reader::new(&packet[8..]);
+        reader.take(8)?;
+        while !reader.is_empty() {
+            let size = reader.i32()?;
+            if size < 0 {
+                bail!("invalid bundle element size {}", size);
+            }
+            decode_into(reader.take(size as usize)?, messages)?;
+        }
+        return Ok(());
+    }
+    messages.push(decode_message(packet)?);
+    Ok(())
+}
+
+fn decode_message(packet: &[u8]) -> Result<OscMessage> {
+    let mut reader = Reader::new(packet);
+    let address = reader.string().context("invalid osc address")?;
+    if !address.starts_with('/') {
+        bail!("osc addresses must start with '/', found {:?}", address);
+    }
+
+    // old implementations can omit the type tags entirely
+    if reader.is_empty() {
+        return Ok(OscMessage {
+            address,
+            args: vec![],
+        });
+    }
+    let tags = reader.string().context("invalid osc type tags")?;
+    if !tags.starts_with(',') {
+        bail!("osc type tags must start with ',', found {:?}", tags);
+    }
+
+    let mut args = vec![];
+    for tag in tags.chars().skip(1) {
+        let arg = match tag {
+            'i' => OscArg::Int(reader.i32()?),
+            'f' => OscArg::Float(f32::from_bits(reader.i32()? as u32)),
+            'd' => OscArg::Double(f64::from_bits(reader.i64()? as u64)),
+            'h' => OscArg::Long(reader.i64()?),
+            's' | 'S' => OscArg::String(reader.string()?),
+            'b' => {
+                let size = reader.i32()?.max(0) as usize;
+                let blob = reader.take(size)?.to_vec();
+                reader.align()?;
+                OscArg::Blob(blob)
+            }
+            'T' => OscArg::Bool(true),
+            'F' => OscArg::Bool(false),
+            'N' => OscArg::Nil,
+            'I' => OscArg::Impulse,
+            // skip the payloads of types without a numeric meaning
+            'c' | 'r' | 'm' => {
+                reader.take(4)?;
+                OscArg::Nil
+            }
+            't' => {
+                reader.take(8)?;
+                OscArg::Nil
+            }
+            '[' | ']' => continue,
+            _ => bail!("unsupported osc type tag {:?}", tag),
+        };
+        args.push(arg);
+    }
+    Ok(OscMessage { address, args })
+}
+
+/// Reads big-endian, four byte aligned OSC values.
+struct Reader<'data> {
+    data: &'data [u8],
+    offset: usize,
+}
+
+impl<'data> Reader<'data> {
+    fn new(data: &'data [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.offset >= self.data.len()
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'data [u8]> {
+        let end = self.offset + count;
+        if end > self.data.len() {
+            bail!("osc packet ended early");
+        }
+        let bytes = &self.data[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn align(&mut self) -> Result<()> {
+        let padding = (4 - self.offset % 4) % 4;
+        self.take(padding)?;
+        Ok(())
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let remaining = &self.data[self.offset.min(self.data.len())..];
+        let length = remaining
+            .iter()
+            .position(|byte| *byte == 0)
+            .context("osc string is not terminated")?;
+        let text = std::str::from_utf8(&remaining[..length])?.to_owned();
+        // the terminator is included in the padding
+        self.take(length + 1)?;
+        self.align()?;
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn padded(text: &str) -> Vec<u8> {
+        let mut bytes = text.as_bytes().to_vec();
+        let padding = 4 - bytes.len() % 4;
+        bytes.extend(vec![0; padding]);
+        bytes
+    }
+
+    fn message(address: &str, tags: &str, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = padded(address);
+        bytes.extend(padded(tags));
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn decode_float_message() -> Result<()> {
+        let packet = message("/speed", ",f", &0.5f32.to_be_bytes());
+        let messages = decode_packet(&packet)?;
+        assert_eq!(
+            messages,
+            vec![OscMessage {
+                address: "/speed".to_owned(),
+                args: vec![OscArg::Float(0.5)],
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn decode_mixed_arguments() -> Result<()> {
+        let mut payload = 7i32.to_be_bytes().to_vec();
+        payload.extend(padded("hello"));
+        payload.extend_from_slice(&2.0f64.to_be_bytes());
+        let packet = message("/a/b", ",isdTN", &payload);
+        let messages = decode_packet(&packet)?;
+        assert_eq!(
+            messages[0].args,
+            vec![
+                OscArg::Int(7),
+                OscArg::String("hello".to_owned()),
+                OscArg::Double(2.0),
+                OscArg::Bool(true),
+                OscArg::Nil,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn decode_bundles() -> Result<()> {
+        let first = message("/x", ",i", &1i32.to_be_bytes());
+        let second = message("/y", ",i", &2i32.to_be_bytes());
+        let mut packet = padded("#bundle");
+        packet.extend_from_slice(&1u64.to_be_bytes());
+        for element in &[&first, &second] {
+            packet.extend_from_slice(&(element.len() as i32).to_be_bytes());
+            packet.extend_from_slice(element);
+        }
+        let messages = decode_packet(&packet)?;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].address, "/x");
+        assert_eq!(messages[1].args, vec![OscArg::Int(2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn messages_without_type_tags_have_no_arguments() -> Result<()> {
+        let messages = decode_packet(&padded("/ping"))?;
+        assert!(messages[0].args.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn truncated_packets_are_errors() {
+        let packet = message("/speed", ",f", &[0, 0]);
+        assert!(decode_packet(&packet).is_err());
+    }
+
+    #[test]
+    fn addresses_must_start_with_a_slash() {
+        assert!(decode_packet(&padded("speed")).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use super::{OscMessage, ParameterTable};
+
+use std::collections::HashMap;
+
+impl ParameterTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The parameter's most recent value.
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.values.get(name).copied()
+    }
+
+    /// Set a parameter, like to give it an initial value.
+    pub fn set(&mut self, name: impl Into<String>, value: f32) {
+        self.values.insert(name.into(), value);
+    }
+
+    /// Every parameter which has a value.
+    pub fn values(&self) -> &HashMap<String, f32> {
+        &self.values
+    }
+
+    /// Also set `name` whenever the MIDI control change arrives.
+    pub fn map_midi_cc(
+        &mut self,
+        channel: u8,
+        controller: u8,
+        name: impl Into<String>,
+    ) {
+        self.midi_names
+            .entry((channel, controller))
+            .or_default()
+            .push(name.into());
+    }
+
+    /// Update parameters from an OSC message.
+    ///
+    /// Messages without a numeric argument are ignored.
+    pub fn apply_osc(&mut self, message: &OscMessage) {
+        let name = message.address.trim_start_matches('/');
+        let numbers = message.args.iter().filter_map(|arg| arg.as_f32());
+        for (index, value) in numbers.enumerate() {
+            if index == 0 {
+                self.values.insert(name.to_owned(), value);
+            } else {
+                self.values.insert(format!("{}/{}", name, index), value);
+            }
+        }
+    }
+
+    /// Update parameters from a raw MIDI message.
+    ///
+    /// Only control changes are used, everything else is ignored.
+    pub fn apply_midi(&mut self, message: &[u8]) {
+        let (status, controller, value) = match message {
+            [status, controller, value, ..] => (*status, *controller, *value),
+            _ => return,
+        };
+        if status & 0xF0 != 0xB0 {
+            return;
+        }
+        let channel = status & 0x0F;
+        let value = value.min(127) as f32 / 127.0;
+        self.values
+            .insert(format!("midi/{}/cc/{}", channel, controller), value);
+        if let Some(names) = self.midi_names.get(&(channel, controller)) {
+            for name in names {
+                self.values.insert(name.clone(), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::control::OscArg;
+
+    fn osc(address: &str, args: Vec<OscArg>) -> OscMessage {
+        OscMessage {
+            address: address.to_owned(),
+            args,
+        }
+    }
+
+    #[test]
+    fn osc_sets_the_parameter_named_by_the_address() {
+        let mut table = ParameterTable::new();
+        table.apply_osc(&osc("/layer/alpha", vec![OscArg::Float(0.25)]));
+        assert_eq!(table.get("layer/alpha"), Some(0.25));
+    }
+
+    #[test]
+    fn extra_osc_arguments_get_numbered_names() {
+        let mut table = ParameterTable::new();
+        table.apply_osc(&osc(
+            "/xy",
+            vec![
+                OscArg::Float(0.1),
+                OscArg::String("ignored".to_owned()),
+                OscArg::Int(3),
+            ],
+        ));
+        assert_eq!(table.get("xy"), Some(0.1));
+        assert_eq!(table.get("xy/1"), Some(3.0));
+    }
+
+    #[test]
+    fn osc_without_numbers_is_ignored() {
+        let mut table = ParameterTable::new();
+        table.apply_osc(&osc("/name", vec![OscArg::String("x".to_owned())]));
+        assert!(table.values().is_empty());
+    }
+
+    #[test]
+    fn midi_control_changes_are_scaled() {
+        let mut table = ParameterTable::new();
+        table.apply_midi(&[0xB2, 74, 127]);
+        assert_eq!(table.get("midi/2/cc/74"), Some(1.0));
+    }
+
+    #[test]
+    fn mapped_midi_control_changes_set_their_names() {
+        let mut table = ParameterTable::new();
+        table.map_midi_cc(0, 1, "speed");
+        table.apply_midi(&[0xB0, 1, 0]);
+        assert_eq!(table.get("speed"), Some(0.0));
+        table.apply_midi(&[0xB1, 1, 127]);
+        assert_eq!(table.get("speed"), Some(0.0));
+    }
+
+    #[test]
+    fn other_midi_messages_are_ignored() {
+        let mut table = ParameterTable::new();
+        table.apply_midi(&[0x90, 60, 100]);
+        table.apply_midi(&[0xB0]);
+        assert!(table.values().is_empty());
+    }
+}
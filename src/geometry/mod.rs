@@ -1,11 +1,27 @@
 mod rect;
+mod transform;
 
 use nalgebra as na;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect<T: na::Scalar> {
     pub left: T,
     pub right: T,
     pub bottom: T,
     pub top: T,
 }
+
+/// A 2d transformation composed of a non-uniform scale, followed by a
+/// rotation, followed by a translation.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform2d {
+    /// The transform's translation in world space.
+    pub position: na::Vector2<f32>,
+
+    /// The transform's rotation, in radians, counter-clockwise.
+    pub rotation: f32,
+
+    /// The transform's scale along its local x and y axes.
+    pub scale: na::Vector2<f32>,
+}
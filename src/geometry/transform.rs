@@ -0,0 +1,175 @@
+use nalgebra as na;
+
+use super::Transform2d;
+
+impl Default for Transform2d {
+    /// The identity transform.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform2d {
+    /// A transform which leaves points unchanged.
+    pub fn identity() -> Self {
+        Self {
+            position: na::Vector2::new(0.0, 0.0),
+            rotation: 0.0,
+            scale: na::Vector2::new(1.0, 1.0),
+        }
+    }
+
+    /// A transform which only translates points.
+    pub fn from_position(position: na::Vector2<f32>) -> Self {
+        Self {
+            position,
+            ..Self::identity()
+        }
+    }
+
+    /// The transform's local x axis, as a unit vector in world space.
+    pub fn x_axis(&self) -> na::Vector2<f32> {
+        na::Vector2::new(self.rotation.cos(), self.rotation.sin())
+    }
+
+    /// The transform's local y axis, as a unit vector in world space.
+    pub fn y_axis(&self) -> na::Vector2<f32> {
+        na::Vector2::new(-self.rotation.sin(), self.rotation.cos())
+    }
+
+    /// Get the full homogeneous transformation matrix.
+    pub fn as_matrix3(&self) -> na::Matrix3<f32> {
+        na::Matrix3::new_translation(&self.position)
+            * na::Rotation2::new(self.rotation).to_homogeneous()
+            * na::Matrix3::new_nonuniform_scaling(&self.scale)
+    }
+
+    /// Get the transformation as a 4x4 matrix which can be multiplied with a
+    /// layer's projection.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use draw2d::{camera::OrthoCamera, geometry::Transform2d};
+    /// # use nalgebra as na;
+    /// #
+    /// let camera = OrthoCamera::with_viewport(10.0, 1.0);
+    /// let transform =
+    ///     Transform2d::from_position(na::Vector2::new(2.0, 3.0));
+    ///
+    /// let projection = camera.as_matrix() * transform.as_matrix();
+    /// ```
+    pub fn as_matrix(&self) -> na::Matrix4<f32> {
+        let m = self.as_matrix3();
+        #[rustfmt::skip]
+        let full = na::Matrix4::new(
+            m[(0, 0)], m[(0, 1)], 0.0, m[(0, 2)],
+            m[(1, 0)], m[(1, 1)], 0.0, m[(1, 2)],
+            0.0,       0.0,       1.0, 0.0,
+            0.0,       0.0,       0.0, 1.0,
+        );
+        full
+    }
+
+    /// Transform a point from the transform's local space into world space.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use draw2d::geometry::Transform2d;
+    /// # use approx::assert_relative_eq;
+    /// # use nalgebra as na;
+    /// #
+    /// let transform = Transform2d {
+    ///     position: na::Vector2::new(1.0, 0.0),
+    ///     rotation: std::f32::consts::FRAC_PI_2,
+    ///     scale: na::Vector2::new(2.0, 2.0),
+    /// };
+    ///
+    /// let world = transform.transform_point(&na::Point2::new(1.0, 0.0));
+    /// assert_relative_eq!(world, na::Point2::new(1.0, 2.0), epsilon = 1e-6);
+    /// ```
+    pub fn transform_point(&self, point: &na::Point2<f32>) -> na::Point2<f32> {
+        let scaled = point.coords.component_mul(&self.scale);
+        let rotated = na::Rotation2::new(self.rotation) * scaled;
+        na::Point2::from(rotated + self.position)
+    }
+
+    /// Transform a point from world space into the transform's local space.
+    ///
+    /// Components with a scale of zero are left at zero.
+    pub fn inverse_transform_point(
+        &self,
+        point: &na::Point2<f32>,
+    ) -> na::Point2<f32> {
+        let translated = point.coords - self.position;
+        let unrotated = na::Rotation2::new(-self.rotation) * translated;
+        na::Point2::new(
+            safe_div(unrotated.x, self.scale.x),
+            safe_div(unrotated.y, self.scale.y),
+        )
+    }
+}
+
+fn safe_div(numerator: f32, denominator: f32) -> f32 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn identity_does_not_move_points() {
+        let point = na::Point2::new(3.0, -2.0);
+        assert_relative_eq!(
+            Transform2d::identity().transform_point(&point),
+            point
+        );
+    }
+
+    #[test]
+    fn matrix_matches_transform_point() {
+        let transform = Transform2d {
+            position: na::Vector2::new(4.0, -1.0),
+            rotation: 0.3,
+            scale: na::Vector2::new(2.0, 0.5),
+        };
+        let point = na::Point2::new(1.5, 2.0);
+
+        let by_matrix = transform.as_matrix3().transform_point(&point);
+        assert_relative_eq!(
+            by_matrix,
+            transform.transform_point(&point),
+            epsilon = 1e-5
+        );
+
+        let by_matrix4 =
+            transform.as_matrix() * na::Vector4::new(1.5, 2.0, 0.0, 1.0);
+        assert_relative_eq!(by_matrix4.x, by_matrix.x, epsilon = 1e-5);
+        assert_relative_eq!(by_matrix4.y, by_matrix.y, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn inverse_round_trip() {
+        let transform = Transform2d {
+            position: na::Vector2::new(-7.0, 2.0),
+            rotation: 1.2,
+            scale: na::Vector2::new(3.0, 0.25),
+        };
+        let point = na::Point2::new(0.75, -4.0);
+        let world = transform.transform_point(&point);
+
+        assert_relative_eq!(
+            transform.inverse_transform_point(&world),
+            point,
+            epsilon = 1e-5
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use super::{GizmoHandle, GizmoMode};
+
+use nalgebra as na;
+
+/// The size of the square at the center of the translate and scale gizmos, in
+/// pixels.
+pub(super) const CENTER_SIZE: f32 = 14.0;
+
+/// Find the handle under an offset from the gizmo's origin.
+///
+/// # Params
+///
+/// - `offset` is the cursor's offset from the transform's position, in pixels,
+///   with y pointing up. For the scale gizmo the offset should already be
+///   rotated into the transform's local space.
+pub(super) fn hit_test(
+    mode: GizmoMode,
+    offset: &na::Vector2<f32>,
+    handle_length: f32,
+    tolerance: f32,
+) -> Option<GizmoHandle> {
+    match mode {
+        GizmoMode::Translate | GizmoMode::Scale => {
+            let half_center = CENTER_SIZE / 2.0;
+            if offset.x.abs() <= half_center && offset.y.abs() <= half_center {
+                Some(GizmoHandle::Center)
+            } else if offset.y.abs() <= tolerance
+                && offset.x >= 0.0
+                && offset.x <= handle_length + tolerance
+            {
+                Some(GizmoHandle::AxisX)
+            } else if offset.x.abs() <= tolerance
+                && offset.y >= 0.0
+                && offset.y <= handle_length + tolerance
+            {
+                Some(GizmoHandle::AxisY)
+            } else {
+                None
+            }
+        }
+        GizmoMode::Rotate => {
+            if (offset.norm() - ring_radius(handle_length)).abs() <= tolerance {
+                Some(GizmoHandle::Ring)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// The rotation ring's radius, in pixels.
+pub(super) fn ring_radius(handle_length: f32) -> f32 {
+    handle_length * 0.75
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn translate_handles() {
+        let hit = |x, y| {
+            hit_test(GizmoMode::Translate, &na::Vector2::new(x, y), 80.0, 5.0)
+        };
+
+        assert_eq!(hit(0.0, 0.0), Some(GizmoHandle::Center));
+        assert_eq!(hit(40.0, 3.0), Some(GizmoHandle::AxisX));
+        assert_eq!(hit(-2.0, 70.0), Some(GizmoHandle::AxisY));
+        assert_eq!(hit(-40.0, 0.0), None);
+        assert_eq!(hit(40.0, 40.0), None);
+        assert_eq!(hit(100.0, 0.0), None);
+    }
+
+    #[test]
+    fn rotate_ring() {
+        let hit = |x, y| {
+            hit_test(GizmoMode::Rotate, &na::Vector2::new(x, y), 80.0, 5.0)
+        };
+
+        assert_eq!(hit(60.0, 0.0), Some(GizmoHandle::Ring));
+        assert_eq!(hit(0.0, -58.0), Some(GizmoHandle::Ring));
+        assert_eq!(hit(0.0, 0.0), None);
+        assert_eq!(hit(80.0, 0.0), None);
+    }
+}
@@ -0,0 +1,220 @@
+use super::{handle, Drag, Gizmo, GizmoHandle, GizmoMode};
+
+use crate::{camera::OrthoCamera, geometry::Transform2d};
+
+use nalgebra as na;
+
+impl Gizmo {
+    /// Create a new gizmo.
+    ///
+    /// # Params
+    ///
+    /// - `mode` controls which handles are shown and how they behave.
+    /// - `window_size` is the window's size in screen coordinates, as
+    ///   reported by `glfw::Window::get_size`. The gizmo keeps this up to date
+    ///   by observing `WindowEvent::Size` events.
+    pub fn new(mode: GizmoMode, window_size: (i32, i32)) -> Self {
+        Self {
+            mode,
+            handle_length: 80.0,
+            grab_tolerance: 6.0,
+            window_size: (window_size.0 as f32, window_size.1 as f32),
+            cursor: na::Point2::new(0.0, 0.0),
+            hovered: None,
+            drag: None,
+        }
+    }
+
+    /// The handle currently under the cursor, or the handle being dragged.
+    pub fn active_handle(&self) -> Option<GizmoHandle> {
+        self.drag.map(|drag| drag.handle).or(self.hovered)
+    }
+
+    /// Returns true while a handle is being dragged.
+    ///
+    /// Applications typically use this to avoid processing the same mouse
+    /// events with other controls, like selection or camera panning.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Update the gizmo's interaction state, and the transform, in response to
+    /// a window event.
+    ///
+    /// The window must have cursor-position and mouse-button polling enabled
+    /// for the gizmo to be interactive.
+    ///
+    /// Returns `true` when the transform or the gizmo's appearance changed.
+    /// Often this is used to trigger a rebuild of the gizmo's batch.
+    #[cfg(not(target_os = "android"))]
+    pub fn handle_event(
+        &mut self,
+        event: &glfw::WindowEvent,
+        camera: &OrthoCamera,
+        transform: &mut Transform2d,
+    ) -> bool {
+        use glfw::{Action, MouseButton, WindowEvent};
+
+        match event {
+            WindowEvent::Size(width, height) => {
+                self.resize(*width as f32, *height as f32)
+            }
+
+            WindowEvent::CursorPos(x, y) => {
+                self.cursor_moved(*x as f32, *y as f32, camera, transform)
+            }
+
+            WindowEvent::MouseButton(
+                MouseButton::Button1,
+                Action::Press,
+                _,
+            ) => self.press(camera, transform),
+
+            WindowEvent::MouseButton(
+                MouseButton::Button1,
+                Action::Release,
+                _,
+            ) => self.release(),
+
+            _ => false,
+        }
+    }
+
+    /// Update the window size, in screen coordinates.
+    ///
+    /// `handle_event` calls this for `WindowEvent::Size`. Platforms without
+    /// glfw window events, like Android, call it directly.
+    pub fn resize(&mut self, width: f32, height: f32) -> bool {
+        self.window_size = (width, height);
+        true
+    }
+
+    /// Move the cursor, in screen coordinates, dragging the active handle if
+    /// there is one.
+    pub fn cursor_moved(
+        &mut self,
+        x: f32,
+        y: f32,
+        camera: &OrthoCamera,
+        transform: &mut Transform2d,
+    ) -> bool {
+        self.cursor = na::Point2::new(x, y);
+        if let Some(drag) = self.drag {
+            *transform = self.apply_drag(&drag, camera);
+            true
+        } else {
+            let hovered = self.pick_handle(camera, transform);
+            let changed = hovered != self.hovered;
+            self.hovered = hovered;
+            changed
+        }
+    }
+
+    /// Start dragging the handle under the cursor, if any.
+    pub fn press(
+        &mut self,
+        camera: &OrthoCamera,
+        transform: &Transform2d,
+    ) -> bool {
+        self.hovered = self.pick_handle(camera, transform);
+        self.drag = self.hovered.map(|handle| Drag {
+            handle,
+            start_cursor: self.cursor_world(camera),
+            start_transform: *transform,
+        });
+        self.drag.is_some()
+    }
+
+    /// Stop dragging.
+    pub fn release(&mut self) -> bool {
+        self.drag.take().is_some()
+    }
+
+    /// The size of a single screen pixel in world units.
+    pub(super) fn world_units_per_pixel(&self, camera: &OrthoCamera) -> f32 {
+        camera.viewport_height() / self.window_size.1.max(1.0)
+    }
+
+    /// The cursor's current location in world space.
+    fn cursor_world(&self, camera: &OrthoCamera) -> na::Point2<f32> {
+        let (width, height) = self.window_size;
+        let ndc = na::Point2::new(
+            2.0 * self.cursor.x / width.max(1.0) - 1.0,
+            2.0 * self.cursor.y / height.max(1.0) - 1.0,
+        );
+        camera.unproject_point(&ndc)
+    }
+
+    /// Find the handle under the cursor, if any.
+    fn pick_handle(
+        &self,
+        camera: &OrthoCamera,
+        transform: &Transform2d,
+    ) -> Option<GizmoHandle> {
+        let offset = (self.cursor_world(camera).coords - transform.position)
+            / self.world_units_per_pixel(camera);
+        let offset = match self.mode {
+            GizmoMode::Scale => {
+                na::Rotation2::new(-transform.rotation) * offset
+            }
+            _ => offset,
+        };
+        handle::hit_test(
+            self.mode,
+            &offset,
+            self.handle_length,
+            self.grab_tolerance,
+        )
+    }
+
+    /// Compute the transform which results from dragging a handle from the
+    /// drag's start to the cursor's current location.
+    fn apply_drag(&self, drag: &Drag, camera: &OrthoCamera) -> Transform2d {
+        let start = drag.start_transform;
+        let cursor = self.cursor_world(camera);
+        let delta = cursor - drag.start_cursor;
+        let mut result = start;
+
+        match (self.mode, drag.handle) {
+            (GizmoMode::Translate, GizmoHandle::AxisX) => {
+                result.position.x += delta.x;
+            }
+            (GizmoMode::Translate, GizmoHandle::AxisY) => {
+                result.position.y += delta.y;
+            }
+            (GizmoMode::Translate, _) => {
+                result.position += delta;
+            }
+
+            (GizmoMode::Rotate, _) => {
+                let from = drag.start_cursor.coords - start.position;
+                let to = cursor.coords - start.position;
+                let angle = from.y.atan2(from.x);
+                let new_angle = to.y.atan2(to.x);
+                result.rotation = start.rotation + (new_angle - angle);
+            }
+
+            (GizmoMode::Scale, handle) => {
+                let length =
+                    self.handle_length * self.world_units_per_pixel(camera);
+                let local = na::Rotation2::new(-start.rotation) * delta;
+                match handle {
+                    GizmoHandle::AxisX => {
+                        result.scale.x =
+                            start.scale.x * (1.0 + local.x / length);
+                    }
+                    GizmoHandle::AxisY => {
+                        result.scale.y =
+                            start.scale.y * (1.0 + local.y / length);
+                    }
+                    _ => {
+                        let factor = 1.0 + (local.x + local.y) / (2.0 * length);
+                        result.scale = start.scale * factor;
+                    }
+                }
+            }
+        }
+
+        result
+    }
+}
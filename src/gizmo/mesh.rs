@@ -0,0 +1,196 @@
+use super::{handle, Gizmo, GizmoHandle, GizmoMode};
+
+use crate::{
+    camera::OrthoCamera,
+    geometry::Transform2d,
+    graphics::{layer::Batch, vertex::Vertex2d},
+};
+
+use nalgebra as na;
+
+const X_COLOR: [f32; 4] = [0.9, 0.2, 0.2, 1.0];
+const Y_COLOR: [f32; 4] = [0.2, 0.8, 0.2, 1.0];
+const CENTER_COLOR: [f32; 4] = [0.3, 0.5, 1.0, 1.0];
+const ACTIVE_COLOR: [f32; 4] = [1.0, 0.85, 0.1, 1.0];
+
+/// The thickness of the handle lines, in pixels.
+const LINE_WIDTH: f32 = 3.0;
+
+/// The number of segments used to approximate the rotation ring.
+const RING_SEGMENTS: usize = 48;
+
+impl Gizmo {
+    /// Build a batch which draws the gizmo's handles around the transform.
+    ///
+    /// The batch is in world space, so it should be added to a layer which
+    /// uses the same camera that is passed to [Self::handle_event].
+    pub fn build_batch(
+        &self,
+        transform: &Transform2d,
+        camera: &OrthoCamera,
+    ) -> Batch {
+        let px = self.world_units_per_pixel(camera);
+        let origin = na::Point2::from(transform.position);
+        let (x_axis, y_axis) = match self.mode {
+            GizmoMode::Scale => (transform.x_axis(), transform.y_axis()),
+            _ => (na::Vector2::x(), na::Vector2::y()),
+        };
+        let length = self.handle_length * px;
+
+        let mut batch = Batch::empty();
+        let vertices = &mut batch.vertices;
+
+        match self.mode {
+            GizmoMode::Translate | GizmoMode::Scale => {
+                for (axis, handle, color) in &[
+                    (x_axis, GizmoHandle::AxisX, X_COLOR),
+                    (y_axis, GizmoHandle::AxisY, Y_COLOR),
+                ] {
+                    let color = self.color_for(*handle, *color);
+                    let tip = origin + axis * length;
+                    push_line(vertices, &origin, &tip, LINE_WIDTH * px, color);
+                    if self.mode == GizmoMode::Translate {
+                        push_arrow_head(vertices, &tip, axis, 12.0 * px, color);
+                    } else {
+                        push_box(vertices, &tip, axis, 10.0 * px, color);
+                    }
+                }
+                let center_color =
+                    self.color_for(GizmoHandle::Center, CENTER_COLOR);
+                push_box(
+                    vertices,
+                    &origin,
+                    &x_axis,
+                    handle::CENTER_SIZE * px,
+                    center_color,
+                );
+            }
+            GizmoMode::Rotate => {
+                let color = self.color_for(GizmoHandle::Ring, CENTER_COLOR);
+                let radius = handle::ring_radius(self.handle_length) * px;
+                push_ring(vertices, &origin, radius, LINE_WIDTH * px, color);
+
+                // a short line indicating the transform's current rotation
+                let tip = origin + transform.x_axis() * radius;
+                push_line(vertices, &origin, &tip, LINE_WIDTH * px, X_COLOR);
+            }
+        }
+
+        batch
+    }
+
+    fn color_for(&self, handle: GizmoHandle, color: [f32; 4]) -> [f32; 4] {
+        if self.active_handle() == Some(handle) {
+            ACTIVE_COLOR
+        } else {
+            color
+        }
+    }
+}
+
+fn vertex(pos: &na::Point2<f32>, rgba: [f32; 4]) -> Vertex2d {
+    Vertex2d {
+        pos: [pos.x, pos.y],
+        rgba,
+        ..Default::default()
+    }
+}
+
+fn push_quad(
+    vertices: &mut Vec<Vertex2d>,
+    corners: [na::Point2<f32>; 4],
+    rgba: [f32; 4],
+) {
+    let [a, b, c, d] = corners;
+    vertices.extend_from_slice(&[
+        vertex(&a, rgba),
+        vertex(&b, rgba),
+        vertex(&c, rgba),
+        vertex(&a, rgba),
+        vertex(&c, rgba),
+        vertex(&d, rgba),
+    ]);
+}
+
+fn push_line(
+    vertices: &mut Vec<Vertex2d>,
+    start: &na::Point2<f32>,
+    end: &na::Point2<f32>,
+    width: f32,
+    rgba: [f32; 4],
+) {
+    let direction = (end - start).normalize();
+    let normal = na::Vector2::new(-direction.y, direction.x) * (width / 2.0);
+    push_quad(
+        vertices,
+        [start + normal, end + normal, end - normal, start - normal],
+        rgba,
+    );
+}
+
+fn push_box(
+    vertices: &mut Vec<Vertex2d>,
+    center: &na::Point2<f32>,
+    x_axis: &na::Vector2<f32>,
+    size: f32,
+    rgba: [f32; 4],
+) {
+    let half_x = x_axis * (size / 2.0);
+    let half_y = na::Vector2::new(-half_x.y, half_x.x);
+    push_quad(
+        vertices,
+        [
+            center - half_x + half_y,
+            center + half_x + half_y,
+            center + half_x - half_y,
+            center - half_x - half_y,
+        ],
+        rgba,
+    );
+}
+
+fn push_arrow_head(
+    vertices: &mut Vec<Vertex2d>,
+    tip: &na::Point2<f32>,
+    axis: &na::Vector2<f32>,
+    size: f32,
+    rgba: [f32; 4],
+) {
+    let normal = na::Vector2::new(-axis.y, axis.x) * (size / 2.0);
+    let point = tip + axis * size;
+    vertices.extend_from_slice(&[
+        vertex(&(tip + normal), rgba),
+        vertex(&point, rgba),
+        vertex(&(tip - normal), rgba),
+    ]);
+}
+
+fn push_ring(
+    vertices: &mut Vec<Vertex2d>,
+    center: &na::Point2<f32>,
+    radius: f32,
+    width: f32,
+    rgba: [f32; 4],
+) {
+    let inner = radius - width / 2.0;
+    let outer = radius + width / 2.0;
+    let step = std::f32::consts::TAU / RING_SEGMENTS as f32;
+    for i in 0..RING_SEGMENTS {
+        let a =
+            na::Vector2::new((i as f32 * step).cos(), (i as f32 * step).sin());
+        let b = na::Vector2::new(
+            ((i + 1) as f32 * step).cos(),
+            ((i + 1) as f32 * step).sin(),
+        );
+        push_quad(
+            vertices,
+            [
+                center + a * inner,
+                center + a * outer,
+                center + b * outer,
+                center + b * inner,
+            ],
+            rgba,
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Editor-style transform gizmos.
+//!
+//! A gizmo draws a set of handles around a [Transform2d] and translates mouse
+//! interaction with those handles into changes to the transform.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut gizmo = Gizmo::new(GizmoMode::Translate, window.get_size());
+//!
+//! for (_, event) in window_surface.poll_events() {
+//!     if gizmo.handle_event(&event, &camera, &mut transform) {
+//!         graphics.get_layer_mut(&gizmo_layer).clear();
+//!         graphics
+//!             .get_layer_mut(&gizmo_layer)
+//!             .push_batch(gizmo.build_batch(&transform, &camera));
+//!     }
+//! }
+//! ```
+
+mod handle;
+mod interaction;
+mod mesh;
+
+use crate::geometry::Transform2d;
+
+use nalgebra as na;
+
+/// Which kind of transformation the gizmo's handles manipulate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GizmoMode {
+    /// Arrows for moving along the world x or y axis, and a center square for
+    /// moving freely.
+    Translate,
+
+    /// A ring which rotates the transform around its position.
+    Rotate,
+
+    /// Boxes for scaling along the transform's local x or y axis, and a center
+    /// square for uniform scaling.
+    Scale,
+}
+
+/// An individual handle which can be hovered and dragged.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GizmoHandle {
+    /// Constrains the interaction to the x axis.
+    AxisX,
+
+    /// Constrains the interaction to the y axis.
+    AxisY,
+
+    /// Unconstrained interaction. (free translation or uniform scale)
+    Center,
+
+    /// The rotation ring.
+    Ring,
+}
+
+/// Interactive handles for manipulating a [Transform2d] with the mouse.
+///
+/// All handle sizes are in screen pixels, so the gizmo stays the same size on
+/// screen regardless of the camera's zoom.
+#[derive(Debug, Clone)]
+pub struct Gizmo {
+    /// The kind of transformation this gizmo applies.
+    pub mode: GizmoMode,
+
+    /// The length of the axis handles, in pixels.
+    pub handle_length: f32,
+
+    /// How close the cursor needs to be to a handle to grab it, in pixels.
+    pub grab_tolerance: f32,
+
+    window_size: (f32, f32),
+    cursor: na::Point2<f32>,
+    hovered: Option<GizmoHandle>,
+    drag: Option<Drag>,
+}
+
+/// The state captured when a handle is grabbed.
+#[derive(Debug, Copy, Clone)]
+struct Drag {
+    handle: GizmoHandle,
+    start_cursor: na::Point2<f32>,
+    start_transform: Transform2d,
+}
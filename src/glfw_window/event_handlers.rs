@@ -0,0 +1,78 @@
+use super::{EventHandler, EventHandlerId, EventHandlers};
+
+impl EventHandlers {
+    /// Add a handler after every existing handler.
+    pub fn add(&mut self, handler: EventHandler) -> EventHandlerId {
+        let id = EventHandlerId { id: self.next_id };
+        self.next_id += 1;
+        self.handlers.push((id, handler));
+        id
+    }
+
+    /// Remove a handler. Returns false if it was already removed.
+    pub fn remove(&mut self, id: EventHandlerId) -> bool {
+        let count = self.handlers.len();
+        self.handlers.retain(|(existing, _)| *existing != id);
+        self.handlers.len() != count
+    }
+
+    /// Call every handler with an event.
+    pub fn dispatch(&mut self, event: &glfw::WindowEvent) {
+        for (_, handler) in &mut self.handlers {
+            handler(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn handlers_should_be_called_in_order() {
+        let calls = Rc::new(RefCell::new(vec![]));
+        let mut handlers = EventHandlers::default();
+        for name in &["first", "second"] {
+            let calls = calls.clone();
+            handlers.add(Box::new(move |_| calls.borrow_mut().push(*name)));
+        }
+
+        handlers.dispatch(&glfw::WindowEvent::Pos(1, 2));
+
+        assert_eq!(*calls.borrow(), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn handlers_should_receive_the_event() {
+        let received = Rc::new(RefCell::new(None));
+        let mut handlers = EventHandlers::default();
+        {
+            let received = received.clone();
+            handlers.add(Box::new(move |event| {
+                *received.borrow_mut() = Some(event.clone())
+            }));
+        }
+
+        handlers.dispatch(&glfw::WindowEvent::Size(640, 480));
+
+        assert_eq!(*received.borrow(), Some(glfw::WindowEvent::Size(640, 480)));
+    }
+
+    #[test]
+    fn removed_handlers_should_not_be_called() {
+        let count = Rc::new(RefCell::new(0));
+        let mut handlers = EventHandlers::default();
+        let id = {
+            let count = count.clone();
+            handlers.add(Box::new(move |_| *count.borrow_mut() += 1))
+        };
+
+        assert!(handlers.remove(id));
+        assert!(!handlers.remove(id));
+        handlers.dispatch(&glfw::WindowEvent::Close);
+
+        assert_eq!(*count.borrow(), 0);
+    }
+}
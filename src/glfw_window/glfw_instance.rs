@@ -0,0 +1,34 @@
+use crate::graphics::vulkan::{ExtensionRequests, Instance};
+
+use anyhow::{bail, Context, Result};
+use std::sync::Arc;
+
+impl Instance {
+    /// Create an instance with the extensions glfw needs for window
+    /// surfaces.
+    ///
+    /// The instance doesn't depend on any window, so tests can create one
+    /// without opening a window and several windows can share one with
+    /// `GlfwWindow::with_instance`.
+    pub fn for_glfw(glfw: &glfw::Glfw) -> Result<Arc<Self>> {
+        Self::for_glfw_with_extensions(glfw, &ExtensionRequests::new())
+    }
+
+    /// Create an instance with the extensions glfw needs for window
+    /// surfaces, along with additional extensions.
+    pub fn for_glfw_with_extensions(
+        glfw: &glfw::Glfw,
+        instance_extensions: &ExtensionRequests,
+    ) -> Result<Arc<Self>> {
+        if !glfw.vulkan_supported() {
+            bail!("vulkan is not supported on this device!");
+        }
+        let surface_extensions = ExtensionRequests {
+            required: glfw.get_required_instance_extensions().context(
+                "unable to get required vulkan extensions for this platform",
+            )?,
+            optional: vec![],
+        };
+        Self::with_extensions(&surface_extensions.merge(instance_extensions))
+    }
+}
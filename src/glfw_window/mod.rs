@@ -1,6 +1,13 @@
+mod event_handlers;
+mod glfw_instance;
+mod monitor_info;
+mod monitors;
 mod window_surface;
 
-use crate::graphics::vulkan::Instance;
+use crate::{
+    graphics::vulkan::{ExtensionRequests, Instance},
+    profiling,
+};
 
 use anyhow::{bail, Context, Result};
 use ash::{extensions::khr::Surface, version::InstanceV1_0, vk, vk::Handle};
@@ -11,6 +18,66 @@ use std::{
 
 pub type EventReceiver = Receiver<(f64, glfw::WindowEvent)>;
 
+/// A callback which receives every window event, see `GlfwWindow::on_event`.
+pub type EventHandler = Box<dyn FnMut(&glfw::WindowEvent)>;
+
+/// Identifies an event handler so it can be removed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EventHandlerId {
+    id: u64,
+}
+
+/// Event handlers in the order they were added.
+#[derive(Default)]
+pub struct EventHandlers {
+    handlers: Vec<(EventHandlerId, EventHandler)>,
+    next_id: u64,
+}
+
+/// A resolution and refresh rate which a monitor supports.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: u32,
+
+    /// The bit depth of each color channel.
+    pub red_bits: u32,
+    pub green_bits: u32,
+    pub blue_bits: u32,
+}
+
+/// A snapshot of a connected monitor, see `GlfwWindow::monitors`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    /// The monitor's position in the list of connected monitors. The
+    /// primary monitor is always first.
+    pub index: usize,
+
+    /// The human readable name, not always unique.
+    pub name: String,
+
+    /// The position of the monitor's top left corner on the virtual
+    /// desktop, in screen coordinates.
+    pub position: (i32, i32),
+
+    /// The area of the monitor not covered by task bars and menus, as
+    /// `(x, y, width, height)` in screen coordinates.
+    pub work_area: (i32, i32, i32, i32),
+
+    /// The monitor's physical size in millimeters, zero when unknown.
+    pub physical_size_mm: (i32, i32),
+
+    /// The ratio between the monitor's pixels and screen coordinates.
+    pub content_scale: (f32, f32),
+
+    /// The mode the monitor is currently using.
+    pub current_mode: Option<VideoMode>,
+
+    /// Every mode the monitor supports, smallest first.
+    pub video_modes: Vec<VideoMode>,
+}
+
 /// Resources required for rendering to a single GLFW window.
 pub struct GlfwWindow {
     /// The glfw library instance
@@ -22,6 +89,9 @@ pub struct GlfwWindow {
     /// The event reciever. Usually consumed by the application's main loop.
     pub event_receiver: EventReceiver,
 
+    /// Callbacks which receive each event as it's polled.
+    event_handlers: EventHandlers,
+
     /// The raw vulkan surface handle
     surface: vk::SurfaceKHR,
 
@@ -43,16 +113,43 @@ impl GlfwWindow {
     where
         F: FnOnce(&mut glfw::Glfw) -> Result<(glfw::Window, EventReceiver)>,
     {
-        let mut glfw = glfw::init(glfw::FAIL_ON_ERRORS)
-            .context("unable to setup glfw for this application")?;
+        Self::with_instance_extensions(create_window, &ExtensionRequests::new())
+    }
+
+    /// Create a new application window and vulkan surface with additional
+    /// instance extensions.
+    ///
+    /// The extensions glfw needs for the surface are always required.
+    pub fn with_instance_extensions<F>(
+        create_window: F,
+        instance_extensions: &ExtensionRequests,
+    ) -> Result<Self>
+    where
+        F: FnOnce(&mut glfw::Glfw) -> Result<(glfw::Window, EventReceiver)>,
+    {
+        let glfw = Self::init_glfw()?;
+        let instance =
+            Instance::for_glfw_with_extensions(&glfw, instance_extensions)?;
+        Self::with_instance(&glfw, instance, create_window)
+    }
 
+    /// Create a new application window and vulkan surface which share an
+    /// existing instance, see `Instance::for_glfw`.
+    ///
+    /// Every window which shares an instance must use the same glfw library
+    /// instance, which is only initialized once per application.
+    pub fn with_instance<F>(
+        glfw: &glfw::Glfw,
+        instance: Arc<Instance>,
+        create_window: F,
+    ) -> Result<Self>
+    where
+        F: FnOnce(&mut glfw::Glfw) -> Result<(glfw::Window, EventReceiver)>,
+    {
+        let mut glfw = glfw.clone();
         let (window, event_receiver) =
             Self::build_vulkan_window(&mut glfw, create_window)?;
-
-        let instance =
-            Instance::new(&glfw.get_required_instance_extensions().context(
-                "unable to get required vulkan extensions for this platform",
-            )?)?;
+        Self::wait_for_configure(&mut glfw, &window);
 
         let surface = Self::create_surface(&instance, &window)?;
         let surface_loader = instance.create_surface_loader();
@@ -64,11 +161,18 @@ impl GlfwWindow {
             glfw,
             window,
             event_receiver,
+            event_handlers: EventHandlers::default(),
 
             instance,
         })
     }
 
+    /// Set up the glfw library for this application.
+    pub fn init_glfw() -> Result<glfw::Glfw> {
+        glfw::init(glfw::FAIL_ON_ERRORS)
+            .context("unable to setup glfw for this application")
+    }
+
     /// Create a new fullscreen window using the primary monitor.
     #[allow(dead_code)]
     pub fn fullscreen(title: &str) -> Result<Self> {
@@ -108,12 +212,55 @@ impl GlfwWindow {
         })
     }
 
+    /// Create a window which is never shown, for rendering offscreen with
+    /// `Graphics::export_rgba`.
+    ///
+    /// A display is still required. On a headless machine run under a
+    /// virtual X server like Xvfb.
+    pub fn hidden(width: u32, height: u32) -> Result<Self> {
+        GlfwWindow::new(|glfw| {
+            glfw.window_hint(glfw::WindowHint::Visible(false));
+            glfw.create_window(
+                width,
+                height,
+                "draw2d hidden window",
+                glfw::WindowMode::Windowed,
+            )
+            .context("unable to create hidden glfw window")
+        })
+    }
+
     /// Poll glfw for window events
+    ///
+    /// Every handler added with `on_event` sees each event before it's
+    /// returned.
     pub fn poll_events(&mut self) -> Vec<(f64, glfw::WindowEvent)> {
+        let _zone = profiling::zone("poll");
         self.glfw.poll_events();
-        glfw::flush_messages(&self.event_receiver)
-            .into_iter()
-            .collect()
+        let events: Vec<(f64, glfw::WindowEvent)> =
+            glfw::flush_messages(&self.event_receiver).collect();
+        for (_, event) in &events {
+            self.event_handlers.dispatch(event);
+        }
+        events
+    }
+
+    /// Call a handler with every event received by `poll_events`, so
+    /// subsystems like camera controllers and input state can follow the
+    /// window without the application forwarding each event.
+    ///
+    /// Handlers are called in the order they were added. Handlers which
+    /// share state with the application usually hold an `Rc<RefCell<_>>`.
+    pub fn on_event<F>(&mut self, handler: F) -> EventHandlerId
+    where
+        F: FnMut(&glfw::WindowEvent) + 'static,
+    {
+        self.event_handlers.add(Box::new(handler))
+    }
+
+    /// Stop calling a handler. Returns false if it was already removed.
+    pub fn remove_event_handler(&mut self, id: EventHandlerId) -> bool {
+        self.event_handlers.remove(id)
     }
 
     /// Build a vulkan-enabled glfw window, using the provided create_window
@@ -128,12 +275,29 @@ impl GlfwWindow {
         if !glfw.vulkan_supported() {
             bail!("vulkan is not supported on this device!");
         }
+        // hints from windows created earlier with the same glfw would leak
+        glfw.default_window_hints();
         glfw.window_hint(glfw::WindowHint::ClientApi(
             glfw::ClientApiHint::NoApi,
         ));
         create_window(glfw)
     }
 
+    /// Wait until the window's framebuffer has some area.
+    ///
+    /// Wayland windows have no size until the compositor sends the first
+    /// configure event, and a swapchain can't be created before then. Events
+    /// received while waiting stay queued for `poll_events`.
+    fn wait_for_configure(glfw: &mut glfw::Glfw, window: &glfw::Window) {
+        loop {
+            let (width, height) = window.get_framebuffer_size();
+            if width > 0 && height > 0 {
+                return;
+            }
+            glfw.wait_events_timeout(0.1);
+        }
+    }
+
     /// Create a vulkan surface using the glfw to handle the platform-specific
     /// setup.
     fn create_surface(
@@ -0,0 +1,148 @@
+use super::{MonitorInfo, VideoMode};
+
+impl From<glfw::VidMode> for VideoMode {
+    fn from(mode: glfw::VidMode) -> Self {
+        Self {
+            width: mode.width,
+            height: mode.height,
+            refresh_rate: mode.refresh_rate,
+            red_bits: mode.red_bits,
+            green_bits: mode.green_bits,
+            blue_bits: mode.blue_bits,
+        }
+    }
+}
+
+impl MonitorInfo {
+    /// Read everything about a monitor.
+    pub(super) fn from_monitor(index: usize, monitor: &glfw::Monitor) -> Self {
+        Self {
+            index,
+            name: monitor.get_name().unwrap_or_default(),
+            position: monitor.get_pos(),
+            work_area: monitor.get_workarea(),
+            physical_size_mm: monitor.get_physical_size(),
+            content_scale: monitor.get_content_scale(),
+            current_mode: monitor.get_video_mode().map(VideoMode::from),
+            video_modes: monitor
+                .get_video_modes()
+                .into_iter()
+                .map(VideoMode::from)
+                .collect(),
+        }
+    }
+
+    /// The monitor's mode with a resolution, preferring the highest
+    /// refresh rate and color depth. When `refresh_rate` is provided, only
+    /// modes with that exact rate are considered.
+    pub fn find_mode(
+        &self,
+        width: u32,
+        height: u32,
+        refresh_rate: Option<u32>,
+    ) -> Option<VideoMode> {
+        self.video_modes
+            .iter()
+            .filter(|mode| mode.width == width && mode.height == height)
+            .filter(|mode| {
+                refresh_rate.is_none()
+                    || refresh_rate == Some(mode.refresh_rate)
+            })
+            .max_by_key(|mode| {
+                (
+                    mode.refresh_rate,
+                    mode.red_bits + mode.green_bits + mode.blue_bits,
+                )
+            })
+            .copied()
+    }
+
+    /// The position which centers a window of the given size in the
+    /// monitor's work area. Windows larger than the work area are aligned
+    /// with its top left corner so their title bar stays reachable.
+    pub fn centered_position(&self, window_size: (i32, i32)) -> (i32, i32) {
+        let (x, y, width, height) = self.work_area;
+        (
+            x + ((width - window_size.0) / 2).max(0),
+            y + ((height - window_size.1) / 2).max(0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn mode(
+        width: u32,
+        height: u32,
+        refresh_rate: u32,
+        bits: u32,
+    ) -> VideoMode {
+        VideoMode {
+            width,
+            height,
+            refresh_rate,
+            red_bits: bits,
+            green_bits: bits,
+            blue_bits: bits,
+        }
+    }
+
+    fn monitor(video_modes: Vec<VideoMode>) -> MonitorInfo {
+        MonitorInfo {
+            index: 1,
+            name: "Projector".to_owned(),
+            position: (1920, 0),
+            work_area: (1920, 40, 1920, 1040),
+            physical_size_mm: (0, 0),
+            content_scale: (1.0, 1.0),
+            current_mode: video_modes.last().copied(),
+            video_modes,
+        }
+    }
+
+    #[test]
+    fn find_mode_should_prefer_the_highest_refresh_rate() {
+        let info = monitor(vec![
+            mode(1920, 1080, 60, 8),
+            mode(1920, 1080, 144, 8),
+            mode(1280, 720, 240, 8),
+        ]);
+
+        assert_eq!(
+            info.find_mode(1920, 1080, None),
+            Some(mode(1920, 1080, 144, 8))
+        );
+    }
+
+    #[test]
+    fn find_mode_should_match_a_requested_refresh_rate() {
+        let info = monitor(vec![
+            mode(1920, 1080, 50, 6),
+            mode(1920, 1080, 50, 8),
+            mode(1920, 1080, 60, 8),
+        ]);
+
+        assert_eq!(
+            info.find_mode(1920, 1080, Some(50)),
+            Some(mode(1920, 1080, 50, 8))
+        );
+        assert_eq!(info.find_mode(1920, 1080, Some(30)), None);
+        assert_eq!(info.find_mode(3840, 2160, None), None);
+    }
+
+    #[test]
+    fn centered_position_should_use_the_work_area() {
+        let info = monitor(vec![]);
+
+        assert_eq!(info.centered_position((1280, 720)), (2240, 200));
+    }
+
+    #[test]
+    fn large_windows_should_align_with_the_work_area() {
+        let info = monitor(vec![]);
+
+        assert_eq!(info.centered_position((2560, 1440)), (1920, 40));
+    }
+}
@@ -0,0 +1,123 @@
+use super::{GlfwWindow, MonitorInfo, VideoMode};
+
+use anyhow::{bail, Result};
+
+impl GlfwWindow {
+    /// Every connected monitor, with the primary monitor first.
+    ///
+    /// Monitors can be connected and disconnected at any time, so indices
+    /// are only valid until the next time the list changes.
+    pub fn monitors(&mut self) -> Vec<MonitorInfo> {
+        self.glfw.with_connected_monitors(|_, monitors| {
+            monitors
+                .iter()
+                .enumerate()
+                .map(|(index, monitor)| {
+                    MonitorInfo::from_monitor(index, monitor)
+                })
+                .collect()
+        })
+    }
+
+    /// The monitor which contains the center of the window, if any.
+    pub fn current_monitor(&mut self) -> Option<MonitorInfo> {
+        let (x, y) = self.window.get_pos();
+        let (width, height) = self.window.get_size();
+        let center = (x + width / 2, y + height / 2);
+        self.monitors().into_iter().find(|monitor| {
+            let mode = match monitor.current_mode {
+                Some(mode) => mode,
+                None => return false,
+            };
+            let (left, top) = monitor.position;
+            center.0 >= left
+                && center.0 < left + mode.width as i32
+                && center.1 >= top
+                && center.1 < top + mode.height as i32
+        })
+    }
+
+    /// Center the window in a monitor's work area, leaving fullscreen if
+    /// needed.
+    pub fn center_on_monitor(&mut self, index: usize) -> Result<()> {
+        let monitor = self.monitor(index)?;
+        let (width, height) = self.window.get_size();
+        let (x, y) = monitor.centered_position((width, height));
+        self.window.set_monitor(
+            glfw::WindowMode::Windowed,
+            x,
+            y,
+            width as u32,
+            height as u32,
+            None,
+        );
+        Ok(())
+    }
+
+    /// Move the window to a position relative to a monitor's top left
+    /// corner, leaving fullscreen if needed.
+    pub fn move_to_monitor(
+        &mut self,
+        index: usize,
+        position: (i32, i32),
+    ) -> Result<()> {
+        let monitor = self.monitor(index)?;
+        let (width, height) = self.window.get_size();
+        self.window.set_monitor(
+            glfw::WindowMode::Windowed,
+            monitor.position.0 + position.0,
+            monitor.position.1 + position.1,
+            width as u32,
+            height as u32,
+            None,
+        );
+        Ok(())
+    }
+
+    /// Make the window fullscreen on a monitor, switching the monitor to a
+    /// video mode. The monitor's current mode is kept when `mode` is None.
+    ///
+    /// Use `MonitorInfo::find_mode` to pick a mode supported by the
+    /// monitor, like a projector's native resolution and refresh rate.
+    /// The swapchain is rebuilt to match by the next `Graphics::render`.
+    pub fn set_fullscreen_on_monitor(
+        &mut self,
+        index: usize,
+        mode: Option<VideoMode>,
+    ) -> Result<()> {
+        let window = &mut self.window;
+        let found = self.glfw.with_connected_monitors_mut(|_, monitors| {
+            let monitor = match monitors.get(index) {
+                Some(monitor) => monitor,
+                None => return false,
+            };
+            let mode = match mode
+                .or_else(|| monitor.get_video_mode().map(VideoMode::from))
+            {
+                Some(mode) => mode,
+                None => return false,
+            };
+            window.set_monitor(
+                glfw::WindowMode::FullScreen(monitor),
+                0,
+                0,
+                mode.width,
+                mode.height,
+                Some(mode.refresh_rate),
+            );
+            true
+        });
+        if !found {
+            bail!("no monitor with a video mode at index {}", index);
+        }
+        Ok(())
+    }
+
+    /// A snapshot of one connected monitor.
+    fn monitor(&mut self, index: usize) -> Result<MonitorInfo> {
+        match self.monitors().into_iter().nth(index) {
+            Some(monitor) => Ok(monitor),
+            None => bail!("no monitor is connected at index {}", index),
+        }
+    }
+}
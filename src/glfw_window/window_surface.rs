@@ -96,4 +96,31 @@ impl vulkan::WindowSurface for GlfwWindow {
             )
             .context("unable to get surface capabiliities for this device")
     }
+
+    /// The Win32 monitor nearest to the window.
+    #[cfg(target_os = "windows")]
+    fn win32_monitor(&self) -> Option<vk::HMONITOR> {
+        use std::ffi::c_void;
+
+        /// Return the monitor nearest to the window if the window doesn't
+        /// intersect any monitor.
+        const MONITOR_DEFAULTTONEAREST: u32 = 2;
+
+        #[link(name = "user32")]
+        extern "system" {
+            fn MonitorFromWindow(hwnd: *mut c_void, flags: u32) -> *mut c_void;
+        }
+
+        let monitor = unsafe {
+            MonitorFromWindow(
+                self.window.get_win32_window(),
+                MONITOR_DEFAULTTONEAREST,
+            )
+        };
+        if monitor.is_null() {
+            None
+        } else {
+            Some(monitor as vk::HMONITOR)
+        }
+    }
 }
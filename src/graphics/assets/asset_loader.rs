@@ -0,0 +1,186 @@
+use super::{
+    asset_registry::{read_font, read_shader},
+    AssetKind, AssetLoader, LoadProgress, ManifestEntry,
+};
+
+use crate::graphics::ext::{DecodedTexture, TextureColorOptions};
+
+use ab_glyph::FontArc;
+use anyhow::Result;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+/// An asset which a worker finished decoding.
+pub(crate) struct LoadedAsset {
+    /// The key the asset was requested with.
+    pub key: String,
+
+    /// The size of the asset's file.
+    pub bytes: u64,
+
+    pub data: Result<LoadedData>,
+}
+
+/// The decoded contents of an asset.
+pub(crate) enum LoadedData {
+    Texture(DecodedTexture),
+    Font(FontArc),
+    Shader(Vec<u32>),
+}
+
+impl AssetLoader {
+    /// Start decoding every entry on up to `worker_count` threads.
+    pub fn start(entries: Vec<ManifestEntry>, worker_count: usize) -> Self {
+        let bytes_total = entries.iter().map(file_size).sum();
+        let progress = LoadProgress::new(entries.len(), bytes_total);
+
+        let worker_count = worker_count.max(1).min(entries.len());
+        let queue = Arc::new(Mutex::new(VecDeque::from(entries)));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (sender, results) = mpsc::channel();
+        let workers = (0..worker_count)
+            .map(|_| {
+                let queue = queue.clone();
+                let cancelled = cancelled.clone();
+                let sender = sender.clone();
+                thread::spawn(move || work(&queue, &cancelled, &sender))
+            })
+            .collect();
+
+        Self {
+            progress,
+            results,
+            cancelled,
+            workers,
+        }
+    }
+
+    /// Progress as of the last call to [Self::take_finished].
+    pub fn progress(&self) -> &LoadProgress {
+        &self.progress
+    }
+
+    /// Take every asset which finished decoding since the last call. Never
+    /// blocks.
+    pub(crate) fn take_finished(&mut self) -> Vec<LoadedAsset> {
+        self.results.try_iter().collect()
+    }
+
+    /// Mutable progress, updated by whoever uploads the finished assets.
+    pub(crate) fn progress_mut(&mut self) -> &mut LoadProgress {
+        &mut self.progress
+    }
+}
+
+impl Drop for AssetLoader {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        for worker in self.workers.drain(..) {
+            if worker.join().is_err() {
+                log::error!("an asset loading worker panicked");
+            }
+        }
+    }
+}
+
+/// Decode entries until the queue is empty or the load is cancelled.
+fn work(
+    queue: &Mutex<VecDeque<ManifestEntry>>,
+    cancelled: &AtomicBool,
+    sender: &Sender<LoadedAsset>,
+) {
+    while !cancelled.load(Ordering::SeqCst) {
+        let entry = match queue.lock().unwrap().pop_front() {
+            Some(entry) => entry,
+            None => return,
+        };
+        let loaded = LoadedAsset {
+            bytes: file_size(&entry),
+            data: decode(&entry),
+            key: entry.key,
+        };
+        if sender.send(loaded).is_err() {
+            return;
+        }
+    }
+}
+
+fn decode(entry: &ManifestEntry) -> Result<LoadedData> {
+    Ok(match entry.kind {
+        AssetKind::Texture => LoadedData::Texture(DecodedTexture::read_file(
+            entry.path.to_string_lossy(),
+            TextureColorOptions::default(),
+        )?),
+        AssetKind::Font => LoadedData::Font(read_font(&entry.path)?),
+        AssetKind::Shader => LoadedData::Shader(read_shader(&entry.path)?),
+    })
+}
+
+/// The size of an entry's file, or zero when it can't be read.
+fn file_size(entry: &ManifestEntry) -> u64 {
+    std::fs::metadata(&entry.path)
+        .map(|metadata| metadata.len())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::time::{Duration, Instant};
+
+    fn wait_for(loader: &mut AssetLoader, count: usize) -> Vec<LoadedAsset> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut finished = vec![];
+        while finished.len() < count && Instant::now() < deadline {
+            finished.extend(loader.take_finished());
+            thread::sleep(Duration::from_millis(1));
+        }
+        finished
+    }
+
+    #[test]
+    fn shaders_should_be_decoded_on_workers() {
+        let path = std::env::temp_dir()
+            .join(format!("draw2d-loader-{}.spv", std::process::id()));
+        let words: [u32; 2] = [0x0723_0203, 0x0001_0000];
+        let bytes: Vec<u8> =
+            words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut loader = AssetLoader::start(
+            vec![
+                ManifestEntry {
+                    kind: AssetKind::Shader,
+                    key: "glow".to_owned(),
+                    path: path.clone(),
+                },
+                ManifestEntry {
+                    kind: AssetKind::Shader,
+                    key: "missing".to_owned(),
+                    path: path.with_extension("missing"),
+                },
+            ],
+            4,
+        );
+        assert_eq!(loader.progress().bytes_total, 8);
+
+        let mut finished = wait_for(&mut loader, 2);
+        finished.sort_by(|a, b| a.key.cmp(&b.key));
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(finished.len(), 2);
+        match &finished[0].data {
+            Ok(LoadedData::Shader(decoded)) => assert_eq!(decoded, &words),
+            _ => panic!("expected the shader to load"),
+        }
+        assert!(finished[1].data.is_err());
+    }
+}
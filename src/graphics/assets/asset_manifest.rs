@@ -0,0 +1,114 @@
+use super::{AssetKind, AssetManifest, ManifestEntry};
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+impl AssetManifest {
+    /// Read a manifest file.
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| {
+            format!("unable to read asset manifest {:?}", path)
+        })?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid asset manifest {:?}", path))
+    }
+
+    /// Parse a manifest with one `kind key path` entry per line.
+    ///
+    /// Blank lines and lines starting with `#` are ignored. Paths may contain
+    /// spaces, everything after the key is part of the path.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut entries = vec![];
+        for (line_index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = parse_entry(line)
+                .with_context(|| format!("line {}", line_index + 1))?;
+            if entries.iter().any(|existing: &ManifestEntry| {
+                existing.kind == entry.kind && existing.key == entry.key
+            }) {
+                bail!(
+                    "line {}: {:?} {:?} is listed more than once",
+                    line_index + 1,
+                    entry.kind,
+                    entry.key
+                );
+            }
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+}
+
+fn parse_entry(line: &str) -> Result<ManifestEntry> {
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let kind = match parts.next() {
+        Some("texture") => AssetKind::Texture,
+        Some("font") => AssetKind::Font,
+        Some("shader") => AssetKind::Shader,
+        other => bail!("unknown asset kind {:?}", other.unwrap_or("")),
+    };
+    let mut rest = parts
+        .next()
+        .unwrap_or("")
+        .trim_start()
+        .splitn(2, char::is_whitespace);
+    let key = rest.next().unwrap_or("");
+    let path = rest.next().unwrap_or("").trim();
+    if key.is_empty() || path.is_empty() {
+        bail!("expected `kind key path` but found {:?}", line);
+    }
+    Ok(ManifestEntry {
+        kind,
+        key: key.to_owned(),
+        path: path.into(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entries_should_be_parsed_in_order() {
+        let manifest = AssetManifest::parse(
+            "# comment\n\ntexture player assets/player.png\nfont body  assets/my font.ttf\n",
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.entries,
+            vec![
+                ManifestEntry {
+                    kind: AssetKind::Texture,
+                    key: "player".to_owned(),
+                    path: "assets/player.png".into(),
+                },
+                ManifestEntry {
+                    kind: AssetKind::Font,
+                    key: "body".to_owned(),
+                    path: "assets/my font.ttf".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_kinds_should_be_rejected() {
+        assert!(AssetManifest::parse("sound boom boom.wav").is_err());
+    }
+
+    #[test]
+    fn missing_paths_should_be_rejected() {
+        assert!(AssetManifest::parse("shader glow").is_err());
+    }
+
+    #[test]
+    fn duplicate_keys_should_be_rejected() {
+        let text = "texture a a.png\ntexture a b.png";
+        assert!(AssetManifest::parse(text).is_err());
+        assert!(AssetManifest::parse("texture a a.png\nfont a a.ttf").is_ok());
+    }
+}
@@ -0,0 +1,94 @@
+use super::AssetRegistry;
+
+use ab_glyph::FontArc;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+impl AssetRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The font loaded with a key, if any.
+    pub fn font(&self, key: &str) -> Option<&FontArc> {
+        self.fonts.get(key)
+    }
+
+    /// The SPIR-V words loaded with a key, if any.
+    pub fn shader(&self, key: &str) -> Option<&[u32]> {
+        self.shaders.get(key).map(Vec::as_slice)
+    }
+
+    /// Get a font by key, reading it from a file the first time the key is
+    /// used.
+    pub fn get_or_load_font(
+        &mut self,
+        key: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<&FontArc> {
+        if !self.fonts.contains_key(key) {
+            let font = read_font(path.as_ref())?;
+            self.fonts.insert(key.to_owned(), font);
+        }
+        Ok(&self.fonts[key])
+    }
+
+    /// Get SPIR-V shader code by key, reading it from a file the first time
+    /// the key is used.
+    pub fn get_or_load_shader(
+        &mut self,
+        key: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<&[u32]> {
+        if !self.shaders.contains_key(key) {
+            let words = read_shader(path.as_ref())?;
+            self.shaders.insert(key.to_owned(), words);
+        }
+        Ok(&self.shaders[key])
+    }
+
+    /// True when a font has been loaded with the key.
+    pub fn has_font(&self, key: &str) -> bool {
+        self.fonts.contains_key(key)
+    }
+
+    /// True when shader code has been loaded with the key.
+    pub fn has_shader(&self, key: &str) -> bool {
+        self.shaders.contains_key(key)
+    }
+
+    /// Add shader code which was loaded some other way, replacing any code
+    /// with the same key.
+    pub fn insert_shader(&mut self, key: impl Into<String>, words: Vec<u32>) {
+        self.shaders.insert(key.into(), words);
+    }
+
+    /// Add a font which was loaded some other way, replacing any font with
+    /// the same key.
+    pub fn insert_font(&mut self, key: impl Into<String>, font: FontArc) {
+        self.fonts.insert(key.into(), font);
+    }
+
+    /// Forget every font and shader.
+    pub fn clear(&mut self) {
+        self.fonts.clear();
+        self.shaders.clear();
+    }
+}
+
+/// Read and parse a font file.
+pub(super) fn read_font(path: &Path) -> Result<FontArc> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("unable to read font {:?}", path))?;
+    FontArc::try_from_vec(bytes)
+        .with_context(|| format!("invalid font {:?}", path))
+}
+
+/// Read a SPIR-V file as 32 bit words.
+pub(crate) fn read_shader(path: &Path) -> Result<Vec<u32>> {
+    let mut file = std::fs::File::open(path)
+        .with_context(|| format!("unable to read shader {:?}", path))?;
+    ash::util::read_spv(&mut file)
+        .with_context(|| format!("invalid spir-v {:?}", path))
+}
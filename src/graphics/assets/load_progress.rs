@@ -0,0 +1,86 @@
+use super::LoadProgress;
+
+impl LoadProgress {
+    /// Progress for a set of assets which haven't started loading.
+    pub fn new(total: usize, bytes_total: u64) -> Self {
+        Self {
+            total,
+            bytes_total,
+            ..Default::default()
+        }
+    }
+
+    /// The fraction of work which is done, from 0 to 1.
+    ///
+    /// Progress is measured in bytes when the file sizes are known, so one
+    /// huge texture counts for more than many tiny ones.
+    pub fn fraction(&self) -> f32 {
+        if self.bytes_total > 0 {
+            self.bytes_loaded as f32 / self.bytes_total as f32
+        } else if self.total > 0 {
+            self.finished() as f32 / self.total as f32
+        } else {
+            1.0
+        }
+    }
+
+    /// The number of assets which finished, successfully or not.
+    pub fn finished(&self) -> usize {
+        self.loaded + self.failed
+    }
+
+    /// True when every asset has finished loading or failed.
+    pub fn is_complete(&self) -> bool {
+        self.finished() >= self.total
+    }
+
+    /// Record an asset which finished loading.
+    pub(crate) fn record_loaded(&mut self, bytes: u64) {
+        self.loaded += 1;
+        self.bytes_loaded += bytes;
+    }
+
+    /// Record an asset which couldn't be loaded.
+    pub(crate) fn record_failed(&mut self, bytes: u64, error: String) {
+        self.failed += 1;
+        self.bytes_loaded += bytes;
+        self.errors.push(error);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fraction_should_be_measured_in_bytes() {
+        let mut progress = LoadProgress::new(2, 1000);
+        progress.record_loaded(750);
+        assert_eq!(progress.fraction(), 0.75);
+        assert!(!progress.is_complete());
+    }
+
+    #[test]
+    fn fraction_should_use_counts_without_sizes() {
+        let mut progress = LoadProgress::new(4, 0);
+        progress.record_loaded(0);
+        assert_eq!(progress.fraction(), 0.25);
+    }
+
+    #[test]
+    fn failures_should_count_towards_completion() {
+        let mut progress = LoadProgress::new(2, 20);
+        progress.record_loaded(10);
+        progress.record_failed(10, "missing".to_owned());
+        assert!(progress.is_complete());
+        assert_eq!(progress.fraction(), 1.0);
+        assert_eq!(progress.errors, vec!["missing".to_owned()]);
+    }
+
+    #[test]
+    fn nothing_to_load_should_be_complete() {
+        let progress = LoadProgress::new(0, 0);
+        assert!(progress.is_complete());
+        assert_eq!(progress.fraction(), 1.0);
+    }
+}
@@ -0,0 +1,109 @@
+//! Central asset management for textures, fonts, and shaders.
+//!
+//! Assets are looked up by string keys so handles don't need to be passed
+//! through every function. Loading is `get_or_load`: the first request for a
+//! key reads the file and later requests return what was loaded.
+//!
+//! Textures live in the texture atlas and use the atlas's logical names as
+//! their keys, see `Graphics::get_or_load_texture`. Fonts and SPIR-V shader
+//! code are kept in the `AssetRegistry`.
+//!
+//! A manifest lists assets to preload, one per line:
+//!
+//! ```text
+//! # kind    key       path
+//! texture   player    assets/player.png
+//! font      body      assets/body.ttf
+//! shader    glow      shaders/glow.frag.spv
+//! ```
+//!
+//! Large manifests can be loaded in the background with
+//! `Graphics::load_assets_in_background`. Files are decoded by a pool of
+//! worker threads and uploaded on the main thread by
+//! `Graphics::update_asset_loading`, which reports a `LoadProgress` that can
+//! drive a loading screen.
+
+mod asset_loader;
+mod asset_manifest;
+mod asset_registry;
+mod load_progress;
+
+pub(crate) use self::{
+    asset_loader::{LoadedAsset, LoadedData},
+    asset_registry::read_shader,
+};
+
+use ab_glyph::FontArc;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, mpsc::Receiver, Arc},
+    thread::JoinHandle,
+};
+
+/// The kinds of assets which can be listed in a manifest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AssetKind {
+    Texture,
+    Font,
+    Shader,
+}
+
+/// A single asset to preload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub kind: AssetKind,
+
+    /// The key used to look up the asset once it's loaded.
+    pub key: String,
+
+    /// The file the asset is read from.
+    pub path: PathBuf,
+}
+
+/// A list of assets which can be loaded all at once.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AssetManifest {
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// Fonts and shader code keyed by name.
+#[derive(Default)]
+pub struct AssetRegistry {
+    fonts: HashMap<String, FontArc>,
+    shaders: HashMap<String, Vec<u32>>,
+}
+
+/// How far a background load has gotten.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LoadProgress {
+    /// The number of assets which finished loading.
+    pub loaded: usize,
+
+    /// The number of assets which couldn't be loaded.
+    pub failed: usize,
+
+    /// The number of assets being loaded.
+    pub total: usize,
+
+    /// The size of the files which finished, successfully or not.
+    pub bytes_loaded: u64,
+
+    /// The size of every file being loaded.
+    pub bytes_total: u64,
+
+    /// A message for every asset which couldn't be loaded.
+    pub errors: Vec<String>,
+}
+
+/// Decodes assets on a pool of worker threads.
+///
+/// Workers only touch the filesystem and cpu memory. Decoded assets are
+/// collected on the main thread, which uploads textures to the device.
+/// Dropping the loader stops the workers after their current file.
+pub struct AssetLoader {
+    progress: LoadProgress,
+    results: Receiver<LoadedAsset>,
+    cancelled: Arc<AtomicBool>,
+    workers: Vec<JoinHandle<()>>,
+}
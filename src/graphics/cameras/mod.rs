@@ -0,0 +1,35 @@
+//! Named cameras which layers can follow.
+//!
+//! The world camera, see `Graphics::set_camera`, is enough for most scenes.
+//! Applications which draw the same world from more than one point of view,
+//! like a minimap or a picture-in-picture editor view, register a named
+//! camera for each with `Graphics::add_camera` and assign layers to it with
+//! `LayerSpace::Camera`.
+//!
+//! Every layer gets its own projection each frame, so layers following
+//! different cameras can be interleaved freely in the layer stack.
+
+mod named_cameras;
+
+use crate::camera::OrthoCamera;
+
+use std::collections::HashMap;
+
+/// A reference to a named camera.
+///
+/// Handles are assigned in the order cameras are first added, so a saved
+/// layer finds its camera again as long as the application adds its
+/// cameras in the same order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CameraHandle {
+    id: u32,
+}
+
+/// Every named camera registered with the graphics subsystem.
+#[derive(Debug, Clone, Default)]
+pub struct Cameras {
+    cameras: HashMap<CameraHandle, OrthoCamera>,
+    handles: HashMap<String, CameraHandle>,
+    next_id: u32,
+}
@@ -0,0 +1,130 @@
+use super::{CameraHandle, Cameras};
+
+use crate::camera::OrthoCamera;
+
+use nalgebra as na;
+
+impl Cameras {
+    /// Create an empty set of cameras.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a camera with a name, or replace the camera which already has
+    /// the name. Replacing a camera keeps its handle.
+    pub fn insert(&mut self, name: &str, camera: OrthoCamera) -> CameraHandle {
+        let handle = match self.handles.get(name) {
+            Some(handle) => *handle,
+            None => {
+                let handle = CameraHandle { id: self.next_id };
+                self.next_id += 1;
+                self.handles.insert(name.to_owned(), handle);
+                handle
+            }
+        };
+        self.cameras.insert(handle, camera);
+        handle
+    }
+
+    /// Remove a camera. Its handle isn't reused.
+    pub fn remove(&mut self, handle: CameraHandle) -> Option<OrthoCamera> {
+        self.handles.retain(|_, existing| *existing != handle);
+        self.cameras.remove(&handle)
+    }
+
+    /// The handle of the camera with a name.
+    pub fn handle(&self, name: &str) -> Option<CameraHandle> {
+        self.handles.get(name).copied()
+    }
+
+    /// The camera for a handle, if it hasn't been removed.
+    pub fn get(&self, handle: CameraHandle) -> Option<&OrthoCamera> {
+        self.cameras.get(&handle)
+    }
+
+    /// Mutable access to the camera for a handle.
+    pub fn get_mut(
+        &mut self,
+        handle: CameraHandle,
+    ) -> Option<&mut OrthoCamera> {
+        self.cameras.get_mut(&handle)
+    }
+
+    /// The projection for a handle's camera.
+    pub fn projection(&self, handle: CameraHandle) -> Option<na::Matrix4<f32>> {
+        self.get(handle).map(OrthoCamera::as_matrix)
+    }
+
+    /// The number of cameras.
+    pub fn len(&self) -> usize {
+        self.cameras.len()
+    }
+
+    /// True when there are no cameras.
+    pub fn is_empty(&self) -> bool {
+        self.cameras.is_empty()
+    }
+
+    /// Match every camera's aspect ratio.
+    pub fn fit_to_aspect_ratio(&mut self, aspect_ratio: f32) {
+        for camera in self.cameras.values_mut() {
+            if (camera.aspect_ratio() - aspect_ratio).abs() > f32::EPSILON {
+                camera.set_aspect_ratio(aspect_ratio);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn inserting_a_name_twice_should_keep_its_handle() {
+        let mut cameras = Cameras::new();
+        let first =
+            cameras.insert("minimap", OrthoCamera::with_viewport(1.0, 1.0));
+        let second =
+            cameras.insert("minimap", OrthoCamera::with_viewport(2.0, 1.0));
+
+        assert_eq!(first, second);
+        assert_eq!(cameras.len(), 1);
+        assert_eq!(cameras.get(first).unwrap().viewport_height(), 2.0);
+    }
+
+    #[test]
+    fn names_should_get_unique_handles() {
+        let mut cameras = Cameras::new();
+        let ui = cameras.insert("ui", OrthoCamera::with_viewport(1.0, 1.0));
+        let minimap =
+            cameras.insert("minimap", OrthoCamera::with_viewport(1.0, 1.0));
+
+        assert_ne!(ui, minimap);
+        assert_eq!(cameras.handle("ui"), Some(ui));
+        assert_eq!(cameras.handle("minimap"), Some(minimap));
+    }
+
+    #[test]
+    fn removed_handles_should_not_be_reused() {
+        let mut cameras = Cameras::new();
+        let old = cameras.insert("ui", OrthoCamera::with_viewport(1.0, 1.0));
+        assert!(cameras.remove(old).is_some());
+        assert_eq!(cameras.handle("ui"), None);
+        assert_eq!(cameras.projection(old), None);
+
+        let new = cameras.insert("ui", OrthoCamera::with_viewport(1.0, 1.0));
+        assert_ne!(old, new);
+    }
+
+    #[test]
+    fn fit_to_aspect_ratio_should_change_every_camera() {
+        let mut cameras = Cameras::new();
+        let a = cameras.insert("a", OrthoCamera::with_viewport(1.0, 1.0));
+        let b = cameras.insert("b", OrthoCamera::with_viewport(1.0, 0.5));
+
+        cameras.fit_to_aspect_ratio(2.0);
+
+        assert_eq!(cameras.get(a).unwrap().aspect_ratio(), 2.0);
+        assert_eq!(cameras.get(b).unwrap().aspect_ratio(), 2.0);
+    }
+}
@@ -0,0 +1,389 @@
+use super::{
+    drawing::transform_vertices, tessellate, Canvas, CanvasStyle, Drawing,
+};
+
+use crate::{
+    geometry::Transform2d,
+    graphics::{
+        layer::Batch,
+        texture_atlas::TextureHandle,
+        vertex::{UvTransform, Vertex2d},
+    },
+    text::{Labels, TextLayout},
+};
+
+use nalgebra as na;
+
+const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+impl Default for CanvasStyle {
+    /// White shapes with no outline.
+    fn default() -> Self {
+        Self {
+            fill: Some(WHITE),
+            stroke: None,
+            stroke_weight: 1.0,
+            circle_segments: 48,
+        }
+    }
+}
+
+impl Canvas {
+    /// Create an empty canvas.
+    pub fn new() -> Self {
+        Self {
+            batches: vec![],
+            style: CanvasStyle::default(),
+            saved_styles: vec![],
+            projection: None,
+            font: None,
+        }
+    }
+
+    /// Fill shapes, and draw text, with a color.
+    pub fn fill(&mut self, rgba: [f32; 4]) {
+        self.style.fill = Some(rgba);
+    }
+
+    /// Leave shapes hollow.
+    pub fn no_fill(&mut self) {
+        self.style.fill = None;
+    }
+
+    /// Outline shapes, and draw lines, with a color.
+    pub fn stroke(&mut self, rgba: [f32; 4]) {
+        self.style.stroke = Some(rgba);
+    }
+
+    /// Skip outlines and lines.
+    pub fn no_stroke(&mut self) {
+        self.style.stroke = None;
+    }
+
+    /// Set the width of lines and outlines.
+    pub fn stroke_weight(&mut self, weight: f32) {
+        self.style.stroke_weight = weight;
+    }
+
+    /// The current style.
+    pub fn style(&self) -> &CanvasStyle {
+        &self.style
+    }
+
+    /// Change the current style directly.
+    pub fn style_mut(&mut self) -> &mut CanvasStyle {
+        &mut self.style
+    }
+
+    /// Save the current style so it can be restored with `pop_style`.
+    pub fn push_style(&mut self) {
+        self.saved_styles.push(self.style);
+    }
+
+    /// Restore the most recently pushed style. Does nothing when no style
+    /// was pushed.
+    pub fn pop_style(&mut self) {
+        if let Some(style) = self.saved_styles.pop() {
+            self.style = style;
+        }
+    }
+
+    /// Draw the canvas with a projection instead of in screen pixels, like a
+    /// camera's matrix.
+    pub fn set_projection(&mut self, projection: na::Matrix4<f32>) {
+        self.projection = Some(projection);
+    }
+
+    /// Go back to drawing in screen pixels.
+    pub fn reset_projection(&mut self) {
+        self.projection = None;
+    }
+
+    /// The canvas's projection, or None when it draws in screen pixels.
+    pub fn projection(&self) -> Option<na::Matrix4<f32>> {
+        self.projection
+    }
+
+    /// Use a font's glyphs to draw text.
+    pub fn set_font(&mut self, font: Labels) {
+        self.font = Some(font);
+    }
+
+    /// Draw a rectangle from its top left corner.
+    pub fn rect(&mut self, (x, y): (f32, f32), (width, height): (f32, f32)) {
+        if let Some(fill) = self.style.fill {
+            let vertices = self.vertices_for(TextureHandle::default());
+            tessellate::push_rect(vertices, (x, y), (width, height), fill);
+        }
+        if let Some(stroke) = self.style.stroke {
+            let weight = self.style.stroke_weight;
+            let corners = [
+                (x, y),
+                (x + width, y),
+                (x + width, y + height),
+                (x, y + height),
+            ];
+            let vertices = self.vertices_for(TextureHandle::default());
+            for i in 0..4 {
+                let (from, to) = (corners[i], corners[(i + 1) % 4]);
+                // extend each side so the corners are filled
+                let (from, to) = extend(from, to, weight / 2.0);
+                tessellate::push_line(vertices, from, to, weight, stroke);
+            }
+        }
+    }
+
+    /// Draw a circle.
+    pub fn circle(&mut self, center: (f32, f32), radius: f32) {
+        let segments = self.style.circle_segments;
+        if let Some(fill) = self.style.fill {
+            let vertices = self.vertices_for(TextureHandle::default());
+            tessellate::push_circle(vertices, center, radius, segments, fill);
+        }
+        if let Some(stroke) = self.style.stroke {
+            let weight = self.style.stroke_weight;
+            let vertices = self.vertices_for(TextureHandle::default());
+            tessellate::push_ring(
+                vertices, center, radius, weight, segments, stroke,
+            );
+        }
+    }
+
+    /// Draw a line with the stroke color.
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32)) {
+        if let Some(stroke) = self.style.stroke {
+            let weight = self.style.stroke_weight;
+            let vertices = self.vertices_for(TextureHandle::default());
+            tessellate::push_line(vertices, from, to, weight, stroke);
+        }
+    }
+
+    /// Draw a single line of text, in the fill color, with its top left
+    /// corner at the position.
+    ///
+    /// Text is skipped when no font is set. See `set_font`.
+    pub fn text(&mut self, text: &str, position: (f32, f32)) {
+        let fill = match self.style.fill {
+            Some(fill) => fill,
+            None => return,
+        };
+        let font = match self.font.take() {
+            Some(font) => font,
+            None => {
+                log::warn!("canvas text needs a font, see Canvas::set_font");
+                return;
+            }
+        };
+        let vertices = self.vertices_for(font.texture_handle());
+        font.layout(text, position, f32::INFINITY, fill, vertices);
+        self.font = Some(font);
+    }
+
+    /// Where each character of a line of text would be drawn by `text`,
+    /// for hit testing and drawing carets or selections.
+    ///
+    /// None when no font is set.
+    pub fn measure_text(
+        &self,
+        text: &str,
+        position: (f32, f32),
+    ) -> Option<TextLayout> {
+        self.font
+            .as_ref()
+            .map(|font| font.measure(text, position, f32::INFINITY))
+    }
+
+    /// Draw a texture stretched over a rectangle, tinted by the fill color.
+    pub fn image(
+        &mut self,
+        texture_handle: TextureHandle,
+        position: (f32, f32),
+        size: (f32, f32),
+    ) {
+        self.transformed_image(
+            texture_handle,
+            position,
+            size,
+            &UvTransform::default(),
+        );
+    }
+
+    /// Draw a texture stretched over a rectangle, with its texture
+    /// coordinates flipped, tiled, or scrolled.
+    pub fn transformed_image(
+        &mut self,
+        texture_handle: TextureHandle,
+        position: (f32, f32),
+        size: (f32, f32),
+        uv_transform: &UvTransform,
+    ) {
+        let tint = self.style.fill.unwrap_or(WHITE);
+        let vertices = self.vertices_for(texture_handle);
+        let first = vertices.len();
+        tessellate::push_rect(vertices, position, size, tint);
+        uv_transform.apply_to_vertices(&mut vertices[first..]);
+    }
+
+    /// Record shapes into a drawing instead of the canvas.
+    ///
+    /// Recording starts with the canvas's current style and font. Style
+    /// changes made while recording don't affect the canvas.
+    pub fn record(&mut self, draw: impl FnOnce(&mut Canvas)) -> Drawing {
+        let mut recorder = Canvas {
+            batches: vec![],
+            style: self.style,
+            saved_styles: vec![],
+            projection: None,
+            font: self.font.take(),
+        };
+        draw(&mut recorder);
+        self.font = recorder.font.take();
+        Drawing::from_batches(recorder.take_batches())
+    }
+
+    /// Draw a recorded drawing moved by a transform.
+    ///
+    /// The drawing's colors are used as they were recorded, the current
+    /// style is ignored.
+    pub fn drawing(&mut self, drawing: &Drawing, transform: &Transform2d) {
+        let matrix = transform.as_matrix3();
+        for batch in drawing.batches() {
+            let vertices = self.vertices_for(batch.texture_handle);
+            let start = vertices.len();
+            vertices.extend_from_slice(&batch.vertices);
+            transform_vertices(&matrix, &mut vertices[start..]);
+        }
+    }
+
+    /// True when nothing has been drawn since the canvas was last cleared.
+    pub fn is_empty(&self) -> bool {
+        self.batches.is_empty()
+    }
+
+    /// The shapes drawn since the canvas was last cleared.
+    pub fn batches(&self) -> &[Batch] {
+        &self.batches
+    }
+
+    /// Throw away every shape. The style and projection are kept.
+    pub fn clear(&mut self) {
+        self.batches.clear();
+    }
+
+    /// Take every shape, leaving the canvas empty.
+    pub(crate) fn take_batches(&mut self) -> Vec<Batch> {
+        std::mem::take(&mut self.batches)
+    }
+
+    /// The vertices for the next shape. Shapes are appended to the last
+    /// batch when it uses the same texture, which keeps draw calls low.
+    fn vertices_for(
+        &mut self,
+        texture_handle: TextureHandle,
+    ) -> &mut Vec<Vertex2d> {
+        let needs_batch = self
+            .batches
+            .last()
+            .map(|batch| batch.texture_handle != texture_handle)
+            .unwrap_or(true);
+        if needs_batch {
+            self.batches.push(Batch {
+                texture_handle,
+                vertices: vec![],
+                layer: 0,
+                palette: None,
+            });
+        }
+        &mut self.batches.last_mut().unwrap().vertices
+    }
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Move both ends of a segment outwards along its direction.
+fn extend(
+    from: (f32, f32),
+    to: (f32, f32),
+    amount: f32,
+) -> ((f32, f32), (f32, f32)) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length <= f32::EPSILON {
+        return (from, to);
+    }
+    let (ox, oy) = (dx / length * amount, dy / length * amount);
+    ((from.0 - ox, from.1 - oy), (to.0 + ox, to.1 + oy))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shapes_with_the_same_texture_share_a_batch() {
+        let mut canvas = Canvas::new();
+        canvas.rect((0.0, 0.0), (10.0, 10.0));
+        canvas.circle((5.0, 5.0), 2.0);
+        assert_eq!(canvas.batches().len(), 1);
+    }
+
+    #[test]
+    fn images_with_the_default_texture_share_a_batch() {
+        let mut canvas = Canvas::new();
+        canvas.rect((0.0, 0.0), (10.0, 10.0));
+        canvas.image(TextureHandle::default(), (0.0, 0.0), (1.0, 1.0));
+        assert_eq!(canvas.batches().len(), 1);
+        canvas.line((0.0, 0.0), (1.0, 1.0));
+        assert_eq!(canvas.batches().len(), 1);
+    }
+
+    #[test]
+    fn hollow_shapes_without_a_stroke_draw_nothing() {
+        let mut canvas = Canvas::new();
+        canvas.no_fill();
+        canvas.rect((0.0, 0.0), (10.0, 10.0));
+        canvas.circle((5.0, 5.0), 2.0);
+        assert!(canvas.is_empty());
+    }
+
+    #[test]
+    fn lines_need_a_stroke() {
+        let mut canvas = Canvas::new();
+        canvas.line((0.0, 0.0), (1.0, 1.0));
+        assert!(canvas.is_empty());
+        canvas.stroke(WHITE);
+        canvas.line((0.0, 0.0), (1.0, 1.0));
+        assert_eq!(canvas.batches()[0].vertices.len(), 6);
+    }
+
+    #[test]
+    fn outlined_rects_have_four_sides() {
+        let mut canvas = Canvas::new();
+        canvas.no_fill();
+        canvas.stroke(WHITE);
+        canvas.rect((0.0, 0.0), (10.0, 10.0));
+        assert_eq!(canvas.batches()[0].vertices.len(), 4 * 6);
+    }
+
+    #[test]
+    fn styles_can_be_pushed_and_popped() {
+        let mut canvas = Canvas::new();
+        canvas.push_style();
+        canvas.fill([1.0, 0.0, 0.0, 1.0]);
+        canvas.stroke_weight(4.0);
+        canvas.pop_style();
+        assert_eq!(*canvas.style(), CanvasStyle::default());
+    }
+
+    #[test]
+    fn taking_batches_empties_the_canvas() {
+        let mut canvas = Canvas::new();
+        canvas.rect((0.0, 0.0), (10.0, 10.0));
+        assert_eq!(canvas.take_batches().len(), 1);
+        assert!(canvas.is_empty());
+    }
+}
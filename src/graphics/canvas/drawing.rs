@@ -0,0 +1,137 @@
+use super::Drawing;
+
+use crate::{
+    geometry::{Rect, Transform2d},
+    graphics::{layer::Batch, vertex::Vertex2d},
+};
+
+use nalgebra as na;
+
+impl Drawing {
+    /// Create a drawing from batches which were already tessellated.
+    pub fn from_batches(batches: Vec<Batch>) -> Self {
+        Self { batches }
+    }
+
+    /// The drawing's untransformed batches.
+    pub fn batches(&self) -> &[Batch] {
+        &self.batches
+    }
+
+    /// True when the drawing has no shapes.
+    pub fn is_empty(&self) -> bool {
+        self.batches.iter().all(|batch| batch.vertices.is_empty())
+    }
+
+    /// The number of vertices in the drawing.
+    pub fn vertex_count(&self) -> usize {
+        self.batches.iter().map(|batch| batch.vertices.len()).sum()
+    }
+
+    /// The untransformed bounds of every shape, or None when the drawing is
+    /// empty.
+    pub fn bounds(&self) -> Option<Rect<f32>> {
+        self.batches.iter().filter_map(|batch| batch.bounds()).fold(
+            None,
+            |bounds: Option<Rect<f32>>, rect| match bounds {
+                None => Some(rect),
+                Some(bounds) => Some(Rect {
+                    left: bounds.left.min(rect.left),
+                    right: bounds.right.max(rect.right),
+                    bottom: bounds.bottom.min(rect.bottom),
+                    top: bounds.top.max(rect.top),
+                }),
+            },
+        )
+    }
+
+    /// Copies of the drawing's batches with every vertex moved by the
+    /// transform. Useful for adding a drawing to a retained layer.
+    pub fn transformed_batches(&self, transform: &Transform2d) -> Vec<Batch> {
+        let matrix = transform.as_matrix3();
+        self.batches
+            .iter()
+            .map(|batch| {
+                let mut transformed = batch.clone();
+                transform_vertices(&matrix, &mut transformed.vertices);
+                transformed
+            })
+            .collect()
+    }
+}
+
+/// Move every vertex by the matrix.
+pub(super) fn transform_vertices(
+    matrix: &na::Matrix3<f32>,
+    vertices: &mut [Vertex2d],
+) {
+    for vertex in vertices {
+        let point = matrix
+            .transform_point(&na::Point2::new(vertex.pos[0], vertex.pos[1]));
+        vertex.pos = [point.x, point.y];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::canvas::Canvas;
+
+    fn square() -> Drawing {
+        Canvas::new().record(|canvas| canvas.rect((0.0, 0.0), (2.0, 2.0)))
+    }
+
+    #[test]
+    fn recording_keeps_the_canvas_empty() {
+        let mut canvas = Canvas::new();
+        let drawing = canvas.record(|canvas| canvas.circle((0.0, 0.0), 1.0));
+        assert!(canvas.is_empty());
+        assert!(!drawing.is_empty());
+    }
+
+    #[test]
+    fn recording_uses_the_current_style_without_changing_it() {
+        let mut canvas = Canvas::new();
+        canvas.fill([1.0, 0.0, 0.0, 1.0]);
+        let drawing = canvas.record(|canvas| {
+            canvas.rect((0.0, 0.0), (1.0, 1.0));
+            canvas.fill([0.0, 1.0, 0.0, 1.0]);
+        });
+        assert_eq!(drawing.batches()[0].vertices[0].rgba, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(canvas.style().fill, Some([1.0, 0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn bounds_cover_every_shape() {
+        let bounds = square().bounds().unwrap();
+        assert_eq!((bounds.left, bounds.right), (0.0, 2.0));
+        assert_eq!((bounds.bottom, bounds.top), (0.0, 2.0));
+        assert!(Drawing::default().bounds().is_none());
+    }
+
+    #[test]
+    fn transformed_batches_move_every_vertex() {
+        let transform = Transform2d::from_position(na::Vector2::new(10.0, 5.0));
+        let batches = square().transformed_batches(&transform);
+        assert_eq!(batches[0].vertices[0].pos, [10.0, 5.0]);
+        assert_eq!(batches[0].vertices[2].pos, [12.0, 7.0]);
+    }
+
+    #[test]
+    fn replaying_adds_transformed_copies_to_the_canvas() {
+        let drawing = square();
+        let mut canvas = Canvas::new();
+        for x in 0..3 {
+            let transform =
+                Transform2d::from_position(na::Vector2::new(x as f32, 0.0));
+            canvas.drawing(&drawing, &transform);
+        }
+        assert_eq!(canvas.batches().len(), 1);
+        assert_eq!(
+            canvas.batches()[0].vertices.len(),
+            drawing.vertex_count() * 3
+        );
+        assert_eq!(canvas.batches()[0].vertices[6].pos, [1.0, 0.0]);
+    }
+}
@@ -0,0 +1,84 @@
+//! An immediate-mode drawing API for sketches which would rather not manage
+//! layers and batches.
+//!
+//! Shapes are described every frame and thrown away once the frame is
+//! rendered:
+//!
+//! ```ignore
+//! let canvas = graphics.canvas();
+//! canvas.fill([1.0, 0.5, 0.2, 1.0]);
+//! canvas.circle((200.0, 150.0), 40.0);
+//! canvas.no_fill();
+//! canvas.stroke([1.0, 1.0, 1.0, 1.0]);
+//! canvas.line((0.0, 0.0), (400.0, 300.0));
+//! graphics.render(&window_surface)?;
+//! ```
+//!
+//! Everything is tessellated into a transient layer which is drawn above the
+//! layers which existed when the canvas was first used. Shapes are in
+//! screen pixels, with the origin in the top left, unless a projection is
+//! set. The retained `Layer` and `Batch` API is still the better choice for
+//! large amounts of geometry which rarely changes.
+//!
+//! Shapes which are drawn many times can be recorded into a `Drawing` once
+//! and replayed with different transforms, which skips tessellating them
+//! again:
+//!
+//! ```ignore
+//! let flower = graphics.canvas().record(|canvas| {
+//!     canvas.circle((0.0, 0.0), 10.0);
+//!     canvas.line((0.0, 10.0), (0.0, 40.0));
+//! });
+//! for transform in &flower_transforms {
+//!     graphics.canvas().drawing(&flower, transform);
+//! }
+//! ```
+
+mod canvas_state;
+mod drawing;
+mod tessellate;
+
+use crate::{graphics::layer::Batch, text::Labels};
+
+use nalgebra as na;
+
+/// How shapes are filled and outlined.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CanvasStyle {
+    /// The color inside shapes, and the color of text. None leaves shapes
+    /// hollow.
+    pub fill: Option<[f32; 4]>,
+
+    /// The color of lines and outlines. None skips outlines.
+    pub stroke: Option<[f32; 4]>,
+
+    /// The width of lines and outlines.
+    pub stroke_weight: f32,
+
+    /// The number of segments used to approximate circles.
+    pub circle_segments: u32,
+}
+
+/// Shapes which will be drawn by the next frame.
+pub struct Canvas {
+    /// Tessellated shapes, with consecutive shapes which share a texture
+    /// merged into the same batch.
+    batches: Vec<Batch>,
+
+    style: CanvasStyle,
+
+    /// Styles saved by `push_style`.
+    saved_styles: Vec<CanvasStyle>,
+
+    /// The projection for the canvas's layer, or None for screen pixels.
+    projection: Option<na::Matrix4<f32>>,
+
+    /// The glyphs used to draw text.
+    font: Option<Labels>,
+}
+
+/// Tessellated shapes which were recorded once and can be drawn many times.
+#[derive(Clone, Debug, Default)]
+pub struct Drawing {
+    batches: Vec<Batch>,
+}
@@ -0,0 +1,199 @@
+//! Triangles for the canvas's shapes.
+
+use crate::graphics::vertex::Vertex2d;
+
+use std::f32::consts::PI;
+
+/// Push two triangles for the quad with corners in clockwise order.
+pub fn push_quad(
+    vertices: &mut Vec<Vertex2d>,
+    corners: [[f32; 2]; 4],
+    uvs: [[f32; 2]; 4],
+    rgba: [f32; 4],
+) {
+    let vertex = |index: usize| Vertex2d {
+        pos: corners[index],
+        uv: uvs[index],
+        rgba,
+    };
+    vertices.extend_from_slice(&[
+        vertex(0),
+        vertex(1),
+        vertex(2),
+        vertex(0),
+        vertex(2),
+        vertex(3),
+    ]);
+}
+
+/// Push an axis aligned rectangle.
+pub fn push_rect(
+    vertices: &mut Vec<Vertex2d>,
+    (x, y): (f32, f32),
+    (width, height): (f32, f32),
+    rgba: [f32; 4],
+) {
+    push_quad(
+        vertices,
+        [
+            [x, y],
+            [x + width, y],
+            [x + width, y + height],
+            [x, y + height],
+        ],
+        [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+        rgba,
+    );
+}
+
+/// Push a line segment with square ends.
+///
+/// Segments with no length are skipped because they have no direction.
+pub fn push_line(
+    vertices: &mut Vec<Vertex2d>,
+    from: (f32, f32),
+    to: (f32, f32),
+    width: f32,
+    rgba: [f32; 4],
+) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length <= f32::EPSILON {
+        return;
+    }
+    let half = width / 2.0;
+    let (nx, ny) = (-dy / length * half, dx / length * half);
+    push_quad(
+        vertices,
+        [
+            [from.0 + nx, from.1 + ny],
+            [to.0 + nx, to.1 + ny],
+            [to.0 - nx, to.1 - ny],
+            [from.0 - nx, from.1 - ny],
+        ],
+        [[0.0; 2]; 4],
+        rgba,
+    );
+}
+
+/// Push a filled circle as a fan of triangles.
+pub fn push_circle(
+    vertices: &mut Vec<Vertex2d>,
+    center: (f32, f32),
+    radius: f32,
+    segments: u32,
+    rgba: [f32; 4],
+) {
+    let points = circle_points(center, radius, segments);
+    let vertex = |pos: [f32; 2]| Vertex2d {
+        pos,
+        uv: [0.0, 0.0],
+        rgba,
+    };
+    for i in 0..points.len() {
+        let next = points[(i + 1) % points.len()];
+        vertices.extend_from_slice(&[
+            vertex([center.0, center.1]),
+            vertex(points[i]),
+            vertex(next),
+        ]);
+    }
+}
+
+/// Push a circle's outline, centered on the radius.
+pub fn push_ring(
+    vertices: &mut Vec<Vertex2d>,
+    center: (f32, f32),
+    radius: f32,
+    width: f32,
+    segments: u32,
+    rgba: [f32; 4],
+) {
+    let half = width / 2.0;
+    let outer = circle_points(center, radius + half, segments);
+    let inner = circle_points(center, (radius - half).max(0.0), segments);
+    for i in 0..outer.len() {
+        let next = (i + 1) % outer.len();
+        push_quad(
+            vertices,
+            [inner[i], outer[i], outer[next], inner[next]],
+            [[0.0; 2]; 4],
+            rgba,
+        );
+    }
+}
+
+/// Points evenly spaced around a circle. At least three are always used.
+fn circle_points(
+    (x, y): (f32, f32),
+    radius: f32,
+    segments: u32,
+) -> Vec<[f32; 2]> {
+    let segments = segments.max(3);
+    (0..segments)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f32 / segments as f32;
+            [x + radius * angle.cos(), y + radius * angle.sin()]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rects_are_two_triangles() {
+        let mut vertices = vec![];
+        push_rect(&mut vertices, (1.0, 2.0), (3.0, 4.0), [1.0; 4]);
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(vertices[2].pos, [4.0, 6.0]);
+        assert_eq!(vertices[2].uv, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn lines_are_offset_by_half_their_width() {
+        let mut vertices = vec![];
+        push_line(&mut vertices, (0.0, 0.0), (10.0, 0.0), 2.0, [1.0; 4]);
+        assert_eq!(vertices[0].pos, [0.0, 1.0]);
+        assert_eq!(vertices[2].pos, [10.0, -1.0]);
+    }
+
+    #[test]
+    fn zero_length_lines_are_skipped() {
+        let mut vertices = vec![];
+        push_line(&mut vertices, (3.0, 3.0), (3.0, 3.0), 2.0, [1.0; 4]);
+        assert!(vertices.is_empty());
+    }
+
+    #[test]
+    fn circles_have_a_triangle_per_segment() {
+        let mut vertices = vec![];
+        push_circle(&mut vertices, (0.0, 0.0), 5.0, 16, [1.0; 4]);
+        assert_eq!(vertices.len(), 16 * 3);
+        for vertex in &vertices {
+            let [x, y] = vertex.pos;
+            assert!((x * x + y * y).sqrt() <= 5.0 + 1e-4);
+        }
+    }
+
+    #[test]
+    fn circles_use_at_least_three_segments() {
+        let mut vertices = vec![];
+        push_circle(&mut vertices, (0.0, 0.0), 5.0, 0, [1.0; 4]);
+        assert_eq!(vertices.len(), 9);
+    }
+
+    #[test]
+    fn rings_straddle_the_radius() {
+        let mut vertices = vec![];
+        push_ring(&mut vertices, (0.0, 0.0), 10.0, 2.0, 8, [1.0; 4]);
+        assert_eq!(vertices.len(), 8 * 6);
+        let distances: Vec<f32> = vertices
+            .iter()
+            .map(|v| (v.pos[0] * v.pos[0] + v.pos[1] * v.pos[1]).sqrt())
+            .collect();
+        assert!(distances.iter().all(|d| *d >= 9.0 - 1e-4));
+        assert!(distances.iter().all(|d| *d <= 11.0 + 1e-4));
+    }
+}
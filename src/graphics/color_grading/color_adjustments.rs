@@ -0,0 +1,53 @@
+use super::{ColorAdjustments, ToneMapping};
+
+impl ToneMapping {
+    /// The value the color grading shader uses to pick the operator.
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            ToneMapping::None => 0,
+            ToneMapping::Reinhard => 1,
+            ToneMapping::Aces => 2,
+        }
+    }
+}
+
+impl ColorAdjustments {
+    /// True when the adjustments leave every color unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.exposure == 1.0
+            && self.gamma == 1.0
+            && self.tone_mapping == ToneMapping::None
+    }
+}
+
+impl Default for ColorAdjustments {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            gamma: 1.0,
+            tone_mapping: ToneMapping::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_adjustments_should_be_the_identity() {
+        assert!(ColorAdjustments::default().is_identity());
+        let brighter = ColorAdjustments {
+            exposure: 2.0,
+            ..Default::default()
+        };
+        assert!(!brighter.is_identity());
+    }
+
+    #[test]
+    fn shader_indices_should_match_the_shader() {
+        assert_eq!(ToneMapping::None.shader_index(), 0);
+        assert_eq!(ToneMapping::Reinhard.shader_index(), 1);
+        assert_eq!(ToneMapping::Aces.shader_index(), 2);
+    }
+}
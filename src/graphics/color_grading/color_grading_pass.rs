@@ -0,0 +1,550 @@
+use super::{
+    ColorAdjustments, ColorGradingPass, DitherPattern, Dithering,
+    GradingPipeline, GradingPushConsts, Lut3d,
+};
+
+use crate::graphics::vulkan::{
+    buffer::CpuBuffer,
+    ffi::any_as_u8_slice,
+    texture::{is_srgb, TextureImage},
+    Device, Swapchain,
+};
+
+use anyhow::{bail, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+impl ColorGradingPass {
+    /// Create the pass with an identity lookup table and no dithering.
+    ///
+    /// Fails if the swapchain images can't be copied.
+    pub fn new(device: Arc<Device>, swapchain: &Swapchain) -> Result<Self> {
+        if !swapchain
+            .image_usage
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            bail!(
+                "color grading requires a swapchain which supports TRANSFER_SRC"
+            );
+        }
+        let pipeline = GradingPipeline::new(device.clone(), swapchain)?;
+        let source = create_source(&device, swapchain)?;
+        let identity = Lut3d::identity(2);
+        let lut = create_lut(&device, &identity)?;
+        let noise_pattern = DitherPattern::None;
+        let (noise, noise_size) = create_noise(&device, noise_pattern)?;
+
+        let sampler = unsafe {
+            device.logical_device.create_sampler(
+                &vk::SamplerCreateInfo {
+                    mag_filter: vk::Filter::LINEAR,
+                    min_filter: vk::Filter::LINEAR,
+                    mipmap_mode: vk::SamplerMipmapMode::NEAREST,
+                    address_mode_u: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+                    address_mode_v: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+                    address_mode_w: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+                    ..Default::default()
+                },
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            "Color Grading Sampler",
+            vk::ObjectType::SAMPLER,
+            &sampler,
+        )?;
+
+        let pool_sizes = [vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            descriptor_count: 3,
+        }];
+        let descriptor_pool = unsafe {
+            device.logical_device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo {
+                    p_pool_sizes: pool_sizes.as_ptr(),
+                    pool_size_count: pool_sizes.len() as u32,
+                    max_sets: 1,
+                    ..Default::default()
+                },
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            "Color Grading Descriptor Pool",
+            vk::ObjectType::DESCRIPTOR_POOL,
+            &descriptor_pool,
+        )?;
+
+        let layouts = [pipeline.descriptor_set_layout];
+        let descriptor_set = unsafe {
+            device.logical_device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo {
+                    descriptor_pool,
+                    p_set_layouts: layouts.as_ptr(),
+                    descriptor_set_count: layouts.len() as u32,
+                    ..Default::default()
+                },
+            )?[0]
+        };
+
+        let pass = Self {
+            pipeline,
+            source,
+            extent: swapchain.extent,
+            lut,
+            lut_size: identity.size(),
+            has_lut: false,
+            adjustments: ColorAdjustments::default(),
+            dithering: Dithering::default(),
+            noise,
+            noise_pattern,
+            noise_size,
+            encode_gamma: encode_gamma(swapchain),
+            sampler,
+            descriptor_pool,
+            descriptor_set,
+            device,
+        };
+        pass.write_descriptors();
+        Ok(pass)
+    }
+
+    /// The adjustments applied before the lookup table.
+    pub fn adjustments(&self) -> &ColorAdjustments {
+        &self.adjustments
+    }
+
+    /// Change the adjustments, used starting with the next frame.
+    pub fn set_adjustments(&mut self, adjustments: ColorAdjustments) {
+        self.adjustments = adjustments;
+    }
+
+    /// The quantization applied after grading.
+    pub fn dithering(&self) -> &Dithering {
+        &self.dithering
+    }
+
+    /// Change the quantization, used starting with the next frame.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the pass is still
+    ///   rendering, the noise texture is replaced when the pattern changes
+    pub unsafe fn set_dithering(&mut self, dithering: Dithering) -> Result<()> {
+        let (generator, _) = dithering.pattern.generator();
+        if generator != self.noise_pattern.generator().0 {
+            let (noise, noise_size) =
+                create_noise(&self.device, dithering.pattern)?;
+            self.noise = noise;
+            self.noise_size = noise_size;
+            self.write_descriptors();
+        }
+        self.noise_pattern = dithering.pattern;
+        self.dithering = dithering;
+        Ok(())
+    }
+
+    /// True when the pass leaves every color unchanged.
+    pub fn is_identity(&self) -> bool {
+        !self.has_lut
+            && self.adjustments.is_identity()
+            && self.dithering.is_identity()
+    }
+
+    /// True when a lookup table is applied.
+    pub fn has_lut(&self) -> bool {
+        self.has_lut
+    }
+
+    /// Stop applying the lookup table.
+    pub fn clear_lut(&mut self) {
+        self.has_lut = false;
+    }
+
+    /// Upload a new lookup table and start applying it.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the pass is still
+    ///   rendering
+    pub unsafe fn set_lut(&mut self, lut: &Lut3d) -> Result<()> {
+        self.lut = create_lut(&self.device, lut)?;
+        self.lut_size = lut.size();
+        self.has_lut = true;
+        self.write_descriptors();
+        Ok(())
+    }
+
+    /// Rebuild the pipeline and source image to match a new swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the pass is still
+    ///   rendering
+    pub unsafe fn rebuild(&mut self, swapchain: &Swapchain) -> Result<()> {
+        self.pipeline = GradingPipeline::new(self.device.clone(), swapchain)?;
+        self.source = create_source(&self.device, swapchain)?;
+        self.extent = swapchain.extent;
+        self.encode_gamma = encode_gamma(swapchain);
+        self.write_descriptors();
+        Ok(())
+    }
+
+    /// How the image which holds a copy of the frame before grading is
+    /// created.
+    pub fn source_create_info(swapchain: &Swapchain) -> vk::ImageCreateInfo {
+        vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_2D,
+            extent: vk::Extent3D {
+                width: swapchain.extent.width,
+                height: swapchain.extent.height,
+                depth: 1,
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            format: swapchain.format,
+            tiling: vk::ImageTiling::OPTIMAL,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            usage: vk::ImageUsageFlags::TRANSFER_DST
+                | vk::ImageUsageFlags::SAMPLED,
+            samples: vk::SampleCountFlags::TYPE_1,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        }
+    }
+
+    /// Use a different source image, usually one which aliases the memory
+    /// of other post effect targets. The source is overwritten every frame,
+    /// so it doesn't need to be initialized.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the pass is still
+    ///   rendering
+    /// - the image must have been created with `source_create_info`
+    pub unsafe fn replace_source(
+        &mut self,
+        source: TextureImage,
+    ) -> Result<()> {
+        let image = source.raw_image();
+        self.device.name_vulkan_object(
+            "Color Grading Source - Image",
+            vk::ObjectType::IMAGE,
+            &image,
+        )?;
+        self.source = source;
+        self.write_descriptors();
+        Ok(())
+    }
+
+    /// The image which holds a copy of the frame before grading.
+    pub fn source_image(&self) -> vk::Image {
+        // SAFE: the handle is only used to record commands while the pass
+        // owns the image
+        unsafe { self.source.raw_image() }
+    }
+
+    /// Record commands which copy the finished swapchain image into the
+    /// source image.
+    ///
+    /// # Safety
+    ///
+    /// - the swapchain image must be in the `TRANSFER_SRC_OPTIMAL` layout
+    ///   and the source in `TRANSFER_DST_OPTIMAL`, the frame graph inserts
+    ///   the transitions
+    pub unsafe fn record_copy(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        swapchain_image: vk::Image,
+    ) {
+        let subresource = vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        let region = vk::ImageCopy {
+            src_subresource: subresource,
+            src_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            dst_subresource: subresource,
+            dst_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            extent: vk::Extent3D {
+                width: self.extent.width,
+                height: self.extent.height,
+                depth: 1,
+            },
+        };
+        self.device.logical_device.cmd_copy_image(
+            command_buffer,
+            swapchain_image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            self.source_image(),
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            &[region],
+        );
+    }
+
+    /// Record commands which draw the graded source into the framebuffer.
+    ///
+    /// # Safety
+    ///
+    /// - the source must be ready to sample and the swapchain image must be
+    ///   a color attachment, the frame graph inserts the transitions
+    /// - the command buffer must be recording outside of a render pass
+    pub unsafe fn record_grading(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        framebuffer: vk::Framebuffer,
+    ) {
+        let logical_device = &self.device.logical_device;
+        logical_device.cmd_begin_render_pass(
+            command_buffer,
+            &vk::RenderPassBeginInfo {
+                render_pass: self.pipeline.render_pass,
+                framebuffer,
+                render_area: vk::Rect2D {
+                    offset: vk::Offset2D { x: 0, y: 0 },
+                    extent: self.extent,
+                },
+                ..Default::default()
+            },
+            vk::SubpassContents::INLINE,
+        );
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline.pipeline,
+        );
+        logical_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline.pipeline_layout,
+            0,
+            &[self.descriptor_set],
+            &[],
+        );
+        let consts = GradingPushConsts::new(
+            &self.adjustments,
+            self.lut_size,
+            self.has_lut,
+        )
+        .with_dithering(
+            &self.dithering,
+            self.noise_size,
+            self.encode_gamma,
+        );
+        logical_device.cmd_push_constants(
+            command_buffer,
+            self.pipeline.pipeline_layout,
+            vk::ShaderStageFlags::FRAGMENT,
+            0,
+            any_as_u8_slice(&consts),
+        );
+        logical_device.cmd_draw(command_buffer, 3, 1, 0, 0);
+        logical_device.cmd_end_render_pass(command_buffer);
+    }
+
+    /// Point the descriptor set at the current source, lookup table, and
+    /// noise.
+    fn write_descriptors(&self) {
+        // SAFE: the views are owned by the pass and the descriptor set is
+        // only written while no frame is using it
+        let (source_view, lut_view, noise_view) = unsafe {
+            (
+                self.source.raw_view(),
+                self.lut.raw_view(),
+                self.noise.raw_view(),
+            )
+        };
+        let image_info = |image_view: vk::ImageView| vk::DescriptorImageInfo {
+            sampler: self.sampler,
+            image_view,
+            image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        };
+        let source_info = [image_info(source_view)];
+        let lut_info = [image_info(lut_view)];
+        let noise_info = [image_info(noise_view)];
+        let write = |binding: u32, info: &[vk::DescriptorImageInfo]| {
+            vk::WriteDescriptorSet {
+                dst_set: self.descriptor_set,
+                dst_binding: binding,
+                dst_array_element: 0,
+                descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                p_image_info: info.as_ptr(),
+                descriptor_count: info.len() as u32,
+                ..Default::default()
+            }
+        };
+        unsafe {
+            self.device.logical_device.update_descriptor_sets(
+                &[
+                    write(0, &source_info),
+                    write(1, &lut_info),
+                    write(2, &noise_info),
+                ],
+                &[],
+            );
+        }
+    }
+}
+
+impl Drop for ColorGradingPass {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_POOL,
+                &self.descriptor_pool,
+            );
+            self.device
+                .logical_device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+            self.device
+                .forget_vulkan_object(vk::ObjectType::SAMPLER, &self.sampler);
+            self.device
+                .logical_device
+                .destroy_sampler(self.sampler, None);
+        }
+    }
+}
+
+/// Create the image which holds the frame before grading, ready to be
+/// sampled.
+fn create_source(
+    device: &Arc<Device>,
+    swapchain: &Swapchain,
+) -> Result<TextureImage> {
+    let source = TextureImage::new(
+        device.clone(),
+        ColorGradingPass::source_create_info(swapchain),
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+    unsafe {
+        let image = source.raw_image();
+        device.name_vulkan_object(
+            "Color Grading Source - Image",
+            vk::ObjectType::IMAGE,
+            &image,
+        )?;
+        device.sync_graphics_commands(|command_buffer| {
+            device.logical_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    image,
+                    subresource_range: vk::ImageSubresourceRange {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        base_mip_level: 0,
+                        level_count: 1,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                    src_access_mask: vk::AccessFlags::empty(),
+                    dst_access_mask: vk::AccessFlags::SHADER_READ,
+                    ..Default::default()
+                }],
+            );
+            Ok(())
+        })?;
+    }
+    Ok(source)
+}
+
+/// Create a 3D texture and upload the lookup table's entries.
+fn create_lut(device: &Arc<Device>, lut: &Lut3d) -> Result<TextureImage> {
+    let mut texture = TextureImage::new(
+        device.clone(),
+        vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_3D,
+            extent: vk::Extent3D {
+                width: lut.size(),
+                height: lut.size(),
+                depth: lut.size(),
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            format: vk::Format::R16G16B16A16_UNORM,
+            tiling: vk::ImageTiling::OPTIMAL,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            usage: vk::ImageUsageFlags::TRANSFER_DST
+                | vk::ImageUsageFlags::SAMPLED,
+            samples: vk::SampleCountFlags::TYPE_1,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        },
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+    let mut staging =
+        CpuBuffer::new(device.clone(), vk::BufferUsageFlags::TRANSFER_SRC)?;
+    unsafe {
+        // SAFE: both the texture and buffer were just created
+        staging.write_data(&lut.rgba16_texels())?;
+        texture.upload_from_buffer(&staging)?;
+        let image = texture.raw_image();
+        device.name_vulkan_object(
+            "Color Grading LUT - Image",
+            vk::ObjectType::IMAGE,
+            &image,
+        )?;
+    }
+    Ok(texture)
+}
+
+/// Generate the threshold texture for a dithering pattern. Returns the
+/// texture and its width.
+fn create_noise(
+    device: &Arc<Device>,
+    pattern: DitherPattern,
+) -> Result<(TextureImage, u32)> {
+    let (generator, size) = pattern.generator();
+    let mut texture = TextureImage::new(
+        device.clone(),
+        vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_2D,
+            extent: vk::Extent3D {
+                width: size,
+                height: size,
+                depth: 1,
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            format: generator.format(),
+            tiling: vk::ImageTiling::OPTIMAL,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            usage: vk::ImageUsageFlags::TRANSFER_DST
+                | vk::ImageUsageFlags::SAMPLED,
+            samples: vk::SampleCountFlags::TYPE_1,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        },
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+    let mut staging =
+        CpuBuffer::new(device.clone(), vk::BufferUsageFlags::TRANSFER_SRC)?;
+    unsafe {
+        // SAFE: both the texture and buffer were just created
+        staging.write_data(&generator.pixels(size, size))?;
+        texture.upload_from_buffer(&staging)?;
+        let image = texture.raw_image();
+        device.name_vulkan_object(
+            format!("Color Grading Noise {:?} - Image", generator),
+            vk::ObjectType::IMAGE,
+            &image,
+        )?;
+    }
+    Ok((texture, size))
+}
+
+/// The gamma the swapchain encodes with, approximating sRGB with 2.2.
+fn encode_gamma(swapchain: &Swapchain) -> f32 {
+    if is_srgb(swapchain.format) {
+        2.2
+    } else {
+        1.0
+    }
+}
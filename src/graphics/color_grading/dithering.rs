@@ -0,0 +1,98 @@
+use super::{DitherPattern, Dithering};
+
+use crate::graphics::texture_generator::Generator;
+
+/// The most bits per channel which are quantized. The swapchain keeps 8
+/// bits, so anything more is never visible.
+const MAX_BITS: u32 = 8;
+
+impl DitherPattern {
+    /// The procedural texture which holds the pattern's thresholds, and the
+    /// size it's generated at.
+    ///
+    /// Patterns without thresholds still use the Bayer matrix, so the pass
+    /// always has a texture to bind.
+    pub fn generator(&self) -> (Generator, u32) {
+        match self {
+            DitherPattern::None | DitherPattern::Ordered => {
+                (Generator::Bayer { size: 8 }, 8)
+            }
+            DitherPattern::BlueNoise => (Generator::BlueNoise { seed: 0 }, 64),
+        }
+    }
+
+    /// The value the color grading shader uses to enable dithering.
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            DitherPattern::None => 0,
+            DitherPattern::Ordered | DitherPattern::BlueNoise => 1,
+        }
+    }
+}
+
+impl Dithering {
+    /// Ordered dithering down to the given bits per channel.
+    pub fn ordered(bits: u32) -> Self {
+        Self {
+            pattern: DitherPattern::Ordered,
+            bits,
+        }
+    }
+
+    /// Blue noise dithering down to the given bits per channel.
+    pub fn blue_noise(bits: u32) -> Self {
+        Self {
+            pattern: DitherPattern::BlueNoise,
+            bits,
+        }
+    }
+
+    /// The largest value of each quantized channel, or 0.0 when colors are
+    /// left alone.
+    pub fn levels(&self) -> f32 {
+        if self.is_identity() {
+            return 0.0;
+        }
+        let bits = self.bits.clamp(1, MAX_BITS);
+        ((1 << bits) - 1) as f32
+    }
+
+    /// True when colors are left unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.pattern == DitherPattern::None && self.bits >= MAX_BITS
+    }
+}
+
+impl Default for Dithering {
+    fn default() -> Self {
+        Self {
+            pattern: DitherPattern::None,
+            bits: MAX_BITS,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn levels_should_match_the_bit_depth() {
+        assert_eq!(Dithering::default().levels(), 0.0);
+        assert_eq!(Dithering::ordered(1).levels(), 1.0);
+        assert_eq!(Dithering::blue_noise(4).levels(), 15.0);
+        assert_eq!(Dithering::ordered(0).levels(), 1.0);
+        assert_eq!(Dithering::ordered(12).levels(), 255.0);
+    }
+
+    #[test]
+    fn quantizing_without_a_pattern_should_not_be_the_identity() {
+        let banded = Dithering {
+            pattern: DitherPattern::None,
+            bits: 3,
+        };
+        assert!(!banded.is_identity());
+        assert_eq!(banded.levels(), 7.0);
+        assert_eq!(banded.pattern.shader_index(), 0);
+    }
+}
@@ -0,0 +1,107 @@
+use super::{ColorAdjustments, Dithering, GradingPushConsts};
+
+impl GradingPushConsts {
+    /// Build the push constants for grading with the adjustments and a
+    /// lookup table with `lut_size` entries along each axis.
+    ///
+    /// The lookup table is only applied when `apply_lut` is true. Gammas
+    /// which aren't positive are treated as 1.0.
+    pub fn new(
+        adjustments: &ColorAdjustments,
+        lut_size: u32,
+        apply_lut: bool,
+    ) -> Self {
+        let gamma = if adjustments.gamma > 0.0 {
+            adjustments.gamma
+        } else {
+            1.0
+        };
+        let size = lut_size.max(1) as f32;
+        Self {
+            exposure: adjustments.exposure,
+            inverse_gamma: 1.0 / gamma,
+            tone_mapping: adjustments.tone_mapping.shader_index(),
+            lut_strength: if apply_lut { 1.0 } else { 0.0 },
+            // sample the centers of the first and last entries
+            lut_scale: (size - 1.0) / size,
+            lut_offset: 0.5 / size,
+            levels: 0.0,
+            dither: 0,
+            inverse_noise_size: 1.0,
+            encode_gamma: 1.0,
+        }
+    }
+
+    /// Quantize colors after grading, using thresholds from a noise texture
+    /// which is `noise_size` texels wide.
+    ///
+    /// Quantization is done after raising colors to `1 / encode_gamma`, so
+    /// levels are evenly spaced in the swapchain's encoding.
+    pub fn with_dithering(
+        self,
+        dithering: &Dithering,
+        noise_size: u32,
+        encode_gamma: f32,
+    ) -> Self {
+        Self {
+            levels: dithering.levels(),
+            dither: dithering.pattern.shader_index(),
+            inverse_noise_size: 1.0 / noise_size.max(1) as f32,
+            encode_gamma,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use memoffset::offset_of;
+    use std::mem::size_of;
+
+    #[test]
+    fn layout_should_match_the_glsl_block() {
+        assert_eq!(offset_of!(GradingPushConsts, exposure), 0);
+        assert_eq!(offset_of!(GradingPushConsts, inverse_gamma), 4);
+        assert_eq!(offset_of!(GradingPushConsts, tone_mapping), 8);
+        assert_eq!(offset_of!(GradingPushConsts, lut_strength), 12);
+        assert_eq!(offset_of!(GradingPushConsts, lut_scale), 16);
+        assert_eq!(offset_of!(GradingPushConsts, lut_offset), 20);
+        assert_eq!(offset_of!(GradingPushConsts, levels), 24);
+        assert_eq!(offset_of!(GradingPushConsts, dither), 28);
+        assert_eq!(offset_of!(GradingPushConsts, inverse_noise_size), 32);
+        assert_eq!(offset_of!(GradingPushConsts, encode_gamma), 36);
+        assert_eq!(size_of::<GradingPushConsts>(), 40);
+    }
+
+    #[test]
+    fn lut_coordinates_should_hit_entry_centers() {
+        let consts = GradingPushConsts::new(&Default::default(), 4, true);
+        assert_eq!(consts.lut_offset, 0.125);
+        assert_eq!(consts.lut_scale + consts.lut_offset, 0.875);
+        assert_eq!(consts.lut_strength, 1.0);
+    }
+
+    #[test]
+    fn invalid_gamma_should_be_ignored() {
+        let adjustments = ColorAdjustments {
+            gamma: 0.0,
+            ..Default::default()
+        };
+        let consts = GradingPushConsts::new(&adjustments, 2, false);
+        assert_eq!(consts.inverse_gamma, 1.0);
+        assert_eq!(consts.lut_strength, 0.0);
+        assert_eq!(consts.levels, 0.0);
+    }
+
+    #[test]
+    fn dithering_should_set_the_levels_and_noise_scale() {
+        let consts = GradingPushConsts::new(&Default::default(), 2, false)
+            .with_dithering(&Dithering::ordered(2), 8, 2.2);
+        assert_eq!(consts.levels, 3.0);
+        assert_eq!(consts.dither, 1);
+        assert_eq!(consts.inverse_noise_size, 0.125);
+        assert_eq!(consts.encode_gamma, 2.2);
+    }
+}
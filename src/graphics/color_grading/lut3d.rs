@@ -0,0 +1,213 @@
+use super::Lut3d;
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+/// The largest table size allowed by the `.cube` format.
+const MAX_SIZE: u32 = 256;
+
+impl Lut3d {
+    /// A table which maps every color to itself.
+    ///
+    /// A table with two entries per axis is already exact, larger tables
+    /// only matter as a starting point for edits.
+    pub fn identity(size: u32) -> Self {
+        let size = size.max(2);
+        let max = (size - 1) as f32;
+        let mut values = Vec::with_capacity((size * size * size) as usize);
+        for blue in 0..size {
+            for green in 0..size {
+                for red in 0..size {
+                    values.push([
+                        red as f32 / max,
+                        green as f32 / max,
+                        blue as f32 / max,
+                    ]);
+                }
+            }
+        }
+        Self { size, values }
+    }
+
+    /// Create a table from its entries, red changing fastest.
+    ///
+    /// Fails unless there are exactly `size` cubed entries.
+    pub fn from_values(size: u32, values: Vec<[f32; 3]>) -> Result<Self> {
+        if !(2..=MAX_SIZE).contains(&size) {
+            bail!(
+                "lookup tables need between 2 and {} entries per axis, not {}",
+                MAX_SIZE,
+                size
+            );
+        }
+        let expected = (size * size * size) as usize;
+        if values.len() != expected {
+            bail!(
+                "a lookup table of size {} needs {} entries, found {}",
+                size,
+                expected,
+                values.len()
+            );
+        }
+        Ok(Self { size, values })
+    }
+
+    /// Parse the contents of an Adobe/Resolve `.cube` file.
+    ///
+    /// Titles and comments are ignored. Only 3D tables with the default
+    /// `0 1` domain are supported.
+    pub fn parse_cube(source: &str) -> Result<Self> {
+        let mut size = None;
+        let mut values = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap();
+            match keyword {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    let value = words
+                        .next()
+                        .context("LUT_3D_SIZE is missing its size")?;
+                    size = Some(value.parse::<u32>().with_context(|| {
+                        format!("invalid LUT_3D_SIZE {:?}", value)
+                    })?);
+                }
+                "LUT_1D_SIZE" => bail!("1D lookup tables are not supported"),
+                "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                    let expected =
+                        if keyword == "DOMAIN_MIN" { 0.0 } else { 1.0 };
+                    let domain = parse_triple(words, index)?;
+                    if domain.iter().any(|bound| *bound != expected) {
+                        bail!("unsupported {} {:?}", keyword, domain);
+                    }
+                }
+                _ => values.push(parse_triple(line.split_whitespace(), index)?),
+            }
+        }
+        let size = size.context("the file is missing LUT_3D_SIZE")?;
+        Self::from_values(size, values)
+    }
+
+    /// Read and parse a `.cube` file.
+    pub fn read_cube_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).with_context(|| {
+            format!("unable to read lookup table {:?}", path)
+        })?;
+        Self::parse_cube(&source)
+            .with_context(|| format!("invalid lookup table {:?}", path))
+    }
+
+    /// The number of entries along each axis.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Every entry, red changing fastest.
+    pub fn values(&self) -> &[[f32; 3]] {
+        &self.values
+    }
+
+    /// The entry nearest to a color.
+    pub fn lookup(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let max = (self.size - 1) as f32;
+        let index = |channel: f32| (channel.clamp(0.0, 1.0) * max).round();
+        let (red, green, blue) = (index(rgb[0]), index(rgb[1]), index(rgb[2]));
+        let size = self.size as f32;
+        self.values[(red + green * size + blue * size * size) as usize]
+    }
+
+    /// The table's entries as RGBA 16-bit unorm texels, ready to upload to a
+    /// 3D texture.
+    pub fn rgba16_texels(&self) -> Vec<u16> {
+        let unorm =
+            |value: f32| (value.clamp(0.0, 1.0) * 65535.0).round() as u16;
+        self.values
+            .iter()
+            .flat_map(|[red, green, blue]| {
+                vec![unorm(*red), unorm(*green), unorm(*blue), u16::MAX]
+            })
+            .collect()
+    }
+}
+
+/// Parse three floats, the remaining words on a line.
+fn parse_triple<'a>(
+    words: impl Iterator<Item = &'a str>,
+    index: usize,
+) -> Result<[f32; 3]> {
+    let numbers = words
+        .map(|word| word.parse::<f32>())
+        .collect::<Result<Vec<f32>, _>>()
+        .with_context(|| format!("invalid number on line {}", index + 1))?;
+    match numbers.as_slice() {
+        [red, green, blue] => Ok([*red, *green, *blue]),
+        _ => bail!("expected 3 numbers on line {}", index + 1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INVERT: &str = "
+        # inverts every color
+        TITLE \"invert\"
+        LUT_3D_SIZE 2
+        DOMAIN_MIN 0.0 0.0 0.0
+        DOMAIN_MAX 1.0 1.0 1.0
+
+        1.0 1.0 1.0
+        0.0 1.0 1.0
+        1.0 0.0 1.0
+        0.0 0.0 1.0
+        1.0 1.0 0.0
+        0.0 1.0 0.0
+        1.0 0.0 0.0
+        0.0 0.0 0.0
+    ";
+
+    #[test]
+    fn parse_cube_should_read_entries_with_red_changing_fastest() {
+        let lut = Lut3d::parse_cube(INVERT).unwrap();
+        assert_eq!(lut.size(), 2);
+        assert_eq!(lut.lookup([0.0, 0.0, 0.0]), [1.0, 1.0, 1.0]);
+        assert_eq!(lut.lookup([1.0, 0.0, 0.0]), [0.0, 1.0, 1.0]);
+        assert_eq!(lut.lookup([0.0, 0.0, 1.0]), [1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn parse_cube_should_reject_the_wrong_entry_count() {
+        let source = "LUT_3D_SIZE 3\n0 0 0\n1 1 1\n";
+        assert!(Lut3d::parse_cube(source).is_err());
+    }
+
+    #[test]
+    fn parse_cube_should_reject_unsupported_tables() {
+        assert!(Lut3d::parse_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        let domain = INVERT.replace("DOMAIN_MAX 1.0", "DOMAIN_MAX 2.0");
+        assert!(Lut3d::parse_cube(&domain).is_err());
+        assert!(Lut3d::parse_cube("0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn identity_should_map_colors_to_themselves() {
+        let lut = Lut3d::identity(5);
+        assert_eq!(lut.values().len(), 125);
+        assert_eq!(lut.lookup([0.25, 0.5, 1.0]), [0.25, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn texels_should_be_opaque_unorm_values() {
+        let texels = Lut3d::identity(2).rgba16_texels();
+        assert_eq!(texels.len(), 8 * 4);
+        assert_eq!(
+            &texels[..8],
+            &[0, 0, 0, u16::MAX, u16::MAX, 0, 0, u16::MAX]
+        );
+    }
+}
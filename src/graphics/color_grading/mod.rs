@@ -0,0 +1,163 @@
+//! An optional final pass which applies exposure, tone mapping, gamma, a 3D
+//! color lookup table, and dithered quantization to every frame.
+//!
+//! # Big Idea
+//!
+//! Once the layers have been drawn, the swapchain image is copied into an
+//! offscreen source image. A fullscreen triangle then samples the source and
+//! writes the graded color back into the swapchain image. Recordings capture
+//! the graded frame, while feedback layers sample the frame before grading
+//! so trails aren't graded over and over.
+//!
+//! Color grading lookup tables are read from `.cube` files, the format used
+//! by most color grading tools, see `Lut3d::parse_cube`.
+//!
+//! Quantization happens last, in the swapchain's encoding, so reducing the
+//! bit depth produces evenly spaced steps on screen. The thresholds come
+//! from the procedural textures in `texture_generator`, tiled across the
+//! screen one texel per pixel.
+
+mod color_adjustments;
+mod color_grading_pass;
+mod dithering;
+mod grading_push_consts;
+mod lut3d;
+mod pipeline;
+
+use crate::graphics::vulkan::{texture::TextureImage, Device};
+
+use ash::vk;
+use std::sync::Arc;
+
+/// How bright colors are compressed into the displayable range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ToneMapping {
+    /// Colors are clamped.
+    #[default]
+    None,
+
+    /// The simple Reinhard operator, `color / (color + 1)`.
+    Reinhard,
+
+    /// Narkowicz's fit of the ACES filmic curve.
+    Aces,
+}
+
+/// Exposure, tone mapping, and gamma applied before the lookup table.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ColorAdjustments {
+    /// Colors are multiplied by the exposure before tone mapping.
+    pub exposure: f32,
+
+    /// The gamma to encode with, after tone mapping. 1.0 leaves colors
+    /// unchanged, the swapchain already encodes sRGB.
+    pub gamma: f32,
+
+    /// How bright colors are brought into range.
+    pub tone_mapping: ToneMapping,
+}
+
+/// The thresholds used to dither colors before they're quantized.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DitherPattern {
+    /// Colors are rounded to the nearest level, leaving visible bands.
+    #[default]
+    None,
+
+    /// An 8x8 Bayer matrix, the crosshatched look of classic hardware.
+    Ordered,
+
+    /// Blue noise, which hides the pattern in fine grain.
+    BlueNoise,
+}
+
+/// Color quantization, applied after every other adjustment.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Dithering {
+    pub pattern: DitherPattern,
+
+    /// The bits kept for each color channel, from 1 to 8. Alpha is never
+    /// quantized.
+    pub bits: u32,
+}
+
+/// A 3D color lookup table.
+///
+/// Entries are ordered with red changing fastest, then green, then blue, the
+/// same as `.cube` files and 3D textures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lut3d {
+    /// The number of entries along each axis.
+    size: u32,
+
+    /// The output color for each entry.
+    values: Vec<[f32; 3]>,
+}
+
+/// The push constants used by the color grading pipeline.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradingPushConsts {
+    pub exposure: f32,
+    pub inverse_gamma: f32,
+    pub tone_mapping: u32,
+    pub lut_strength: f32,
+    pub lut_scale: f32,
+    pub lut_offset: f32,
+    pub levels: f32,
+    pub dither: u32,
+    pub inverse_noise_size: f32,
+    pub encode_gamma: f32,
+}
+
+/// The gpu resources used to grade each frame.
+pub struct ColorGradingPass {
+    pipeline: GradingPipeline,
+
+    /// A copy of the frame before grading.
+    source: TextureImage,
+
+    /// The size of the source image and swapchain.
+    extent: vk::Extent2D,
+
+    /// The lookup table, an identity table until one is set.
+    lut: TextureImage,
+
+    /// The number of entries along each axis of the lookup table.
+    lut_size: u32,
+
+    /// True once a lookup table has been set, so the table is applied.
+    has_lut: bool,
+
+    adjustments: ColorAdjustments,
+
+    dithering: Dithering,
+
+    /// The dithering thresholds, tiled across the screen.
+    noise: TextureImage,
+
+    /// The pattern the noise texture was generated for.
+    noise_pattern: DitherPattern,
+
+    /// The width and height of the noise texture.
+    noise_size: u32,
+
+    /// 2.2 when the swapchain sRGB encodes colors, so quantization can
+    /// happen in the same encoding, otherwise 1.0.
+    encode_gamma: f32,
+
+    sampler: vk::Sampler,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set: vk::DescriptorSet,
+
+    device: Arc<Device>,
+}
+
+/// The render pass and pipeline which draw the graded frame.
+struct GradingPipeline {
+    render_pass: vk::RenderPass,
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    device: Arc<Device>,
+}
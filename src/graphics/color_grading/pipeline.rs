@@ -0,0 +1,358 @@
+use super::{GradingPipeline, GradingPushConsts};
+
+use crate::graphics::vulkan::{shader_module::ShaderModule, Device, Swapchain};
+
+use anyhow::{Context, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::{ffi::CString, mem::size_of, sync::Arc};
+
+impl GradingPipeline {
+    /// Create the render pass and pipeline which draw a graded frame into
+    /// the swapchain's framebuffers.
+    pub fn new(device: Arc<Device>, swapchain: &Swapchain) -> Result<Self> {
+        let render_pass = create_render_pass(&device, swapchain.format)?;
+        let descriptor_set_layout = match create_descriptor_set_layout(&device)
+        {
+            Ok(layout) => layout,
+            Err(error) => {
+                unsafe {
+                    device.forget_vulkan_object(
+                        vk::ObjectType::RENDER_PASS,
+                        &render_pass,
+                    );
+                    device
+                        .logical_device
+                        .destroy_render_pass(render_pass, None);
+                }
+                return Err(error);
+            }
+        };
+
+        let layouts = [descriptor_set_layout];
+        let push_constant_ranges = [vk::PushConstantRange {
+            stage_flags: vk::ShaderStageFlags::FRAGMENT,
+            size: size_of::<GradingPushConsts>() as u32,
+            offset: 0,
+        }];
+        let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+            p_set_layouts: layouts.as_ptr(),
+            set_layout_count: layouts.len() as u32,
+            p_push_constant_ranges: push_constant_ranges.as_ptr(),
+            push_constant_range_count: push_constant_ranges.len() as u32,
+            ..Default::default()
+        };
+        let pipeline_layout = unsafe {
+            device
+                .logical_device
+                .create_pipeline_layout(&pipeline_layout_create_info, None)?
+        };
+        device.name_vulkan_object(
+            "Color Grading Pipeline Layout",
+            vk::ObjectType::PIPELINE_LAYOUT,
+            &pipeline_layout,
+        )?;
+
+        let result = create_pipeline(
+            &device,
+            swapchain.extent,
+            render_pass,
+            pipeline_layout,
+        );
+        let pipeline = match result {
+            Ok(pipeline) => pipeline,
+            Err(error) => {
+                unsafe {
+                    device.forget_vulkan_object(
+                        vk::ObjectType::PIPELINE_LAYOUT,
+                        &pipeline_layout,
+                    );
+                    device
+                        .logical_device
+                        .destroy_pipeline_layout(pipeline_layout, None);
+                    device.forget_vulkan_object(
+                        vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+                        &descriptor_set_layout,
+                    );
+                    device.logical_device.destroy_descriptor_set_layout(
+                        descriptor_set_layout,
+                        None,
+                    );
+                    device.forget_vulkan_object(
+                        vk::ObjectType::RENDER_PASS,
+                        &render_pass,
+                    );
+                    device
+                        .logical_device
+                        .destroy_render_pass(render_pass, None);
+                }
+                return Err(error);
+            }
+        };
+
+        Ok(Self {
+            render_pass,
+            pipeline_layout,
+            pipeline,
+            descriptor_set_layout,
+            device,
+        })
+    }
+}
+
+impl Drop for GradingPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .forget_vulkan_object(vk::ObjectType::PIPELINE, &self.pipeline);
+            self.device
+                .logical_device
+                .destroy_pipeline(self.pipeline, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::PIPELINE_LAYOUT,
+                &self.pipeline_layout,
+            );
+            self.device
+                .logical_device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+                &self.descriptor_set_layout,
+            );
+            self.device.logical_device.destroy_descriptor_set_layout(
+                self.descriptor_set_layout,
+                None,
+            );
+            self.device.forget_vulkan_object(
+                vk::ObjectType::RENDER_PASS,
+                &self.render_pass,
+            );
+            self.device
+                .logical_device
+                .destroy_render_pass(self.render_pass, None);
+        }
+    }
+}
+
+/// Create a render pass which is compatible with the swapchain's
+/// framebuffers.
+///
+/// The graded color replaces every pixel, so the old contents aren't loaded.
+/// The image stays a color attachment, the frame graph transitions it
+/// afterwards.
+fn create_render_pass(
+    device: &Device,
+    format: vk::Format,
+) -> Result<vk::RenderPass> {
+    let attachments = [vk::AttachmentDescription {
+        format,
+        samples: vk::SampleCountFlags::TYPE_1,
+        load_op: vk::AttachmentLoadOp::DONT_CARE,
+        store_op: vk::AttachmentStoreOp::STORE,
+        stencil_load_op: vk::AttachmentLoadOp::DONT_CARE,
+        stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
+        initial_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        final_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        ..Default::default()
+    }];
+
+    let color_references = [vk::AttachmentReference {
+        attachment: 0,
+        layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+    }];
+
+    let subpasses = [vk::SubpassDescription {
+        pipeline_bind_point: vk::PipelineBindPoint::GRAPHICS,
+        p_color_attachments: color_references.as_ptr(),
+        color_attachment_count: color_references.len() as u32,
+        ..Default::default()
+    }];
+
+    let create_info = vk::RenderPassCreateInfo {
+        p_attachments: attachments.as_ptr(),
+        attachment_count: attachments.len() as u32,
+        p_subpasses: subpasses.as_ptr(),
+        subpass_count: subpasses.len() as u32,
+        ..Default::default()
+    };
+
+    let render_pass = unsafe {
+        device
+            .logical_device
+            .create_render_pass(&create_info, None)?
+    };
+    device.name_vulkan_object(
+        "Color Grading Render Pass",
+        vk::ObjectType::RENDER_PASS,
+        &render_pass,
+    )?;
+    Ok(render_pass)
+}
+
+/// The source frame is bound first, then the lookup table, then the
+/// dithering thresholds.
+fn create_descriptor_set_layout(
+    device: &Device,
+) -> Result<vk::DescriptorSetLayout> {
+    let binding = |binding: u32| vk::DescriptorSetLayoutBinding {
+        binding,
+        descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+        descriptor_count: 1,
+        stage_flags: vk::ShaderStageFlags::FRAGMENT,
+        p_immutable_samplers: std::ptr::null(),
+    };
+    let bindings = [binding(0), binding(1), binding(2)];
+    let descriptor_set_layout = unsafe {
+        device.logical_device.create_descriptor_set_layout(
+            &vk::DescriptorSetLayoutCreateInfo {
+                p_bindings: bindings.as_ptr(),
+                binding_count: bindings.len() as u32,
+                ..Default::default()
+            },
+            None,
+        )?
+    };
+    device.name_vulkan_object(
+        "Color Grading Descriptor Set Layout",
+        vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+        &descriptor_set_layout,
+    )?;
+    Ok(descriptor_set_layout)
+}
+
+/// Create a pipeline which draws a single fullscreen triangle.
+fn create_pipeline(
+    device: &Arc<Device>,
+    extent: vk::Extent2D,
+    render_pass: vk::RenderPass,
+    pipeline_layout: vk::PipelineLayout,
+) -> Result<vk::Pipeline> {
+    let vertex_module = ShaderModule::new(
+        device,
+        "Color Grading Vertex Shader",
+        std::include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/shaders/fullscreen.vert.sprv"
+        )),
+    )?;
+    let fragment_module = ShaderModule::new(
+        device,
+        "Color Grading Fragment Shader",
+        std::include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/shaders/color_grading.frag.sprv"
+        )),
+    )?;
+
+    let entry = CString::new("main").unwrap();
+    let stages = [
+        vk::PipelineShaderStageCreateInfo {
+            stage: vk::ShaderStageFlags::VERTEX,
+            module: vertex_module.shader_module,
+            p_name: entry.as_ptr(),
+            ..Default::default()
+        },
+        vk::PipelineShaderStageCreateInfo {
+            stage: vk::ShaderStageFlags::FRAGMENT,
+            module: fragment_module.shader_module,
+            p_name: entry.as_ptr(),
+            ..Default::default()
+        },
+    ];
+
+    // the triangle's corners come from the vertex index
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::default();
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo {
+        topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+        primitive_restart_enable: 0,
+        ..Default::default()
+    };
+
+    let viewports = [vk::Viewport {
+        x: 0.0,
+        y: 0.0,
+        width: extent.width as f32,
+        height: extent.height as f32,
+        min_depth: 0.0,
+        max_depth: 1.0,
+    }];
+
+    let scissors = [vk::Rect2D {
+        offset: vk::Offset2D { x: 0, y: 0 },
+        extent,
+    }];
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo {
+        p_viewports: viewports.as_ptr(),
+        viewport_count: 1,
+        p_scissors: scissors.as_ptr(),
+        scissor_count: 1,
+        ..Default::default()
+    };
+
+    let raster_state = vk::PipelineRasterizationStateCreateInfo {
+        polygon_mode: vk::PolygonMode::FILL,
+        line_width: 1.0,
+        cull_mode: vk::CullModeFlags::NONE,
+        front_face: vk::FrontFace::CLOCKWISE,
+        ..Default::default()
+    };
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo {
+        rasterization_samples: vk::SampleCountFlags::TYPE_1,
+        min_sample_shading: 1.0,
+        ..Default::default()
+    };
+
+    let blend_attachments = [vk::PipelineColorBlendAttachmentState {
+        color_write_mask: vk::ColorComponentFlags::R
+            | vk::ColorComponentFlags::G
+            | vk::ColorComponentFlags::B
+            | vk::ColorComponentFlags::A,
+        blend_enable: 0,
+        ..Default::default()
+    }];
+
+    let blend_state = vk::PipelineColorBlendStateCreateInfo {
+        logic_op_enable: 0,
+        logic_op: vk::LogicOp::COPY,
+        p_attachments: blend_attachments.as_ptr(),
+        attachment_count: blend_attachments.len() as u32,
+        ..Default::default()
+    };
+
+    let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+        p_stages: stages.as_ptr(),
+        stage_count: stages.len() as u32,
+        p_vertex_input_state: &vertex_input_state,
+        p_input_assembly_state: &input_assembly_state,
+        p_viewport_state: &viewport_state,
+        p_rasterization_state: &raster_state,
+        p_multisample_state: &multisample_state,
+        p_color_blend_state: &blend_state,
+        layout: pipeline_layout,
+        render_pass,
+        subpass: 0,
+        base_pipeline_index: -1,
+        base_pipeline_handle: vk::Pipeline::null(),
+        ..Default::default()
+    };
+
+    let pipelines = unsafe {
+        device
+            .logical_device
+            .create_graphics_pipelines(
+                vk::PipelineCache::null(),
+                &[pipeline_create_info],
+                None,
+            )
+            .map_err(|(_, err)| err)
+            .context("unable to create the color grading pipeline")?
+    };
+    device.name_vulkan_object(
+        "Color Grading Pipeline",
+        vk::ObjectType::PIPELINE,
+        &pipelines[0],
+    )?;
+    Ok(pipelines[0])
+}
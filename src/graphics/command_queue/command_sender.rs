@@ -0,0 +1,81 @@
+use super::{CommandSender, GraphicsCommand, LayerCommand};
+
+use crate::graphics::layer::{Batch, Layer, LayerHandle};
+
+use anyhow::{Context, Result};
+use nalgebra as na;
+
+impl CommandSender {
+    /// Queue a command. Fails if the graphics subsystem has been dropped.
+    pub fn send(&self, command: GraphicsCommand) -> Result<()> {
+        self.sender.send(command).ok().with_context(|| {
+            "the graphics subsystem is no longer accepting commands"
+        })
+    }
+
+    /// Queue a new layer above every existing layer.
+    ///
+    /// The handle can be used in other commands right away.
+    pub fn add_layer_to_top(&self) -> Result<LayerHandle> {
+        let handle = LayerHandle::generate();
+        self.send_layer(LayerCommand::AddToTop(handle))?;
+        Ok(handle)
+    }
+
+    /// Queue a new layer below every existing layer.
+    ///
+    /// The handle can be used in other commands right away.
+    pub fn add_layer_to_bottom(&self) -> Result<LayerHandle> {
+        let handle = LayerHandle::generate();
+        self.send_layer(LayerCommand::AddToBottom(handle))?;
+        Ok(handle)
+    }
+
+    /// Replace everything in a layer.
+    pub fn replace_layer(
+        &self,
+        handle: LayerHandle,
+        layer: Layer,
+    ) -> Result<()> {
+        self.send_layer(LayerCommand::Replace(handle, layer))
+    }
+
+    /// Add batches to the end of a layer.
+    pub fn push_batches(
+        &self,
+        handle: LayerHandle,
+        batches: Vec<Batch>,
+    ) -> Result<()> {
+        self.send_layer(LayerCommand::PushBatches(handle, batches))
+    }
+
+    /// Set a layer's projection.
+    pub fn set_projection(
+        &self,
+        handle: LayerHandle,
+        projection: na::Matrix4<f32>,
+    ) -> Result<()> {
+        self.send_layer(LayerCommand::SetProjection(handle, projection))
+    }
+
+    /// Remove every batch and hairline from a layer.
+    pub fn clear_layer(&self, handle: LayerHandle) -> Result<()> {
+        self.send_layer(LayerCommand::Clear(handle))
+    }
+
+    /// Queue a texture file to be loaded into the atlas with a name.
+    pub fn load_texture(
+        &self,
+        path: impl Into<String>,
+        name: impl Into<String>,
+    ) -> Result<()> {
+        self.send(GraphicsCommand::LoadTexture {
+            path: path.into(),
+            name: name.into(),
+        })
+    }
+
+    fn send_layer(&self, command: LayerCommand) -> Result<()> {
+        self.send(GraphicsCommand::Layer(command))
+    }
+}
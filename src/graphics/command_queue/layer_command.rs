@@ -0,0 +1,101 @@
+use super::LayerCommand;
+
+use crate::graphics::layer::{Layer, LayerHandle, LayerStack};
+
+use anyhow::{Context, Result};
+
+impl LayerCommand {
+    /// Apply the command to a layer stack.
+    ///
+    /// Fails if the command refers to a layer which doesn't exist.
+    pub fn apply(self, layer_stack: &mut LayerStack) -> Result<()> {
+        match self {
+            LayerCommand::AddToTop(handle) => {
+                layer_stack.insert_layer_on_top(handle)
+            }
+            LayerCommand::AddToBottom(handle) => {
+                layer_stack.insert_layer_on_bottom(handle)
+            }
+            LayerCommand::Replace(handle, layer) => {
+                *get_layer(layer_stack, handle)? = layer
+            }
+            LayerCommand::PushBatches(handle, batches) => {
+                get_layer(layer_stack, handle)?.push_batches(&batches)
+            }
+            LayerCommand::SetProjection(handle, projection) => {
+                get_layer(layer_stack, handle)?.set_projection(projection)
+            }
+            LayerCommand::Clear(handle) => {
+                get_layer(layer_stack, handle)?.clear()
+            }
+        }
+        Ok(())
+    }
+}
+
+fn get_layer(
+    layer_stack: &mut LayerStack,
+    handle: LayerHandle,
+) -> Result<&mut Layer> {
+    layer_stack
+        .get_layer_mut(&handle)
+        .with_context(|| format!("{:?} doesn't refer to a real layer", handle))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::graphics::{
+        command_queue::{CommandQueue, GraphicsCommand},
+        layer::{Batch, LayerHandle, LayerStack},
+    };
+
+    use anyhow::Result;
+    use std::thread;
+
+    fn apply_all(queue: &CommandQueue, stack: &mut LayerStack) -> Result<()> {
+        for command in queue.drain() {
+            match command {
+                GraphicsCommand::Layer(command) => command.apply(stack)?,
+                GraphicsCommand::LoadTexture { .. } => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn layers_built_on_another_thread_should_apply_in_order() -> Result<()> {
+        let queue = CommandQueue::new();
+        let sender = queue.sender();
+        let worker = thread::spawn(move || -> Result<_> {
+            let bottom = sender.add_layer_to_top()?;
+            let top = sender.add_layer_to_top()?;
+            sender.push_batches(bottom, vec![Batch::empty(); 2])?;
+            sender.push_batches(top, vec![Batch::empty()])?;
+            sender.clear_layer(top)?;
+            Ok((bottom, top))
+        });
+        let (bottom, top) = worker.join().unwrap()?;
+
+        let mut stack = LayerStack::new();
+        apply_all(&queue, &mut stack)?;
+
+        let layers = stack.layers_with_handles();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].0, bottom);
+        assert_eq!(layers[0].1.batches().len(), 2);
+        assert_eq!(layers[1].0, top);
+        assert!(layers[1].1.batches().is_empty());
+        assert!(queue.drain().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn commands_for_missing_layers_should_fail() {
+        let queue = CommandQueue::new();
+        let sender = queue.sender();
+        let handle = LayerHandle::generate();
+        sender.clear_layer(handle).unwrap();
+
+        assert!(apply_all(&queue, &mut LayerStack::new()).is_err());
+    }
+}
@@ -0,0 +1,68 @@
+//! Build layers and request textures from other threads.
+//!
+//! # Big Idea
+//!
+//! `Graphics` owns vulkan resources and must stay on the main thread, but
+//! building vertex data is plain cpu work which can happen anywhere. Worker
+//! threads send commands through a `CommandSender`, which can be cloned and
+//! moved to any thread, and `Graphics::render` applies every queued command
+//! in the order it was sent before drawing.
+//!
+//! Layer handles are generated when a worker asks for a new layer, so the
+//! worker can fill the layer right away without waiting for the main thread.
+
+mod command_sender;
+mod layer_command;
+mod queue;
+
+use crate::graphics::layer::{Batch, Layer, LayerHandle};
+
+use nalgebra as na;
+use std::sync::mpsc::{Receiver, Sender};
+
+/// A change to the graphics subsystem which was requested from another
+/// thread.
+#[derive(Debug)]
+pub enum GraphicsCommand {
+    /// Change the layer stack.
+    Layer(LayerCommand),
+
+    /// Read a texture file, add it to the texture atlas, and give it a name
+    /// so batches can find it with `GpuAtlas::texture_by_name`.
+    LoadTexture { path: String, name: String },
+}
+
+/// A change to the layer stack.
+#[derive(Debug)]
+pub enum LayerCommand {
+    /// Add an empty layer above every existing layer.
+    AddToTop(LayerHandle),
+
+    /// Add an empty layer below every existing layer.
+    AddToBottom(LayerHandle),
+
+    /// Replace everything in a layer.
+    Replace(LayerHandle, Layer),
+
+    /// Add batches to the end of a layer.
+    PushBatches(LayerHandle, Vec<Batch>),
+
+    /// Set a layer's projection.
+    SetProjection(LayerHandle, na::Matrix4<f32>),
+
+    /// Remove every batch and hairline from a layer.
+    Clear(LayerHandle),
+}
+
+/// Sends commands to the graphics subsystem from any thread.
+#[derive(Debug, Clone)]
+pub struct CommandSender {
+    sender: Sender<GraphicsCommand>,
+}
+
+/// Commands which have been sent but not yet applied.
+#[derive(Debug)]
+pub struct CommandQueue {
+    sender: Sender<GraphicsCommand>,
+    receiver: Receiver<GraphicsCommand>,
+}
@@ -0,0 +1,30 @@
+use super::{CommandQueue, CommandSender, GraphicsCommand};
+
+use std::sync::mpsc;
+
+impl CommandQueue {
+    /// Create an empty queue.
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+
+    /// Create a sender which adds commands to this queue.
+    pub fn sender(&self) -> CommandSender {
+        CommandSender {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Take every command which has been sent so far, in the order they were
+    /// sent. Never blocks.
+    pub fn drain(&self) -> Vec<GraphicsCommand> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+impl Default for CommandQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,214 @@
+use super::{CustomBatch, CustomPipelineHandle, VertexLayout};
+
+use crate::graphics::{
+    pipeline2d::MAX_USER_DATA_SIZE, texture_atlas::TextureHandle,
+};
+
+use anyhow::{bail, Result};
+
+impl CustomBatch {
+    /// Create a batch from a slice of vertex structs.
+    ///
+    /// Fails when the size of `T` doesn't match the layout's stride.
+    pub fn new<T: Copy>(
+        pipeline: CustomPipelineHandle,
+        layout: &VertexLayout,
+        texture_handle: TextureHandle,
+        vertices: &[T],
+    ) -> Result<Self> {
+        let size = std::mem::size_of::<T>();
+        if size != layout.stride() as usize {
+            bail!(
+                "vertices are {} bytes but the layout's stride is {}",
+                size,
+                layout.stride()
+            );
+        }
+        let bytes = unsafe {
+            // SAFE: T is Copy and the slice is read exactly once
+            std::slice::from_raw_parts(
+                vertices.as_ptr() as *const u8,
+                std::mem::size_of_val(vertices),
+            )
+        };
+        Self::from_bytes(pipeline, layout, texture_handle, bytes.to_vec())
+    }
+
+    /// Create a batch from vertices which are already packed as bytes.
+    ///
+    /// Fails when the bytes aren't a whole number of vertices.
+    pub fn from_bytes(
+        pipeline: CustomPipelineHandle,
+        layout: &VertexLayout,
+        texture_handle: TextureHandle,
+        bytes: Vec<u8>,
+    ) -> Result<Self> {
+        let stride = layout.stride();
+        let partial_vertex = stride == 0
+            || !bytes.chunks_exact(stride as usize).remainder().is_empty();
+        if partial_vertex {
+            bail!(
+                "{} bytes is not a whole number of {} byte vertices",
+                bytes.len(),
+                stride
+            );
+        }
+        Ok(Self {
+            pipeline,
+            texture_handle,
+            stride,
+            bytes,
+            user_data: vec![],
+        })
+    }
+
+    /// Push a value to the pipeline's shaders before the batch is drawn.
+    ///
+    /// Fails when the value is larger than `MAX_USER_DATA_SIZE` or isn't a
+    /// whole number of 4 byte words.
+    pub fn with_user_data<T: Copy>(mut self, data: &T) -> Result<Self> {
+        self.set_user_data(data)?;
+        Ok(self)
+    }
+
+    /// Replace the value pushed before the batch is drawn.
+    pub fn set_user_data<T: Copy>(&mut self, data: &T) -> Result<()> {
+        let size = std::mem::size_of::<T>() as u32;
+        check_user_data_size(size)?;
+        let bytes = unsafe {
+            // SAFE: T is Copy and the value is read exactly once
+            std::slice::from_raw_parts(
+                data as *const T as *const u8,
+                size as usize,
+            )
+        };
+        self.user_data = bytes.to_vec();
+        Ok(())
+    }
+
+    /// The bytes pushed before the batch is drawn.
+    pub fn user_data(&self) -> &[u8] {
+        &self.user_data
+    }
+
+    /// The number of vertices in the batch.
+    pub fn vertex_count(&self) -> u32 {
+        (self.bytes.len() / self.stride as usize) as u32
+    }
+
+    /// The number of bytes between consecutive vertices.
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    /// The batch's vertices as bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Fails unless `size` bytes of user data fit in the push constants.
+pub(super) fn check_user_data_size(size: u32) -> Result<()> {
+    if size > MAX_USER_DATA_SIZE {
+        bail!(
+            "{} bytes of user data is more than the limit of {}",
+            size,
+            MAX_USER_DATA_SIZE
+        );
+    }
+    if size & 3 != 0 {
+        bail!(
+            "user data must be a whole number of 4 byte words, not {}",
+            size
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ash::vk;
+
+    #[repr(C)]
+    #[derive(Copy, Clone)]
+    struct Particle {
+        pos: [f32; 2],
+        age: f32,
+    }
+
+    fn layout() -> VertexLayout {
+        VertexLayout::for_type::<Particle>()
+            .attribute(0, vk::Format::R32G32_SFLOAT, 0)
+            .attribute(1, vk::Format::R32_SFLOAT, 8)
+    }
+
+    #[test]
+    fn batches_should_store_vertices_as_bytes() {
+        let vertices = [Particle {
+            pos: [1.0, 2.0],
+            age: 3.0,
+        }; 3];
+        let batch = CustomBatch::new(
+            CustomPipelineHandle { index: 0 },
+            &layout(),
+            TextureHandle::default(),
+            &vertices,
+        )
+        .unwrap();
+        assert_eq!(batch.vertex_count(), 3);
+        assert_eq!(batch.bytes().len(), 36);
+        assert_eq!(&batch.bytes()[8..12], &3.0f32.to_ne_bytes());
+    }
+
+    #[test]
+    fn mismatched_vertex_sizes_should_be_rejected() {
+        let result = CustomBatch::new(
+            CustomPipelineHandle { index: 0 },
+            &layout(),
+            TextureHandle::default(),
+            &[[0.0f32; 2]],
+        );
+        assert!(result.is_err());
+
+        let result = CustomBatch::from_bytes(
+            CustomPipelineHandle { index: 0 },
+            &layout(),
+            TextureHandle::default(),
+            vec![0; 13],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn user_data_should_be_stored_as_bytes() {
+        let batch = CustomBatch::from_bytes(
+            CustomPipelineHandle { index: 0 },
+            &layout(),
+            TextureHandle::default(),
+            vec![],
+        )
+        .unwrap()
+        .with_user_data(&[1.0f32, 0.5, 0.0, 1.0])
+        .unwrap();
+        assert_eq!(batch.user_data().len(), 16);
+        assert_eq!(&batch.user_data()[4..8], &0.5f32.to_ne_bytes());
+    }
+
+    #[test]
+    fn oversized_or_unaligned_user_data_should_be_rejected() {
+        assert!(check_user_data_size(MAX_USER_DATA_SIZE).is_ok());
+        assert!(check_user_data_size(MAX_USER_DATA_SIZE + 4).is_err());
+        assert!(check_user_data_size(6).is_err());
+        let mut batch = CustomBatch::from_bytes(
+            CustomPipelineHandle { index: 0 },
+            &layout(),
+            TextureHandle::default(),
+            vec![],
+        )
+        .unwrap();
+        assert!(batch.set_user_data(&[0u8; 3]).is_err());
+        assert!(batch.user_data().is_empty());
+    }
+}
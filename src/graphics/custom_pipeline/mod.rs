@@ -0,0 +1,132 @@
+//! Pipelines with user provided shaders and vertex structs.
+//!
+//! A custom pipeline is registered with `Graphics::register_pipeline` along
+//! with a `VertexLayout` which describes the vertex struct its vertex shader
+//! reads. Vertices are stored in layers as type-erased bytes in a
+//! `CustomBatch`, which checks that the vertex struct's size matches the
+//! layout's stride when the batch is created.
+//!
+//! Custom shaders share the 2d pipeline's descriptor sets and push constants,
+//! so they can sample the texture atlas with the same interface as
+//! texture2d.frag:
+//!
+//! ```glsl
+//! layout(push_constant) uniform PushConsts {
+//!     mat4 projection;
+//!     uint textureIndex;
+//!     layout(offset = 72) uint samplerIndex;
+//! } pushConsts;
+//!
+//! layout(constant_id = 0) const uint MAX_TEXTURES = 1;
+//! layout(constant_id = 1) const uint MAX_SAMPLERS = 1;
+//! layout(binding = 0) uniform texture2D textures[MAX_TEXTURES];
+//! layout(binding = 2) uniform sampler samplers[MAX_SAMPLERS];
+//!
+//! vec4 sampleBatchTexture(vec2 uv) {
+//!     return texture(
+//!         sampler2D(
+//!             textures[pushConsts.textureIndex],
+//!             samplers[pushConsts.samplerIndex]),
+//!         uv);
+//! }
+//! ```
+//!
+//! Custom batches are drawn after a layer's regular batches and before its
+//! hairlines.
+//!
+//! The frame's uniforms are available too, see
+//! `graphics::frame::FrameUniforms`:
+//!
+//! ```glsl
+//! layout(set = 1, binding = 0) uniform FrameUniforms {
+//!     mat4 camera;
+//!     vec2 resolution;
+//!     float time;
+//!     float deltaTime;
+//! } frame;
+//! ```
+//!
+//! Pipelines registered with `Graphics::register_pipeline_with_user_data`
+//! also read a few bytes of per-batch parameters, like an outline color or
+//! effect strength, from the end of the push constants. Each batch sets its
+//! own with `CustomBatch::with_user_data`:
+//!
+//! ```glsl
+//! layout(push_constant) uniform PushConsts {
+//!     mat4 projection;
+//!     uint textureIndex;
+//!     layout(offset = 80) vec4 outlineColor;
+//! } pushConsts;
+//! ```
+//!
+//! Batches with less data than the pipeline reads are padded with zeros,
+//! batches with more are skipped.
+
+mod custom_batch;
+mod raw_pipeline;
+mod registered_pipeline;
+mod vertex_layout;
+
+use crate::graphics::{texture_atlas::TextureHandle, vulkan::Device};
+
+use ash::vk;
+use std::sync::Arc;
+
+/// A unique reference to a registered custom pipeline.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CustomPipelineHandle {
+    index: usize,
+}
+
+/// One attribute read by a custom vertex shader.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VertexAttribute {
+    /// The attribute's `layout(location = N)` in the vertex shader.
+    pub location: u32,
+
+    pub format: vk::Format,
+
+    /// The attribute's offset in bytes from the start of the vertex.
+    pub offset: u32,
+}
+
+/// Describes the memory layout of a custom vertex struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VertexLayout {
+    stride: u32,
+    attributes: Vec<VertexAttribute>,
+}
+
+/// Vertices for a custom pipeline, stored as bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomBatch {
+    pub pipeline: CustomPipelineHandle,
+    pub texture_handle: TextureHandle,
+    stride: u32,
+    bytes: Vec<u8>,
+
+    /// Pushed at `USER_DATA_OFFSET` before the batch is drawn.
+    user_data: Vec<u8>,
+}
+
+/// A registered pipeline, which keeps its shaders so it can be rebuilt
+/// when the swapchain changes.
+pub struct CustomPipeline {
+    name: String,
+    vertex_spirv: Vec<u32>,
+    fragment_spirv: Vec<u32>,
+    vertex_layout: VertexLayout,
+
+    /// The number of bytes of user data the shaders read.
+    user_data_size: u32,
+
+    raw: RawCustomPipeline,
+}
+
+/// The vulkan objects for a custom pipeline.
+struct RawCustomPipeline {
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    descriptor_set_layouts: [vk::DescriptorSetLayout; 2],
+    device: Arc<Device>,
+}
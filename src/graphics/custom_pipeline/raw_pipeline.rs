@@ -0,0 +1,265 @@
+use super::{RawCustomPipeline, VertexLayout};
+
+use crate::graphics::{
+    pipeline2d::descriptor_sets,
+    vulkan::{ffi, shader_module::ShaderModule, Device, Swapchain},
+};
+
+use anyhow::{Context, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::{
+    ffi::{c_void, CString},
+    sync::Arc,
+};
+
+impl RawCustomPipeline {
+    /// Create a pipeline with the provided shaders and vertex layout.
+    ///
+    /// The pipeline layout matches the 2d pipeline's, so descriptor sets
+    /// bound for the 2d pipeline stay bound.
+    pub fn new(
+        device: Arc<Device>,
+        swapchain: &Swapchain,
+        name: &str,
+        vertex_spirv: &[u32],
+        fragment_spirv: &[u32],
+        vertex_layout: &VertexLayout,
+    ) -> Result<Self> {
+        let vertex_module = ShaderModule::from_words(
+            &device,
+            format!("{} Vertex Shader", name),
+            vertex_spirv,
+        )?;
+        let fragment_module = ShaderModule::from_words(
+            &device,
+            format!("{} Fragment Shader", name),
+            fragment_spirv,
+        )?;
+
+        let entry = CString::new("main").unwrap();
+        let specialization_map_entries =
+            descriptor_sets::atlas_specialization_entries();
+        let array_sizes = descriptor_sets::atlas_array_sizes(&device);
+        let specialization_data = unsafe { ffi::any_as_u8_slice(&array_sizes) };
+        let fragment_specialization_info = vk::SpecializationInfo {
+            p_map_entries: specialization_map_entries.as_ptr(),
+            map_entry_count: specialization_map_entries.len() as u32,
+            p_data: specialization_data.as_ptr() as *const c_void,
+            data_size: specialization_data.len(),
+        };
+        let stages = [
+            vk::PipelineShaderStageCreateInfo {
+                stage: vk::ShaderStageFlags::VERTEX,
+                module: vertex_module.shader_module,
+                p_name: entry.as_ptr(),
+                ..Default::default()
+            },
+            vk::PipelineShaderStageCreateInfo {
+                stage: vk::ShaderStageFlags::FRAGMENT,
+                module: fragment_module.shader_module,
+                p_specialization_info: &fragment_specialization_info,
+                p_name: entry.as_ptr(),
+                ..Default::default()
+            },
+        ];
+
+        let (binding_descriptions, attribute_descriptions) =
+            vertex_layout.binding_description();
+        let vertex_input_state = vk::PipelineVertexInputStateCreateInfo {
+            p_vertex_binding_descriptions: binding_descriptions.as_ptr(),
+            vertex_binding_description_count: binding_descriptions.len() as u32,
+            p_vertex_attribute_descriptions: attribute_descriptions.as_ptr(),
+            vertex_attribute_description_count: attribute_descriptions.len()
+                as u32,
+            ..Default::default()
+        };
+
+        let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo {
+            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            primitive_restart_enable: 0,
+            ..Default::default()
+        };
+
+        let viewports = [vk::Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: swapchain.extent.width as f32,
+            height: swapchain.extent.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }];
+
+        let scissors = [vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent: swapchain.extent,
+        }];
+
+        let viewport_state = vk::PipelineViewportStateCreateInfo {
+            p_viewports: viewports.as_ptr(),
+            viewport_count: 1,
+            p_scissors: scissors.as_ptr(),
+            scissor_count: 1,
+            ..Default::default()
+        };
+
+        let raster_state = vk::PipelineRasterizationStateCreateInfo {
+            polygon_mode: vk::PolygonMode::FILL,
+            line_width: 1.0,
+            cull_mode: vk::CullModeFlags::NONE,
+            front_face: vk::FrontFace::CLOCKWISE,
+            ..Default::default()
+        };
+
+        let multisample_state = vk::PipelineMultisampleStateCreateInfo {
+            rasterization_samples: vk::SampleCountFlags::TYPE_1,
+            min_sample_shading: 1.0,
+            ..Default::default()
+        };
+
+        let blend_attachments = [vk::PipelineColorBlendAttachmentState {
+            color_write_mask: vk::ColorComponentFlags::R
+                | vk::ColorComponentFlags::G
+                | vk::ColorComponentFlags::B
+                | vk::ColorComponentFlags::A,
+            blend_enable: 1,
+            src_color_blend_factor: vk::BlendFactor::SRC_ALPHA,
+            dst_color_blend_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+            color_blend_op: vk::BlendOp::ADD,
+            src_alpha_blend_factor: vk::BlendFactor::ONE,
+            dst_alpha_blend_factor: vk::BlendFactor::ZERO,
+            alpha_blend_op: vk::BlendOp::ADD,
+        }];
+
+        let blend_state = vk::PipelineColorBlendStateCreateInfo {
+            logic_op_enable: 0,
+            logic_op: vk::LogicOp::COPY,
+            p_attachments: blend_attachments.as_ptr(),
+            attachment_count: blend_attachments.len() as u32,
+            ..Default::default()
+        };
+
+        let descriptor_set_layouts = unsafe {
+            descriptor_sets::create_descriptor_set_layouts(&device, name)?
+        };
+        let push_constant_ranges =
+            [descriptor_sets::create_push_constant_range()];
+        let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+            p_set_layouts: descriptor_set_layouts.as_ptr(),
+            set_layout_count: descriptor_set_layouts.len() as u32,
+            p_push_constant_ranges: push_constant_ranges.as_ptr(),
+            push_constant_range_count: push_constant_ranges.len() as u32,
+            ..Default::default()
+        };
+
+        let pipeline_layout = unsafe {
+            let result = device
+                .logical_device
+                .create_pipeline_layout(&pipeline_layout_create_info, None);
+            match result {
+                Ok(pipeline_layout) => pipeline_layout,
+                Err(error) => {
+                    descriptor_sets::destroy_descriptor_set_layouts(
+                        &device,
+                        &descriptor_set_layouts,
+                    );
+                    return Err(error.into());
+                }
+            }
+        };
+        device.name_vulkan_object(
+            format!("{} Pipeline Layout", name),
+            vk::ObjectType::PIPELINE_LAYOUT,
+            &pipeline_layout,
+        )?;
+
+        // the layer pass may render above the swapchain's resolution, see
+        // `Graphics::set_render_scale`
+        let dynamic_states =
+            [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+        let dynamic_state = vk::PipelineDynamicStateCreateInfo {
+            p_dynamic_states: dynamic_states.as_ptr(),
+            dynamic_state_count: dynamic_states.len() as u32,
+            ..Default::default()
+        };
+
+        let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+            p_stages: stages.as_ptr(),
+            stage_count: stages.len() as u32,
+            p_vertex_input_state: &vertex_input_state,
+            p_input_assembly_state: &input_assembly_state,
+            p_viewport_state: &viewport_state,
+            p_rasterization_state: &raster_state,
+            p_multisample_state: &multisample_state,
+            p_color_blend_state: &blend_state,
+            p_dynamic_state: &dynamic_state,
+            layout: pipeline_layout,
+            render_pass: swapchain.render_pass,
+            subpass: 0,
+            base_pipeline_index: -1,
+            base_pipeline_handle: vk::Pipeline::null(),
+            ..Default::default()
+        };
+
+        let result = unsafe {
+            device
+                .logical_device
+                .create_graphics_pipelines(
+                    vk::PipelineCache::null(),
+                    &[pipeline_create_info],
+                    None,
+                )
+                .map_err(|(_, err)| err)
+                .with_context(|| format!("unable to create {}", name))
+        };
+        let pipeline = match result {
+            Ok(pipelines) => pipelines[0],
+            Err(error) => {
+                unsafe {
+                    device.forget_vulkan_object(
+                        vk::ObjectType::PIPELINE_LAYOUT,
+                        &pipeline_layout,
+                    );
+                    device
+                        .logical_device
+                        .destroy_pipeline_layout(pipeline_layout, None);
+                    descriptor_sets::destroy_descriptor_set_layouts(
+                        &device,
+                        &descriptor_set_layouts,
+                    );
+                }
+                return Err(error);
+            }
+        };
+        device.name_vulkan_object(name, vk::ObjectType::PIPELINE, &pipeline)?;
+
+        Ok(Self {
+            pipeline_layout,
+            pipeline,
+            descriptor_set_layouts,
+            device,
+        })
+    }
+}
+
+impl Drop for RawCustomPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .forget_vulkan_object(vk::ObjectType::PIPELINE, &self.pipeline);
+            self.device
+                .logical_device
+                .destroy_pipeline(self.pipeline, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::PIPELINE_LAYOUT,
+                &self.pipeline_layout,
+            );
+            self.device
+                .logical_device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+            descriptor_sets::destroy_descriptor_set_layouts(
+                &self.device,
+                &self.descriptor_set_layouts,
+            );
+        }
+    }
+}
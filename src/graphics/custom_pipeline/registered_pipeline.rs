@@ -0,0 +1,99 @@
+use super::{
+    custom_batch::check_user_data_size, CustomPipeline, CustomPipelineHandle,
+    RawCustomPipeline, VertexLayout,
+};
+
+use crate::graphics::vulkan::{Device, Swapchain};
+
+use anyhow::{Context, Result};
+use ash::vk;
+use std::sync::Arc;
+
+impl CustomPipelineHandle {
+    pub(crate) fn new(index: usize) -> Self {
+        Self { index }
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl CustomPipeline {
+    /// Create a pipeline from SPIR-V shaders which read vertices with the
+    /// provided layout, and `user_data_size` bytes of per-batch user data.
+    pub fn new(
+        device: Arc<Device>,
+        swapchain: &Swapchain,
+        name: impl Into<String>,
+        vertex_spirv: &[u32],
+        fragment_spirv: &[u32],
+        vertex_layout: VertexLayout,
+        user_data_size: u32,
+    ) -> Result<Self> {
+        let name = name.into();
+        vertex_layout
+            .validate()
+            .with_context(|| format!("invalid vertex layout for {}", name))?;
+        check_user_data_size(user_data_size)
+            .with_context(|| format!("invalid user data for {}", name))?;
+        let raw = RawCustomPipeline::new(
+            device,
+            swapchain,
+            &name,
+            vertex_spirv,
+            fragment_spirv,
+            &vertex_layout,
+        )?;
+        Ok(Self {
+            name,
+            vertex_spirv: vertex_spirv.to_vec(),
+            fragment_spirv: fragment_spirv.to_vec(),
+            vertex_layout,
+            user_data_size,
+            raw,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The layout of the vertices this pipeline reads.
+    pub fn vertex_layout(&self) -> &VertexLayout {
+        &self.vertex_layout
+    }
+
+    /// The number of bytes of user data the pipeline's shaders read.
+    pub fn user_data_size(&self) -> u32 {
+        self.user_data_size
+    }
+
+    /// Borrow the raw vulkan pipeline handle.
+    pub fn raw_pipeline(&self) -> vk::Pipeline {
+        self.raw.pipeline
+    }
+
+    /// Borrow the pipeline layout handle.
+    pub fn raw_pipeline_layout(&self) -> vk::PipelineLayout {
+        self.raw.pipeline_layout
+    }
+
+    /// Rebuild the pipeline for a new swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - the previous pipeline is destroyed, the caller must make sure no
+    ///   frame is still using it
+    pub unsafe fn rebuild(&mut self, swapchain: &Swapchain) -> Result<()> {
+        self.raw = RawCustomPipeline::new(
+            self.raw.device.clone(),
+            swapchain,
+            &self.name,
+            &self.vertex_spirv,
+            &self.fragment_spirv,
+            &self.vertex_layout,
+        )?;
+        Ok(())
+    }
+}
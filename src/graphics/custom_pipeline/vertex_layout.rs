@@ -0,0 +1,188 @@
+use super::{VertexAttribute, VertexLayout};
+
+use anyhow::{bail, Result};
+use ash::vk;
+
+impl VertexLayout {
+    /// Create a layout for vertices which are `stride` bytes apart.
+    pub fn new(stride: u32) -> Self {
+        Self {
+            stride,
+            attributes: vec![],
+        }
+    }
+
+    /// Create a layout whose stride is the size of the vertex struct.
+    pub fn for_type<T>() -> Self {
+        Self::new(std::mem::size_of::<T>() as u32)
+    }
+
+    /// Add an attribute to the layout.
+    ///
+    /// Use `memoffset::offset_of!` to find the offset of a field in a
+    /// `#[repr(C)]` struct.
+    pub fn attribute(
+        mut self,
+        location: u32,
+        format: vk::Format,
+        offset: u32,
+    ) -> Self {
+        self.attributes.push(VertexAttribute {
+            location,
+            format,
+            offset,
+        });
+        self
+    }
+
+    /// The number of bytes between consecutive vertices.
+    pub fn stride(&self) -> u32 {
+        self.stride
+    }
+
+    pub fn attributes(&self) -> &[VertexAttribute] {
+        &self.attributes
+    }
+
+    /// Check that the layout can be used by a pipeline.
+    ///
+    /// Every attribute must use a supported format, fit inside the stride,
+    /// and have a unique location.
+    pub fn validate(&self) -> Result<()> {
+        if self.stride == 0 {
+            bail!("vertex layouts must have a non-zero stride");
+        }
+        if self.attributes.is_empty() {
+            bail!("vertex layouts must have at least one attribute");
+        }
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            let size = match format_size(attribute.format) {
+                Some(size) => size,
+                None => bail!(
+                    "unsupported vertex attribute format {:?}",
+                    attribute.format
+                ),
+            };
+            if attribute.offset + size > self.stride {
+                bail!(
+                    "vertex attribute at location {} ends at byte {} which \
+                     is past the stride of {}",
+                    attribute.location,
+                    attribute.offset + size,
+                    self.stride
+                );
+            }
+            let duplicate = self.attributes[..i]
+                .iter()
+                .any(|other| other.location == attribute.location);
+            if duplicate {
+                bail!(
+                    "more than one vertex attribute uses location {}",
+                    attribute.location
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the binding description for this layout.
+    pub fn binding_description(
+        &self,
+    ) -> (
+        Vec<vk::VertexInputBindingDescription>,
+        Vec<vk::VertexInputAttributeDescription>,
+    ) {
+        let binding = vk::VertexInputBindingDescription {
+            binding: 0,
+            stride: self.stride,
+            input_rate: vk::VertexInputRate::VERTEX,
+        };
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|attribute| vk::VertexInputAttributeDescription {
+                binding: 0,
+                location: attribute.location,
+                format: attribute.format,
+                offset: attribute.offset,
+            })
+            .collect();
+        (vec![binding], attributes)
+    }
+}
+
+/// The size in bytes of the vertex attribute formats which can be used in
+/// a layout.
+fn format_size(format: vk::Format) -> Option<u32> {
+    let size = match format {
+        vk::Format::R32_SFLOAT
+        | vk::Format::R32_UINT
+        | vk::Format::R32_SINT => 4,
+        vk::Format::R32G32_SFLOAT
+        | vk::Format::R32G32_UINT
+        | vk::Format::R32G32_SINT => 8,
+        vk::Format::R32G32B32_SFLOAT
+        | vk::Format::R32G32B32_UINT
+        | vk::Format::R32G32B32_SINT => 12,
+        vk::Format::R32G32B32A32_SFLOAT
+        | vk::Format::R32G32B32A32_UINT
+        | vk::Format::R32G32B32A32_SINT => 16,
+        vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_UINT => 4,
+        _ => return None,
+    };
+    Some(size)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[repr(C)]
+    struct Particle {
+        pos: [f32; 2],
+        age: f32,
+        rotation: f32,
+    }
+
+    fn particle_layout() -> VertexLayout {
+        VertexLayout::for_type::<Particle>()
+            .attribute(0, vk::Format::R32G32_SFLOAT, 0)
+            .attribute(1, vk::Format::R32_SFLOAT, 8)
+            .attribute(2, vk::Format::R32_SFLOAT, 12)
+    }
+
+    #[test]
+    fn a_layout_matching_its_struct_should_be_valid() {
+        let layout = particle_layout();
+        assert_eq!(layout.stride(), 16);
+        assert!(layout.validate().is_ok());
+
+        let (bindings, attributes) = layout.binding_description();
+        assert_eq!(bindings[0].stride, 16);
+        assert_eq!(attributes.len(), 3);
+        assert_eq!(attributes[2].offset, 12);
+    }
+
+    #[test]
+    fn attributes_past_the_stride_should_be_invalid() {
+        let layout =
+            particle_layout().attribute(3, vk::Format::R32G32_SFLOAT, 12);
+        assert!(layout.validate().is_err());
+    }
+
+    #[test]
+    fn duplicate_locations_should_be_invalid() {
+        let layout = VertexLayout::new(16)
+            .attribute(0, vk::Format::R32G32_SFLOAT, 0)
+            .attribute(0, vk::Format::R32G32_SFLOAT, 8);
+        assert!(layout.validate().is_err());
+    }
+
+    #[test]
+    fn unsupported_formats_should_be_invalid() {
+        let layout =
+            VertexLayout::new(16).attribute(0, vk::Format::D32_SFLOAT, 0);
+        assert!(layout.validate().is_err());
+        assert!(VertexLayout::new(0).validate().is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use super::{DamageRect, DamageTracker};
+
+use ash::vk;
+
+impl DamageRect {
+    /// True when the rect covers no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// The smallest rect which contains both rects.
+    pub fn union(&self, other: &DamageRect) -> DamageRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        DamageRect {
+            x,
+            y,
+            width: self.right().max(other.right()) - x,
+            height: self.bottom().max(other.bottom()) - y,
+        }
+    }
+
+    /// True when the rects overlap or share an edge.
+    pub fn touches(&self, other: &DamageRect) -> bool {
+        self.x <= other.right()
+            && other.x <= self.right()
+            && self.y <= other.bottom()
+            && other.y <= self.bottom()
+    }
+
+    /// The rect as a vulkan scissor or copy region.
+    pub fn as_rect2d(&self) -> vk::Rect2D {
+        vk::Rect2D {
+            offset: vk::Offset2D {
+                x: self.x as i32,
+                y: self.y as i32,
+            },
+            extent: vk::Extent2D {
+                width: self.width,
+                height: self.height,
+            },
+        }
+    }
+
+    fn right(&self) -> u32 {
+        self.x + self.width
+    }
+
+    fn bottom(&self) -> u32 {
+        self.y + self.height
+    }
+}
+
+impl DamageTracker {
+    /// The default limit on disjoint regions before they are collapsed.
+    pub const DEFAULT_MAX_RECTS: usize = 16;
+
+    /// Create a tracker for a surface with no damage.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            rects: vec![],
+            max_rects: Self::DEFAULT_MAX_RECTS,
+        }
+    }
+
+    /// Set the number of disjoint regions tracked before they are collapsed
+    /// into their bounding rect.
+    pub fn with_max_rects(self, max_rects: usize) -> Self {
+        Self {
+            max_rects: max_rects.max(1),
+            ..self
+        }
+    }
+
+    /// Mark a region as damaged. The region is clipped to the surface.
+    pub fn add(&mut self, rect: DamageRect) {
+        let mut damage = match self.clip(rect) {
+            Some(damage) => damage,
+            None => return,
+        };
+
+        // absorb every region the new damage touches, repeating because the
+        // grown rect can reach regions it didn't touch before
+        loop {
+            let before = self.rects.len();
+            self.rects.retain(|existing| {
+                if existing.touches(&damage) {
+                    damage = damage.union(existing);
+                    false
+                } else {
+                    true
+                }
+            });
+            if self.rects.len() == before {
+                break;
+            }
+        }
+        self.rects.push(damage);
+
+        if self.rects.len() > self.max_rects {
+            let bounds = self.bounds().unwrap();
+            self.rects = vec![bounds];
+        }
+    }
+
+    /// Mark the entire surface as damaged.
+    pub fn add_all(&mut self) {
+        self.rects = vec![DamageRect {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        }];
+    }
+
+    /// Resize the surface. All of the resized surface is considered damaged.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.add_all();
+    }
+
+    /// True when nothing has changed since the damage was last taken.
+    pub fn is_clean(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// The disjoint damaged regions.
+    pub fn rects(&self) -> &[DamageRect] {
+        &self.rects
+    }
+
+    /// The smallest rect containing all damage.
+    pub fn bounds(&self) -> Option<DamageRect> {
+        let mut rects = self.rects.iter();
+        let first = *rects.next()?;
+        Some(rects.fold(first, |bounds, rect| bounds.union(rect)))
+    }
+
+    /// Take the damaged regions, leaving the tracker clean.
+    pub fn take(&mut self) -> Vec<DamageRect> {
+        std::mem::take(&mut self.rects)
+    }
+
+    fn clip(&self, rect: DamageRect) -> Option<DamageRect> {
+        let x = rect.x.min(self.width);
+        let y = rect.y.min(self.height);
+        let clipped = DamageRect {
+            x,
+            y,
+            width: rect.x.saturating_add(rect.width).min(self.width) - x,
+            height: rect.y.saturating_add(rect.height).min(self.height) - y,
+        };
+        if clipped.is_empty() {
+            None
+        } else {
+            Some(clipped)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> DamageRect {
+        DamageRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn add_should_keep_separate_regions_disjoint() {
+        let mut tracker = DamageTracker::new(100, 100);
+        tracker.add(rect(0, 0, 10, 10));
+        tracker.add(rect(50, 50, 10, 10));
+
+        assert_eq!(
+            tracker.rects(),
+            &[rect(0, 0, 10, 10), rect(50, 50, 10, 10)]
+        );
+        assert_eq!(tracker.bounds(), Some(rect(0, 0, 60, 60)));
+    }
+
+    #[test]
+    fn add_should_merge_touching_regions() {
+        let mut tracker = DamageTracker::new(100, 100);
+        tracker.add(rect(0, 0, 10, 10));
+        tracker.add(rect(30, 0, 10, 10));
+        // bridges both existing regions
+        tracker.add(rect(5, 5, 30, 2));
+
+        assert_eq!(tracker.rects(), &[rect(0, 0, 40, 10)]);
+    }
+
+    #[test]
+    fn add_should_clip_to_the_surface() {
+        let mut tracker = DamageTracker::new(20, 20);
+        tracker.add(rect(15, 15, 10, 10));
+        tracker.add(rect(40, 40, 5, 5));
+
+        assert_eq!(tracker.rects(), &[rect(15, 15, 5, 5)]);
+    }
+
+    #[test]
+    fn add_should_collapse_when_there_are_too_many_regions() {
+        let mut tracker = DamageTracker::new(100, 100).with_max_rects(2);
+        tracker.add(rect(0, 0, 1, 1));
+        tracker.add(rect(10, 10, 1, 1));
+        tracker.add(rect(20, 20, 1, 1));
+
+        assert_eq!(tracker.take(), vec![rect(0, 0, 21, 21)]);
+        assert!(tracker.is_clean());
+    }
+}
@@ -0,0 +1,32 @@
+//! Track which regions of a persistent drawing surface have changed.
+//!
+//! A surface which is mostly static only needs to redraw, and read back, the
+//! pixels touched since the last update. The tracker merges overlapping
+//! damage so callers get a small set of disjoint rectangles which can be used
+//! as scissor rects for drawing and as regions for `ReadbackBuffer` copies.
+
+mod damage_tracker;
+
+/// A rectangle of pixels, with the origin at the top-left of the surface.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DamageRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Accumulates damaged regions of a surface between updates.
+#[derive(Debug, Clone)]
+pub struct DamageTracker {
+    /// The size of the surface in pixels.
+    width: u32,
+    height: u32,
+
+    /// Disjoint damaged regions.
+    rects: Vec<DamageRect>,
+
+    /// Once more than this many disjoint regions are tracked, they are
+    /// collapsed into their bounding rect.
+    max_rects: usize,
+}
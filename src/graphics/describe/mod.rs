@@ -0,0 +1,67 @@
+//! Runtime introspection for the resources owned by the graphics subsystem.
+//!
+//! `Graphics::describe` accepts any resource handle and returns a
+//! `ResourceInfo` with whatever metadata is known about the resource. This is
+//! meant for debugging overlays and editor inspectors, so nothing here is
+//! needed to render a frame.
+
+mod resource_handle;
+mod resource_usage;
+
+use crate::graphics::{
+    layer::LayerHandle,
+    texture_atlas::{SamplerHandle, TextureHandle},
+};
+
+use std::{collections::HashMap, path::PathBuf};
+
+/// Any handle which can be passed to `Graphics::describe`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceHandle {
+    Texture(TextureHandle),
+    Sampler(SamplerHandle),
+    Layer(LayerHandle),
+
+    /// A batch is identified by its layer and its index within the layer.
+    /// Clearing or reordering a layer's batches changes which batch an index
+    /// refers to.
+    Batch {
+        layer: LayerHandle,
+        index: usize,
+    },
+}
+
+/// Metadata about a single resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceInfo {
+    /// The handle used to find this resource.
+    pub handle: ResourceHandle,
+
+    /// The resource's logical name for textures, or its debug name for
+    /// samplers.
+    pub name: Option<String>,
+
+    /// The width and height, in pixels, of a texture's first mipmap level.
+    pub size: Option<(u32, u32)>,
+
+    /// The number of vertices held by a layer or batch.
+    pub vertex_count: Option<usize>,
+
+    /// Bytes owned by this resource. Textures count their device memory and
+    /// layers and batches count their cpu-side vertex data. Level-of-detail
+    /// variants share their source's image so they own no memory.
+    pub memory_bytes: u64,
+
+    /// The most recent frame which drew with this resource, or None if the
+    /// resource has never been drawn.
+    pub last_used_frame: Option<u64>,
+
+    /// The file a texture was read from.
+    pub source_path: Option<PathBuf>,
+}
+
+/// Tracks the most recent frame which used each resource.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceUsage {
+    last_used: HashMap<ResourceHandle, u64>,
+}
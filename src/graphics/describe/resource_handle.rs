@@ -0,0 +1,44 @@
+use super::ResourceHandle;
+
+use crate::graphics::{
+    layer::LayerHandle,
+    picking::{PickResult, PickedBatch},
+    texture_atlas::{SamplerHandle, TextureHandle},
+};
+
+impl From<TextureHandle> for ResourceHandle {
+    fn from(handle: TextureHandle) -> Self {
+        ResourceHandle::Texture(handle)
+    }
+}
+
+impl From<SamplerHandle> for ResourceHandle {
+    fn from(handle: SamplerHandle) -> Self {
+        ResourceHandle::Sampler(handle)
+    }
+}
+
+impl From<LayerHandle> for ResourceHandle {
+    fn from(handle: LayerHandle) -> Self {
+        ResourceHandle::Layer(handle)
+    }
+}
+
+impl From<PickedBatch> for ResourceHandle {
+    fn from(picked: PickedBatch) -> Self {
+        ResourceHandle::Batch {
+            layer: picked.layer,
+            index: picked.batch,
+        }
+    }
+}
+
+impl From<PickResult> for ResourceHandle {
+    /// Describe the batch containing the picked sprite.
+    fn from(picked: PickResult) -> Self {
+        ResourceHandle::Batch {
+            layer: picked.layer,
+            index: picked.batch,
+        }
+    }
+}
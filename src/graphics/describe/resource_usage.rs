@@ -0,0 +1,75 @@
+use super::{ResourceHandle, ResourceUsage};
+
+use crate::graphics::{
+    layer::LayerHandle,
+    texture_atlas::{SamplerHandle, TextureHandle},
+};
+
+impl ResourceUsage {
+    /// Create an empty usage tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a resource was used by a frame.
+    pub fn mark(&mut self, handle: ResourceHandle, frame_number: u64) {
+        self.last_used.insert(handle, frame_number);
+    }
+
+    /// The most recent frame which used the resource.
+    pub fn last_used(&self, handle: &ResourceHandle) -> Option<u64> {
+        self.last_used.get(handle).copied()
+    }
+
+    /// Record that a batch was drawn along with the layer, texture, and
+    /// sampler it was drawn with.
+    pub fn mark_batch(
+        &mut self,
+        layer: LayerHandle,
+        index: usize,
+        texture: TextureHandle,
+        sampler: Option<SamplerHandle>,
+        frame_number: u64,
+    ) {
+        self.mark(layer.into(), frame_number);
+        self.mark(ResourceHandle::Batch { layer, index }, frame_number);
+        self.mark(texture.into(), frame_number);
+        if let Some(sampler) = sampler {
+            self.mark(sampler.into(), frame_number);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::picking::PickedBatch;
+
+    #[test]
+    fn last_used_should_be_the_most_recent_mark() {
+        let mut usage = ResourceUsage::new();
+        let texture = ResourceHandle::from(TextureHandle::default());
+        assert_eq!(usage.last_used(&texture), None);
+
+        usage.mark(texture, 3);
+        usage.mark(texture, 7);
+
+        assert_eq!(usage.last_used(&texture), Some(7));
+    }
+
+    #[test]
+    fn batches_should_be_tracked_per_layer() {
+        let mut usage = ResourceUsage::new();
+        let first = LayerHandle::generate();
+        let second = LayerHandle::generate();
+        let batch =
+            |layer| ResourceHandle::from(PickedBatch { layer, batch: 0 });
+
+        usage.mark(batch(first), 4);
+
+        assert_eq!(usage.last_used(&batch(first)), Some(4));
+        assert_eq!(usage.last_used(&batch(second)), None);
+        assert_eq!(usage.last_used(&first.into()), None);
+    }
+}
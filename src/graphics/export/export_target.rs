@@ -0,0 +1,225 @@
+use super::{ExportTarget, ExportTile};
+
+use crate::graphics::vulkan::{texture::TextureImage, Device};
+
+use anyhow::Result;
+use ash::{version::DeviceV1_0, vk};
+use nalgebra as na;
+use std::sync::Arc;
+
+impl ExportTarget {
+    /// Create a target large enough for every tile.
+    pub fn new(
+        device: Arc<Device>,
+        format: vk::Format,
+        extent: vk::Extent2D,
+    ) -> Result<Self> {
+        let render_pass = create_render_pass(&device, format)?;
+        let image = TextureImage::new(
+            device.clone(),
+            vk::ImageCreateInfo {
+                image_type: vk::ImageType::TYPE_2D,
+                extent: vk::Extent3D {
+                    width: extent.width,
+                    height: extent.height,
+                    depth: 1,
+                },
+                mip_levels: 1,
+                array_layers: 1,
+                format,
+                tiling: vk::ImageTiling::OPTIMAL,
+                initial_layout: vk::ImageLayout::UNDEFINED,
+                usage: vk::ImageUsageFlags::COLOR_ATTACHMENT
+                    | vk::ImageUsageFlags::TRANSFER_SRC,
+                samples: vk::SampleCountFlags::TYPE_1,
+                sharing_mode: vk::SharingMode::EXCLUSIVE,
+                ..Default::default()
+            },
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+        let (raw_image, view) =
+            unsafe { (image.raw_image(), image.raw_view()) };
+        device.name_vulkan_object(
+            "Export Target - Image",
+            vk::ObjectType::IMAGE,
+            &raw_image,
+        )?;
+
+        let attachments = [view];
+        let framebuffer = unsafe {
+            device.logical_device.create_framebuffer(
+                &vk::FramebufferCreateInfo {
+                    render_pass,
+                    p_attachments: attachments.as_ptr(),
+                    attachment_count: attachments.len() as u32,
+                    width: extent.width,
+                    height: extent.height,
+                    layers: 1,
+                    ..Default::default()
+                },
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            "Export Target - Framebuffer",
+            vk::ObjectType::FRAMEBUFFER,
+            &framebuffer,
+        )?;
+
+        let tile = ExportTile {
+            x: 0,
+            y: 0,
+            width: extent.width,
+            height: extent.height,
+        };
+        Ok(Self {
+            extent,
+            image,
+            render_pass,
+            framebuffer,
+            tile,
+            crop: na::Matrix4::identity(),
+            device,
+        })
+    }
+
+    /// Draw the next frame into part of an image.
+    ///
+    /// The tile is drawn into the target's top left corner.
+    pub fn set_tile(
+        &mut self,
+        tile: ExportTile,
+        image_width: u32,
+        image_height: u32,
+    ) {
+        debug_assert!(
+            tile.width <= self.extent.width
+                && tile.height <= self.extent.height
+        );
+        self.tile = tile;
+        self.crop = tile.crop(image_width, image_height);
+    }
+
+    /// The tile being drawn.
+    pub fn tile(&self) -> ExportTile {
+        self.tile
+    }
+
+    /// The transform applied to every projection while drawing the tile.
+    pub fn crop(&self) -> &na::Matrix4<f32> {
+        &self.crop
+    }
+
+    /// The render pass to begin instead of the swapchain's. It leaves the
+    /// target ready to be copied.
+    pub fn render_pass(&self) -> vk::RenderPass {
+        self.render_pass
+    }
+
+    pub fn framebuffer(&self) -> vk::Framebuffer {
+        self.framebuffer
+    }
+
+    /// The image tiles are drawn into.
+    pub fn image(&self) -> vk::Image {
+        // SAFE: the handle is only used to record commands while the target
+        // owns the image
+        unsafe { self.image.raw_image() }
+    }
+}
+
+impl Drop for ExportTarget {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.forget_vulkan_object(
+                vk::ObjectType::FRAMEBUFFER,
+                &self.framebuffer,
+            );
+            self.device
+                .logical_device
+                .destroy_framebuffer(self.framebuffer, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::RENDER_PASS,
+                &self.render_pass,
+            );
+            self.device
+                .logical_device
+                .destroy_render_pass(self.render_pass, None);
+        }
+    }
+}
+
+/// Create a render pass which is compatible with the swapchain's and leaves
+/// the target ready to be copied into a buffer.
+fn create_render_pass(
+    device: &Device,
+    format: vk::Format,
+) -> Result<vk::RenderPass> {
+    let attachments = [vk::AttachmentDescription {
+        format,
+        samples: vk::SampleCountFlags::TYPE_1,
+        load_op: vk::AttachmentLoadOp::CLEAR,
+        store_op: vk::AttachmentStoreOp::STORE,
+        stencil_load_op: vk::AttachmentLoadOp::DONT_CARE,
+        stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        final_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ..Default::default()
+    }];
+
+    let color_references = [vk::AttachmentReference {
+        attachment: 0,
+        layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+    }];
+
+    let subpasses = [vk::SubpassDescription {
+        pipeline_bind_point: vk::PipelineBindPoint::GRAPHICS,
+        p_color_attachments: color_references.as_ptr(),
+        color_attachment_count: color_references.len() as u32,
+        ..Default::default()
+    }];
+
+    let dependencies = [
+        vk::SubpassDependency {
+            src_subpass: vk::SUBPASS_EXTERNAL,
+            src_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            src_access_mask: vk::AccessFlags::empty(),
+            dst_subpass: 0,
+            dst_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            dst_access_mask: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            dependency_flags: vk::DependencyFlags::default(),
+        },
+        // the tile is copied into a readback buffer once it's drawn
+        vk::SubpassDependency {
+            src_subpass: 0,
+            src_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            src_access_mask: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            dst_subpass: vk::SUBPASS_EXTERNAL,
+            dst_stage_mask: vk::PipelineStageFlags::TRANSFER,
+            dst_access_mask: vk::AccessFlags::TRANSFER_READ,
+            dependency_flags: vk::DependencyFlags::default(),
+        },
+    ];
+
+    let create_info = vk::RenderPassCreateInfo {
+        p_attachments: attachments.as_ptr(),
+        attachment_count: attachments.len() as u32,
+        p_subpasses: subpasses.as_ptr(),
+        subpass_count: subpasses.len() as u32,
+        p_dependencies: dependencies.as_ptr(),
+        dependency_count: dependencies.len() as u32,
+        ..Default::default()
+    };
+
+    let render_pass = unsafe {
+        device
+            .logical_device
+            .create_render_pass(&create_info, None)?
+    };
+    device.name_vulkan_object(
+        "Export Render Pass",
+        vk::ObjectType::RENDER_PASS,
+        &render_pass,
+    )?;
+    Ok(render_pass)
+}
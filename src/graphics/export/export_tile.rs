@@ -0,0 +1,130 @@
+use super::ExportTile;
+
+use ash::vk;
+use nalgebra as na;
+
+impl ExportTile {
+    /// Split an image into tiles no larger than `max_size` on either side,
+    /// in rows from the top left corner.
+    pub fn split(width: u32, height: u32, max_size: u32) -> Vec<Self> {
+        let max_size = max_size.max(1);
+        let mut tiles = vec![];
+        for y in (0..height).step_by(max_size as usize) {
+            for x in (0..width).step_by(max_size as usize) {
+                tiles.push(Self {
+                    x,
+                    y,
+                    width: max_size.min(width - x),
+                    height: max_size.min(height - y),
+                });
+            }
+        }
+        tiles
+    }
+
+    /// The size of the tile.
+    pub fn extent(&self) -> vk::Extent2D {
+        vk::Extent2D {
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// A transform which stretches the tile's part of an image's clip space
+    /// over the whole of clip space.
+    ///
+    /// Drawing with every projection multiplied by the crop renders just the
+    /// tile, at the image's resolution.
+    pub fn crop(
+        &self,
+        image_width: u32,
+        image_height: u32,
+    ) -> na::Matrix4<f32> {
+        let axis = |start: u32, size: u32, image_size: u32| {
+            let scale = image_size as f32 / size as f32;
+            let offset = (image_size as f32 - 2.0 * start as f32) / size as f32;
+            (scale, offset - 1.0)
+        };
+        let (scale_x, offset_x) = axis(self.x, self.width, image_width);
+        let (scale_y, offset_y) = axis(self.y, self.height, image_height);
+        na::Matrix4::new_translation(&na::Vector3::new(offset_x, offset_y, 0.0))
+            * na::Matrix4::new_nonuniform_scaling(&na::Vector3::new(
+                scale_x, scale_y, 1.0,
+            ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn clip(crop: &na::Matrix4<f32>, x: f32, y: f32) -> (f32, f32) {
+        let point = crop.transform_point(&na::Point3::new(x, y, 0.0));
+        (point.x, point.y)
+    }
+
+    #[test]
+    fn split_should_return_one_tile_for_small_images() {
+        assert_eq!(
+            ExportTile::split(640, 480, 1024),
+            vec![ExportTile {
+                x: 0,
+                y: 0,
+                width: 640,
+                height: 480
+            }]
+        );
+    }
+
+    #[test]
+    fn split_should_cover_large_images_with_partial_edges() {
+        let tiles = ExportTile::split(250, 120, 100);
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(
+            tiles[2],
+            ExportTile {
+                x: 200,
+                y: 0,
+                width: 50,
+                height: 100
+            }
+        );
+        assert_eq!(
+            tiles[5],
+            ExportTile {
+                x: 200,
+                y: 100,
+                width: 50,
+                height: 20
+            }
+        );
+        let area: u32 = tiles.iter().map(|t| t.width * t.height).sum();
+        assert_eq!(area, 250 * 120);
+    }
+
+    #[test]
+    fn crop_should_be_identity_for_the_whole_image() {
+        let tile = ExportTile {
+            x: 0,
+            y: 0,
+            width: 300,
+            height: 200,
+        };
+        assert_eq!(tile.crop(300, 200), na::Matrix4::identity());
+    }
+
+    #[test]
+    fn crop_should_stretch_the_tile_over_clip_space() {
+        // the bottom right quarter of the image
+        let tile = ExportTile {
+            x: 100,
+            y: 50,
+            width: 100,
+            height: 50,
+        };
+        let crop = tile.crop(200, 100);
+        assert_eq!(clip(&crop, 0.0, 0.0), (-1.0, -1.0));
+        assert_eq!(clip(&crop, 1.0, 1.0), (1.0, 1.0));
+        assert_eq!(clip(&crop, 0.5, 0.5), (0.0, 0.0));
+    }
+}
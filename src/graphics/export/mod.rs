@@ -0,0 +1,56 @@
+//! Offline export of the layers at any resolution.
+//!
+//! # Big Idea
+//!
+//! Exports are drawn with the same commands as an ordinary frame, but into
+//! an offscreen target instead of the swapchain. Images larger than the
+//! device can render in one pass are split into tiles. Each tile is drawn
+//! with every projection cropped to the tile's part of the image, then read
+//! back and copied into place.
+//!
+//! Only the layers are exported. The shader canvas, render nodes, and the
+//! passes which run after the layers, like color grading, aren't applied.
+
+mod export_target;
+mod export_tile;
+
+use crate::graphics::vulkan::{texture::TextureImage, Device};
+
+use ash::vk;
+use nalgebra as na;
+use std::sync::Arc;
+
+/// The largest tile drawn in one pass, even on devices which support larger
+/// images, to keep the offscreen target's memory use reasonable.
+pub const MAX_TILE_SIZE: u32 = 4096;
+
+/// A rectangle of the exported image, in pixels from the top left corner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ExportTile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The offscreen target tiles are drawn into.
+pub struct ExportTarget {
+    /// The size of the target, which fits the largest tile.
+    extent: vk::Extent2D,
+
+    image: TextureImage,
+
+    /// A render pass compatible with the swapchain's, so the layer
+    /// pipelines can draw into the target.
+    render_pass: vk::RenderPass,
+
+    framebuffer: vk::Framebuffer,
+
+    /// The tile being drawn.
+    tile: ExportTile,
+
+    /// Maps the tile's part of clip space to the whole target.
+    crop: na::Matrix4<f32>,
+
+    device: Arc<Device>,
+}
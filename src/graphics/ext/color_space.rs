@@ -0,0 +1,179 @@
+use ash::vk;
+
+/// The color space of 8-bit texture data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Values are sRGB encoded. Most color images are stored this way.
+    Srgb,
+
+    /// Values are linear. Normal maps, masks, and other data textures are
+    /// usually stored this way.
+    Linear,
+}
+
+/// Controls how image data is converted when it is uploaded into a texture.
+///
+/// The `source` is the color space of the image data and `storage` is the
+/// color space of the texture. Textures stored as `Srgb` use the
+/// `R8G8B8A8_SRGB` format so the GPU decodes them when sampling, textures
+/// stored as `Linear` use `R8G8B8A8_UNORM` and are sampled as-is. When the
+/// color spaces differ the color channels are converted before upload, alpha
+/// is always left unchanged.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureColorOptions {
+    pub source: ColorSpace,
+    pub storage: ColorSpace,
+}
+
+impl ColorSpace {
+    /// The texture format used to store data in this color space.
+    pub fn texture_format(self) -> vk::Format {
+        match self {
+            ColorSpace::Srgb => vk::Format::R8G8B8A8_SRGB,
+            ColorSpace::Linear => vk::Format::R8G8B8A8_UNORM,
+        }
+    }
+}
+
+impl TextureColorOptions {
+    /// Upload sRGB image data into a linear texture.
+    pub fn srgb_to_linear() -> Self {
+        Self {
+            source: ColorSpace::Srgb,
+            storage: ColorSpace::Linear,
+        }
+    }
+
+    /// Upload linear image data into an sRGB texture.
+    pub fn linear_to_srgb() -> Self {
+        Self {
+            source: ColorSpace::Linear,
+            storage: ColorSpace::Srgb,
+        }
+    }
+
+    /// Upload linear image data, like a normal map, without any conversion.
+    pub fn linear_data() -> Self {
+        Self {
+            source: ColorSpace::Linear,
+            storage: ColorSpace::Linear,
+        }
+    }
+
+    /// The texture format used for textures with these options.
+    pub fn texture_format(&self) -> vk::Format {
+        self.storage.texture_format()
+    }
+
+    /// Convert tightly packed rgba8 pixels from the source color space to the
+    /// storage color space.
+    pub fn convert_rgba8(&self, pixels: &mut [u8]) {
+        let table = match (self.source, self.storage) {
+            (ColorSpace::Srgb, ColorSpace::Linear) => {
+                conversion_table(srgb_to_linear)
+            }
+            (ColorSpace::Linear, ColorSpace::Srgb) => {
+                conversion_table(linear_to_srgb)
+            }
+            _ => return,
+        };
+        for pixel in pixels.chunks_exact_mut(4) {
+            for channel in &mut pixel[0..3] {
+                *channel = table[*channel as usize];
+            }
+        }
+    }
+}
+
+impl Default for TextureColorOptions {
+    /// sRGB image data stored in an sRGB texture.
+    fn default() -> Self {
+        Self {
+            source: ColorSpace::Srgb,
+            storage: ColorSpace::Srgb,
+        }
+    }
+}
+
+/// Decode a normalized sRGB value to linear using the exact piecewise
+/// transfer function.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a normalized linear value as sRGB using the exact piecewise
+/// transfer function.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Build a lookup table which applies a conversion to every 8-bit value.
+fn conversion_table(convert: fn(f32) -> f32) -> [u8; 256] {
+    let mut table = [0; 256];
+    for (index, entry) in table.iter_mut().enumerate() {
+        let converted = convert(index as f32 / 255.0);
+        *entry = (converted.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn transfer_functions_should_match_reference_values() {
+        // reference values from the sRGB specification (IEC 61966-2-1)
+        assert_relative_eq!(srgb_to_linear(0.0), 0.0);
+        assert_relative_eq!(srgb_to_linear(0.04045), 0.003130805);
+        assert_relative_eq!(srgb_to_linear(0.5), 0.21404114, epsilon = 1e-6);
+        assert_relative_eq!(srgb_to_linear(1.0), 1.0);
+
+        assert_relative_eq!(linear_to_srgb(0.0031308), 0.04044994);
+        assert_relative_eq!(linear_to_srgb(0.18), 0.46135613, epsilon = 1e-6);
+        assert_relative_eq!(linear_to_srgb(1.0), 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn transfer_functions_should_round_trip() {
+        for i in 0..=100 {
+            let value = i as f32 / 100.0;
+            assert_relative_eq!(
+                linear_to_srgb(srgb_to_linear(value)),
+                value,
+                epsilon = 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn convert_rgba8_should_match_reference_bytes() {
+        // sRGB 128 is 21.6% linear, which rounds to 55
+        let mut pixels = [0, 128, 255, 128];
+        TextureColorOptions::srgb_to_linear().convert_rgba8(&mut pixels);
+        assert_eq!(pixels, [0, 55, 255, 128]);
+
+        // linear 55 encodes back to sRGB 128
+        TextureColorOptions::linear_to_srgb().convert_rgba8(&mut pixels);
+        assert_eq!(pixels, [0, 128, 255, 128]);
+    }
+
+    #[test]
+    fn convert_rgba8_should_not_change_matching_color_spaces() {
+        let original = [12, 34, 56, 78];
+        let mut pixels = original;
+        TextureColorOptions::default().convert_rgba8(&mut pixels);
+        TextureColorOptions::linear_data().convert_rgba8(&mut pixels);
+        assert_eq!(pixels, original);
+    }
+}
@@ -1,8 +1,13 @@
+mod color_space;
 mod sampler_factory;
 mod texture_2d_factory;
 mod texture_loader;
 
 pub use self::{
-    sampler_factory::SamplerFactory, texture_2d_factory::Texture2dFactory,
-    texture_loader::TextureLoader,
+    color_space::{
+        linear_to_srgb, srgb_to_linear, ColorSpace, TextureColorOptions,
+    },
+    sampler_factory::SamplerFactory,
+    texture_2d_factory::Texture2dFactory,
+    texture_loader::{DecodedTexture, TextureLoader, TextureSource},
 };
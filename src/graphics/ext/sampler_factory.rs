@@ -1,4 +1,4 @@
-use crate::graphics::{Device, Graphics};
+use crate::graphics::{texture_atlas::SamplerPreset, Device, Graphics};
 
 use anyhow::{Context, Result};
 use ash::{version::DeviceV1_0, vk};
@@ -7,7 +7,7 @@ use ash::{version::DeviceV1_0, vk};
 pub trait SamplerFactory {
     /// Create a new sampler.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller must remember to destroy the sampler when they're done
     ///   with it.
@@ -19,11 +19,27 @@ pub trait SamplerFactory {
 
     /// Destroy a sampler.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller must ensure that the sampler is not in use by the GPU when
     ///   it is destroyed.
     unsafe fn destroy_sampler(&self, sampler: vk::Sampler);
+
+    /// Create a new sampler with one of the preset configurations.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must remember to destroy the sampler when they're done
+    ///   with it.
+    unsafe fn create_preset_sampler(
+        &self,
+        preset: SamplerPreset,
+    ) -> Result<vk::Sampler> {
+        self.create_sampler(
+            format!("{:?} preset sampler", preset),
+            preset.create_info(),
+        )
+    }
 }
 
 impl SamplerFactory for Graphics {
@@ -42,15 +58,23 @@ impl SamplerFactory for Graphics {
 }
 
 impl SamplerFactory for Device {
+    /// Create a new sampler.
+    ///
+    /// Anisotropy is clamped to the device's limit, and disabled when the
+    /// device doesn't support it.
     unsafe fn create_sampler(
         &self,
         debug_name: impl Into<String>,
         sampler_create_info: vk::SamplerCreateInfo,
     ) -> Result<vk::Sampler> {
         let owned_name = debug_name.into();
+        let create_info = limit_anisotropy(
+            sampler_create_info,
+            self.max_sampler_anisotropy(),
+        );
         let sampler = self
             .logical_device
-            .create_sampler(&sampler_create_info, None)
+            .create_sampler(&create_info, None)
             .with_context(|| {
                 format!("unable to create sampler {:?}", owned_name.clone())
             })?;
@@ -61,6 +85,56 @@ impl SamplerFactory for Device {
     }
 
     unsafe fn destroy_sampler(&self, sampler: vk::Sampler) {
+        self.forget_vulkan_object(vk::ObjectType::SAMPLER, &sampler);
         self.logical_device.destroy_sampler(sampler, None);
     }
 }
+
+/// Clamp a sampler's anisotropy to the device's limit. Anisotropy is
+/// disabled when the limit is None because the feature isn't enabled.
+fn limit_anisotropy(
+    create_info: vk::SamplerCreateInfo,
+    max_supported: Option<f32>,
+) -> vk::SamplerCreateInfo {
+    if create_info.anisotropy_enable != vk::TRUE {
+        return create_info;
+    }
+    match max_supported {
+        Some(max) => vk::SamplerCreateInfo {
+            max_anisotropy: create_info.max_anisotropy.min(max).max(1.0),
+            ..create_info
+        },
+        None => vk::SamplerCreateInfo {
+            anisotropy_enable: vk::FALSE,
+            max_anisotropy: 1.0,
+            ..create_info
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn anisotropic(max_anisotropy: f32) -> vk::SamplerCreateInfo {
+        vk::SamplerCreateInfo {
+            anisotropy_enable: vk::TRUE,
+            max_anisotropy,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn anisotropy_should_be_clamped_to_the_device_limit() {
+        let info = limit_anisotropy(anisotropic(64.0), Some(16.0));
+        assert_eq!(info.anisotropy_enable, vk::TRUE);
+        assert_eq!(info.max_anisotropy, 16.0);
+    }
+
+    #[test]
+    fn anisotropy_should_be_disabled_without_the_feature() {
+        let info = limit_anisotropy(anisotropic(8.0), None);
+        assert_eq!(info.anisotropy_enable, vk::FALSE);
+        assert_eq!(info.max_anisotropy, 1.0);
+    }
+}
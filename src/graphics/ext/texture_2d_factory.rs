@@ -1,11 +1,15 @@
-use crate::graphics::{vulkan::texture::TextureImage, Device, Graphics};
+use crate::graphics::{
+    vulkan::texture::{TextureImage, CUBE_FACES},
+    Device, Graphics,
+};
 
 use anyhow::Result;
 use ash::vk;
 use std::sync::Arc;
 
 /// Types which implement this trait can easily construct new texture images
-/// which represent 2d rgba textures.
+/// which represent rgba textures. 2d textures are the common case, 1d textures
+/// and cube maps are available for effects which need them.
 pub trait Texture2dFactory {
     /// Create a new 2d texture image and view.
     fn create_empty_2d_texture(
@@ -15,6 +19,54 @@ pub trait Texture2dFactory {
         height: u32,
         mip_levels: u32,
     ) -> Result<TextureImage>;
+
+    /// Create a new 2d texture image and view with a specific format.
+    ///
+    /// Single channel formats like R8_UNORM are a good fit for masks and
+    /// glyphs, they use a quarter of the memory of an rgba8 texture.
+    fn create_empty_2d_texture_with_format(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+        format: vk::Format,
+    ) -> Result<TextureImage>;
+
+    /// Create a new 2d array texture where every layer has the same size.
+    ///
+    /// Upload each layer with `TextureImage::upload_layer_mipmaps_from_buffer`.
+    /// Once added to the atlas, batches pick the layer they draw with
+    /// `Batch::with_layer`.
+    fn create_empty_2d_array_texture(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        array_layers: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage>;
+
+    /// Create a new 1d texture, useful for lookup tables and gradients.
+    ///
+    /// Mipmaps are uploaded with a height of one.
+    fn create_empty_1d_texture(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage>;
+
+    /// Create a new cube map where every face is a `size` by `size` square.
+    ///
+    /// Upload each face with `TextureImage::upload_layer_mipmaps_from_buffer`
+    /// using layers in the order +X, -X, +Y, -Y, +Z, -Z.
+    fn create_empty_cube_texture(
+        &self,
+        name: impl Into<String>,
+        size: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage>;
 }
 
 impl Texture2dFactory for Graphics {
@@ -30,6 +82,55 @@ impl Texture2dFactory for Graphics {
         self.device
             .create_empty_2d_texture(name, width, height, mip_levels)
     }
+
+    fn create_empty_2d_texture_with_format(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+        format: vk::Format,
+    ) -> Result<TextureImage> {
+        self.device.create_empty_2d_texture_with_format(
+            name, width, height, mip_levels, format,
+        )
+    }
+
+    fn create_empty_2d_array_texture(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        array_layers: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage> {
+        self.device.create_empty_2d_array_texture(
+            name,
+            width,
+            height,
+            array_layers,
+            mip_levels,
+        )
+    }
+
+    fn create_empty_1d_texture(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage> {
+        self.device.create_empty_1d_texture(name, width, mip_levels)
+    }
+
+    fn create_empty_cube_texture(
+        &self,
+        name: impl Into<String>,
+        size: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage> {
+        self.device
+            .create_empty_cube_texture(name, size, mip_levels)
+    }
 }
 
 impl Texture2dFactory for Arc<Device> {
@@ -40,43 +141,144 @@ impl Texture2dFactory for Arc<Device> {
         height: u32,
         mip_levels: u32,
     ) -> Result<TextureImage> {
-        let (format, bytes_per_pixel) = (vk::Format::R8G8B8A8_SRGB, 4 as u64);
-        let texture = TextureImage::new(
-            self.clone(),
+        self.create_empty_2d_texture_with_format(
+            name,
+            width,
+            height,
+            mip_levels,
+            vk::Format::R8G8B8A8_SRGB,
+        )
+    }
+
+    fn create_empty_2d_texture_with_format(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        mip_levels: u32,
+        format: vk::Format,
+    ) -> Result<TextureImage> {
+        create_texture(
+            self,
+            name,
             vk::ImageCreateInfo {
                 image_type: vk::ImageType::TYPE_2D,
-                extent: vk::Extent3D {
-                    width,
-                    height,
-                    depth: 1,
-                },
+                extent: extent(width, height),
                 mip_levels,
                 array_layers: 1,
                 format,
-                tiling: vk::ImageTiling::OPTIMAL,
-                initial_layout: vk::ImageLayout::UNDEFINED,
-                usage: vk::ImageUsageFlags::TRANSFER_DST
-                    | vk::ImageUsageFlags::SAMPLED,
-                samples: vk::SampleCountFlags::TYPE_1,
-                sharing_mode: vk::SharingMode::EXCLUSIVE,
                 ..Default::default()
             },
-            vk::MemoryPropertyFlags::DEVICE_LOCAL,
-            bytes_per_pixel,
-        )?;
-
-        let owned_name = name.into();
-        self.name_vulkan_object(
-            format!("{} - Image", owned_name.clone()),
-            vk::ObjectType::IMAGE,
-            unsafe { &texture.raw_image() },
-        )?;
-        self.name_vulkan_object(
-            format!("{} - Image View", owned_name.clone()),
-            vk::ObjectType::IMAGE_VIEW,
-            unsafe { &texture.raw_view() },
-        )?;
-
-        Ok(texture)
+        )
     }
+
+    fn create_empty_2d_array_texture(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        height: u32,
+        array_layers: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage> {
+        create_texture(
+            self,
+            name,
+            vk::ImageCreateInfo {
+                image_type: vk::ImageType::TYPE_2D,
+                extent: extent(width, height),
+                mip_levels,
+                array_layers,
+                format: vk::Format::R8G8B8A8_SRGB,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn create_empty_1d_texture(
+        &self,
+        name: impl Into<String>,
+        width: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage> {
+        create_texture(
+            self,
+            name,
+            vk::ImageCreateInfo {
+                image_type: vk::ImageType::TYPE_1D,
+                extent: extent(width, 1),
+                mip_levels,
+                array_layers: 1,
+                format: vk::Format::R8G8B8A8_SRGB,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn create_empty_cube_texture(
+        &self,
+        name: impl Into<String>,
+        size: u32,
+        mip_levels: u32,
+    ) -> Result<TextureImage> {
+        create_texture(
+            self,
+            name,
+            vk::ImageCreateInfo {
+                flags: vk::ImageCreateFlags::CUBE_COMPATIBLE,
+                image_type: vk::ImageType::TYPE_2D,
+                extent: extent(size, size),
+                mip_levels,
+                array_layers: CUBE_FACES,
+                format: vk::Format::R8G8B8A8_SRGB,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// A single-depth extent for 1d and 2d images.
+fn extent(width: u32, height: u32) -> vk::Extent3D {
+    vk::Extent3D {
+        width,
+        height,
+        depth: 1,
+    }
+}
+
+/// Create a texture and name its vulkan objects.
+///
+/// The shape provides the flags, image type, extent, layers, mip levels, and
+/// format. Everything else is the same for every sampled texture.
+fn create_texture(
+    device: &Arc<Device>,
+    name: impl Into<String>,
+    shape: vk::ImageCreateInfo,
+) -> Result<TextureImage> {
+    let texture = TextureImage::new(
+        device.clone(),
+        vk::ImageCreateInfo {
+            tiling: vk::ImageTiling::OPTIMAL,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            usage: vk::ImageUsageFlags::TRANSFER_DST
+                | vk::ImageUsageFlags::SAMPLED,
+            samples: vk::SampleCountFlags::TYPE_1,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..shape
+        },
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+
+    let owned_name = name.into();
+    device.name_vulkan_object(
+        format!("{} - Image", owned_name.clone()),
+        vk::ObjectType::IMAGE,
+        unsafe { &texture.raw_image() },
+    )?;
+    device.name_vulkan_object(
+        format!("{} - Image View", owned_name.clone()),
+        vk::ObjectType::IMAGE_VIEW,
+        unsafe { &texture.raw_view() },
+    )?;
+
+    Ok(texture)
 }
@@ -1,9 +1,10 @@
-use super::Texture2dFactory;
+use super::{Texture2dFactory, TextureColorOptions};
 
 use crate::graphics::{
     vulkan::{
         buffer::CpuBuffer,
         texture::{MipmapExtent, TextureImage},
+        Device,
     },
     Graphics,
 };
@@ -11,7 +12,7 @@ use crate::graphics::{
 use anyhow::Result;
 use ash::vk;
 use image::ImageBuffer;
-use std::path::Path;
+use std::sync::Arc;
 
 /// Types which implement this trait can load 2d textures from files on the
 /// disk.
@@ -21,6 +22,14 @@ pub trait TextureLoader {
         &self,
         file_path: impl Into<String>,
     ) -> Result<TextureImage>;
+
+    /// Read a file into a texture, converting the image data between color
+    /// spaces as described by the options.
+    fn read_texture_file_with_options(
+        &self,
+        file_path: impl Into<String>,
+        options: TextureColorOptions,
+    ) -> Result<TextureImage>;
 }
 
 impl TextureLoader for Graphics {
@@ -29,66 +38,261 @@ impl TextureLoader for Graphics {
         &self,
         file_path: impl Into<String>,
     ) -> Result<TextureImage> {
-        let path_string = file_path.into();
+        self.device.read_texture_file(file_path)
+    }
+
+    fn read_texture_file_with_options(
+        &self,
+        file_path: impl Into<String>,
+        options: TextureColorOptions,
+    ) -> Result<TextureImage> {
+        self.device
+            .read_texture_file_with_options(file_path, options)
+    }
+}
+
+impl TextureLoader for Arc<Device> {
+    /// Read a file from the local filesystem into memory as a usable texture.
+    fn read_texture_file(
+        &self,
+        file_path: impl Into<String>,
+    ) -> Result<TextureImage> {
+        self.read_texture_file_with_options(
+            file_path,
+            TextureColorOptions::default(),
+        )
+    }
+
+    fn read_texture_file_with_options(
+        &self,
+        file_path: impl Into<String>,
+        options: TextureColorOptions,
+    ) -> Result<TextureImage> {
+        DecodedTexture::read_file(file_path, options)?.upload(self)
+    }
+}
+
+/// Where the image data for a texture comes from.
+#[derive(Debug, Clone, Copy)]
+pub enum TextureSource<'a> {
+    /// A file on the local filesystem.
+    File(&'a str),
+
+    /// An encoded image which is already in memory, like one included with
+    /// `include_bytes!`. The name is only used to label the texture.
+    Bytes { name: &'a str, bytes: &'a [u8] },
+}
+
+impl<'a> From<&'a str> for TextureSource<'a> {
+    fn from(path: &'a str) -> Self {
+        TextureSource::File(path)
+    }
+}
+
+/// A texture file which has been read and color converted, but not uploaded.
+///
+/// Decoding and generating mipmaps is the slow part of loading a texture and
+/// doesn't touch the device, so it can happen on any thread.
+pub struct DecodedTexture {
+    path: String,
+    is_file: bool,
+    mipmaps: Vec<ImageBufferU8>,
+    format: vk::Format,
+}
 
-        let mipmaps = read_file_mipmaps(&path_string)?;
-        let packed_mipmap_data: Vec<&[u8]> = mipmaps
+impl DecodedTexture {
+    /// Read a file and generate its mipmaps.
+    pub fn read_file(
+        file_path: impl Into<String>,
+        options: TextureColorOptions,
+    ) -> Result<Self> {
+        let path = file_path.into();
+        let image = image::open(&path)?.into_rgba8();
+        Ok(Self::from_rgba8(path, true, image, options))
+    }
+
+    /// Decode an image which is already in memory and generate its mipmaps.
+    /// The image format is guessed from the data.
+    pub fn from_bytes(
+        name: impl Into<String>,
+        bytes: &[u8],
+        options: TextureColorOptions,
+    ) -> Result<Self> {
+        let image = image::load_from_memory(bytes)?.into_rgba8();
+        Ok(Self::from_rgba8(name.into(), false, image, options))
+    }
+
+    /// Decode a texture from either a file or bytes in memory.
+    pub fn from_source(
+        source: TextureSource,
+        options: TextureColorOptions,
+    ) -> Result<Self> {
+        match source {
+            TextureSource::File(path) => Self::read_file(path, options),
+            TextureSource::Bytes { name, bytes } => {
+                Self::from_bytes(name, bytes, options)
+            }
+        }
+    }
+
+    fn from_rgba8(
+        path: String,
+        is_file: bool,
+        image: ImageBufferU8,
+        options: TextureColorOptions,
+    ) -> Self {
+        let mut mipmaps = generate_mipmaps(image);
+        for mipmap in &mut mipmaps {
+            options.convert_rgba8(mipmap);
+        }
+        Self {
+            path,
+            is_file,
+            mipmaps,
+            format: options.texture_format(),
+        }
+    }
+
+    /// The file the texture was read from, or the name it was given when
+    /// decoded from bytes.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The number of bytes which will be uploaded, including every mipmap.
+    pub fn size_in_bytes(&self) -> u64 {
+        self.mipmaps
             .iter()
-            .map(|mipmap| mipmap.as_raw() as &[u8])
-            .collect();
+            .map(|mipmap| mipmap.as_raw().len() as u64)
+            .sum()
+    }
+
+    /// Create a texture and upload the mipmaps to it.
+    pub fn upload(self, device: &Arc<Device>) -> Result<TextureImage> {
+        let mut textures = Self::upload_batch(device, vec![self])?;
+        Ok(textures.remove(0))
+    }
 
-        let mut texture = self.create_empty_2d_texture(
-            path_string,
-            mipmaps[0].width(),
-            mipmaps[0].height(),
-            mipmaps.len() as u32,
-        )?;
+    /// Create a texture for each decoded image and upload all of them at
+    /// once.
+    ///
+    /// Every mipmap is packed into one transfer buffer and every copy is
+    /// recorded into one command buffer, so the batch costs a single
+    /// submission instead of one per texture. The copies run on the device's
+    /// transfer queue, then the textures are handed to the graphics queue.
+    /// Textures are returned in the same order as the decoded images.
+    pub fn upload_batch(
+        device: &Arc<Device>,
+        decoded: Vec<DecodedTexture>,
+    ) -> Result<Vec<TextureImage>> {
+        if decoded.is_empty() {
+            return Ok(vec![]);
+        }
 
-        let mut transfer_buffer = CpuBuffer::new(
-            self.device.clone(),
-            vk::BufferUsageFlags::TRANSFER_SRC,
-        )?;
+        let mut textures = Vec::with_capacity(decoded.len());
+        for texture in &decoded {
+            textures.push(device.create_empty_2d_texture_with_format(
+                texture.path.clone(),
+                texture.mipmaps[0].width(),
+                texture.mipmaps[0].height(),
+                texture.mipmaps.len() as u32,
+                texture.format,
+            )?);
+        }
+
+        // rgba8 mipmaps are always a multiple of 4 bytes, so every texture
+        // starts at a valid copy offset
+        let packed_mipmap_data: Vec<&[u8]> = decoded
+            .iter()
+            .flat_map(|texture| texture.mipmaps.iter())
+            .map(|mipmap| mipmap.as_raw() as &[u8])
+            .collect();
+        let mut transfer_buffer =
+            CpuBuffer::new(device.clone(), vk::BufferUsageFlags::TRANSFER_SRC)?;
 
         unsafe {
             transfer_buffer.write_data_arrays(&packed_mipmap_data)?;
 
-            let mipmap_sizes: Vec<MipmapExtent> = mipmaps
+            let uploads: Vec<(u64, Vec<MipmapExtent>)> = decoded
                 .iter()
-                .map(|mipmap| MipmapExtent {
-                    width: mipmap.width(),
-                    height: mipmap.height(),
+                .scan(0, |offset, texture| {
+                    let start = *offset;
+                    *offset += texture.size_in_bytes();
+                    Some((start, texture.mipmap_sizes()))
                 })
                 .collect();
+            let mut pending =
+                device.submit_transfer_commands_async(|command_buffer| {
+                    for (texture, (offset, mipmap_sizes)) in
+                        textures.iter().zip(&uploads)
+                    {
+                        texture.record_upload_from_buffer(
+                            command_buffer,
+                            &transfer_buffer,
+                            *offset,
+                            0,
+                            mipmap_sizes,
+                        )?;
+                        texture.release_to(
+                            command_buffer,
+                            &texture.upload_transfer(),
+                        );
+                    }
+                    Ok(())
+                })?;
+            pending.wait()?;
+
+            // the release finished before the acquire is submitted
+            let transfer = textures[0].upload_transfer();
+            if transfer.is_ownership_transfer() {
+                device.sync_graphics_commands(|command_buffer| {
+                    for texture in &textures {
+                        texture.acquire_from(command_buffer, &transfer);
+                    }
+                    Ok(())
+                })?;
+            }
+        }
 
-            texture
-                .upload_mipmaps_from_buffer(&transfer_buffer, &mipmap_sizes)?;
+        for (texture, decoded) in textures.iter_mut().zip(decoded) {
+            if decoded.is_file {
+                texture.set_source_path(decoded.path);
+            }
         }
-        Ok(texture)
+        Ok(textures)
+    }
+
+    fn mipmap_sizes(&self) -> Vec<MipmapExtent> {
+        self.mipmaps
+            .iter()
+            .map(|mipmap| MipmapExtent {
+                width: mipmap.width(),
+                height: mipmap.height(),
+            })
+            .collect()
     }
 }
 
 type ImageBufferU8 = ImageBuffer<image::Rgba<u8>, Vec<u8>>;
 
-/// Read a file as an rgba8 image. Mipmaps are automatically generated based
-/// on the file size and a Gaussian filter. The returned list is the set of
-/// all image mipmaps in a R8G8B8A8 format.
-fn read_file_mipmaps(path: &impl AsRef<Path>) -> Result<Vec<ImageBufferU8>> {
-    let image_file = image::open(path)?.into_rgba8();
-    let (width, height) = (image_file.width(), image_file.height());
+/// Mipmaps are generated based on the image size and a Gaussian filter. The
+/// returned list is the set of all image mipmaps in a R8G8B8A8 format,
+/// starting with the image itself.
+fn generate_mipmaps(image: ImageBufferU8) -> Vec<ImageBufferU8> {
+    let (width, height) = (image.width(), image.height());
     let mip_levels = (height.max(width) as f32).log2().floor() as u32 + 1;
 
     let mut mipmaps = Vec::with_capacity(mip_levels as usize);
-    mipmaps.push(image_file.clone());
     for mipmap_level in 1..mip_levels {
         use image::imageops;
         let mipmap = imageops::resize(
-            &image_file,
+            &image,
             width >> mipmap_level,
             height >> mipmap_level,
             imageops::FilterType::Gaussian,
         );
         mipmaps.push(mipmap);
     }
-
-    Ok(mipmaps)
+    mipmaps.insert(0, image);
+    mipmaps
 }
@@ -0,0 +1,258 @@
+use super::Feedback;
+
+use crate::graphics::{
+    texture_atlas::{GpuAtlas, TextureAtlas, TextureHandle},
+    vulkan::{texture::TextureImage, Device, Swapchain},
+};
+
+use anyhow::{bail, Context, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+impl Feedback {
+    /// Create both feedback images and add them to the atlas.
+    ///
+    /// Fails if the swapchain images can't be copied.
+    pub fn new(
+        device: &Arc<Device>,
+        atlas: &mut GpuAtlas,
+        swapchain: &Swapchain,
+    ) -> Result<Self> {
+        if !swapchain
+            .image_usage
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            bail!("feedback requires a swapchain which supports TRANSFER_SRC");
+        }
+        let first = atlas.add_texture(create_target(device, swapchain, 0)?)?;
+        let second = match create_target(device, swapchain, 1)
+            .and_then(|target| atlas.add_texture(target))
+        {
+            Ok(second) => second,
+            Err(error) => {
+                // SAFE: the texture was just added and has not been drawn
+                unsafe { atlas.take_texture(first)? };
+                return Err(error);
+            }
+        };
+        Ok(Self {
+            textures: [first, second],
+            read: 0,
+            extent: swapchain.extent,
+        })
+    }
+
+    /// The handle which batches use to sample the previous frame.
+    pub fn texture(&self) -> TextureHandle {
+        self.textures[0]
+    }
+
+    /// The texture a batch should actually sample this frame.
+    ///
+    /// The feedback handle is replaced by whichever image holds the previous
+    /// frame, all other handles are returned unchanged.
+    pub fn resolve(&self, texture_handle: TextureHandle) -> TextureHandle {
+        if texture_handle == self.textures[0] {
+            self.textures[self.read]
+        } else {
+            texture_handle
+        }
+    }
+
+    /// Swap the images after a frame's copy has been recorded, so the next
+    /// frame samples the image which was just written.
+    pub fn swap(&mut self) {
+        self.read = 1 - self.read;
+    }
+
+    /// Recreate both images to match a new swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure the atlas is not in use when this method
+    ///   is called
+    pub unsafe fn resize(
+        &mut self,
+        device: &Arc<Device>,
+        atlas: &mut GpuAtlas,
+        swapchain: &Swapchain,
+    ) -> Result<()> {
+        if self.extent == swapchain.extent {
+            return Ok(());
+        }
+        for (index, texture) in self.textures.iter().enumerate() {
+            let target = create_target(device, swapchain, index)?;
+            atlas.replace_texture(*texture, target)?;
+        }
+        self.extent = swapchain.extent;
+        Ok(())
+    }
+
+    /// Remove both images from the atlas.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure the atlas is not in use when this method
+    ///   is called
+    pub unsafe fn destroy(self, atlas: &mut GpuAtlas) -> Result<()> {
+        for texture in &self.textures {
+            atlas.take_texture(*texture)?;
+        }
+        Ok(())
+    }
+
+    /// The image which this frame's copy writes, the one not being sampled.
+    pub fn write_target(&self, atlas: &GpuAtlas) -> Result<vk::Image> {
+        let target = atlas
+            .texture_image(self.textures[1 - self.read])
+            .context("the feedback image is missing from the atlas")?;
+        // SAFE: the handle is only used to record commands while the atlas
+        // owns the image
+        Ok(unsafe { target.raw_image() })
+    }
+
+    /// Record commands which copy the finished swapchain image into the
+    /// write target.
+    ///
+    /// # Safety
+    ///
+    /// - the swapchain image must be in the `TRANSFER_SRC_OPTIMAL` layout
+    ///   and the target in `TRANSFER_DST_OPTIMAL`, the frame graph inserts
+    ///   the transitions
+    /// - the swapchain image must have the same extent as the feedback images
+    pub unsafe fn record_copy(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        swapchain_image: vk::Image,
+        target: vk::Image,
+    ) {
+        let subresource = vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        let region = vk::ImageCopy {
+            src_subresource: subresource,
+            src_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            dst_subresource: subresource,
+            dst_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            extent: vk::Extent3D {
+                width: self.extent.width,
+                height: self.extent.height,
+                depth: 1,
+            },
+        };
+        device.logical_device.cmd_copy_image(
+            command_buffer,
+            swapchain_image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            target,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            &[region],
+        );
+    }
+}
+
+/// Create a feedback image which matches the swapchain, cleared to
+/// transparent black and ready to be sampled.
+fn create_target(
+    device: &Arc<Device>,
+    swapchain: &Swapchain,
+    index: usize,
+) -> Result<TextureImage> {
+    let target = TextureImage::new(
+        device.clone(),
+        vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_2D,
+            extent: vk::Extent3D {
+                width: swapchain.extent.width,
+                height: swapchain.extent.height,
+                depth: 1,
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            format: swapchain.format,
+            tiling: vk::ImageTiling::OPTIMAL,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            usage: vk::ImageUsageFlags::TRANSFER_DST
+                | vk::ImageUsageFlags::SAMPLED,
+            samples: vk::SampleCountFlags::TYPE_1,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        },
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+    unsafe {
+        let image = target.raw_image();
+        device.name_vulkan_object(
+            format!("Feedback {} - Image", index),
+            vk::ObjectType::IMAGE,
+            &image,
+        )?;
+        device.sync_graphics_commands(|command_buffer| {
+            target.write_barrier(command_buffer, 0);
+            device.logical_device.cmd_clear_color_image(
+                command_buffer,
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &vk::ClearColorValue {
+                    float32: [0.0, 0.0, 0.0, 0.0],
+                },
+                &[vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                }],
+            );
+            device.logical_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[image_barrier(
+                    image,
+                    (
+                        vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    ),
+                    (
+                        vk::AccessFlags::TRANSFER_WRITE,
+                        vk::AccessFlags::SHADER_READ,
+                    ),
+                )],
+            );
+            Ok(())
+        })?;
+    }
+    Ok(target)
+}
+
+fn image_barrier(
+    image: vk::Image,
+    (old_layout, new_layout): (vk::ImageLayout, vk::ImageLayout),
+    (src_access_mask, dst_access_mask): (vk::AccessFlags, vk::AccessFlags),
+) -> vk::ImageMemoryBarrier {
+    vk::ImageMemoryBarrier {
+        old_layout,
+        new_layout,
+        src_access_mask,
+        dst_access_mask,
+        src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+        image,
+        subresource_range: vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        },
+        ..Default::default()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::graphics::{
+    layer::Batch, texture_atlas::TextureHandle, vertex::Vertex2d,
+};
+
+/// Build a batch which covers the entire screen with a texture when drawn
+/// in a layer with an identity projection.
+///
+/// The texture is drawn with the given alpha, so drawing the feedback
+/// texture with an alpha less than 1 fades the previous frame into the clear
+/// color.
+pub fn fullscreen_batch(texture_handle: TextureHandle, alpha: f32) -> Batch {
+    let vertex = |x: f32, y: f32| Vertex2d {
+        pos: [x, y],
+        // normalized device coordinates put y = -1 at the top of the screen,
+        // which is the first row of the image
+        uv: [(x + 1.0) * 0.5, (y + 1.0) * 0.5],
+        rgba: [1.0, 1.0, 1.0, alpha],
+    };
+    let top_left = vertex(-1.0, -1.0);
+    let top_right = vertex(1.0, -1.0);
+    let bottom_left = vertex(-1.0, 1.0);
+    let bottom_right = vertex(1.0, 1.0);
+    Batch {
+        texture_handle,
+        vertices: vec![
+            top_left,
+            top_right,
+            bottom_right,
+            top_left,
+            bottom_right,
+            bottom_left,
+        ],
+        layer: 0,
+        palette: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use nalgebra as na;
+
+    #[test]
+    fn fullscreen_batch_should_cover_the_identity_view() {
+        let batch = fullscreen_batch(TextureHandle::default(), 0.5);
+        let bounds = batch.bounds().unwrap();
+
+        assert_eq!(
+            (bounds.left, bounds.right, bounds.bottom, bounds.top),
+            (-1.0, 1.0, -1.0, 1.0)
+        );
+        assert!(batch.is_visible(&na::Matrix4::identity()));
+        assert!(batch.vertices.iter().all(|vertex| vertex.rgba[3] == 0.5));
+    }
+
+    #[test]
+    fn fullscreen_batch_should_map_the_screen_to_the_whole_texture() {
+        let batch = fullscreen_batch(TextureHandle::default(), 1.0);
+        for vertex in &batch.vertices {
+            let expected =
+                [(vertex.pos[0] + 1.0) * 0.5, (vertex.pos[1] + 1.0) * 0.5];
+            assert_eq!(vertex.uv, expected);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Feedback lets layers sample the previous frame's output.
+//!
+//! # Big Idea
+//!
+//! Two offscreen images are added to the texture atlas. Each frame samples
+//! one image while the finished swapchain image is copied into the other,
+//! then the roles swap for the next frame. Drawing the previous frame with a
+//! little transparency, before anything else, leaves fading trails behind
+//! everything that moves.
+//!
+//! Batches refer to the previous frame with the handle returned by
+//! `Graphics::enable_feedback`. The handle is resolved to whichever image
+//! holds the previous frame when the batch is drawn.
+
+mod feedback_targets;
+mod fullscreen;
+
+pub use self::fullscreen::fullscreen_batch;
+
+use crate::graphics::texture_atlas::TextureHandle;
+
+use ash::vk;
+
+/// The ping-ponged images which hold the previous frame's output.
+pub struct Feedback {
+    /// The atlas textures for both images. The first handle is the public
+    /// handle for 'the previous frame'.
+    textures: [TextureHandle; 2],
+
+    /// The index of the texture which holds the previous frame.
+    read: usize,
+
+    /// The size of both images, always the same as the swapchain.
+    extent: vk::Extent2D,
+}
@@ -0,0 +1,72 @@
+use std::time::Instant;
+
+/// Measures the time given to shaders in each frame's uniforms.
+#[derive(Debug, Copy, Clone)]
+pub struct FrameClock {
+    start: Instant,
+    last_tick: Option<Instant>,
+    time: f32,
+    delta_time: f32,
+}
+
+impl FrameClock {
+    /// Create a clock which counts time from `start`.
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start,
+            last_tick: None,
+            time: 0.0,
+            delta_time: 0.0,
+        }
+    }
+
+    /// Advance the clock to a new frame. The first frame has no delta time.
+    pub fn tick(&mut self, now: Instant) {
+        self.time = now.saturating_duration_since(self.start).as_secs_f32();
+        self.delta_time = match self.last_tick {
+            Some(last_tick) => {
+                now.saturating_duration_since(last_tick).as_secs_f32()
+            }
+            None => 0.0,
+        };
+        self.last_tick = Some(now);
+    }
+
+    /// Seconds from the clock's start to the most recent tick.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Seconds between the two most recent ticks.
+    pub fn delta_time(&self) -> f32 {
+        self.delta_time
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn the_first_tick_should_have_no_delta() {
+        let start = Instant::now();
+        let mut clock = FrameClock::new(start);
+        clock.tick(start + Duration::from_millis(500));
+
+        assert_eq!(clock.time(), 0.5);
+        assert_eq!(clock.delta_time(), 0.0);
+    }
+
+    #[test]
+    fn later_ticks_should_measure_from_the_previous_tick() {
+        let start = Instant::now();
+        let mut clock = FrameClock::new(start);
+        clock.tick(start + Duration::from_millis(500));
+        clock.tick(start + Duration::from_millis(750));
+
+        assert_eq!(clock.time(), 0.75);
+        assert_eq!(clock.delta_time(), 0.25);
+    }
+}
@@ -1,6 +1,5 @@
 use crate::graphics::{
-    pipeline2d,
-    texture_atlas::{AtlasVersion, TextureAtlas},
+    frame::FRAME_UNIFORMS_BINDING, pipeline2d, storage::STORAGE_BUFFER_BINDING,
     vulkan::Device,
 };
 
@@ -11,13 +10,14 @@ use ash::{version::DeviceV1_0, vk};
 
 /// All DescriptorSet-related resources required by this applications frames.
 ///
-/// Each frame has it's own descriptor set, pool, and uniform buffers. Thus,
-/// none of these resources are shared between frames. Not sharing is convenient
-/// because things like the uniform buffer can be updated in the render loop
-/// without any additional synchronization.
+/// Each frame has it's own descriptor set and pool for its uniform and storage
+/// buffers. Thus, none of these resources are shared between frames. Not
+/// sharing is convenient because the buffers can be updated in the render
+/// loop without any additional synchronization.
+///
+/// Textures live in the texture atlas's descriptor set, which is shared by
+/// every frame.
 pub struct FrameDescriptor {
-    atlas_version: AtlasVersion,
-
     ///! A Descriptor Pool is required for allocating a Descriptor Set.
     descriptor_pool: vk::DescriptorPool,
 
@@ -41,7 +41,7 @@ impl FrameDescriptor {
     {
         let owned_name = name.into();
         let (descriptor_set_layout, bindings) = unsafe {
-            pipeline2d::descriptor_sets::create_descriptor_set_layout(&device)?
+            pipeline2d::descriptor_sets::create_frame_set_layout(&device)?
         };
         device.name_vulkan_object(
             format!("{} - DescriptorSetLayout", owned_name.clone()),
@@ -98,44 +98,32 @@ impl FrameDescriptor {
             descriptor_pool,
             descriptor_set_layout,
             descriptor_set,
-            atlas_version: AtlasVersion::new_out_of_date(),
             device,
         })
     }
 
-    /// Update the combined image sampler descriptor based on a texture atlas.
+    /// Point the frame uniforms' binding at a buffer.
     ///
-    /// Unsafe:  it is up to the caller to make sure the image sampler is not
-    ///          currently in use by the gpu. This should be safe to invoke in
-    ///          the middle of a frame's draw call.
-    pub unsafe fn update_texture_atlas(
-        &mut self,
-        texture_atlas: &impl TextureAtlas,
-    ) {
-        if texture_atlas.version().is_out_of_date(&self.atlas_version) {
-            self.write_texture_descriptor(
-                &texture_atlas.build_descriptor_image_info(),
-            );
-            self.atlas_version = texture_atlas.version();
-        }
-    }
-
-    /// Update the combined image sampler descriptor.
+    /// # Safety
     ///
-    /// Unsafe:  it is up to the caller to make sure the image sampler is not
-    ///          currently in use by the gpu. This should be safe to invoke in
-    ///          the middle of a frame's draw call.
-    unsafe fn write_texture_descriptor(
+    /// - the descriptor set must not be in use by the gpu
+    /// - the buffer must live for as long as the descriptor set refers to it
+    pub unsafe fn write_uniform_buffer_descriptor(
         &mut self,
-        image_infos: &[vk::DescriptorImageInfo],
+        buffer: vk::Buffer,
     ) {
+        let buffer_info = vk::DescriptorBufferInfo {
+            buffer,
+            offset: 0,
+            range: vk::WHOLE_SIZE,
+        };
         let descriptor_write = vk::WriteDescriptorSet {
             dst_set: self.descriptor_set,
-            dst_binding: 0,
+            dst_binding: FRAME_UNIFORMS_BINDING,
             dst_array_element: 0,
-            descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
-            p_image_info: image_infos.as_ptr(),
-            descriptor_count: image_infos.len() as u32,
+            descriptor_type: vk::DescriptorType::UNIFORM_BUFFER,
+            p_buffer_info: &buffer_info,
+            descriptor_count: 1,
             ..Default::default()
         };
         self.device
@@ -143,9 +131,52 @@ impl FrameDescriptor {
             .update_descriptor_sets(&[descriptor_write], &[]);
     }
 
+    /// Point entries in the storage buffer array at the provided buffers.
+    ///
+    /// Entries which aren't provided keep their previous buffer, so shaders
+    /// must only read from storage buffers which have been written.
+    ///
+    /// # Safety
+    ///
+    /// - the descriptor set must not be in use by the gpu
+    /// - each buffer must live for as long as the descriptor set refers to it
+    pub unsafe fn write_storage_buffer_descriptors(
+        &mut self,
+        buffers: &[(u32, vk::Buffer)],
+    ) {
+        let buffer_infos: Vec<vk::DescriptorBufferInfo> = buffers
+            .iter()
+            .map(|(_, buffer)| vk::DescriptorBufferInfo {
+                buffer: *buffer,
+                offset: 0,
+                range: vk::WHOLE_SIZE,
+            })
+            .collect();
+        let descriptor_writes: Vec<vk::WriteDescriptorSet> = buffers
+            .iter()
+            .zip(&buffer_infos)
+            .map(|((index, _), buffer_info)| vk::WriteDescriptorSet {
+                dst_set: self.descriptor_set,
+                dst_binding: STORAGE_BUFFER_BINDING,
+                dst_array_element: *index,
+                descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+                p_buffer_info: buffer_info,
+                descriptor_count: 1,
+                ..Default::default()
+            })
+            .collect();
+        if !descriptor_writes.is_empty() {
+            self.device
+                .logical_device
+                .update_descriptor_sets(&descriptor_writes, &[]);
+        }
+    }
+
     /// Return a non-owning handle to the raw vulkan descriptor set object.
     ///
-    /// Unsafe:  it is up to the caller to synchronize usage of the set.
+    /// # Safety
+    ///
+    /// - it is up to the caller to synchronize usage of the set
     pub unsafe fn raw_descriptor_set(&self) -> vk::DescriptorSet {
         self.descriptor_set
     }
@@ -154,9 +185,22 @@ impl FrameDescriptor {
 impl Drop for FrameDescriptor {
     fn drop(&mut self) {
         unsafe {
+            // the descriptor set is freed along with its pool
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_SET,
+                &self.descriptor_set,
+            );
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_POOL,
+                &self.descriptor_pool,
+            );
             self.device
                 .logical_device
                 .destroy_descriptor_pool(self.descriptor_pool, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+                &self.descriptor_set_layout,
+            );
             self.device.logical_device.destroy_descriptor_set_layout(
                 self.descriptor_set_layout,
                 None,
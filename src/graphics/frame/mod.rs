@@ -1,26 +1,72 @@
+mod clock;
 mod descriptor;
+mod readback;
+mod storage;
 mod sync;
+mod timestamps;
+mod uniform_buffer;
+mod uniforms;
 
-use self::{descriptor::FrameDescriptor, sync::FrameSync};
+pub use self::{
+    clock::FrameClock,
+    descriptor::FrameDescriptor,
+    readback::FrameReadback,
+    storage::FrameStorage,
+    timestamps::{FrameTimestamps, MAX_GPU_ZONES},
+    uniform_buffer::FrameUniformBuffer,
+    uniforms::FrameUniforms,
+};
+
+use self::sync::FrameSync;
 
 use crate::graphics::vulkan::{
-    buffer::CpuBuffer, command_pool::ReusableCommandPool, Device,
+    buffer::{BufferSlice, TransientBuffer},
+    command_pool::{CommandPoolRegistry, CommandRecorder},
+    Device, Swapchain,
 };
 
 use anyhow::{Context, Result};
 use ash::{version::DeviceV1_0, vk};
 use std::sync::Arc;
 
+/// The descriptor set owned by each frame, bound after the texture atlas's
+/// shared set. It holds the frame uniforms and storage buffers.
+pub const FRAME_DESCRIPTOR_SET: u32 = 1;
+
+/// The descriptor binding for the frame uniforms' buffer.
+pub const FRAME_UNIFORMS_BINDING: u32 = 0;
+
 /// All per-frame resources and synchronization for this application.
 pub struct Frame {
     pub sync: FrameSync,
     pub descriptor: FrameDescriptor,
-    pub vertex_buffer: CpuBuffer,
-    pub command_pool: ReusableCommandPool,
+
+    /// Vertex, uniform, and staging data which only lives for this frame.
+    pub transient: TransientBuffer,
+
+    /// Where this frame's vertices were pushed in the transient buffer.
+    pub vertices: BufferSlice,
+    pub hairline_vertices: BufferSlice,
+    pub custom_vertices: BufferSlice,
+
+    /// The command pools shared with the other frames in flight, and this
+    /// frame's index into them.
+    pub command_pools: Arc<CommandPoolRegistry>,
+    pub index: usize,
+
     pub framebuffer: vk::Framebuffer,
+    pub image: vk::Image,
+    pub readback: FrameReadback,
+    pub storage: FrameStorage,
+    pub uniforms: FrameUniformBuffer,
+    pub timestamps: FrameTimestamps,
 
     command_buffers: Vec<vk::CommandBuffer>,
 
+    /// The recorders for every submitted command buffer, held until the
+    /// frame begins again so their pools aren't reset early.
+    recorders: Vec<CommandRecorder>,
+
     device: Arc<Device>,
 }
 
@@ -29,14 +75,20 @@ impl Frame {
     /// frame index.
     pub fn create_n_frames(
         device: &Arc<Device>,
-        framebuffers: &[vk::Framebuffer],
+        swapchain: &Swapchain,
     ) -> Result<Vec<Option<Self>>> {
         let mut result = vec![];
-        for (i, framebuffer) in framebuffers.iter().enumerate() {
-            result.push(Some(Self::new(
+        let command_pools =
+            Arc::new(CommandPoolRegistry::new(device.clone(), "Frames"));
+        let targets = swapchain.framebuffers.iter().zip(&swapchain.images);
+        for (i, (framebuffer, image)) in targets.enumerate() {
+            result.push(Some(Self::with_command_pools(
                 device.clone(),
                 *framebuffer,
+                *image,
                 format!("Frame {}", i),
+                command_pools.clone(),
+                i,
             )?));
         }
         Ok(result)
@@ -44,13 +96,40 @@ impl Frame {
 
     /// Create a new frame.
     ///
-    /// Frames do not own framebuffers, it is the responsibility of the
-    /// application to ensure no Frame instances are used after the swapchain
-    /// has been dropped.
+    /// Frames do not own framebuffers or swapchain images, it is the
+    /// responsibility of the application to ensure no Frame instances are used
+    /// after the swapchain has been dropped.
     pub fn new<Name>(
         device: Arc<Device>,
         framebuffer: vk::Framebuffer,
+        image: vk::Image,
+        name: Name,
+    ) -> Result<Self>
+    where
+        Name: Into<String> + Clone,
+    {
+        let command_pools =
+            Arc::new(CommandPoolRegistry::new(device.clone(), name.clone()));
+        Self::with_command_pools(
+            device,
+            framebuffer,
+            image,
+            name,
+            command_pools,
+            0,
+        )
+    }
+
+    /// Create a new frame which takes its command pools from a registry
+    /// shared with other frames. `index` must be unique among the frames
+    /// sharing the registry.
+    pub fn with_command_pools<Name>(
+        device: Arc<Device>,
+        framebuffer: vk::Framebuffer,
+        image: vk::Image,
         name: Name,
+        command_pools: Arc<CommandPoolRegistry>,
+        index: usize,
     ) -> Result<Self>
     where
         Name: Into<String> + Clone,
@@ -58,16 +137,25 @@ impl Frame {
         Ok(Self {
             sync: FrameSync::new(&device, name.clone())?,
             descriptor: FrameDescriptor::new(device.clone(), name.clone())?,
-            vertex_buffer: CpuBuffer::new(
-                device.clone(),
-                vk::BufferUsageFlags::VERTEX_BUFFER,
-            )?,
-            command_pool: ReusableCommandPool::new(
+            transient: TransientBuffer::new(
                 device.clone(),
-                name.clone(),
-            )?,
+                vk::BufferUsageFlags::VERTEX_BUFFER
+                    | vk::BufferUsageFlags::UNIFORM_BUFFER
+                    | vk::BufferUsageFlags::TRANSFER_SRC,
+            ),
+            vertices: BufferSlice::default(),
+            hairline_vertices: BufferSlice::default(),
+            custom_vertices: BufferSlice::default(),
+            command_pools,
+            index,
             framebuffer,
+            image,
+            readback: FrameReadback::new(device.clone()),
+            storage: FrameStorage::new(device.clone())?,
+            uniforms: FrameUniformBuffer::new(device.clone())?,
+            timestamps: FrameTimestamps::new(device.clone())?,
             command_buffers: vec![],
+            recorders: vec![],
             device,
         })
     }
@@ -75,23 +163,35 @@ impl Frame {
     /// Begin the frame's rendering operations.
     ///
     /// Blocks until the previous render with this frame has finished.
-    /// Resets the command pool used by this frame.
+    /// Resets every thread's command pool for this frame, which fails if a
+    /// thread is still holding a recorder from the previous render.
     pub fn begin_frame(&mut self) -> Result<()> {
         unsafe {
             self.wait_for_graphics_to_complete()?;
-            self.command_pool.reset()?;
+            self.recorders.clear();
+            self.command_pools.reset_frame(self.index)?;
         }
         self.command_buffers.clear();
         Ok(())
     }
 
-    /// Submit command buffers to be added to the graphics queue when the frame
-    /// is finished by the frame context.
+    /// Request a command buffer from the calling thread's pool for this
+    /// frame. The frame's pools aren't reset until the recorder is submitted
+    /// or dropped.
+    pub fn request_command_buffer(&self) -> Result<CommandRecorder> {
+        self.command_pools.request_command_buffer(self.index)
+    }
+
+    /// Submit recorded command buffers to be added to the graphics queue when
+    /// the frame is finished by the frame context.
     pub fn submit_graphics_commands(
         &mut self,
-        command_buffers: &[vk::CommandBuffer],
+        recorders: impl IntoIterator<Item = CommandRecorder>,
     ) {
-        self.command_buffers.extend_from_slice(command_buffers);
+        for recorder in recorders {
+            self.command_buffers.push(recorder.raw());
+            self.recorders.push(recorder);
+        }
     }
 
     /// Finish the frame by submitting all command buffers to the graphics
@@ -101,10 +201,43 @@ impl Frame {
         &mut self,
         image_available: vk::Semaphore,
     ) -> Result<vk::Semaphore> {
-        let wait_semaphores = [image_available];
+        self.submit(Some(image_available), true)?;
+        Ok(self.sync.render_finished_semaphore)
+    }
+
+    /// Finish a frame which was rendered into an application provided render
+    /// target.
+    ///
+    /// The submission only waits when there's an image available semaphore.
+    /// The render finished semaphore is returned when it's signaled,
+    /// otherwise a null semaphore is returned.
+    pub fn finish_render_target_frame(
+        &mut self,
+        image_available: Option<vk::Semaphore>,
+        signal_render_finished: bool,
+    ) -> Result<vk::Semaphore> {
+        self.submit(image_available, signal_render_finished)?;
+        if signal_render_finished {
+            Ok(self.sync.render_finished_semaphore)
+        } else {
+            Ok(vk::Semaphore::null())
+        }
+    }
+
+    /// Submit every command buffer to the graphics queue.
+    fn submit(
+        &mut self,
+        image_available: Option<vk::Semaphore>,
+        signal_render_finished: bool,
+    ) -> Result<()> {
+        let wait_semaphores: Vec<vk::Semaphore> =
+            image_available.into_iter().collect();
         let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
-        let render_finished_signal_semaphores =
-            [self.sync.render_finished_semaphore];
+        let render_finished_signal_semaphores = if signal_render_finished {
+            vec![self.sync.render_finished_semaphore]
+        } else {
+            vec![]
+        };
         let submit_info = [vk::SubmitInfo {
             p_wait_semaphores: wait_semaphores.as_ptr(),
             p_wait_dst_stage_mask: wait_stages.as_ptr(),
@@ -126,7 +259,27 @@ impl Frame {
                 )
                 .with_context(|| "unable to submit graphics commands!")?;
         }
-        Ok(self.sync.render_finished_semaphore)
+        Ok(())
+    }
+
+    /// Block until the frame's most recent graphics submission has completed.
+    ///
+    /// The fence is left signaled, so this can be called any number of times
+    /// without affecting the next call to `begin_frame`.
+    pub fn wait_for_graphics(&self) -> Result<()> {
+        unsafe {
+            self.device
+                .logical_device
+                .wait_for_fences(
+                    &[self.sync.graphics_finished_fence],
+                    true,
+                    u64::MAX,
+                )
+                .with_context(|| {
+                    "error while waiting for the graphics fence to complete!"
+                })?;
+        }
+        Ok(())
     }
 
     /// Called at the beginning of each frame.
@@ -135,16 +288,7 @@ impl Frame {
     /// reset the fences. Unsafe because this function must be considered in
     /// the context of a full frame and how rendering commansd are submitted.
     unsafe fn wait_for_graphics_to_complete(&mut self) -> Result<()> {
-        self.device
-            .logical_device
-            .wait_for_fences(
-                &[self.sync.graphics_finished_fence],
-                true,
-                u64::MAX,
-            )
-            .with_context(|| {
-                "error while waiting for the graphics fence to complete!"
-            })?;
+        self.wait_for_graphics()?;
         self.device
             .logical_device
             .reset_fences(&[self.sync.graphics_finished_fence])
@@ -156,7 +300,7 @@ impl Frame {
 impl Drop for Frame {
     fn drop(&mut self) {
         unsafe {
-            self.wait_for_graphics_to_complete()
+            self.wait_for_graphics()
                 .expect("error while waiting for resources to clear");
             self.sync.destroy(&self.device);
         }
@@ -0,0 +1,126 @@
+use crate::graphics::{
+    recorder::{CapturedFrame, PixelOrder},
+    vulkan::{
+        buffer::{Buffer, ReadbackBuffer},
+        Device,
+    },
+};
+
+use anyhow::{bail, Result};
+use ash::vk;
+use std::sync::Arc;
+
+/// Per-frame resources used to copy the presented swapchain image back to the
+/// cpu.
+///
+/// Each frame owns a separate readback buffer, so a capture can be read the
+/// next time the frame is acquired without stalling the frames in flight.
+pub struct FrameReadback {
+    buffer: Option<ReadbackBuffer>,
+    pending: Option<PendingCapture>,
+    device: Arc<Device>,
+}
+
+/// A capture which has been recorded but not yet read back.
+struct PendingCapture {
+    frame_number: u64,
+    extent: vk::Extent2D,
+    pixel_order: PixelOrder,
+}
+
+impl FrameReadback {
+    /// Create readback resources. No gpu memory is allocated until the first
+    /// capture is recorded.
+    pub fn new(device: Arc<Device>) -> Self {
+        Self {
+            buffer: None,
+            pending: None,
+            device,
+        }
+    }
+
+    /// The order of each pixel's bytes when reading back an image with the
+    /// given format, or None if the format can't be captured.
+    pub fn pixel_order(format: vk::Format) -> Option<PixelOrder> {
+        match format {
+            vk::Format::B8G8R8A8_SRGB | vk::Format::B8G8R8A8_UNORM => {
+                Some(PixelOrder::Bgra)
+            }
+            vk::Format::R8G8B8A8_SRGB | vk::Format::R8G8B8A8_UNORM => {
+                Some(PixelOrder::Rgba)
+            }
+            _ => None,
+        }
+    }
+
+    /// Record commands which copy the swapchain image into this frame's
+    /// readback buffer.
+    ///
+    /// # Safety
+    ///
+    /// - the image must be in the `TRANSFER_SRC_OPTIMAL` layout, the frame
+    ///   graph inserts the transition
+    /// - the image must have been created with `TRANSFER_SRC` usage
+    pub unsafe fn record_capture(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        frame_number: u64,
+    ) -> Result<()> {
+        let pixel_order = match Self::pixel_order(format) {
+            Some(pixel_order) => pixel_order,
+            None => bail!("unable to capture images with format {:?}", format),
+        };
+        let size = extent.width as u64 * extent.height as u64 * 4;
+        let too_small = self
+            .buffer
+            .as_ref()
+            .map(|buffer| buffer.size_in_bytes() < size)
+            .unwrap_or(true);
+        if too_small {
+            self.buffer = Some(ReadbackBuffer::new(self.device.clone(), size)?);
+        }
+
+        self.buffer.as_ref().unwrap().record_image_copy(
+            command_buffer,
+            image,
+            vk::Offset2D { x: 0, y: 0 },
+            extent,
+        );
+
+        self.pending = Some(PendingCapture {
+            frame_number,
+            extent,
+            pixel_order,
+        });
+        Ok(())
+    }
+
+    /// Read back the most recently recorded capture, if there is one.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must ensure that the commands which recorded the capture
+    ///   have finished executing
+    pub unsafe fn take_capture(&mut self) -> Result<Option<CapturedFrame>> {
+        let pending = match self.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(None),
+        };
+        let vk::Extent2D { width, height } = pending.extent;
+        let bytes = self
+            .buffer
+            .as_ref()
+            .unwrap()
+            .read_bytes(width as u64 * height as u64 * 4)?;
+        Ok(Some(CapturedFrame {
+            frame_number: pending.frame_number,
+            width,
+            height,
+            pixel_order: pending.pixel_order,
+            bytes,
+        }))
+    }
+}
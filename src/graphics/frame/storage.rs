@@ -0,0 +1,72 @@
+use crate::graphics::{
+    frame::FrameDescriptor,
+    storage::{StorageBuffers, MAX_STORAGE_BUFFERS},
+    vulkan::{
+        buffer::{Buffer, CpuBuffer},
+        Device,
+    },
+};
+
+use anyhow::Result;
+use ash::vk;
+use std::sync::Arc;
+
+/// The frame's gpu copy of every storage buffer.
+pub struct FrameStorage {
+    buffers: Vec<CpuBuffer>,
+
+    /// The storage revision last copied into this frame's buffers.
+    revision: Option<u64>,
+}
+
+impl FrameStorage {
+    /// Create storage resources. No gpu memory is allocated until a storage
+    /// buffer is written.
+    pub fn new(device: Arc<Device>) -> Result<Self> {
+        let buffers = (0..MAX_STORAGE_BUFFERS)
+            .map(|_| {
+                CpuBuffer::new(
+                    device.clone(),
+                    vk::BufferUsageFlags::STORAGE_BUFFER,
+                )
+            })
+            .collect::<Result<Vec<CpuBuffer>>>()?;
+        Ok(Self {
+            buffers,
+            revision: None,
+        })
+    }
+
+    /// Copy the storage buffers into this frame's gpu buffers and rewrite the
+    /// descriptors, if anything changed since the last update.
+    ///
+    /// Returns the number of bytes uploaded.
+    ///
+    /// # Safety
+    ///
+    /// - the frame's buffers and descriptor set must not be in use by the gpu
+    pub unsafe fn update(
+        &mut self,
+        storage: &StorageBuffers,
+        descriptor: &mut FrameDescriptor,
+    ) -> Result<u64> {
+        if self.revision == Some(storage.revision()) {
+            return Ok(0);
+        }
+        let mut uploaded = 0;
+        let mut descriptor_buffers = vec![];
+        for (handle, bytes) in storage.iter() {
+            // empty buffers can't be bound
+            if bytes.is_empty() {
+                continue;
+            }
+            let buffer = &mut self.buffers[handle.index() as usize];
+            buffer.write_data(bytes)?;
+            uploaded += buffer.size_in_bytes();
+            descriptor_buffers.push((handle.index(), buffer.raw()));
+        }
+        descriptor.write_storage_buffer_descriptors(&descriptor_buffers);
+        self.revision = Some(storage.revision());
+        Ok(uploaded)
+    }
+}
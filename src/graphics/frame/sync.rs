@@ -73,12 +73,24 @@ impl FrameSync {
     pub unsafe fn destroy(&mut self, device: &Device) {
         //! This function does no checking that the semaphores are done being used,
         //! that is up to the owner. (for example, wait for the device to idle)
+        device.forget_vulkan_object(
+            vk::ObjectType::SEMAPHORE,
+            &self.image_available_semaphore,
+        );
         device
             .logical_device
             .destroy_semaphore(self.image_available_semaphore, None);
+        device.forget_vulkan_object(
+            vk::ObjectType::SEMAPHORE,
+            &self.render_finished_semaphore,
+        );
         device
             .logical_device
             .destroy_semaphore(self.render_finished_semaphore, None);
+        device.forget_vulkan_object(
+            vk::ObjectType::FENCE,
+            &self.graphics_finished_fence,
+        );
         device
             .logical_device
             .destroy_fence(self.graphics_finished_fence, None);
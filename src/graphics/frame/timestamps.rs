@@ -0,0 +1,169 @@
+use crate::{
+    graphics::vulkan::Device,
+    profiling::{self, GpuZone},
+};
+
+use anyhow::Result;
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+/// The most gpu zones which can be measured in one frame.
+pub const MAX_GPU_ZONES: u32 = 8;
+
+/// Per-frame timestamp queries for gpu profiling.
+///
+/// Queries are only created when profiling is enabled and the device can
+/// write timestamps, otherwise every method does nothing.
+pub struct FrameTimestamps {
+    query_pool: vk::QueryPool,
+
+    /// The name of every zone begun this frame, in order.
+    zones: Vec<&'static str>,
+
+    /// The indices of zones which have begun but not ended.
+    open: Vec<usize>,
+
+    device: Arc<Device>,
+}
+
+impl FrameTimestamps {
+    pub fn new(device: Arc<Device>) -> Result<Self> {
+        let query_pool =
+            if profiling::ENABLED && device.timestamp_period().is_some() {
+                let create_info = vk::QueryPoolCreateInfo {
+                    query_type: vk::QueryType::TIMESTAMP,
+                    query_count: MAX_GPU_ZONES * 2,
+                    ..Default::default()
+                };
+                unsafe {
+                    device
+                        .logical_device
+                        .create_query_pool(&create_info, None)?
+                }
+            } else {
+                vk::QueryPool::null()
+            };
+        Ok(Self {
+            query_pool,
+            zones: vec![],
+            open: vec![],
+            device,
+        })
+    }
+
+    fn is_active(&self) -> bool {
+        self.query_pool != vk::QueryPool::null()
+    }
+
+    /// Reset the queries at the start of the frame's command buffer.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording, outside of a render pass
+    /// - the frame's previous submission must have finished
+    pub unsafe fn reset(&mut self, command_buffer: vk::CommandBuffer) {
+        self.zones.clear();
+        self.open.clear();
+        if self.is_active() {
+            self.device.logical_device.cmd_reset_query_pool(
+                command_buffer,
+                self.query_pool,
+                0,
+                MAX_GPU_ZONES * 2,
+            );
+        }
+    }
+
+    /// Write the timestamp which begins a zone. Zones past `MAX_GPU_ZONES`
+    /// are ignored.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording and `reset` must have been
+    ///   recorded into it
+    pub unsafe fn begin_zone(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        name: &'static str,
+    ) {
+        if !self.is_active() || self.zones.len() as u32 >= MAX_GPU_ZONES {
+            return;
+        }
+        let index = self.zones.len();
+        self.device.logical_device.cmd_write_timestamp(
+            command_buffer,
+            vk::PipelineStageFlags::TOP_OF_PIPE,
+            self.query_pool,
+            index as u32 * 2,
+        );
+        self.zones.push(name);
+        self.open.push(index);
+    }
+
+    /// Write the timestamp which ends the most recently begun zone.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording and must be the one the zone
+    ///   was begun in
+    pub unsafe fn end_zone(&mut self, command_buffer: vk::CommandBuffer) {
+        if let Some(index) = self.open.pop() {
+            self.device.logical_device.cmd_write_timestamp(
+                command_buffer,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                self.query_pool,
+                index as u32 * 2 + 1,
+            );
+        }
+    }
+
+    /// Read the zones measured by the frame's last submission. Each
+    /// submission's zones are only returned once.
+    ///
+    /// # Safety
+    ///
+    /// - the frame's most recent submission must have finished
+    pub unsafe fn take_zones(&mut self) -> Result<Vec<GpuZone>> {
+        // zones which were never ended have no end timestamp to read
+        let count = match self.open.first() {
+            Some(first_open) => *first_open,
+            None => self.zones.len(),
+        };
+        if count == 0 {
+            self.zones.clear();
+            return Ok(vec![]);
+        }
+        let mut timestamps = vec![0u64; count * 2];
+        self.device.logical_device.get_query_pool_results(
+            self.query_pool,
+            0,
+            count as u32 * 2,
+            &mut timestamps,
+            vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WAIT,
+        )?;
+        let zones = self
+            .zones
+            .drain(..)
+            .zip(timestamps.chunks(2))
+            .map(|(name, pair)| GpuZone {
+                name,
+                start: pair[0],
+                end: pair[1],
+            })
+            .collect();
+        self.open.clear();
+        Ok(zones)
+    }
+}
+
+impl Drop for FrameTimestamps {
+    fn drop(&mut self) {
+        if self.is_active() {
+            unsafe {
+                self.device
+                    .logical_device
+                    .destroy_query_pool(self.query_pool, None);
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::graphics::{
+    frame::{FrameDescriptor, FrameUniforms},
+    vulkan::{
+        buffer::{Buffer, CpuBuffer},
+        Device,
+    },
+};
+
+use anyhow::Result;
+use ash::vk;
+use std::sync::Arc;
+
+/// The frame's gpu copy of the frame uniforms.
+pub struct FrameUniformBuffer {
+    buffer: CpuBuffer,
+
+    /// The buffer last written to the frame's descriptor set.
+    descriptor_buffer: vk::Buffer,
+}
+
+impl FrameUniformBuffer {
+    /// Create the uniform buffer. No gpu memory is allocated until the
+    /// uniforms are first written.
+    pub fn new(device: Arc<Device>) -> Result<Self> {
+        Ok(Self {
+            buffer: CpuBuffer::new(
+                device,
+                vk::BufferUsageFlags::UNIFORM_BUFFER,
+            )?,
+            descriptor_buffer: vk::Buffer::null(),
+        })
+    }
+
+    /// Copy the uniforms into this frame's gpu buffer, and point the
+    /// descriptor set at the buffer when it's first allocated.
+    ///
+    /// Returns the number of bytes uploaded.
+    ///
+    /// # Safety
+    ///
+    /// - the frame's buffer and descriptor set must not be in use by the gpu
+    pub unsafe fn update(
+        &mut self,
+        uniforms: &FrameUniforms,
+        descriptor: &mut FrameDescriptor,
+    ) -> Result<u64> {
+        self.buffer.write_data(&[*uniforms])?;
+        let raw = self.buffer.raw();
+        if raw != self.descriptor_buffer {
+            descriptor.write_uniform_buffer_descriptor(raw);
+            self.descriptor_buffer = raw;
+        }
+        Ok(std::mem::size_of::<FrameUniforms>() as u64)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::graphics::frame::FrameClock;
+
+use ash::vk;
+use nalgebra as na;
+
+/// The data in every frame's uniform buffer, which any shader can read at
+/// `FRAME_UNIFORMS_BINDING` in the frame's descriptor set:
+///
+/// ```glsl
+/// layout(set = 1, binding = 0) uniform FrameUniforms {
+///     mat4 camera;
+///     vec2 resolution;
+///     float time;
+///     float deltaTime;
+/// } frame;
+/// ```
+///
+/// The layout matches std140, so the struct can be copied into the buffer
+/// as-is.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FrameUniforms {
+    /// The projection given to world space layers, with the same clip
+    /// transform as the layers' push constants.
+    pub camera: [[f32; 4]; 4],
+
+    /// The size of the layer pass's viewport, in pixels.
+    pub resolution: [f32; 2],
+
+    /// Seconds since the graphics subsystem was created.
+    pub time: f32,
+
+    /// Seconds since the previous frame.
+    pub delta_time: f32,
+}
+
+impl FrameUniforms {
+    /// Build the uniforms for a frame.
+    pub fn new(
+        camera: &na::Matrix4<f32>,
+        extent: vk::Extent2D,
+        clock: &FrameClock,
+    ) -> Self {
+        Self {
+            camera: (*camera).into(),
+            resolution: [extent.width as f32, extent.height as f32],
+            time: clock.time(),
+            delta_time: clock.delta_time(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::{
+        mem::size_of,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn the_layout_should_match_std140() {
+        let uniforms = FrameUniforms::new(
+            &na::Matrix4::identity(),
+            vk::Extent2D::default(),
+            &FrameClock::new(Instant::now()),
+        );
+        let base = &uniforms as *const FrameUniforms as usize;
+        let offset = |field: *const f32| field as usize - base;
+
+        assert_eq!(offset(uniforms.resolution.as_ptr()), 64);
+        assert_eq!(offset(&uniforms.time), 72);
+        assert_eq!(offset(&uniforms.delta_time), 76);
+        assert_eq!(size_of::<FrameUniforms>(), 80);
+    }
+
+    #[test]
+    fn new_should_copy_the_frame_state() {
+        let start = Instant::now();
+        let mut clock = FrameClock::new(start);
+        clock.tick(start + Duration::from_secs(2));
+        let camera = na::Matrix4::new_scaling(3.0);
+
+        let uniforms = FrameUniforms::new(
+            &camera,
+            vk::Extent2D {
+                width: 640,
+                height: 480,
+            },
+            &clock,
+        );
+
+        let expected: [[f32; 4]; 4] = camera.into();
+        assert_eq!(uniforms.camera, expected);
+        assert_eq!(uniforms.resolution, [640.0, 480.0]);
+        assert_eq!(uniforms.time, 2.0);
+        assert_eq!(uniforms.delta_time, 0.0);
+    }
+}
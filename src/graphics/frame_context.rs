@@ -1,6 +1,13 @@
-use crate::graphics::{
-    frame::Frame,
-    vulkan::{Device, Swapchain, WindowSurface},
+use crate::{
+    graphics::{
+        frame::Frame,
+        recorder::CapturedFrame,
+        vulkan::{
+            Device, RenderTargetProvider, Swapchain, SwapchainOptions,
+            WindowSurface,
+        },
+    },
+    profiling,
 };
 
 use anyhow::Result;
@@ -36,6 +43,14 @@ pub struct FrameContext {
     /// automatically when the frame is completed.
     current_image_acquired_semaphore: vk::Semaphore,
 
+    /// False when the render target provider didn't signal the image
+    /// acquired semaphore for the current frame.
+    current_image_acquired_signaled: bool,
+
+    /// Images provided by the application which are rendered into instead
+    /// of the window's swapchain, when set.
+    render_targets: Option<Box<dyn RenderTargetProvider>>,
+
     ///! An owning reference to the application swapchain.
     swapchain: Arc<Swapchain>,
 
@@ -47,12 +62,11 @@ impl FrameContext {
     /// Create a new Frame context.
     pub fn new(device: Arc<Device>, swapchain: Arc<Swapchain>) -> Result<Self> {
         Ok(Self {
-            frames_in_flight: Frame::create_n_frames(
-                &device,
-                &swapchain.framebuffers,
-            )?,
+            frames_in_flight: Frame::create_n_frames(&device, &swapchain)?,
             swapchain_state: SwapchainState::Ok,
             current_image_acquired_semaphore: vk::Semaphore::null(),
+            current_image_acquired_signaled: true,
+            render_targets: None,
             current_frame_index: 0,
             swapchain,
             device,
@@ -78,6 +92,11 @@ impl FrameContext {
             .sync
             .image_available_semaphore;
 
+        if self.render_targets.is_some() {
+            return self.acquire_render_target_frame();
+        }
+
+        let _zone = profiling::zone("present");
         let result = unsafe {
             self.swapchain.swapchain_loader.acquire_next_image(
                 self.swapchain.swapchain,
@@ -86,7 +105,9 @@ impl FrameContext {
                 vk::Fence::null(),
             )
         };
-        if let Err(vk::Result::ERROR_OUT_OF_DATE_KHR) = result {
+        if let Err(vk::Result::ERROR_OUT_OF_DATE_KHR)
+        | Err(vk::Result::ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT) = result
+        {
             return Err(SwapchainState::NeedsRebuild);
         }
         if let Ok((_, true)) = result {
@@ -94,7 +115,36 @@ impl FrameContext {
         }
 
         let (index, _) = result.ok().unwrap();
-        self.current_frame_index = index as usize;
+        self.current_image_acquired_signaled = true;
+        self.take_frame(index as usize)
+    }
+
+    /// Acquire the next image from the render target provider.
+    fn acquire_render_target_frame(&mut self) -> Result<Frame, SwapchainState> {
+        let render_targets = self.render_targets.as_mut().unwrap();
+        if render_targets.needs_rebuild() {
+            return Err(SwapchainState::NeedsRebuild);
+        }
+        let target = match render_targets
+            .acquire(self.current_image_acquired_semaphore)
+        {
+            Ok(target) => target,
+            Err(error) => {
+                log::error!("unable to acquire a render target {:?}", error);
+                return Err(SwapchainState::NeedsRebuild);
+            }
+        };
+        if target.index as usize >= self.frames_in_flight.len() {
+            log::error!("render target {} doesn't exist!", target.index);
+            return Err(SwapchainState::NeedsRebuild);
+        }
+        self.current_image_acquired_signaled = target.signals_image_available;
+        self.take_frame(target.index as usize)
+    }
+
+    /// Take the frame for the acquired image and begin it.
+    fn take_frame(&mut self, index: usize) -> Result<Frame, SwapchainState> {
+        self.current_frame_index = index;
 
         let mut current_frame = self.frames_in_flight[self.current_frame_index]
             .take()
@@ -109,9 +159,15 @@ impl FrameContext {
 
     /// Complete the current frame and present the framebuffer.
     pub fn return_frame(&mut self, mut frame: Frame) -> Result<()> {
+        if self.render_targets.is_some() {
+            return self.return_render_target_frame(frame);
+        }
+
         let image_acquired_semaphore = self.current_image_acquired_semaphore;
-        let render_finished_semaphore =
-            frame.finish_frame(image_acquired_semaphore)?;
+        let render_finished_semaphore = {
+            let _zone = profiling::zone("submit");
+            frame.finish_frame(image_acquired_semaphore)?
+        };
         self.frames_in_flight[self.current_frame_index] = Some(frame);
 
         let render_finished_semaphores = &[render_finished_semaphore];
@@ -127,18 +183,131 @@ impl FrameContext {
             ..Default::default()
         };
 
+        let _zone = profiling::zone("present");
         let result = unsafe {
             self.swapchain
                 .swapchain_loader
                 .queue_present(self.device.present_queue.raw(), &present_info)
         };
-        if Err(vk::Result::ERROR_OUT_OF_DATE_KHR) == result {
+        if Err(vk::Result::ERROR_OUT_OF_DATE_KHR) == result
+            || Err(vk::Result::ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT)
+                == result
+        {
             self.swapchain_state = SwapchainState::NeedsRebuild;
         }
 
         Ok(())
     }
 
+    /// Complete the current frame and release the image back to the render
+    /// target provider.
+    fn return_render_target_frame(&mut self, mut frame: Frame) -> Result<()> {
+        let render_targets = self.render_targets.as_mut().unwrap();
+        let image_acquired = if self.current_image_acquired_signaled {
+            Some(self.current_image_acquired_semaphore)
+        } else {
+            None
+        };
+        let render_finished = frame.finish_render_target_frame(
+            image_acquired,
+            render_targets.signals_render_finished(),
+        )?;
+        self.frames_in_flight[self.current_frame_index] = Some(frame);
+        render_targets.release(self.current_frame_index as u32, render_finished)
+    }
+
+    /// True when frames are rendered into an application's render targets
+    /// instead of the window's swapchain.
+    pub fn has_render_targets(&self) -> bool {
+        self.render_targets.is_some()
+    }
+
+    /// Render into the provider's images instead of the window's swapchain.
+    ///
+    /// Waits for every frame to finish, then replaces the swapchain. Returns
+    /// a clone of the new swapchain which can be used by other systems.
+    pub fn use_render_targets(
+        &mut self,
+        render_targets: Box<dyn RenderTargetProvider>,
+    ) -> Result<Arc<Swapchain>> {
+        self.render_targets = Some(render_targets);
+        let options = self.swapchain.options().clone();
+        self.rebuild_render_targets(options)
+    }
+
+    /// Stop rendering into the application's render targets and build a
+    /// swapchain for the window again.
+    ///
+    /// The render target provider is dropped after every frame has finished.
+    /// It's kept when the window's swapchain can't be built.
+    pub fn use_window_surface(
+        &mut self,
+        window_surface: &dyn WindowSurface,
+    ) -> Result<Arc<Swapchain>> {
+        let render_targets = self.render_targets.take();
+        let result = self.rebuild_swapchain(window_surface);
+        if result.is_err() {
+            self.render_targets = render_targets;
+        }
+        result
+    }
+
+    /// Wait for every frame to finish, then wrap the render target
+    /// provider's current images.
+    fn rebuild_render_targets(
+        &mut self,
+        options: SwapchainOptions,
+    ) -> Result<Arc<Swapchain>> {
+        self.wait_for_frames()?;
+        self.frames_in_flight.clear();
+        self.swapchain.release_full_screen_exclusive();
+        self.swapchain = Swapchain::for_render_targets(
+            self.device.clone(),
+            self.render_targets.as_deref().unwrap(),
+            options,
+        )?;
+        self.frames_in_flight =
+            Frame::create_n_frames(&self.device, &self.swapchain)?;
+        self.swapchain_state = SwapchainState::Ok;
+
+        Ok(self.swapchain.clone())
+    }
+
+    /// Block until every frame's graphics commands have completed and the
+    /// presentation queue is done with the frames' semaphores.
+    ///
+    /// Only the frames' own work is waited on, so unrelated submissions can
+    /// keep running while frames are torn down.
+    pub fn wait_for_frames(&self) -> Result<()> {
+        for frame in self.frames_in_flight.iter().flatten() {
+            frame.wait_for_graphics()?;
+        }
+        unsafe {
+            // presentation waits on each frame's render finished semaphore,
+            // which can't be destroyed until the wait has happened
+            self.device
+                .logical_device
+                .queue_wait_idle(self.device.present_queue.raw())?;
+        }
+        Ok(())
+    }
+
+    /// Wait for every frame's rendering operations to complete, then read
+    /// back every capture which is still pending in a frame.
+    pub fn take_pending_captures(&mut self) -> Result<Vec<CapturedFrame>> {
+        let mut captures = vec![];
+        unsafe {
+            for frame in self.frames_in_flight.iter_mut().flatten() {
+                // SAFE: the frame's capture commands are complete
+                frame.wait_for_graphics()?;
+                if let Some(capture) = frame.readback.take_capture()? {
+                    captures.push(capture);
+                }
+            }
+        }
+        Ok(captures)
+    }
+
     /// Wait for all rendering operations to complete on every frame, then
     /// rebuild the swapchain.
     ///
@@ -147,30 +316,74 @@ impl FrameContext {
         &mut self,
         window_surface: &dyn WindowSurface,
     ) -> Result<Arc<Swapchain>> {
-        unsafe {
-            self.device.logical_device.device_wait_idle()?;
-            self.frames_in_flight.clear();
+        let options = self.swapchain.options().clone();
+        self.rebuild_swapchain_with_options(window_surface, options)
+    }
+
+    /// Wait for all rendering operations to complete on every frame, then
+    /// rebuild the swapchain with new options.
+    ///
+    /// Full screen exclusive mode is released from the old swapchain and
+    /// acquired again by the new one if it was held.
+    ///
+    /// The render target provider's images are queried again instead when
+    /// frames are rendered into an application's render targets.
+    pub fn rebuild_swapchain_with_options(
+        &mut self,
+        window_surface: &dyn WindowSurface,
+        options: SwapchainOptions,
+    ) -> Result<Arc<Swapchain>> {
+        if self.render_targets.is_some() {
+            return self.rebuild_render_targets(options);
+        }
+        self.wait_for_frames()?;
+        self.frames_in_flight.clear();
+        let was_exclusive = self.swapchain.is_full_screen_exclusive_acquired();
+        self.swapchain.release_full_screen_exclusive();
+        self.swapchain = self
+            .swapchain
+            .rebuild_with_options(window_surface, options)?;
+        if was_exclusive {
+            self.swapchain.acquire_full_screen_exclusive();
         }
-        self.swapchain = self.swapchain.rebuild(window_surface)?;
         self.frames_in_flight =
-            Frame::create_n_frames(&self.device, &self.swapchain.framebuffers)?;
+            Frame::create_n_frames(&self.device, &self.swapchain)?;
         self.swapchain_state = SwapchainState::Ok;
 
         Ok(self.swapchain.clone())
     }
+
+    /// Wait for every frame to finish, then destroy the window's swapchain so
+    /// the window surface can be destroyed.
+    ///
+    /// Frames can't be acquired until the swapchain is rebuilt for a new
+    /// surface. The old swapchain isn't handed to the new one because its
+    /// surface is already gone. Does nothing when frames are rendered into
+    /// an application's render targets.
+    pub fn suspend(&mut self) -> Result<()> {
+        if self.render_targets.is_some() || self.swapchain.is_suspended() {
+            return Ok(());
+        }
+        self.wait_for_frames()?;
+        self.frames_in_flight.clear();
+        self.swapchain.release_full_screen_exclusive();
+        self.swapchain = self.swapchain.suspended()?;
+        self.swapchain_state = SwapchainState::NeedsRebuild;
+        Ok(())
+    }
+
+    /// True after `suspend` until the swapchain is rebuilt.
+    pub fn is_suspended(&self) -> bool {
+        self.swapchain.is_suspended()
+    }
 }
 
 impl Drop for FrameContext {
     fn drop(&mut self) {
-        unsafe {
-            // don't delete anything until the GPU has stoped using our
-            // resources
-            self.device
-                .logical_device
-                .device_wait_idle()
-                .expect("wait for device to idle");
-
-            self.frames_in_flight.clear();
-        }
+        // don't delete anything until the GPU has stoped using our
+        // resources
+        self.wait_for_frames()
+            .expect("wait for frames to finish rendering");
+        self.frames_in_flight.clear();
     }
 }
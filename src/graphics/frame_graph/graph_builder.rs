@@ -0,0 +1,166 @@
+use super::{
+    FrameGraph, Pass, PendingBarrier, ResourceTracker, ResourceUse,
+    ScheduledPass,
+};
+
+use crate::graphics::vulkan::Device;
+
+use anyhow::{Context, Result};
+use ash::vk;
+
+impl<'a> FrameGraph<'a> {
+    /// Create a graph with no passes.
+    pub fn new() -> Self {
+        Self { passes: vec![] }
+    }
+
+    /// Add a pass which runs after every pass added before it.
+    pub fn add_pass<F>(
+        &mut self,
+        name: impl Into<String>,
+        uses: Vec<ResourceUse>,
+        record: F,
+    ) where
+        F: FnOnce(&Device, vk::CommandBuffer) -> Result<()> + 'a,
+    {
+        self.passes.push(Pass {
+            name: name.into(),
+            uses,
+            record: Box::new(record),
+        });
+    }
+
+    /// Add a pass which records no commands, so resources are left the way
+    /// the pass declares, like returning an image to a layout for the next
+    /// frame.
+    pub fn add_transition(
+        &mut self,
+        name: impl Into<String>,
+        uses: Vec<ResourceUse>,
+    ) {
+        self.add_pass(name, uses, |_, _| Ok(()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// The barriers which would be recorded before each pass, starting from
+    /// the tracker's current state.
+    pub fn schedule(&self, tracker: &ResourceTracker) -> Vec<ScheduledPass> {
+        let mut tracker = tracker.clone();
+        self.passes
+            .iter()
+            .map(|pass| ScheduledPass {
+                name: pass.name.clone(),
+                barriers: barriers_for(&mut tracker, &pass.uses),
+            })
+            .collect()
+    }
+
+    /// Record every pass in order with the barriers it needs.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording and outside of a render pass
+    /// - every pass must use resources as it declared
+    pub unsafe fn execute(
+        self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        swapchain_image: vk::Image,
+        tracker: &mut ResourceTracker,
+    ) -> Result<()> {
+        for pass in self.passes {
+            for barrier in barriers_for(tracker, &pass.uses) {
+                barrier.record(device, command_buffer, swapchain_image);
+            }
+            let name = pass.name;
+            (pass.record)(device, command_buffer)
+                .with_context(|| format!("unable to record pass {}", name))?;
+        }
+        Ok(())
+    }
+}
+
+fn barriers_for(
+    tracker: &mut ResourceTracker,
+    uses: &[ResourceUse],
+) -> Vec<PendingBarrier> {
+    uses.iter()
+        .filter_map(|usage| tracker.transition(*usage))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::frame_graph::FrameResource;
+
+    fn swapchain_use(
+        stage: vk::PipelineStageFlags,
+        access: vk::AccessFlags,
+        layout: vk::ImageLayout,
+    ) -> ResourceUse {
+        ResourceUse {
+            resource: FrameResource::SwapchainImage,
+            stage,
+            access,
+            layout,
+        }
+    }
+
+    fn transfer_read() -> ResourceUse {
+        swapchain_use(
+            vk::PipelineStageFlags::TRANSFER,
+            vk::AccessFlags::TRANSFER_READ,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        )
+    }
+
+    #[test]
+    fn consecutive_copies_should_share_one_transition() {
+        let mut tracker = ResourceTracker::new();
+        tracker.assume(swapchain_use(
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+        ));
+
+        let mut graph = FrameGraph::new();
+        graph.add_pass("feedback", vec![transfer_read()], |_, _| Ok(()));
+        graph.add_pass("capture", vec![transfer_read()], |_, _| Ok(()));
+        graph.add_transition(
+            "present",
+            vec![swapchain_use(
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::AccessFlags::empty(),
+                vk::ImageLayout::PRESENT_SRC_KHR,
+            )],
+        );
+
+        let schedule = graph.schedule(&tracker);
+        let barrier_counts: Vec<usize> =
+            schedule.iter().map(|pass| pass.barriers.len()).collect();
+        assert_eq!(barrier_counts, vec![1, 0, 1]);
+        assert_eq!(
+            schedule[0].barriers[0].new_layout,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL
+        );
+        assert_eq!(
+            schedule[2].barriers[0].src_stage,
+            vk::PipelineStageFlags::TRANSFER
+        );
+
+        // scheduling doesn't change the tracker
+        assert_eq!(
+            tracker.layout(&FrameResource::SwapchainImage),
+            Some(vk::ImageLayout::PRESENT_SRC_KHR)
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! A minimal frame graph which orders the passes recorded into a frame's
+//! command buffer and inserts the barriers between them.
+//!
+//! Each pass declares the resources it reads and writes with a
+//! `ResourceUse`. A `ResourceTracker` remembers the last use of every
+//! resource, so a barrier and layout transition is only recorded when a pass
+//! needs to wait for earlier work. Passes run in the order they were added.
+//!
+//! Work which happens outside of the graph, like the layer render pass,
+//! tells the tracker what it left behind with `ResourceTracker::assume`.
+
+mod graph_builder;
+mod pending_barrier;
+mod resource_tracker;
+
+use crate::graphics::vulkan::Device;
+
+use anyhow::Result;
+use ash::vk;
+use std::collections::HashMap;
+
+/// A resource which passes can declare a dependency on.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FrameResource {
+    /// The swapchain image the frame is presenting.
+    SwapchainImage,
+
+    /// A single-mip, single-layer color image owned by the application.
+    Image(vk::Image),
+
+    Buffer(vk::Buffer),
+}
+
+/// How a pass uses a resource.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceUse {
+    pub resource: FrameResource,
+
+    /// The pipeline stages which use the resource.
+    pub stage: vk::PipelineStageFlags,
+
+    /// The kind of access, like SHADER_READ or TRANSFER_WRITE.
+    pub access: vk::AccessFlags,
+
+    /// The layout the pass expects images to be in. Ignored for buffers.
+    pub layout: vk::ImageLayout,
+}
+
+/// A barrier needed before a resource can be used.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PendingBarrier {
+    pub resource: FrameResource,
+    pub src_stage: vk::PipelineStageFlags,
+    pub dst_stage: vk::PipelineStageFlags,
+    pub src_access: vk::AccessFlags,
+    pub dst_access: vk::AccessFlags,
+    pub old_layout: vk::ImageLayout,
+    pub new_layout: vk::ImageLayout,
+}
+
+/// The most recent use of every resource during a frame.
+#[derive(Clone, Debug, Default)]
+pub struct ResourceTracker {
+    states: HashMap<FrameResource, ResourceUse>,
+}
+
+/// Records a pass's commands.
+pub type RecordPass<'a> =
+    Box<dyn FnOnce(&Device, vk::CommandBuffer) -> Result<()> + 'a>;
+
+/// A named unit of work and the resources it uses.
+pub struct Pass<'a> {
+    name: String,
+    uses: Vec<ResourceUse>,
+    record: RecordPass<'a>,
+}
+
+/// A pass and the barriers recorded before it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledPass {
+    pub name: String,
+    pub barriers: Vec<PendingBarrier>,
+}
+
+/// Passes which will be recorded in order.
+#[derive(Default)]
+pub struct FrameGraph<'a> {
+    passes: Vec<Pass<'a>>,
+}
@@ -0,0 +1,71 @@
+use super::{FrameResource, PendingBarrier};
+
+use crate::graphics::vulkan::Device;
+
+use ash::{version::DeviceV1_0, vk};
+
+impl PendingBarrier {
+    /// Record the barrier.
+    ///
+    /// Images are transitioned as a single color mip level and array layer.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording and outside of a render pass
+    /// - `swapchain_image` is used for `FrameResource::SwapchainImage`
+    pub unsafe fn record(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        swapchain_image: vk::Image,
+    ) {
+        let image = match self.resource {
+            FrameResource::SwapchainImage => Some(swapchain_image),
+            FrameResource::Image(image) => Some(image),
+            FrameResource::Buffer(_) => None,
+        };
+        let image_barriers: Vec<vk::ImageMemoryBarrier> = image
+            .into_iter()
+            .map(|image| vk::ImageMemoryBarrier {
+                old_layout: self.old_layout,
+                new_layout: self.new_layout,
+                src_access_mask: self.src_access,
+                dst_access_mask: self.dst_access,
+                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                image,
+                subresource_range: vk::ImageSubresourceRange {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                },
+                ..Default::default()
+            })
+            .collect();
+        let buffer_barriers: Vec<vk::BufferMemoryBarrier> = match self.resource
+        {
+            FrameResource::Buffer(buffer) => vec![vk::BufferMemoryBarrier {
+                src_access_mask: self.src_access,
+                dst_access_mask: self.dst_access,
+                src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+                buffer,
+                offset: 0,
+                size: vk::WHOLE_SIZE,
+                ..Default::default()
+            }],
+            _ => vec![],
+        };
+        device.logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            self.src_stage,
+            self.dst_stage,
+            vk::DependencyFlags::empty(),
+            &[],
+            &buffer_barriers,
+            &image_barriers,
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use super::{FrameResource, PendingBarrier, ResourceTracker, ResourceUse};
+
+use ash::vk;
+use std::collections::HashMap;
+
+impl ResourceTracker {
+    /// Create a tracker where nothing has been used yet this frame.
+    pub fn new() -> Self {
+        Self {
+            states: HashMap::new(),
+        }
+    }
+
+    /// Record a use which happened outside of any node, like the layer
+    /// pass, without emitting a barrier.
+    pub fn assume(&mut self, state: ResourceUse) {
+        self.states.insert(state.resource, state);
+    }
+
+    /// Record that an image's contents aren't needed, because it's about to
+    /// be overwritten or because it aliases memory used by other images.
+    ///
+    /// The first use transitions the image from `UNDEFINED` and waits for
+    /// any earlier work, which may have touched the same memory through
+    /// another image.
+    pub fn assume_undefined(&mut self, image: vk::Image) {
+        self.assume(ResourceUse {
+            resource: FrameResource::Image(image),
+            stage: vk::PipelineStageFlags::ALL_COMMANDS,
+            access: vk::AccessFlags::MEMORY_WRITE,
+            layout: vk::ImageLayout::UNDEFINED,
+        });
+    }
+
+    /// Record a use of the resource, returning the barrier needed first.
+    ///
+    /// Resources which haven't been used this frame are assumed to have
+    /// been written by earlier work in any stage, and images are assumed to
+    /// already be in the requested layout.
+    pub fn transition(&mut self, next: ResourceUse) -> Option<PendingBarrier> {
+        let previous = self.states.insert(next.resource, next);
+        let previous = previous.unwrap_or(ResourceUse {
+            stage: vk::PipelineStageFlags::ALL_COMMANDS,
+            access: vk::AccessFlags::MEMORY_WRITE,
+            ..next
+        });
+
+        let layout_changes = match next.resource {
+            FrameResource::Buffer(_) => false,
+            _ => previous.layout != next.layout,
+        };
+        let previous_writes = is_write(previous.access);
+        let next_writes = is_write(next.access);
+        if !layout_changes && !previous_writes && !next_writes {
+            // reads after reads don't need to wait on each other
+            if let Some(state) = self.states.get_mut(&next.resource) {
+                state.stage |= previous.stage;
+                state.access |= previous.access;
+            }
+            return None;
+        }
+
+        Some(PendingBarrier {
+            resource: next.resource,
+            src_stage: previous.stage,
+            dst_stage: next.stage,
+            // only writes need to be made available
+            src_access: previous.access & write_mask(),
+            dst_access: next.access,
+            old_layout: previous.layout,
+            new_layout: next.layout,
+        })
+    }
+
+    /// Barriers which make every write so far visible to the layer pass.
+    ///
+    /// The layer pass reads vertex buffers and samples images, so writes
+    /// must be finished before vertex input and any shader stage.
+    pub fn barriers_for_layer_pass(&mut self) -> Vec<PendingBarrier> {
+        let written: Vec<ResourceUse> = self
+            .states
+            .values()
+            .filter(|state| is_write(state.access))
+            .copied()
+            .collect();
+        written
+            .into_iter()
+            .filter_map(|state| {
+                self.transition(ResourceUse {
+                    stage: vk::PipelineStageFlags::VERTEX_INPUT
+                        | vk::PipelineStageFlags::VERTEX_SHADER
+                        | vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    access: vk::AccessFlags::VERTEX_ATTRIBUTE_READ
+                        | vk::AccessFlags::SHADER_READ,
+                    ..state
+                })
+            })
+            .collect()
+    }
+
+    /// The layout a resource was last used in, if it's been used.
+    pub fn layout(&self, resource: &FrameResource) -> Option<vk::ImageLayout> {
+        self.states.get(resource).map(|state| state.layout)
+    }
+}
+
+fn write_mask() -> vk::AccessFlags {
+    vk::AccessFlags::SHADER_WRITE
+        | vk::AccessFlags::COLOR_ATTACHMENT_WRITE
+        | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE
+        | vk::AccessFlags::TRANSFER_WRITE
+        | vk::AccessFlags::HOST_WRITE
+        | vk::AccessFlags::MEMORY_WRITE
+}
+
+fn is_write(access: vk::AccessFlags) -> bool {
+    access.intersects(write_mask())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn buffer() -> FrameResource {
+        FrameResource::Buffer(vk::Buffer::null())
+    }
+
+    fn use_of(
+        resource: FrameResource,
+        stage: vk::PipelineStageFlags,
+        access: vk::AccessFlags,
+        layout: vk::ImageLayout,
+    ) -> ResourceUse {
+        ResourceUse {
+            resource,
+            stage,
+            access,
+            layout,
+        }
+    }
+
+    #[test]
+    fn reads_after_writes_should_wait_for_the_write() {
+        let mut tracker = ResourceTracker::new();
+        tracker.transition(use_of(
+            buffer(),
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::AccessFlags::SHADER_WRITE,
+            vk::ImageLayout::UNDEFINED,
+        ));
+        let barrier = tracker
+            .transition(use_of(
+                buffer(),
+                vk::PipelineStageFlags::TRANSFER,
+                vk::AccessFlags::TRANSFER_READ,
+                vk::ImageLayout::UNDEFINED,
+            ))
+            .unwrap();
+        assert_eq!(barrier.src_stage, vk::PipelineStageFlags::COMPUTE_SHADER);
+        assert_eq!(barrier.src_access, vk::AccessFlags::SHADER_WRITE);
+        assert_eq!(barrier.dst_access, vk::AccessFlags::TRANSFER_READ);
+    }
+
+    #[test]
+    fn reads_after_reads_should_not_need_a_barrier() {
+        let mut tracker = ResourceTracker::new();
+        tracker.assume(use_of(
+            buffer(),
+            vk::PipelineStageFlags::VERTEX_INPUT,
+            vk::AccessFlags::VERTEX_ATTRIBUTE_READ,
+            vk::ImageLayout::UNDEFINED,
+        ));
+        let barrier = tracker.transition(use_of(
+            buffer(),
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::AccessFlags::SHADER_READ,
+            vk::ImageLayout::UNDEFINED,
+        ));
+        assert_eq!(barrier, None);
+
+        // a later write waits for both reads
+        let barrier = tracker
+            .transition(use_of(
+                buffer(),
+                vk::PipelineStageFlags::TRANSFER,
+                vk::AccessFlags::TRANSFER_WRITE,
+                vk::ImageLayout::UNDEFINED,
+            ))
+            .unwrap();
+        assert_eq!(
+            barrier.src_stage,
+            vk::PipelineStageFlags::COMPUTE_SHADER
+                | vk::PipelineStageFlags::VERTEX_INPUT
+        );
+        assert!(barrier.src_access.is_empty());
+    }
+
+    #[test]
+    fn layout_changes_should_always_need_a_barrier() {
+        let mut tracker = ResourceTracker::new();
+        tracker.assume(use_of(
+            FrameResource::SwapchainImage,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            vk::ImageLayout::PRESENT_SRC_KHR,
+        ));
+        let barrier = tracker
+            .transition(use_of(
+                FrameResource::SwapchainImage,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::AccessFlags::TRANSFER_WRITE,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            ))
+            .unwrap();
+        assert_eq!(barrier.old_layout, vk::ImageLayout::PRESENT_SRC_KHR);
+        assert_eq!(barrier.new_layout, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+        assert_eq!(
+            tracker.layout(&FrameResource::SwapchainImage),
+            Some(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+        );
+    }
+
+    #[test]
+    fn undefined_images_should_wait_for_any_earlier_work() {
+        let mut tracker = ResourceTracker::new();
+        tracker.assume_undefined(vk::Image::null());
+        let barrier = tracker
+            .transition(use_of(
+                FrameResource::Image(vk::Image::null()),
+                vk::PipelineStageFlags::TRANSFER,
+                vk::AccessFlags::TRANSFER_WRITE,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            ))
+            .unwrap();
+        assert_eq!(barrier.old_layout, vk::ImageLayout::UNDEFINED);
+        assert_eq!(barrier.src_stage, vk::PipelineStageFlags::ALL_COMMANDS);
+        assert_eq!(barrier.src_access, vk::AccessFlags::MEMORY_WRITE);
+    }
+
+    #[test]
+    fn writes_should_be_made_visible_to_the_layer_pass() {
+        let mut tracker = ResourceTracker::new();
+        let image = FrameResource::Image(vk::Image::null());
+        tracker.transition(use_of(
+            image,
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::AccessFlags::SHADER_WRITE,
+            vk::ImageLayout::GENERAL,
+        ));
+        tracker.transition(use_of(
+            buffer(),
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::AccessFlags::SHADER_READ,
+            vk::ImageLayout::UNDEFINED,
+        ));
+        let barriers = tracker.barriers_for_layer_pass();
+        assert_eq!(barriers.len(), 1);
+        assert_eq!(barriers[0].resource, image);
+        assert_eq!(barriers[0].new_layout, vk::ImageLayout::GENERAL);
+        assert!(tracker.barriers_for_layer_pass().is_empty());
+    }
+}
@@ -1,33 +1,136 @@
 use super::Graphics;
 
-use crate::graphics::{
-    frame::Frame,
-    frame_context::FrameContext,
-    layer::{Layer, LayerHandle, LayerStack},
-    pipeline2d::Pipeline2d,
-    texture_atlas::GpuAtlas,
-    vulkan::{Device, Swapchain, WindowSurface},
+use crate::{
+    graphics::{
+        assets::AssetRegistry,
+        cameras::Cameras,
+        canvas::Canvas,
+        command_queue::CommandQueue,
+        describe::ResourceUsage,
+        ext::{
+            DecodedTexture, TextureColorOptions, TextureLoader, TextureSource,
+        },
+        frame::{Frame, FrameClock, FRAME_DESCRIPTOR_SET},
+        frame_context::FrameContext,
+        frame_graph::ResourceTracker,
+        hairline::HairlinePipeline,
+        layer::{Layer, LayerHandle, LayerStack},
+        pipeline2d::Pipeline2d,
+        render_node::RenderNodes,
+        report::RenderReport,
+        storage::StorageBuffers,
+        texture_atlas::{
+            CachedAtlas, GpuAtlas, TextureAtlas, TextureHandle,
+            ATLAS_DESCRIPTOR_SET,
+        },
+        vulkan::{
+            Device, EnabledFeatures, ExtensionRequests, Swapchain,
+            SwapchainInfo, SwapchainOptions, WindowSurface,
+        },
+    },
+    profiling::{self, GpuProfiler},
 };
 
 use anyhow::Result;
+use ash::vk;
+use std::{sync::Arc, time::Instant};
 
 impl Graphics {
     /// Instantiate the graphics subsystem.
     pub fn new(window_surface: &dyn WindowSurface) -> Result<Self> {
-        let device = Device::new(window_surface)?;
-        let swapchain = Swapchain::new(device.clone(), window_surface, None)?;
+        Self::with_swapchain_options(
+            window_surface,
+            SwapchainOptions::default(),
+        )
+    }
+
+    /// Instantiate the graphics subsystem with control over how the
+    /// swapchain buffers and presents frames.
+    pub fn with_swapchain_options(
+        window_surface: &dyn WindowSurface,
+        swapchain_options: SwapchainOptions,
+    ) -> Result<Self> {
+        Self::with_extensions(
+            window_surface,
+            &ExtensionRequests::new(),
+            swapchain_options,
+        )
+    }
+
+    /// Instantiate the graphics subsystem with additional device extensions.
+    ///
+    /// Fails if any required extension is unsupported. Check which optional
+    /// extensions were enabled with `enabled_features`.
+    pub fn with_extensions(
+        window_surface: &dyn WindowSurface,
+        device_extensions: &ExtensionRequests,
+        swapchain_options: SwapchainOptions,
+    ) -> Result<Self> {
+        let device =
+            Device::with_extensions(window_surface, device_extensions)?;
+        Self::with_device(device, window_surface, swapchain_options)
+    }
+
+    /// Instantiate the graphics subsystem with a device the application
+    /// created, like one picked with `Device::with_policy`.
+    pub fn with_device(
+        device: Arc<Device>,
+        window_surface: &dyn WindowSurface,
+        swapchain_options: SwapchainOptions,
+    ) -> Result<Self> {
+        let swapchain = Swapchain::new(
+            device.clone(),
+            window_surface,
+            None,
+            swapchain_options,
+        )?;
 
         let frame_context =
             FrameContext::new(device.clone(), swapchain.clone())?;
         let pipeline2d = Pipeline2d::new(device.clone(), &swapchain)?;
-        let texture_atlas = GpuAtlas::new(device.clone())?;
+        let hairline_pipeline =
+            HairlinePipeline::new(device.clone(), &swapchain)?;
+        let texture_atlas = CachedAtlas::new(GpuAtlas::new(device.clone())?);
         let layer_stack = LayerStack::new();
 
         Ok(Self {
             pipeline2d,
+            hairline_pipeline,
+            custom_pipelines: vec![],
             texture_atlas,
             frame_context,
             layer_stack,
+            canvas: Canvas::new(),
+            canvas_layer: None,
+            recorder: None,
+            snapshots: None,
+            report: RenderReport::default(),
+            report_log: None,
+            gpu_profiler: GpuProfiler::new(&device)?,
+            metrics: None,
+            last_frame_end: None,
+            frame_clock: FrameClock::new(Instant::now()),
+            id_pass: None,
+            feedback: None,
+            particles: None,
+            color_grading: None,
+            temporal_aa: None,
+            render_scale: None,
+            export_target: None,
+            shader_canvas: None,
+            render_nodes: RenderNodes::new(),
+            resource_tracker: ResourceTracker::new(),
+            assets: AssetRegistry::new(),
+            asset_loader: None,
+            storage_buffers: StorageBuffers::new(),
+            command_queue: CommandQueue::new(),
+            resource_usage: ResourceUsage::new(),
+            wireframe: false,
+            world_projection: nalgebra::Matrix4::identity(),
+            camera: None,
+            cameras: Cameras::new(),
+            virtual_resolution: None,
+            frame_number: 0,
             clear_color: [0.0, 0.0, 0.0, 1.0],
             device,
         })
@@ -47,6 +150,68 @@ impl Graphics {
         self.layer_stack.add_layer_to_bottom()
     }
 
+    /// The instance and device extensions and device features which were
+    /// enabled.
+    pub fn enabled_features(&self) -> EnabledFeatures {
+        self.device.enabled_features()
+    }
+
+    /// The largest anisotropy samplers can use, or None when anisotropic
+    /// filtering isn't supported.
+    pub fn max_sampler_anisotropy(&self) -> Option<f32> {
+        self.device.max_sampler_anisotropy()
+    }
+
+    /// Read a texture file and add it to the texture atlas.
+    ///
+    /// Files which are already in the atlas aren't read again, the existing
+    /// texture's handle is returned instead.
+    pub fn add_texture_file(
+        &mut self,
+        path: impl Into<String>,
+    ) -> Result<TextureHandle> {
+        let path = path.into();
+        if let Some(handle) = self.texture_atlas.cached_texture(&path) {
+            return Ok(handle);
+        }
+        let texture = self.read_texture_file(path)?;
+        self.add_texture(texture)
+    }
+
+    /// Read many textures and add them to the texture atlas, uploading all of
+    /// them with a single submission.
+    ///
+    /// Handles are returned in the same order as the sources. Files which
+    /// are already in the atlas aren't read again, just like
+    /// [Self::add_texture_file].
+    pub fn add_textures(
+        &mut self,
+        sources: &[TextureSource],
+    ) -> Result<Vec<TextureHandle>> {
+        let mut handles = vec![None; sources.len()];
+        let mut decoded = vec![];
+        let mut decoded_indices = vec![];
+        for (index, source) in sources.iter().enumerate() {
+            if let TextureSource::File(path) = source {
+                if let Some(handle) = self.texture_atlas.cached_texture(path) {
+                    handles[index] = Some(handle);
+                    continue;
+                }
+            }
+            decoded.push(DecodedTexture::from_source(
+                *source,
+                TextureColorOptions::default(),
+            )?);
+            decoded_indices.push(index);
+        }
+
+        let textures = DecodedTexture::upload_batch(&self.device, decoded)?;
+        for (index, texture) in decoded_indices.into_iter().zip(textures) {
+            handles[index] = Some(self.add_texture(texture)?);
+        }
+        Ok(handles.into_iter().map(Option::unwrap).collect())
+    }
+
     /// Return a mutable reference to the layer referenced by the handle
     ///
     /// PANICs if the layer handle doesn't refer to an actual layer.
@@ -57,56 +222,277 @@ impl Graphics {
     }
 
     /// Render a single frame to the screen.
+    ///
+    /// Commands queued from other threads are applied first. Nothing is
+    /// drawn while the window has no area, like when it's minimized.
     pub fn render(&mut self, window_surface: &dyn WindowSurface) -> Result<()> {
+        {
+            let _zone = profiling::zone("build");
+            self.apply_queued_commands()?;
+            self.release_dropped_textures()?;
+            self.reload_changed_shader_canvas()?;
+            self.resolve_layer_projections();
+            self.flush_canvas();
+        }
+        if self.frame_context.is_suspended() {
+            // there's no surface to render to until `resume` is called
+            return Ok(());
+        }
         if let Ok(mut frame) = self.frame_context.acquire_frame() {
+            self.begin_snapshot();
+            self.report = RenderReport::for_frame(self.frame_number);
+            self.tick_frame_clock();
             self.draw_to_frame(&mut frame)?;
-            self.frame_context.return_frame(frame)?
-        } else {
+            self.frame_context.return_frame(frame)?;
+            profiling::frame_mark();
+            self.end_snapshot();
+            self.finish_report();
+            self.record_frame_metrics();
+            self.frame_number += 1;
+        } else if self.frame_context.has_render_targets()
+            || Swapchain::surface_has_area(&self.device, window_surface)?
+        {
             self.rebuild_swapchain(window_surface)?;
         }
         Ok(())
     }
 
+    /// Destroy textures whose last `TextureRef` was dropped. Frames are only
+    /// waited on when there's something to release.
+    fn release_dropped_textures(&mut self) -> Result<()> {
+        if self.texture_atlas.has_dropped_textures() {
+            self.frame_context.wait_for_frames()?;
+            // SAFE: no frame is using the atlas after the wait
+            unsafe {
+                self.texture_atlas.release_dropped_textures()?;
+            }
+        }
+        Ok(())
+    }
+
     fn draw_to_frame(&mut self, frame: &mut Frame) -> Result<()> {
+        // SAFE: the frame's prior submission completed when it was acquired
+        if let Some(capture) = unsafe { frame.readback.take_capture()? } {
+            if let Some(recorder) = &mut self.recorder {
+                recorder.submit(capture)?;
+            }
+        }
+        // SAFE: the frame's prior submission completed when it was acquired
+        let gpu_zones = unsafe { frame.timestamps.take_zones()? };
+        self.gpu_profiler.upload(&gpu_zones);
+
         let all_vertices = self.layer_stack.vertices();
-        if all_vertices.len() == 0 {
+        let all_hairline_vertices = self.layer_stack.hairline_vertices();
+        let all_custom_bytes = self.layer_stack.custom_vertex_bytes();
+        if all_vertices.is_empty()
+            && all_hairline_vertices.is_empty()
+            && all_custom_bytes.is_empty()
+            && self.particles.is_none()
+            && self.shader_canvas.is_none()
+        {
+            self.write_frame_uniforms(frame)?;
+            let _zone = profiling::zone("record");
             let graphics_commands = self.record_no_op_commands(frame)?;
-            frame.submit_graphics_commands(&[graphics_commands]);
+            frame.submit_graphics_commands(vec![graphics_commands]);
         } else {
-            // Fill per-frame gpu resources with the relevant data.
-            // SAFE: because resources are not shared between frames.
-            unsafe {
-                frame.descriptor.update_texture_atlas(&self.texture_atlas);
-                frame.vertex_buffer.write_data_arrays(&all_vertices)?;
+            {
+                let _zone = profiling::zone("upload");
+                self.write_frame_data(frame)?;
             }
-
+            let _zone = profiling::zone("record");
             let graphics_commands = self.record_layer_draw_commands(frame)?;
-            frame.submit_graphics_commands(&[graphics_commands]);
+            frame.submit_graphics_commands(vec![graphics_commands]);
+        }
+        Ok(())
+    }
+
+    /// Fill the frame's vertex buffers, uniforms, storage buffers, and
+    /// descriptors with the layers' data.
+    pub(super) fn write_frame_data(&mut self, frame: &mut Frame) -> Result<()> {
+        self.write_atlas_descriptors()?;
+        self.write_frame_uniforms(frame)?;
+
+        let all_vertices = self.layer_stack.vertices();
+        let all_hairline_vertices = self.layer_stack.hairline_vertices();
+        let all_custom_bytes = self.layer_stack.custom_vertex_bytes();
+
+        // SAFE: because resources are not shared between frames, and the
+        // frame waited for its last submission in begin_frame.
+        unsafe {
+            frame.transient.reset()?;
+            frame.vertices = frame
+                .transient
+                .push_arrays(VERTEX_ALIGNMENT, &all_vertices)?;
+            frame.hairline_vertices = frame
+                .transient
+                .push_arrays(VERTEX_ALIGNMENT, &all_hairline_vertices)?;
+            frame.custom_vertices = frame
+                .transient
+                .push_arrays(VERTEX_ALIGNMENT, &all_custom_bytes)?;
+            self.report.bytes_uploaded += frame.vertices.size
+                + frame.hairline_vertices.size
+                + frame.custom_vertices.size;
+            self.report.bytes_uploaded += frame
+                .storage
+                .update(&self.storage_buffers, &mut frame.descriptor)?;
+        }
+        self.snapshot_vertex_buffer(frame);
+        Ok(())
+    }
+
+    /// Write the texture atlas's changed descriptors into its shared
+    /// descriptor set.
+    ///
+    /// Changed slots are never used by frames in flight, so devices which
+    /// support update-after-bind write them right away. Otherwise every frame
+    /// is waited on first, which only happens when the atlas changed.
+    pub(super) fn write_atlas_descriptors(&mut self) -> Result<()> {
+        if !self.texture_atlas.has_descriptor_writes() {
+            return Ok(());
+        }
+        if !self.device.supports_update_after_bind() {
+            self.frame_context.wait_for_frames()?;
+        }
+        // SAFE: new textures and samplers aren't used by any frame yet, and
+        //       the whole set is idle when update-after-bind isn't supported
+        let writes = unsafe { self.texture_atlas.write_descriptor_set() };
+        if !writes.textures.is_empty() {
+            self.snapshot_texture_descriptor_writes(&writes.textures);
+        }
+        if let Some(samplers) = writes.samplers {
+            self.snapshot_sampler_descriptor_writes(samplers);
         }
         Ok(())
     }
 
+    /// The descriptor sets bound for the layer pass, in set order: the
+    /// texture atlas's shared set, then the frame's own set.
+    pub(super) fn layer_descriptor_sets(
+        &self,
+        frame: &Frame,
+    ) -> [vk::DescriptorSet; 2] {
+        let mut descriptor_sets = [vk::DescriptorSet::null(); 2];
+        // SAFE: the sets are only bound in the frame's own command buffers
+        unsafe {
+            descriptor_sets[ATLAS_DESCRIPTOR_SET as usize] =
+                self.texture_atlas.raw_descriptor_set();
+            descriptor_sets[FRAME_DESCRIPTOR_SET as usize] =
+                frame.descriptor.raw_descriptor_set();
+        }
+        descriptor_sets
+    }
+
     /// Replace the swapchain and all dependent resources in the Triangle
     /// subsystem.
     pub fn rebuild_swapchain(
         &mut self,
         window_surface: &dyn WindowSurface,
     ) -> Result<()> {
-        let swapchain = self.frame_context.rebuild_swapchain(window_surface)?;
-        self.pipeline2d = Pipeline2d::new(self.device.clone(), &swapchain)?;
+        let options = self.frame_context.swapchain().options().clone();
+        self.set_swapchain_options(window_surface, options)
+    }
+
+    /// Rebuild the swapchain with new buffering and presentation options.
+    pub fn set_swapchain_options(
+        &mut self,
+        window_surface: &dyn WindowSurface,
+        options: SwapchainOptions,
+    ) -> Result<()> {
+        self.submit_pending_captures()?;
+        let swapchain = self
+            .frame_context
+            .rebuild_swapchain_with_options(window_surface, options)?;
+        self.rebuild_swapchain_resources(&swapchain)
+    }
+
+    /// Rebuild every pipeline and resource which depends on the swapchain.
+    ///
+    /// Must only be called after the frame context has waited for every frame
+    /// to finish.
+    pub(super) fn rebuild_swapchain_resources(
+        &mut self,
+        swapchain: &Swapchain,
+    ) -> Result<()> {
+        self.pipeline2d = Pipeline2d::new(self.device.clone(), swapchain)?;
+        self.hairline_pipeline =
+            HairlinePipeline::new(self.device.clone(), swapchain)?;
+        if let Some(canvas) = &mut self.shader_canvas {
+            // SAFE: rebuilding the swapchain waits for every frame to finish
+            unsafe { canvas.rebuild(swapchain)? };
+        }
+        for pipeline in &mut self.custom_pipelines {
+            // SAFE: rebuilding the swapchain waits for every frame to finish
+            unsafe { pipeline.rebuild(swapchain)? };
+        }
+        // SAFE: rebuilding the swapchain waits for every frame to finish
+        unsafe { self.resize_feedback()? };
+        if let Some(particles) = &mut self.particles {
+            // SAFE: rebuilding the swapchain waits for every frame to finish
+            unsafe { particles.rebuild(swapchain)? };
+        }
+        if let Some(grading) = &mut self.color_grading {
+            // SAFE: rebuilding the swapchain waits for every frame to finish
+            unsafe { grading.rebuild(swapchain)? };
+        }
+        if let Some(taa) = &mut self.temporal_aa {
+            // SAFE: rebuilding the swapchain waits for every frame to finish
+            unsafe { taa.rebuild(swapchain)? };
+        }
+        if let Some(render_scale) = &mut self.render_scale {
+            // SAFE: rebuilding the swapchain waits for every frame to finish
+            unsafe { render_scale.rebuild(swapchain)? };
+        }
+        self.alias_post_targets()?;
+        self.resolve_layer_projections();
         Ok(())
     }
+
+    /// The image count, present mode, and format actually used by the
+    /// swapchain.
+    pub fn swapchain_info(&self) -> SwapchainInfo {
+        self.frame_context.swapchain().info()
+    }
+
+    /// Take exclusive control of the display after the window enters
+    /// fullscreen.
+    ///
+    /// Returns false, and keeps presenting normally, when the swapchain
+    /// can't use full screen exclusive mode.
+    pub fn acquire_full_screen_exclusive(&self) -> bool {
+        self.frame_context
+            .swapchain()
+            .acquire_full_screen_exclusive()
+    }
+
+    /// Give up exclusive control of the display before the window leaves
+    /// fullscreen.
+    pub fn release_full_screen_exclusive(&self) {
+        self.frame_context
+            .swapchain()
+            .release_full_screen_exclusive()
+    }
 }
 
+/// Vertex data is pushed at offsets with this alignment, which covers every
+/// vertex attribute's format.
+const VERTEX_ALIGNMENT: u64 = 16;
+
 impl Drop for Graphics {
-    /// Block until the vulkan device idles.
+    /// Finish any recording, then block until every frame has finished
+    /// rendering and every upload has finished on the transfer queue.
     fn drop(&mut self) {
-        use ash::version::DeviceV1_0;
+        if let Err(error) = self.stop_recording() {
+            log::error!("unable to stop recording: {:?}", error);
+        }
+        self.frame_context
+            .wait_for_frames()
+            .expect("error while waiting for frames to finish rendering!");
+
+        // SAFE: no frames are submitted once the graphics subsystem drops
         unsafe {
             self.device
-                .logical_device
-                .device_wait_idle()
-                .expect("error while waiting for the graphics device to idle!")
+                .wait_for_transfers()
+                .expect("error while waiting for uploads to finish!");
         }
     }
 }
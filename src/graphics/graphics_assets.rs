@@ -0,0 +1,154 @@
+use super::Graphics;
+
+use crate::graphics::{
+    assets::{
+        AssetKind, AssetLoader, AssetManifest, AssetRegistry, LoadProgress,
+        LoadedAsset, LoadedData,
+    },
+    texture_atlas::{TextureAtlas, TextureHandle},
+};
+
+use anyhow::{Context, Result};
+use std::path::Path;
+
+impl Graphics {
+    /// The fonts and shaders loaded by key.
+    pub fn assets(&self) -> &AssetRegistry {
+        &self.assets
+    }
+
+    /// Mutable access to the fonts and shaders, used to load them.
+    pub fn assets_mut(&mut self) -> &mut AssetRegistry {
+        &mut self.assets
+    }
+
+    /// The texture loaded with a key, if any.
+    pub fn texture(&self, key: &str) -> Option<TextureHandle> {
+        self.texture_atlas.texture_by_name(key)
+    }
+
+    /// Get a texture by key, reading it from a file the first time the key
+    /// is used.
+    ///
+    /// Keys are the texture atlas's logical names, so the texture can be
+    /// saved in a scene.
+    pub fn get_or_load_texture(
+        &mut self,
+        key: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<TextureHandle> {
+        if let Some(handle) = self.texture(key) {
+            return Ok(handle);
+        }
+        let path = path.as_ref();
+        let handle = self
+            .add_texture_file(path.to_string_lossy())
+            .with_context(|| format!("unable to load texture {:?}", key))?;
+        self.texture_atlas.name_texture(handle, key)?;
+        Ok(handle)
+    }
+
+    /// Load every asset listed in a manifest file.
+    pub fn preload_manifest_file(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let manifest = AssetManifest::read_file(path)?;
+        self.preload(&manifest)
+    }
+
+    /// Load every asset listed in a manifest. Assets which were already
+    /// loaded with the same key are kept.
+    pub fn preload(&mut self, manifest: &AssetManifest) -> Result<()> {
+        for entry in &manifest.entries {
+            match entry.kind {
+                AssetKind::Texture => {
+                    self.get_or_load_texture(&entry.key, &entry.path)?;
+                }
+                AssetKind::Font => {
+                    self.assets.get_or_load_font(&entry.key, &entry.path)?;
+                }
+                AssetKind::Shader => {
+                    self.assets.get_or_load_shader(&entry.key, &entry.path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Start loading a manifest on `worker_count` background threads.
+    ///
+    /// Assets which are already loaded are skipped. Call
+    /// [Self::update_asset_loading] every frame to upload finished assets.
+    /// Starting another load cancels the current one.
+    pub fn load_assets_in_background(
+        &mut self,
+        manifest: &AssetManifest,
+        worker_count: usize,
+    ) {
+        let entries = manifest
+            .entries
+            .iter()
+            .filter(|entry| !self.is_asset_loaded(entry.kind, &entry.key))
+            .cloned()
+            .collect();
+        self.asset_loader = Some(AssetLoader::start(entries, worker_count));
+    }
+
+    /// Upload every asset which finished decoding since the last call and
+    /// return the background load's progress.
+    ///
+    /// Returns None when no background load is running. The final progress,
+    /// including any errors, is returned once, after which the load is
+    /// finished.
+    pub fn update_asset_loading(&mut self) -> Option<LoadProgress> {
+        let mut loader = self.asset_loader.take()?;
+        for finished in loader.take_finished() {
+            let key = finished.key.clone();
+            match self.store_loaded_asset(finished) {
+                Ok(bytes) => loader.progress_mut().record_loaded(bytes),
+                Err((bytes, error)) => loader
+                    .progress_mut()
+                    .record_failed(bytes, format!("{}: {:?}", key, error)),
+            }
+        }
+        let progress = loader.progress().clone();
+        if !progress.is_complete() {
+            self.asset_loader = Some(loader);
+        }
+        Some(progress)
+    }
+
+    fn is_asset_loaded(&self, kind: AssetKind, key: &str) -> bool {
+        match kind {
+            AssetKind::Texture => self.texture(key).is_some(),
+            AssetKind::Font => self.assets.has_font(key),
+            AssetKind::Shader => self.assets.has_shader(key),
+        }
+    }
+
+    /// Upload or store a decoded asset. Returns the asset's size for the
+    /// progress report either way.
+    fn store_loaded_asset(
+        &mut self,
+        loaded: LoadedAsset,
+    ) -> std::result::Result<u64, (u64, anyhow::Error)> {
+        let LoadedAsset { key, bytes, data } = loaded;
+        let stored = data.and_then(|data| match data {
+            LoadedData::Texture(decoded) => {
+                let texture = decoded.upload(&self.device)?;
+                let handle = self.add_texture(texture)?;
+                self.texture_atlas.name_texture(handle, key)
+            }
+            LoadedData::Font(font) => {
+                self.assets.insert_font(key, font);
+                Ok(())
+            }
+            LoadedData::Shader(words) => {
+                self.assets.insert_shader(key, words);
+                Ok(())
+            }
+        });
+        stored.map(|_| bytes).map_err(|error| (bytes, error))
+    }
+}
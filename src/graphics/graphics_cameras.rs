@@ -0,0 +1,50 @@
+use super::Graphics;
+
+use crate::{camera::OrthoCamera, graphics::cameras::CameraHandle};
+
+impl Graphics {
+    /// Register a named camera which layers can follow with
+    /// `LayerSpace::Camera`, or replace the camera which already has the
+    /// name.
+    ///
+    /// Like the world camera, named cameras have their aspect ratio matched
+    /// to the framebuffer. See `graphics::cameras`.
+    pub fn add_camera(
+        &mut self,
+        name: &str,
+        camera: OrthoCamera,
+    ) -> CameraHandle {
+        let handle = self.cameras.insert(name, camera);
+        let extent = self.frame_context.swapchain().extent;
+        self.fit_cameras_to(extent);
+        handle
+    }
+
+    /// The handle of the named camera, if one was added.
+    pub fn camera_handle(&self, name: &str) -> Option<CameraHandle> {
+        self.cameras.handle(name)
+    }
+
+    /// A named camera, if it hasn't been removed.
+    pub fn named_camera(&self, handle: CameraHandle) -> Option<&OrthoCamera> {
+        self.cameras.get(handle)
+    }
+
+    /// Mutable access to a named camera. Changes are picked up by the next
+    /// frame.
+    pub fn named_camera_mut(
+        &mut self,
+        handle: CameraHandle,
+    ) -> Option<&mut OrthoCamera> {
+        self.cameras.get_mut(handle)
+    }
+
+    /// Remove a named camera. Layers which followed it keep the camera's
+    /// last projection.
+    pub fn remove_camera(
+        &mut self,
+        handle: CameraHandle,
+    ) -> Option<OrthoCamera> {
+        self.cameras.remove(handle)
+    }
+}
@@ -0,0 +1,46 @@
+use super::Graphics;
+
+use crate::graphics::{canvas::Canvas, layer::LayerHandle};
+
+impl Graphics {
+    /// The immediate-mode canvas. Shapes drawn on it are rendered by the
+    /// next call to `render`, then thrown away.
+    pub fn canvas(&mut self) -> &mut Canvas {
+        &mut self.canvas
+    }
+
+    /// The transient layer which draws the canvas, once it has been used.
+    pub fn canvas_layer(&self) -> Option<LayerHandle> {
+        self.canvas_layer
+    }
+
+    /// Move the canvas's shapes into its layer.
+    ///
+    /// The layer is created on top of the stack the first time anything is
+    /// drawn, and cleared on frames where nothing was drawn.
+    pub(super) fn flush_canvas(&mut self) {
+        if self.canvas.is_empty() && self.canvas_layer.is_none() {
+            return;
+        }
+        let handle = match self.canvas_layer {
+            Some(handle) => handle,
+            None => {
+                let handle = self.layer_stack.add_layer_to_top();
+                self.canvas_layer = Some(handle);
+                handle
+            }
+        };
+        let projection = self
+            .canvas
+            .projection()
+            .unwrap_or_else(|| self.screen_projection());
+        let batches = self.canvas.take_batches();
+        let layer = self
+            .layer_stack
+            .get_layer_mut(&handle)
+            .expect("the canvas layer is never removed");
+        layer.clear();
+        layer.set_projection(projection);
+        layer.push_batches(&batches);
+    }
+}
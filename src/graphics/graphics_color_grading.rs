@@ -0,0 +1,112 @@
+use super::Graphics;
+
+use crate::graphics::color_grading::{
+    ColorAdjustments, ColorGradingPass, Dithering, Lut3d,
+};
+
+use anyhow::Result;
+use std::path::Path;
+
+impl Graphics {
+    /// Grade every frame with a 3D lookup table.
+    ///
+    /// Replaces any existing table. The current adjustments are applied
+    /// first, so tables made for display-referred images should be used with
+    /// tone mapping enabled.
+    pub fn set_color_grading(&mut self, lut: Lut3d) -> Result<()> {
+        self.frame_context.wait_for_frames()?;
+        let grading = self.color_grading_pass()?;
+        // SAFE: no frame is sampling the old table after the wait
+        unsafe { grading.set_lut(&lut) }
+    }
+
+    /// Grade every frame with a lookup table read from a `.cube` file.
+    pub fn load_color_grading(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let lut = Lut3d::read_cube_file(path)?;
+        self.set_color_grading(lut)
+    }
+
+    /// Set the exposure, tone mapping, and gamma applied to every frame.
+    ///
+    /// The grading pass is removed entirely when it would leave every color
+    /// unchanged.
+    pub fn set_color_adjustments(
+        &mut self,
+        adjustments: ColorAdjustments,
+    ) -> Result<()> {
+        if self.color_grading.is_none() && adjustments.is_identity() {
+            return Ok(());
+        }
+        self.color_grading_pass()?.set_adjustments(adjustments);
+        self.remove_identity_color_grading()
+    }
+
+    /// The adjustments applied to every frame.
+    pub fn color_adjustments(&self) -> ColorAdjustments {
+        self.color_grading
+            .as_ref()
+            .map(|grading| *grading.adjustments())
+            .unwrap_or_default()
+    }
+
+    /// Quantize every frame to fewer bits per channel, dithering with an
+    /// ordered or blue noise pattern.
+    ///
+    /// Quantization happens after every other adjustment, so it's the last
+    /// thing to touch the frame before it's presented or captured.
+    pub fn set_dithering(&mut self, dithering: Dithering) -> Result<()> {
+        if self.color_grading.is_none() && dithering.is_identity() {
+            return Ok(());
+        }
+        self.frame_context.wait_for_frames()?;
+        let grading = self.color_grading_pass()?;
+        // SAFE: no frame is sampling the old noise after the wait
+        unsafe { grading.set_dithering(dithering)? };
+        self.remove_identity_color_grading()
+    }
+
+    /// The quantization applied to every frame.
+    pub fn dithering(&self) -> Dithering {
+        self.color_grading
+            .as_ref()
+            .map(|grading| *grading.dithering())
+            .unwrap_or_default()
+    }
+
+    /// Stop grading frames and release the grading pass's resources.
+    pub fn clear_color_grading(&mut self) -> Result<()> {
+        if self.color_grading.is_some() {
+            self.frame_context.wait_for_frames()?;
+            self.color_grading = None;
+        }
+        Ok(())
+    }
+
+    /// Remove the grading pass when it doesn't change any colors, so it
+    /// doesn't cost a copy every frame.
+    fn remove_identity_color_grading(&mut self) -> Result<()> {
+        let is_identity = self
+            .color_grading
+            .as_ref()
+            .map(|grading| grading.is_identity())
+            .unwrap_or(false);
+        if is_identity {
+            self.clear_color_grading()?;
+        }
+        Ok(())
+    }
+
+    /// The grading pass, created the first time it's needed.
+    fn color_grading_pass(&mut self) -> Result<&mut ColorGradingPass> {
+        if self.color_grading.is_none() {
+            let pass = ColorGradingPass::new(
+                self.device.clone(),
+                self.frame_context.swapchain(),
+            )?;
+            self.frame_context.wait_for_frames()?;
+            self.color_grading = Some(pass);
+            self.alias_post_targets()?;
+        }
+        Ok(self.color_grading.as_mut().unwrap())
+    }
+}
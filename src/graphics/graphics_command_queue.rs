@@ -0,0 +1,47 @@
+use super::Graphics;
+
+use crate::graphics::command_queue::{CommandSender, GraphicsCommand};
+
+use anyhow::{Context, Result};
+
+impl Graphics {
+    /// Create a sender which can queue commands from any thread.
+    ///
+    /// Queued commands are applied at the start of the next call to
+    /// `render`.
+    pub fn command_sender(&self) -> CommandSender {
+        self.command_queue.sender()
+    }
+
+    /// Apply every queued command in the order it was sent.
+    ///
+    /// A failed command doesn't stop the rest of the queue from being
+    /// applied. The first failure is returned after every command has been
+    /// tried.
+    pub fn apply_queued_commands(&mut self) -> Result<()> {
+        let mut first_error = None;
+        for command in self.command_queue.drain() {
+            if let Err(error) = self.apply_command(command) {
+                first_error.get_or_insert(error);
+            }
+        }
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_command(&mut self, command: GraphicsCommand) -> Result<()> {
+        match command {
+            GraphicsCommand::Layer(command) => {
+                command.apply(&mut self.layer_stack)
+            }
+            GraphicsCommand::LoadTexture { path, name } => {
+                let handle = self
+                    .add_texture_file(path.clone())
+                    .with_context(|| format!("unable to load {:?}", path))?;
+                self.texture_atlas.name_texture(handle, name)
+            }
+        }
+    }
+}
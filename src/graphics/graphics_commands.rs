@@ -1,12 +1,17 @@
 use super::Graphics;
 
 use crate::graphics::{
-    frame::Frame, pipeline2d::PushConsts, vulkan::buffer::Buffer,
-    vulkan::ffi::any_as_u8_slice,
+    custom_pipeline::CustomBatch,
+    frame::Frame,
+    hairline::{HairlinePushConsts, Hairlines},
+    pipeline2d::{PushConsts, USER_DATA_OFFSET},
+    pipeline_cache::{BlendMode, RenderState},
+    vulkan::{command_pool::CommandRecorder, ffi::any_as_u8_slice},
 };
 
 use anyhow::Result;
 use ash::{version::DeviceV1_0, vk};
+use nalgebra as na;
 
 /// Use Frame resources to record a one-time use CommandBuffer which actually
 /// renders the draw2d render pass.
@@ -16,16 +21,38 @@ impl Graphics {
     pub(super) fn record_layer_draw_commands(
         &mut self,
         frame: &mut Frame,
-    ) -> Result<vk::CommandBuffer> {
-        let command_buffer = self.begin_frame_commands(frame)?;
+    ) -> Result<CommandRecorder> {
+        let recorder = self.begin_frame_commands(frame)?;
+        let command_buffer = recorder.raw();
+        let mut offset: u32 = 0;
+        let mut draw_calls: u32 = 0;
+        let mut variant_binds = vec![];
+        let render_extent = self.render_extent();
         unsafe {
+            let descriptor_sets = self.layer_descriptor_sets(frame);
+
+            // the canvas's resolution can't follow an export's tiles
+            let exporting = self.export_target.is_some();
+            if let Some(canvas) =
+                self.shader_canvas.as_mut().filter(|_| !exporting)
+            {
+                canvas.record_draw(
+                    command_buffer,
+                    &descriptor_sets,
+                    &self.texture_atlas,
+                    self.frame_context.swapchain(),
+                    render_extent,
+                );
+                draw_calls += 1;
+            }
+
             self.device.logical_device.cmd_bind_pipeline(
                 command_buffer,
                 vk::PipelineBindPoint::GRAPHICS,
                 *self.pipeline2d.raw_pipeline(),
             );
+            self.snapshot_pipeline_bind(*self.pipeline2d.raw_pipeline());
 
-            let descriptor_sets = [frame.descriptor.raw_descriptor_set()];
             self.device.logical_device.cmd_bind_descriptor_sets(
                 command_buffer,
                 vk::PipelineBindPoint::GRAPHICS,
@@ -35,59 +62,477 @@ impl Graphics {
                 &[],
             );
 
-            let buffers = [frame.vertex_buffer.raw()];
-            let offsets = [0];
-            self.device.logical_device.cmd_bind_vertex_buffers(
-                command_buffer,
-                0,
-                &buffers,
-                &offsets,
-            );
+            let has_batch_vertices = self
+                .layer_stack
+                .vertices()
+                .iter()
+                .any(|vertices| !vertices.is_empty());
+            if has_batch_vertices {
+                let buffers = [frame.vertices.buffer];
+                let offsets = [frame.vertices.offset];
+                self.device.logical_device.cmd_bind_vertex_buffers(
+                    command_buffer,
+                    0,
+                    &buffers,
+                    &offsets,
+                );
+            }
 
-            let mut offset: u32 = 0;
+            // variants are created up front because the layer stack stays
+            // borrowed while recording
+            let mut layer_pipelines = vec![];
             for layer in self.layer_stack.layers() {
-                for batch in layer.batches() {
-                    let consts = PushConsts {
-                        projection: (*layer.projection()).into(),
-                        texture_index: batch.texture_handle.texture_index(),
-                    };
-                    self.device.logical_device.cmd_push_constants(
+                let wireframe = (self.wireframe || layer.wireframe())
+                    && self.device.supports_wireframe();
+                let state = RenderState {
+                    blend_mode: if layer.blend_constants().is_some() {
+                        BlendMode::Constant
+                    } else {
+                        BlendMode::Alpha
+                    },
+                    color_write_mask: layer.color_write_mask(),
+                    polygon_mode: if wireframe {
+                        vk::PolygonMode::LINE
+                    } else {
+                        vk::PolygonMode::FILL
+                    },
+                    ..RenderState::default()
+                };
+                let uses_palettes =
+                    layer.batches().iter().any(|batch| batch.palette.is_some());
+                let palette_pipeline = if uses_palettes {
+                    self.pipeline2d.pipeline_for(RenderState {
+                        shaders: RenderState::PALETTE_SHADERS,
+                        ..state
+                    })?
+                } else {
+                    vk::Pipeline::null()
+                };
+                let uses_array_textures = layer.batches().iter().any(|batch| {
+                    self.texture_atlas.is_array_texture(batch.texture_handle)
+                });
+                let array_pipeline = if uses_array_textures {
+                    self.pipeline2d.pipeline_for(RenderState {
+                        shaders: RenderState::TEXTURE2D_ARRAY_SHADERS,
+                        ..state
+                    })?
+                } else {
+                    vk::Pipeline::null()
+                };
+                layer_pipelines.push((
+                    self.pipeline2d.pipeline_for(state)?,
+                    palette_pipeline,
+                    array_pipeline,
+                ));
+            }
+            let default_pipeline = *self.pipeline2d.raw_pipeline();
+
+            let mut hairline_offset: u32 = 0;
+            let mut custom_offset: u64 = 0;
+            let mut bound_texture = None;
+            let frame_number = self.frame_number;
+            let rotation = self.clip_transform();
+            for (
+                (layer_handle, layer),
+                (pipeline, palette_pipeline, array_pipeline),
+            ) in self
+                .layer_stack
+                .layers_with_handles()
+                .into_iter()
+                .zip(layer_pipelines)
+            {
+                let uses_variant =
+                    pipeline != default_pipeline && !layer.batches().is_empty();
+                if uses_variant {
+                    self.device.logical_device.cmd_bind_pipeline(
                         command_buffer,
-                        *self.pipeline2d.raw_pipeline_layout(),
-                        vk::ShaderStageFlags::FRAGMENT
-                            | vk::ShaderStageFlags::VERTEX,
-                        0,
-                        any_as_u8_slice(&consts),
+                        vk::PipelineBindPoint::GRAPHICS,
+                        pipeline,
                     );
-                    self.device.logical_device.cmd_draw(
+                    if let Some(constants) = layer.blend_constants() {
+                        self.device.logical_device.cmd_set_blend_constants(
+                            command_buffer,
+                            &constants,
+                        );
+                    }
+                    variant_binds.push(pipeline);
+                }
+                let mut bound_pipeline = if uses_variant {
+                    pipeline
+                } else {
+                    default_pipeline
+                };
+
+                // wrapped layers are drawn once per visible copy of the world
+                let projections = layer.projections();
+                for (batch_index, batch) in layer.batches().iter().enumerate() {
+                    let vertex_count = batch.vertices.len() as u32;
+                    let visible: Vec<&na::Matrix4<f32>> = projections
+                        .iter()
+                        .filter(|projection| batch.is_visible(projection))
+                        .collect();
+                    // culled batches still count as using their textures so
+                    // they aren't evicted while the layer holds them
+                    self.texture_atlas
+                        .mark_texture_used(batch.texture_handle, frame_number);
+                    if let Some(palette) = batch.palette {
+                        self.texture_atlas
+                            .mark_texture_used(palette, frame_number);
+                    }
+                    if visible.is_empty() {
+                        self.report.batches_culled += 1;
+                        offset += vertex_count;
+                        continue;
+                    }
+
+                    let texture_handle = match &self.feedback {
+                        Some(feedback) => {
+                            feedback.resolve(batch.texture_handle)
+                        }
+                        None => batch.texture_handle,
+                    };
+                    // the palette shaders only sample 2d textures
+                    let array_texture_index = match batch.palette {
+                        Some(_) => None,
+                        None => self
+                            .texture_atlas
+                            .shader_array_texture_index(texture_handle),
+                    };
+                    let texture_index = match array_texture_index {
+                        Some(index) => index,
+                        None => self
+                            .texture_atlas
+                            .shader_texture_index(texture_handle),
+                    };
+                    let sampler_index =
+                        self.texture_atlas.sampler_index(texture_handle);
+                    let batch_pipeline =
+                        match (batch.palette, array_texture_index) {
+                            (Some(_), _) => palette_pipeline,
+                            (None, Some(_)) => array_pipeline,
+                            (None, None) => pipeline,
+                        };
+                    if batch_pipeline != bound_pipeline {
+                        self.device.logical_device.cmd_bind_pipeline(
+                            command_buffer,
+                            vk::PipelineBindPoint::GRAPHICS,
+                            batch_pipeline,
+                        );
+                        bound_pipeline = batch_pipeline;
+                        variant_binds.push(batch_pipeline);
+                    }
+                    let palette_index = match batch.palette {
+                        Some(palette) => {
+                            self.texture_atlas.shader_texture_index(palette)
+                        }
+                        None => 0,
+                    };
+                    if bound_texture != Some(texture_index) {
+                        bound_texture = Some(texture_index);
+                        self.report.texture_binds += 1;
+                    }
+                    self.report.batches_drawn += 1;
+                    self.resource_usage.mark_batch(
+                        layer_handle,
+                        batch_index,
+                        batch.texture_handle,
+                        self.texture_atlas
+                            .texture_sampler(batch.texture_handle),
+                        frame_number,
+                    );
+
+                    for projection in visible {
+                        let consts = PushConsts {
+                            projection: (rotation * projection).into(),
+                            texture_index,
+                            palette_index,
+                            sampler_index,
+                            layer: batch.layer,
+                        };
+                        self.device.logical_device.cmd_push_constants(
+                            command_buffer,
+                            *self.pipeline2d.raw_pipeline_layout(),
+                            vk::ShaderStageFlags::FRAGMENT
+                                | vk::ShaderStageFlags::VERTEX,
+                            0,
+                            any_as_u8_slice(&consts),
+                        );
+                        self.device.logical_device.cmd_draw(
+                            command_buffer,
+                            vertex_count, // vertex count
+                            1,            // instance count
+                            offset,       // first vertex
+                            0,            // first instance
+                        );
+                        draw_calls += 1;
+                        self.report.vertices_submitted += vertex_count as u64;
+                    }
+                    offset += vertex_count;
+                }
+
+                if bound_pipeline != default_pipeline {
+                    self.device.logical_device.cmd_bind_pipeline(
                         command_buffer,
-                        batch.vertices.len() as u32, // vertex count
-                        1,                           // instance count
-                        offset,                      // first vertex
-                        0,                           // first instance
+                        vk::PipelineBindPoint::GRAPHICS,
+                        default_pipeline,
                     );
-                    offset += batch.vertices.len() as u32;
                 }
+
+                if !layer.custom_batches().is_empty() {
+                    for batch in layer.custom_batches() {
+                        self.texture_atlas.mark_texture_used(
+                            batch.texture_handle,
+                            frame_number,
+                        );
+                    }
+                    let (custom_draws, custom_vertices) = self
+                        .record_custom_draw_commands(
+                            frame,
+                            command_buffer,
+                            &projections,
+                            layer.custom_batches(),
+                            &mut custom_offset,
+                            has_batch_vertices,
+                        );
+                    draw_calls += custom_draws;
+                    self.report.vertices_submitted += custom_vertices;
+                }
+
+                let has_hairline_vertices = layer
+                    .hairlines()
+                    .iter()
+                    .any(|hairlines| !hairlines.vertices.is_empty());
+                if has_hairline_vertices {
+                    let (hairline_draws, hairline_vertices) = self
+                        .record_hairline_draw_commands(
+                            frame,
+                            command_buffer,
+                            &projections,
+                            layer.hairlines(),
+                            &mut hairline_offset,
+                            has_batch_vertices,
+                        );
+                    draw_calls += hairline_draws;
+                    self.report.vertices_submitted += hairline_vertices;
+                }
+            }
+
+            if let Some(particles) = &self.particles {
+                particles.record_draw(
+                    command_buffer,
+                    &(rotation * self.world_projection),
+                );
+                draw_calls += 1;
             }
         }
-        self.end_frame_commands(command_buffer)?;
-        Ok(command_buffer)
+        for pipeline in variant_binds {
+            self.snapshot_pipeline_bind(pipeline);
+        }
+        self.report.draw_calls += draw_calls as u64;
+        self.snapshot_draws(draw_calls, offset);
+        self.end_frame_commands(frame, command_buffer)?;
+        Ok(recorder)
+    }
+
+    /// Draw a layer's custom batches with their pipelines, then rebind the
+    /// 2d pipeline. Returns the number of draw calls and vertices drawn.
+    ///
+    /// Batches with an invalid pipeline handle, or more user data than their
+    /// pipeline reads, are skipped.
+    unsafe fn record_custom_draw_commands(
+        &self,
+        frame: &Frame,
+        command_buffer: vk::CommandBuffer,
+        projections: &[na::Matrix4<f32>],
+        custom_batches: &[CustomBatch],
+        byte_offset: &mut u64,
+        rebind_vertex_buffer: bool,
+    ) -> (u32, u64) {
+        let logical_device = &self.device.logical_device;
+        let rotation = self.clip_transform();
+        let mut draw_calls = 0;
+        let mut vertices = 0;
+
+        let mut bound_pipeline = None;
+        for batch in custom_batches {
+            let batch_offset = *byte_offset;
+            *byte_offset += batch.bytes().len() as u64;
+            let pipeline =
+                match self.custom_pipelines.get(batch.pipeline.index()) {
+                    Some(pipeline) => pipeline,
+                    None => continue,
+                };
+            let user_data_size = pipeline.user_data_size() as usize;
+            if batch.vertex_count() == 0
+                || batch.user_data().len() > user_data_size
+            {
+                continue;
+            }
+            if bound_pipeline != Some(pipeline.raw_pipeline()) {
+                bound_pipeline = Some(pipeline.raw_pipeline());
+                logical_device.cmd_bind_pipeline(
+                    command_buffer,
+                    vk::PipelineBindPoint::GRAPHICS,
+                    pipeline.raw_pipeline(),
+                );
+            }
+            logical_device.cmd_bind_vertex_buffers(
+                command_buffer,
+                0,
+                &[frame.custom_vertices.buffer],
+                &[frame.custom_vertices.offset + batch_offset],
+            );
+            if user_data_size > 0 {
+                // zeros keep the previous batch's data from leaking through
+                let mut user_data = batch.user_data().to_vec();
+                user_data.resize(user_data_size, 0);
+                logical_device.cmd_push_constants(
+                    command_buffer,
+                    pipeline.raw_pipeline_layout(),
+                    vk::ShaderStageFlags::FRAGMENT
+                        | vk::ShaderStageFlags::VERTEX,
+                    USER_DATA_OFFSET,
+                    &user_data,
+                );
+            }
+            for projection in projections {
+                let consts = PushConsts {
+                    projection: (rotation * projection).into(),
+                    texture_index: self
+                        .texture_atlas
+                        .shader_texture_index(batch.texture_handle),
+                    palette_index: 0,
+                    sampler_index: self
+                        .texture_atlas
+                        .sampler_index(batch.texture_handle),
+                    layer: 0,
+                };
+                logical_device.cmd_push_constants(
+                    command_buffer,
+                    pipeline.raw_pipeline_layout(),
+                    vk::ShaderStageFlags::FRAGMENT
+                        | vk::ShaderStageFlags::VERTEX,
+                    0,
+                    any_as_u8_slice(&consts),
+                );
+                logical_device.cmd_draw(
+                    command_buffer,
+                    batch.vertex_count(),
+                    1,
+                    0,
+                    0,
+                );
+                draw_calls += 1;
+                vertices += batch.vertex_count() as u64;
+            }
+        }
+
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            *self.pipeline2d.raw_pipeline(),
+        );
+        if rebind_vertex_buffer {
+            logical_device.cmd_bind_vertex_buffers(
+                command_buffer,
+                0,
+                &[frame.vertices.buffer],
+                &[frame.vertices.offset],
+            );
+        }
+        (draw_calls, vertices)
+    }
+
+    /// Draw a layer's hairlines, then rebind the 2d pipeline for the next
+    /// layer's batches. Returns the number of draw calls and vertices drawn.
+    unsafe fn record_hairline_draw_commands(
+        &self,
+        frame: &Frame,
+        command_buffer: vk::CommandBuffer,
+        projections: &[na::Matrix4<f32>],
+        all_hairlines: &[Hairlines],
+        offset: &mut u32,
+        rebind_vertex_buffer: bool,
+    ) -> (u32, u64) {
+        let logical_device = &self.device.logical_device;
+        let viewport = self.layer_viewport();
+        let rotation = self.clip_transform();
+        let mut draw_calls = 0;
+        let mut vertices = 0;
+
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            *self.hairline_pipeline.raw_pipeline(),
+        );
+        logical_device.cmd_bind_vertex_buffers(
+            command_buffer,
+            0,
+            &[frame.hairline_vertices.buffer],
+            &[frame.hairline_vertices.offset],
+        );
+        for hairlines in all_hairlines {
+            for projection in projections {
+                let consts = HairlinePushConsts {
+                    projection: (rotation * projection).into(),
+                    viewport_size: [viewport.width, viewport.height],
+                    line_width: hairlines.width,
+                };
+                logical_device.cmd_push_constants(
+                    command_buffer,
+                    *self.hairline_pipeline.raw_pipeline_layout(),
+                    vk::ShaderStageFlags::FRAGMENT
+                        | vk::ShaderStageFlags::VERTEX,
+                    0,
+                    any_as_u8_slice(&consts),
+                );
+                logical_device.cmd_draw(
+                    command_buffer,
+                    hairlines.vertices.len() as u32,
+                    1,
+                    *offset,
+                    0,
+                );
+                draw_calls += 1;
+                vertices += hairlines.vertices.len() as u64;
+            }
+            *offset += hairlines.vertices.len() as u32;
+        }
+
+        // descriptor sets bound with the 2d pipeline's layout stay bound
+        // while the hairline pipeline is in use
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            *self.pipeline2d.raw_pipeline(),
+        );
+        if rebind_vertex_buffer {
+            logical_device.cmd_bind_vertex_buffers(
+                command_buffer,
+                0,
+                &[frame.vertices.buffer],
+                &[frame.vertices.offset],
+            );
+        }
+        (draw_calls, vertices)
     }
 
     pub(super) fn record_no_op_commands(
         &mut self,
         frame: &mut Frame,
-    ) -> Result<vk::CommandBuffer> {
-        let command_buffer = self.begin_frame_commands(frame)?;
-        self.end_frame_commands(command_buffer)?;
-        Ok(command_buffer)
+    ) -> Result<CommandRecorder> {
+        let recorder = self.begin_frame_commands(frame)?;
+        let command_buffer = recorder.raw();
+        self.end_frame_commands(frame, command_buffer)?;
+        Ok(recorder)
     }
 
     fn begin_frame_commands(
         &mut self,
         frame: &mut Frame,
-    ) -> Result<vk::CommandBuffer> {
-        let command_buffer = frame.command_pool.request_command_buffer()?;
+    ) -> Result<CommandRecorder> {
+        let recorder = frame.request_command_buffer()?;
+        let command_buffer = recorder.raw();
         let begin_info = vk::CommandBufferBeginInfo {
             flags: vk::CommandBufferUsageFlags::empty(),
             ..Default::default()
@@ -96,6 +541,19 @@ impl Graphics {
             self.device
                 .logical_device
                 .begin_command_buffer(command_buffer, &begin_info)?;
+            frame.timestamps.reset(command_buffer);
+            frame.timestamps.begin_zone(command_buffer, "frame");
+            if self.export_target.is_none() {
+                self.record_passes_before_layers(frame, command_buffer)?;
+                // export tiles all show the same moment, so particles only
+                // move between frames
+                if let Some(particles) = &mut self.particles {
+                    particles.record_simulation(
+                        command_buffer,
+                        self.frame_clock.delta_time(),
+                    );
+                }
+            }
         }
         // begin the render pass
         let clear_values = [vk::ClearValue {
@@ -103,29 +561,55 @@ impl Graphics {
                 float32: self.clear_color,
             },
         }];
+        let (render_pass, framebuffer) =
+            match (&self.export_target, &self.render_scale) {
+                (Some(target), _) => {
+                    (target.render_pass(), target.framebuffer())
+                }
+                (None, Some(pass)) => (pass.render_pass(), pass.framebuffer()),
+                (None, None) => (
+                    self.frame_context.swapchain().render_pass,
+                    frame.framebuffer,
+                ),
+            };
+        let extent = self.render_extent();
         let render_pass_begin_info = vk::RenderPassBeginInfo {
-            render_pass: self.frame_context.swapchain().render_pass,
-            framebuffer: frame.framebuffer,
+            render_pass,
+            framebuffer,
             render_area: vk::Rect2D {
                 offset: vk::Offset2D { x: 0, y: 0 },
-                extent: self.frame_context.swapchain().extent,
+                extent,
             },
             p_clear_values: clear_values.as_ptr(),
             clear_value_count: clear_values.len() as u32,
             ..Default::default()
         };
         unsafe {
+            frame.timestamps.begin_zone(command_buffer, "layers");
             self.device.logical_device.cmd_begin_render_pass(
                 command_buffer,
                 &render_pass_begin_info,
                 vk::SubpassContents::INLINE,
             );
+            self.record_virtual_resolution_bars(command_buffer);
+            // every layer pipeline takes its viewport from the command buffer
+            self.device.logical_device.cmd_set_viewport(
+                command_buffer,
+                0,
+                &[self.layer_viewport()],
+            );
+            self.device.logical_device.cmd_set_scissor(
+                command_buffer,
+                0,
+                &[self.layer_scissor()],
+            );
         }
-        Ok(command_buffer)
+        Ok(recorder)
     }
 
     fn end_frame_commands(
-        &self,
+        &mut self,
+        frame: &mut Frame,
         command_buffer: vk::CommandBuffer,
     ) -> Result<()> {
         unsafe {
@@ -133,7 +617,22 @@ impl Graphics {
             self.device
                 .logical_device
                 .cmd_end_render_pass(command_buffer);
-
+            frame.timestamps.end_zone(command_buffer);
+            frame.timestamps.begin_zone(command_buffer, "post");
+            match &self.export_target {
+                Some(target) => frame.readback.record_capture(
+                    command_buffer,
+                    target.image(),
+                    target.tile().extent(),
+                    self.frame_context.swapchain().format,
+                    self.frame_number,
+                )?,
+                None => {
+                    self.record_passes_after_layers(frame, command_buffer)?
+                }
+            }
+            frame.timestamps.end_zone(command_buffer);
+            frame.timestamps.end_zone(command_buffer);
             self.device
                 .logical_device
                 .end_command_buffer(command_buffer)?;
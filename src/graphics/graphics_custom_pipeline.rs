@@ -0,0 +1,77 @@
+use super::Graphics;
+
+use crate::graphics::custom_pipeline::{
+    CustomPipeline, CustomPipelineHandle, VertexLayout,
+};
+
+use anyhow::Result;
+
+impl Graphics {
+    /// Register a pipeline which draws `CustomBatch` vertices with the
+    /// provided SPIR-V shaders.
+    ///
+    /// The vertex layout is validated before the pipeline is created. See
+    /// the `custom_pipeline` module for the interface the shaders can use.
+    pub fn register_pipeline(
+        &mut self,
+        name: impl Into<String>,
+        vertex_spirv: &[u32],
+        fragment_spirv: &[u32],
+        vertex_layout: VertexLayout,
+    ) -> Result<CustomPipelineHandle> {
+        self.register_pipeline_with_user_data(
+            name,
+            vertex_spirv,
+            fragment_spirv,
+            vertex_layout,
+            0,
+        )
+    }
+
+    /// Register a pipeline whose shaders also read `user_data_size` bytes of
+    /// per-batch user data at `USER_DATA_OFFSET` in the push constants.
+    ///
+    /// Fails when the size is over `MAX_USER_DATA_SIZE` or isn't a multiple
+    /// of 4.
+    pub fn register_pipeline_with_user_data(
+        &mut self,
+        name: impl Into<String>,
+        vertex_spirv: &[u32],
+        fragment_spirv: &[u32],
+        vertex_layout: VertexLayout,
+        user_data_size: u32,
+    ) -> Result<CustomPipelineHandle> {
+        let pipeline = CustomPipeline::new(
+            self.device.clone(),
+            self.frame_context.swapchain(),
+            name,
+            vertex_spirv,
+            fragment_spirv,
+            vertex_layout,
+            user_data_size,
+        )?;
+        self.custom_pipelines.push(pipeline);
+        Ok(CustomPipelineHandle::new(self.custom_pipelines.len() - 1))
+    }
+
+    /// The vertex layout used by a registered pipeline.
+    ///
+    /// Returns None if the handle is invalid.
+    pub fn vertex_layout(
+        &self,
+        handle: CustomPipelineHandle,
+    ) -> Option<&VertexLayout> {
+        self.custom_pipelines
+            .get(handle.index())
+            .map(|pipeline| pipeline.vertex_layout())
+    }
+
+    /// The number of bytes of user data a registered pipeline reads.
+    ///
+    /// Returns None if the handle is invalid.
+    pub fn user_data_size(&self, handle: CustomPipelineHandle) -> Option<u32> {
+        self.custom_pipelines
+            .get(handle.index())
+            .map(|pipeline| pipeline.user_data_size())
+    }
+}
@@ -0,0 +1,106 @@
+use super::Graphics;
+
+use crate::graphics::{
+    describe::{ResourceHandle, ResourceInfo},
+    hairline::HairlineVertex,
+    layer::{Batch, Layer},
+    texture_atlas::{SamplerHandle, TextureHandle},
+    vertex::Vertex2d,
+};
+
+use ash::vk;
+use std::mem::size_of;
+
+impl Graphics {
+    /// Describe a texture, sampler, layer, or batch.
+    ///
+    /// Returns None if the handle doesn't refer to an existing resource.
+    pub fn describe(
+        &self,
+        handle: impl Into<ResourceHandle>,
+    ) -> Option<ResourceInfo> {
+        let handle = handle.into();
+        let mut info = match handle {
+            ResourceHandle::Texture(texture) => self.describe_texture(texture),
+            ResourceHandle::Sampler(sampler) => self.describe_sampler(sampler),
+            ResourceHandle::Layer(layer) => {
+                let layer = self.layer_stack.get_layer(&layer)?;
+                Some(describe_layer(handle, layer))
+            }
+            ResourceHandle::Batch { layer, index } => {
+                let layer = self.layer_stack.get_layer(&layer)?;
+                Some(describe_batch(handle, layer.batches().get(index)?))
+            }
+        }?;
+        info.last_used_frame = self.resource_usage.last_used(&handle);
+        Some(info)
+    }
+
+    fn describe_texture(&self, texture: TextureHandle) -> Option<ResourceInfo> {
+        let image = self.texture_atlas.texture_image(texture)?;
+        let extent = image.extent();
+        let memory_bytes = if self.texture_atlas.is_lod_variant(texture) {
+            0
+        } else {
+            image.memory_size()
+        };
+        Some(ResourceInfo {
+            name: self.texture_atlas.texture_name(texture).map(str::to_owned),
+            size: Some((extent.width, extent.height)),
+            memory_bytes,
+            source_path: image.source_path().map(|path| path.to_owned()),
+            ..empty_info(texture.into())
+        })
+    }
+
+    fn describe_sampler(&self, sampler: SamplerHandle) -> Option<ResourceInfo> {
+        let raw_sampler = self.texture_atlas.raw_sampler(sampler)?;
+        Some(ResourceInfo {
+            name: Some(
+                self.device
+                    .vulkan_object_name(vk::ObjectType::SAMPLER, &raw_sampler),
+            ),
+            ..empty_info(sampler.into())
+        })
+    }
+}
+
+fn describe_layer(handle: ResourceHandle, layer: &Layer) -> ResourceInfo {
+    let batch_vertices: usize = layer
+        .batches()
+        .iter()
+        .map(|batch| batch.vertices.len())
+        .sum();
+    let hairline_vertices: usize = layer
+        .hairlines()
+        .iter()
+        .map(|hairlines| hairlines.vertices.len())
+        .sum();
+    let memory_bytes = batch_vertices * size_of::<Vertex2d>()
+        + hairline_vertices * size_of::<HairlineVertex>();
+    ResourceInfo {
+        vertex_count: Some(batch_vertices + hairline_vertices),
+        memory_bytes: memory_bytes as u64,
+        ..empty_info(handle)
+    }
+}
+
+fn describe_batch(handle: ResourceHandle, batch: &Batch) -> ResourceInfo {
+    ResourceInfo {
+        vertex_count: Some(batch.vertices.len()),
+        memory_bytes: (batch.vertices.len() * size_of::<Vertex2d>()) as u64,
+        ..empty_info(handle)
+    }
+}
+
+fn empty_info(handle: ResourceHandle) -> ResourceInfo {
+    ResourceInfo {
+        handle,
+        name: None,
+        size: None,
+        vertex_count: None,
+        memory_bytes: 0,
+        last_used_frame: None,
+        source_path: None,
+    }
+}
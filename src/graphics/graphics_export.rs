@@ -0,0 +1,118 @@
+use super::Graphics;
+
+use crate::graphics::{
+    export::{ExportTarget, ExportTile, MAX_TILE_SIZE},
+    frame::{Frame, FrameReadback},
+    report::RenderReport,
+};
+
+use anyhow::{bail, Context, Result};
+use ash::vk;
+use image::RgbaImage;
+use std::path::Path;
+
+impl Graphics {
+    /// Render the layers offscreen at any resolution and write them to a
+    /// PNG file.
+    ///
+    /// Images larger than the device can render at once are drawn in tiles.
+    /// The owned camera is fit to the image's aspect ratio while exporting,
+    /// and screen space layers are stretched over the whole image. Shapes
+    /// drawn on the canvas appear once they've been rendered by `render`.
+    /// See `graphics::export` for what isn't included.
+    pub fn export_image(
+        &mut self,
+        width: u32,
+        height: u32,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let path = path.as_ref();
+        let image = self.export_rgba(width, height)?;
+        image.save(path).with_context(|| {
+            format!("unable to write the export to {:?}", path)
+        })
+    }
+
+    /// Render the layers offscreen at any resolution.
+    pub fn export_rgba(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage> {
+        if width == 0 || height == 0 {
+            bail!("unable to export a {}x{} image", width, height);
+        }
+        let format = self.frame_context.swapchain().format;
+        if FrameReadback::pixel_order(format).is_none() {
+            bail!("unable to export images with format {:?}", format);
+        }
+        let tile_size = self.device.max_image_dimension_2d().min(MAX_TILE_SIZE);
+        let tiles = ExportTile::split(width, height, tile_size);
+        let target = ExportTarget::new(
+            self.device.clone(),
+            format,
+            vk::Extent2D {
+                width: width.min(tile_size),
+                height: height.min(tile_size),
+            },
+        )?;
+        let mut frame = Frame::new(
+            self.device.clone(),
+            target.framebuffer(),
+            target.image(),
+            "Export",
+        )?;
+
+        self.apply_queued_commands()?;
+        self.frame_context.wait_for_frames()?;
+        self.export_target = Some(target);
+        self.resolve_layer_projections_for(vk::Extent2D { width, height });
+        let report = std::mem::replace(
+            &mut self.report,
+            RenderReport::for_frame(self.frame_number),
+        );
+
+        let result = self.draw_export_tiles(&mut frame, &tiles, width, height);
+
+        self.report = report;
+        self.resolve_layer_projections();
+        // SAFE: every tile waited for its commands to finish
+        self.export_target = None;
+        result
+    }
+
+    /// Draw each tile and copy it into place.
+    fn draw_export_tiles(
+        &mut self,
+        frame: &mut Frame,
+        tiles: &[ExportTile],
+        width: u32,
+        height: u32,
+    ) -> Result<RgbaImage> {
+        let mut image = RgbaImage::new(width, height);
+        self.write_frame_data(frame)?;
+        for tile in tiles {
+            self.export_target
+                .as_mut()
+                .unwrap()
+                .set_tile(*tile, width, height);
+            frame.begin_frame()?;
+            let graphics_commands = self.record_layer_draw_commands(frame)?;
+            frame.submit_graphics_commands(vec![graphics_commands]);
+            frame.finish_render_target_frame(None, false)?;
+            frame.wait_for_graphics()?;
+
+            // SAFE: the tile's commands finished executing
+            let capture = unsafe { frame.readback.take_capture()? }
+                .context("the export tile wasn't captured")?;
+            let pixels = RgbaImage::from_raw(
+                capture.width,
+                capture.height,
+                capture.into_rgba(),
+            )
+            .context("the export tile has the wrong size")?;
+            image::imageops::replace(&mut image, &pixels, tile.x, tile.y);
+        }
+        Ok(image)
+    }
+}
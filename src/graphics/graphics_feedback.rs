@@ -0,0 +1,83 @@
+use super::Graphics;
+
+use crate::graphics::{
+    feedback::{fullscreen_batch, Feedback},
+    layer::LayerHandle,
+    texture_atlas::TextureHandle,
+};
+
+use anyhow::Result;
+use ash::version::DeviceV1_0;
+
+impl Graphics {
+    /// Make the previous frame's output available as a texture.
+    ///
+    /// Batches drawn with the returned handle sample whatever was presented
+    /// by the previous frame. Enabling feedback again returns the same
+    /// handle.
+    pub fn enable_feedback(&mut self) -> Result<TextureHandle> {
+        if let Some(feedback) = &self.feedback {
+            return Ok(feedback.texture());
+        }
+        let feedback = Feedback::new(
+            &self.device,
+            &mut self.texture_atlas,
+            self.frame_context.swapchain(),
+        )?;
+        let texture = feedback.texture();
+        self.feedback = Some(feedback);
+        Ok(texture)
+    }
+
+    /// The handle for the previous frame's output, if feedback is enabled.
+    pub fn feedback_texture(&self) -> Option<TextureHandle> {
+        self.feedback.as_ref().map(|feedback| feedback.texture())
+    }
+
+    /// Add a layer to the bottom of the stack which redraws the previous
+    /// frame with the given alpha.
+    ///
+    /// Everything drawn above the layer leaves a trail which fades into the
+    /// clear color. An alpha near 1 leaves long trails, an alpha near 0
+    /// leaves almost none.
+    pub fn add_feedback_layer(&mut self, alpha: f32) -> Result<LayerHandle> {
+        let texture = self.enable_feedback()?;
+        let handle = self.add_layer_to_bottom();
+        self.get_layer_mut(&handle)
+            .push_batch(fullscreen_batch(texture, alpha));
+        Ok(handle)
+    }
+
+    /// Stop copying frames and release the feedback images.
+    ///
+    /// Batches which still use the feedback handle will sample the atlas's
+    /// default texture.
+    pub fn disable_feedback(&mut self) -> Result<()> {
+        if let Some(feedback) = self.feedback.take() {
+            unsafe {
+                // SAFE: the device is idle so the images are not in use
+                self.device.logical_device.device_wait_idle()?;
+                feedback.destroy(&mut self.texture_atlas)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the finished frame into the feedback image which was not sampled
+    /// by this frame, then swap the images for the next frame.
+    /// Resize the feedback images to match the swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - the device must be idle
+    pub(super) unsafe fn resize_feedback(&mut self) -> Result<()> {
+        if let Some(feedback) = &mut self.feedback {
+            feedback.resize(
+                &self.device,
+                &mut self.texture_atlas,
+                self.frame_context.swapchain(),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,307 @@
+use super::Graphics;
+
+use crate::graphics::{
+    frame::Frame,
+    frame_graph::{FrameGraph, FrameResource, ResourceTracker, ResourceUse},
+    render_node::{FrameResources, NodeStage},
+};
+
+use anyhow::Result;
+use ash::vk;
+
+impl Graphics {
+    /// Record the passes which run before the layer render pass begins.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording and outside of a render pass
+    pub(super) unsafe fn record_passes_before_layers(
+        &mut self,
+        frame: &Frame,
+        command_buffer: vk::CommandBuffer,
+    ) -> Result<()> {
+        self.resource_tracker = ResourceTracker::new();
+        if self.render_nodes.is_empty() {
+            return Ok(());
+        }
+        let swapchain = self.frame_context.swapchain();
+        let resources = FrameResources {
+            device: &self.device,
+            swapchain_image: frame.image,
+            format: swapchain.format,
+            extent: swapchain.extent,
+            descriptor_sets: self.layer_descriptor_sets(frame),
+            frame_number: self.frame_number,
+        };
+        let mut graph = FrameGraph::new();
+        self.render_nodes.add_passes(
+            NodeStage::BeforeLayers,
+            &mut graph,
+            &resources,
+        );
+        graph.execute(
+            &self.device,
+            command_buffer,
+            frame.image,
+            &mut self.resource_tracker,
+        )?;
+        for barrier in self.resource_tracker.barriers_for_layer_pass() {
+            barrier.record(&self.device, command_buffer, frame.image);
+        }
+        Ok(())
+    }
+
+    /// Record the passes which run after the layer render pass ends: the
+    /// render scale blit, render nodes, temporal anti-aliasing, the feedback copy, color grading, and
+    /// the recorder's capture.
+    ///
+    /// The swapchain image is left ready to present, or in the render target
+    /// provider's final layout.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording, just after the layer render
+    ///   pass ended
+    pub(super) unsafe fn record_passes_after_layers(
+        &mut self,
+        frame: &mut Frame,
+        command_buffer: vk::CommandBuffer,
+    ) -> Result<()> {
+        let swapchain = self.frame_context.swapchain();
+        let image = frame.image;
+        let (extent, format) = (swapchain.extent, swapchain.format);
+        let final_layout = swapchain.final_layout;
+        let resources = FrameResources {
+            device: &self.device,
+            swapchain_image: image,
+            format,
+            extent,
+            descriptor_sets: self.layer_descriptor_sets(frame),
+            frame_number: self.frame_number,
+        };
+        let tracker = &mut self.resource_tracker;
+
+        let mut graph = FrameGraph::new();
+        if let Some(render_scale) = &self.render_scale {
+            let target = render_scale.target_image();
+            // the render pass leaves the target ready to blit and the
+            // swapchain image hasn't been touched
+            tracker.assume(ResourceUse {
+                resource: FrameResource::Image(target),
+                stage: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                access: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            });
+            tracker.assume(swapchain_use(
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::AccessFlags::empty(),
+                vk::ImageLayout::UNDEFINED,
+            ));
+            let blit_uses = vec![
+                ResourceUse {
+                    resource: FrameResource::Image(target),
+                    stage: vk::PipelineStageFlags::TRANSFER,
+                    access: vk::AccessFlags::TRANSFER_READ,
+                    layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                },
+                swapchain_use(
+                    vk::PipelineStageFlags::TRANSFER,
+                    vk::AccessFlags::TRANSFER_WRITE,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                ),
+            ];
+            graph.add_pass("render scale blit", blit_uses, move |_, cmd| {
+                render_scale.record_blit(cmd, image);
+                Ok(())
+            });
+        } else {
+            // the render pass leaves the image in its final layout
+            tracker.assume(swapchain_use(
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                final_layout,
+            ));
+        }
+
+        self.render_nodes.add_passes(
+            NodeStage::AfterLayers,
+            &mut graph,
+            &resources,
+        );
+
+        if let Some(taa) = &self.temporal_aa {
+            let (current, history) = (taa.current_image(), taa.history_image());
+            let sampled = |image| ResourceUse {
+                resource: FrameResource::Image(image),
+                stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+                access: vk::AccessFlags::SHADER_READ,
+                layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            };
+            let transfer_write = |image| ResourceUse {
+                resource: FrameResource::Image(image),
+                stage: vk::PipelineStageFlags::TRANSFER,
+                access: vk::AccessFlags::TRANSFER_WRITE,
+                layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            };
+            // the previous frame sampled the history, the current image is
+            // overwritten and may alias other post effect targets
+            tracker.assume_undefined(current);
+            tracker.assume(sampled(history));
+            let copy_uses = vec![transfer_read(), transfer_write(current)];
+            graph.add_pass("temporal aa copy", copy_uses, move |_, cmd| {
+                taa.record_copy(cmd, image, current);
+                Ok(())
+            });
+            let accumulate_uses = vec![
+                swapchain_use(
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                ),
+                sampled(current),
+                sampled(history),
+            ];
+            let framebuffer = frame.framebuffer;
+            graph.add_pass(
+                "temporal aa accumulate",
+                accumulate_uses,
+                move |_, cmd| {
+                    taa.record_accumulate(cmd, framebuffer);
+                    Ok(())
+                },
+            );
+            let history_uses = vec![transfer_read(), transfer_write(history)];
+            graph.add_pass(
+                "temporal aa history",
+                history_uses,
+                move |_, cmd| {
+                    taa.record_copy(cmd, image, history);
+                    Ok(())
+                },
+            );
+            graph.add_transition(
+                "temporal aa ready",
+                vec![sampled(current), sampled(history)],
+            );
+        }
+
+        if let Some(feedback) = &self.feedback {
+            let target = feedback.write_target(&self.texture_atlas)?;
+            let sampled = ResourceUse {
+                resource: FrameResource::Image(target),
+                stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+                access: vk::AccessFlags::SHADER_READ,
+                layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            };
+            // the previous frame sampled the target
+            tracker.assume(sampled);
+            let copy_uses = vec![
+                transfer_read(),
+                ResourceUse {
+                    resource: FrameResource::Image(target),
+                    stage: vk::PipelineStageFlags::TRANSFER,
+                    access: vk::AccessFlags::TRANSFER_WRITE,
+                    layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                },
+            ];
+            graph.add_pass("feedback copy", copy_uses, move |device, cmd| {
+                feedback.record_copy(device, cmd, image, target);
+                Ok(())
+            });
+            graph.add_transition("feedback ready", vec![sampled]);
+        }
+
+        if let Some(grading) = &self.color_grading {
+            let source = grading.source_image();
+            let sampled = ResourceUse {
+                resource: FrameResource::Image(source),
+                stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+                access: vk::AccessFlags::SHADER_READ,
+                layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            };
+            // the source is overwritten and may alias other post effect
+            // targets
+            tracker.assume_undefined(source);
+            let copy_uses = vec![
+                transfer_read(),
+                ResourceUse {
+                    resource: FrameResource::Image(source),
+                    stage: vk::PipelineStageFlags::TRANSFER,
+                    access: vk::AccessFlags::TRANSFER_WRITE,
+                    layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                },
+            ];
+            graph.add_pass("color grading copy", copy_uses, move |_, cmd| {
+                grading.record_copy(cmd, image);
+                Ok(())
+            });
+            let grading_uses = vec![
+                swapchain_use(
+                    vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                    vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                ),
+                sampled,
+            ];
+            let framebuffer = frame.framebuffer;
+            graph.add_pass("color grading", grading_uses, move |_, cmd| {
+                grading.record_grading(cmd, framebuffer);
+                Ok(())
+            });
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            let frame_number = recorder.next_frame_number();
+            let readback = &mut frame.readback;
+            graph.add_pass("capture", vec![transfer_read()], move |_, cmd| {
+                readback.record_capture(
+                    cmd,
+                    image,
+                    extent,
+                    format,
+                    frame_number,
+                )
+            });
+        }
+
+        graph.add_transition(
+            "present",
+            vec![swapchain_use(
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::AccessFlags::empty(),
+                final_layout,
+            )],
+        );
+        graph.execute(&self.device, command_buffer, image, tracker)?;
+
+        if let Some(feedback) = &mut self.feedback {
+            feedback.swap();
+        }
+        if let Some(taa) = &mut self.temporal_aa {
+            taa.advance();
+        }
+        Ok(())
+    }
+}
+
+fn swapchain_use(
+    stage: vk::PipelineStageFlags,
+    access: vk::AccessFlags,
+    layout: vk::ImageLayout,
+) -> ResourceUse {
+    ResourceUse {
+        resource: FrameResource::SwapchainImage,
+        stage,
+        access,
+        layout,
+    }
+}
+
+/// Copying from the swapchain image.
+fn transfer_read() -> ResourceUse {
+    swapchain_use(
+        vk::PipelineStageFlags::TRANSFER,
+        vk::AccessFlags::TRANSFER_READ,
+        vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+    )
+}
@@ -0,0 +1,46 @@
+use super::Graphics;
+
+use crate::graphics::frame::{Frame, FrameUniforms};
+
+use anyhow::Result;
+use ash::vk;
+use std::time::Instant;
+
+impl Graphics {
+    /// The uniforms given to every shader in the frame being drawn.
+    ///
+    /// Shaders read them from the frame's descriptor set, see
+    /// `graphics::frame::FrameUniforms`. The camera matches the projection
+    /// given to world space layers, including the swapchain's pre-rotation
+    /// and any temporal anti-aliasing jitter.
+    pub fn frame_uniforms(&self) -> FrameUniforms {
+        let viewport = self.layer_viewport();
+        FrameUniforms::new(
+            &(self.clip_transform() * self.world_projection),
+            vk::Extent2D {
+                width: viewport.width.round() as u32,
+                height: viewport.height.round() as u32,
+            },
+            &self.frame_clock,
+        )
+    }
+
+    /// Advance the time given to shaders. Called once per rendered frame,
+    /// so export tiles all share the same time.
+    pub(super) fn tick_frame_clock(&mut self) {
+        self.frame_clock.tick(Instant::now());
+    }
+
+    /// Copy this frame's uniforms into the frame's uniform buffer.
+    pub(super) fn write_frame_uniforms(
+        &mut self,
+        frame: &mut Frame,
+    ) -> Result<()> {
+        let uniforms = self.frame_uniforms();
+        // SAFE: because resources are not shared between frames, and the
+        // frame waited for its last submission in begin_frame.
+        self.report.bytes_uploaded +=
+            unsafe { frame.uniforms.update(&uniforms, &mut frame.descriptor)? };
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use super::Graphics;
+
+use crate::camera::OrthoCamera;
+
+use ash::vk;
+use nalgebra as na;
+
+impl Graphics {
+    /// Follow a camera with every world space layer.
+    ///
+    /// The graphics subsystem keeps its own copy of the camera and matches
+    /// the camera's aspect ratio to the framebuffer whenever the window is
+    /// resized, so applications don't need to handle resize events. Move
+    /// the camera with `camera_mut`. See `Layer::set_space`.
+    pub fn set_camera(&mut self, camera: OrthoCamera) {
+        self.camera = Some(camera);
+        self.fit_camera_to_framebuffer();
+    }
+
+    /// The camera followed by world space layers, if one was set.
+    pub fn camera(&self) -> Option<&OrthoCamera> {
+        self.camera.as_ref()
+    }
+
+    /// Mutable access to the camera followed by world space layers, if one
+    /// was set. Changes are picked up by the next frame.
+    pub fn camera_mut(&mut self) -> Option<&mut OrthoCamera> {
+        self.camera.as_mut()
+    }
+
+    /// Stop following the camera. World space layers keep the camera's last
+    /// projection until `set_world_projection` is called.
+    pub fn take_camera(&mut self) -> Option<OrthoCamera> {
+        self.camera.take()
+    }
+
+    /// Set the projection given to every world space layer directly, for
+    /// applications with their own cameras.
+    ///
+    /// Any camera set with `set_camera` is dropped.
+    pub fn set_world_projection(&mut self, projection: na::Matrix4<f32>) {
+        self.camera = None;
+        self.world_projection = projection;
+    }
+
+    /// The projection given to world space layers.
+    pub fn world_projection(&self) -> &na::Matrix4<f32> {
+        &self.world_projection
+    }
+
+    /// The projection given to screen space layers. It maps pixels, with
+    /// the origin in the top left, to the current framebuffer, or virtual
+    /// pixels when a virtual resolution is set.
+    pub fn screen_projection(&self) -> na::Matrix4<f32> {
+        let (width, height) = match self.active_virtual_resolution() {
+            Some(resolution) => (resolution.width, resolution.height),
+            None => {
+                let extent = self.frame_context.swapchain().extent;
+                (extent.width, extent.height)
+            }
+        };
+        na::Matrix4::new_orthographic(
+            0.0,
+            width as f32,
+            0.0,
+            height as f32,
+            -1.0,
+            1.0,
+        )
+    }
+
+    /// Give every world, screen, and camera space layer its projection for
+    /// the next frame.
+    ///
+    /// Projections are derived from the current framebuffer each frame, so
+    /// they follow the swapchain through every resize.
+    pub(super) fn resolve_layer_projections(&mut self) {
+        let extent = self.frame_context.swapchain().extent;
+        self.resolve_layer_projections_for(extent);
+    }
+
+    /// Give every world, screen, and camera space layer its projection for
+    /// an image with the given size. The owned and named cameras' aspect
+    /// ratios are fit to the image.
+    pub(super) fn resolve_layer_projections_for(
+        &mut self,
+        extent: vk::Extent2D,
+    ) {
+        self.fit_camera_to(extent);
+        self.fit_cameras_to(extent);
+        if let Some(camera) = &self.camera {
+            self.world_projection = camera.as_matrix();
+        }
+        let screen = self.screen_projection();
+        self.layer_stack.resolve_projections(
+            &self.world_projection,
+            &screen,
+            &self.cameras,
+        );
+    }
+
+    /// Match the owned camera's aspect ratio to the framebuffer. Nothing
+    /// changes while the framebuffer has no area.
+    fn fit_camera_to_framebuffer(&mut self) {
+        let extent = self.frame_context.swapchain().extent;
+        self.fit_camera_to(extent);
+    }
+
+    /// Match every named camera's aspect ratio to an image, or to the
+    /// virtual resolution when one is set. Nothing changes when the image
+    /// has no area.
+    pub(super) fn fit_cameras_to(&mut self, extent: vk::Extent2D) {
+        if let Some(aspect_ratio) = self.camera_aspect_ratio(extent) {
+            self.cameras.fit_to_aspect_ratio(aspect_ratio);
+        }
+    }
+
+    /// Match the owned camera's aspect ratio to an image, or to the virtual
+    /// resolution when one is set. Nothing changes when the image has no
+    /// area.
+    fn fit_camera_to(&mut self, extent: vk::Extent2D) {
+        let aspect_ratio = match self.camera_aspect_ratio(extent) {
+            Some(aspect_ratio) => aspect_ratio,
+            None => return,
+        };
+        let camera = match &mut self.camera {
+            Some(camera) => camera,
+            None => return,
+        };
+        if (camera.aspect_ratio() - aspect_ratio).abs() > f32::EPSILON {
+            camera.set_aspect_ratio(aspect_ratio);
+        }
+    }
+}
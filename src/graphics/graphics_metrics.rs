@@ -0,0 +1,49 @@
+use super::Graphics;
+
+use crate::metrics::{FrameMetrics, Metrics, SharedMetrics};
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+impl Graphics {
+    /// Send frame times, render reports, and device memory events to a
+    /// metrics sink. Replaces any previous sink.
+    ///
+    /// The returned handle can be used to read the sink's state while it's
+    /// installed.
+    pub fn set_metrics<M>(&mut self, metrics: M) -> Arc<Mutex<M>>
+    where
+        M: Metrics + 'static,
+    {
+        let metrics = Arc::new(Mutex::new(metrics));
+        let shared: SharedMetrics = metrics.clone();
+        self.device.set_metrics(Some(shared.clone()));
+        self.metrics = Some(shared);
+        self.last_frame_end = None;
+        metrics
+    }
+
+    /// Stop sending metrics.
+    pub fn clear_metrics(&mut self) {
+        self.device.set_metrics(None);
+        self.metrics = None;
+    }
+
+    pub(super) fn record_frame_metrics(&mut self) {
+        let metrics = match &self.metrics {
+            Some(metrics) => metrics,
+            None => return,
+        };
+        let now = Instant::now();
+        let frame_time =
+            self.last_frame_end.map(|end| now - end).unwrap_or_default();
+        self.last_frame_end = Some(now);
+        metrics.lock().unwrap().record_frame(&FrameMetrics {
+            frame_number: self.frame_number,
+            frame_time,
+            render: self.report,
+        });
+    }
+}
@@ -0,0 +1,73 @@
+use super::Graphics;
+
+use crate::graphics::{
+    ext::Texture2dFactory,
+    palette::{IndexedImage, Palette, MAX_PALETTE_COLORS},
+    texture_atlas::{SamplerPreset, TextureAtlas, TextureHandle},
+    vulkan::buffer::CpuBuffer,
+};
+
+use anyhow::Result;
+use ash::vk;
+
+impl Graphics {
+    /// Upload an indexed image to the texture atlas.
+    ///
+    /// The texture is sampled with nearest filtering so indices are never
+    /// blended. Draw it with a batch which has a palette, see
+    /// `Batch::with_palette`.
+    pub fn add_indexed_texture(
+        &mut self,
+        name: impl Into<String>,
+        image: &IndexedImage,
+    ) -> Result<TextureHandle> {
+        let texture = self.upload_texels(
+            name,
+            (image.width(), image.height()),
+            vk::Format::R8_UNORM,
+            image.indices(),
+        )?;
+        let sampler =
+            self.texture_atlas.preset_sampler(SamplerPreset::PixelArt)?;
+        self.bind_sampler_to_texture(sampler, texture)?;
+        Ok(texture)
+    }
+
+    /// Upload a palette to the texture atlas as a 256x1 texture.
+    ///
+    /// The returned handle can be used as any batch's palette.
+    pub fn add_palette(
+        &mut self,
+        name: impl Into<String>,
+        palette: &Palette,
+    ) -> Result<TextureHandle> {
+        self.upload_texels(
+            name,
+            (MAX_PALETTE_COLORS as u32, 1),
+            vk::Format::R8G8B8A8_SRGB,
+            &palette.texels(),
+        )
+    }
+
+    /// Create a single mip level texture and add it to the atlas.
+    fn upload_texels(
+        &mut self,
+        name: impl Into<String>,
+        (width, height): (u32, u32),
+        format: vk::Format,
+        texels: &[u8],
+    ) -> Result<TextureHandle> {
+        let mut texture = self.create_empty_2d_texture_with_format(
+            name, width, height, 1, format,
+        )?;
+        unsafe {
+            let mut transfer_buffer = CpuBuffer::new(
+                self.device.clone(),
+                vk::BufferUsageFlags::TRANSFER_SRC,
+            )?;
+            transfer_buffer.write_data(texels)?;
+            texture.upload_from_buffer(&transfer_buffer)?;
+        }
+        self.add_texture(texture)
+    }
+}
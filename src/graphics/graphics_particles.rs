@@ -0,0 +1,48 @@
+use super::Graphics;
+
+use crate::graphics::particles::ParticleSystem;
+
+use anyhow::Result;
+
+impl Graphics {
+    /// Simulate and draw particles on the gpu, with room for `capacity` to
+    /// be alive at once. Particles are drawn in world space above every
+    /// layer.
+    ///
+    /// Replaces any existing particle system. See the `particles` module.
+    pub fn set_particles(
+        &mut self,
+        capacity: u32,
+    ) -> Result<&mut ParticleSystem> {
+        let particles = ParticleSystem::new(
+            self.device.clone(),
+            self.frame_context.swapchain(),
+            capacity,
+        )?;
+        self.replace_particles(Some(particles))?;
+        Ok(self.particles.as_mut().unwrap())
+    }
+
+    /// Stop simulating and drawing particles.
+    pub fn remove_particles(&mut self) -> Result<()> {
+        self.replace_particles(None)
+    }
+
+    /// The current particle system, used to emit particles.
+    pub fn particles_mut(&mut self) -> Option<&mut ParticleSystem> {
+        self.particles.as_mut()
+    }
+
+    /// Wait for frames which could be using the old system before it's
+    /// destroyed.
+    fn replace_particles(
+        &mut self,
+        particles: Option<ParticleSystem>,
+    ) -> Result<()> {
+        if self.particles.is_some() {
+            self.frame_context.wait_for_frames()?;
+        }
+        self.particles = particles;
+        Ok(())
+    }
+}
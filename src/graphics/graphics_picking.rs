@@ -0,0 +1,145 @@
+use super::Graphics;
+
+use crate::{
+    camera::OrthoCamera,
+    graphics::{
+        id_pass::IdPass,
+        picking::{
+            batch_for_object_id, pick_layer, PickMode, PickResult, PickedBatch,
+        },
+    },
+};
+
+use anyhow::{bail, Result};
+use nalgebra as na;
+
+impl Graphics {
+    /// Find every sprite under a point on the screen using each sprite's
+    /// bounding box.
+    ///
+    /// The point is in pixels with the origin at the top-left of the window.
+    /// Results are ordered front to back, so the first result is the sprite
+    /// which was drawn on top.
+    pub fn pick(
+        &self,
+        screen_point: na::Point2<f32>,
+        camera: &OrthoCamera,
+    ) -> Vec<PickResult> {
+        self.pick_with_mode(screen_point, camera, PickMode::Bounds)
+    }
+
+    /// Find every sprite under a point on the screen, testing geometry as
+    /// described by the mode.
+    pub fn pick_with_mode(
+        &self,
+        screen_point: na::Point2<f32>,
+        camera: &OrthoCamera,
+        mode: PickMode,
+    ) -> Vec<PickResult> {
+        let ndc = self.window_to_ndc(&screen_point);
+        let world_point = camera.unproject_point(&ndc);
+
+        self.layer_stack
+            .layers_with_handles()
+            .into_iter()
+            .rev()
+            .flat_map(|(handle, layer)| {
+                pick_layer(handle, layer, &world_point, mode)
+            })
+            .collect()
+    }
+
+    /// Start maintaining the offscreen object id pass used by
+    /// `pick_object`.
+    pub fn enable_object_id_picking(&mut self) -> Result<()> {
+        if self.id_pass.is_none() {
+            let extent = self.frame_context.swapchain().extent;
+            self.id_pass = Some(IdPass::new(self.device.clone(), extent)?);
+        }
+        Ok(())
+    }
+
+    /// Release the object id pass's gpu resources.
+    pub fn disable_object_id_picking(&mut self) {
+        self.id_pass = None;
+    }
+
+    /// Find the batch which is visible under a point on the screen.
+    ///
+    /// Unlike `pick`, this renders every batch's id into an offscreen target
+    /// and reads back the pixel under the point, so nearly transparent parts
+    /// of overlapping sprites are ignored. The point is in pixels with the
+    /// origin at the top-left of the window. Blocks until the gpu finishes.
+    ///
+    /// Fails if `enable_object_id_picking` has not been called.
+    pub fn pick_object(
+        &mut self,
+        screen_point: na::Point2<f32>,
+    ) -> Result<Option<PickedBatch>> {
+        if self.id_pass.is_some() {
+            self.write_atlas_descriptors()?;
+        }
+        let extent = self.frame_context.swapchain().extent;
+        let pixel = match self.id_pass_pixel(&screen_point) {
+            Some(pixel) => pixel,
+            None => return Ok(None),
+        };
+        let id_pass = match &mut self.id_pass {
+            Some(id_pass) => id_pass,
+            None => bail!("object id picking has not been enabled!"),
+        };
+        if id_pass.extent() != extent {
+            *id_pass = IdPass::new(self.device.clone(), extent)?;
+        }
+
+        // SAFE: textures are only destroyed by the application while it holds
+        //       exclusive access to the graphics subsystem, and the atlas's
+        //       descriptor set was just written
+        let object_id = unsafe {
+            id_pass.read_object_id(
+                &self.layer_stack,
+                &self.texture_atlas,
+                pixel,
+            )?
+        };
+        Ok(batch_for_object_id(
+            &self.layer_stack.layers_with_handles(),
+            object_id,
+        ))
+    }
+
+    /// The id pass pixel under a point on the screen, or None when the
+    /// point is outside of the layers.
+    ///
+    /// The id pass draws the whole virtual resolution over its extent, so
+    /// points are mapped through the design when one is set.
+    fn id_pass_pixel(
+        &self,
+        screen_point: &na::Point2<f32>,
+    ) -> Option<(u32, u32)> {
+        if screen_point.x < 0.0 || screen_point.y < 0.0 {
+            return None;
+        }
+        let resolution = match &self.virtual_resolution {
+            Some(resolution) => resolution,
+            None => {
+                return Some((screen_point.x as u32, screen_point.y as u32))
+            }
+        };
+        let extent = self.frame_context.swapchain().extent;
+        let point = resolution.to_virtual(extent, screen_point);
+        let width = resolution.width as f32;
+        let height = resolution.height as f32;
+        if point.x < 0.0
+            || point.y < 0.0
+            || point.x >= width
+            || point.y >= height
+        {
+            return None;
+        }
+        Some((
+            (point.x / width * extent.width as f32) as u32,
+            (point.y / height * extent.height as f32) as u32,
+        ))
+    }
+}
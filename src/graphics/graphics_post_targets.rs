@@ -0,0 +1,75 @@
+use super::Graphics;
+
+use crate::graphics::{
+    color_grading::ColorGradingPass,
+    temporal_aa::TemporalAaPass,
+    vulkan::texture::{AliasedMemory, ImageLifetime},
+};
+
+use anyhow::Result;
+use ash::vk;
+
+/// The order post effects use their targets during a frame.
+const RENDER_SCALE_PASS: u32 = 0;
+const TEMPORAL_AA_PASS: u32 = 1;
+const COLOR_GRADING_PASS: u32 = 2;
+
+impl Graphics {
+    /// Recreate the targets which post effects only need for part of a
+    /// frame, so they share a single block of memory.
+    ///
+    /// The render scale target is finished once it's been blitted into the
+    /// swapchain image, and the copies used by temporal anti-aliasing and
+    /// color grading are overwritten every frame, so none of them need their
+    /// own memory. Nothing is aliased while fewer than two of the effects
+    /// are enabled.
+    ///
+    /// Must only be called after the frame context has waited for every frame
+    /// to finish.
+    pub(super) fn alias_post_targets(&mut self) -> Result<()> {
+        let swapchain = self.frame_context.swapchain();
+        let mut requests = vec![];
+        if let Some(render_scale) = &self.render_scale {
+            requests.push((
+                render_scale.target_create_info(),
+                ImageLifetime::pass(RENDER_SCALE_PASS),
+            ));
+        }
+        if self.temporal_aa.is_some() {
+            requests.push((
+                TemporalAaPass::current_create_info(swapchain),
+                ImageLifetime::pass(TEMPORAL_AA_PASS),
+            ));
+        }
+        if self.color_grading.is_some() {
+            requests.push((
+                ColorGradingPass::source_create_info(swapchain),
+                ImageLifetime::pass(COLOR_GRADING_PASS),
+            ));
+        }
+        if requests.len() < 2 {
+            return Ok(());
+        }
+
+        let mut targets = AliasedMemory::create_images(
+            self.device.clone(),
+            &requests,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?
+        .into_iter();
+
+        // SAFE: the caller waited for every frame to finish
+        unsafe {
+            if let Some(render_scale) = &mut self.render_scale {
+                render_scale.replace_target(targets.next().unwrap())?;
+            }
+            if let Some(taa) = &mut self.temporal_aa {
+                taa.replace_current(targets.next().unwrap())?;
+            }
+            if let Some(grading) = &mut self.color_grading {
+                grading.replace_source(targets.next().unwrap())?;
+            }
+        }
+        Ok(())
+    }
+}
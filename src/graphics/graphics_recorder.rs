@@ -0,0 +1,55 @@
+use super::Graphics;
+
+use crate::graphics::{
+    frame::FrameReadback,
+    recorder::{Recorder, RecorderOutput},
+};
+
+use anyhow::{bail, Result};
+use ash::vk;
+
+impl Graphics {
+    /// Start capturing every rendered frame to the given output.
+    ///
+    /// Any recording which is already in progress is stopped first.
+    pub fn start_recording(&mut self, output: RecorderOutput) -> Result<()> {
+        let swapchain = self.frame_context.swapchain();
+        if !swapchain
+            .image_usage
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            bail!("the swapchain images do not support transfers!");
+        }
+        if FrameReadback::pixel_order(swapchain.format).is_none() {
+            bail!("unable to record swapchain format {:?}", swapchain.format);
+        }
+        self.stop_recording()?;
+        self.recorder = Some(Recorder::new(output)?);
+        Ok(())
+    }
+
+    /// Stop recording and block until every captured frame is written.
+    pub fn stop_recording(&mut self) -> Result<()> {
+        self.submit_pending_captures()?;
+        match self.recorder.take() {
+            Some(mut recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    /// True when rendered frames are being captured.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Read back captures which are still held by frames in flight and send
+    /// them to the recorder.
+    pub(super) fn submit_pending_captures(&mut self) -> Result<()> {
+        if let Some(recorder) = &mut self.recorder {
+            for capture in self.frame_context.take_pending_captures()? {
+                recorder.submit(capture)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use super::Graphics;
+
+use crate::graphics::render_node::{RenderNode, RenderNodeHandle};
+
+use anyhow::Result;
+
+impl Graphics {
+    /// Register work which is recorded into every frame, before or after
+    /// the layers depending on the node's stage.
+    ///
+    /// Nodes in the same stage are recorded in the order they were added.
+    pub fn add_render_node(
+        &mut self,
+        node: Box<dyn RenderNode>,
+    ) -> Result<RenderNodeHandle> {
+        self.render_nodes.add(node)
+    }
+
+    /// Stop recording a node and give it back.
+    ///
+    /// Waits for every frame to finish so the node's resources can be
+    /// destroyed as soon as it's returned.
+    pub fn remove_render_node(
+        &mut self,
+        handle: RenderNodeHandle,
+    ) -> Result<Option<Box<dyn RenderNode>>> {
+        self.frame_context.wait_for_frames()?;
+        Ok(self.render_nodes.remove(handle))
+    }
+}
@@ -0,0 +1,53 @@
+use super::Graphics;
+
+use crate::graphics::render_scale::RenderScalePass;
+
+use anyhow::Result;
+use ash::vk;
+
+impl Graphics {
+    /// Draw the layers at `scale` times the swapchain's resolution, then
+    /// filter them down to size.
+    ///
+    /// Scales above 1 supersample every frame, which smooths edges and thin
+    /// strokes without MSAA and is handy for high quality captures. Scales
+    /// below 1 draw fewer pixels and stretch them to fit. A scale of 1
+    /// renders straight into the swapchain again. See
+    /// `graphics::render_scale`.
+    pub fn set_render_scale(&mut self, scale: f32) -> Result<()> {
+        if (scale - self.render_scale()).abs() <= f32::EPSILON {
+            return Ok(());
+        }
+        let pass = if (scale - 1.0).abs() <= f32::EPSILON {
+            None
+        } else {
+            Some(RenderScalePass::new(
+                self.device.clone(),
+                self.frame_context.swapchain(),
+                scale,
+            )?)
+        };
+        self.frame_context.wait_for_frames()?;
+        self.render_scale = pass;
+        self.alias_post_targets()
+    }
+
+    /// The multiple of the swapchain's resolution the layers are drawn at.
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+            .as_ref()
+            .map(|pass| pass.scale())
+            .unwrap_or(1.0)
+    }
+
+    /// The size of the layer pass's render area.
+    pub(super) fn render_extent(&self) -> vk::Extent2D {
+        if let Some(target) = &self.export_target {
+            return target.tile().extent();
+        }
+        match &self.render_scale {
+            Some(pass) => pass.extent(),
+            None => self.frame_context.swapchain().extent,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::Graphics;
+
+use crate::graphics::vulkan::{RenderTargetProvider, WindowSurface};
+
+use anyhow::Result;
+
+impl Graphics {
+    /// Render every frame into images owned by another API, like an OpenXR
+    /// swapchain or a projection mapping service, instead of the window.
+    ///
+    /// The provider is asked for its images right away and again whenever it
+    /// reports that they need to be rebuilt. Layers, render nodes, feedback,
+    /// and recording all work the same as they do with the window's
+    /// swapchain.
+    pub fn set_render_targets(
+        &mut self,
+        render_targets: Box<dyn RenderTargetProvider>,
+    ) -> Result<()> {
+        self.submit_pending_captures()?;
+        let swapchain =
+            self.frame_context.use_render_targets(render_targets)?;
+        self.rebuild_swapchain_resources(&swapchain)
+    }
+
+    /// Stop rendering into the application's render targets and present to
+    /// the window again.
+    ///
+    /// The render target provider is dropped once every frame which used
+    /// its images has finished.
+    pub fn clear_render_targets(
+        &mut self,
+        window_surface: &dyn WindowSurface,
+    ) -> Result<()> {
+        self.submit_pending_captures()?;
+        let swapchain =
+            self.frame_context.use_window_surface(window_surface)?;
+        self.rebuild_swapchain_resources(&swapchain)
+    }
+
+    /// True when frames are rendered into an application's render targets
+    /// instead of the window.
+    pub fn has_render_targets(&self) -> bool {
+        self.frame_context.has_render_targets()
+    }
+}
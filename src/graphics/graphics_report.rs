@@ -0,0 +1,30 @@
+use super::Graphics;
+
+use crate::graphics::report::{RenderReport, ReportLog};
+
+impl Graphics {
+    /// Statistics for the most recently rendered frame.
+    pub fn render_report(&self) -> &RenderReport {
+        &self.report
+    }
+
+    /// Log the totals for every `interval` frames at the info level.
+    ///
+    /// Replaces any previous logging interval.
+    pub fn log_render_reports(&mut self, interval: u64) {
+        self.report_log = Some(ReportLog::new(interval));
+    }
+
+    /// Stop logging render reports.
+    pub fn stop_logging_render_reports(&mut self) {
+        self.report_log = None;
+    }
+
+    pub(super) fn finish_report(&mut self) {
+        if let Some(report_log) = &mut self.report_log {
+            if let Some(totals) = report_log.record(&self.report) {
+                log::info!("{}", totals);
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use super::Graphics;
+
+use crate::graphics::{
+    layer::{Layer, LayerHandle},
+    scene::{Scene, SceneLayer},
+};
+
+use anyhow::Result;
+
+impl Graphics {
+    /// Save every layer and the clear color as a scene.
+    ///
+    /// The scene's camera is left empty for the application to fill in.
+    /// Fails if any batch uses a texture which has not been named with
+    /// `GpuAtlas::name_texture`.
+    pub fn save_scene(&self) -> Result<Scene> {
+        let layers = self
+            .layer_stack
+            .layers()
+            .into_iter()
+            .map(|layer| SceneLayer::from_layer(layer, &self.texture_atlas))
+            .collect::<Result<Vec<SceneLayer>>>()?;
+        Ok(Scene {
+            camera: None,
+            clear_color: self.clear_color,
+            layers,
+        })
+    }
+
+    /// Add a scene's layers on top of all existing layers and use its clear
+    /// color.
+    ///
+    /// Texture names are resolved against the texture atlas, so every texture
+    /// used by the scene must be loaded and named first. Returns the new
+    /// layer handles, back to front.
+    pub fn load_scene(&mut self, scene: &Scene) -> Result<Vec<LayerHandle>> {
+        // resolve every texture before adding any layers
+        let mut layers = vec![];
+        for scene_layer in &scene.layers {
+            let mut layer = Layer::empty();
+            scene_layer.fill_layer(&mut layer, &self.texture_atlas)?;
+            layers.push(layer);
+        }
+
+        let mut handles = vec![];
+        for layer in layers {
+            let handle = self.layer_stack.add_layer_to_top();
+            *self.get_layer_mut(&handle) = layer;
+            handles.push(handle);
+        }
+        self.clear_color = scene.clear_color;
+        Ok(handles)
+    }
+}
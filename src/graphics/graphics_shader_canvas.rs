@@ -0,0 +1,82 @@
+use super::Graphics;
+
+use crate::graphics::shader_canvas::ShaderCanvas;
+
+use anyhow::Result;
+use std::path::Path;
+
+impl Graphics {
+    /// Draw a fullscreen fragment shader behind every layer.
+    ///
+    /// Replaces any existing canvas. See the `shader_canvas` module for the
+    /// interface the shader must use.
+    pub fn set_shader_canvas(
+        &mut self,
+        fragment_spirv: &[u32],
+    ) -> Result<&mut ShaderCanvas> {
+        let canvas = ShaderCanvas::new(
+            self.device.clone(),
+            self.frame_context.swapchain(),
+            fragment_spirv,
+        )?;
+        self.replace_shader_canvas(Some(canvas))?;
+        Ok(self.shader_canvas.as_mut().unwrap())
+    }
+
+    /// Draw a fullscreen fragment shader, read from a SPIR-V file, behind
+    /// every layer.
+    ///
+    /// The shader is rebuilt whenever the file changes. Changes which fail
+    /// to build are logged and the previous shader keeps drawing.
+    pub fn load_shader_canvas(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Result<&mut ShaderCanvas> {
+        let canvas = ShaderCanvas::from_file(
+            self.device.clone(),
+            self.frame_context.swapchain(),
+            path,
+        )?;
+        self.replace_shader_canvas(Some(canvas))?;
+        Ok(self.shader_canvas.as_mut().unwrap())
+    }
+
+    /// Stop drawing the shader canvas.
+    pub fn remove_shader_canvas(&mut self) -> Result<()> {
+        self.replace_shader_canvas(None)
+    }
+
+    /// The current shader canvas, used to set the mouse and channels.
+    pub fn shader_canvas_mut(&mut self) -> Option<&mut ShaderCanvas> {
+        self.shader_canvas.as_mut()
+    }
+
+    /// Rebuild the shader canvas if its source file changed.
+    pub(super) fn reload_changed_shader_canvas(&mut self) -> Result<()> {
+        let changed = self
+            .shader_canvas
+            .as_ref()
+            .map(|canvas| canvas.source_changed())
+            .unwrap_or(false);
+        if changed {
+            self.frame_context.wait_for_frames()?;
+            let swapchain = self.frame_context.swapchain();
+            // SAFE: no frame is using the old pipeline after the wait
+            unsafe { self.shader_canvas.as_mut().unwrap().reload(swapchain) };
+        }
+        Ok(())
+    }
+
+    /// Wait for frames which could be drawing the old canvas before it's
+    /// destroyed.
+    fn replace_shader_canvas(
+        &mut self,
+        canvas: Option<ShaderCanvas>,
+    ) -> Result<()> {
+        if self.shader_canvas.is_some() {
+            self.frame_context.wait_for_frames()?;
+        }
+        self.shader_canvas = canvas;
+        Ok(())
+    }
+}
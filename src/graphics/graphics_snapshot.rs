@@ -0,0 +1,142 @@
+use super::Graphics;
+
+use crate::graphics::{
+    frame::Frame,
+    snapshot::{DescriptorWrite, FrameSnapshot, SnapshotDiff, SnapshotHistory},
+    texture_atlas::{TextureAtlas, SAMPLER_BINDING, TEXTURE_BINDING},
+};
+
+use ash::vk;
+use std::ops::Range;
+
+impl Graphics {
+    /// Start recording a snapshot of the gpu state used by each frame.
+    ///
+    /// At most `capacity` of the most recent snapshots are kept.
+    pub fn enable_snapshots(&mut self, capacity: usize) {
+        self.snapshots = Some(SnapshotHistory::new(capacity));
+    }
+
+    /// Stop recording snapshots and discard the history.
+    pub fn disable_snapshots(&mut self) {
+        self.snapshots = None;
+    }
+
+    /// The number of the next frame to be rendered.
+    pub fn frame_number(&self) -> u64 {
+        self.frame_number
+    }
+
+    /// Get the snapshot for a recently rendered frame.
+    pub fn snapshot(&self, frame_number: u64) -> Option<&FrameSnapshot> {
+        self.snapshots.as_ref()?.get(frame_number)
+    }
+
+    /// Describe what changed between two recently rendered frames.
+    ///
+    /// Returns None if snapshots are disabled or either frame is no longer in
+    /// the history.
+    pub fn diff_snapshots(&self, from: u64, to: u64) -> Option<SnapshotDiff> {
+        self.snapshots.as_ref()?.diff(from, to)
+    }
+
+    pub(super) fn begin_snapshot(&mut self) {
+        let frame_number = self.frame_number;
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.begin_frame(frame_number);
+        }
+    }
+
+    pub(super) fn end_snapshot(&mut self) {
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.end_frame();
+        }
+    }
+
+    /// Record which ranges of the texture atlas were written to its
+    /// descriptor set, one write per range.
+    pub(super) fn snapshot_texture_descriptor_writes(
+        &mut self,
+        ranges: &[Range<usize>],
+    ) {
+        if self.current_snapshot().is_none() {
+            return;
+        }
+        let image_infos = self.texture_atlas.build_descriptor_image_info();
+        let writes: Vec<DescriptorWrite> = ranges
+            .iter()
+            .map(|range| DescriptorWrite {
+                binding: TEXTURE_BINDING,
+                first_element: range.start as u32,
+                resources: image_infos[range.clone()]
+                    .iter()
+                    .map(|info| {
+                        self.device.vulkan_object_name(
+                            vk::ObjectType::IMAGE_VIEW,
+                            &info.image_view,
+                        )
+                    })
+                    .collect(),
+            })
+            .collect();
+        if let Some(snapshot) = self.current_snapshot() {
+            snapshot.descriptor_writes.extend(writes);
+        }
+    }
+
+    /// Record which of the atlas's samplers were written to its descriptor
+    /// set.
+    pub(super) fn snapshot_sampler_descriptor_writes(
+        &mut self,
+        range: Range<usize>,
+    ) {
+        if self.current_snapshot().is_none() {
+            return;
+        }
+        let resources = self.texture_atlas.build_descriptor_sampler_info()
+            [range.clone()]
+        .iter()
+        .map(|info| {
+            self.device
+                .vulkan_object_name(vk::ObjectType::SAMPLER, &info.sampler)
+        })
+        .collect();
+        if let Some(snapshot) = self.current_snapshot() {
+            snapshot.descriptor_writes.push(DescriptorWrite {
+                binding: SAMPLER_BINDING,
+                first_element: range.start as u32,
+                resources,
+            });
+        }
+    }
+
+    /// Record the size of the frame's vertex buffer.
+    pub(super) fn snapshot_vertex_buffer(&mut self, frame: &Frame) {
+        let size = frame.vertices.size;
+        if let Some(snapshot) = self.current_snapshot() {
+            snapshot
+                .buffer_sizes
+                .insert("vertex buffer".to_owned(), size);
+        }
+    }
+
+    pub(super) fn snapshot_pipeline_bind(&mut self, pipeline: vk::Pipeline) {
+        let name = self
+            .device
+            .vulkan_object_name(vk::ObjectType::PIPELINE, &pipeline);
+        if let Some(snapshot) = self.current_snapshot() {
+            snapshot.pipeline_binds.push(name);
+        }
+    }
+
+    pub(super) fn snapshot_draws(&mut self, draw_calls: u32, vertices: u32) {
+        if let Some(snapshot) = self.current_snapshot() {
+            snapshot.draw_calls += draw_calls;
+            snapshot.vertex_count += vertices;
+        }
+    }
+
+    fn current_snapshot(&mut self) -> Option<&mut FrameSnapshot> {
+        self.snapshots.as_mut()?.current_mut()
+    }
+}
@@ -0,0 +1,35 @@
+use super::Graphics;
+
+use crate::graphics::storage::StorageBufferHandle;
+
+use anyhow::Result;
+
+impl Graphics {
+    /// Create an empty storage buffer which shaders can read.
+    pub fn create_storage_buffer(&mut self) -> Result<StorageBufferHandle> {
+        self.storage_buffers.create()
+    }
+
+    /// Replace the contents of a storage buffer.
+    ///
+    /// The data is copied immediately and reaches the gpu the next time each
+    /// frame is rendered, so this never waits for the gpu.
+    pub fn write_storage_buffer<T>(
+        &mut self,
+        handle: StorageBufferHandle,
+        data: &[T],
+    ) -> Result<()>
+    where
+        T: Sized + Copy,
+    {
+        self.storage_buffers.write(handle, data)
+    }
+
+    /// Destroy a storage buffer.
+    pub fn destroy_storage_buffer(
+        &mut self,
+        handle: StorageBufferHandle,
+    ) -> Result<()> {
+        self.storage_buffers.destroy(handle)
+    }
+}
@@ -0,0 +1,31 @@
+use super::Graphics;
+
+use crate::graphics::vulkan::WindowSurface;
+
+use anyhow::Result;
+
+impl Graphics {
+    /// Stop presenting to the window and destroy the swapchain, so the
+    /// window surface can be destroyed.
+    ///
+    /// Call this before the window surface goes away, like when an Android
+    /// activity is paused and its native window is destroyed. Nothing is
+    /// rendered until `resume` is called with the new surface. Layers,
+    /// textures, and every other resource which doesn't depend on the
+    /// surface are kept.
+    pub fn suspend(&mut self) -> Result<()> {
+        self.submit_pending_captures()?;
+        self.frame_context.suspend()
+    }
+
+    /// Build a swapchain for a new window surface and start rendering
+    /// again after `suspend`.
+    pub fn resume(&mut self, window_surface: &dyn WindowSurface) -> Result<()> {
+        self.rebuild_swapchain(window_surface)
+    }
+
+    /// True after `suspend` until `resume` is called.
+    pub fn is_suspended(&self) -> bool {
+        self.frame_context.is_suspended()
+    }
+}
@@ -0,0 +1,59 @@
+use super::Graphics;
+
+use crate::graphics::{pipeline2d::pre_rotation, temporal_aa::TemporalAaPass};
+
+use anyhow::Result;
+use nalgebra as na;
+
+impl Graphics {
+    /// Turn temporal anti-aliasing on or off.
+    ///
+    /// While enabled, layers are drawn with a different sub-pixel offset
+    /// each frame and the frames are blended into a running average. Still
+    /// images converge on smooth edges, moving ones trail slightly. See
+    /// `graphics::temporal_aa`.
+    pub fn set_temporal_aa(&mut self, enabled: bool) -> Result<()> {
+        if enabled == self.temporal_aa.is_some() {
+            return Ok(());
+        }
+        self.frame_context.wait_for_frames()?;
+        self.temporal_aa = if enabled {
+            Some(TemporalAaPass::new(
+                self.device.clone(),
+                self.frame_context.swapchain(),
+            )?)
+        } else {
+            None
+        };
+        self.alias_post_targets()
+    }
+
+    /// True when frames are being jittered and accumulated.
+    pub fn is_temporal_aa_enabled(&self) -> bool {
+        self.temporal_aa.is_some()
+    }
+
+    /// Throw away the accumulated history, so the next frame isn't blended
+    /// with anything drawn before it. Useful after a cut or a jump in the
+    /// camera.
+    pub fn reset_temporal_aa(&mut self) {
+        if let Some(taa) = &mut self.temporal_aa {
+            taa.reset();
+        }
+    }
+
+    /// The transform applied to every projection before it's given to a
+    /// shader: the swapchain's pre-rotation, then the frame's jitter. Exports
+    /// are cropped to the tile being drawn instead.
+    pub(super) fn clip_transform(&self) -> na::Matrix4<f32> {
+        if let Some(target) = &self.export_target {
+            return *target.crop();
+        }
+        let rotation =
+            pre_rotation(self.frame_context.swapchain().pre_transform);
+        match &self.temporal_aa {
+            Some(taa) => taa.jitter() * rotation,
+            None => rotation,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use super::Graphics;
+
+use crate::graphics::{
+    ext::Texture2dFactory,
+    texture_atlas::{TextureAtlas, TextureHandle},
+    texture_generator::Generator,
+    vulkan::buffer::CpuBuffer,
+};
+
+use anyhow::Result;
+use ash::vk;
+
+impl Graphics {
+    /// Generate a procedural texture and add it to the texture atlas.
+    ///
+    /// Generated textures have a single mip level, so noise keeps its fine
+    /// detail at every scale.
+    pub fn generate_texture(
+        &mut self,
+        generator: Generator,
+        (width, height): (u32, u32),
+    ) -> Result<TextureHandle> {
+        let pixels = generator.pixels(width, height);
+        let mut texture = self.create_empty_2d_texture_with_format(
+            format!("{:?}", generator),
+            width,
+            height,
+            1,
+            generator.format(),
+        )?;
+        unsafe {
+            let mut transfer_buffer = CpuBuffer::new(
+                self.device.clone(),
+                vk::BufferUsageFlags::TRANSFER_SRC,
+            )?;
+            transfer_buffer.write_data(&pixels)?;
+            texture.upload_from_buffer(&transfer_buffer)?;
+        }
+        self.add_texture(texture)
+    }
+}
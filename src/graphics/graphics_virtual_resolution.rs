@@ -0,0 +1,170 @@
+use super::Graphics;
+
+use crate::graphics::virtual_resolution::VirtualResolution;
+
+use ash::{version::DeviceV1_0, vk};
+use nalgebra as na;
+
+impl Graphics {
+    /// Draw at a fixed virtual resolution which adapts to any window.
+    ///
+    /// Every camera is fit to the design's aspect ratio instead of the
+    /// window's, screen space layers are measured in virtual pixels, and the
+    /// resolution's policy decides how the design fills the window. See
+    /// `graphics::virtual_resolution`.
+    pub fn set_virtual_resolution(&mut self, resolution: VirtualResolution) {
+        self.virtual_resolution = Some(resolution);
+        self.resolve_layer_projections();
+    }
+
+    /// Go back to drawing at the window's resolution.
+    pub fn clear_virtual_resolution(&mut self) {
+        self.virtual_resolution = None;
+        self.resolve_layer_projections();
+    }
+
+    /// The virtual resolution, if one was set.
+    pub fn virtual_resolution(&self) -> Option<&VirtualResolution> {
+        self.virtual_resolution.as_ref()
+    }
+
+    /// Convert a point in window pixels to virtual pixels, for handling
+    /// mouse input. Points are unchanged when no virtual resolution is set.
+    pub fn window_to_virtual(
+        &self,
+        window_point: &na::Point2<f32>,
+    ) -> na::Point2<f32> {
+        match &self.virtual_resolution {
+            Some(resolution) => resolution.to_virtual(
+                self.frame_context.swapchain().extent,
+                window_point,
+            ),
+            None => *window_point,
+        }
+    }
+
+    /// The virtual resolution applied to the frame being drawn. Exports
+    /// are drawn at their own size, so they don't use it.
+    pub(super) fn active_virtual_resolution(
+        &self,
+    ) -> Option<&VirtualResolution> {
+        self.virtual_resolution
+            .as_ref()
+            .filter(|_| self.export_target.is_none())
+    }
+
+    /// Convert a point in window pixels to normalized device coordinates in
+    /// the layer pass's viewport.
+    pub(super) fn window_to_ndc(
+        &self,
+        window_point: &na::Point2<f32>,
+    ) -> na::Point2<f32> {
+        let extent = self.frame_context.swapchain().extent;
+        let (point, width, height) = match &self.virtual_resolution {
+            Some(resolution) => (
+                resolution.to_virtual(extent, window_point),
+                resolution.width,
+                resolution.height,
+            ),
+            None => (*window_point, extent.width, extent.height),
+        };
+        na::Point2::new(
+            2.0 * point.x / (width.max(1) as f32) - 1.0,
+            2.0 * point.y / (height.max(1) as f32) - 1.0,
+        )
+    }
+
+    /// The viewport used by every layer pipeline.
+    pub(super) fn layer_viewport(&self) -> vk::Viewport {
+        let extent = self.render_extent();
+        let viewport = match self.active_virtual_resolution() {
+            Some(resolution) => resolution.viewport(extent),
+            None => {
+                return vk::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: extent.width as f32,
+                    height: extent.height as f32,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                }
+            }
+        };
+        vk::Viewport {
+            x: viewport.x,
+            y: viewport.y,
+            width: viewport.width,
+            height: viewport.height,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    }
+
+    /// The scissor used by every layer pipeline.
+    pub(super) fn layer_scissor(&self) -> vk::Rect2D {
+        let extent = self.render_extent();
+        match self.active_virtual_resolution() {
+            Some(resolution) => resolution.scissor(extent),
+            None => vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent,
+            },
+        }
+    }
+
+    /// The aspect ratio which cameras are fit to for an image with the
+    /// given size, or None when the image has no area.
+    pub(super) fn camera_aspect_ratio(
+        &self,
+        extent: vk::Extent2D,
+    ) -> Option<f32> {
+        if let Some(resolution) = self.active_virtual_resolution() {
+            return Some(resolution.aspect_ratio());
+        }
+        if extent.width == 0 || extent.height == 0 {
+            return None;
+        }
+        Some(extent.width as f32 / extent.height as f32)
+    }
+
+    /// Clear the parts of the render area outside of the virtual
+    /// resolution's design with its bar color.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording inside of the layer pass
+    pub(super) unsafe fn record_virtual_resolution_bars(
+        &self,
+        command_buffer: vk::CommandBuffer,
+    ) {
+        let resolution = match self.active_virtual_resolution() {
+            Some(resolution) => resolution,
+            None => return,
+        };
+        let bars = resolution.bars(self.render_extent());
+        if bars.is_empty() {
+            return;
+        }
+        let clear_rects: Vec<vk::ClearRect> = bars
+            .into_iter()
+            .map(|rect| vk::ClearRect {
+                rect,
+                base_array_layer: 0,
+                layer_count: 1,
+            })
+            .collect();
+        self.device.logical_device.cmd_clear_attachments(
+            command_buffer,
+            &[vk::ClearAttachment {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                color_attachment: 0,
+                clear_value: vk::ClearValue {
+                    color: vk::ClearColorValue {
+                        float32: resolution.bar_color,
+                    },
+                },
+            }],
+            &clear_rects,
+        );
+    }
+}
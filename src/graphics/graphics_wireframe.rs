@@ -0,0 +1,20 @@
+use super::Graphics;
+
+impl Graphics {
+    /// Draw the edges of every triangle in every layer instead of filling
+    /// them. Layers can also be drawn as wireframes individually with
+    /// `Layer::set_wireframe`.
+    ///
+    /// Ignored on devices which don't support wireframe rendering.
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        if wireframe && !self.device.supports_wireframe() {
+            log::warn!("wireframe rendering is not supported by this device");
+        }
+        self.wireframe = wireframe;
+    }
+
+    /// True when every layer is drawn as a wireframe.
+    pub fn is_wireframe(&self) -> bool {
+        self.wireframe
+    }
+}
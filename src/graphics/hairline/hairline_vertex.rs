@@ -0,0 +1,43 @@
+use super::HairlineVertex;
+
+use ash::vk;
+use memoffset::offset_of;
+
+impl HairlineVertex {
+    /// Build a binding description for this vertex type.
+    pub fn binding_description() -> (
+        Vec<vk::VertexInputBindingDescription>,
+        Vec<vk::VertexInputAttributeDescription>,
+    ) {
+        let binding = vk::VertexInputBindingDescription {
+            binding: 0,
+            stride: std::mem::size_of::<Self>() as u32,
+            input_rate: vk::VertexInputRate::VERTEX,
+        };
+        let pos = vk::VertexInputAttributeDescription {
+            binding: 0,
+            location: 0,
+            format: vk::Format::R32G32_SFLOAT,
+            offset: offset_of!(HairlineVertex, pos) as u32,
+        };
+        let other = vk::VertexInputAttributeDescription {
+            binding: 0,
+            location: 1,
+            format: vk::Format::R32G32_SFLOAT,
+            offset: offset_of!(HairlineVertex, other) as u32,
+        };
+        let side = vk::VertexInputAttributeDescription {
+            binding: 0,
+            location: 2,
+            format: vk::Format::R32_SFLOAT,
+            offset: offset_of!(HairlineVertex, side) as u32,
+        };
+        let rgba = vk::VertexInputAttributeDescription {
+            binding: 0,
+            location: 3,
+            format: vk::Format::R32G32B32A32_SFLOAT,
+            offset: offset_of!(HairlineVertex, rgba) as u32,
+        };
+        (vec![binding], vec![pos, other, side, rgba])
+    }
+}
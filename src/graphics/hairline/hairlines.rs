@@ -0,0 +1,123 @@
+use super::{HairlineVertex, Hairlines};
+
+use nalgebra as na;
+
+impl Hairlines {
+    /// The number of vertices used for each line segment.
+    pub const VERTICES_PER_SEGMENT: usize = 6;
+
+    /// Create an empty set of lines with a width in screen pixels.
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            vertices: vec![],
+        }
+    }
+
+    /// Add a line segment between two points.
+    pub fn push_line(
+        &mut self,
+        start: na::Point2<f32>,
+        end: na::Point2<f32>,
+        rgba: [f32; 4],
+    ) {
+        let corner = |pos: na::Point2<f32>, other: na::Point2<f32>, side| {
+            HairlineVertex {
+                pos: [pos.x, pos.y],
+                other: [other.x, other.y],
+                side,
+                rgba,
+            }
+        };
+
+        // the normal at the end points the opposite way because the shader
+        // computes it from the vertex towards the other endpoint
+        let start_right = corner(start, end, -1.0);
+        let start_left = corner(start, end, 1.0);
+        let end_left = corner(end, start, -1.0);
+        let end_right = corner(end, start, 1.0);
+
+        self.vertices.extend_from_slice(&[
+            start_right,
+            start_left,
+            end_left,
+            start_right,
+            end_left,
+            end_right,
+        ]);
+    }
+
+    /// Add a connected series of line segments.
+    pub fn push_polyline(
+        &mut self,
+        points: &[na::Point2<f32>],
+        rgba: [f32; 4],
+    ) {
+        for segment in points.windows(2) {
+            self.push_line(segment[0], segment[1], rgba);
+        }
+    }
+
+    /// The number of line segments.
+    pub fn segment_count(&self) -> usize {
+        self.vertices.len() / Self::VERTICES_PER_SEGMENT
+    }
+}
+
+impl Default for Hairlines {
+    /// Lines which are a single pixel wide.
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The offset direction the shader would apply to a vertex.
+    fn offset(vertex: &HairlineVertex) -> na::Vector2<f32> {
+        let dir = (na::Point2::from(vertex.other)
+            - na::Point2::from(vertex.pos))
+        .normalize();
+        na::Vector2::new(-dir.y, dir.x) * vertex.side
+    }
+
+    #[test]
+    fn push_line_should_build_a_quad_which_straddles_the_line() {
+        let mut lines = Hairlines::default();
+        lines.push_line(
+            na::Point2::new(0.0, 0.0),
+            na::Point2::new(10.0, 0.0),
+            [1.0; 4],
+        );
+
+        assert_eq!(lines.segment_count(), 1);
+        let offsets: Vec<f32> = lines
+            .vertices
+            .iter()
+            .map(|vertex| offset(vertex).y)
+            .collect();
+        // both triangles cover one side at each end, and the other side
+        assert_eq!(offsets, vec![-1.0, 1.0, 1.0, -1.0, 1.0, -1.0]);
+        assert_eq!(lines.vertices[2].pos, [10.0, 0.0]);
+        assert_eq!(lines.vertices[5].pos, [10.0, 0.0]);
+    }
+
+    #[test]
+    fn push_polyline_should_add_one_segment_per_pair_of_points() {
+        let mut lines = Hairlines::new(2.0);
+        lines.push_polyline(
+            &[
+                na::Point2::new(0.0, 0.0),
+                na::Point2::new(1.0, 0.0),
+                na::Point2::new(1.0, 1.0),
+            ],
+            [1.0; 4],
+        );
+        lines.push_polyline(&[na::Point2::new(5.0, 5.0)], [1.0; 4]);
+
+        assert_eq!(lines.segment_count(), 2);
+        assert_eq!(lines.vertices.len(), 12);
+    }
+}
@@ -0,0 +1,66 @@
+//! Antialiased hairlines which keep a constant width on screen.
+//!
+//! Each line segment is stored as a quad whose vertices know both endpoints.
+//! The vertex shader expands the quad to the requested width in screen
+//! pixels, plus a pixel of feathering, and the fragment shader computes
+//! coverage from the distance to the line's center. Lines stay crisp and
+//! uniform no matter how far the camera zooms.
+//!
+//! The expansion assumes an orthographic projection, like the one used by
+//! `OrthoCamera`.
+
+mod hairline_vertex;
+mod hairlines;
+mod pipeline;
+
+use crate::graphics::Device;
+
+use ash::vk;
+use std::sync::Arc;
+
+/// A vertex for one corner of a hairline segment's quad.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct HairlineVertex {
+    /// The segment endpoint this vertex belongs to.
+    pub pos: [f32; 2],
+
+    /// The segment's other endpoint.
+    pub other: [f32; 2],
+
+    /// Which side of the line the vertex is pushed towards, -1 or 1.
+    pub side: f32,
+
+    pub rgba: [f32; 4],
+}
+
+/// A set of hairlines which share a width.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hairlines {
+    /// The width of every line, in screen pixels.
+    pub width: f32,
+
+    pub vertices: Vec<HairlineVertex>,
+}
+
+/// The vulkan pipeline for rendering hairlines.
+pub struct HairlinePipeline {
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    device: Arc<Device>,
+}
+
+/// The push constants used by the hairline pipeline.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct HairlinePushConsts {
+    pub projection: [[f32; 4]; 4],
+
+    /// The size of the viewport in pixels.
+    pub viewport_size: [f32; 2],
+
+    /// The width of the line in pixels.
+    pub line_width: f32,
+}
@@ -0,0 +1,58 @@
+//! An optional offscreen pass which renders batch ids for pixel-accurate
+//! picking.
+//!
+//! Every batch is drawn with a unique object id into an `R32_UINT` target
+//! using the same vertices and textures as the visible frame. Fragments which
+//! are nearly transparent are discarded, so overlapping sprites are picked
+//! by what is actually visible under the cursor rather than by their bounds.
+
+mod pipeline;
+mod rendering;
+
+use crate::graphics::vulkan::{
+    buffer::{CpuBuffer, ReadbackBuffer},
+    texture::TextureImage,
+    Device,
+};
+
+use ash::vk;
+use std::sync::Arc;
+
+/// The object id written where no batch was drawn.
+pub const NO_OBJECT: u32 = 0;
+
+/// Offscreen resources for rendering and reading back object ids.
+pub struct IdPass {
+    /// The `R32_UINT` image which holds the object id for each pixel.
+    target: TextureImage,
+    render_pass: vk::RenderPass,
+    framebuffer: vk::Framebuffer,
+
+    descriptor_set_layouts: [vk::DescriptorSetLayout; 2],
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+
+    /// The pass keeps its own copy of the vertex data so picking never
+    /// touches resources owned by frames in flight. Textures are read through
+    /// the atlas's shared descriptor set.
+    vertex_buffer: CpuBuffer,
+
+    /// Holds the single object id copied from under the cursor.
+    readback: ReadbackBuffer,
+
+    extent: vk::Extent2D,
+    device: Arc<Device>,
+}
+
+/// The push constants used by the object id pipeline.
+///
+/// The layout extends `pipeline2d::PushConsts` so the same vertex shader can
+/// be used by both pipelines.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct IdPushConsts {
+    pub projection: [[f32; 4]; 4],
+    pub texture_index: u32,
+    pub object_id: u32,
+    pub sampler_index: u32,
+}
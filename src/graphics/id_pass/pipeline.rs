@@ -0,0 +1,285 @@
+//! Functions to create the render pass and graphics pipeline used by the
+//! object id pass.
+
+use super::IdPushConsts;
+
+use crate::graphics::{
+    pipeline2d::descriptor_sets,
+    vertex::Vertex2d,
+    vulkan::{ffi, shader_module::ShaderModule, Device},
+};
+
+use anyhow::{Context, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::{
+    ffi::{c_void, CString},
+    mem::size_of,
+    sync::Arc,
+};
+
+/// The format of the object id target.
+pub const ID_FORMAT: vk::Format = vk::Format::R32_UINT;
+
+/// Create a render pass which clears the id target and leaves it ready to be
+/// copied into a buffer.
+pub fn create_render_pass(device: &Device) -> Result<vk::RenderPass> {
+    let attachments = [vk::AttachmentDescription {
+        format: ID_FORMAT,
+        samples: vk::SampleCountFlags::TYPE_1,
+        load_op: vk::AttachmentLoadOp::CLEAR,
+        store_op: vk::AttachmentStoreOp::STORE,
+        stencil_load_op: vk::AttachmentLoadOp::DONT_CARE,
+        stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        final_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ..Default::default()
+    }];
+
+    let color_references = [vk::AttachmentReference {
+        attachment: 0,
+        layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+    }];
+
+    let subpasses = [vk::SubpassDescription {
+        pipeline_bind_point: vk::PipelineBindPoint::GRAPHICS,
+        p_color_attachments: color_references.as_ptr(),
+        color_attachment_count: color_references.len() as u32,
+        ..Default::default()
+    }];
+
+    let dependencies = [
+        vk::SubpassDependency {
+            src_subpass: vk::SUBPASS_EXTERNAL,
+            src_stage_mask: vk::PipelineStageFlags::TRANSFER,
+            src_access_mask: vk::AccessFlags::TRANSFER_READ,
+            dst_subpass: 0,
+            dst_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            dst_access_mask: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            dependency_flags: vk::DependencyFlags::default(),
+        },
+        vk::SubpassDependency {
+            src_subpass: 0,
+            src_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            src_access_mask: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            dst_subpass: vk::SUBPASS_EXTERNAL,
+            dst_stage_mask: vk::PipelineStageFlags::TRANSFER,
+            dst_access_mask: vk::AccessFlags::TRANSFER_READ,
+            dependency_flags: vk::DependencyFlags::default(),
+        },
+    ];
+
+    let create_info = vk::RenderPassCreateInfo {
+        p_attachments: attachments.as_ptr(),
+        attachment_count: attachments.len() as u32,
+        p_subpasses: subpasses.as_ptr(),
+        subpass_count: subpasses.len() as u32,
+        p_dependencies: dependencies.as_ptr(),
+        dependency_count: dependencies.len() as u32,
+        ..Default::default()
+    };
+
+    let render_pass = unsafe {
+        device
+            .logical_device
+            .create_render_pass(&create_info, None)?
+    };
+
+    device.name_vulkan_object(
+        "Object Id Render Pass",
+        vk::ObjectType::RENDER_PASS,
+        &render_pass,
+    )?;
+
+    Ok(render_pass)
+}
+
+/// Create the pipeline which writes each batch's object id.
+///
+/// Returns the descriptor set layout, pipeline layout, and pipeline. The
+/// caller is responsible for destroying all three.
+pub fn create_pipeline(
+    device: &Arc<Device>,
+    render_pass: vk::RenderPass,
+    extent: vk::Extent2D,
+) -> Result<(
+    [vk::DescriptorSetLayout; 2],
+    vk::PipelineLayout,
+    vk::Pipeline,
+)> {
+    let vertex_module = ShaderModule::new(
+        device,
+        "Object Id Vertex Shader",
+        std::include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/shaders/texture2d.vert.sprv"
+        )),
+    )?;
+    let fragment_module = ShaderModule::new(
+        device,
+        "Object Id Fragment Shader",
+        std::include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/shaders/object_id.frag.sprv"
+        )),
+    )?;
+
+    let entry = CString::new("main").unwrap();
+    let vertex_create_info = vk::PipelineShaderStageCreateInfo {
+        stage: vk::ShaderStageFlags::VERTEX,
+        module: vertex_module.shader_module,
+        p_name: entry.as_ptr(),
+        ..Default::default()
+    };
+
+    let specialization_map_entries =
+        descriptor_sets::atlas_specialization_entries();
+    let array_sizes = descriptor_sets::atlas_array_sizes(device);
+    let specialization_data = unsafe { ffi::any_as_u8_slice(&array_sizes) };
+    let fragment_specialization_info = vk::SpecializationInfo {
+        p_map_entries: specialization_map_entries.as_ptr(),
+        map_entry_count: specialization_map_entries.len() as u32,
+        p_data: specialization_data.as_ptr() as *const c_void,
+        data_size: specialization_data.len(),
+    };
+    let fragment_create_info = vk::PipelineShaderStageCreateInfo {
+        stage: vk::ShaderStageFlags::FRAGMENT,
+        module: fragment_module.shader_module,
+        p_specialization_info: &fragment_specialization_info,
+        p_name: entry.as_ptr(),
+        ..Default::default()
+    };
+
+    let (binding_descriptions, attribute_descriptions) =
+        Vertex2d::binding_description();
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo {
+        p_vertex_binding_descriptions: binding_descriptions.as_ptr(),
+        vertex_binding_description_count: binding_descriptions.len() as u32,
+        p_vertex_attribute_descriptions: attribute_descriptions.as_ptr(),
+        vertex_attribute_description_count: attribute_descriptions.len() as u32,
+        ..Default::default()
+    };
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo {
+        topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+        primitive_restart_enable: 0,
+        ..Default::default()
+    };
+
+    let viewports = [vk::Viewport {
+        x: 0.0,
+        y: 0.0,
+        width: extent.width as f32,
+        height: extent.height as f32,
+        min_depth: 0.0,
+        max_depth: 1.0,
+    }];
+
+    let scissors = [vk::Rect2D {
+        offset: vk::Offset2D { x: 0, y: 0 },
+        extent,
+    }];
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo {
+        p_viewports: viewports.as_ptr(),
+        viewport_count: 1,
+        p_scissors: scissors.as_ptr(),
+        scissor_count: 1,
+        ..Default::default()
+    };
+
+    let raster_state = vk::PipelineRasterizationStateCreateInfo {
+        polygon_mode: vk::PolygonMode::FILL,
+        line_width: 1.0,
+        cull_mode: vk::CullModeFlags::NONE,
+        front_face: vk::FrontFace::CLOCKWISE,
+        ..Default::default()
+    };
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo {
+        rasterization_samples: vk::SampleCountFlags::TYPE_1,
+        min_sample_shading: 1.0,
+        ..Default::default()
+    };
+
+    // integer attachments can't be blended, later draws simply replace the
+    // ids written by earlier draws
+    let blend_attachments = [vk::PipelineColorBlendAttachmentState {
+        color_write_mask: vk::ColorComponentFlags::R,
+        blend_enable: 0,
+        ..Default::default()
+    }];
+
+    let blend_state = vk::PipelineColorBlendStateCreateInfo {
+        logic_op_enable: 0,
+        logic_op: vk::LogicOp::COPY,
+        p_attachments: blend_attachments.as_ptr(),
+        attachment_count: blend_attachments.len() as u32,
+        ..Default::default()
+    };
+
+    let descriptor_set_layouts = unsafe {
+        descriptor_sets::create_descriptor_set_layouts(device, "Object Id")?
+    };
+    let push_constant_ranges = [vk::PushConstantRange {
+        stage_flags: vk::ShaderStageFlags::FRAGMENT
+            | vk::ShaderStageFlags::VERTEX,
+        size: size_of::<IdPushConsts>() as u32,
+        offset: 0,
+    }];
+    let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+        p_set_layouts: descriptor_set_layouts.as_ptr(),
+        set_layout_count: descriptor_set_layouts.len() as u32,
+        p_push_constant_ranges: push_constant_ranges.as_ptr(),
+        push_constant_range_count: push_constant_ranges.len() as u32,
+        ..Default::default()
+    };
+
+    let pipeline_layout = unsafe {
+        device
+            .logical_device
+            .create_pipeline_layout(&pipeline_layout_create_info, None)?
+    };
+    device.name_vulkan_object(
+        "Object Id Pipeline Layout",
+        vk::ObjectType::PIPELINE_LAYOUT,
+        &pipeline_layout,
+    )?;
+
+    let stages = [vertex_create_info, fragment_create_info];
+    let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+        p_stages: stages.as_ptr(),
+        stage_count: stages.len() as u32,
+        p_vertex_input_state: &vertex_input_state,
+        p_input_assembly_state: &input_assembly_state,
+        p_viewport_state: &viewport_state,
+        p_rasterization_state: &raster_state,
+        p_multisample_state: &multisample_state,
+        p_color_blend_state: &blend_state,
+        layout: pipeline_layout,
+        render_pass,
+        subpass: 0,
+        base_pipeline_index: -1,
+        base_pipeline_handle: vk::Pipeline::null(),
+        ..Default::default()
+    };
+
+    let pipelines = unsafe {
+        device
+            .logical_device
+            .create_graphics_pipelines(
+                vk::PipelineCache::null(),
+                &[pipeline_create_info],
+                None,
+            )
+            .map_err(|(_, err)| err)
+            .context("unable to create the object id pipeline")?
+    };
+    let pipeline = pipelines[0];
+    device.name_vulkan_object(
+        "Object Id Pipeline",
+        vk::ObjectType::PIPELINE,
+        &pipeline,
+    )?;
+
+    Ok((descriptor_set_layouts, pipeline_layout, pipeline))
+}
@@ -0,0 +1,274 @@
+use super::{pipeline, IdPass, IdPushConsts, NO_OBJECT};
+
+use crate::graphics::{
+    layer::LayerStack,
+    pipeline2d::descriptor_sets,
+    texture_atlas::{GpuAtlas, ATLAS_DESCRIPTOR_SET},
+    vulkan::{
+        buffer::{Buffer, CpuBuffer, ReadbackBuffer},
+        ffi::any_as_u8_slice,
+        texture::TextureImage,
+        Device,
+    },
+};
+
+use anyhow::Result;
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+impl IdPass {
+    /// Create the offscreen target and pipeline for rendering object ids at
+    /// the given size.
+    pub fn new(device: Arc<Device>, extent: vk::Extent2D) -> Result<Self> {
+        let target = TextureImage::new(
+            device.clone(),
+            vk::ImageCreateInfo {
+                image_type: vk::ImageType::TYPE_2D,
+                extent: vk::Extent3D {
+                    width: extent.width,
+                    height: extent.height,
+                    depth: 1,
+                },
+                mip_levels: 1,
+                array_layers: 1,
+                format: pipeline::ID_FORMAT,
+                tiling: vk::ImageTiling::OPTIMAL,
+                initial_layout: vk::ImageLayout::UNDEFINED,
+                usage: vk::ImageUsageFlags::COLOR_ATTACHMENT
+                    | vk::ImageUsageFlags::TRANSFER_SRC,
+                samples: vk::SampleCountFlags::TYPE_1,
+                sharing_mode: vk::SharingMode::EXCLUSIVE,
+                ..Default::default()
+            },
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+        device.name_vulkan_object(
+            "Object Id Target - Image",
+            vk::ObjectType::IMAGE,
+            unsafe { &target.raw_image() },
+        )?;
+
+        let render_pass = pipeline::create_render_pass(&device)?;
+        let attachments = [unsafe { target.raw_view() }];
+        let framebuffer = unsafe {
+            device.logical_device.create_framebuffer(
+                &vk::FramebufferCreateInfo {
+                    render_pass,
+                    p_attachments: attachments.as_ptr(),
+                    attachment_count: attachments.len() as u32,
+                    width: extent.width,
+                    height: extent.height,
+                    layers: 1,
+                    ..Default::default()
+                },
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            "Object Id Framebuffer",
+            vk::ObjectType::FRAMEBUFFER,
+            &framebuffer,
+        )?;
+
+        let (descriptor_set_layouts, pipeline_layout, pipeline) =
+            pipeline::create_pipeline(&device, render_pass, extent)?;
+
+        Ok(Self {
+            target,
+            render_pass,
+            framebuffer,
+            descriptor_set_layouts,
+            pipeline_layout,
+            pipeline,
+            vertex_buffer: CpuBuffer::new(
+                device.clone(),
+                vk::BufferUsageFlags::VERTEX_BUFFER,
+            )?,
+            readback: ReadbackBuffer::new(
+                device.clone(),
+                std::mem::size_of::<u32>() as u64,
+            )?,
+            extent,
+            device,
+        })
+    }
+
+    /// The size of the object id target.
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+
+    /// Render every batch with its object id, then read back the id under a
+    /// pixel.
+    ///
+    /// Batches are numbered from 1 in render order, the same order as
+    /// `LayerStack::layers_with_handles`, and `NO_OBJECT` is returned when no
+    /// batch covers the pixel. Blocks until the gpu has finished.
+    ///
+    /// # Safety
+    ///
+    /// - the texture atlas's textures must not be destroyed while the pass is
+    ///   rendering
+    /// - the texture atlas's descriptor set must already be written with
+    ///   every texture the layers use
+    pub unsafe fn read_object_id(
+        &mut self,
+        layer_stack: &LayerStack,
+        texture_atlas: &GpuAtlas,
+        (x, y): (u32, u32),
+    ) -> Result<u32> {
+        let all_vertices = layer_stack.vertices();
+        if all_vertices.iter().all(|vertices| vertices.is_empty())
+            || x >= self.extent.width
+            || y >= self.extent.height
+        {
+            return Ok(NO_OBJECT);
+        }
+
+        // SAFE: the previous pick blocked until the gpu finished
+        self.vertex_buffer.write_data_arrays(&all_vertices)?;
+
+        let device = self.device.clone();
+        device.sync_graphics_commands(|command_buffer| {
+            self.record_draw_commands(
+                command_buffer,
+                layer_stack,
+                texture_atlas,
+            );
+            self.readback.record_image_copy(
+                command_buffer,
+                self.target.raw_image(),
+                vk::Offset2D {
+                    x: x as i32,
+                    y: y as i32,
+                },
+                vk::Extent2D {
+                    width: 1,
+                    height: 1,
+                },
+            );
+            Ok(())
+        })?;
+
+        let bytes = self.readback.read_bytes(4)?;
+        Ok(u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    unsafe fn record_draw_commands(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        layer_stack: &LayerStack,
+        texture_atlas: &GpuAtlas,
+    ) {
+        let logical_device = &self.device.logical_device;
+        let clear_values = [vk::ClearValue {
+            color: vk::ClearColorValue {
+                uint32: [NO_OBJECT; 4],
+            },
+        }];
+        let render_pass_begin_info = vk::RenderPassBeginInfo {
+            render_pass: self.render_pass,
+            framebuffer: self.framebuffer,
+            render_area: vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent: self.extent,
+            },
+            p_clear_values: clear_values.as_ptr(),
+            clear_value_count: clear_values.len() as u32,
+            ..Default::default()
+        };
+        logical_device.cmd_begin_render_pass(
+            command_buffer,
+            &render_pass_begin_info,
+            vk::SubpassContents::INLINE,
+        );
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline,
+        );
+        logical_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline_layout,
+            ATLAS_DESCRIPTOR_SET,
+            &[texture_atlas.raw_descriptor_set()],
+            &[],
+        );
+        logical_device.cmd_bind_vertex_buffers(
+            command_buffer,
+            0,
+            &[self.vertex_buffer.raw()],
+            &[0],
+        );
+
+        let mut offset: u32 = 0;
+        let mut object_id = NO_OBJECT;
+        for layer in layer_stack.layers() {
+            let projections = layer.projections();
+            for batch in layer.batches() {
+                object_id += 1;
+                for projection in &projections {
+                    let consts = IdPushConsts {
+                        projection: (*projection).into(),
+                        texture_index: texture_atlas
+                            .shader_texture_index(batch.texture_handle),
+                        object_id,
+                        sampler_index: texture_atlas
+                            .sampler_index(batch.texture_handle),
+                    };
+                    logical_device.cmd_push_constants(
+                        command_buffer,
+                        self.pipeline_layout,
+                        vk::ShaderStageFlags::FRAGMENT
+                            | vk::ShaderStageFlags::VERTEX,
+                        0,
+                        any_as_u8_slice(&consts),
+                    );
+                    logical_device.cmd_draw(
+                        command_buffer,
+                        batch.vertices.len() as u32,
+                        1,
+                        offset,
+                        0,
+                    );
+                }
+                offset += batch.vertices.len() as u32;
+            }
+        }
+
+        logical_device.cmd_end_render_pass(command_buffer);
+    }
+}
+
+impl Drop for IdPass {
+    fn drop(&mut self) {
+        unsafe {
+            let device = &self.device;
+            device
+                .forget_vulkan_object(vk::ObjectType::PIPELINE, &self.pipeline);
+            device.forget_vulkan_object(
+                vk::ObjectType::PIPELINE_LAYOUT,
+                &self.pipeline_layout,
+            );
+            device.forget_vulkan_object(
+                vk::ObjectType::FRAMEBUFFER,
+                &self.framebuffer,
+            );
+            device.forget_vulkan_object(
+                vk::ObjectType::RENDER_PASS,
+                &self.render_pass,
+            );
+
+            let logical_device = &device.logical_device;
+            logical_device.destroy_pipeline(self.pipeline, None);
+            logical_device.destroy_pipeline_layout(self.pipeline_layout, None);
+            descriptor_sets::destroy_descriptor_set_layouts(
+                device,
+                &self.descriptor_set_layouts,
+            );
+            logical_device.destroy_framebuffer(self.framebuffer, None);
+            logical_device.destroy_render_pass(self.render_pass, None);
+        }
+    }
+}
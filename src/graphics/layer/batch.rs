@@ -1,5 +1,12 @@
 use super::Batch;
 
+use crate::{
+    geometry::Rect,
+    graphics::{texture_atlas::TextureHandle, vertex::UvTransform},
+};
+
+use nalgebra as na;
+
 impl Batch {
     /// Create a new empty batch.
     pub fn empty() -> Self {
@@ -7,4 +14,112 @@ impl Batch {
             ..Default::default()
         }
     }
+
+    /// Draw one layer of the batch's 2d array texture.
+    pub fn with_layer(self, layer: u32) -> Self {
+        Self { layer, ..self }
+    }
+
+    /// Draw the batch's indexed texture with a palette.
+    pub fn with_palette(self, palette: TextureHandle) -> Self {
+        Self {
+            palette: Some(palette),
+            ..self
+        }
+    }
+
+    /// Flip, tile, or scroll the texture coordinates of every vertex.
+    ///
+    /// Flips mirror the coordinates within the range the batch covers, so
+    /// a batch drawing one cell of a sprite sheet stays in that cell.
+    pub fn transform_uvs(&mut self, transform: &UvTransform) {
+        transform.apply_to_vertices(&mut self.vertices);
+    }
+
+    /// The axis-aligned bounds of the batch's vertices, or None when the
+    /// batch is empty.
+    pub fn bounds(&self) -> Option<Rect<f32>> {
+        let (first, rest) = self.vertices.split_first()?;
+        let initial = Rect {
+            left: first.pos[0],
+            right: first.pos[0],
+            bottom: first.pos[1],
+            top: first.pos[1],
+        };
+        Some(rest.iter().fold(initial, |rect, vertex| Rect {
+            left: rect.left.min(vertex.pos[0]),
+            right: rect.right.max(vertex.pos[0]),
+            bottom: rect.bottom.min(vertex.pos[1]),
+            top: rect.top.max(vertex.pos[1]),
+        }))
+    }
+
+    /// True when any part of the batch's bounds can be seen through the
+    /// projection.
+    pub fn is_visible(&self, projection: &na::Matrix4<f32>) -> bool {
+        let bounds = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return false,
+        };
+        let corners = [
+            (bounds.left, bounds.bottom),
+            (bounds.right, bounds.bottom),
+            (bounds.left, bounds.top),
+            (bounds.right, bounds.top),
+        ];
+        let mut ndc = Rect {
+            left: f32::INFINITY,
+            right: f32::NEG_INFINITY,
+            bottom: f32::INFINITY,
+            top: f32::NEG_INFINITY,
+        };
+        for (x, y) in corners.iter() {
+            let point =
+                projection.transform_point(&na::Point3::new(*x, *y, 0.0));
+            ndc.left = ndc.left.min(point.x);
+            ndc.right = ndc.right.max(point.x);
+            ndc.bottom = ndc.bottom.min(point.y);
+            ndc.top = ndc.top.max(point.y);
+        }
+        ndc.left <= 1.0
+            && ndc.right >= -1.0
+            && ndc.bottom <= 1.0
+            && ndc.top >= -1.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::vertex::Vertex2d;
+
+    fn batch_at(x: f32, y: f32) -> Batch {
+        let vertex = |dx: f32, dy: f32| Vertex2d {
+            pos: [x + dx, y + dy],
+            ..Default::default()
+        };
+        Batch {
+            vertices: vec![
+                vertex(0.0, 0.0),
+                vertex(1.0, 0.0),
+                vertex(0.0, 1.0),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn is_visible_should_test_bounds_against_the_view() {
+        let projection =
+            na::Orthographic3::new(0.0, 10.0, 0.0, 10.0, 1.0, -1.0)
+                .to_homogeneous();
+
+        assert!(batch_at(5.0, 5.0).is_visible(&projection));
+        // partially overlapping the left edge
+        assert!(batch_at(-0.5, 5.0).is_visible(&projection));
+        assert!(!batch_at(20.0, 5.0).is_visible(&projection));
+        assert!(!batch_at(5.0, -3.0).is_visible(&projection));
+        assert!(!Batch::empty().is_visible(&projection));
+    }
 }
@@ -0,0 +1,79 @@
+use super::ColorWriteMask;
+
+use ash::vk;
+
+impl ColorWriteMask {
+    /// Write every channel.
+    pub const ALL: Self = Self::new(true, true, true, true);
+
+    /// Write red, green, and blue but leave alpha alone.
+    pub const RGB: Self = Self::new(true, true, true, false);
+
+    /// Only write alpha.
+    pub const ALPHA: Self = Self::new(false, false, false, true);
+
+    /// Write nothing.
+    pub const NONE: Self = Self::new(false, false, false, false);
+
+    /// Create a mask from individual channels.
+    pub const fn new(red: bool, green: bool, blue: bool, alpha: bool) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// The equivalent vulkan color component flags.
+    pub fn as_vk_flags(&self) -> vk::ColorComponentFlags {
+        let mut flags = vk::ColorComponentFlags::empty();
+        if self.red {
+            flags |= vk::ColorComponentFlags::R;
+        }
+        if self.green {
+            flags |= vk::ColorComponentFlags::G;
+        }
+        if self.blue {
+            flags |= vk::ColorComponentFlags::B;
+        }
+        if self.alpha {
+            flags |= vk::ColorComponentFlags::A;
+        }
+        flags
+    }
+}
+
+impl Default for ColorWriteMask {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn all_channels_should_match_the_default_pipeline() {
+        assert_eq!(
+            ColorWriteMask::ALL.as_vk_flags(),
+            vk::ColorComponentFlags::R
+                | vk::ColorComponentFlags::G
+                | vk::ColorComponentFlags::B
+                | vk::ColorComponentFlags::A
+        );
+    }
+
+    #[test]
+    fn partial_masks_should_only_set_their_channels() {
+        assert_eq!(
+            ColorWriteMask::ALPHA.as_vk_flags(),
+            vk::ColorComponentFlags::A
+        );
+        assert!(!ColorWriteMask::RGB
+            .as_vk_flags()
+            .contains(vk::ColorComponentFlags::A));
+        assert!(ColorWriteMask::NONE.as_vk_flags().is_empty());
+    }
+}
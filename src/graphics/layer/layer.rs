@@ -1,4 +1,9 @@
-use super::{Batch, Layer};
+use super::{Batch, ColorWriteMask, Layer, LayerSpace};
+
+use crate::graphics::{
+    cameras::Cameras, custom_pipeline::CustomBatch, hairline::Hairlines,
+    texture_atlas::TextureHandle,
+};
 
 use nalgebra as na;
 
@@ -8,12 +13,21 @@ impl Layer {
         Self {
             projection: na::Matrix4::identity(),
             batches: vec![],
+            custom_batches: vec![],
+            hairlines: vec![],
+            wrap_bounds: None,
+            color_write_mask: ColorWriteMask::ALL,
+            blend_constants: None,
+            wireframe: false,
+            space: LayerSpace::Custom,
         }
     }
 
-    /// Clear all batches from the layer.
+    /// Clear all batches and hairlines from the layer.
     pub fn clear(&mut self) {
         self.batches.clear();
+        self.custom_batches.clear();
+        self.hairlines.clear();
     }
 
     /// Set the layer's projection matrix.
@@ -26,6 +40,38 @@ impl Layer {
         &self.projection
     }
 
+    /// Choose where the layer's projection comes from.
+    ///
+    /// World, screen, and camera space layers have their projection replaced
+    /// every frame, so `set_projection` only matters for custom layers.
+    pub fn set_space(&mut self, space: LayerSpace) {
+        self.space = space;
+    }
+
+    /// Where the layer's projection comes from.
+    pub fn space(&self) -> LayerSpace {
+        self.space
+    }
+
+    /// Replace the projection of world, screen, and camera space layers.
+    pub(crate) fn resolve_projection(
+        &mut self,
+        world: &na::Matrix4<f32>,
+        screen: &na::Matrix4<f32>,
+        cameras: &Cameras,
+    ) {
+        match self.space {
+            LayerSpace::Custom => (),
+            LayerSpace::World => self.projection = *world,
+            LayerSpace::Screen => self.projection = *screen,
+            LayerSpace::Camera(handle) => {
+                if let Some(projection) = cameras.projection(handle) {
+                    self.projection = projection;
+                }
+            }
+        }
+    }
+
     /// Add a batch to the layer.
     ///
     /// Batches will persist until `clear` is called on this layer.
@@ -43,4 +89,87 @@ impl Layer {
     pub fn batches(&self) -> &[Batch] {
         &self.batches
     }
+
+    /// Change the palette used to draw a batch, or stop using one.
+    ///
+    /// Only the handle is stored, so palettes can be swapped every frame
+    /// without touching the batch's vertices. Returns false when the layer
+    /// has no batch at the index.
+    pub fn set_batch_palette(
+        &mut self,
+        batch_index: usize,
+        palette: Option<TextureHandle>,
+    ) -> bool {
+        match self.batches.get_mut(batch_index) {
+            Some(batch) => {
+                batch.palette = palette;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Add a batch of vertices for a custom pipeline to the layer.
+    ///
+    /// Custom batches are drawn after the layer's regular batches and
+    /// persist until `clear` is called on this layer.
+    pub fn push_custom_batch(&mut self, batch: CustomBatch) {
+        self.custom_batches.push(batch);
+    }
+
+    pub fn custom_batches(&self) -> &[CustomBatch] {
+        &self.custom_batches
+    }
+
+    /// Add a set of hairlines to the layer.
+    ///
+    /// Hairlines will persist until `clear` is called on this layer.
+    pub fn push_hairlines(&mut self, hairlines: Hairlines) {
+        self.hairlines.push(hairlines);
+    }
+
+    pub fn hairlines(&self) -> &[Hairlines] {
+        &self.hairlines
+    }
+
+    /// Choose which color channels the layer's batches write.
+    ///
+    /// Hairlines always write every channel.
+    pub fn set_color_write_mask(&mut self, mask: ColorWriteMask) {
+        self.color_write_mask = mask;
+    }
+
+    /// The color channels the layer's batches write.
+    pub fn color_write_mask(&self) -> ColorWriteMask {
+        self.color_write_mask
+    }
+
+    /// Blend the layer's batches with constant weights instead of their
+    /// alpha.
+    ///
+    /// Each channel is written as `src * constant + dst * (1 - constant)`,
+    /// so constants of 0.25 blend a quarter of the layer over what's below
+    /// regardless of texture transparency. None restores alpha blending.
+    pub fn set_blend_constants(&mut self, constants: Option<[f32; 4]>) {
+        self.blend_constants = constants;
+    }
+
+    /// The constants the layer is blended with, if any.
+    pub fn blend_constants(&self) -> Option<[f32; 4]> {
+        self.blend_constants
+    }
+
+    /// Draw the edges of every triangle in the layer's batches instead of
+    /// filling them, for inspecting tessellation.
+    ///
+    /// Ignored on devices which don't support wireframe rendering, see
+    /// `Device::supports_wireframe`.
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        self.wireframe = wireframe;
+    }
+
+    /// True when the layer's batches are drawn as wireframes.
+    pub fn wireframe(&self) -> bool {
+        self.wireframe
+    }
 }
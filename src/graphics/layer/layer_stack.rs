@@ -1,9 +1,13 @@
 use std::collections::HashMap;
 
-use crate::graphics::vertex::Vertex2d;
+use crate::graphics::{
+    cameras::Cameras, hairline::HairlineVertex, vertex::Vertex2d,
+};
 
 use super::{Layer, LayerHandle, LayerStack};
 
+use nalgebra as na;
+
 impl LayerStack {
     /// Create a new stack with zero visible layers.
     pub fn new() -> Self {
@@ -16,23 +20,31 @@ impl LayerStack {
     /// Add a layer to be rendered on top of all existing layers.
     pub fn add_layer_to_top(&mut self) -> LayerHandle {
         let handle = LayerHandle::generate();
-        self.layers.insert(handle, Layer::empty());
-        self.render_order.push(handle);
+        self.insert_layer_on_top(handle);
         handle
     }
 
     /// Add a layer to be rendered under all existing layers
     pub fn add_layer_to_bottom(&mut self) -> LayerHandle {
         let handle = LayerHandle::generate();
-        self.layers.insert(handle, Layer::empty());
-        if self.render_order.is_empty() {
-            self.render_order.push(handle);
-        } else {
-            self.render_order.insert(0, handle);
-        }
+        self.insert_layer_on_bottom(handle);
         handle
     }
 
+    /// Add an empty layer with a handle which was generated elsewhere, like
+    /// on another thread, on top of all existing layers.
+    pub(crate) fn insert_layer_on_top(&mut self, handle: LayerHandle) {
+        self.layers.insert(handle, Layer::empty());
+        self.render_order.push(handle);
+    }
+
+    /// Add an empty layer with a handle which was generated elsewhere under
+    /// all existing layers.
+    pub(crate) fn insert_layer_on_bottom(&mut self, handle: LayerHandle) {
+        self.layers.insert(handle, Layer::empty());
+        self.render_order.insert(0, handle);
+    }
+
     /// Return the set of all layer references in their render order.
     pub fn layers(&self) -> Vec<&Layer> {
         self.render_order
@@ -41,6 +53,21 @@ impl LayerStack {
             .collect::<Vec<&Layer>>()
     }
 
+    /// Return every layer along with its handle in render order.
+    pub fn layers_with_handles(&self) -> Vec<(LayerHandle, &Layer)> {
+        self.render_order
+            .iter()
+            .map(|handle| (*handle, self.layers.get(handle).unwrap()))
+            .collect()
+    }
+
+    /// Get the layer referenced by a handle.
+    ///
+    /// Returns None if the handle is invalid.
+    pub fn get_layer(&self, handle: &LayerHandle) -> Option<&Layer> {
+        self.layers.get(handle)
+    }
+
     /// Get the layer referenced by a handle.
     ///
     /// Returns None if the handle is invalid.
@@ -51,6 +78,19 @@ impl LayerStack {
         self.layers.get_mut(handle)
     }
 
+    /// Give every world, screen, and camera space layer its projection for
+    /// the next frame.
+    pub(crate) fn resolve_projections(
+        &mut self,
+        world: &na::Matrix4<f32>,
+        screen: &na::Matrix4<f32>,
+        cameras: &Cameras,
+    ) {
+        for layer in self.layers.values_mut() {
+            layer.resolve_projection(world, screen, cameras);
+        }
+    }
+
     /// Get the slice of all vertices for all layers and batches in order.
     ///
     /// This can be used to build a vertex buffer when rendering.
@@ -83,4 +123,27 @@ impl LayerStack {
         }
         verts
     }
+
+    /// Get the bytes of every custom batch for all layers in order.
+    pub fn custom_vertex_bytes(&self) -> Vec<&[u8]> {
+        self.render_order
+            .iter()
+            .map(|handle| self.layers.get(handle).unwrap())
+            .flat_map(|layer| &layer.custom_batches)
+            .map(|batch| batch.bytes())
+            .collect()
+    }
+
+    /// Get the slice of all hairline vertices for all layers in order.
+    ///
+    /// The layout matches `vertices`, with each layer's hairlines in the
+    /// order they were added.
+    pub fn hairline_vertices(&self) -> Vec<&[HairlineVertex]> {
+        self.render_order
+            .iter()
+            .map(|handle| self.layers.get(handle).unwrap())
+            .flat_map(|layer| &layer.hairlines)
+            .map(|hairlines| hairlines.vertices.as_slice())
+            .collect()
+    }
 }
@@ -1,11 +1,19 @@
 mod batch;
+mod color_write_mask;
 mod layer;
 mod layer_handle;
 mod layer_stack;
+mod wrap;
 
 use std::collections::HashMap;
 
-use crate::graphics::{texture_atlas::TextureHandle, vertex::Vertex2d};
+use crate::{
+    geometry::Rect,
+    graphics::{
+        cameras::CameraHandle, custom_pipeline::CustomBatch,
+        hairline::Hairlines, texture_atlas::TextureHandle, vertex::Vertex2d,
+    },
+};
 
 /// A layer handle is a unique reference to a layer.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -16,9 +24,74 @@ pub struct LayerHandle {
 /// Layers are ordered, back to front, and render a persistent collection of
 /// vertex batches.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Layer {
     projection: nalgebra::Matrix4<f32>,
     batches: Vec<Batch>,
+
+    /// Batches for custom pipelines are drawn after the regular batches.
+    /// Pipeline handles are only valid for one run, so they aren't saved.
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    custom_batches: Vec<CustomBatch>,
+
+    /// Hairlines are drawn after all of the layer's batches.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    hairlines: Vec<Hairlines>,
+
+    /// When set, the layer is drawn again shifted by the size of these
+    /// bounds wherever the view extends past them.
+    wrap_bounds: Option<Rect<f32>>,
+
+    /// The color channels which the layer's batches write.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    color_write_mask: ColorWriteMask,
+
+    /// When set, batches are blended with these constants instead of their
+    /// alpha. See `Layer::set_blend_constants`.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    blend_constants: Option<[f32; 4]>,
+
+    /// Draw the outlines of the layer's triangles instead of filling them.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    wireframe: bool,
+
+    /// Where the layer's projection comes from.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    space: LayerSpace,
+}
+
+/// Where a layer's projection comes from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayerSpace {
+    /// The projection is whatever was last passed to
+    /// `Layer::set_projection`.
+    #[default]
+    Custom,
+
+    /// The layer follows the camera, see `Graphics::set_camera`.
+    World,
+
+    /// The layer is fixed to the window, in pixels with the origin in the
+    /// top left. This is the usual choice for HUDs and interfaces.
+    Screen,
+
+    /// The layer follows a named camera, see `Graphics::add_camera`. The
+    /// layer keeps its last projection while the camera doesn't exist.
+    Camera(CameraHandle),
+}
+
+/// Which color channels are written when a layer is drawn.
+///
+/// Writing only alpha is useful for building masks, and writing only color
+/// for tinting what's already there without changing its coverage.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorWriteMask {
+    pub red: bool,
+    pub green: bool,
+    pub blue: bool,
+    pub alpha: bool,
 }
 
 /// A collection of ordered layers for rendering.
@@ -31,7 +104,18 @@ pub struct LayerStack {
 ///
 /// These are comparable to 'meshes' in other rendering frameworks.
 #[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Batch {
     pub texture_handle: TextureHandle,
     pub vertices: Vec<Vertex2d>,
+
+    /// The layer which is drawn when the texture is a 2d array texture, like
+    /// one frame of a sprite sheet. Ignored for other textures.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub layer: u32,
+
+    /// When set, the texture is an indexed texture and each index is drawn
+    /// with this palette's color. See the `palette` module.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub palette: Option<TextureHandle>,
 }
@@ -0,0 +1,159 @@
+use super::Layer;
+
+use crate::geometry::Rect;
+
+use nalgebra as na;
+
+/// The most copies drawn along each axis. This keeps a zoomed-out camera
+/// from drawing an unbounded number of copies of a tiny world.
+const MAX_COPIES_PER_AXIS: i32 = 3;
+
+impl Layer {
+    /// Make the layer wrap around at the edges of a toroidal world.
+    ///
+    /// Whenever the view extends past the bounds, the layer is drawn again
+    /// shifted by the bounds' width and/or height, so content near one edge
+    /// appears seamlessly beside the opposite edge. Pass None to disable
+    /// wrapping.
+    pub fn set_wrap_bounds(&mut self, wrap_bounds: Option<Rect<f32>>) {
+        self.wrap_bounds = wrap_bounds;
+    }
+
+    /// The bounds the layer wraps around, if wrapping is enabled.
+    pub fn wrap_bounds(&self) -> Option<&Rect<f32>> {
+        self.wrap_bounds.as_ref()
+    }
+
+    /// Every projection the layer should be drawn with.
+    ///
+    /// This is just the layer's projection unless wrapping is enabled, in
+    /// which case there is one projection for each shifted copy of the world
+    /// which overlaps the view.
+    pub fn projections(&self) -> Vec<na::Matrix4<f32>> {
+        let bounds = match &self.wrap_bounds {
+            Some(bounds) if bounds.width() > 0.0 && bounds.height() > 0.0 => {
+                bounds
+            }
+            _ => return vec![self.projection],
+        };
+        let view = match self.view_bounds() {
+            Some(view) => view,
+            None => return vec![self.projection],
+        };
+
+        let columns = copies(
+            (view.left, view.right),
+            (bounds.left.min(bounds.right), bounds.width()),
+        );
+        let rows = copies(
+            (view.bottom, view.top),
+            (bounds.bottom.min(bounds.top), bounds.height()),
+        );
+
+        let mut projections = vec![];
+        for row in rows.0..=rows.1 {
+            for column in columns.0..=columns.1 {
+                let offset = na::Vector3::new(
+                    column as f32 * bounds.width(),
+                    row as f32 * bounds.height(),
+                    0.0,
+                );
+                projections.push(
+                    self.projection * na::Matrix4::new_translation(&offset),
+                );
+            }
+        }
+        projections
+    }
+
+    /// The region of world space visible through the layer's projection.
+    fn view_bounds(&self) -> Option<Rect<f32>> {
+        let inverse = self.projection.try_inverse()?;
+        let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)];
+        let mut view = Rect {
+            left: f32::INFINITY,
+            right: f32::NEG_INFINITY,
+            bottom: f32::INFINITY,
+            top: f32::NEG_INFINITY,
+        };
+        for (x, y) in corners.iter() {
+            let world = inverse.transform_point(&na::Point3::new(*x, *y, 0.0));
+            view.left = view.left.min(world.x);
+            view.right = view.right.max(world.x);
+            view.bottom = view.bottom.min(world.y);
+            view.top = view.top.max(world.y);
+        }
+        Some(view)
+    }
+}
+
+/// The inclusive range of copy indices along one axis whose shifted world
+/// range overlaps the view range.
+fn copies(
+    (view_min, view_max): (f32, f32),
+    (start, size): (f32, f32),
+) -> (i32, i32) {
+    let first = ((view_min - (start + size)) / size).ceil() as i32;
+    let last = ((view_max - start) / size).floor() as i32;
+    (
+        first.clamp(-MAX_COPIES_PER_AXIS, 0),
+        last.clamp(0, MAX_COPIES_PER_AXIS),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layer_viewing(view: Rect<f32>) -> Layer {
+        let mut layer = Layer::empty();
+        layer.set_projection(
+            na::Orthographic3::new(
+                view.left,
+                view.right,
+                view.bottom,
+                view.top,
+                -1.0,
+                1.0,
+            )
+            .to_homogeneous(),
+        );
+        layer.set_wrap_bounds(Some(Rect {
+            left: 0.0,
+            right: 100.0,
+            bottom: 0.0,
+            top: 50.0,
+        }));
+        layer
+    }
+
+    #[test]
+    fn projections_should_not_repeat_when_the_view_is_inside_the_world() {
+        let layer = layer_viewing(Rect {
+            left: 10.0,
+            right: 90.0,
+            bottom: 10.0,
+            top: 40.0,
+        });
+        assert_eq!(layer.projections(), vec![*layer.projection()]);
+    }
+
+    #[test]
+    fn projections_should_repeat_past_each_crossed_edge() {
+        let layer = layer_viewing(Rect {
+            left: 80.0,
+            right: 120.0,
+            bottom: -10.0,
+            top: 20.0,
+        });
+        // one copy to the right, one below, and one diagonal
+        assert_eq!(layer.projections().len(), 4);
+    }
+
+    #[test]
+    fn copies_should_cover_the_view() {
+        assert_eq!(copies((-10.0, 20.0), (0.0, 100.0)), (-1, 0));
+        assert_eq!(copies((90.0, 110.0), (0.0, 100.0)), (0, 1));
+        assert_eq!(copies((-500.0, 500.0), (0.0, 100.0)), (-3, 3));
+    }
+}
@@ -1,36 +1,232 @@
+pub mod assets;
+pub mod cameras;
+pub mod canvas;
+pub mod color_grading;
+pub mod command_queue;
+pub mod custom_pipeline;
+pub mod damage;
+pub mod describe;
+pub mod export;
 pub mod ext;
+pub mod feedback;
 pub mod frame;
 pub mod frame_context;
+pub mod frame_graph;
+pub mod hairline;
+pub mod id_pass;
 pub mod layer;
+pub mod palette;
+pub mod particles;
+pub mod picking;
+pub mod pipeline_cache;
+pub mod recorder;
+pub mod render_node;
+pub mod render_scale;
+pub mod report;
+pub mod scene;
+pub mod shader_canvas;
+pub mod snapshot;
+pub mod storage;
+pub mod temporal_aa;
 pub mod texture_atlas;
+pub mod texture_generator;
 pub mod vertex;
+pub mod virtual_resolution;
 pub mod vulkan;
 
 mod graphics;
+mod graphics_assets;
+mod graphics_cameras;
+mod graphics_canvas;
+mod graphics_color_grading;
+mod graphics_command_queue;
 mod graphics_commands;
+mod graphics_custom_pipeline;
+mod graphics_describe;
+mod graphics_export;
+mod graphics_feedback;
+mod graphics_frame_graph;
+mod graphics_frame_uniforms;
+mod graphics_layer_space;
+mod graphics_metrics;
+mod graphics_palette;
+mod graphics_particles;
+mod graphics_picking;
+mod graphics_post_targets;
+mod graphics_recorder;
+mod graphics_render_node;
+mod graphics_render_scale;
+mod graphics_render_targets;
+mod graphics_report;
+mod graphics_scene;
+mod graphics_shader_canvas;
+mod graphics_snapshot;
+mod graphics_storage;
+mod graphics_suspend;
+mod graphics_temporal_aa;
+mod graphics_texture_generator;
+mod graphics_virtual_resolution;
+mod graphics_wireframe;
 mod pipeline2d;
 
 use self::{
-    frame_context::FrameContext, layer::LayerStack, pipeline2d::Pipeline2d,
-    texture_atlas::GpuAtlas, vulkan::Device,
+    assets::{AssetLoader, AssetRegistry},
+    cameras::Cameras,
+    canvas::Canvas,
+    color_grading::ColorGradingPass,
+    command_queue::CommandQueue,
+    custom_pipeline::CustomPipeline,
+    describe::ResourceUsage,
+    export::ExportTarget,
+    feedback::Feedback,
+    frame::FrameClock,
+    frame_context::FrameContext,
+    frame_graph::ResourceTracker,
+    hairline::HairlinePipeline,
+    id_pass::IdPass,
+    layer::{LayerHandle, LayerStack},
+    particles::ParticleSystem,
+    pipeline2d::Pipeline2d,
+    recorder::Recorder,
+    render_node::RenderNodes,
+    render_scale::RenderScalePass,
+    report::{RenderReport, ReportLog},
+    shader_canvas::ShaderCanvas,
+    snapshot::SnapshotHistory,
+    storage::StorageBuffers,
+    temporal_aa::TemporalAaPass,
+    texture_atlas::{CachedAtlas, GpuAtlas},
+    virtual_resolution::VirtualResolution,
+    vulkan::Device,
 };
 
-use std::sync::Arc;
+use crate::{
+    camera::OrthoCamera, metrics::SharedMetrics, profiling::GpuProfiler,
+};
+
+use std::{sync::Arc, time::Instant};
 
 /// The application's graphics subsystem.
 pub struct Graphics {
     /// The graphics pipeline for rendering 2d geometry.
     pipeline2d: Pipeline2d,
 
-    /// The graphics subsystem's texture atlas.
-    pub texture_atlas: GpuAtlas,
+    /// The graphics pipeline for rendering screen-space hairlines.
+    hairline_pipeline: HairlinePipeline,
+
+    /// Pipelines registered by the application, indexed by their handles.
+    custom_pipelines: Vec<CustomPipeline>,
+
+    /// The graphics subsystem's texture atlas. Textures read from the same
+    /// file are only added once.
+    pub texture_atlas: CachedAtlas<GpuAtlas>,
 
     /// The graphics subsystem's visual layers.
     layer_stack: LayerStack,
 
+    /// Shapes drawn in immediate mode since the last frame.
+    canvas: Canvas,
+
+    /// The transient layer which draws the canvas, once it has been used.
+    canvas_layer: Option<LayerHandle>,
+
     /// This object owns the swapchain and all per-frame resources.
     frame_context: FrameContext,
 
+    /// Captures each presented frame while recording.
+    recorder: Option<Recorder>,
+
+    /// Records the gpu state used by recent frames for debugging.
+    snapshots: Option<SnapshotHistory>,
+
+    /// Statistics for the frame currently being rendered, or the most
+    /// recent frame between calls to `render`.
+    report: RenderReport,
+
+    /// Periodically logs render reports when enabled.
+    report_log: Option<ReportLog>,
+
+    /// Sends the timestamps measured by each frame to the profiler.
+    gpu_profiler: GpuProfiler,
+
+    /// Receives frame times and render reports when installed.
+    metrics: Option<SharedMetrics>,
+
+    /// When the previous frame finished, used to measure frame times.
+    last_frame_end: Option<Instant>,
+
+    /// The time and delta time given to shaders in the frame uniforms.
+    frame_clock: FrameClock,
+
+    /// Renders batch ids offscreen for pixel-accurate picking.
+    id_pass: Option<IdPass>,
+
+    /// A fullscreen fragment shader drawn behind every layer, when set.
+    shader_canvas: Option<ShaderCanvas>,
+
+    /// Application work recorded before and after the layer pass.
+    render_nodes: RenderNodes,
+
+    /// How resources have been used in the frame being recorded.
+    resource_tracker: ResourceTracker,
+
+    /// Ping-ponged copies of the previous frame, when enabled.
+    feedback: Option<Feedback>,
+
+    /// Particles simulated by a compute shader and drawn above every layer,
+    /// when set.
+    particles: Option<ParticleSystem>,
+
+    /// Tone mapping and lookup table grading, when enabled.
+    color_grading: Option<ColorGradingPass>,
+
+    /// Jitters and accumulates frames for anti-aliasing, when enabled.
+    temporal_aa: Option<TemporalAaPass>,
+
+    /// The offscreen target layers are drawn into when rendering at a
+    /// multiple of the swapchain's resolution.
+    render_scale: Option<RenderScalePass>,
+
+    /// The offscreen target layers are drawn into while an export is
+    /// running, see `export_image`.
+    export_target: Option<ExportTarget>,
+
+    /// Commands sent from other threads which will be applied before the
+    /// next frame is drawn.
+    command_queue: CommandQueue,
+
+    /// Fonts and shaders loaded by key.
+    assets: AssetRegistry,
+
+    /// Decodes assets on worker threads while a background load is running.
+    asset_loader: Option<AssetLoader>,
+
+    /// The cpu-side contents of every storage buffer.
+    storage_buffers: StorageBuffers,
+
+    /// The most recent frame which drew with each resource.
+    resource_usage: ResourceUsage,
+
+    /// Draw every layer's batches as wireframes.
+    wireframe: bool,
+
+    /// The projection given to world space layers.
+    world_projection: nalgebra::Matrix4<f32>,
+
+    /// The camera followed by world space layers, when the graphics
+    /// subsystem owns it. Its aspect ratio tracks the framebuffer.
+    camera: Option<OrthoCamera>,
+
+    /// Named cameras followed by `LayerSpace::Camera` layers.
+    cameras: Cameras,
+
+    /// The fixed resolution layers are designed for, when set.
+    virtual_resolution: Option<VirtualResolution>,
+
+    /// The number of frames rendered since the graphics subsystem was
+    /// created.
+    frame_number: u64,
+
     /// the color used to clear the screen
     pub clear_color: [f32; 4],
 
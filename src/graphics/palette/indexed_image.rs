@@ -0,0 +1,91 @@
+use super::{IndexedImage, Palette};
+
+use anyhow::{bail, Context, Result};
+
+impl IndexedImage {
+    /// Create an image from one index per pixel, row by row.
+    pub fn new(width: u32, height: u32, indices: Vec<u8>) -> Result<Self> {
+        if width == 0 || height == 0 {
+            bail!("indexed images can't be empty");
+        }
+        let expected = (width as usize) * (height as usize);
+        if indices.len() != expected {
+            bail!(
+                "a {}x{} indexed image needs {} indices, found {}",
+                width,
+                height,
+                expected,
+                indices.len()
+            );
+        }
+        Ok(Self {
+            width,
+            height,
+            indices,
+        })
+    }
+
+    /// Convert rgba8 pixels to indices into a palette.
+    ///
+    /// Every pixel must exactly match one of the palette's colors, which is
+    /// normally the case for pixel art drawn with a fixed palette.
+    pub fn from_rgba(
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        palette: &Palette,
+    ) -> Result<Self> {
+        let indices = pixels
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(pixel, rgba)| {
+                let color = [rgba[0], rgba[1], rgba[2], rgba[3]];
+                palette.index_of(color).with_context(|| {
+                    format!(
+                        "pixel {} has a color {:?} not in the palette",
+                        pixel, color
+                    )
+                })
+            })
+            .collect::<Result<Vec<u8>>>()?;
+        Self::new(width, height, indices)
+    }
+
+    /// The image's width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The image's height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// One index per pixel, row by row.
+    pub fn indices(&self) -> &[u8] {
+        &self.indices
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn new_should_require_one_index_per_pixel() {
+        assert!(IndexedImage::new(2, 2, vec![0; 4]).is_ok());
+        assert!(IndexedImage::new(2, 2, vec![0; 3]).is_err());
+        assert!(IndexedImage::new(0, 2, vec![]).is_err());
+    }
+
+    #[test]
+    fn from_rgba_should_look_up_each_pixel() {
+        let palette = Palette::from_rgb(&[[0, 0, 0], [255, 255, 255]]).unwrap();
+        let pixels = [255, 255, 255, 255, 0, 0, 0, 255];
+        let image = IndexedImage::from_rgba(2, 1, &pixels, &palette).unwrap();
+        assert_eq!(image.indices(), &[1, 0]);
+
+        let unknown = [255, 0, 0, 255, 0, 0, 0, 255];
+        assert!(IndexedImage::from_rgba(2, 1, &unknown, &palette).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+//! Indexed color textures and the palettes used to draw them.
+//!
+//! # Big Idea
+//!
+//! An indexed texture stores one byte per pixel, an index into a palette of
+//! up to 256 colors. Palettes are uploaded as 256x1 textures in the atlas,
+//! so the same indexed texture can be drawn with any palette just by
+//! changing `Batch::palette`. Swapping palettes is how pixel art games make
+//! enemy variants, damage flashes, and day/night cycles without storing a
+//! texture for each one.
+//!
+//! Indexed textures are always sampled with nearest filtering, blending
+//! between indices would produce unrelated colors.
+
+mod indexed_image;
+mod palette_colors;
+
+/// The most colors a palette can hold, one for every value of a byte.
+pub const MAX_PALETTE_COLORS: usize = 256;
+
+/// An ordered list of srgb rgba colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    colors: Vec<[u8; 4]>,
+}
+
+/// An image where every pixel is an index into a palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedImage {
+    width: u32,
+    height: u32,
+
+    /// One index per pixel, row by row.
+    indices: Vec<u8>,
+}
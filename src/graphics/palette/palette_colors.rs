@@ -0,0 +1,96 @@
+use super::{Palette, MAX_PALETTE_COLORS};
+
+use anyhow::{bail, Result};
+
+impl Palette {
+    /// Create a palette from its colors.
+    ///
+    /// Fails when there are no colors or more than 256.
+    pub fn new(colors: Vec<[u8; 4]>) -> Result<Self> {
+        if colors.is_empty() || colors.len() > MAX_PALETTE_COLORS {
+            bail!(
+                "palettes need between 1 and {} colors, not {}",
+                MAX_PALETTE_COLORS,
+                colors.len()
+            );
+        }
+        Ok(Self { colors })
+    }
+
+    /// Create a palette of opaque colors.
+    pub fn from_rgb(colors: &[[u8; 3]]) -> Result<Self> {
+        Self::new(
+            colors
+                .iter()
+                .map(|[red, green, blue]| [*red, *green, *blue, 255])
+                .collect(),
+        )
+    }
+
+    /// The palette's colors, in index order.
+    pub fn colors(&self) -> &[[u8; 4]] {
+        &self.colors
+    }
+
+    /// The index of the first entry with exactly this color.
+    pub fn index_of(&self, color: [u8; 4]) -> Option<u8> {
+        self.colors
+            .iter()
+            .position(|entry| *entry == color)
+            .map(|index| index as u8)
+    }
+
+    /// A copy of the palette with one entry replaced.
+    ///
+    /// Indices past the end of the palette leave it unchanged.
+    pub fn with_color(&self, index: u8, color: [u8; 4]) -> Self {
+        let mut swapped = self.clone();
+        if let Some(entry) = swapped.colors.get_mut(index as usize) {
+            *entry = color;
+        }
+        swapped
+    }
+
+    /// Tightly packed rgba8 texels for a 256x1 texture.
+    ///
+    /// Entries past the end of the palette are transparent black, so
+    /// indices without a color draw nothing.
+    pub fn texels(&self) -> Vec<u8> {
+        let mut texels = Vec::with_capacity(MAX_PALETTE_COLORS * 4);
+        for color in &self.colors {
+            texels.extend_from_slice(color);
+        }
+        texels.resize(MAX_PALETTE_COLORS * 4, 0);
+        texels
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn new_should_reject_empty_and_oversized_palettes() {
+        assert!(Palette::new(vec![]).is_err());
+        assert!(Palette::new(vec![[0, 0, 0, 255]; 257]).is_err());
+        assert!(Palette::new(vec![[0, 0, 0, 255]; 256]).is_ok());
+    }
+
+    #[test]
+    fn texels_should_pad_to_a_full_row() {
+        let palette = Palette::from_rgb(&[[255, 0, 0], [0, 255, 0]]).unwrap();
+        let texels = palette.texels();
+        assert_eq!(texels.len(), 256 * 4);
+        assert_eq!(&texels[..8], &[255, 0, 0, 255, 0, 255, 0, 255]);
+        assert!(texels[8..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn with_color_should_only_replace_one_entry() {
+        let palette = Palette::from_rgb(&[[1, 1, 1], [2, 2, 2]]).unwrap();
+        let swapped = palette.with_color(1, [9, 9, 9, 255]);
+        assert_eq!(swapped.colors(), &[[1, 1, 1, 255], [9, 9, 9, 255]]);
+        assert_eq!(palette.with_color(7, [9, 9, 9, 255]), palette);
+        assert_eq!(swapped.index_of([9, 9, 9, 255]), Some(1));
+    }
+}
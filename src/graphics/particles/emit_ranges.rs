@@ -0,0 +1,101 @@
+use super::EmitRange;
+
+/// Split `emitted` particles into runs of consecutive pool slots, starting
+/// at `next_slot` and wrapping around to overwrite the oldest particles.
+///
+/// When more particles are emitted than the pool holds, only the newest
+/// `capacity` are kept. Returns the runs and the slot for the next emitted
+/// particle.
+pub(super) fn emit_ranges(
+    next_slot: u32,
+    emitted: usize,
+    capacity: u32,
+) -> (Vec<EmitRange>, u32) {
+    let capacity_len = capacity as usize;
+    let mut start = emitted.saturating_sub(capacity_len);
+    let mut slot = next_slot % capacity;
+    let mut ranges = vec![];
+    while start < emitted {
+        let run = (emitted - start).min(capacity_len - slot as usize);
+        ranges.push(EmitRange {
+            slot,
+            start,
+            end: start + run,
+        });
+        start += run;
+        slot = (slot + run as u32) % capacity;
+    }
+    (ranges, slot)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn particles_should_fill_consecutive_slots() {
+        let (ranges, next_slot) = emit_ranges(2, 3, 8);
+
+        assert_eq!(
+            ranges,
+            vec![EmitRange {
+                slot: 2,
+                start: 0,
+                end: 3
+            }]
+        );
+        assert_eq!(next_slot, 5);
+    }
+
+    #[test]
+    fn particles_should_wrap_around_to_the_oldest_slots() {
+        let (ranges, next_slot) = emit_ranges(6, 4, 8);
+
+        assert_eq!(
+            ranges,
+            vec![
+                EmitRange {
+                    slot: 6,
+                    start: 0,
+                    end: 2
+                },
+                EmitRange {
+                    slot: 0,
+                    start: 2,
+                    end: 4
+                },
+            ]
+        );
+        assert_eq!(next_slot, 2);
+    }
+
+    #[test]
+    fn only_the_newest_particles_should_be_kept_when_the_pool_overflows() {
+        let (ranges, next_slot) = emit_ranges(3, 10, 4);
+
+        assert_eq!(
+            ranges,
+            vec![
+                EmitRange {
+                    slot: 3,
+                    start: 6,
+                    end: 7
+                },
+                EmitRange {
+                    slot: 0,
+                    start: 7,
+                    end: 10
+                },
+            ]
+        );
+        assert_eq!(next_slot, 3);
+    }
+
+    #[test]
+    fn nothing_should_be_written_without_particles() {
+        let (ranges, next_slot) = emit_ranges(5, 0, 8);
+
+        assert!(ranges.is_empty());
+        assert_eq!(next_slot, 5);
+    }
+}
@@ -0,0 +1,132 @@
+//! Particles which are simulated and drawn entirely on the gpu.
+//!
+//! # Big Idea
+//!
+//! Every particle lives in a storage buffer which is never read back by the
+//! cpu. Each frame, before the layer pass, a compute shader ages and moves
+//! every live particle, then appends the ones which are still alive to a
+//! second buffer. The append bumps the instance count of a
+//! `VkDrawIndirectCommand` with an atomic add, so the draw after the layers
+//! is issued with `vkCmdDrawIndirect` and the cpu never learns how many
+//! particles are alive.
+//!
+//! The application only ever emits particles. Emitted particles are copied
+//! into the pool with `vkCmdUpdateBuffer` in the frame's own command buffer,
+//! overwriting the oldest slots once the pool is full.
+//!
+//! The buffers are shared by every frame in flight. Frames are submitted to
+//! the same queue in order, so a barrier at the start of each frame's
+//! simulation waits for the previous frame's compute and draw before the
+//! buffers are touched again.
+
+mod emit_ranges;
+mod particle;
+mod particle_pipeline;
+mod particle_system;
+
+use crate::graphics::vulkan::{buffer::StaticBuffer, Device};
+
+use ash::vk;
+use std::sync::Arc;
+
+/// The number of particles simulated by each compute workgroup, the
+/// `local_size_x` in `particles.comp`.
+pub const PARTICLE_WORKGROUP_SIZE: u32 = 64;
+
+/// One particle in the gpu simulation.
+///
+/// The layout matches the std430 `Particle` struct in the particle shaders.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Particle {
+    /// The particle's center in world space.
+    pub pos: [f32; 2],
+
+    /// World units per second.
+    pub vel: [f32; 2],
+
+    pub rgba: [f32; 4],
+
+    /// Seconds since the particle was emitted.
+    pub age: f32,
+
+    /// Seconds the particle lives for. It fades out as it ages.
+    pub lifetime: f32,
+
+    /// Half the width of the particle's quad, in world units.
+    pub size: f32,
+
+    /// Pads the struct to the shader's 16 byte alignment.
+    padding: f32,
+}
+
+/// The particles simulated by a compute shader and drawn, in world space,
+/// above every layer.
+pub struct ParticleSystem {
+    pipeline: ParticlePipeline,
+
+    /// Every particle slot, live or not.
+    pool: StaticBuffer,
+
+    /// The particles which survived this frame's simulation, read by the
+    /// vertex shader.
+    alive: StaticBuffer,
+
+    /// The `VkDrawIndirectCommand` used to draw the live particles.
+    draw: StaticBuffer,
+
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set: vk::DescriptorSet,
+
+    /// The number of particle slots in the pool.
+    capacity: u32,
+
+    /// The slot the next emitted particle is written to.
+    next_slot: u32,
+
+    /// Particles emitted since the last frame was recorded.
+    pending: Vec<Particle>,
+
+    /// World units per second squared, applied to every particle.
+    gravity: [f32; 2],
+
+    device: Arc<Device>,
+}
+
+/// The compute pipeline which simulates particles and the graphics pipeline
+/// which draws them.
+struct ParticlePipeline {
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    compute_layout: vk::PipelineLayout,
+    compute_pipeline: vk::Pipeline,
+    draw_layout: vk::PipelineLayout,
+    draw_pipeline: vk::Pipeline,
+    device: Arc<Device>,
+}
+
+/// The push constants used by the particle compute shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct SimulatePushConsts {
+    gravity: [f32; 2],
+    dt: f32,
+    count: u32,
+}
+
+/// The push constants used by the particle vertex shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+struct DrawPushConsts {
+    projection: [[f32; 4]; 4],
+}
+
+/// A run of emitted particles which is copied into consecutive pool slots.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct EmitRange {
+    /// The first pool slot written.
+    slot: u32,
+
+    /// The range of emitted particles copied into the pool.
+    start: usize,
+    end: usize,
+}
@@ -0,0 +1,38 @@
+use super::Particle;
+
+impl Particle {
+    /// A white particle which lives for one second.
+    pub fn new(pos: [f32; 2], vel: [f32; 2]) -> Self {
+        Self {
+            pos,
+            vel,
+            rgba: [1.0, 1.0, 1.0, 1.0],
+            age: 0.0,
+            lifetime: 1.0,
+            size: 1.0,
+            padding: 0.0,
+        }
+    }
+
+    /// Set the particle's color before it fades.
+    pub fn with_rgba(self, rgba: [f32; 4]) -> Self {
+        Self { rgba, ..self }
+    }
+
+    /// Set how many seconds the particle lives for.
+    pub fn with_lifetime(self, lifetime: f32) -> Self {
+        Self { lifetime, ..self }
+    }
+
+    /// Set half the width of the particle, in world units.
+    pub fn with_size(self, size: f32) -> Self {
+        Self { size, ..self }
+    }
+}
+
+impl Default for Particle {
+    /// A particle at the origin which doesn't move.
+    fn default() -> Self {
+        Self::new([0.0, 0.0], [0.0, 0.0])
+    }
+}
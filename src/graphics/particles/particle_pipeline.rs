@@ -0,0 +1,367 @@
+use super::{DrawPushConsts, ParticlePipeline, SimulatePushConsts};
+
+use crate::graphics::vulkan::{shader_module::ShaderModule, Device, Swapchain};
+
+use anyhow::{Context, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::{ffi::CString, mem::size_of, sync::Arc};
+
+impl ParticlePipeline {
+    /// Create the simulation and draw pipelines, which share a descriptor
+    /// set with the particle buffers.
+    pub fn new(device: Arc<Device>, swapchain: &Swapchain) -> Result<Self> {
+        // handles are filled in as they're created, so drop destroys
+        // whichever were created when a later step fails
+        let mut pipeline = Self {
+            descriptor_set_layout: vk::DescriptorSetLayout::null(),
+            compute_layout: vk::PipelineLayout::null(),
+            compute_pipeline: vk::Pipeline::null(),
+            draw_layout: vk::PipelineLayout::null(),
+            draw_pipeline: vk::Pipeline::null(),
+            device,
+        };
+        let device = pipeline.device.clone();
+
+        pipeline.descriptor_set_layout = create_descriptor_set_layout(&device)?;
+        pipeline.compute_layout = create_pipeline_layout(
+            &device,
+            "Particle Simulation Pipeline Layout",
+            pipeline.descriptor_set_layout,
+            vk::ShaderStageFlags::COMPUTE,
+            size_of::<SimulatePushConsts>(),
+        )?;
+        pipeline.compute_pipeline =
+            create_compute_pipeline(&device, pipeline.compute_layout)?;
+        pipeline.draw_layout = create_pipeline_layout(
+            &device,
+            "Particle Draw Pipeline Layout",
+            pipeline.descriptor_set_layout,
+            vk::ShaderStageFlags::VERTEX,
+            size_of::<DrawPushConsts>(),
+        )?;
+        pipeline.draw_pipeline =
+            create_draw_pipeline(&device, swapchain, pipeline.draw_layout)?;
+        Ok(pipeline)
+    }
+
+    /// Rebuild the draw pipeline for a new swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the pipeline is still
+    ///   rendering
+    pub unsafe fn rebuild(&mut self, swapchain: &Swapchain) -> Result<()> {
+        let draw_pipeline =
+            create_draw_pipeline(&self.device, swapchain, self.draw_layout)?;
+        self.device.forget_vulkan_object(
+            vk::ObjectType::PIPELINE,
+            &self.draw_pipeline,
+        );
+        self.device
+            .logical_device
+            .destroy_pipeline(self.draw_pipeline, None);
+        self.draw_pipeline = draw_pipeline;
+        Ok(())
+    }
+}
+
+impl Drop for ParticlePipeline {
+    fn drop(&mut self) {
+        unsafe {
+            for pipeline in &[self.draw_pipeline, self.compute_pipeline] {
+                self.device
+                    .forget_vulkan_object(vk::ObjectType::PIPELINE, pipeline);
+                self.device.logical_device.destroy_pipeline(*pipeline, None);
+            }
+            for layout in &[self.draw_layout, self.compute_layout] {
+                self.device.forget_vulkan_object(
+                    vk::ObjectType::PIPELINE_LAYOUT,
+                    layout,
+                );
+                self.device
+                    .logical_device
+                    .destroy_pipeline_layout(*layout, None);
+            }
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+                &self.descriptor_set_layout,
+            );
+            self.device.logical_device.destroy_descriptor_set_layout(
+                self.descriptor_set_layout,
+                None,
+            );
+        }
+    }
+}
+
+/// The pool is bound first, then the live particles, then the indirect draw
+/// command. Only the live particles are read while drawing.
+fn create_descriptor_set_layout(
+    device: &Device,
+) -> Result<vk::DescriptorSetLayout> {
+    let binding = |binding: u32, stage_flags: vk::ShaderStageFlags| {
+        vk::DescriptorSetLayoutBinding {
+            binding,
+            descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+            descriptor_count: 1,
+            stage_flags,
+            p_immutable_samplers: std::ptr::null(),
+        }
+    };
+    let bindings = [
+        binding(0, vk::ShaderStageFlags::COMPUTE),
+        binding(
+            1,
+            vk::ShaderStageFlags::COMPUTE | vk::ShaderStageFlags::VERTEX,
+        ),
+        binding(2, vk::ShaderStageFlags::COMPUTE),
+    ];
+    let descriptor_set_layout = unsafe {
+        device.logical_device.create_descriptor_set_layout(
+            &vk::DescriptorSetLayoutCreateInfo {
+                p_bindings: bindings.as_ptr(),
+                binding_count: bindings.len() as u32,
+                ..Default::default()
+            },
+            None,
+        )?
+    };
+    device.name_vulkan_object(
+        "Particle Descriptor Set Layout",
+        vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+        &descriptor_set_layout,
+    )?;
+    Ok(descriptor_set_layout)
+}
+
+fn create_pipeline_layout(
+    device: &Device,
+    name: &str,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    stage_flags: vk::ShaderStageFlags,
+    push_consts_size: usize,
+) -> Result<vk::PipelineLayout> {
+    let layouts = [descriptor_set_layout];
+    let push_constant_ranges = [vk::PushConstantRange {
+        stage_flags,
+        size: push_consts_size as u32,
+        offset: 0,
+    }];
+    let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+        p_set_layouts: layouts.as_ptr(),
+        set_layout_count: layouts.len() as u32,
+        p_push_constant_ranges: push_constant_ranges.as_ptr(),
+        push_constant_range_count: push_constant_ranges.len() as u32,
+        ..Default::default()
+    };
+    let pipeline_layout = unsafe {
+        device
+            .logical_device
+            .create_pipeline_layout(&pipeline_layout_create_info, None)?
+    };
+    device.name_vulkan_object(
+        name,
+        vk::ObjectType::PIPELINE_LAYOUT,
+        &pipeline_layout,
+    )?;
+    Ok(pipeline_layout)
+}
+
+fn create_compute_pipeline(
+    device: &Arc<Device>,
+    pipeline_layout: vk::PipelineLayout,
+) -> Result<vk::Pipeline> {
+    let compute_module = ShaderModule::new(
+        device,
+        "Particle Compute Shader",
+        std::include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/shaders/particles.comp.sprv"
+        )),
+    )?;
+    let entry = CString::new("main").unwrap();
+    let create_info = vk::ComputePipelineCreateInfo {
+        stage: vk::PipelineShaderStageCreateInfo {
+            stage: vk::ShaderStageFlags::COMPUTE,
+            module: compute_module.shader_module,
+            p_name: entry.as_ptr(),
+            ..Default::default()
+        },
+        layout: pipeline_layout,
+        base_pipeline_index: -1,
+        base_pipeline_handle: vk::Pipeline::null(),
+        ..Default::default()
+    };
+    let pipelines = unsafe {
+        device
+            .logical_device
+            .create_compute_pipelines(
+                vk::PipelineCache::null(),
+                &[create_info],
+                None,
+            )
+            .map_err(|(_, err)| err)
+            .context("unable to create the particle simulation pipeline")?
+    };
+    device.name_vulkan_object(
+        "Particle Simulation Pipeline",
+        vk::ObjectType::PIPELINE,
+        &pipelines[0],
+    )?;
+    Ok(pipelines[0])
+}
+
+/// Create a pipeline which draws a quad for every live particle. The quad's
+/// corners come from the vertex index and the particle from the instance
+/// index, so there are no vertex buffers.
+fn create_draw_pipeline(
+    device: &Arc<Device>,
+    swapchain: &Swapchain,
+    pipeline_layout: vk::PipelineLayout,
+) -> Result<vk::Pipeline> {
+    let vertex_module = ShaderModule::new(
+        device,
+        "Particle Vertex Shader",
+        std::include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/shaders/particles.vert.sprv"
+        )),
+    )?;
+    let fragment_module = ShaderModule::new(
+        device,
+        "Particle Fragment Shader",
+        std::include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/shaders/particles.frag.sprv"
+        )),
+    )?;
+
+    let entry = CString::new("main").unwrap();
+    let stages = [
+        vk::PipelineShaderStageCreateInfo {
+            stage: vk::ShaderStageFlags::VERTEX,
+            module: vertex_module.shader_module,
+            p_name: entry.as_ptr(),
+            ..Default::default()
+        },
+        vk::PipelineShaderStageCreateInfo {
+            stage: vk::ShaderStageFlags::FRAGMENT,
+            module: fragment_module.shader_module,
+            p_name: entry.as_ptr(),
+            ..Default::default()
+        },
+    ];
+
+    let vertex_input_state = vk::PipelineVertexInputStateCreateInfo::default();
+
+    let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo {
+        topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+        primitive_restart_enable: 0,
+        ..Default::default()
+    };
+
+    let viewports = [vk::Viewport {
+        x: 0.0,
+        y: 0.0,
+        width: swapchain.extent.width as f32,
+        height: swapchain.extent.height as f32,
+        min_depth: 0.0,
+        max_depth: 1.0,
+    }];
+
+    let scissors = [vk::Rect2D {
+        offset: vk::Offset2D { x: 0, y: 0 },
+        extent: swapchain.extent,
+    }];
+
+    let viewport_state = vk::PipelineViewportStateCreateInfo {
+        p_viewports: viewports.as_ptr(),
+        viewport_count: 1,
+        p_scissors: scissors.as_ptr(),
+        scissor_count: 1,
+        ..Default::default()
+    };
+
+    let raster_state = vk::PipelineRasterizationStateCreateInfo {
+        polygon_mode: vk::PolygonMode::FILL,
+        line_width: 1.0,
+        cull_mode: vk::CullModeFlags::NONE,
+        front_face: vk::FrontFace::CLOCKWISE,
+        ..Default::default()
+    };
+
+    let multisample_state = vk::PipelineMultisampleStateCreateInfo {
+        rasterization_samples: vk::SampleCountFlags::TYPE_1,
+        min_sample_shading: 1.0,
+        ..Default::default()
+    };
+
+    // particles fade by lowering their alpha
+    let blend_attachments = [vk::PipelineColorBlendAttachmentState {
+        color_write_mask: vk::ColorComponentFlags::R
+            | vk::ColorComponentFlags::G
+            | vk::ColorComponentFlags::B
+            | vk::ColorComponentFlags::A,
+        blend_enable: 1,
+        src_color_blend_factor: vk::BlendFactor::SRC_ALPHA,
+        dst_color_blend_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        color_blend_op: vk::BlendOp::ADD,
+        src_alpha_blend_factor: vk::BlendFactor::ONE,
+        dst_alpha_blend_factor: vk::BlendFactor::ZERO,
+        alpha_blend_op: vk::BlendOp::ADD,
+    }];
+
+    let blend_state = vk::PipelineColorBlendStateCreateInfo {
+        logic_op_enable: 0,
+        logic_op: vk::LogicOp::COPY,
+        p_attachments: blend_attachments.as_ptr(),
+        attachment_count: blend_attachments.len() as u32,
+        ..Default::default()
+    };
+
+    // the layer pass may render above the swapchain's resolution, see
+    // `Graphics::set_render_scale`
+    let dynamic_states =
+        [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic_state = vk::PipelineDynamicStateCreateInfo {
+        p_dynamic_states: dynamic_states.as_ptr(),
+        dynamic_state_count: dynamic_states.len() as u32,
+        ..Default::default()
+    };
+
+    let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+        p_stages: stages.as_ptr(),
+        stage_count: stages.len() as u32,
+        p_vertex_input_state: &vertex_input_state,
+        p_input_assembly_state: &input_assembly_state,
+        p_viewport_state: &viewport_state,
+        p_rasterization_state: &raster_state,
+        p_multisample_state: &multisample_state,
+        p_color_blend_state: &blend_state,
+        p_dynamic_state: &dynamic_state,
+        layout: pipeline_layout,
+        render_pass: swapchain.render_pass,
+        subpass: 0,
+        base_pipeline_index: -1,
+        base_pipeline_handle: vk::Pipeline::null(),
+        ..Default::default()
+    };
+
+    let pipelines = unsafe {
+        device
+            .logical_device
+            .create_graphics_pipelines(
+                vk::PipelineCache::null(),
+                &[pipeline_create_info],
+                None,
+            )
+            .map_err(|(_, err)| err)
+            .context("unable to create the particle draw pipeline")?
+    };
+    device.name_vulkan_object(
+        "Particle Draw Pipeline",
+        vk::ObjectType::PIPELINE,
+        &pipelines[0],
+    )?;
+    Ok(pipelines[0])
+}
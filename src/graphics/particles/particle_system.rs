@@ -0,0 +1,407 @@
+use super::{
+    emit_ranges::emit_ranges, DrawPushConsts, Particle, ParticlePipeline,
+    ParticleSystem, SimulatePushConsts, PARTICLE_WORKGROUP_SIZE,
+};
+
+use crate::graphics::vulkan::{
+    buffer::{Buffer, StaticBuffer},
+    ffi::any_as_u8_slice,
+    Device, Swapchain,
+};
+
+use anyhow::{bail, Result};
+use ash::{version::DeviceV1_0, vk};
+use nalgebra as na;
+use std::{mem::size_of, sync::Arc};
+
+/// `vkCmdUpdateBuffer` copies at most 65536 bytes at a time.
+const MAX_UPDATE_PARTICLES: usize = 65536 / size_of::<Particle>();
+
+/// Each particle is drawn as two triangles.
+const VERTICES_PER_PARTICLE: u32 = 6;
+
+impl ParticleSystem {
+    /// Create a system with room for `capacity` particles. Every slot starts
+    /// out dead.
+    pub fn new(
+        device: Arc<Device>,
+        swapchain: &Swapchain,
+        capacity: u32,
+    ) -> Result<Self> {
+        if capacity == 0 {
+            bail!("a particle system needs room for at least one particle");
+        }
+        let pipeline = ParticlePipeline::new(device.clone(), swapchain)?;
+
+        let particle_bytes = size_of::<Particle>() as u64 * capacity as u64;
+        let create_buffer = |usage: vk::BufferUsageFlags, size: u64| {
+            StaticBuffer::create(
+                device.clone(),
+                vk::BufferUsageFlags::STORAGE_BUFFER | usage,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                size,
+            )
+        };
+        let pool =
+            create_buffer(vk::BufferUsageFlags::TRANSFER_DST, particle_bytes)?;
+        let alive =
+            create_buffer(vk::BufferUsageFlags::empty(), particle_bytes)?;
+        let draw = create_buffer(
+            vk::BufferUsageFlags::INDIRECT_BUFFER
+                | vk::BufferUsageFlags::TRANSFER_DST,
+            size_of::<vk::DrawIndirectCommand>() as u64,
+        )?;
+
+        let pool_sizes = [vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::STORAGE_BUFFER,
+            descriptor_count: 3,
+        }];
+        let descriptor_pool = unsafe {
+            device.logical_device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo {
+                    p_pool_sizes: pool_sizes.as_ptr(),
+                    pool_size_count: pool_sizes.len() as u32,
+                    max_sets: 1,
+                    ..Default::default()
+                },
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            "Particle Descriptor Pool",
+            vk::ObjectType::DESCRIPTOR_POOL,
+            &descriptor_pool,
+        )?;
+
+        let layouts = [pipeline.descriptor_set_layout];
+        let descriptor_set = unsafe {
+            device.logical_device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo {
+                    descriptor_pool,
+                    p_set_layouts: layouts.as_ptr(),
+                    descriptor_set_count: layouts.len() as u32,
+                    ..Default::default()
+                },
+            )?[0]
+        };
+
+        let system = Self {
+            pipeline,
+            pool,
+            alive,
+            draw,
+            descriptor_pool,
+            descriptor_set,
+            capacity,
+            next_slot: 0,
+            pending: vec![],
+            gravity: [0.0, 0.0],
+            device,
+        };
+        system.write_descriptors();
+
+        // SAFE: the buffers were just created, so nothing is using them
+        unsafe {
+            system.device.sync_graphics_commands(|command_buffer| {
+                // a zeroed particle has no lifetime, so it's dead
+                system.device.logical_device.cmd_fill_buffer(
+                    command_buffer,
+                    system.pool.raw(),
+                    0,
+                    vk::WHOLE_SIZE,
+                    0,
+                );
+                system.record_draw_reset(command_buffer);
+                Ok(())
+            })?;
+        }
+        Ok(system)
+    }
+
+    /// Add particles to the simulation, starting with the next frame.
+    ///
+    /// Once every slot is in use, new particles replace the oldest ones.
+    pub fn emit(&mut self, particles: &[Particle]) {
+        self.pending.extend_from_slice(particles);
+    }
+
+    /// The acceleration applied to every particle, in world units per second
+    /// squared.
+    pub fn gravity(&self) -> [f32; 2] {
+        self.gravity
+    }
+
+    /// Change the acceleration applied to every particle.
+    pub fn set_gravity(&mut self, gravity: [f32; 2]) {
+        self.gravity = gravity;
+    }
+
+    /// The number of particles which can be alive at once.
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Rebuild the draw pipeline for a new swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the system is still
+    ///   rendering
+    pub unsafe fn rebuild(&mut self, swapchain: &Swapchain) -> Result<()> {
+        self.pipeline.rebuild(swapchain)
+    }
+
+    /// Copy emitted particles into the pool, then advance every particle by
+    /// `dt` seconds and gather the live ones for drawing.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording, outside of a render pass, on
+    ///   the graphics queue which every frame is submitted to
+    pub unsafe fn record_simulation(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        dt: f32,
+    ) {
+        let logical_device = &self.device.logical_device;
+
+        // the buffers are shared by every frame, so wait for the previous
+        // frame's simulation and draw to finish with them
+        record_memory_barrier(
+            &self.device,
+            command_buffer,
+            (
+                vk::PipelineStageFlags::COMPUTE_SHADER
+                    | vk::PipelineStageFlags::DRAW_INDIRECT
+                    | vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::SHADER_WRITE,
+            ),
+            (
+                vk::PipelineStageFlags::TRANSFER
+                    | vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::AccessFlags::TRANSFER_WRITE
+                    | vk::AccessFlags::SHADER_READ
+                    | vk::AccessFlags::SHADER_WRITE,
+            ),
+        );
+
+        let (ranges, next_slot) =
+            emit_ranges(self.next_slot, self.pending.len(), self.capacity);
+        for range in ranges {
+            let emitted = &self.pending[range.start..range.end];
+            for (chunk_index, chunk) in
+                emitted.chunks(MAX_UPDATE_PARTICLES).enumerate()
+            {
+                let slot =
+                    range.slot as usize + chunk_index * MAX_UPDATE_PARTICLES;
+                // SAFE: Particle is repr(C) and Copy
+                let bytes = std::slice::from_raw_parts(
+                    chunk.as_ptr() as *const u8,
+                    std::mem::size_of_val(chunk),
+                );
+                logical_device.cmd_update_buffer(
+                    command_buffer,
+                    self.pool.raw(),
+                    (slot * size_of::<Particle>()) as u64,
+                    bytes,
+                );
+            }
+        }
+        self.pending.clear();
+        self.next_slot = next_slot;
+        self.record_draw_reset(command_buffer);
+
+        record_memory_barrier(
+            &self.device,
+            command_buffer,
+            (
+                vk::PipelineStageFlags::TRANSFER,
+                vk::AccessFlags::TRANSFER_WRITE,
+            ),
+            (
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE,
+            ),
+        );
+
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::COMPUTE,
+            self.pipeline.compute_pipeline,
+        );
+        logical_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::COMPUTE,
+            self.pipeline.compute_layout,
+            0,
+            &[self.descriptor_set],
+            &[],
+        );
+        let consts = SimulatePushConsts {
+            gravity: self.gravity,
+            dt,
+            count: self.capacity,
+        };
+        logical_device.cmd_push_constants(
+            command_buffer,
+            self.pipeline.compute_layout,
+            vk::ShaderStageFlags::COMPUTE,
+            0,
+            any_as_u8_slice(&consts),
+        );
+        let workgroups = self.capacity.div_ceil(PARTICLE_WORKGROUP_SIZE);
+        logical_device.cmd_dispatch(command_buffer, workgroups, 1, 1);
+
+        record_memory_barrier(
+            &self.device,
+            command_buffer,
+            (
+                vk::PipelineStageFlags::COMPUTE_SHADER,
+                vk::AccessFlags::SHADER_WRITE,
+            ),
+            (
+                vk::PipelineStageFlags::DRAW_INDIRECT
+                    | vk::PipelineStageFlags::VERTEX_SHADER,
+                vk::AccessFlags::INDIRECT_COMMAND_READ
+                    | vk::AccessFlags::SHADER_READ,
+            ),
+        );
+    }
+
+    /// Draw the particles gathered by the most recent simulation.
+    ///
+    /// The 2d pipeline's descriptor sets are replaced, so this is recorded
+    /// after every layer.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording inside the layer render pass
+    pub unsafe fn record_draw(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        projection: &na::Matrix4<f32>,
+    ) {
+        let logical_device = &self.device.logical_device;
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline.draw_pipeline,
+        );
+        logical_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline.draw_layout,
+            0,
+            &[self.descriptor_set],
+            &[],
+        );
+        let consts = DrawPushConsts {
+            projection: (*projection).into(),
+        };
+        logical_device.cmd_push_constants(
+            command_buffer,
+            self.pipeline.draw_layout,
+            vk::ShaderStageFlags::VERTEX,
+            0,
+            any_as_u8_slice(&consts),
+        );
+        logical_device.cmd_draw_indirect(
+            command_buffer,
+            self.draw.raw(),
+            0,
+            1,
+            size_of::<vk::DrawIndirectCommand>() as u32,
+        );
+    }
+
+    /// Reset the indirect draw to zero instances, ready for the simulation
+    /// to count the live particles.
+    unsafe fn record_draw_reset(&self, command_buffer: vk::CommandBuffer) {
+        let command = vk::DrawIndirectCommand {
+            vertex_count: VERTICES_PER_PARTICLE,
+            instance_count: 0,
+            first_vertex: 0,
+            first_instance: 0,
+        };
+        self.device.logical_device.cmd_update_buffer(
+            command_buffer,
+            self.draw.raw(),
+            0,
+            any_as_u8_slice(&command),
+        );
+    }
+
+    /// Point the descriptor set at the pool, live particles, and indirect
+    /// draw.
+    fn write_descriptors(&self) {
+        // SAFE: the buffers are owned by the system and live as long as the
+        // descriptor set
+        let buffers =
+            unsafe { [self.pool.raw(), self.alive.raw(), self.draw.raw()] };
+        let buffer_infos = buffers
+            .iter()
+            .map(|&buffer| {
+                [vk::DescriptorBufferInfo {
+                    buffer,
+                    offset: 0,
+                    range: vk::WHOLE_SIZE,
+                }]
+            })
+            .collect::<Vec<_>>();
+        let writes = buffer_infos
+            .iter()
+            .enumerate()
+            .map(|(binding, info)| vk::WriteDescriptorSet {
+                dst_set: self.descriptor_set,
+                dst_binding: binding as u32,
+                dst_array_element: 0,
+                descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+                p_buffer_info: info.as_ptr(),
+                descriptor_count: info.len() as u32,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        unsafe {
+            self.device
+                .logical_device
+                .update_descriptor_sets(&writes, &[]);
+        }
+    }
+}
+
+impl Drop for ParticleSystem {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_POOL,
+                &self.descriptor_pool,
+            );
+            self.device
+                .logical_device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+        }
+    }
+}
+
+/// Record a global memory barrier between two (stages, access) pairs.
+unsafe fn record_memory_barrier(
+    device: &Device,
+    command_buffer: vk::CommandBuffer,
+    (src_stage, src_access_mask): (vk::PipelineStageFlags, vk::AccessFlags),
+    (dst_stage, dst_access_mask): (vk::PipelineStageFlags, vk::AccessFlags),
+) {
+    let barrier = vk::MemoryBarrier {
+        src_access_mask,
+        dst_access_mask,
+        ..Default::default()
+    };
+    device.logical_device.cmd_pipeline_barrier(
+        command_buffer,
+        src_stage,
+        dst_stage,
+        vk::DependencyFlags::empty(),
+        &[barrier],
+        &[],
+        &[],
+    );
+}
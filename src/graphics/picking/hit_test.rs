@@ -0,0 +1,209 @@
+use super::{PickMode, PickResult, VERTICES_PER_SPRITE};
+
+use crate::{
+    geometry::Rect,
+    graphics::{
+        layer::{Layer, LayerHandle},
+        vertex::Vertex2d,
+    },
+};
+
+use nalgebra as na;
+
+/// Find every sprite in a layer which contains the point.
+///
+/// Results are ordered front to back, so the sprite drawn last comes first.
+pub(crate) fn pick_layer(
+    handle: LayerHandle,
+    layer: &Layer,
+    point: &na::Point2<f32>,
+    mode: PickMode,
+) -> Vec<PickResult> {
+    let mut results = vec![];
+    for (batch_index, batch) in layer.batches().iter().enumerate().rev() {
+        match bounds(&batch.vertices) {
+            Some(batch_bounds) if batch_bounds.contains(&point.coords) => (),
+            _ => continue,
+        }
+        let sprites = batch.vertices.chunks(VERTICES_PER_SPRITE);
+        for (sprite_index, sprite) in sprites.enumerate().rev() {
+            if sprite_contains(sprite, point, mode) {
+                results.push(PickResult {
+                    layer: handle,
+                    batch: batch_index,
+                    sprite: sprite_index,
+                });
+            }
+        }
+    }
+    results
+}
+
+fn sprite_contains(
+    vertices: &[Vertex2d],
+    point: &na::Point2<f32>,
+    mode: PickMode,
+) -> bool {
+    match mode {
+        PickMode::Bounds => bounds(vertices)
+            .map(|sprite_bounds| sprite_bounds.contains(&point.coords))
+            .unwrap_or(false),
+        PickMode::Triangles => vertices
+            .chunks_exact(3)
+            .any(|triangle| triangle_contains(triangle, point)),
+    }
+}
+
+/// The axis-aligned bounding box for a set of vertices.
+fn bounds(vertices: &[Vertex2d]) -> Option<Rect<f32>> {
+    let (first, rest) = vertices.split_first()?;
+    let initial = Rect {
+        left: first.pos[0],
+        right: first.pos[0],
+        bottom: first.pos[1],
+        top: first.pos[1],
+    };
+    Some(rest.iter().fold(initial, |rect, vertex| Rect {
+        left: rect.left.min(vertex.pos[0]),
+        right: rect.right.max(vertex.pos[0]),
+        bottom: rect.bottom.min(vertex.pos[1]),
+        top: rect.top.max(vertex.pos[1]),
+    }))
+}
+
+/// True when the point is inside or on the edge of the triangle, regardless
+/// of winding order.
+fn triangle_contains(triangle: &[Vertex2d], point: &na::Point2<f32>) -> bool {
+    let corner = |i: usize| na::Point2::from(triangle[i].pos);
+    let (a, b, c) = (corner(0), corner(1), corner(2));
+    let edge = |from: na::Point2<f32>, to: na::Point2<f32>| {
+        (to - from).perp(&(point - from))
+    };
+    let (ab, bc, ca) = (edge(a, b), edge(b, c), edge(c, a));
+    let has_negative = ab < 0.0 || bc < 0.0 || ca < 0.0;
+    let has_positive = ab > 0.0 || bc > 0.0 || ca > 0.0;
+    !(has_negative && has_positive)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::layer::Batch;
+
+    fn vertex(x: f32, y: f32) -> Vertex2d {
+        Vertex2d {
+            pos: [x, y],
+            ..Default::default()
+        }
+    }
+
+    /// A right triangle covering the lower-left half of a unit square at x.
+    fn half_square(x: f32) -> Vec<Vertex2d> {
+        vec![vertex(x, 0.0), vertex(x + 1.0, 0.0), vertex(x, 1.0)]
+    }
+
+    fn quad(x: f32) -> Vec<Vertex2d> {
+        let mut vertices = half_square(x);
+        vertices.extend(&[
+            vertex(x + 1.0, 0.0),
+            vertex(x + 1.0, 1.0),
+            vertex(x, 1.0),
+        ]);
+        vertices
+    }
+
+    fn layer_with(batches: Vec<Vec<Vertex2d>>) -> Layer {
+        let mut layer = Layer::empty();
+        for vertices in batches {
+            layer.push_batch(Batch {
+                vertices,
+                ..Default::default()
+            });
+        }
+        layer
+    }
+
+    #[test]
+    fn pick_layer_should_find_sprites_front_to_back() {
+        let handle = LayerHandle::generate();
+        let mut first = quad(0.0);
+        first.extend(quad(5.0));
+        let layer = layer_with(vec![first, quad(0.0)]);
+
+        let results = pick_layer(
+            handle,
+            &layer,
+            &na::Point2::new(0.5, 0.5),
+            PickMode::Bounds,
+        );
+
+        assert_eq!(
+            results,
+            vec![
+                PickResult {
+                    layer: handle,
+                    batch: 1,
+                    sprite: 0
+                },
+                PickResult {
+                    layer: handle,
+                    batch: 0,
+                    sprite: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pick_layer_should_report_the_sprite_within_a_batch() {
+        let handle = LayerHandle::generate();
+        let mut vertices = quad(0.0);
+        vertices.extend(quad(5.0));
+        let layer = layer_with(vec![vertices]);
+
+        let results = pick_layer(
+            handle,
+            &layer,
+            &na::Point2::new(5.5, 0.5),
+            PickMode::Triangles,
+        );
+
+        assert_eq!(
+            results,
+            vec![PickResult {
+                layer: handle,
+                batch: 0,
+                sprite: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn triangles_mode_should_miss_empty_parts_of_the_bounds() {
+        let handle = LayerHandle::generate();
+        let layer = layer_with(vec![half_square(0.0)]);
+        let upper_right = na::Point2::new(0.9, 0.9);
+
+        assert_eq!(
+            pick_layer(handle, &layer, &upper_right, PickMode::Bounds).len(),
+            1
+        );
+        assert!(
+            pick_layer(handle, &layer, &upper_right, PickMode::Triangles)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn triangle_contains_should_ignore_winding_order() {
+        let clockwise = [vertex(0.0, 0.0), vertex(0.0, 1.0), vertex(1.0, 0.0)];
+        let counter_clockwise =
+            [vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)];
+        let point = na::Point2::new(0.25, 0.25);
+
+        assert!(triangle_contains(&clockwise, &point));
+        assert!(triangle_contains(&counter_clockwise, &point));
+        assert!(!triangle_contains(&clockwise, &na::Point2::new(1.0, 1.0)));
+    }
+}
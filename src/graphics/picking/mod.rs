@@ -0,0 +1,51 @@
+//! Find which batches and sprites are under a point on the screen.
+//!
+//! Picking usually happens on the CPU using the vertices already stored in
+//! each layer, so it works for any layer whose projection matches the camera
+//! used to unproject the point. The optional object id pass picks on the GPU
+//! instead, see `Graphics::enable_object_id_picking`.
+
+mod hit_test;
+mod object_id;
+
+pub(crate) use self::{hit_test::pick_layer, object_id::batch_for_object_id};
+
+use crate::graphics::layer::LayerHandle;
+
+/// The vertices in a single sprite. Batches are treated as a list of quads,
+/// two triangles each, when reporting which sprite was hit.
+pub const VERTICES_PER_SPRITE: usize = 6;
+
+/// How precisely picking tests geometry.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PickMode {
+    /// Hit any sprite whose axis-aligned bounding box contains the point.
+    Bounds,
+
+    /// Only hit sprites with a triangle which contains the point.
+    Triangles,
+}
+
+/// Identifies a single sprite under the picked point.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PickResult {
+    /// The layer containing the sprite.
+    pub layer: LayerHandle,
+
+    /// The index of the batch within the layer.
+    pub batch: usize,
+
+    /// The index of the sprite within the batch. The sprite's vertices start
+    /// at `sprite * VERTICES_PER_SPRITE`.
+    pub sprite: usize,
+}
+
+/// Identifies the batch which was drawn at a pixel by the object id pass.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PickedBatch {
+    /// The layer containing the batch.
+    pub layer: LayerHandle,
+
+    /// The index of the batch within the layer.
+    pub batch: usize,
+}
@@ -0,0 +1,65 @@
+use super::PickedBatch;
+
+use crate::graphics::layer::{Layer, LayerHandle};
+
+/// Find the batch which was drawn with an object id.
+///
+/// Ids count batches from 1 in render order, with 0 meaning nothing was
+/// drawn.
+pub(crate) fn batch_for_object_id(
+    layers: &[(LayerHandle, &Layer)],
+    object_id: u32,
+) -> Option<PickedBatch> {
+    let mut remaining = (object_id as usize).checked_sub(1)?;
+    for (handle, layer) in layers {
+        let batch_count = layer.batches().len();
+        if remaining < batch_count {
+            return Some(PickedBatch {
+                layer: *handle,
+                batch: remaining,
+            });
+        }
+        remaining -= batch_count;
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::layer::Batch;
+
+    fn layer_with_batches(count: usize) -> Layer {
+        let mut layer = Layer::empty();
+        for _ in 0..count {
+            layer.push_batch(Batch::empty());
+        }
+        layer
+    }
+
+    #[test]
+    fn batch_for_object_id_should_count_batches_across_layers() {
+        let (first, second) = (layer_with_batches(2), layer_with_batches(3));
+        let (first_handle, second_handle) =
+            (LayerHandle::generate(), LayerHandle::generate());
+        let layers = [(first_handle, &first), (second_handle, &second)];
+
+        assert_eq!(batch_for_object_id(&layers, 0), None);
+        assert_eq!(
+            batch_for_object_id(&layers, 2),
+            Some(PickedBatch {
+                layer: first_handle,
+                batch: 1
+            })
+        );
+        assert_eq!(
+            batch_for_object_id(&layers, 3),
+            Some(PickedBatch {
+                layer: second_handle,
+                batch: 0
+            })
+        );
+        assert_eq!(batch_for_object_id(&layers, 6), None);
+    }
+}
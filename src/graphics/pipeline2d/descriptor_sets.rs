@@ -1,21 +1,120 @@
-use super::PushConsts;
+use super::PUSH_CONSTANTS_SIZE;
 
-use std::mem::size_of;
-
-use crate::graphics::{texture_atlas::MAX_SUPPORTED_TEXTURES, vulkan::Device};
+use crate::graphics::{
+    frame::{FRAME_DESCRIPTOR_SET, FRAME_UNIFORMS_BINDING},
+    storage::{MAX_STORAGE_BUFFERS, STORAGE_BUFFER_BINDING},
+    texture_atlas::{ATLAS_DESCRIPTOR_SET, SAMPLER_BINDING, TEXTURE_BINDING},
+    vulkan::Device,
+};
 
 use anyhow::Result;
 use ash::{version::DeviceV1_0, vk};
+use std::ffi::c_void;
+
+/// Create the layouts for every descriptor set used by Draw2d, in set order:
+/// the texture atlas's global set, then the frame's set.
+///
+/// Every layout is named with the provided name as a prefix.
+///
+/// Unsafe:  the returned layouts are unowned. The caller is responsible for
+///          destroying them with [destroy_descriptor_set_layouts].
+pub unsafe fn create_descriptor_set_layouts(
+    device: &Device,
+    name: &str,
+) -> Result<[vk::DescriptorSetLayout; 2]> {
+    let (atlas_layout, _bindings) = create_atlas_set_layout(device)?;
+    device.name_vulkan_object(
+        format!("{} Atlas Descriptor Set Layout", name),
+        vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+        &atlas_layout,
+    )?;
+    let (frame_layout, _bindings) = create_frame_set_layout(device)?;
+    device.name_vulkan_object(
+        format!("{} Frame Descriptor Set Layout", name),
+        vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+        &frame_layout,
+    )?;
+
+    let mut layouts = [vk::DescriptorSetLayout::null(); 2];
+    layouts[ATLAS_DESCRIPTOR_SET as usize] = atlas_layout;
+    layouts[FRAME_DESCRIPTOR_SET as usize] = frame_layout;
+    Ok(layouts)
+}
 
-/// Create a descriptor set layout instance which describes the bindings used by
-/// Draw2d.
+/// Destroy the layouts created by [create_descriptor_set_layouts].
 ///
-/// Unsafe:  the returned descriptor set is unowned. The caller is responsible
-///          destroying it when it is no longer being used.
-pub unsafe fn create_descriptor_set_layout(
+/// Unsafe:  the layouts must not be used after this call.
+pub unsafe fn destroy_descriptor_set_layouts(
+    device: &Device,
+    layouts: &[vk::DescriptorSetLayout; 2],
+) {
+    for layout in layouts {
+        device.forget_vulkan_object(
+            vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+            layout,
+        );
+        device
+            .logical_device
+            .destroy_descriptor_set_layout(*layout, None);
+    }
+}
+
+/// Create the layout for the texture atlas's global descriptor set, which
+/// holds every texture and sampler in the atlas.
+///
+/// The layout is created for update-after-bind when the device supports it,
+/// so new textures can be written while frames which use the set are in
+/// flight. The arrays are partially bound so elements which are being
+/// written, and aren't used by any draw, don't invalidate the set.
+///
+/// Unsafe:  the returned descriptor set layout is unowned. The caller is
+///          responsible destroying it when it is no longer being used.
+pub unsafe fn create_atlas_set_layout(
     device: &Device,
 ) -> Result<(vk::DescriptorSetLayout, Vec<vk::DescriptorSetLayoutBinding>)> {
-    let bindings = vec![sampler_layout_binding()];
+    let bindings = vec![
+        texture_layout_binding(device.max_supported_textures()),
+        sampler_layout_binding(device.max_supported_samplers()),
+    ];
+    let binding_flags = vec![
+        vk::DescriptorBindingFlags::UPDATE_AFTER_BIND
+            | vk::DescriptorBindingFlags::UPDATE_UNUSED_WHILE_PENDING
+            | vk::DescriptorBindingFlags::PARTIALLY_BOUND;
+        bindings.len()
+    ];
+    let binding_flags_create_info =
+        vk::DescriptorSetLayoutBindingFlagsCreateInfo {
+            p_binding_flags: binding_flags.as_ptr(),
+            binding_count: binding_flags.len() as u32,
+            ..Default::default()
+        };
+    let mut create_info = vk::DescriptorSetLayoutCreateInfo {
+        p_bindings: bindings.as_ptr(),
+        binding_count: bindings.len() as u32,
+        ..Default::default()
+    };
+    if device.supports_update_after_bind() {
+        create_info.flags =
+            vk::DescriptorSetLayoutCreateFlags::UPDATE_AFTER_BIND_POOL;
+        create_info.p_next = &binding_flags_create_info
+            as *const vk::DescriptorSetLayoutBindingFlagsCreateInfo
+            as *const c_void;
+    }
+    let descriptor_set_layout = device
+        .logical_device
+        .create_descriptor_set_layout(&create_info, None)?;
+    Ok((descriptor_set_layout, bindings))
+}
+
+/// Create the layout for a frame's descriptor set, which holds the frame's
+/// uniforms and storage buffers.
+///
+/// Unsafe:  the returned descriptor set layout is unowned. The caller is
+///          responsible destroying it when it is no longer being used.
+pub unsafe fn create_frame_set_layout(
+    device: &Device,
+) -> Result<(vk::DescriptorSetLayout, Vec<vk::DescriptorSetLayoutBinding>)> {
+    let bindings = vec![uniform_layout_binding(), storage_layout_binding()];
     let descriptor_set_layout =
         device.logical_device.create_descriptor_set_layout(
             &vk::DescriptorSetLayoutCreateInfo {
@@ -29,22 +128,94 @@ pub unsafe fn create_descriptor_set_layout(
 }
 
 /// Create the push constant range definition for the graphics pipeline.
+///
+/// The range covers `PushConsts` plus room for custom batches' user data,
+/// so the 2d and custom pipeline layouts stay compatible.
 pub fn create_push_constant_range() -> vk::PushConstantRange {
     vk::PushConstantRange {
         stage_flags: vk::ShaderStageFlags::FRAGMENT
             | vk::ShaderStageFlags::VERTEX,
-        size: size_of::<PushConsts>() as u32,
+        size: PUSH_CONSTANTS_SIZE,
         offset: 0,
     }
 }
 
-/// the combined image sampler layout binding
-fn sampler_layout_binding() -> vk::DescriptorSetLayoutBinding {
+/// The specialization constants which size the atlas arrays in fragment
+/// shaders. `MAX_TEXTURES` is constant 0 and `MAX_SAMPLERS` is constant 1,
+/// the data comes from [atlas_array_sizes].
+pub fn atlas_specialization_entries() -> [vk::SpecializationMapEntry; 2] {
+    let size = std::mem::size_of::<u32>();
+    [
+        vk::SpecializationMapEntry {
+            constant_id: 0,
+            offset: 0,
+            size,
+        },
+        vk::SpecializationMapEntry {
+            constant_id: 1,
+            offset: size as u32,
+            size,
+        },
+    ]
+}
+
+/// The number of textures and samplers in the atlas arrays, in the order
+/// used by [atlas_specialization_entries].
+pub fn atlas_array_sizes(device: &Device) -> [u32; 2] {
+    [
+        device.max_supported_textures(),
+        device.max_supported_samplers(),
+    ]
+}
+
+/// the sampled image layout binding, an array with one entry for every
+/// texture in the atlas
+fn texture_layout_binding(
+    texture_count: u32,
+) -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding {
+        binding: TEXTURE_BINDING,
+        descriptor_count: texture_count,
+        descriptor_type: vk::DescriptorType::SAMPLED_IMAGE,
+        stage_flags: vk::ShaderStageFlags::FRAGMENT,
+        ..Default::default()
+    }
+}
+
+/// the sampler layout binding, an array with one entry for every sampler in
+/// the atlas
+fn sampler_layout_binding(
+    sampler_count: u32,
+) -> vk::DescriptorSetLayoutBinding {
     vk::DescriptorSetLayoutBinding {
-        binding: 0,
-        descriptor_count: MAX_SUPPORTED_TEXTURES as u32,
-        descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+        binding: SAMPLER_BINDING,
+        descriptor_count: sampler_count,
+        descriptor_type: vk::DescriptorType::SAMPLER,
         stage_flags: vk::ShaderStageFlags::FRAGMENT,
         ..Default::default()
     }
 }
+
+/// the frame uniforms layout binding
+fn uniform_layout_binding() -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding {
+        binding: FRAME_UNIFORMS_BINDING,
+        descriptor_count: 1,
+        descriptor_type: vk::DescriptorType::UNIFORM_BUFFER,
+        stage_flags: vk::ShaderStageFlags::FRAGMENT
+            | vk::ShaderStageFlags::VERTEX,
+        ..Default::default()
+    }
+}
+
+/// the storage buffer array layout binding
+fn storage_layout_binding() -> vk::DescriptorSetLayoutBinding {
+    vk::DescriptorSetLayoutBinding {
+        binding: STORAGE_BUFFER_BINDING,
+        descriptor_count: MAX_STORAGE_BUFFERS as u32,
+        descriptor_type: vk::DescriptorType::STORAGE_BUFFER,
+        stage_flags: vk::ShaderStageFlags::FRAGMENT
+            | vk::ShaderStageFlags::VERTEX,
+        ..Default::default()
+    }
+}
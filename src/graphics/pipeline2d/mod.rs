@@ -1,8 +1,14 @@
 pub mod descriptor_sets;
 
 mod pipeline2d;
+mod pre_rotation;
 
-use crate::graphics::Device;
+pub use self::pre_rotation::pre_rotation;
+
+use crate::graphics::{
+    pipeline_cache::PipelineCacheMap,
+    vulkan::{shader_module::ShaderModule, Device},
+};
 
 use ash::vk;
 use std::sync::Arc;
@@ -10,11 +16,39 @@ use std::sync::Arc;
 /// The 2d graphics vulkan pipeline.
 pub struct Pipeline2d {
     pipeline_layout: vk::PipelineLayout,
+    descriptor_set_layouts: [vk::DescriptorSetLayout; 2],
+
+    /// The pipeline for the default render state, owned by `pipelines`.
     pipeline: vk::Pipeline,
-    descriptor_set_layout: vk::DescriptorSetLayout,
+
+    /// Every pipeline built for a render state, created the first time a
+    /// layer asks for it.
+    pipelines: PipelineCacheMap,
+
+    vertex_module: ShaderModule,
+    fragment_module: ShaderModule,
+
+    /// Draws indexed textures by looking up each index in a palette.
+    palette_fragment_module: ShaderModule,
+
+    /// Draws a layer of a 2d array texture.
+    array_fragment_module: ShaderModule,
+    render_pass: vk::RenderPass,
+    extent: vk::Extent2D,
     device: Arc<Device>,
 }
 
+/// The size of the push constant range shared by the 2d and custom
+/// pipelines, the smallest limit every device supports.
+pub const PUSH_CONSTANTS_SIZE: u32 = 128;
+
+/// Where per-batch user data starts in the push constants. Aligned to 16
+/// bytes so a `vec4` can come first.
+pub const USER_DATA_OFFSET: u32 = 80;
+
+/// The most bytes of user data a custom batch can push.
+pub const MAX_USER_DATA_SIZE: u32 = PUSH_CONSTANTS_SIZE - USER_DATA_OFFSET;
+
 /// The push constants used by the pipeline.
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
@@ -23,4 +57,13 @@ pub struct PushConsts {
     /// An index into the global texture array indicating which texture to
     /// sample for rendering.
     pub texture_index: u32,
+    /// The palette texture's index, only read by the palette shaders.
+    pub palette_index: u32,
+    /// An index into the global sampler array indicating which sampler to
+    /// combine with the texture. The palette shaders use it for both
+    /// textures.
+    pub sampler_index: u32,
+    /// The layer of a 2d array texture to draw, only read by the array
+    /// texture shader.
+    pub layer: u32,
 }
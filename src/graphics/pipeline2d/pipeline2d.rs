@@ -1,12 +1,12 @@
 use super::{descriptor_sets, Pipeline2d};
 
 use crate::graphics::{
-    texture_atlas::MAX_SUPPORTED_TEXTURES,
+    pipeline_cache::{BlendMode, PipelineCacheMap, RenderState, VertexFormat},
     vertex::Vertex2d,
     vulkan::{ffi, shader_module::ShaderModule, Device, Swapchain},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use ash::{version::DeviceV1_0, vk};
 use std::{
     ffi::{c_void, CString},
@@ -18,14 +18,134 @@ impl Pipeline2d {
         let vertex_module = ShaderModule::new(
             &device,
             "Vertex Shader",
-            std::include_bytes!("../../../shaders/sprv/texture2d.vert.sprv"),
+            std::include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/shaders/texture2d.vert.sprv"
+            )),
         )?;
         let fragment_module = ShaderModule::new(
             &device,
             "Fragment Shader",
-            std::include_bytes!("../../../shaders/sprv/texture2d.frag.sprv"),
+            std::include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/shaders/texture2d.frag.sprv"
+            )),
+        )?;
+        let palette_fragment_module = ShaderModule::new(
+            &device,
+            "Palette Fragment Shader",
+            std::include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/shaders/palette.frag.sprv"
+            )),
+        )?;
+        let array_fragment_module = ShaderModule::new(
+            &device,
+            "Array Texture Fragment Shader",
+            std::include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/shaders/texture2d_array.frag.sprv"
+            )),
+        )?;
+
+        let descriptor_set_layouts = unsafe {
+            descriptor_sets::create_descriptor_set_layouts(
+                &device,
+                "Graphics Pipeline",
+            )?
+        };
+        let push_constant_ranges =
+            [descriptor_sets::create_push_constant_range()];
+        let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+            p_set_layouts: descriptor_set_layouts.as_ptr(),
+            set_layout_count: descriptor_set_layouts.len() as u32,
+            p_push_constant_ranges: push_constant_ranges.as_ptr(),
+            push_constant_range_count: push_constant_ranges.len() as u32,
+            ..Default::default()
+        };
+
+        let pipeline_layout = unsafe {
+            device
+                .logical_device
+                .create_pipeline_layout(&pipeline_layout_create_info, None)?
+        };
+        device.name_vulkan_object(
+            "Graphics Pipeline Layout",
+            vk::ObjectType::PIPELINE_LAYOUT,
+            &pipeline_layout,
         )?;
 
+        let mut pipeline2d = Self {
+            descriptor_set_layouts,
+            pipeline_layout,
+            pipeline: vk::Pipeline::null(),
+            pipelines: PipelineCacheMap::new(device.clone(), "Graphics")?,
+            vertex_module,
+            fragment_module,
+            palette_fragment_module,
+            array_fragment_module,
+            render_pass: swapchain.render_pass,
+            extent: swapchain.extent,
+            device,
+        };
+        pipeline2d.pipeline = pipeline2d.pipeline_for(RenderState::default())?;
+        Ok(pipeline2d)
+    }
+
+    /// Get the pipeline for a render state, creating it the first time it's
+    /// needed.
+    ///
+    /// Only states which use the 2d, palette, or array texture shaders and
+    /// `Vertex2d` vertices can be built. Pipelines created here live as long
+    /// as this Pipeline2d.
+    pub fn pipeline_for(&mut self, state: RenderState) -> Result<vk::Pipeline> {
+        let Self {
+            pipelines,
+            pipeline_layout,
+            vertex_module,
+            fragment_module,
+            palette_fragment_module,
+            array_fragment_module,
+            render_pass,
+            extent,
+            device,
+            ..
+        } = self;
+        pipelines.get_or_create(state, |state, pipeline_cache| {
+            let fragment_module = match state.shaders {
+                RenderState::PALETTE_SHADERS => &*palette_fragment_module,
+                RenderState::TEXTURE2D_ARRAY_SHADERS => &*array_fragment_module,
+                _ => &*fragment_module,
+            };
+            Self::create_pipeline(
+                device,
+                state,
+                pipeline_cache,
+                *pipeline_layout,
+                [vertex_module, fragment_module],
+                *render_pass,
+                *extent,
+            )
+        })
+    }
+
+    /// Create a pipeline which uses the 2d vertex shader and layout.
+    fn create_pipeline(
+        device: &Device,
+        state: &RenderState,
+        pipeline_cache: vk::PipelineCache,
+        pipeline_layout: vk::PipelineLayout,
+        [vertex_module, fragment_module]: [&ShaderModule; 2],
+        render_pass: vk::RenderPass,
+        extent: vk::Extent2D,
+    ) -> Result<vk::Pipeline> {
+        let known_shaders = state.shaders == RenderState::TEXTURE2D_SHADERS
+            || state.shaders == RenderState::PALETTE_SHADERS
+            || state.shaders == RenderState::TEXTURE2D_ARRAY_SHADERS;
+        if !known_shaders || state.vertex_format != VertexFormat::Vertex2d {
+            bail!("the 2d pipeline can't be built for {:?}", state);
+        }
+
         // Dynamic parts of the pipeline
 
         let entry = CString::new("main").unwrap();
@@ -36,14 +156,10 @@ impl Pipeline2d {
             ..Default::default()
         };
 
-        let specialization_map_entries = [vk::SpecializationMapEntry {
-            constant_id: 0,
-            offset: 0,
-            size: std::mem::size_of::<u32>(),
-            ..Default::default()
-        }];
-        let specialization_data =
-            unsafe { ffi::any_as_u8_slice(&MAX_SUPPORTED_TEXTURES) };
+        let specialization_map_entries =
+            descriptor_sets::atlas_specialization_entries();
+        let array_sizes = descriptor_sets::atlas_array_sizes(device);
+        let specialization_data = unsafe { ffi::any_as_u8_slice(&array_sizes) };
         let fragment_specialization_info = vk::SpecializationInfo {
             p_map_entries: specialization_map_entries.as_ptr(),
             map_entry_count: specialization_map_entries.len() as u32,
@@ -80,15 +196,15 @@ impl Pipeline2d {
         let viewports = [vk::Viewport {
             x: 0.0,
             y: 0.0,
-            width: swapchain.extent.width as f32,
-            height: swapchain.extent.height as f32,
+            width: extent.width as f32,
+            height: extent.height as f32,
             min_depth: 0.0,
             max_depth: 1.0,
         }];
 
         let scissors = [vk::Rect2D {
             offset: vk::Offset2D { x: 0, y: 0 },
-            extent: swapchain.extent,
+            extent,
         }];
 
         let viewport_state = vk::PipelineViewportStateCreateInfo {
@@ -102,7 +218,7 @@ impl Pipeline2d {
         let raster_state = vk::PipelineRasterizationStateCreateInfo {
             depth_clamp_enable: 0,
             rasterizer_discard_enable: 0,
-            polygon_mode: vk::PolygonMode::FILL,
+            polygon_mode: state.polygon_mode,
             line_width: 1.0,
             cull_mode: vk::CullModeFlags::NONE,
             front_face: vk::FrontFace::CLOCKWISE,
@@ -115,7 +231,7 @@ impl Pipeline2d {
 
         let multisample_state = vk::PipelineMultisampleStateCreateInfo {
             sample_shading_enable: 0,
-            rasterization_samples: vk::SampleCountFlags::TYPE_1,
+            rasterization_samples: state.sample_count,
             p_sample_mask: std::ptr::null(),
             min_sample_shading: 1.0,
             alpha_to_coverage_enable: 0,
@@ -123,14 +239,25 @@ impl Pipeline2d {
             ..Default::default()
         };
 
+        let (src_color_blend_factor, dst_color_blend_factor) = match state
+            .blend_mode
+        {
+            BlendMode::Alpha => (
+                vk::BlendFactor::SRC_ALPHA,
+                vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+            ),
+            BlendMode::Constant => (
+                vk::BlendFactor::CONSTANT_COLOR,
+                vk::BlendFactor::ONE_MINUS_CONSTANT_COLOR,
+            ),
+            BlendMode::Opaque => (vk::BlendFactor::ONE, vk::BlendFactor::ZERO),
+        };
         let blend_attachments = [vk::PipelineColorBlendAttachmentState {
-            color_write_mask: vk::ColorComponentFlags::R
-                | vk::ColorComponentFlags::G
-                | vk::ColorComponentFlags::B
-                | vk::ColorComponentFlags::A,
-            blend_enable: 1,
-            src_color_blend_factor: vk::BlendFactor::SRC_ALPHA,
-            dst_color_blend_factor: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+            color_write_mask: state.color_write_mask.as_vk_flags(),
+            // transparent texels would hide wireframe edges
+            blend_enable: if state.is_filled() { 1 } else { 0 },
+            src_color_blend_factor,
+            dst_color_blend_factor,
             color_blend_op: vk::BlendOp::ADD,
             src_alpha_blend_factor: vk::BlendFactor::ONE,
             dst_alpha_blend_factor: vk::BlendFactor::ZERO,
@@ -146,35 +273,23 @@ impl Pipeline2d {
             ..Default::default()
         };
 
-        let (descriptor_set_layout, _bindings) =
-            unsafe { descriptor_sets::create_descriptor_set_layout(&device)? };
-        device.name_vulkan_object(
-            "Graphics Pipeline Descriptor Set Layout",
-            vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
-            &descriptor_set_layout,
-        )?;
-
-        let layouts = [descriptor_set_layout];
-        let push_constant_ranges =
-            vec![descriptor_sets::create_push_constant_range()];
-        let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
-            p_set_layouts: layouts.as_ptr(),
-            set_layout_count: layouts.len() as u32,
-            p_push_constant_ranges: push_constant_ranges.as_ptr(),
-            push_constant_range_count: push_constant_ranges.len() as u32,
-            ..Default::default()
+        // the viewport follows the render scale and blend constants are set
+        // per layer while recording
+        let dynamic_states = [
+            vk::DynamicState::VIEWPORT,
+            vk::DynamicState::SCISSOR,
+            vk::DynamicState::BLEND_CONSTANTS,
+        ];
+        let dynamic_state_count = if state.uses_blend_constants() {
+            dynamic_states.len()
+        } else {
+            2
         };
-
-        let pipeline_layout = unsafe {
-            device
-                .logical_device
-                .create_pipeline_layout(&pipeline_layout_create_info, None)?
+        let dynamic_state = vk::PipelineDynamicStateCreateInfo {
+            p_dynamic_states: dynamic_states.as_ptr(),
+            dynamic_state_count: dynamic_state_count as u32,
+            ..Default::default()
         };
-        device.name_vulkan_object(
-            "Graphics Pipeline Layout",
-            vk::ObjectType::PIPELINE_LAYOUT,
-            &pipeline_layout,
-        )?;
 
         let stages = [vertex_create_info, fragment_create_info];
         let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
@@ -188,11 +303,11 @@ impl Pipeline2d {
             p_color_blend_state: &blend_state,
 
             p_tessellation_state: std::ptr::null(),
-            p_dynamic_state: std::ptr::null(),
+            p_dynamic_state: &dynamic_state,
             p_depth_stencil_state: std::ptr::null(),
 
             layout: pipeline_layout,
-            render_pass: swapchain.render_pass,
+            render_pass,
             subpass: 0,
             base_pipeline_index: -1,
             base_pipeline_handle: vk::Pipeline::null(),
@@ -204,7 +319,7 @@ impl Pipeline2d {
             device
                 .logical_device
                 .create_graphics_pipelines(
-                    vk::PipelineCache::null(),
+                    pipeline_cache,
                     &[pipeline_create_info],
                     None,
                 )
@@ -212,18 +327,14 @@ impl Pipeline2d {
                 .context("unable to create graphics pipeline")?
         };
         let pipeline = pipelines[0];
-        device.name_vulkan_object(
-            "Application Graphics Pipeline",
-            vk::ObjectType::PIPELINE,
-            &pipeline,
-        )?;
+        let name = if *state == RenderState::default() {
+            "Application Graphics Pipeline".to_owned()
+        } else {
+            format!("Application Graphics Pipeline {:?}", state)
+        };
+        device.name_vulkan_object(name, vk::ObjectType::PIPELINE, &pipeline)?;
 
-        Ok(Self {
-            descriptor_set_layout,
-            pipeline_layout,
-            pipeline,
-            device: device.clone(),
-        })
+        Ok(pipeline)
     }
 
     /// Borrow the raw vulkan pipeline handle.
@@ -240,15 +351,17 @@ impl Pipeline2d {
 impl Drop for Pipeline2d {
     fn drop(&mut self) {
         unsafe {
-            self.device
-                .logical_device
-                .destroy_pipeline(self.pipeline, None);
+            self.pipelines.clear();
+            self.device.forget_vulkan_object(
+                vk::ObjectType::PIPELINE_LAYOUT,
+                &self.pipeline_layout,
+            );
             self.device
                 .logical_device
                 .destroy_pipeline_layout(self.pipeline_layout, None);
-            self.device.logical_device.destroy_descriptor_set_layout(
-                self.descriptor_set_layout,
-                None,
+            descriptor_sets::destroy_descriptor_set_layouts(
+                &self.device,
+                &self.descriptor_set_layouts,
             );
         }
     }
@@ -0,0 +1,64 @@
+use ash::vk;
+use nalgebra as na;
+
+/// The rotation which must be applied after a layer's projection so the
+/// image is upright once the presentation engine applies the surface's
+/// transform.
+///
+/// Clip space is rotated by the same quarter turns as the surface, any other
+/// transform is left alone.
+pub fn pre_rotation(
+    pre_transform: vk::SurfaceTransformFlagsKHR,
+) -> na::Matrix4<f32> {
+    let quarter_turns =
+        if pre_transform == vk::SurfaceTransformFlagsKHR::ROTATE_90 {
+            1.0
+        } else if pre_transform == vk::SurfaceTransformFlagsKHR::ROTATE_180 {
+            2.0
+        } else if pre_transform == vk::SurfaceTransformFlagsKHR::ROTATE_270 {
+            3.0
+        } else {
+            return na::Matrix4::identity();
+        };
+    let angle = quarter_turns * std::f32::consts::FRAC_PI_2;
+    na::Matrix4::new_rotation(na::Vector3::z() * angle)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rotate(
+        pre_transform: vk::SurfaceTransformFlagsKHR,
+        x: f32,
+        y: f32,
+    ) -> (f32, f32) {
+        let point = pre_rotation(pre_transform)
+            .transform_point(&na::Point3::new(x, y, 0.0));
+        (point.x.round(), point.y.round())
+    }
+
+    #[test]
+    fn identity_should_not_rotate() {
+        assert_eq!(
+            pre_rotation(vk::SurfaceTransformFlagsKHR::IDENTITY),
+            na::Matrix4::identity()
+        );
+    }
+
+    #[test]
+    fn rotations_should_turn_clip_space() {
+        assert_eq!(
+            rotate(vk::SurfaceTransformFlagsKHR::ROTATE_90, 1.0, 0.0),
+            (0.0, 1.0)
+        );
+        assert_eq!(
+            rotate(vk::SurfaceTransformFlagsKHR::ROTATE_180, 1.0, 0.0),
+            (-1.0, 0.0)
+        );
+        assert_eq!(
+            rotate(vk::SurfaceTransformFlagsKHR::ROTATE_270, 1.0, 0.0),
+            (0.0, -1.0)
+        );
+    }
+}
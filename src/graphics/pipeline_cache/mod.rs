@@ -0,0 +1,62 @@
+//! Lazily built pipeline objects keyed by the render state they implement.
+//!
+//! Features like color write masks, constant blending, and wireframes each
+//! need their own pipeline. Rather than tracking every combination by hand,
+//! a `PipelineCacheMap` builds the pipeline for a `RenderState` the first
+//! time it's requested and keeps it until the map is dropped. Pipelines are
+//! built through a vulkan pipeline cache so related variants can share
+//! compiled shader code.
+
+mod pipeline_cache_map;
+mod render_state;
+
+use crate::graphics::{layer::ColorWriteMask, vulkan::Device};
+
+use ash::vk;
+use std::{collections::HashMap, sync::Arc};
+
+/// How fragments are combined with the color already in the framebuffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Blend with the fragment's alpha.
+    Alpha,
+
+    /// Blend with the dynamic blend constants, ignoring the fragment's
+    /// alpha.
+    Constant,
+
+    /// Replace the framebuffer's color.
+    Opaque,
+}
+
+/// The vertex layout a pipeline reads.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum VertexFormat {
+    /// `vertex::Vertex2d`
+    Vertex2d,
+
+    /// `hairline::HairlineVertex`
+    Hairline,
+}
+
+/// Everything which can differ between pipelines built from the same
+/// pipeline layout.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RenderState {
+    pub blend_mode: BlendMode,
+    pub color_write_mask: ColorWriteMask,
+    pub sample_count: vk::SampleCountFlags,
+    pub polygon_mode: vk::PolygonMode,
+
+    /// A name which identifies the shader modules used by the pipeline.
+    pub shaders: &'static str,
+
+    pub vertex_format: VertexFormat,
+}
+
+/// Owns every pipeline built for a set of render states.
+pub struct PipelineCacheMap {
+    pipelines: HashMap<RenderState, vk::Pipeline>,
+    pipeline_cache: vk::PipelineCache,
+    device: Arc<Device>,
+}
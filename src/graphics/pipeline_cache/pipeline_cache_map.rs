@@ -0,0 +1,98 @@
+use super::{PipelineCacheMap, RenderState};
+
+use crate::graphics::vulkan::Device;
+
+use anyhow::Result;
+use ash::{version::DeviceV1_0, vk};
+use std::{collections::HashMap, sync::Arc};
+
+impl PipelineCacheMap {
+    /// Create an empty map backed by a new vulkan pipeline cache.
+    pub fn new(device: Arc<Device>, name: &str) -> Result<Self> {
+        let pipeline_cache = unsafe {
+            device.logical_device.create_pipeline_cache(
+                &vk::PipelineCacheCreateInfo::default(),
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            format!("{} Pipeline Cache", name),
+            vk::ObjectType::PIPELINE_CACHE,
+            &pipeline_cache,
+        )?;
+        Ok(Self {
+            pipelines: HashMap::new(),
+            pipeline_cache,
+            device,
+        })
+    }
+
+    /// Get the pipeline for a render state, building it with `create` the
+    /// first time the state is requested.
+    ///
+    /// `create` receives the vulkan pipeline cache to build with. The map
+    /// owns the returned pipeline and destroys it when dropped.
+    pub fn get_or_create<F>(
+        &mut self,
+        state: RenderState,
+        create: F,
+    ) -> Result<vk::Pipeline>
+    where
+        F: FnOnce(&RenderState, vk::PipelineCache) -> Result<vk::Pipeline>,
+    {
+        if let Some(pipeline) = self.pipelines.get(&state) {
+            return Ok(*pipeline);
+        }
+        let pipeline = create(&state, self.pipeline_cache)?;
+        self.pipelines.insert(state, pipeline);
+        Ok(pipeline)
+    }
+
+    /// The pipeline for a render state, if it has already been built.
+    pub fn get(&self, state: &RenderState) -> Option<vk::Pipeline> {
+        self.pipelines.get(state).copied()
+    }
+
+    /// The number of pipelines which have been built.
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    /// True when no pipelines have been built.
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// The render state of every pipeline which has been built.
+    pub fn states(&self) -> impl Iterator<Item = &RenderState> {
+        self.pipelines.keys()
+    }
+
+    /// Destroy every pipeline in the map.
+    ///
+    /// # Safety
+    ///
+    /// - none of the pipelines can be in use by the gpu
+    pub unsafe fn clear(&mut self) {
+        for (_, pipeline) in self.pipelines.drain() {
+            self.device
+                .forget_vulkan_object(vk::ObjectType::PIPELINE, &pipeline);
+            self.device.logical_device.destroy_pipeline(pipeline, None);
+        }
+    }
+}
+
+impl Drop for PipelineCacheMap {
+    fn drop(&mut self) {
+        unsafe {
+            self.clear();
+            self.device.forget_vulkan_object(
+                vk::ObjectType::PIPELINE_CACHE,
+                &self.pipeline_cache,
+            );
+            self.device
+                .logical_device
+                .destroy_pipeline_cache(self.pipeline_cache, None);
+        }
+    }
+}
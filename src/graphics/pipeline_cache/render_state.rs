@@ -0,0 +1,92 @@
+use super::{BlendMode, RenderState, VertexFormat};
+
+use crate::graphics::layer::ColorWriteMask;
+
+use ash::vk;
+
+impl RenderState {
+    /// The shader set used by the 2d pipeline.
+    pub const TEXTURE2D_SHADERS: &'static str = "texture2d";
+
+    /// The 2d vertex shader with a fragment shader which samples one layer
+    /// of a 2d array texture.
+    pub const TEXTURE2D_ARRAY_SHADERS: &'static str = "texture2d_array";
+
+    /// The 2d vertex shader with a fragment shader which looks up indexed
+    /// textures in a palette.
+    pub const PALETTE_SHADERS: &'static str = "palette";
+
+    /// True when the pipeline fills triangles.
+    pub fn is_filled(&self) -> bool {
+        self.polygon_mode == vk::PolygonMode::FILL
+    }
+
+    /// True when the pipeline needs blend constants to be set while
+    /// recording.
+    pub fn uses_blend_constants(&self) -> bool {
+        self.blend_mode == BlendMode::Constant
+    }
+}
+
+impl Default for RenderState {
+    /// Alpha blended, filled triangles using the 2d shaders.
+    fn default() -> Self {
+        Self {
+            blend_mode: BlendMode::Alpha,
+            color_write_mask: ColorWriteMask::ALL,
+            sample_count: vk::SampleCountFlags::TYPE_1,
+            polygon_mode: vk::PolygonMode::FILL,
+            shaders: Self::TEXTURE2D_SHADERS,
+            vertex_format: VertexFormat::Vertex2d,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn states_which_differ_in_any_field_should_be_distinct_keys() {
+        let default = RenderState::default();
+        let states = [
+            default,
+            RenderState {
+                blend_mode: BlendMode::Constant,
+                ..default
+            },
+            RenderState {
+                color_write_mask: ColorWriteMask::ALPHA,
+                ..default
+            },
+            RenderState {
+                sample_count: vk::SampleCountFlags::TYPE_4,
+                ..default
+            },
+            RenderState {
+                polygon_mode: vk::PolygonMode::LINE,
+                ..default
+            },
+            RenderState {
+                shaders: "hairline",
+                ..default
+            },
+            RenderState {
+                vertex_format: VertexFormat::Hairline,
+                ..default
+            },
+        ];
+        let keys: HashSet<RenderState> = states.iter().copied().collect();
+        assert_eq!(keys.len(), states.len());
+        assert!(keys.contains(&RenderState::default()));
+    }
+
+    #[test]
+    fn default_state_should_be_filled_alpha_blending() {
+        let state = RenderState::default();
+        assert!(state.is_filled());
+        assert!(!state.uses_blend_constants());
+    }
+}
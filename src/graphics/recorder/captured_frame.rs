@@ -0,0 +1,41 @@
+use super::{CapturedFrame, PixelOrder};
+
+impl CapturedFrame {
+    /// Consume the capture and return the pixels in RGBA order.
+    pub fn into_rgba(self) -> Vec<u8> {
+        let mut bytes = self.bytes;
+        if self.pixel_order == PixelOrder::Bgra {
+            for pixel in bytes.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn capture(pixel_order: PixelOrder, bytes: Vec<u8>) -> CapturedFrame {
+        CapturedFrame {
+            frame_number: 0,
+            width: 2,
+            height: 1,
+            pixel_order,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn into_rgba_should_swizzle_bgra_pixels() {
+        let frame = capture(PixelOrder::Bgra, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(frame.into_rgba(), vec![3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    fn into_rgba_should_keep_rgba_pixels() {
+        let frame = capture(PixelOrder::Rgba, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(frame.into_rgba(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
@@ -0,0 +1,182 @@
+use super::{CapturedFrame, RecorderOutput};
+
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc::Receiver,
+};
+
+/// The maximum number of out-of-order frames held while waiting for a missing
+/// frame. Once exceeded, the missing frame is assumed lost and skipped.
+const MAX_REORDER_FRAMES: usize = 8;
+
+/// Receive captured frames and encode them in frame-number order until the
+/// sending half of the channel is dropped.
+pub fn run(
+    output: RecorderOutput,
+    receiver: Receiver<CapturedFrame>,
+) -> Result<()> {
+    let mut sink = Sink::new(output);
+    let mut pending = BTreeMap::new();
+    let mut expected = 0;
+
+    for frame in receiver {
+        pending.insert(frame.frame_number, frame);
+        while let Some(frame) = pending.remove(&expected) {
+            sink.write(frame)?;
+            expected += 1;
+        }
+        if pending.len() > MAX_REORDER_FRAMES {
+            expected = *pending.keys().next().unwrap();
+        }
+    }
+
+    for (_, frame) in pending {
+        sink.write(frame)?;
+    }
+    sink.finish()
+}
+
+/// The destination for encoded frames.
+enum Sink {
+    PngSequence {
+        directory: PathBuf,
+        prefix: String,
+        index: u64,
+    },
+    Ffmpeg {
+        program: PathBuf,
+        output: PathBuf,
+        framerate: u32,
+        child: Option<(Child, u32, u32)>,
+    },
+}
+
+impl Sink {
+    fn new(output: RecorderOutput) -> Self {
+        match output {
+            RecorderOutput::PngSequence { directory, prefix } => {
+                Sink::PngSequence {
+                    directory,
+                    prefix,
+                    index: 0,
+                }
+            }
+            RecorderOutput::Ffmpeg {
+                program,
+                output,
+                framerate,
+            } => Sink::Ffmpeg {
+                program,
+                output,
+                framerate,
+                child: None,
+            },
+        }
+    }
+
+    /// Write a single frame.
+    fn write(&mut self, frame: CapturedFrame) -> Result<()> {
+        match self {
+            Sink::PngSequence {
+                directory,
+                prefix,
+                index,
+            } => {
+                let path =
+                    directory.join(format!("{}{:06}.png", prefix, index));
+                let (width, height) = (frame.width, frame.height);
+                image::save_buffer(
+                    &path,
+                    &frame.into_rgba(),
+                    width,
+                    height,
+                    image::ColorType::Rgba8,
+                )
+                .with_context(|| format!("unable to write {:?}", path))?;
+                *index += 1;
+            }
+            Sink::Ffmpeg {
+                program,
+                output,
+                framerate,
+                child,
+            } => {
+                if child.is_none() {
+                    let process = spawn_ffmpeg(
+                        program,
+                        output,
+                        *framerate,
+                        (frame.width, frame.height),
+                    )?;
+                    *child = Some((process, frame.width, frame.height));
+                }
+                let (process, width, height) = child.as_mut().unwrap();
+                if (frame.width, frame.height) != (*width, *height) {
+                    // ffmpeg can't change the size of a rawvideo stream
+                    log::warn!(
+                        "skipping frame {} with size {}x{}",
+                        frame.frame_number,
+                        frame.width,
+                        frame.height
+                    );
+                    return Ok(());
+                }
+                process
+                    .stdin
+                    .as_mut()
+                    .unwrap()
+                    .write_all(&frame.into_rgba())
+                    .with_context(|| "unable to write a frame to ffmpeg")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush the sink, waiting for any child process to exit.
+    fn finish(self) -> Result<()> {
+        if let Sink::Ffmpeg {
+            child: Some((mut process, _, _)),
+            ..
+        } = self
+        {
+            // closing stdin signals the end of the stream
+            drop(process.stdin.take());
+            let status = process
+                .wait()
+                .with_context(|| "unable to wait for ffmpeg to exit")?;
+            if !status.success() {
+                bail!("ffmpeg exited with {}", status);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Spawn an ffmpeg process which reads rgba frames of the given size from
+/// stdin and encodes them to the output path.
+fn spawn_ffmpeg(
+    program: &Path,
+    output: &Path,
+    framerate: u32,
+    (width, height): (u32, u32),
+) -> Result<Child> {
+    Command::new(program)
+        .args(["-y", "-loglevel", "error"])
+        .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+        .arg("-s")
+        .arg(format!("{}x{}", width, height))
+        .arg("-r")
+        .arg(framerate.to_string())
+        .args(["-i", "-"])
+        // yuv420p requires even dimensions
+        .args(["-vf", "scale=trunc(iw/2)*2:trunc(ih/2)*2"])
+        .args(["-pix_fmt", "yuv420p"])
+        .arg(output)
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("unable to spawn {:?}", program))
+}
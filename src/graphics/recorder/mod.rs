@@ -0,0 +1,62 @@
+//! Capture presented frames and write them to disk.
+//!
+//! Frames are copied out of the swapchain by the frame which rendered them
+//! and are read back the next time that frame is acquired, so the renderer
+//! never blocks waiting for a capture. Encoding happens on a worker thread.
+
+mod captured_frame;
+mod encoder;
+mod recording;
+
+use anyhow::Result;
+use std::{path::PathBuf, sync::mpsc::Sender, thread::JoinHandle};
+
+/// Where a recorder should write captured frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecorderOutput {
+    /// Write each frame as `{directory}/{prefix}{index:06}.png`.
+    PngSequence { directory: PathBuf, prefix: String },
+
+    /// Pipe raw RGBA frames into an ffmpeg child process.
+    ///
+    /// The `program` is invoked with arguments for reading rawvideo from
+    /// stdin at the given framerate and writing the result to `output`.
+    Ffmpeg {
+        program: PathBuf,
+        output: PathBuf,
+        framerate: u32,
+    },
+}
+
+/// The byte order of each pixel in a captured frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PixelOrder {
+    Rgba,
+    Bgra,
+}
+
+/// The raw pixels read back from a single presented frame.
+#[derive(Debug, Clone)]
+pub struct CapturedFrame {
+    /// The order in which this frame was captured by the recorder.
+    pub frame_number: u64,
+
+    pub width: u32,
+    pub height: u32,
+    pub pixel_order: PixelOrder,
+
+    /// Tightly packed rows of 4-byte pixels, top row first.
+    pub bytes: Vec<u8>,
+}
+
+/// Writes captured frames to a png sequence or video file.
+pub struct Recorder {
+    /// The frame number which will be assigned to the next capture.
+    next_frame_number: u64,
+
+    /// Captures are sent to the worker thread through this channel.
+    sender: Option<Sender<CapturedFrame>>,
+
+    /// The worker thread which encodes frames.
+    worker: Option<JoinHandle<Result<()>>>,
+}
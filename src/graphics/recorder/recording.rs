@@ -0,0 +1,99 @@
+use super::{encoder, CapturedFrame, Recorder, RecorderOutput};
+
+use anyhow::{anyhow, Context, Result};
+use std::{path::PathBuf, sync::mpsc, thread};
+
+impl Recorder {
+    /// Create a recorder which writes a numbered png sequence into the
+    /// directory. The directory is created if it does not already exist.
+    pub fn png_sequence<Dir, Prefix>(
+        directory: Dir,
+        prefix: Prefix,
+    ) -> Result<Self>
+    where
+        Dir: Into<PathBuf>,
+        Prefix: Into<String>,
+    {
+        Self::new(RecorderOutput::PngSequence {
+            directory: directory.into(),
+            prefix: prefix.into(),
+        })
+    }
+
+    /// Create a recorder which pipes frames into `ffmpeg` (found on the PATH)
+    /// to produce a video file.
+    pub fn ffmpeg<Output>(output: Output, framerate: u32) -> Result<Self>
+    where
+        Output: Into<PathBuf>,
+    {
+        Self::new(RecorderOutput::Ffmpeg {
+            program: PathBuf::from("ffmpeg"),
+            output: output.into(),
+            framerate,
+        })
+    }
+
+    /// Create a recorder for an arbitrary output and start the worker thread
+    /// which encodes frames.
+    pub fn new(output: RecorderOutput) -> Result<Self> {
+        if let RecorderOutput::PngSequence { directory, .. } = &output {
+            std::fs::create_dir_all(directory).with_context(|| {
+                format!("unable to create directory {:?}", directory)
+            })?;
+        }
+        let (sender, receiver) = mpsc::channel();
+        let worker = thread::Builder::new()
+            .name("draw2d recorder".to_owned())
+            .spawn(move || encoder::run(output, receiver))
+            .with_context(|| "unable to spawn the recorder thread")?;
+        Ok(Self {
+            next_frame_number: 0,
+            sender: Some(sender),
+            worker: Some(worker),
+        })
+    }
+
+    /// Reserve the frame number for the next capture.
+    pub fn next_frame_number(&mut self) -> u64 {
+        let frame_number = self.next_frame_number;
+        self.next_frame_number += 1;
+        frame_number
+    }
+
+    /// Send a captured frame to the worker thread to be encoded.
+    ///
+    /// Captures may be submitted in any order, the worker writes them in
+    /// order of their frame numbers.
+    pub fn submit(&mut self, frame: CapturedFrame) -> Result<()> {
+        let sent = self
+            .sender
+            .as_ref()
+            .map(|sender| sender.send(frame).is_ok())
+            .unwrap_or(false);
+        if sent {
+            Ok(())
+        } else {
+            // the worker only hangs up when it fails, so report its error
+            self.finish()
+        }
+    }
+
+    /// Stop recording and block until every submitted frame is written.
+    pub fn finish(&mut self) -> Result<()> {
+        self.sender = None;
+        match self.worker.take() {
+            Some(worker) => worker
+                .join()
+                .map_err(|_| anyhow!("the recorder thread panicked!"))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Err(error) = self.finish() {
+            log::error!("unable to finish recording: {:?}", error);
+        }
+    }
+}
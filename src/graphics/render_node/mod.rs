@@ -0,0 +1,83 @@
+//! User defined work recorded into the same command buffer as the layers.
+//!
+//! A `RenderNode` runs either before the 2d layer pass or after it, outside
+//! of the render pass, so it can dispatch compute work, copy images, or
+//! drive an external renderer. Each node declares the resources it reads
+//! and writes and is added to the frame graph as a pass, which inserts
+//! pipeline barriers between nodes, and between nodes and the layer pass, so
+//! nodes don't need to synchronize with each other.
+//!
+//! Nodes which run after the layers can use the swapchain image. It's
+//! returned to the presentation layout once every node has been recorded,
+//! so captures and feedback see the node's output. Nodes which run before
+//! the layers can't use the swapchain image because the layer pass clears
+//! it.
+
+mod render_node_handle;
+mod render_nodes;
+
+pub use crate::graphics::frame_graph::{FrameResource, ResourceUse};
+
+use crate::graphics::vulkan::Device;
+
+use anyhow::Result;
+use ash::vk;
+
+/// When a node is recorded relative to the 2d layer pass.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NodeStage {
+    BeforeLayers,
+    AfterLayers,
+}
+
+/// Everything a node can use while recording.
+pub struct FrameResources<'a> {
+    pub device: &'a Device,
+    pub swapchain_image: vk::Image,
+    pub format: vk::Format,
+    pub extent: vk::Extent2D,
+
+    /// The descriptor sets used by the 2d pipeline, in set order: the
+    /// texture atlas's shared set with every texture, then the frame's set
+    /// with its storage buffers.
+    pub descriptor_sets: [vk::DescriptorSet; 2],
+
+    /// The number of frames rendered before this one.
+    pub frame_number: u64,
+}
+
+/// Work which the application records into every frame.
+pub trait RenderNode {
+    /// A name used when reporting errors.
+    fn name(&self) -> &str;
+
+    /// When the node is recorded.
+    fn stage(&self) -> NodeStage;
+
+    /// Every resource the node reads or writes. Barriers are inserted
+    /// before `record` so each use is safe.
+    fn uses(&self) -> Vec<ResourceUse>;
+
+    /// Record the node's commands.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer is not inside a render pass
+    /// - every resource must be used as declared by `uses`
+    unsafe fn record(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        resources: &FrameResources,
+    ) -> Result<()>;
+}
+
+/// A unique reference to a registered render node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RenderNodeHandle {
+    id: u64,
+}
+
+/// Every registered node in the order it was added.
+pub struct RenderNodes {
+    nodes: Vec<(RenderNodeHandle, Box<dyn RenderNode>)>,
+}
@@ -0,0 +1,12 @@
+use super::RenderNodeHandle;
+
+use std::sync::atomic::{self, AtomicU64};
+
+impl RenderNodeHandle {
+    /// Generate a new handle which is known to be unique in this process.
+    pub(crate) fn generate() -> Self {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let id = COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
+        Self { id }
+    }
+}
@@ -0,0 +1,88 @@
+use super::{
+    FrameResource, FrameResources, NodeStage, RenderNode, RenderNodeHandle,
+    RenderNodes,
+};
+
+use crate::graphics::frame_graph::FrameGraph;
+
+use anyhow::{bail, Result};
+
+impl RenderNodes {
+    /// Create an empty set of nodes.
+    pub fn new() -> Self {
+        Self { nodes: vec![] }
+    }
+
+    /// Add a node which is recorded after every node added before it.
+    ///
+    /// Fails when a node which runs before the layers uses the swapchain
+    /// image.
+    pub fn add(
+        &mut self,
+        node: Box<dyn RenderNode>,
+    ) -> Result<RenderNodeHandle> {
+        let uses_swapchain = node
+            .uses()
+            .iter()
+            .any(|usage| usage.resource == FrameResource::SwapchainImage);
+        if node.stage() == NodeStage::BeforeLayers && uses_swapchain {
+            bail!(
+                "render node {} runs before the layer pass, which clears the \
+                 swapchain image, so it can't use the swapchain image",
+                node.name()
+            );
+        }
+        let handle = RenderNodeHandle::generate();
+        self.nodes.push((handle, node));
+        Ok(handle)
+    }
+
+    /// Remove a node, returning it when the handle was valid.
+    pub fn remove(
+        &mut self,
+        handle: RenderNodeHandle,
+    ) -> Option<Box<dyn RenderNode>> {
+        let index = self
+            .nodes
+            .iter()
+            .position(|(node_handle, _)| *node_handle == handle)?;
+        Some(self.nodes.remove(index).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Add a pass to the graph for every node in a stage.
+    pub(crate) fn add_passes<'a>(
+        &'a mut self,
+        stage: NodeStage,
+        graph: &mut FrameGraph<'a>,
+        resources: &'a FrameResources<'a>,
+    ) {
+        let nodes = self
+            .nodes
+            .iter_mut()
+            .filter(|(_, node)| node.stage() == stage);
+        for (_, node) in nodes {
+            let name = format!("render node {}", node.name());
+            graph.add_pass(
+                name,
+                node.uses(),
+                move |_, command_buffer| unsafe {
+                    node.record(command_buffer, resources)
+                },
+            );
+        }
+    }
+}
+
+impl Default for RenderNodes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
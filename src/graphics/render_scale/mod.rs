@@ -0,0 +1,54 @@
+//! Supersampled rendering: draw the layers at a multiple of the swapchain's
+//! resolution and filter them down to size.
+//!
+//! # Big Idea
+//!
+//! While a render scale is set, the layer render pass draws into an
+//! offscreen target instead of the swapchain image. The target has the
+//! swapchain's format and `scale` times its size. As soon as the layer pass
+//! ends, the target is blitted into the swapchain image with linear
+//! filtering, so render nodes, feedback, color grading, and recordings all
+//! see an ordinary frame at the swapchain's resolution.
+//!
+//! Every pipeline used in the layer pass sets its viewport and scissor
+//! while recording, which lets the same pipelines draw into targets of any
+//! size without being rebuilt.
+//!
+//! Hairlines stay one pixel wide in the target, so they're drawn thinner and
+//! fainter as the scale grows.
+
+mod render_scale_pass;
+mod scaled_extent;
+
+pub use self::scaled_extent::scaled_extent;
+
+use crate::graphics::vulkan::{texture::TextureImage, Device};
+
+use ash::vk;
+use std::sync::Arc;
+
+/// The largest supported render scale. A linear blit only reads a 2x2
+/// footprint, so larger scales would skip texels when filtering down.
+pub const MAX_RENDER_SCALE: f32 = 4.0;
+
+/// The offscreen target the layers are drawn into at the render scale.
+pub struct RenderScalePass {
+    /// The multiple of the swapchain's resolution used for the target.
+    scale: f32,
+
+    /// The size of the target.
+    extent: vk::Extent2D,
+
+    /// The size of the swapchain images the target is blitted into.
+    swapchain_extent: vk::Extent2D,
+
+    target: TextureImage,
+
+    /// A render pass compatible with the swapchain's, which leaves the
+    /// target ready to be blitted.
+    render_pass: vk::RenderPass,
+
+    framebuffer: vk::Framebuffer,
+
+    device: Arc<Device>,
+}
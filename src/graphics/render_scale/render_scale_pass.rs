@@ -0,0 +1,348 @@
+use super::{scaled_extent, RenderScalePass, MAX_RENDER_SCALE};
+
+use crate::graphics::vulkan::{texture::TextureImage, Device, Swapchain};
+
+use anyhow::{bail, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+impl RenderScalePass {
+    /// Create a target `scale` times the size of the swapchain.
+    ///
+    /// Fails when the scale is out of range or when the swapchain images
+    /// can't be blitted into.
+    pub fn new(
+        device: Arc<Device>,
+        swapchain: &Swapchain,
+        scale: f32,
+    ) -> Result<Self> {
+        if !(scale > 0.0 && scale <= MAX_RENDER_SCALE) {
+            bail!(
+                "the render scale must be greater than 0 and at most {}, \
+                 found {}",
+                MAX_RENDER_SCALE,
+                scale
+            );
+        }
+        if !swapchain
+            .image_usage
+            .contains(vk::ImageUsageFlags::TRANSFER_DST)
+        {
+            bail!(
+                "render scale requires a swapchain which supports TRANSFER_DST"
+            );
+        }
+        let required = vk::FormatFeatureFlags::BLIT_SRC
+            | vk::FormatFeatureFlags::BLIT_DST
+            | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR;
+        if !device
+            .optimal_format_features(swapchain.format)
+            .contains(required)
+        {
+            bail!(
+                "render scale requires linear blits with the swapchain's \
+                 format {:?}",
+                swapchain.format
+            );
+        }
+
+        let render_pass = create_render_pass(&device, swapchain.format)?;
+        let extent = scaled_extent(swapchain.extent, scale);
+        let (target, framebuffer) =
+            create_target(&device, swapchain.format, extent, render_pass)?;
+        Ok(Self {
+            scale,
+            extent,
+            swapchain_extent: swapchain.extent,
+            target,
+            render_pass,
+            framebuffer,
+            device,
+        })
+    }
+
+    /// The multiple of the swapchain's resolution used for the target.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// The size of the target.
+    pub fn extent(&self) -> vk::Extent2D {
+        self.extent
+    }
+
+    /// The render pass to begin instead of the swapchain's.
+    pub fn render_pass(&self) -> vk::RenderPass {
+        self.render_pass
+    }
+
+    /// The framebuffer to render the layers into.
+    pub fn framebuffer(&self) -> vk::Framebuffer {
+        self.framebuffer
+    }
+
+    /// The image the layers are rendered into.
+    pub fn target_image(&self) -> vk::Image {
+        // SAFE: the handle is only used to record commands while the pass
+        // owns the image
+        unsafe { self.target.raw_image() }
+    }
+
+    /// How the target is created.
+    pub fn target_create_info(&self) -> vk::ImageCreateInfo {
+        target_create_info(self.target.format(), self.extent)
+    }
+
+    /// Render into a different target, usually one which aliases the memory
+    /// of other post effect targets. The render pass clears the target, so
+    /// it doesn't need to be initialized.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the target is still
+    ///   rendering
+    /// - the image must have been created with `target_create_info`
+    pub unsafe fn replace_target(
+        &mut self,
+        target: TextureImage,
+    ) -> Result<()> {
+        let framebuffer = create_framebuffer(
+            &self.device,
+            &target,
+            self.extent,
+            self.render_pass,
+        )?;
+        self.device.forget_vulkan_object(
+            vk::ObjectType::FRAMEBUFFER,
+            &self.framebuffer,
+        );
+        self.device
+            .logical_device
+            .destroy_framebuffer(self.framebuffer, None);
+        self.framebuffer = framebuffer;
+        self.target = target;
+        Ok(())
+    }
+
+    /// Rebuild the target to match a new swapchain, keeping the scale.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the target is still
+    ///   rendering
+    pub unsafe fn rebuild(&mut self, swapchain: &Swapchain) -> Result<()> {
+        *self = Self::new(self.device.clone(), swapchain, self.scale)?;
+        Ok(())
+    }
+
+    /// Record commands which filter the target down into the swapchain
+    /// image.
+    ///
+    /// # Safety
+    ///
+    /// - the target must be in the `TRANSFER_SRC_OPTIMAL` layout and the
+    ///   swapchain image in `TRANSFER_DST_OPTIMAL`, the frame graph inserts
+    ///   the transitions
+    pub unsafe fn record_blit(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        swapchain_image: vk::Image,
+    ) {
+        let subresource = vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        let corner = |extent: vk::Extent2D| vk::Offset3D {
+            x: extent.width as i32,
+            y: extent.height as i32,
+            z: 1,
+        };
+        let region = vk::ImageBlit {
+            src_subresource: subresource,
+            src_offsets: [vk::Offset3D::default(), corner(self.extent)],
+            dst_subresource: subresource,
+            dst_offsets: [
+                vk::Offset3D::default(),
+                corner(self.swapchain_extent),
+            ],
+        };
+        self.device.logical_device.cmd_blit_image(
+            command_buffer,
+            self.target.raw_image(),
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            swapchain_image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            &[region],
+            vk::Filter::LINEAR,
+        );
+    }
+}
+
+impl Drop for RenderScalePass {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.forget_vulkan_object(
+                vk::ObjectType::FRAMEBUFFER,
+                &self.framebuffer,
+            );
+            self.device
+                .logical_device
+                .destroy_framebuffer(self.framebuffer, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::RENDER_PASS,
+                &self.render_pass,
+            );
+            self.device
+                .logical_device
+                .destroy_render_pass(self.render_pass, None);
+        }
+    }
+}
+
+/// Create a render pass which is compatible with the swapchain's, so the
+/// layer pipelines can be used with it, but leaves the target ready to be
+/// blitted.
+fn create_render_pass(
+    device: &Device,
+    format: vk::Format,
+) -> Result<vk::RenderPass> {
+    let attachments = [vk::AttachmentDescription {
+        format,
+        samples: vk::SampleCountFlags::TYPE_1,
+        load_op: vk::AttachmentLoadOp::CLEAR,
+        store_op: vk::AttachmentStoreOp::STORE,
+        stencil_load_op: vk::AttachmentLoadOp::DONT_CARE,
+        stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        final_layout: vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        ..Default::default()
+    }];
+
+    let color_references = [vk::AttachmentReference {
+        attachment: 0,
+        layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+    }];
+
+    let subpasses = [vk::SubpassDescription {
+        pipeline_bind_point: vk::PipelineBindPoint::GRAPHICS,
+        p_color_attachments: color_references.as_ptr(),
+        color_attachment_count: color_references.len() as u32,
+        ..Default::default()
+    }];
+
+    // the previous frame's blit must finish reading before the target is
+    // cleared, and the target may alias images the previous frame's post
+    // effects copied into
+    let dependencies = [vk::SubpassDependency {
+        src_subpass: vk::SUBPASS_EXTERNAL,
+        src_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT
+            | vk::PipelineStageFlags::TRANSFER,
+        src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+        dst_subpass: 0,
+        dst_stage_mask: vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+        dst_access_mask: vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+        dependency_flags: vk::DependencyFlags::default(),
+    }];
+
+    let create_info = vk::RenderPassCreateInfo {
+        p_attachments: attachments.as_ptr(),
+        attachment_count: attachments.len() as u32,
+        p_subpasses: subpasses.as_ptr(),
+        subpass_count: subpasses.len() as u32,
+        p_dependencies: dependencies.as_ptr(),
+        dependency_count: dependencies.len() as u32,
+        ..Default::default()
+    };
+
+    let render_pass = unsafe {
+        device
+            .logical_device
+            .create_render_pass(&create_info, None)?
+    };
+    device.name_vulkan_object(
+        "Render Scale Render Pass",
+        vk::ObjectType::RENDER_PASS,
+        &render_pass,
+    )?;
+    Ok(render_pass)
+}
+
+/// Create the target image and a framebuffer which renders into it.
+fn create_target(
+    device: &Arc<Device>,
+    format: vk::Format,
+    extent: vk::Extent2D,
+    render_pass: vk::RenderPass,
+) -> Result<(TextureImage, vk::Framebuffer)> {
+    let target = TextureImage::new(
+        device.clone(),
+        target_create_info(format, extent),
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+    let framebuffer = create_framebuffer(device, &target, extent, render_pass)?;
+    Ok((target, framebuffer))
+}
+
+/// How the target is created.
+fn target_create_info(
+    format: vk::Format,
+    extent: vk::Extent2D,
+) -> vk::ImageCreateInfo {
+    vk::ImageCreateInfo {
+        image_type: vk::ImageType::TYPE_2D,
+        extent: vk::Extent3D {
+            width: extent.width,
+            height: extent.height,
+            depth: 1,
+        },
+        mip_levels: 1,
+        array_layers: 1,
+        format,
+        tiling: vk::ImageTiling::OPTIMAL,
+        initial_layout: vk::ImageLayout::UNDEFINED,
+        usage: vk::ImageUsageFlags::COLOR_ATTACHMENT
+            | vk::ImageUsageFlags::TRANSFER_SRC,
+        samples: vk::SampleCountFlags::TYPE_1,
+        sharing_mode: vk::SharingMode::EXCLUSIVE,
+        ..Default::default()
+    }
+}
+
+/// Name the target and create a framebuffer which renders into it.
+fn create_framebuffer(
+    device: &Arc<Device>,
+    target: &TextureImage,
+    extent: vk::Extent2D,
+    render_pass: vk::RenderPass,
+) -> Result<vk::Framebuffer> {
+    let (image, view) = unsafe { (target.raw_image(), target.raw_view()) };
+    device.name_vulkan_object(
+        "Render Scale Target - Image",
+        vk::ObjectType::IMAGE,
+        &image,
+    )?;
+
+    let attachments = [view];
+    let framebuffer = unsafe {
+        device.logical_device.create_framebuffer(
+            &vk::FramebufferCreateInfo {
+                render_pass,
+                p_attachments: attachments.as_ptr(),
+                attachment_count: attachments.len() as u32,
+                width: extent.width,
+                height: extent.height,
+                layers: 1,
+                ..Default::default()
+            },
+            None,
+        )?
+    };
+    device.name_vulkan_object(
+        "Render Scale Target - Framebuffer",
+        vk::ObjectType::FRAMEBUFFER,
+        &framebuffer,
+    )?;
+    Ok(framebuffer)
+}
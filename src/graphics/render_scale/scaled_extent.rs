@@ -0,0 +1,61 @@
+use ash::vk;
+
+/// The size of a target `scale` times larger than the extent, rounded to the
+/// nearest pixel. Neither side is ever smaller than one pixel.
+pub fn scaled_extent(extent: vk::Extent2D, scale: f32) -> vk::Extent2D {
+    let scale_side = |side: u32| (side as f32 * scale).round().max(1.0) as u32;
+    vk::Extent2D {
+        width: scale_side(extent.width),
+        height: scale_side(extent.height),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scaled_extent_should_multiply_each_side() {
+        let extent = vk::Extent2D {
+            width: 800,
+            height: 600,
+        };
+        assert_eq!(
+            scaled_extent(extent, 2.0),
+            vk::Extent2D {
+                width: 1600,
+                height: 1200
+            }
+        );
+    }
+
+    #[test]
+    fn scaled_extent_should_round_to_whole_pixels() {
+        let extent = vk::Extent2D {
+            width: 101,
+            height: 33,
+        };
+        assert_eq!(
+            scaled_extent(extent, 1.5),
+            vk::Extent2D {
+                width: 152,
+                height: 50
+            }
+        );
+    }
+
+    #[test]
+    fn scaled_extent_should_never_be_empty() {
+        let extent = vk::Extent2D {
+            width: 1,
+            height: 1,
+        };
+        assert_eq!(
+            scaled_extent(extent, 0.25),
+            vk::Extent2D {
+                width: 1,
+                height: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Per-frame statistics about the work submitted to the gpu.
+//!
+//! `Graphics::render_report` returns the statistics for the most recently
+//! rendered frame, and `Graphics::log_render_reports` periodically logs the
+//! totals for a span of frames.
+
+mod render_report;
+
+/// Counters for the work done to render a single frame, or the totals for a
+/// span of frames.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct RenderReport {
+    /// The first frame counted by this report.
+    pub frame_number: u64,
+
+    /// The number of frames counted by this report.
+    pub frames: u64,
+
+    pub draw_calls: u64,
+
+    /// Batches with at least one draw call.
+    pub batches_drawn: u64,
+
+    /// Batches which were skipped because they were empty or entirely
+    /// outside of the view.
+    pub batches_culled: u64,
+
+    /// Vertices processed by all draw calls. A batch drawn more than once,
+    /// like a wrapped layer, counts its vertices once per draw.
+    pub vertices_submitted: u64,
+
+    /// The number of times a draw used a different texture than the draw
+    /// before it.
+    pub texture_binds: u64,
+
+    /// Bytes of vertex data copied into gpu-visible buffers.
+    pub bytes_uploaded: u64,
+}
+
+/// Accumulates reports and decides when the totals should be logged.
+#[derive(Debug, Clone)]
+pub struct ReportLog {
+    /// The number of frames counted by each logged report.
+    interval: u64,
+
+    totals: Option<RenderReport>,
+}
@@ -0,0 +1,105 @@
+use super::{RenderReport, ReportLog};
+
+use std::fmt;
+
+impl RenderReport {
+    /// An empty report for a single frame.
+    pub fn for_frame(frame_number: u64) -> Self {
+        Self {
+            frame_number,
+            frames: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Add another report's counters to this report.
+    pub fn accumulate(&mut self, other: &RenderReport) {
+        self.frames += other.frames;
+        self.draw_calls += other.draw_calls;
+        self.batches_drawn += other.batches_drawn;
+        self.batches_culled += other.batches_culled;
+        self.vertices_submitted += other.vertices_submitted;
+        self.texture_binds += other.texture_binds;
+        self.bytes_uploaded += other.bytes_uploaded;
+    }
+}
+
+impl fmt::Display for RenderReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let per_frame = |total: u64| total as f64 / self.frames.max(1) as f64;
+        write!(
+            f,
+            "frames {}..{}: {:.1} draw calls, {:.1} batches drawn, \
+             {:.1} batches culled, {:.1} vertices, {:.1} texture binds, \
+             {:.1} bytes uploaded per frame",
+            self.frame_number,
+            self.frame_number + self.frames,
+            per_frame(self.draw_calls),
+            per_frame(self.batches_drawn),
+            per_frame(self.batches_culled),
+            per_frame(self.vertices_submitted),
+            per_frame(self.texture_binds),
+            per_frame(self.bytes_uploaded),
+        )
+    }
+}
+
+impl ReportLog {
+    /// Create a log which produces a report every `interval` frames.
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            totals: None,
+        }
+    }
+
+    /// Add a frame's report. Returns the totals once `interval` frames have
+    /// been recorded, then starts counting again.
+    pub fn record(&mut self, report: &RenderReport) -> Option<RenderReport> {
+        let totals = self.totals.get_or_insert(RenderReport {
+            frame_number: report.frame_number,
+            ..Default::default()
+        });
+        totals.accumulate(report);
+        if totals.frames >= self.interval {
+            self.totals.take()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(frame_number: u64, draw_calls: u64) -> RenderReport {
+        RenderReport {
+            draw_calls,
+            ..RenderReport::for_frame(frame_number)
+        }
+    }
+
+    #[test]
+    fn record_should_return_totals_every_interval() {
+        let mut log = ReportLog::new(2);
+
+        assert_eq!(log.record(&frame(10, 3)), None);
+        let totals = log.record(&frame(11, 5)).unwrap();
+        assert_eq!(totals.frame_number, 10);
+        assert_eq!(totals.frames, 2);
+        assert_eq!(totals.draw_calls, 8);
+
+        assert_eq!(log.record(&frame(12, 1)), None);
+    }
+
+    #[test]
+    fn display_should_show_per_frame_averages() {
+        let mut totals = frame(4, 3);
+        totals.accumulate(&frame(5, 5));
+
+        let text = totals.to_string();
+
+        assert!(text.starts_with("frames 4..6: 4.0 draw calls"), "{}", text);
+    }
+}
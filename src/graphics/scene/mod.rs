@@ -0,0 +1,55 @@
+//! A document type for saving and reloading everything a sketch has drawn.
+//!
+//! Scenes reference textures by the logical names given with
+//! `GpuAtlas::name_texture`, so a saved scene can be loaded by a later run
+//! of the application which loads the same textures in any order. Enable the
+//! `serialize` feature to read and write scenes with serde.
+
+mod scene_layer;
+
+use crate::{
+    camera::OrthoCamera,
+    graphics::{hairline::Hairlines, vertex::Vertex2d},
+};
+
+use nalgebra as na;
+
+/// A saved copy of the graphics subsystem's layers.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scene {
+    /// The camera used to view the scene, if the application saved one.
+    pub camera: Option<OrthoCamera>,
+
+    /// The color used to clear the screen.
+    pub clear_color: [f32; 4],
+
+    /// Every layer, back to front.
+    pub layers: Vec<SceneLayer>,
+}
+
+/// A saved layer.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SceneLayer {
+    pub projection: na::Matrix4<f32>,
+    pub batches: Vec<SceneBatch>,
+
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub hairlines: Vec<Hairlines>,
+}
+
+/// A saved batch.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct SceneBatch {
+    /// The logical name of the batch's texture, or None for the atlas's
+    /// default all-white texture.
+    pub texture: Option<String>,
+
+    pub vertices: Vec<Vertex2d>,
+
+    /// The layer drawn from a 2d array texture.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub layer: u32,
+}
@@ -0,0 +1,118 @@
+use super::{SceneBatch, SceneLayer};
+
+use crate::graphics::{
+    layer::{Batch, Layer},
+    texture_atlas::{GpuAtlas, TextureHandle},
+};
+
+use anyhow::{bail, Context, Result};
+
+impl SceneLayer {
+    /// Save a layer, replacing texture handles with texture names.
+    ///
+    /// Fails if any batch uses a texture which has not been named.
+    pub fn from_layer(layer: &Layer, atlas: &GpuAtlas) -> Result<Self> {
+        let batches = layer
+            .batches()
+            .iter()
+            .map(|batch| SceneBatch::from_batch(batch, atlas))
+            .collect::<Result<Vec<SceneBatch>>>()?;
+        Ok(Self {
+            projection: *layer.projection(),
+            batches,
+            hairlines: layer.hairlines().to_vec(),
+        })
+    }
+
+    /// Fill a layer with this layer's batches, resolving texture names
+    /// against the atlas.
+    pub fn fill_layer(
+        &self,
+        layer: &mut Layer,
+        atlas: &GpuAtlas,
+    ) -> Result<()> {
+        let batches = self
+            .batches
+            .iter()
+            .map(|batch| batch.to_batch(atlas))
+            .collect::<Result<Vec<Batch>>>()?;
+        layer.clear();
+        layer.set_projection(self.projection);
+        layer.push_batches(&batches);
+        for hairlines in &self.hairlines {
+            layer.push_hairlines(hairlines.clone());
+        }
+        Ok(())
+    }
+}
+
+impl SceneBatch {
+    /// Save a batch, replacing its texture handle with the texture's name.
+    pub fn from_batch(batch: &Batch, atlas: &GpuAtlas) -> Result<Self> {
+        let texture = if batch.texture_handle == TextureHandle::default() {
+            None
+        } else {
+            let name = atlas.texture_name(batch.texture_handle).with_context(
+                || {
+                    format!(
+                        "{:?} must be named before it can be saved",
+                        batch.texture_handle
+                    )
+                },
+            )?;
+            Some(name.to_owned())
+        };
+        Ok(Self {
+            texture,
+            vertices: batch.vertices.clone(),
+            layer: batch.layer,
+        })
+    }
+
+    /// Build a batch, resolving the texture name against the atlas.
+    pub fn to_batch(&self, atlas: &GpuAtlas) -> Result<Batch> {
+        let texture_handle = match &self.texture {
+            None => TextureHandle::default(),
+            Some(name) => match atlas.texture_by_name(name) {
+                Some(handle) => handle,
+                None => bail!("no texture named {:?} in the atlas", name),
+            },
+        };
+        Ok(Batch {
+            texture_handle,
+            vertices: self.vertices.clone(),
+            layer: self.layer,
+            palette: None,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+mod test {
+    use super::super::*;
+
+    use crate::graphics::{hairline::Hairlines, vertex::Vertex2d};
+
+    #[test]
+    fn scenes_should_round_trip_through_json() -> anyhow::Result<()> {
+        let scene = Scene {
+            camera: Some(OrthoCamera::with_viewport(10.0, 1.5)),
+            clear_color: [0.1, 0.2, 0.3, 1.0],
+            layers: vec![SceneLayer {
+                projection: na::Matrix4::identity(),
+                batches: vec![SceneBatch {
+                    texture: Some("tiles".to_owned()),
+                    vertices: vec![Vertex2d::default(); 3],
+                    layer: 2,
+                }],
+                hairlines: vec![Hairlines::new(2.0)],
+            }],
+        };
+
+        let json = serde_json::to_string(&scene)?;
+        let loaded: Scene = serde_json::from_str(&json)?;
+
+        assert_eq!(loaded, scene);
+        Ok(())
+    }
+}
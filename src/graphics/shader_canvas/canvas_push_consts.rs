@@ -0,0 +1,68 @@
+use super::{CanvasPushConsts, MAX_CHANNELS};
+
+use nalgebra as na;
+
+impl CanvasPushConsts {
+    /// Build the push constants for a single draw. Channels are indices
+    /// into the shaders' texture array.
+    pub fn new(
+        projection: na::Matrix4<f32>,
+        resolution: [f32; 2],
+        time: f32,
+        time_delta: f32,
+        frame: u32,
+        mouse: [f32; 4],
+        channels: [u32; MAX_CHANNELS],
+    ) -> Self {
+        Self {
+            projection: projection.into(),
+            texture_index: 0,
+            frame,
+            time,
+            time_delta,
+            resolution,
+            _padding: [0.0; 2],
+            mouse,
+            channels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use memoffset::offset_of;
+    use std::mem::size_of;
+
+    #[test]
+    fn layout_should_match_the_documented_glsl_block() {
+        assert_eq!(offset_of!(CanvasPushConsts, frame), 68);
+        assert_eq!(offset_of!(CanvasPushConsts, time), 72);
+        assert_eq!(offset_of!(CanvasPushConsts, time_delta), 76);
+        assert_eq!(offset_of!(CanvasPushConsts, resolution), 80);
+        assert_eq!(offset_of!(CanvasPushConsts, mouse), 96);
+        assert_eq!(offset_of!(CanvasPushConsts, channels), 112);
+    }
+
+    #[test]
+    fn push_consts_should_fit_the_guaranteed_limit() {
+        // every vulkan device supports at least 128 bytes of push constants
+        assert!(size_of::<CanvasPushConsts>() <= 128);
+    }
+
+    #[test]
+    fn channels_should_use_atlas_indices() {
+        let consts = CanvasPushConsts::new(
+            na::Matrix4::identity(),
+            [640.0, 480.0],
+            1.5,
+            0.016,
+            90,
+            [0.0; 4],
+            [0, 3, 0, 7],
+        );
+        assert_eq!(consts.channels, [0, 3, 0, 7]);
+        assert_eq!(consts.resolution, [640.0, 480.0]);
+    }
+}
@@ -0,0 +1,121 @@
+//! A fullscreen quad driven by a user fragment shader, in the style of
+//! Shadertoy.
+//!
+//! The canvas is drawn behind every layer. Its fragment shader receives the
+//! quad's uv coordinates, with (0, 0) in the top left corner, and the same
+//! texture atlas and storage buffers as the 2d pipeline. Time, resolution,
+//! mouse, and four texture channels are provided as push constants:
+//!
+//! ```glsl
+//! #version 450
+//!
+//! layout(constant_id = 0) const uint MAX_TEXTURES = 1;
+//! layout(constant_id = 1) const uint MAX_SAMPLERS = 1;
+//! layout(binding = 0) uniform texture2D textures[MAX_TEXTURES];
+//! layout(binding = 2) uniform sampler samplers[MAX_SAMPLERS];
+//!
+//! layout(location = 0) in vec2 vary_uv;
+//! layout(location = 0) out vec4 frag_color;
+//!
+//! layout(push_constant) uniform Canvas {
+//!     layout(offset = 68) uint frame;
+//!     float time;
+//!     float time_delta;
+//!     vec2 resolution;
+//!     layout(offset = 96) vec4 mouse;
+//!     uvec4 channels;
+//! } canvas;
+//!
+//! void main() {
+//!     vec4 channel0 = texture(
+//!         sampler2D(textures[canvas.channels.x], samplers[0]),
+//!         vary_uv);
+//!     frag_color = channel0 * vec4(vary_uv, 0.5 + 0.5 * sin(canvas.time), 1);
+//! }
+//! ```
+//!
+//! The canvas can also read the frame's uniforms, which add the world space
+//! camera, from `layout(set = 1, binding = 0)`. See
+//! `graphics::frame::FrameUniforms`.
+//!
+//! Channels default to the atlas's all-white texture. Sampler 0 is the
+//! atlas's default sampler, a channel's bound sampler isn't passed to the
+//! canvas. Shaders loaded from a
+//! file with `Graphics::load_shader_canvas` are rebuilt whenever the file
+//! changes.
+
+mod canvas_push_consts;
+mod pipeline;
+mod shader_canvas_pass;
+
+use crate::graphics::{
+    texture_atlas::TextureHandle,
+    vulkan::{buffer::StaticBuffer, Device},
+};
+
+use ash::vk;
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
+
+/// The number of texture channels available to a canvas shader.
+pub const MAX_CHANNELS: usize = 4;
+
+/// A fullscreen quad drawn with a user fragment shader.
+pub struct ShaderCanvas {
+    pipeline: CanvasPipeline,
+
+    /// The SPIR-V code for the fragment shader.
+    fragment_spirv: Vec<u32>,
+
+    /// The file the fragment shader was read from, when hot reloading.
+    source_path: Option<PathBuf>,
+
+    /// The file's modification time when it was last read.
+    source_modified: Option<SystemTime>,
+
+    /// The fullscreen quad's vertices, written once.
+    quad: StaticBuffer,
+
+    mouse: [f32; 4],
+    channels: [TextureHandle; MAX_CHANNELS],
+
+    /// When the canvas was created, time starts at zero here.
+    start: Instant,
+
+    /// When the canvas was last drawn, used for the time delta.
+    last_draw: Option<Instant>,
+
+    /// The number of times the canvas has been drawn.
+    frame: u32,
+
+    device: Arc<Device>,
+}
+
+/// The vulkan pipeline for rendering a canvas.
+struct CanvasPipeline {
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    descriptor_set_layouts: [vk::DescriptorSetLayout; 2],
+    device: Arc<Device>,
+}
+
+/// The push constants used by the canvas pipeline.
+///
+/// The first two fields match the 2d pipeline's vertex shader, which the
+/// canvas reuses to draw its quad.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CanvasPushConsts {
+    pub projection: [[f32; 4]; 4],
+    pub texture_index: u32,
+    pub frame: u32,
+    pub time: f32,
+    pub time_delta: f32,
+    pub resolution: [f32; 2],
+    pub _padding: [f32; 2],
+    pub mouse: [f32; 4],
+    pub channels: [u32; MAX_CHANNELS],
+}
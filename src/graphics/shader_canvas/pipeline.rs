@@ -0,0 +1,273 @@
+use super::{CanvasPipeline, CanvasPushConsts};
+
+use crate::graphics::{
+    pipeline2d::descriptor_sets,
+    vertex::Vertex2d,
+    vulkan::{ffi, shader_module::ShaderModule, Device, Swapchain},
+};
+
+use anyhow::{Context, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::{
+    ffi::{c_void, CString},
+    mem::size_of,
+    sync::Arc,
+};
+
+impl CanvasPipeline {
+    /// Create a pipeline which draws with the 2d vertex shader and the
+    /// provided fragment shader.
+    pub fn new(
+        device: Arc<Device>,
+        swapchain: &Swapchain,
+        fragment_spirv: &[u32],
+    ) -> Result<Self> {
+        let vertex_module = ShaderModule::new(
+            &device,
+            "Shader Canvas Vertex Shader",
+            std::include_bytes!(concat!(
+                env!("OUT_DIR"),
+                "/shaders/texture2d.vert.sprv"
+            )),
+        )?;
+        let fragment_module = ShaderModule::from_words(
+            &device,
+            "Shader Canvas Fragment Shader",
+            fragment_spirv,
+        )?;
+
+        let entry = CString::new("main").unwrap();
+        let specialization_map_entries =
+            descriptor_sets::atlas_specialization_entries();
+        let array_sizes = descriptor_sets::atlas_array_sizes(&device);
+        let specialization_data = unsafe { ffi::any_as_u8_slice(&array_sizes) };
+        let fragment_specialization_info = vk::SpecializationInfo {
+            p_map_entries: specialization_map_entries.as_ptr(),
+            map_entry_count: specialization_map_entries.len() as u32,
+            p_data: specialization_data.as_ptr() as *const c_void,
+            data_size: specialization_data.len(),
+        };
+        let stages = [
+            vk::PipelineShaderStageCreateInfo {
+                stage: vk::ShaderStageFlags::VERTEX,
+                module: vertex_module.shader_module,
+                p_name: entry.as_ptr(),
+                ..Default::default()
+            },
+            vk::PipelineShaderStageCreateInfo {
+                stage: vk::ShaderStageFlags::FRAGMENT,
+                module: fragment_module.shader_module,
+                p_specialization_info: &fragment_specialization_info,
+                p_name: entry.as_ptr(),
+                ..Default::default()
+            },
+        ];
+
+        let (binding_descriptions, attribute_descriptions) =
+            Vertex2d::binding_description();
+        let vertex_input_state = vk::PipelineVertexInputStateCreateInfo {
+            p_vertex_binding_descriptions: binding_descriptions.as_ptr(),
+            vertex_binding_description_count: binding_descriptions.len() as u32,
+            p_vertex_attribute_descriptions: attribute_descriptions.as_ptr(),
+            vertex_attribute_description_count: attribute_descriptions.len()
+                as u32,
+            ..Default::default()
+        };
+
+        let input_assembly_state = vk::PipelineInputAssemblyStateCreateInfo {
+            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            primitive_restart_enable: 0,
+            ..Default::default()
+        };
+
+        let viewports = [vk::Viewport {
+            x: 0.0,
+            y: 0.0,
+            width: swapchain.extent.width as f32,
+            height: swapchain.extent.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }];
+
+        let scissors = [vk::Rect2D {
+            offset: vk::Offset2D { x: 0, y: 0 },
+            extent: swapchain.extent,
+        }];
+
+        let viewport_state = vk::PipelineViewportStateCreateInfo {
+            p_viewports: viewports.as_ptr(),
+            viewport_count: 1,
+            p_scissors: scissors.as_ptr(),
+            scissor_count: 1,
+            ..Default::default()
+        };
+
+        let raster_state = vk::PipelineRasterizationStateCreateInfo {
+            polygon_mode: vk::PolygonMode::FILL,
+            line_width: 1.0,
+            cull_mode: vk::CullModeFlags::NONE,
+            front_face: vk::FrontFace::CLOCKWISE,
+            ..Default::default()
+        };
+
+        let multisample_state = vk::PipelineMultisampleStateCreateInfo {
+            rasterization_samples: vk::SampleCountFlags::TYPE_1,
+            min_sample_shading: 1.0,
+            ..Default::default()
+        };
+
+        // the canvas covers the whole screen, so it replaces the clear color
+        // rather than blending with it
+        let blend_attachments = [vk::PipelineColorBlendAttachmentState {
+            color_write_mask: vk::ColorComponentFlags::R
+                | vk::ColorComponentFlags::G
+                | vk::ColorComponentFlags::B
+                | vk::ColorComponentFlags::A,
+            blend_enable: 0,
+            ..Default::default()
+        }];
+
+        let blend_state = vk::PipelineColorBlendStateCreateInfo {
+            logic_op_enable: 0,
+            logic_op: vk::LogicOp::COPY,
+            p_attachments: blend_attachments.as_ptr(),
+            attachment_count: blend_attachments.len() as u32,
+            ..Default::default()
+        };
+
+        let descriptor_set_layouts = unsafe {
+            descriptor_sets::create_descriptor_set_layouts(
+                &device,
+                "Shader Canvas",
+            )?
+        };
+        let push_constant_ranges = [vk::PushConstantRange {
+            stage_flags: vk::ShaderStageFlags::FRAGMENT
+                | vk::ShaderStageFlags::VERTEX,
+            size: size_of::<CanvasPushConsts>() as u32,
+            offset: 0,
+        }];
+        let pipeline_layout_create_info = vk::PipelineLayoutCreateInfo {
+            p_set_layouts: descriptor_set_layouts.as_ptr(),
+            set_layout_count: descriptor_set_layouts.len() as u32,
+            p_push_constant_ranges: push_constant_ranges.as_ptr(),
+            push_constant_range_count: push_constant_ranges.len() as u32,
+            ..Default::default()
+        };
+
+        let pipeline_layout = unsafe {
+            device
+                .logical_device
+                .create_pipeline_layout(&pipeline_layout_create_info, None)?
+        };
+        device.name_vulkan_object(
+            "Shader Canvas Pipeline Layout",
+            vk::ObjectType::PIPELINE_LAYOUT,
+            &pipeline_layout,
+        )?;
+
+        // the layer pass may render above the swapchain's resolution, see
+        // `Graphics::set_render_scale`
+        let dynamic_states =
+            [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+        let dynamic_state = vk::PipelineDynamicStateCreateInfo {
+            p_dynamic_states: dynamic_states.as_ptr(),
+            dynamic_state_count: dynamic_states.len() as u32,
+            ..Default::default()
+        };
+
+        let pipeline_create_info = vk::GraphicsPipelineCreateInfo {
+            p_stages: stages.as_ptr(),
+            stage_count: stages.len() as u32,
+            p_vertex_input_state: &vertex_input_state,
+            p_input_assembly_state: &input_assembly_state,
+            p_viewport_state: &viewport_state,
+            p_rasterization_state: &raster_state,
+            p_multisample_state: &multisample_state,
+            p_color_blend_state: &blend_state,
+            p_dynamic_state: &dynamic_state,
+            layout: pipeline_layout,
+            render_pass: swapchain.render_pass,
+            subpass: 0,
+            base_pipeline_index: -1,
+            base_pipeline_handle: vk::Pipeline::null(),
+            ..Default::default()
+        };
+
+        let result = unsafe {
+            device
+                .logical_device
+                .create_graphics_pipelines(
+                    vk::PipelineCache::null(),
+                    &[pipeline_create_info],
+                    None,
+                )
+                .map_err(|(_, err)| err)
+                .context("unable to create the shader canvas pipeline")
+        };
+        let pipeline = match result {
+            Ok(pipelines) => pipelines[0],
+            Err(error) => {
+                unsafe {
+                    device.forget_vulkan_object(
+                        vk::ObjectType::PIPELINE_LAYOUT,
+                        &pipeline_layout,
+                    );
+                    device
+                        .logical_device
+                        .destroy_pipeline_layout(pipeline_layout, None);
+                    descriptor_sets::destroy_descriptor_set_layouts(
+                        &device,
+                        &descriptor_set_layouts,
+                    );
+                }
+                return Err(error);
+            }
+        };
+        device.name_vulkan_object(
+            "Shader Canvas Pipeline",
+            vk::ObjectType::PIPELINE,
+            &pipeline,
+        )?;
+
+        Ok(Self {
+            pipeline_layout,
+            pipeline,
+            descriptor_set_layouts,
+            device,
+        })
+    }
+
+    /// Borrow the raw vulkan pipeline handle.
+    pub fn raw_pipeline(&self) -> &vk::Pipeline {
+        &self.pipeline
+    }
+
+    /// Borrow the pipeline layout handle.
+    pub fn raw_pipeline_layout(&self) -> &vk::PipelineLayout {
+        &self.pipeline_layout
+    }
+}
+
+impl Drop for CanvasPipeline {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .forget_vulkan_object(vk::ObjectType::PIPELINE, &self.pipeline);
+            self.device
+                .logical_device
+                .destroy_pipeline(self.pipeline, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::PIPELINE_LAYOUT,
+                &self.pipeline_layout,
+            );
+            self.device
+                .logical_device
+                .destroy_pipeline_layout(self.pipeline_layout, None);
+            descriptor_sets::destroy_descriptor_set_layouts(
+                &self.device,
+                &self.descriptor_set_layouts,
+            );
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use super::{CanvasPipeline, CanvasPushConsts, ShaderCanvas, MAX_CHANNELS};
+
+use crate::graphics::{
+    assets::read_shader,
+    pipeline2d::pre_rotation,
+    texture_atlas::{GpuAtlas, TextureHandle},
+    vertex::Vertex2d,
+    vulkan::{
+        buffer::{Buffer, StaticBuffer},
+        ffi::any_as_u8_slice,
+        Device, Swapchain,
+    },
+};
+
+use anyhow::{bail, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Instant, SystemTime},
+};
+
+impl ShaderCanvas {
+    /// Create a canvas which draws with a fragment shader's SPIR-V code.
+    pub fn new(
+        device: Arc<Device>,
+        swapchain: &Swapchain,
+        fragment_spirv: &[u32],
+    ) -> Result<Self> {
+        let pipeline =
+            CanvasPipeline::new(device.clone(), swapchain, fragment_spirv)?;
+        let quad = StaticBuffer::from_data(
+            device.clone(),
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+            &fullscreen_quad(),
+        )?;
+        Ok(Self {
+            pipeline,
+            fragment_spirv: fragment_spirv.to_vec(),
+            source_path: None,
+            source_modified: None,
+            quad,
+            mouse: [0.0; 4],
+            channels: [TextureHandle::default(); MAX_CHANNELS],
+            start: Instant::now(),
+            last_draw: None,
+            frame: 0,
+            device,
+        })
+    }
+
+    /// Create a canvas which draws with a SPIR-V fragment shader file.
+    ///
+    /// The file is watched, see `source_changed`.
+    pub fn from_file(
+        device: Arc<Device>,
+        swapchain: &Swapchain,
+        path: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let modified = modified_time(path);
+        let spirv = read_shader(path)?;
+        let mut canvas = Self::new(device, swapchain, &spirv)?;
+        canvas.source_path = Some(path.to_path_buf());
+        canvas.source_modified = modified;
+        Ok(canvas)
+    }
+
+    /// The file the fragment shader is read from, if any.
+    pub fn source_path(&self) -> Option<&PathBuf> {
+        self.source_path.as_ref()
+    }
+
+    /// Set the mouse uniform.
+    ///
+    /// Like Shadertoy, the first two components are usually the current
+    /// position in pixels and the last two are where the button was pressed.
+    pub fn set_mouse(&mut self, mouse: [f32; 4]) {
+        self.mouse = mouse;
+    }
+
+    /// Bind a texture to one of the canvas's input channels.
+    ///
+    /// Fails when the index is outside the supported channels.
+    pub fn set_channel(
+        &mut self,
+        index: usize,
+        texture_handle: TextureHandle,
+    ) -> Result<()> {
+        if index >= MAX_CHANNELS {
+            bail!(
+                "channel {} is out of range, only {} channels are supported",
+                index,
+                MAX_CHANNELS
+            );
+        }
+        self.channels[index] = texture_handle;
+        Ok(())
+    }
+
+    /// The textures bound to each input channel.
+    pub fn channels(&self) -> &[TextureHandle; MAX_CHANNELS] {
+        &self.channels
+    }
+
+    /// The atlas indices for each input channel. Channels whose textures
+    /// were taken from the atlas sample the default texture.
+    fn channel_indices(&self, texture_atlas: &GpuAtlas) -> [u32; MAX_CHANNELS] {
+        let mut indices = [0; MAX_CHANNELS];
+        for (index, channel) in self.channels.iter().enumerate() {
+            indices[index] = texture_atlas.shader_texture_index(*channel);
+        }
+        indices
+    }
+
+    /// Restart time and the frame counter from zero.
+    pub fn reset_time(&mut self) {
+        self.start = Instant::now();
+        self.last_draw = None;
+        self.frame = 0;
+    }
+
+    /// True when the fragment shader's file was modified since it was last
+    /// read.
+    pub fn source_changed(&self) -> bool {
+        match &self.source_path {
+            Some(path) => modified_time(path) != self.source_modified,
+            None => false,
+        }
+    }
+
+    /// Read the fragment shader file again and rebuild the pipeline.
+    ///
+    /// Shaders which fail to load or compile are logged and the previous
+    /// pipeline is kept, so a typo doesn't end the application. The file
+    /// isn't read again until it changes.
+    ///
+    /// # Safety
+    ///
+    /// - the previous pipeline is destroyed, the caller must make sure no
+    ///   frame is still using it
+    pub unsafe fn reload(&mut self, swapchain: &Swapchain) {
+        let path = match &self.source_path {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        self.source_modified = modified_time(&path);
+        let result = read_shader(&path).and_then(|spirv| {
+            let pipeline =
+                CanvasPipeline::new(self.device.clone(), swapchain, &spirv)?;
+            Ok((spirv, pipeline))
+        });
+        match result {
+            Ok((spirv, pipeline)) => {
+                self.fragment_spirv = spirv;
+                self.pipeline = pipeline;
+                log::info!("reloaded shader canvas {:?}", path);
+            }
+            Err(error) => {
+                log::error!(
+                    "unable to reload shader canvas {:?}: {:?}",
+                    path,
+                    error
+                );
+            }
+        }
+    }
+
+    /// Rebuild the pipeline for a new swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - the previous pipeline is destroyed, the caller must make sure no
+    ///   frame is still using it
+    pub unsafe fn rebuild(&mut self, swapchain: &Swapchain) -> Result<()> {
+        self.pipeline = CanvasPipeline::new(
+            self.device.clone(),
+            swapchain,
+            &self.fragment_spirv,
+        )?;
+        Ok(())
+    }
+
+    /// Record commands which draw the canvas over the whole framebuffer.
+    ///
+    /// Time and the frame counter advance every time the canvas is drawn.
+    /// The resolution is the size of the layer pass's render area, which is
+    /// larger than the swapchain while a render scale is set, and the mouse
+    /// is scaled to match.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be inside the swapchain's render pass
+    /// - the descriptor sets must be compatible with the 2d pipeline's
+    ///   layout, in set order
+    pub(crate) unsafe fn record_draw(
+        &mut self,
+        command_buffer: vk::CommandBuffer,
+        descriptor_sets: &[vk::DescriptorSet],
+        texture_atlas: &GpuAtlas,
+        swapchain: &Swapchain,
+        extent: vk::Extent2D,
+    ) {
+        let now = Instant::now();
+        let time_delta = match self.last_draw {
+            Some(last_draw) => (now - last_draw).as_secs_f32(),
+            None => 0.0,
+        };
+        self.last_draw = Some(now);
+
+        let consts = CanvasPushConsts::new(
+            pre_rotation(swapchain.pre_transform),
+            [extent.width as f32, extent.height as f32],
+            (now - self.start).as_secs_f32(),
+            time_delta,
+            self.frame,
+            scale_mouse(self.mouse, swapchain.extent, extent),
+            self.channel_indices(texture_atlas),
+        );
+        self.frame = self.frame.wrapping_add(1);
+
+        let logical_device = &self.device.logical_device;
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            *self.pipeline.raw_pipeline(),
+        );
+        logical_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            *self.pipeline.raw_pipeline_layout(),
+            0,
+            descriptor_sets,
+            &[],
+        );
+        logical_device.cmd_bind_vertex_buffers(
+            command_buffer,
+            0,
+            &[self.quad.raw()],
+            &[0],
+        );
+        logical_device.cmd_push_constants(
+            command_buffer,
+            *self.pipeline.raw_pipeline_layout(),
+            vk::ShaderStageFlags::FRAGMENT | vk::ShaderStageFlags::VERTEX,
+            0,
+            any_as_u8_slice(&consts),
+        );
+        logical_device.cmd_draw(command_buffer, 6, 1, 0, 0);
+    }
+}
+
+/// Two triangles which cover clip space, with uv (0, 0) in the top left.
+fn fullscreen_quad() -> [Vertex2d; 6] {
+    let corner = |x: f32, y: f32| Vertex2d {
+        pos: [x, y],
+        uv: [(x + 1.0) * 0.5, (y + 1.0) * 0.5],
+        ..Default::default()
+    };
+    [
+        corner(-1.0, -1.0),
+        corner(1.0, -1.0),
+        corner(1.0, 1.0),
+        corner(-1.0, -1.0),
+        corner(1.0, 1.0),
+        corner(-1.0, 1.0),
+    ]
+}
+
+/// The file's modification time, or None when it can't be read.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Move mouse coordinates from swapchain pixels into render area pixels.
+fn scale_mouse(
+    mouse: [f32; 4],
+    swapchain_extent: vk::Extent2D,
+    extent: vk::Extent2D,
+) -> [f32; 4] {
+    let x = extent.width as f32 / swapchain_extent.width.max(1) as f32;
+    let y = extent.height as f32 / swapchain_extent.height.max(1) as f32;
+    [mouse[0] * x, mouse[1] * y, mouse[2] * x, mouse[3] * y]
+}
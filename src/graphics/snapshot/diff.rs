@@ -0,0 +1,229 @@
+use super::{DescriptorWrite, FrameSnapshot, SnapshotDiff};
+
+use std::fmt;
+
+impl FrameSnapshot {
+    /// Describe everything which changed between this snapshot and another.
+    pub fn diff(&self, other: &FrameSnapshot) -> SnapshotDiff {
+        let mut changes = vec![];
+
+        if self.pipeline_binds != other.pipeline_binds {
+            changes.push(format!(
+                "pipeline binds: {:?} -> {:?}",
+                self.pipeline_binds, other.pipeline_binds
+            ));
+        }
+
+        diff_descriptor_writes(
+            &self.descriptor_writes,
+            &other.descriptor_writes,
+            &mut changes,
+        );
+
+        for (name, size) in &self.buffer_sizes {
+            match other.buffer_sizes.get(name) {
+                Some(other_size) if other_size != size => changes.push(
+                    format!("{}: {} -> {} bytes", name, size, other_size),
+                ),
+                None => changes.push(format!("- buffer {}", name)),
+                _ => (),
+            }
+        }
+        for (name, size) in &other.buffer_sizes {
+            if !self.buffer_sizes.contains_key(name) {
+                changes.push(format!("+ buffer {}: {} bytes", name, size));
+            }
+        }
+
+        if self.draw_calls != other.draw_calls {
+            changes.push(format!(
+                "draw calls: {} -> {}",
+                self.draw_calls, other.draw_calls
+            ));
+        }
+        if self.vertex_count != other.vertex_count {
+            changes.push(format!(
+                "vertices: {} -> {}",
+                self.vertex_count, other.vertex_count
+            ));
+        }
+
+        SnapshotDiff {
+            from: self.frame_number,
+            to: other.frame_number,
+            changes,
+        }
+    }
+}
+
+/// Describe descriptor writes which only happened in one of the frames, and
+/// resources which differ between writes to the same part of a binding.
+fn diff_descriptor_writes(
+    from: &[DescriptorWrite],
+    to: &[DescriptorWrite],
+    changes: &mut Vec<String>,
+) {
+    let same_target = |a: &DescriptorWrite, b: &DescriptorWrite| {
+        a.binding == b.binding && a.first_element == b.first_element
+    };
+    for write in from {
+        match to.iter().find(|other| same_target(write, other)) {
+            Some(other) => {
+                let elements = write.resources.len().max(other.resources.len());
+                for element in 0..elements {
+                    let before = write.resources.get(element);
+                    let after = other.resources.get(element);
+                    if before != after {
+                        changes.push(format!(
+                            "descriptor binding {}[{}]: {} -> {}",
+                            write.binding,
+                            write.first_element as usize + element,
+                            before.map(String::as_str).unwrap_or("<none>"),
+                            after.map(String::as_str).unwrap_or("<none>"),
+                        ));
+                    }
+                }
+            }
+            None => changes.push(format!("- {}", describe_write(write))),
+        }
+    }
+    for write in to {
+        if !from.iter().any(|other| same_target(write, other)) {
+            changes.push(format!("+ {}", describe_write(write)));
+        }
+    }
+}
+
+fn describe_write(write: &DescriptorWrite) -> String {
+    if write.first_element == 0 {
+        format!(
+            "descriptor write to binding {} ({} descriptors)",
+            write.binding,
+            write.resources.len()
+        )
+    } else {
+        format!(
+            "descriptor write to binding {} from element {} ({} descriptors)",
+            write.binding,
+            write.first_element,
+            write.resources.len()
+        )
+    }
+}
+
+impl SnapshotDiff {
+    /// True when both frames used identical gpu state.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "frame {} -> frame {}", self.from, self.to)?;
+        if self.is_empty() {
+            writeln!(f, "  no changes")?;
+        }
+        for change in &self.changes {
+            writeln!(f, "  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot(frame_number: u64) -> FrameSnapshot {
+        FrameSnapshot {
+            frame_number,
+            pipeline_binds: vec!["Application Graphics Pipeline".to_owned()],
+            draw_calls: 2,
+            vertex_count: 12,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_of_identical_frames_should_be_empty() {
+        let diff = snapshot(1).diff(&snapshot(2));
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "frame 1 -> frame 2\n  no changes\n");
+    }
+
+    #[test]
+    fn diff_should_report_new_descriptor_writes_and_buffer_growth() {
+        let mut before = snapshot(1);
+        before.buffer_sizes.insert("vertex buffer".to_owned(), 1024);
+        let mut after = snapshot(2);
+        after.buffer_sizes.insert("vertex buffer".to_owned(), 4096);
+        after.descriptor_writes.push(DescriptorWrite {
+            binding: 0,
+            first_element: 0,
+            resources: vec!["a".to_owned(), "b".to_owned()],
+        });
+
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff.changes,
+            vec![
+                "+ descriptor write to binding 0 (2 descriptors)".to_owned(),
+                "vertex buffer: 1024 -> 4096 bytes".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_should_report_changed_descriptor_resources() {
+        let mut before = snapshot(1);
+        before.descriptor_writes.push(DescriptorWrite {
+            binding: 0,
+            first_element: 0,
+            resources: vec!["a".to_owned()],
+        });
+        let mut after = snapshot(2);
+        after.descriptor_writes.push(DescriptorWrite {
+            binding: 0,
+            first_element: 0,
+            resources: vec!["a".to_owned(), "b".to_owned()],
+        });
+
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff.changes,
+            vec!["descriptor binding 0[1]: <none> -> b".to_owned()]
+        );
+    }
+
+    #[test]
+    fn diff_should_report_partial_writes_by_element() {
+        let mut before = snapshot(1);
+        before.descriptor_writes.push(DescriptorWrite {
+            binding: 0,
+            first_element: 5,
+            resources: vec!["a".to_owned()],
+        });
+        let mut after = snapshot(2);
+        after.descriptor_writes.push(DescriptorWrite {
+            binding: 0,
+            first_element: 5,
+            resources: vec!["b".to_owned()],
+        });
+        after.descriptor_writes.push(DescriptorWrite {
+            binding: 0,
+            first_element: 9,
+            resources: vec!["c".to_owned()],
+        });
+
+        let diff = before.diff(&after);
+        assert_eq!(
+            diff.changes,
+            vec![
+                "descriptor binding 0[5]: a -> b".to_owned(),
+                "+ descriptor write to binding 0 from element 9 (1 descriptors)"
+                    .to_owned(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use super::{FrameSnapshot, SnapshotDiff, SnapshotHistory};
+
+use std::collections::VecDeque;
+
+impl SnapshotHistory {
+    /// Create an empty history which keeps at most `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            snapshots: VecDeque::with_capacity(capacity),
+            current: None,
+        }
+    }
+
+    /// Start recording a snapshot for a new frame.
+    ///
+    /// Any snapshot which was started but never finished is discarded.
+    pub fn begin_frame(&mut self, frame_number: u64) {
+        self.current = Some(FrameSnapshot {
+            frame_number,
+            ..Default::default()
+        });
+    }
+
+    /// The snapshot for the frame currently being rendered, if any.
+    pub fn current_mut(&mut self) -> Option<&mut FrameSnapshot> {
+        self.current.as_mut()
+    }
+
+    /// Finish the current snapshot and add it to the history, evicting the
+    /// oldest snapshot when the history is full.
+    pub fn end_frame(&mut self) {
+        if let Some(snapshot) = self.current.take() {
+            if self.snapshots.len() == self.capacity {
+                self.snapshots.pop_front();
+            }
+            self.snapshots.push_back(snapshot);
+        }
+    }
+
+    /// Get the snapshot for a frame if it is still in the history.
+    pub fn get(&self, frame_number: u64) -> Option<&FrameSnapshot> {
+        self.snapshots
+            .iter()
+            .find(|snapshot| snapshot.frame_number == frame_number)
+    }
+
+    /// The most recently finished snapshot.
+    pub fn latest(&self) -> Option<&FrameSnapshot> {
+        self.snapshots.back()
+    }
+
+    /// Diff two frames in the history.
+    ///
+    /// Returns None if either frame is no longer in the history.
+    pub fn diff(&self, from: u64, to: u64) -> Option<SnapshotDiff> {
+        Some(self.get(from)?.diff(self.get(to)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(history: &mut SnapshotHistory, frame_number: u64, draws: u32) {
+        history.begin_frame(frame_number);
+        history.current_mut().unwrap().draw_calls = draws;
+        history.end_frame();
+    }
+
+    #[test]
+    fn end_frame_should_evict_the_oldest_snapshot() {
+        let mut history = SnapshotHistory::new(2);
+        record(&mut history, 0, 1);
+        record(&mut history, 1, 1);
+        record(&mut history, 2, 1);
+
+        assert!(history.get(0).is_none());
+        assert!(history.get(1).is_some());
+        assert_eq!(history.latest().unwrap().frame_number, 2);
+    }
+
+    #[test]
+    fn diff_should_compare_frames_in_the_history() {
+        let mut history = SnapshotHistory::new(4);
+        record(&mut history, 0, 1);
+        record(&mut history, 1, 3);
+
+        let diff = history.diff(0, 1).unwrap();
+        assert_eq!(diff.changes, vec!["draw calls: 1 -> 3".to_owned()]);
+        assert!(history.diff(0, 7).is_none());
+    }
+}
@@ -0,0 +1,62 @@
+//! Record the gpu state used by each frame so frames can be compared.
+//!
+//! Snapshots answer questions like "why did frame 1024 hitch?" by showing
+//! which descriptors were rewritten, which pipelines were bound, and which
+//! buffers changed size compared to an earlier frame. Resources are labeled
+//! with the names given by `Device::name_vulkan_object`.
+
+mod diff;
+mod history;
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// A single write to a descriptor set binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorWrite {
+    pub binding: u32,
+
+    /// The first array element which was written.
+    pub first_element: u32,
+
+    /// One label per array element which was written, starting with
+    /// `first_element`.
+    pub resources: Vec<String>,
+}
+
+/// The gpu state used to render a single frame.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FrameSnapshot {
+    pub frame_number: u64,
+
+    /// The name of each pipeline bound, in the order it was bound.
+    pub pipeline_binds: Vec<String>,
+
+    /// Descriptor writes performed while preparing the frame.
+    pub descriptor_writes: Vec<DescriptorWrite>,
+
+    /// The size, in bytes, of each buffer used by the frame.
+    pub buffer_sizes: BTreeMap<String, u64>,
+
+    pub draw_calls: u32,
+    pub vertex_count: u32,
+}
+
+/// The differences between two frame snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotDiff {
+    pub from: u64,
+    pub to: u64,
+
+    /// A human readable description of each change.
+    pub changes: Vec<String>,
+}
+
+/// A bounded history of the most recent frame snapshots.
+#[derive(Debug, Clone)]
+pub struct SnapshotHistory {
+    capacity: usize,
+    snapshots: VecDeque<FrameSnapshot>,
+
+    /// The snapshot for the frame currently being rendered.
+    current: Option<FrameSnapshot>,
+}
@@ -0,0 +1,43 @@
+//! Storage buffers hold arrays of data which shaders can read, like
+//! per-instance transforms, which would be too large for push constants.
+//!
+//! # Big Idea
+//!
+//! The application writes data into a storage buffer on the cpu at any time.
+//! Each frame owns its own gpu copy of every storage buffer, which is only
+//! rewritten when the frame is acquired after the data has changed. So, like
+//! the texture atlas, writing a storage buffer never needs to wait for the gpu.
+//!
+//! Shaders access the buffers through an array of storage buffers at
+//! `STORAGE_BUFFER_BINDING` in descriptor set `STORAGE_BUFFER_SET`, and
+//! select a buffer with the handle's index.
+
+mod storage_buffer_handle;
+mod storage_buffers;
+
+use crate::graphics::frame::FRAME_DESCRIPTOR_SET;
+
+/// The maximum number of storage buffers which can be bound at once.
+///
+/// Vulkan only guarantees four storage buffers per shader stage.
+pub const MAX_STORAGE_BUFFERS: usize = 4;
+
+/// The descriptor binding used for the array of storage buffers.
+pub const STORAGE_BUFFER_BINDING: u32 = 1;
+
+/// The descriptor set which holds the storage buffers, each frame's own set.
+pub const STORAGE_BUFFER_SET: u32 = FRAME_DESCRIPTOR_SET;
+
+/// A unique identifier for a storage buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StorageBufferHandle(u32);
+
+/// The cpu-side contents of every storage buffer.
+#[derive(Debug, Clone)]
+pub struct StorageBuffers {
+    /// The bytes in each storage buffer, indexed by handle.
+    buffers: Vec<Option<Vec<u8>>>,
+
+    /// Incremented each time any buffer is created, written, or destroyed.
+    revision: u64,
+}
@@ -0,0 +1,13 @@
+use super::StorageBufferHandle;
+
+impl StorageBufferHandle {
+    pub(super) fn new(index: u32) -> Self {
+        StorageBufferHandle(index)
+    }
+
+    /// Return the index of the buffer in the shader's storage buffer array.
+    pub fn index(&self) -> u32 {
+        let StorageBufferHandle(index) = self;
+        *index
+    }
+}
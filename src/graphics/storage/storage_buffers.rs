@@ -0,0 +1,156 @@
+use super::{StorageBufferHandle, StorageBuffers, MAX_STORAGE_BUFFERS};
+
+use anyhow::{bail, Result};
+
+impl StorageBuffers {
+    /// Create a collection with no storage buffers.
+    pub fn new() -> Self {
+        Self {
+            buffers: vec![None; MAX_STORAGE_BUFFERS],
+            revision: 0,
+        }
+    }
+
+    /// Create a new, empty, storage buffer.
+    ///
+    /// Fails if every storage buffer slot is already in use.
+    pub fn create(&mut self) -> Result<StorageBufferHandle> {
+        let index = match self.buffers.iter().position(Option::is_none) {
+            Some(index) => index,
+            None => bail!(
+                "no more than {} storage buffers can exist at once",
+                MAX_STORAGE_BUFFERS
+            ),
+        };
+        self.buffers[index] = Some(vec![]);
+        self.revision += 1;
+        Ok(StorageBufferHandle::new(index as u32))
+    }
+
+    /// Replace the contents of a storage buffer.
+    ///
+    /// The data is copied as raw bytes, so `T` should be `#[repr(C)]` and
+    /// match the layout declared by the shader.
+    pub fn write<T>(
+        &mut self,
+        handle: StorageBufferHandle,
+        data: &[T],
+    ) -> Result<()>
+    where
+        T: Sized + Copy,
+    {
+        let buffer = self.buffer_mut(handle)?;
+        // SAFE: T is Copy, so the bytes can be read without running any code
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                data.as_ptr() as *const u8,
+                std::mem::size_of_val(data),
+            )
+        };
+        buffer.clear();
+        buffer.extend_from_slice(bytes);
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// Destroy a storage buffer, freeing its slot for another buffer.
+    pub fn destroy(&mut self, handle: StorageBufferHandle) -> Result<()> {
+        self.buffer_mut(handle)?;
+        self.buffers[handle.index() as usize] = None;
+        self.revision += 1;
+        Ok(())
+    }
+
+    /// The bytes currently held by a storage buffer.
+    pub fn bytes(&self, handle: StorageBufferHandle) -> Option<&[u8]> {
+        self.buffers
+            .get(handle.index() as usize)?
+            .as_ref()
+            .map(Vec::as_slice)
+    }
+
+    /// Every storage buffer with its contents, in handle order.
+    pub fn iter(&self) -> impl Iterator<Item = (StorageBufferHandle, &[u8])> {
+        self.buffers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, buffer)| {
+                buffer.as_ref().map(|bytes| {
+                    (StorageBufferHandle::new(index as u32), bytes.as_slice())
+                })
+            })
+    }
+
+    /// Changes each time any storage buffer is created, written, or
+    /// destroyed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn buffer_mut(
+        &mut self,
+        handle: StorageBufferHandle,
+    ) -> Result<&mut Vec<u8>> {
+        match self.buffers.get_mut(handle.index() as usize) {
+            Some(Some(buffer)) => Ok(buffer),
+            _ => bail!("{:?} does not refer to a storage buffer", handle),
+        }
+    }
+}
+
+impl Default for StorageBuffers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write_should_replace_the_buffer_bytes() -> Result<()> {
+        let mut storage = StorageBuffers::new();
+        let handle = storage.create()?;
+        assert_eq!(storage.bytes(handle), Some(&[][..]));
+
+        storage.write(handle, &[1u32, 2u32])?;
+        storage.write(handle, &[0x04030201u32])?;
+
+        assert_eq!(storage.bytes(handle), Some(&[1u8, 2, 3, 4][..]));
+        Ok(())
+    }
+
+    #[test]
+    fn create_should_reuse_destroyed_slots() -> Result<()> {
+        let mut storage = StorageBuffers::new();
+        let handles = (0..MAX_STORAGE_BUFFERS)
+            .map(|_| storage.create())
+            .collect::<Result<Vec<_>>>()?;
+        assert!(storage.create().is_err());
+
+        storage.destroy(handles[1])?;
+
+        assert!(storage.bytes(handles[1]).is_none());
+        assert!(storage.write(handles[1], &[1u8]).is_err());
+        assert_eq!(storage.create()?, handles[1]);
+        Ok(())
+    }
+
+    #[test]
+    fn every_change_should_update_the_revision() -> Result<()> {
+        let mut storage = StorageBuffers::new();
+        let mut revisions = vec![storage.revision()];
+
+        let handle = storage.create()?;
+        revisions.push(storage.revision());
+        storage.write(handle, &[1.0f32])?;
+        revisions.push(storage.revision());
+        storage.destroy(handle)?;
+        revisions.push(storage.revision());
+
+        revisions.dedup();
+        assert_eq!(revisions.len(), 4);
+        Ok(())
+    }
+}
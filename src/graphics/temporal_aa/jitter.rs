@@ -0,0 +1,89 @@
+use super::{JITTER_SEQUENCE_LENGTH, MAX_ACCUMULATED_SAMPLES};
+
+use ash::vk;
+use nalgebra as na;
+
+/// The Halton low-discrepancy sequence, in the range [0, 1).
+///
+/// Index 0 is always 0, so sequences usually start at 1.
+pub fn halton(mut index: u32, base: u32) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// The sub-pixel offset for a frame, in pixels between -0.5 and 0.5.
+pub fn jitter_offset(frame: u32) -> [f32; 2] {
+    let index = frame % JITTER_SEQUENCE_LENGTH + 1;
+    [halton(index, 2) - 0.5, halton(index, 3) - 0.5]
+}
+
+/// A clip space translation which moves everything by an offset in pixels.
+pub fn jitter_matrix(
+    offset: [f32; 2],
+    extent: vk::Extent2D,
+) -> na::Matrix4<f32> {
+    if extent.width == 0 || extent.height == 0 {
+        return na::Matrix4::identity();
+    }
+    na::Matrix4::new_translation(&na::Vector3::new(
+        2.0 * offset[0] / extent.width as f32,
+        2.0 * offset[1] / extent.height as f32,
+        0.0,
+    ))
+}
+
+/// The weight given to the newest frame when the history already holds
+/// some number of frames.
+pub fn accumulation_blend(samples: u32) -> f32 {
+    1.0 / (samples.min(MAX_ACCUMULATED_SAMPLES - 1) + 1) as f32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn halton_should_match_the_known_sequence() {
+        let base2: Vec<f32> = (1..5).map(|i| halton(i, 2)).collect();
+        assert_eq!(base2, vec![0.5, 0.25, 0.75, 0.125]);
+        assert!((halton(2, 3) - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn jitter_offset_should_stay_within_a_pixel_and_repeat() {
+        for frame in 0..JITTER_SEQUENCE_LENGTH {
+            let [x, y] = jitter_offset(frame);
+            assert!((-0.5..0.5).contains(&x) && (-0.5..0.5).contains(&y));
+        }
+        assert_eq!(jitter_offset(3), jitter_offset(3 + JITTER_SEQUENCE_LENGTH));
+    }
+
+    #[test]
+    fn jitter_matrix_should_move_by_pixels_in_clip_space() {
+        let extent = vk::Extent2D {
+            width: 200,
+            height: 100,
+        };
+        let moved = jitter_matrix([0.5, -0.25], extent)
+            .transform_point(&na::Point3::origin());
+        assert!((moved.x - 0.005).abs() < 1e-6);
+        assert!((moved.y + 0.005).abs() < 1e-6);
+    }
+
+    #[test]
+    fn accumulation_blend_should_average_then_settle() {
+        assert_eq!(accumulation_blend(0), 1.0);
+        assert_eq!(accumulation_blend(1), 0.5);
+        assert_eq!(accumulation_blend(3), 0.25);
+        assert_eq!(
+            accumulation_blend(1000),
+            1.0 / MAX_ACCUMULATED_SAMPLES as f32
+        );
+    }
+}
@@ -0,0 +1,83 @@
+//! An optional temporal anti-aliasing pass, a lighter alternative to MSAA
+//! for art which mostly holds still.
+//!
+//! # Big Idea
+//!
+//! Every frame the layers are drawn with their projections nudged by a
+//! different sub-pixel offset, taken from a Halton sequence. Once the layers
+//! have been drawn, the swapchain image is copied into an offscreen current
+//! image and a fullscreen triangle blends it into the running average kept
+//! in a history image. The blended frame is written back into the swapchain
+//! image, then copied into the history for the next frame.
+//!
+//! While nothing moves, the average converges on a supersampled image. The
+//! newest frame always gets at least `1 / MAX_ACCUMULATED_SAMPLES` of the
+//! weight, so moving geometry leaves a short trail rather than smearing
+//! forever. Call `Graphics::reset_temporal_aa` after a cut to start the
+//! average over.
+//!
+//! The pass runs before feedback and color grading, so both see the
+//! anti-aliased frame.
+
+mod jitter;
+mod pipeline;
+mod temporal_aa_pass;
+
+pub use self::jitter::{
+    accumulation_blend, halton, jitter_matrix, jitter_offset,
+};
+
+use crate::graphics::vulkan::{texture::TextureImage, Device};
+
+use ash::vk;
+use std::sync::Arc;
+
+/// The number of jitter offsets before the sequence repeats.
+pub const JITTER_SEQUENCE_LENGTH: u32 = 16;
+
+/// The number of frames in the running average before it starts to forget
+/// the oldest ones.
+pub const MAX_ACCUMULATED_SAMPLES: u32 = 16;
+
+/// The push constants used by the accumulation pipeline.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AccumulationPushConsts {
+    /// The weight given to the newest frame.
+    pub blend: f32,
+}
+
+/// The gpu resources used to accumulate jittered frames.
+pub struct TemporalAaPass {
+    pipeline: AccumulationPipeline,
+
+    /// A copy of the jittered frame.
+    current: TextureImage,
+
+    /// The running average of earlier frames.
+    history: TextureImage,
+
+    /// The size of the images and swapchain.
+    extent: vk::Extent2D,
+
+    /// The number of frames in the history, zero when it holds nothing.
+    samples: u32,
+
+    /// The position in the jitter sequence for the next frame.
+    jitter_index: u32,
+
+    sampler: vk::Sampler,
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set: vk::DescriptorSet,
+
+    device: Arc<Device>,
+}
+
+/// The render pass and pipeline which draw the blended frame.
+struct AccumulationPipeline {
+    render_pass: vk::RenderPass,
+    pipeline_layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    device: Arc<Device>,
+}
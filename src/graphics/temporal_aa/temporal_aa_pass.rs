@@ -0,0 +1,428 @@
+use super::{
+    accumulation_blend, jitter_matrix, jitter_offset, AccumulationPipeline,
+    AccumulationPushConsts, TemporalAaPass, JITTER_SEQUENCE_LENGTH,
+};
+
+use crate::graphics::vulkan::{
+    ffi::any_as_u8_slice, texture::TextureImage, Device, Swapchain,
+};
+
+use anyhow::{bail, Result};
+use ash::{version::DeviceV1_0, vk};
+use nalgebra as na;
+use std::sync::Arc;
+
+impl TemporalAaPass {
+    /// Create the pass with an empty history.
+    ///
+    /// Fails if the swapchain images can't be copied.
+    pub fn new(device: Arc<Device>, swapchain: &Swapchain) -> Result<Self> {
+        if !swapchain
+            .image_usage
+            .contains(vk::ImageUsageFlags::TRANSFER_SRC)
+        {
+            bail!(
+                "temporal anti-aliasing requires a swapchain which supports TRANSFER_SRC"
+            );
+        }
+        let pipeline = AccumulationPipeline::new(device.clone(), swapchain)?;
+        let current = create_target(&device, swapchain, "Current")?;
+        let history = create_target(&device, swapchain, "History")?;
+
+        let sampler = unsafe {
+            device.logical_device.create_sampler(
+                &vk::SamplerCreateInfo {
+                    mag_filter: vk::Filter::NEAREST,
+                    min_filter: vk::Filter::NEAREST,
+                    mipmap_mode: vk::SamplerMipmapMode::NEAREST,
+                    address_mode_u: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+                    address_mode_v: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+                    address_mode_w: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+                    ..Default::default()
+                },
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            "Temporal AA Sampler",
+            vk::ObjectType::SAMPLER,
+            &sampler,
+        )?;
+
+        let pool_sizes = [vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            descriptor_count: 2,
+        }];
+        let descriptor_pool = unsafe {
+            device.logical_device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo {
+                    p_pool_sizes: pool_sizes.as_ptr(),
+                    pool_size_count: pool_sizes.len() as u32,
+                    max_sets: 1,
+                    ..Default::default()
+                },
+                None,
+            )?
+        };
+        device.name_vulkan_object(
+            "Temporal AA Descriptor Pool",
+            vk::ObjectType::DESCRIPTOR_POOL,
+            &descriptor_pool,
+        )?;
+
+        let layouts = [pipeline.descriptor_set_layout];
+        let descriptor_set = unsafe {
+            device.logical_device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo {
+                    descriptor_pool,
+                    p_set_layouts: layouts.as_ptr(),
+                    descriptor_set_count: layouts.len() as u32,
+                    ..Default::default()
+                },
+            )?[0]
+        };
+
+        let pass = Self {
+            pipeline,
+            current,
+            history,
+            extent: swapchain.extent,
+            samples: 0,
+            jitter_index: 0,
+            sampler,
+            descriptor_pool,
+            descriptor_set,
+            device,
+        };
+        pass.write_descriptors();
+        Ok(pass)
+    }
+
+    /// The clip space translation for the frame being drawn.
+    pub fn jitter(&self) -> na::Matrix4<f32> {
+        jitter_matrix(jitter_offset(self.jitter_index), self.extent)
+    }
+
+    /// The number of frames in the running average.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Forget the history, so the next frame starts a new average.
+    pub fn reset(&mut self) {
+        self.samples = 0;
+    }
+
+    /// Move on to the next jitter offset once a frame has been recorded.
+    pub fn advance(&mut self) {
+        self.samples = self.samples.saturating_add(1);
+        self.jitter_index = (self.jitter_index + 1) % JITTER_SEQUENCE_LENGTH;
+    }
+
+    /// Rebuild the pipeline and images to match a new swapchain. The
+    /// history starts over.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the pass is still
+    ///   rendering
+    pub unsafe fn rebuild(&mut self, swapchain: &Swapchain) -> Result<()> {
+        self.pipeline =
+            AccumulationPipeline::new(self.device.clone(), swapchain)?;
+        self.current = create_target(&self.device, swapchain, "Current")?;
+        self.history = create_target(&self.device, swapchain, "History")?;
+        self.extent = swapchain.extent;
+        self.samples = 0;
+        self.write_descriptors();
+        Ok(())
+    }
+
+    /// How the image which holds a copy of the jittered frame is created.
+    /// The history is created the same way.
+    pub fn current_create_info(swapchain: &Swapchain) -> vk::ImageCreateInfo {
+        vk::ImageCreateInfo {
+            image_type: vk::ImageType::TYPE_2D,
+            extent: vk::Extent3D {
+                width: swapchain.extent.width,
+                height: swapchain.extent.height,
+                depth: 1,
+            },
+            mip_levels: 1,
+            array_layers: 1,
+            format: swapchain.format,
+            tiling: vk::ImageTiling::OPTIMAL,
+            initial_layout: vk::ImageLayout::UNDEFINED,
+            usage: vk::ImageUsageFlags::TRANSFER_DST
+                | vk::ImageUsageFlags::SAMPLED,
+            samples: vk::SampleCountFlags::TYPE_1,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        }
+    }
+
+    /// Use a different image for the copy of the jittered frame, usually
+    /// one which aliases the memory of other post effect targets.
+    ///
+    /// The copy is overwritten every frame, so the image doesn't need to be
+    /// initialized. The history is left alone because it has to survive from
+    /// one frame to the next.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure no frame which uses the pass is still
+    ///   rendering
+    /// - the image must have been created with `current_create_info`
+    pub unsafe fn replace_current(
+        &mut self,
+        current: TextureImage,
+    ) -> Result<()> {
+        let image = current.raw_image();
+        self.device.name_vulkan_object(
+            "Temporal AA Current - Image",
+            vk::ObjectType::IMAGE,
+            &image,
+        )?;
+        self.current = current;
+        self.write_descriptors();
+        Ok(())
+    }
+
+    /// The image which holds a copy of the jittered frame.
+    pub fn current_image(&self) -> vk::Image {
+        // SAFE: the handle is only used to record commands while the pass
+        // owns the image
+        unsafe { self.current.raw_image() }
+    }
+
+    /// The image which holds the running average.
+    pub fn history_image(&self) -> vk::Image {
+        // SAFE: the handle is only used to record commands while the pass
+        // owns the image
+        unsafe { self.history.raw_image() }
+    }
+
+    /// Record commands which copy the swapchain image into another image
+    /// the size of the frame.
+    ///
+    /// # Safety
+    ///
+    /// - the source must be in the `TRANSFER_SRC_OPTIMAL` layout and the
+    ///   destination in `TRANSFER_DST_OPTIMAL`, the frame graph inserts the
+    ///   transitions
+    pub unsafe fn record_copy(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        src_image: vk::Image,
+        dst_image: vk::Image,
+    ) {
+        let subresource = vk::ImageSubresourceLayers {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            mip_level: 0,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        let region = vk::ImageCopy {
+            src_subresource: subresource,
+            src_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            dst_subresource: subresource,
+            dst_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
+            extent: vk::Extent3D {
+                width: self.extent.width,
+                height: self.extent.height,
+                depth: 1,
+            },
+        };
+        self.device.logical_device.cmd_copy_image(
+            command_buffer,
+            src_image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            dst_image,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            &[region],
+        );
+    }
+
+    /// Record commands which blend the current frame into the history and
+    /// draw the result into the framebuffer.
+    ///
+    /// # Safety
+    ///
+    /// - both images must be ready to sample and the swapchain image must
+    ///   be a color attachment, the frame graph inserts the transitions
+    /// - the command buffer must be recording outside of a render pass
+    pub unsafe fn record_accumulate(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        framebuffer: vk::Framebuffer,
+    ) {
+        let logical_device = &self.device.logical_device;
+        logical_device.cmd_begin_render_pass(
+            command_buffer,
+            &vk::RenderPassBeginInfo {
+                render_pass: self.pipeline.render_pass,
+                framebuffer,
+                render_area: vk::Rect2D {
+                    offset: vk::Offset2D { x: 0, y: 0 },
+                    extent: self.extent,
+                },
+                ..Default::default()
+            },
+            vk::SubpassContents::INLINE,
+        );
+        logical_device.cmd_bind_pipeline(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline.pipeline,
+        );
+        logical_device.cmd_bind_descriptor_sets(
+            command_buffer,
+            vk::PipelineBindPoint::GRAPHICS,
+            self.pipeline.pipeline_layout,
+            0,
+            &[self.descriptor_set],
+            &[],
+        );
+        let consts = AccumulationPushConsts {
+            blend: accumulation_blend(self.samples),
+        };
+        logical_device.cmd_push_constants(
+            command_buffer,
+            self.pipeline.pipeline_layout,
+            vk::ShaderStageFlags::FRAGMENT,
+            0,
+            any_as_u8_slice(&consts),
+        );
+        logical_device.cmd_draw(command_buffer, 3, 1, 0, 0);
+        logical_device.cmd_end_render_pass(command_buffer);
+    }
+
+    /// Point the descriptor set at the current and history images.
+    fn write_descriptors(&self) {
+        // SAFE: the views are owned by the pass and the descriptor set is
+        // only written while no frame is using it
+        let (current_view, history_view) =
+            unsafe { (self.current.raw_view(), self.history.raw_view()) };
+        let image_info = |image_view: vk::ImageView| vk::DescriptorImageInfo {
+            sampler: self.sampler,
+            image_view,
+            image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        };
+        let current_info = [image_info(current_view)];
+        let history_info = [image_info(history_view)];
+        let write = |binding: u32, info: &[vk::DescriptorImageInfo]| {
+            vk::WriteDescriptorSet {
+                dst_set: self.descriptor_set,
+                dst_binding: binding,
+                dst_array_element: 0,
+                descriptor_type: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+                p_image_info: info.as_ptr(),
+                descriptor_count: info.len() as u32,
+                ..Default::default()
+            }
+        };
+        unsafe {
+            self.device.logical_device.update_descriptor_sets(
+                &[write(0, &current_info), write(1, &history_info)],
+                &[],
+            );
+        }
+    }
+}
+
+impl Drop for TemporalAaPass {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_POOL,
+                &self.descriptor_pool,
+            );
+            self.device
+                .logical_device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+            self.device
+                .forget_vulkan_object(vk::ObjectType::SAMPLER, &self.sampler);
+            self.device
+                .logical_device
+                .destroy_sampler(self.sampler, None);
+        }
+    }
+}
+
+/// Create an image the size of the swapchain, cleared to transparent black
+/// and ready to be sampled.
+///
+/// The history is sampled before anything is copied into it, so it can't be
+/// left undefined even though the first frame gives it no weight.
+fn create_target(
+    device: &Arc<Device>,
+    swapchain: &Swapchain,
+    name: &str,
+) -> Result<TextureImage> {
+    let target = TextureImage::new(
+        device.clone(),
+        TemporalAaPass::current_create_info(swapchain),
+        vk::MemoryPropertyFlags::DEVICE_LOCAL,
+    )?;
+    unsafe {
+        let image = target.raw_image();
+        device.name_vulkan_object(
+            format!("Temporal AA {} - Image", name),
+            vk::ObjectType::IMAGE,
+            &image,
+        )?;
+        let range = vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count: 1,
+        };
+        device.sync_graphics_commands(|command_buffer| {
+            device.logical_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    image,
+                    subresource_range: range,
+                    src_access_mask: vk::AccessFlags::empty(),
+                    dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    ..Default::default()
+                }],
+            );
+            device.logical_device.cmd_clear_color_image(
+                command_buffer,
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &vk::ClearColorValue {
+                    float32: [0.0, 0.0, 0.0, 0.0],
+                },
+                &[range],
+            );
+            device.logical_device.cmd_pipeline_barrier(
+                command_buffer,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+                    image,
+                    subresource_range: range,
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::SHADER_READ,
+                    ..Default::default()
+                }],
+            );
+            Ok(())
+        })?;
+    }
+    Ok(target)
+}
@@ -0,0 +1,246 @@
+use crate::graphics::{
+    pipeline2d::descriptor_sets,
+    texture_atlas::{
+        atlas_version::changed_sampler_range, AtlasVersion, SAMPLER_BINDING,
+        TEXTURE_BINDING,
+    },
+    vulkan::Device,
+};
+
+use anyhow::Result;
+use ash::{version::DeviceV1_0, vk};
+use std::{ops::Range, sync::Arc};
+
+/// The descriptors written by a single call to
+/// `GpuAtlas::write_descriptor_set`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AtlasDescriptorWrites {
+    /// The ranges of texture slots which were written.
+    pub textures: Vec<Range<usize>>,
+
+    /// The range of sampler slots which were written, if any.
+    pub samplers: Option<Range<usize>>,
+}
+
+impl AtlasDescriptorWrites {
+    /// True when nothing was written.
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty() && self.samplers.is_none()
+    }
+}
+
+/// The device-global descriptor set which holds every texture and sampler in
+/// a texture atlas.
+///
+/// A single set is shared by every frame, so a texture's descriptor is only
+/// written once no matter how many frames are in flight.
+pub struct AtlasDescriptor {
+    /// The atlas version whose textures were last written.
+    texture_version: AtlasVersion,
+
+    /// The number of the atlas's samplers which have been written, zero
+    /// until the sampler array is first written.
+    sampler_count: usize,
+
+    descriptor_pool: vk::DescriptorPool,
+    descriptor_set_layout: vk::DescriptorSetLayout,
+    descriptor_set: vk::DescriptorSet,
+    device: Arc<Device>,
+}
+
+impl AtlasDescriptor {
+    /// Create the global descriptor set for a texture atlas.
+    ///
+    /// The pool and set are created for update-after-bind when the device
+    /// supports it.
+    pub fn new(device: Arc<Device>) -> Result<Self> {
+        let (descriptor_set_layout, bindings) =
+            unsafe { descriptor_sets::create_atlas_set_layout(&device)? };
+        device.name_vulkan_object(
+            "Texture Atlas - DescriptorSetLayout",
+            vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+            &descriptor_set_layout,
+        )?;
+
+        // create a descriptor pool which exactly matches the number of bindings
+        let pool_sizes: Vec<vk::DescriptorPoolSize> = bindings
+            .iter()
+            .map(|binding| vk::DescriptorPoolSize {
+                ty: binding.descriptor_type,
+                descriptor_count: binding.descriptor_count,
+            })
+            .collect();
+        let flags = if device.supports_update_after_bind() {
+            vk::DescriptorPoolCreateFlags::UPDATE_AFTER_BIND
+        } else {
+            vk::DescriptorPoolCreateFlags::empty()
+        };
+        let pool_create_info = vk::DescriptorPoolCreateInfo {
+            p_pool_sizes: pool_sizes.as_ptr(),
+            pool_size_count: pool_sizes.len() as u32,
+            max_sets: 1,
+            flags,
+            ..Default::default()
+        };
+        let descriptor_pool = unsafe {
+            device
+                .logical_device
+                .create_descriptor_pool(&pool_create_info, None)?
+        };
+        device.name_vulkan_object(
+            "Texture Atlas - DescriptorPool",
+            vk::ObjectType::DESCRIPTOR_POOL,
+            &descriptor_pool,
+        )?;
+
+        let descriptor_set_layouts = [descriptor_set_layout];
+        let descriptor_set_allocate_info = vk::DescriptorSetAllocateInfo {
+            descriptor_pool,
+            p_set_layouts: descriptor_set_layouts.as_ptr(),
+            descriptor_set_count: descriptor_set_layouts.len() as u32,
+            ..Default::default()
+        };
+        let descriptor_set = unsafe {
+            device
+                .logical_device
+                .allocate_descriptor_sets(&descriptor_set_allocate_info)?[0]
+        };
+        device.name_vulkan_object(
+            "Texture Atlas - DescriptorSet",
+            vk::ObjectType::DESCRIPTOR_SET,
+            &descriptor_set,
+        )?;
+
+        Ok(Self {
+            texture_version: AtlasVersion::new_out_of_date(),
+            sampler_count: 0,
+            descriptor_pool,
+            descriptor_set_layout,
+            descriptor_set,
+            device,
+        })
+    }
+
+    /// The atlas version whose textures were last written.
+    pub fn texture_version(&self) -> AtlasVersion {
+        self.texture_version
+    }
+
+    /// The range of sampler slots which need to be written for an atlas with
+    /// `sampler_count` samplers.
+    ///
+    /// The first write covers the whole array so unused entries are valid.
+    /// After that only new samplers are written, samplers are never removed
+    /// so the rest of the array can still be in use.
+    pub fn sampler_range(
+        &self,
+        sampler_count: usize,
+        array_size: usize,
+    ) -> Option<Range<usize>> {
+        changed_sampler_range(self.sampler_count, sampler_count, array_size)
+    }
+
+    /// Write runs of sampled image descriptors and record the atlas version
+    /// they came from.
+    ///
+    /// # Safety
+    ///
+    /// - the written slots must not be in use by the gpu, and the set must
+    ///   not be in use at all unless the device supports update-after-bind
+    pub unsafe fn write_textures(
+        &mut self,
+        version: AtlasVersion,
+        ranges: &[Range<usize>],
+        image_infos: &[vk::DescriptorImageInfo],
+    ) {
+        for range in ranges {
+            self.write_image_descriptor(
+                TEXTURE_BINDING,
+                range.start as u32,
+                vk::DescriptorType::SAMPLED_IMAGE,
+                &image_infos[range.clone()],
+            );
+        }
+        self.texture_version = version;
+    }
+
+    /// Write a run of sampler descriptors and record how many of the atlas's
+    /// samplers have been written.
+    ///
+    /// # Safety
+    ///
+    /// - the written slots must not be in use by the gpu, and the set must
+    ///   not be in use at all unless the device supports update-after-bind
+    pub unsafe fn write_samplers(
+        &mut self,
+        range: Range<usize>,
+        sampler_count: usize,
+        sampler_infos: &[vk::DescriptorImageInfo],
+    ) {
+        self.write_image_descriptor(
+            SAMPLER_BINDING,
+            range.start as u32,
+            vk::DescriptorType::SAMPLER,
+            &sampler_infos[range],
+        );
+        self.sampler_count = sampler_count;
+    }
+
+    /// Write a run of image or sampler descriptors into an array.
+    unsafe fn write_image_descriptor(
+        &mut self,
+        binding: u32,
+        first_element: u32,
+        descriptor_type: vk::DescriptorType,
+        image_infos: &[vk::DescriptorImageInfo],
+    ) {
+        let descriptor_write = vk::WriteDescriptorSet {
+            dst_set: self.descriptor_set,
+            dst_binding: binding,
+            dst_array_element: first_element,
+            descriptor_type,
+            p_image_info: image_infos.as_ptr(),
+            descriptor_count: image_infos.len() as u32,
+            ..Default::default()
+        };
+        self.device
+            .logical_device
+            .update_descriptor_sets(&[descriptor_write], &[]);
+    }
+
+    /// Return a non-owning handle to the raw vulkan descriptor set object.
+    ///
+    /// # Safety
+    ///
+    /// - it is up to the caller to synchronize usage of the set
+    pub unsafe fn raw_descriptor_set(&self) -> vk::DescriptorSet {
+        self.descriptor_set
+    }
+}
+
+impl Drop for AtlasDescriptor {
+    fn drop(&mut self) {
+        unsafe {
+            // the descriptor set is freed along with its pool
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_SET,
+                &self.descriptor_set,
+            );
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_POOL,
+                &self.descriptor_pool,
+            );
+            self.device
+                .logical_device
+                .destroy_descriptor_pool(self.descriptor_pool, None);
+            self.device.forget_vulkan_object(
+                vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+                &self.descriptor_set_layout,
+            );
+            self.device.logical_device.destroy_descriptor_set_layout(
+                self.descriptor_set_layout,
+                None,
+            );
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 /// At atlas's version changes any time that the loaded textures are changed
 /// in some way.
 ///
@@ -31,6 +33,57 @@ impl AtlasVersion {
             revision_count: self.revision_count + 1,
         }
     }
+
+    /// True when this version came after another one.
+    ///
+    /// Every version is newer than a version created with
+    /// `AtlasVersion::new_out_of_date`.
+    pub fn is_newer_than(&self, version: &Self) -> bool {
+        version.revision_count == 0
+            || self.revision_count > version.revision_count
+    }
+}
+
+/// The contiguous ranges of slots which changed after a version, given the
+/// version each slot last changed in.
+///
+/// Ranges are merged so each one can be written with a single descriptor
+/// write.
+pub(super) fn changed_slot_ranges(
+    slot_versions: &[AtlasVersion],
+    since: &AtlasVersion,
+) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for (index, slot_version) in slot_versions.iter().enumerate() {
+        if !slot_version.is_newer_than(since) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end == index => range.end += 1,
+            _ => ranges.push(index..index + 1),
+        }
+    }
+    ranges
+}
+
+/// The range of sampler slots to write, given how many samplers were
+/// written before and how many the atlas has now.
+///
+/// Nothing has been written when `written` is zero, so the whole array is
+/// written. Samplers are only ever added, so later writes only cover the new
+/// ones.
+pub(super) fn changed_sampler_range(
+    written: usize,
+    sampler_count: usize,
+    array_size: usize,
+) -> Option<Range<usize>> {
+    if written == 0 {
+        Some(0..array_size)
+    } else if sampler_count > written {
+        Some(written..sampler_count)
+    } else {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -60,4 +113,55 @@ mod test {
 
         assert!(v1.is_out_of_date(&v2));
     }
+
+    #[test]
+    fn newer_versions_should_be_newer() {
+        let v1 = AtlasVersion { revision_count: 1 };
+        let v2 = AtlasVersion { revision_count: 2 };
+
+        assert!(v2.is_newer_than(&v1));
+        assert!(!v1.is_newer_than(&v2));
+        assert!(!v1.is_newer_than(&v1));
+        assert!(v1.is_newer_than(&AtlasVersion::new_out_of_date()));
+    }
+
+    #[test]
+    fn only_slots_changed_after_the_version_should_be_returned() {
+        let versions: Vec<AtlasVersion> = [1, 3, 3, 2, 4, 1, 5]
+            .iter()
+            .map(|&revision_count| AtlasVersion { revision_count })
+            .collect();
+
+        let since = AtlasVersion { revision_count: 2 };
+        assert_eq!(
+            changed_slot_ranges(&versions, &since),
+            vec![1..3, 4..5, 6..7]
+        );
+
+        let since = AtlasVersion { revision_count: 5 };
+        assert!(changed_slot_ranges(&versions, &since).is_empty());
+    }
+
+    #[test]
+    fn every_slot_should_change_since_an_out_of_date_version() {
+        let versions = vec![AtlasVersion { revision_count: 1 }; 4];
+        let since = AtlasVersion::new_out_of_date();
+
+        assert_eq!(changed_slot_ranges(&versions, &since), vec![0..4]);
+    }
+
+    #[test]
+    fn the_first_sampler_write_should_cover_the_whole_array() {
+        assert_eq!(changed_sampler_range(0, 1, 16), Some(0..16));
+    }
+
+    #[test]
+    fn later_sampler_writes_should_only_cover_new_samplers() {
+        assert_eq!(changed_sampler_range(1, 3, 16), Some(1..3));
+    }
+
+    #[test]
+    fn no_samplers_should_be_written_without_new_samplers() {
+        assert_eq!(changed_sampler_range(3, 3, 16), None);
+    }
 }
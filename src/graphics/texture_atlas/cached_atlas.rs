@@ -0,0 +1,214 @@
+use crate::graphics::{
+    ext::TextureLoader,
+    texture_atlas::{
+        AtlasVersion, GpuAtlas, LodSettings, SamplerHandle, SamplerPreset,
+        TextureAtlas, TextureHandle,
+    },
+    vulkan::texture::TextureImage,
+};
+
+use anyhow::Result;
+use ash::vk;
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut, Range},
+    path::{Path, PathBuf},
+};
+
+/// A texture atlas decorator which deduplicates textures by the file they
+/// were read from.
+///
+/// Adding a texture whose source path is already in the atlas returns the
+/// existing handle and drops the new texture. Use
+/// [CachedAtlas::add_texture_file] to skip reading the file entirely.
+///
+/// The wrapped atlas is available through `Deref`, so its own methods can
+/// still be called directly.
+pub struct CachedAtlas<Atlas: TextureAtlas> {
+    atlas: Atlas,
+
+    /// Texture handles keyed by the file they were read from.
+    paths: HashMap<PathBuf, TextureHandle>,
+}
+
+impl<Atlas: TextureAtlas> CachedAtlas<Atlas> {
+    /// Wrap an atlas with an empty cache.
+    pub fn new(atlas: Atlas) -> Self {
+        Self {
+            atlas,
+            paths: HashMap::new(),
+        }
+    }
+
+    /// Unwrap the decorated atlas.
+    pub fn into_inner(self) -> Atlas {
+        self.atlas
+    }
+
+    /// The handle for a texture which was read from a file, if the atlas
+    /// still owns it.
+    ///
+    /// Entries are checked against the atlas, so textures which were taken
+    /// or evicted without going through the cache are never returned.
+    pub fn cached_texture(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Option<TextureHandle> {
+        let path = path.as_ref();
+        let handle = *self.paths.get(path)?;
+        if self.atlas.texture_source_path(handle) == Some(path) {
+            Some(handle)
+        } else {
+            None
+        }
+    }
+
+    /// Read a texture file and add it to the atlas, unless the file is
+    /// already in the atlas.
+    pub fn add_texture_file(
+        &mut self,
+        loader: &impl TextureLoader,
+        path: impl Into<String>,
+    ) -> Result<TextureHandle> {
+        let path = path.into();
+        if let Some(handle) = self.cached_texture(&path) {
+            return Ok(handle);
+        }
+        let texture = loader.read_texture_file(path)?;
+        self.add_texture(texture)
+    }
+
+    /// Add a texture without deduplicating it, even when its file is already
+    /// in the atlas. Useful when the same image needs a second handle, like
+    /// when sampling it with two different samplers.
+    pub fn add_uncached_texture(
+        &mut self,
+        texture: TextureImage,
+    ) -> Result<TextureHandle> {
+        self.atlas.add_texture(texture)
+    }
+
+    /// Forget the texture read from a file so the next add reads it again.
+    ///
+    /// Call this when a file changes on disk. The texture itself stays in
+    /// the atlas, and its previous handle is returned so it can be replaced
+    /// or taken.
+    pub fn invalidate_path(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Option<TextureHandle> {
+        self.paths.remove(path.as_ref())
+    }
+
+    /// Forget every cached path.
+    pub fn invalidate_all(&mut self) {
+        self.paths.clear();
+    }
+}
+
+impl CachedAtlas<GpuAtlas> {
+    /// Load a texture file and sample it with a preset sampler, reusing the
+    /// texture when the file is already in the atlas.
+    ///
+    /// Reused textures are rebound to the preset's sampler.
+    pub fn add_texture_with_sampler(
+        &mut self,
+        loader: &impl TextureLoader,
+        path: impl Into<String>,
+        preset: SamplerPreset,
+    ) -> Result<TextureHandle> {
+        let sampler_handle = self.atlas.preset_sampler(preset)?;
+        let texture_handle = self.add_texture_file(loader, path)?;
+        self.bind_sampler_to_texture(sampler_handle, texture_handle)?;
+        Ok(texture_handle)
+    }
+}
+
+impl<Atlas: TextureAtlas> TextureAtlas for CachedAtlas<Atlas> {
+    fn version(&self) -> AtlasVersion {
+        self.atlas.version()
+    }
+
+    fn sampler_version(&self) -> AtlasVersion {
+        self.atlas.sampler_version()
+    }
+
+    fn changed_slots(&self, since: &AtlasVersion) -> Vec<Range<usize>> {
+        self.atlas.changed_slots(since)
+    }
+
+    fn build_descriptor_image_info(&self) -> Vec<vk::DescriptorImageInfo> {
+        self.atlas.build_descriptor_image_info()
+    }
+
+    fn build_descriptor_sampler_info(&self) -> Vec<vk::DescriptorImageInfo> {
+        self.atlas.build_descriptor_sampler_info()
+    }
+
+    fn add_sampler(&mut self, sampler: vk::Sampler) -> Result<SamplerHandle> {
+        self.atlas.add_sampler(sampler)
+    }
+
+    /// Textures read from a file which is already in the atlas are dropped
+    /// and the existing handle is returned.
+    fn add_texture(&mut self, texture: TextureImage) -> Result<TextureHandle> {
+        let path = texture.source_path().map(Path::to_owned);
+        if let Some(path) = &path {
+            if let Some(handle) = self.cached_texture(path) {
+                return Ok(handle);
+            }
+        }
+        let handle = self.atlas.add_texture(texture)?;
+        if let Some(path) = path {
+            self.paths.insert(path, handle);
+        }
+        Ok(handle)
+    }
+
+    unsafe fn take_texture(
+        &mut self,
+        texture_handle: TextureHandle,
+    ) -> Result<TextureImage> {
+        let texture = self.atlas.take_texture(texture_handle)?;
+        self.paths.retain(|_, handle| *handle != texture_handle);
+        Ok(texture)
+    }
+
+    fn bind_sampler_to_texture(
+        &mut self,
+        sampler_handle: SamplerHandle,
+        texture_handle: TextureHandle,
+    ) -> Result<()> {
+        self.atlas
+            .bind_sampler_to_texture(sampler_handle, texture_handle)
+    }
+
+    fn texture_with_lod(
+        &mut self,
+        texture_handle: TextureHandle,
+        lod: LodSettings,
+    ) -> Result<TextureHandle> {
+        self.atlas.texture_with_lod(texture_handle, lod)
+    }
+
+    fn texture_source_path(
+        &self,
+        texture_handle: TextureHandle,
+    ) -> Option<&Path> {
+        self.atlas.texture_source_path(texture_handle)
+    }
+}
+
+impl<Atlas: TextureAtlas> Deref for CachedAtlas<Atlas> {
+    type Target = Atlas;
+
+    fn deref(&self) -> &Atlas {
+        &self.atlas
+    }
+}
+
+impl<Atlas: TextureAtlas> DerefMut for CachedAtlas<Atlas> {
+    fn deref_mut(&mut self) -> &mut Atlas {
+        &mut self.atlas
+    }
+}
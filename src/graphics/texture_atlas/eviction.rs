@@ -0,0 +1,140 @@
+use super::TextureHandle;
+
+/// Called with the handle and logical name of every texture evicted from the
+/// atlas, so the application can reload it later if it's needed again.
+pub type EvictionCallback = Box<dyn FnMut(TextureHandle, Option<&str>)>;
+
+/// Keep the atlas's texture memory under a budget by evicting the least
+/// recently drawn textures.
+///
+/// Apps which stream many large images can set a policy with
+/// `GpuAtlas::set_eviction_policy`. Evicted texture handles fall back to the
+/// default texture until they're reused.
+pub struct EvictionPolicy {
+    /// Textures are evicted when adding a texture would take the atlas's
+    /// texture memory over this many bytes.
+    pub budget_bytes: u64,
+
+    on_evict: Option<EvictionCallback>,
+}
+
+/// A texture which could be evicted from the atlas.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct EvictionCandidate {
+    /// The texture's slot in the atlas.
+    pub index: usize,
+
+    /// The most recent frame which drew with the texture.
+    pub last_used: u64,
+
+    /// The device memory owned by the texture.
+    pub bytes: u64,
+}
+
+impl EvictionPolicy {
+    /// Evict textures once the atlas uses more than `budget_bytes`.
+    pub fn with_budget(budget_bytes: u64) -> Self {
+        Self {
+            budget_bytes,
+            on_evict: None,
+        }
+    }
+
+    /// Call a function for every evicted texture.
+    pub fn on_evict<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(TextureHandle, Option<&str>) + 'static,
+    {
+        self.on_evict = Some(Box::new(callback));
+        self
+    }
+
+    /// The number of bytes which must be freed before `incoming_bytes` more
+    /// can be added without going over budget.
+    pub(crate) fn bytes_to_free(
+        &self,
+        used_bytes: u64,
+        incoming_bytes: u64,
+    ) -> u64 {
+        (used_bytes + incoming_bytes).saturating_sub(self.budget_bytes)
+    }
+
+    /// Notify the application that a texture was evicted.
+    pub(crate) fn notify(
+        &mut self,
+        texture: TextureHandle,
+        name: Option<&str>,
+    ) {
+        if let Some(callback) = &mut self.on_evict {
+            callback(texture, name);
+        }
+    }
+}
+
+/// Pick the least recently used candidates which free at least
+/// `bytes_to_free` bytes.
+///
+/// Ties are broken by slot index so eviction order is predictable. Every
+/// candidate is returned when they can't free enough memory together.
+pub(crate) fn least_recently_used(
+    mut candidates: Vec<EvictionCandidate>,
+    bytes_to_free: u64,
+) -> Vec<usize> {
+    candidates.sort_by_key(|candidate| (candidate.last_used, candidate.index));
+    let mut freed = 0;
+    candidates
+        .into_iter()
+        .take_while(|candidate| {
+            let needed = freed < bytes_to_free;
+            freed += candidate.bytes;
+            needed
+        })
+        .map(|candidate| candidate.index)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candidate(
+        index: usize,
+        last_used: u64,
+        bytes: u64,
+    ) -> EvictionCandidate {
+        EvictionCandidate {
+            index,
+            last_used,
+            bytes,
+        }
+    }
+
+    #[test]
+    fn oldest_textures_should_be_evicted_first() {
+        let candidates = vec![
+            candidate(1, 9, 100),
+            candidate(2, 3, 100),
+            candidate(3, 5, 100),
+        ];
+        assert_eq!(least_recently_used(candidates, 150), vec![2, 3]);
+    }
+
+    #[test]
+    fn nothing_should_be_evicted_when_under_budget() {
+        let candidates = vec![candidate(1, 0, 100)];
+        assert!(least_recently_used(candidates, 0).is_empty());
+    }
+
+    #[test]
+    fn ties_should_evict_the_lowest_slot_first() {
+        let candidates = vec![candidate(4, 2, 100), candidate(3, 2, 100)];
+        assert_eq!(least_recently_used(candidates, 50), vec![3]);
+    }
+
+    #[test]
+    fn budget_should_account_for_incoming_textures() {
+        let policy = EvictionPolicy::with_budget(1000);
+        assert_eq!(policy.bytes_to_free(800, 100), 0);
+        assert_eq!(policy.bytes_to_free(800, 300), 100);
+    }
+}
@@ -1,34 +1,107 @@
 use crate::graphics::{
-    ext::Texture2dFactory,
+    ext::{Texture2dFactory, TextureLoader},
     texture_atlas::{
-        AtlasVersion, SamplerHandle, TextureAtlas, TextureHandle,
-        MAX_SUPPORTED_TEXTURES,
+        atlas_descriptor::AtlasDescriptor,
+        atlas_version::changed_slot_ranges,
+        eviction::{least_recently_used, EvictionCandidate},
+        AtlasDescriptorWrites, AtlasVersion, EvictionPolicy, LodSettings,
+        SamplerHandle, SamplerPreset, TextureAtlas, TextureHandle, TextureRef,
     },
     vulkan::{buffer::CpuBuffer, texture::TextureImage, Device},
 };
 
 use anyhow::Result;
 use ash::{version::DeviceV1_0, vk};
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+};
 
 struct Binding {
     texture: TextureImage,
     sampler_handle: SamplerHandle,
+
+    /// True when the texture is owned by `TextureRef`s and released when the
+    /// last one drops.
+    ref_counted: bool,
+}
+
+/// An entry in the atlas's texture array.
+enum Slot {
+    /// A texture owned by the atlas.
+    Texture(Binding),
+
+    /// Another slot's texture, sampled with a different sampler.
+    Variant {
+        source: usize,
+        sampler_handle: SamplerHandle,
+    },
 }
 
 /// The GPU Atlas is responsible for actually loading texture data into gpu
 /// memory.
 pub struct GpuAtlas {
     /// The collection of all textures owned by this atlas.
-    textures: Vec<Option<Binding>>,
+    textures: Vec<Option<Slot>>,
 
     /// The samplers used by textures owned by this atlas.
     samplers: Vec<vk::Sampler>,
 
+    /// Samplers created for level-of-detail variants, keyed by their
+    /// settings.
+    lod_samplers: Vec<(LodSettings, SamplerHandle)>,
+
+    /// Samplers created for presets, keyed by the preset.
+    preset_samplers: Vec<(SamplerPreset, SamplerHandle)>,
+
+    /// Logical names for textures, keyed by texture index.
+    names: HashMap<usize, String>,
+
+    /// The most recent frame which drew with each slot's texture.
+    last_used: Vec<u64>,
+
+    /// Incremented whenever a slot is freed, so handles to the slot's old
+    /// texture don't refer to whatever is added to the slot next.
+    generations: Vec<u32>,
+
+    /// The most recent frame number seen by `mark_texture_used`. New
+    /// textures count as used in this frame.
+    current_frame: u64,
+
+    /// Evicts least recently used textures when set.
+    eviction: Option<EvictionPolicy>,
+
+    /// Cloned into every `TextureRef` so dropped textures can be queued for
+    /// release.
+    release_sender: Sender<TextureHandle>,
+
+    /// Ref counted textures whose last reference was dropped.
+    release_receiver: Receiver<TextureHandle>,
+
+    /// Released handles which have been received but not destroyed yet.
+    pending_releases: Vec<TextureHandle>,
+
     /// The version be used to determine when a shader's descriptors need to
     /// be updated.
     version: AtlasVersion,
 
+    /// The version each slot's descriptor last changed in, so descriptor
+    /// sets only rewrite the slots which changed since they were last
+    /// written.
+    slot_versions: Vec<AtlasVersion>,
+
+    /// The version of the sampler array, tracked separately so adding a
+    /// sampler doesn't rewrite every texture descriptor.
+    sampler_version: AtlasVersion,
+
+    /// The descriptor set which every frame binds to sample the atlas.
+    descriptor: AtlasDescriptor,
+
     /// A handle to the vulkan device.
     device: Arc<Device>,
 }
@@ -40,23 +113,7 @@ impl GpuAtlas {
             use crate::graphics::ext::SamplerFactory;
             device.create_sampler(
                 "default sampler",
-                vk::SamplerCreateInfo {
-                    mag_filter: vk::Filter::LINEAR,
-                    min_filter: vk::Filter::LINEAR,
-                    address_mode_u: vk::SamplerAddressMode::REPEAT,
-                    address_mode_v: vk::SamplerAddressMode::REPEAT,
-                    address_mode_w: vk::SamplerAddressMode::REPEAT,
-                    anisotropy_enable: 0,
-                    border_color: vk::BorderColor::INT_OPAQUE_BLACK,
-                    unnormalized_coordinates: 0,
-                    compare_enable: 0,
-                    compare_op: vk::CompareOp::ALWAYS,
-                    mipmap_mode: vk::SamplerMipmapMode::LINEAR,
-                    mip_lod_bias: 0.0,
-                    min_lod: 0.0,
-                    max_lod: vk::LOD_CLAMP_NONE,
-                    ..Default::default()
-                },
+                Self::default_sampler_create_info(),
             )?
         };
 
@@ -74,25 +131,557 @@ impl GpuAtlas {
             tex
         };
 
-        let mut bindings = vec![];
-        bindings.reserve(MAX_SUPPORTED_TEXTURES);
+        // every slot the device can bind, the shaders' texture array is
+        // specialized to the same size
+        let capacity = device.max_supported_textures() as usize;
+        let mut bindings = Vec::with_capacity(capacity);
 
-        bindings.push(Some(Binding {
+        bindings.push(Some(Slot::Texture(Binding {
             texture: default_texture,
             sampler_handle: SamplerHandle::default(),
-        }));
+            ref_counted: false,
+        })));
 
-        for _ in 1..MAX_SUPPORTED_TEXTURES {
+        for _ in 1..capacity {
             bindings.push(None);
         }
 
+        let (release_sender, release_receiver) = mpsc::channel();
+        let version = AtlasVersion::new_out_of_date().increment();
+
         Ok(Self {
             textures: bindings,
-            version: AtlasVersion::new_out_of_date().increment(),
+            version,
+            slot_versions: vec![version; capacity],
+            sampler_version: AtlasVersion::new_out_of_date().increment(),
             samplers: vec![sampler],
+            lod_samplers: vec![],
+            preset_samplers: vec![],
+            names: HashMap::new(),
+            last_used: vec![0; capacity],
+            generations: vec![0; capacity],
+            current_frame: 0,
+            eviction: None,
+            release_sender,
+            release_receiver,
+            pending_releases: vec![],
+            descriptor: AtlasDescriptor::new(device.clone())?,
             device,
         })
     }
+
+    /// Add a texture which is released automatically when the last clone of
+    /// the returned reference is dropped.
+    ///
+    /// Ref counted textures are never evicted. Don't take them from the atlas
+    /// by hand, the slot could be reused before the references drop.
+    pub fn add_texture_ref(
+        &mut self,
+        texture: TextureImage,
+    ) -> Result<TextureRef> {
+        let handle = self.add_texture(texture)?;
+        if let Some(Some(Slot::Texture(binding))) =
+            self.textures.get_mut(handle.texture_index() as usize)
+        {
+            binding.ref_counted = true;
+        }
+        Ok(TextureRef::new(handle, self.release_sender.clone()))
+    }
+
+    /// True when a `TextureRef` has been fully dropped and its texture is
+    /// waiting to be released by [Self::release_dropped_textures].
+    pub fn has_dropped_textures(&mut self) -> bool {
+        self.pending_releases
+            .extend(self.release_receiver.try_iter());
+        !self.pending_releases.is_empty()
+    }
+
+    /// Destroy every ref counted texture whose last reference was dropped.
+    /// Returns the number of released textures.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure the atlas is not in use when this method
+    ///   is called
+    pub unsafe fn release_dropped_textures(&mut self) -> Result<usize> {
+        self.has_dropped_textures();
+        let mut released = 0;
+        for handle in std::mem::take(&mut self.pending_releases) {
+            let is_ref_counted = matches!(
+                self.slot(handle),
+                Some(Slot::Texture(Binding {
+                    ref_counted: true,
+                    ..
+                }))
+            );
+            if is_ref_counted {
+                drop(self.take_texture(handle)?);
+                released += 1;
+            }
+        }
+        Ok(released)
+    }
+
+    /// The device memory owned by a texture. Level-of-detail variants share
+    /// their source's image so they own no memory.
+    pub fn texture_memory(&self, texture_handle: TextureHandle) -> Option<u64> {
+        match self.slot(texture_handle)? {
+            Slot::Texture(binding) => Some(binding.texture.memory_size()),
+            Slot::Variant { .. } => Some(0),
+        }
+    }
+
+    /// The device memory owned by every texture in the atlas, including the
+    /// default texture.
+    pub fn total_texture_memory(&self) -> u64 {
+        self.textures
+            .iter()
+            .map(|slot| match slot {
+                Some(Slot::Texture(binding)) => binding.texture.memory_size(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Record that a frame drew with a texture. Drawing with a variant counts
+    /// as using its source texture.
+    pub fn mark_texture_used(
+        &mut self,
+        texture_handle: TextureHandle,
+        frame_number: u64,
+    ) {
+        let index = match self.slot(texture_handle) {
+            Some(Slot::Texture(_)) => texture_handle.texture_index() as usize,
+            Some(Slot::Variant { source, .. }) => *source,
+            None => return,
+        };
+        self.last_used[index] = frame_number;
+        self.current_frame = self.current_frame.max(frame_number);
+    }
+
+    /// Set or clear the policy used to evict textures.
+    ///
+    /// Setting a policy doesn't evict anything by itself, textures are only
+    /// evicted by [Self::evict_for].
+    pub fn set_eviction_policy(&mut self, policy: Option<EvictionPolicy>) {
+        self.eviction = policy;
+    }
+
+    /// True when the atlas has an eviction policy and adding
+    /// `incoming_bytes` would go over its budget.
+    pub fn needs_eviction(&self, incoming_bytes: u64) -> bool {
+        match &self.eviction {
+            Some(policy) => {
+                policy
+                    .bytes_to_free(self.total_texture_memory(), incoming_bytes)
+                    > 0
+            }
+            None => false,
+        }
+    }
+
+    /// Evict the least recently used textures until `incoming_bytes` fits in
+    /// the eviction policy's budget. Returns the number of evicted textures.
+    ///
+    /// The default texture and textures used by the most recent frame,
+    /// including textures of batches which were culled, are never evicted,
+    /// so the atlas can still go over budget. Evicted textures are destroyed
+    /// and their handles, names, and variants are released just like
+    /// [TextureAtlas::take_texture]. Old handles draw the default texture
+    /// rather than whatever reuses the slot.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure the atlas is not in use when this method
+    ///   is called
+    pub unsafe fn evict_for(&mut self, incoming_bytes: u64) -> Result<usize> {
+        let bytes_to_free = match &self.eviction {
+            Some(policy) => policy
+                .bytes_to_free(self.total_texture_memory(), incoming_bytes),
+            None => return Ok(0),
+        };
+        let candidates = self
+            .textures
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(index, _)| self.last_used[*index] < self.current_frame)
+            .filter_map(|(index, slot)| match slot {
+                Some(Slot::Texture(binding)) if !binding.ref_counted => {
+                    Some(EvictionCandidate {
+                        index,
+                        last_used: self.last_used[index],
+                        bytes: binding.texture.memory_size(),
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+        let evicted = least_recently_used(candidates, bytes_to_free);
+        for index in &evicted {
+            let texture_handle = self.handle_for(*index);
+            let name = self.names.get(index).cloned();
+            drop(self.take_texture(texture_handle)?);
+            if let Some(policy) = &mut self.eviction {
+                policy.notify(texture_handle, name.as_deref());
+            }
+        }
+        Ok(evicted.len())
+    }
+
+    /// Load a texture file and sample it with a preset sampler.
+    ///
+    /// Preset samplers are created once and shared by every texture which
+    /// uses the same preset.
+    pub fn add_texture_with_sampler(
+        &mut self,
+        path: impl Into<String>,
+        preset: SamplerPreset,
+    ) -> Result<TextureHandle> {
+        let sampler_handle = self.preset_sampler(preset)?;
+        let texture = self.device.read_texture_file(path)?;
+        let texture_handle = self.add_texture(texture)?;
+        self.bind_sampler_to_texture(sampler_handle, texture_handle)?;
+        Ok(texture_handle)
+    }
+
+    /// Get the sampler for a preset, creating it if needed.
+    pub fn preset_sampler(
+        &mut self,
+        preset: SamplerPreset,
+    ) -> Result<SamplerHandle> {
+        if preset == SamplerPreset::Smooth {
+            // the default sampler uses the smooth preset
+            return Ok(SamplerHandle::default());
+        }
+        if let Some((_, handle)) = self
+            .preset_samplers
+            .iter()
+            .find(|(cached, _)| *cached == preset)
+        {
+            return Ok(*handle);
+        }
+        let sampler = unsafe {
+            use crate::graphics::ext::SamplerFactory;
+            self.device.create_preset_sampler(preset)?
+        };
+        let handle = self.add_sampler(sampler)?;
+        self.preset_samplers.push((preset, handle));
+        Ok(handle)
+    }
+
+    /// Give a texture a logical name which can be used to find it later.
+    ///
+    /// Names are unique, so naming a second texture with the same name
+    /// removes the name from the first texture.
+    pub fn name_texture<Name>(
+        &mut self,
+        texture_handle: TextureHandle,
+        name: Name,
+    ) -> Result<()>
+    where
+        Name: Into<String>,
+    {
+        let index = match self.slot_index(texture_handle) {
+            Some(index) => index,
+            None => anyhow::bail!(
+                "the provided texture handle does not match an existing texture!"
+            ),
+        };
+        let owned_name = name.into();
+        self.names.retain(|_, existing| *existing != owned_name);
+        self.names.insert(index, owned_name);
+        Ok(())
+    }
+
+    /// The logical name given to a texture, if any.
+    pub fn texture_name(&self, texture_handle: TextureHandle) -> Option<&str> {
+        let index = self.slot_index(texture_handle)?;
+        self.names.get(&index).map(String::as_str)
+    }
+
+    /// Find a texture by its logical name.
+    pub fn texture_by_name(&self, name: &str) -> Option<TextureHandle> {
+        self.names
+            .iter()
+            .find(|(_, existing)| existing.as_str() == name)
+            .map(|(index, _)| self.handle_for(*index))
+    }
+
+    /// The image sampled through a texture handle.
+    ///
+    /// Level-of-detail variants return the image they share with their
+    /// source texture.
+    pub fn texture_image(
+        &self,
+        texture_handle: TextureHandle,
+    ) -> Option<&TextureImage> {
+        match self.slot(texture_handle)? {
+            Slot::Texture(binding) => Some(&binding.texture),
+            Slot::Variant { source, .. } => match &self.textures[*source] {
+                Some(Slot::Texture(binding)) => Some(&binding.texture),
+                _ => None,
+            },
+        }
+    }
+
+    /// True when the handle refers to a level-of-detail variant which shares
+    /// another texture's image.
+    pub fn is_lod_variant(&self, texture_handle: TextureHandle) -> bool {
+        matches!(self.slot(texture_handle), Some(Slot::Variant { .. }))
+    }
+
+    /// The index into the shaders' texture array for a texture handle.
+    /// Handles to textures which were taken or evicted use the default
+    /// texture.
+    ///
+    /// 2d array textures use the default texture too, because shaders which
+    /// bind the atlas as an array of `texture2D` can't sample them. Use
+    /// `shader_array_texture_index` for the array texture shaders.
+    pub fn shader_texture_index(&self, texture_handle: TextureHandle) -> u32 {
+        match self.slot_index(texture_handle) {
+            Some(_) if self.is_array_texture(texture_handle) => 0,
+            Some(index) => index as u32,
+            None => 0,
+        }
+    }
+
+    /// The index into the array texture shaders' `texture2DArray` array for
+    /// a texture handle, or None when the handle doesn't refer to a 2d array
+    /// texture.
+    pub fn shader_array_texture_index(
+        &self,
+        texture_handle: TextureHandle,
+    ) -> Option<u32> {
+        if self.is_array_texture(texture_handle) {
+            self.slot_index(texture_handle).map(|index| index as u32)
+        } else {
+            None
+        }
+    }
+
+    /// True when the handle refers to a 2d array texture, which is drawn one
+    /// layer at a time with the array texture shaders.
+    pub fn is_array_texture(&self, texture_handle: TextureHandle) -> bool {
+        self.texture_image(texture_handle).is_some_and(|texture| {
+            texture.view_type() == vk::ImageViewType::TYPE_2D_ARRAY
+        })
+    }
+
+    /// The sampler currently bound to a texture.
+    pub fn texture_sampler(
+        &self,
+        texture_handle: TextureHandle,
+    ) -> Option<SamplerHandle> {
+        match self.slot(texture_handle)? {
+            Slot::Texture(binding) => Some(binding.sampler_handle),
+            Slot::Variant { sampler_handle, .. } => Some(*sampler_handle),
+        }
+    }
+
+    /// The index into the shaders' sampler array for the sampler bound to a
+    /// texture. Handles which don't refer to a texture use the default
+    /// sampler.
+    pub fn sampler_index(&self, texture_handle: TextureHandle) -> u32 {
+        self.texture_sampler(texture_handle)
+            .unwrap_or_default()
+            .index()
+    }
+
+    /// The raw sampler referenced by a sampler handle.
+    pub(crate) fn raw_sampler(
+        &self,
+        sampler_handle: SamplerHandle,
+    ) -> Option<vk::Sampler> {
+        self.samplers.get(sampler_handle.index() as usize).copied()
+    }
+
+    /// Replace the image owned by a texture, keeping the texture's handle,
+    /// name, sampler binding, and level-of-detail variants. Returns the
+    /// previous image.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must make sure the atlas is not in use when this method
+    ///   is called
+    pub unsafe fn replace_texture(
+        &mut self,
+        texture_handle: TextureHandle,
+        texture: TextureImage,
+    ) -> Result<TextureImage> {
+        let index = match self.slot_index(texture_handle) {
+            Some(index) => index,
+            None => {
+                anyhow::bail!("no texture bound with that texture handle!")
+            }
+        };
+        let binding = match &mut self.textures[index] {
+            Some(Slot::Texture(binding)) => binding,
+            _ => anyhow::bail!("the texture handle refers to a lod variant!"),
+        };
+        let previous = std::mem::replace(&mut binding.texture, texture);
+
+        self.mark_slots_changed(&self.slot_with_variants(index));
+
+        Ok(previous)
+    }
+
+    /// True when textures or samplers changed since the atlas's descriptor
+    /// set was last written.
+    pub fn has_descriptor_writes(&self) -> bool {
+        self.version
+            .is_out_of_date(&self.descriptor.texture_version())
+            || self.changed_sampler_range().is_some()
+    }
+
+    /// Write every texture and sampler which changed into the atlas's
+    /// descriptor set, and return the slots which were written.
+    ///
+    /// # Safety
+    ///
+    /// - changed slots must not be in use by the gpu, which is true for new
+    ///   textures and samplers
+    /// - unless the device supports update-after-bind, the descriptor set
+    ///   must not be in use by the gpu at all
+    pub unsafe fn write_descriptor_set(&mut self) -> AtlasDescriptorWrites {
+        let mut writes = AtlasDescriptorWrites::default();
+        let texture_version = self.descriptor.texture_version();
+        if self.version.is_out_of_date(&texture_version) {
+            writes.textures = self.changed_slots(&texture_version);
+            let image_infos = if writes.textures.is_empty() {
+                vec![]
+            } else {
+                self.build_descriptor_image_info()
+            };
+            self.descriptor.write_textures(
+                self.version,
+                &writes.textures,
+                &image_infos,
+            );
+        }
+        if let Some(range) = self.changed_sampler_range() {
+            let sampler_infos = self.build_descriptor_sampler_info();
+            self.descriptor.write_samplers(
+                range.clone(),
+                self.samplers.len(),
+                &sampler_infos,
+            );
+            writes.samplers = Some(range);
+        }
+        writes
+    }
+
+    /// Return a non-owning handle to the atlas's descriptor set, which is
+    /// bound as `ATLAS_DESCRIPTOR_SET`.
+    ///
+    /// # Safety
+    ///
+    /// - it is up to the caller to synchronize usage of the set, and to
+    ///   write the set before it's used
+    pub unsafe fn raw_descriptor_set(&self) -> vk::DescriptorSet {
+        self.descriptor.raw_descriptor_set()
+    }
+
+    /// The sampler slots which haven't been written to the descriptor set.
+    fn changed_sampler_range(&self) -> Option<Range<usize>> {
+        self.descriptor
+            .sampler_range(self.samplers.len(), self.max_samplers())
+    }
+
+    fn slot(&self, texture_handle: TextureHandle) -> Option<&Slot> {
+        self.textures[self.slot_index(texture_handle)?].as_ref()
+    }
+
+    /// The index of the slot a handle refers to. Returns None when the slot
+    /// is empty, or when the handle's texture was taken and the slot was
+    /// reused.
+    fn slot_index(&self, texture_handle: TextureHandle) -> Option<usize> {
+        let index = texture_handle.texture_index() as usize;
+        let generation = *self.generations.get(index)?;
+        if generation != texture_handle.generation() {
+            return None;
+        }
+        self.textures[index].as_ref().map(|_| index)
+    }
+
+    /// A handle to the texture currently in a slot.
+    fn handle_for(&self, index: usize) -> TextureHandle {
+        TextureHandle::new(index as u32, self.generations[index])
+    }
+
+    /// Empty a slot and invalidate every handle which refers to it.
+    fn release_slot(&mut self, index: usize) {
+        self.textures[index] = None;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.names.remove(&index);
+    }
+
+    /// The number of entries in the shaders' sampler array.
+    fn max_samplers(&self) -> usize {
+        self.device.max_supported_samplers() as usize
+    }
+
+    /// The settings used by the atlas's default sampler. Level-of-detail
+    /// variants are derived from these settings.
+    fn default_sampler_create_info() -> vk::SamplerCreateInfo {
+        SamplerPreset::Smooth.create_info()
+    }
+
+    /// Get the cached sampler for a set of level-of-detail settings, creating
+    /// it if needed.
+    fn lod_sampler(&mut self, lod: LodSettings) -> Result<SamplerHandle> {
+        if let Some((_, handle)) = self
+            .lod_samplers
+            .iter()
+            .find(|(settings, _)| *settings == lod)
+        {
+            return Ok(*handle);
+        }
+        let sampler = unsafe {
+            use crate::graphics::ext::SamplerFactory;
+            self.device.create_sampler(
+                format!("lod sampler {:?}", lod),
+                lod.apply(Self::default_sampler_create_info()),
+            )?
+        };
+        let handle = self.add_sampler(sampler)?;
+        self.lod_samplers.push((lod, handle));
+        Ok(handle)
+    }
+
+    /// A slot and every level-of-detail variant which shares its image.
+    fn slot_with_variants(&self, index: usize) -> Vec<usize> {
+        let variants = self.textures.iter().enumerate().filter_map(
+            |(slot_index, slot)| match slot {
+                Some(Slot::Variant { source, .. }) if *source == index => {
+                    Some(slot_index)
+                }
+                _ => None,
+            },
+        );
+        std::iter::once(index).chain(variants).collect()
+    }
+
+    /// Create a new atlas version where the slots' descriptors changed.
+    fn mark_slots_changed(&mut self, slots: &[usize]) {
+        self.version = self.version.increment();
+        for &slot in slots {
+            self.slot_versions[slot] = self.version;
+        }
+    }
+
+    /// Find the first free slot in the texture array.
+    fn free_slot_index(&self) -> Result<usize> {
+        use anyhow::Context;
+
+        Ok(self
+            .textures
+            .iter()
+            .enumerate()
+            .find(|(_i, entry)| entry.is_none())
+            .with_context(|| "unable to find a free texture slot!")?
+            .0)
+    }
 }
 
 impl TextureAtlas for GpuAtlas {
@@ -100,9 +689,33 @@ impl TextureAtlas for GpuAtlas {
         self.version
     }
 
+    fn sampler_version(&self) -> AtlasVersion {
+        self.sampler_version
+    }
+
+    fn changed_slots(&self, since: &AtlasVersion) -> Vec<Range<usize>> {
+        changed_slot_ranges(&self.slot_versions, since)
+    }
+
+    /// The atlas owns the sampler and destroys it when the atlas is dropped,
+    /// even when the atlas is full and the sampler can't be added.
     fn add_sampler(&mut self, sampler: vk::Sampler) -> Result<SamplerHandle> {
+        if self.samplers.len() >= self.max_samplers() {
+            unsafe {
+                self.device
+                    .forget_vulkan_object(vk::ObjectType::SAMPLER, &sampler);
+                self.device.logical_device.destroy_sampler(sampler, None);
+            }
+            anyhow::bail!(
+                "the atlas already has the maximum of {} samplers!",
+                self.max_samplers()
+            );
+        }
         self.samplers.push(sampler);
         let index = self.samplers.len() - 1;
+
+        self.sampler_version = self.sampler_version.increment();
+
         Ok(SamplerHandle::new(index as u32))
     }
 
@@ -111,13 +724,23 @@ impl TextureAtlas for GpuAtlas {
         sampler_handle: SamplerHandle,
         texture_handle: TextureHandle,
     ) -> Result<()> {
-        if let Some(binding) =
-            &mut self.textures[texture_handle.texture_index() as usize]
-        {
-            binding.sampler_handle = sampler_handle;
-            Ok(())
-        } else {
-            anyhow::bail!("the provide texture handle does not match an existing texture!");
+        let index = self.slot_index(texture_handle);
+        match index.and_then(|index| self.textures[index].as_mut()) {
+            Some(Slot::Texture(Binding {
+                sampler_handle: bound,
+                ..
+            })) => {
+                *bound = sampler_handle;
+                Ok(())
+            }
+            Some(Slot::Variant { .. }) => {
+                // variants are shared by every caller asking for the same
+                // settings, so their sampler can't change
+                anyhow::bail!("the texture handle refers to a lod variant!")
+            }
+            None => {
+                anyhow::bail!("the provide texture handle does not match an existing texture!");
+            }
         }
     }
 
@@ -125,28 +748,37 @@ impl TextureAtlas for GpuAtlas {
     ///
     /// Texture handles can be used when drawing to get the texture_index which
     /// the shader uses to select this texture from the global array.
+    ///
+    /// 2d array textures share the array with 2d textures. Batches which use
+    /// them are drawn with the array texture shaders, which declare the
+    /// array as `texture2DArray` and pick a layer with the batch's `layer`.
     fn add_texture(&mut self, texture: TextureImage) -> Result<TextureHandle> {
-        use anyhow::Context;
-
-        let free_slot_index = self
-            .textures
-            .iter()
-            .enumerate()
-            .find(|(_i, entry)| entry.is_none())
-            .with_context(|| "unable to find a free texture slot!")?
-            .0;
+        let view_type = texture.view_type();
+        if view_type != vk::ImageViewType::TYPE_2D
+            && view_type != vk::ImageViewType::TYPE_2D_ARRAY
+        {
+            // the shaders declare the atlas as texture2D or texture2DArray
+            anyhow::bail!(
+                "only 2d and 2d array textures can be added to the atlas, \
+                 not {:?}!",
+                view_type
+            );
+        }
+        let free_slot_index = self.free_slot_index()?;
+        self.last_used[free_slot_index] = self.current_frame;
 
-        self.textures[free_slot_index] = Some(Binding {
+        self.textures[free_slot_index] = Some(Slot::Texture(Binding {
             texture,
             sampler_handle: SamplerHandle::default(),
-        });
+            ref_counted: false,
+        }));
 
-        self.version = self.version.increment();
+        self.mark_slots_changed(&[free_slot_index]);
 
-        Ok(TextureHandle::new(free_slot_index as u32))
+        Ok(self.handle_for(free_slot_index))
     }
 
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller must make sure the atlas is not in use when this method
     ///   is called
@@ -154,39 +786,129 @@ impl TextureAtlas for GpuAtlas {
         &mut self,
         texture_handle: TextureHandle,
     ) -> Result<TextureImage> {
-        use anyhow::Context;
+        let index = match self.slot_index(texture_handle) {
+            Some(index) => index,
+            None => {
+                anyhow::bail!("no texture bound with that texture handle!")
+            }
+        };
+        let texture = match self.textures[index].take() {
+            Some(Slot::Texture(binding)) => binding.texture,
+            variant => {
+                self.textures[index] = variant;
+                anyhow::bail!("the texture handle refers to a lod variant!");
+            }
+        };
 
-        let texture = self.textures[texture_handle.texture_index() as usize]
-            .take()
-            .context("no texture bound with that texture handle!")?
-            .texture;
+        // freed slots fall back to the default texture, including variants
+        // which can't outlive the texture they sample
+        let changed = self.slot_with_variants(index);
+        for &slot_index in &changed {
+            self.release_slot(slot_index);
+        }
 
-        self.version = self.version.increment();
+        self.mark_slots_changed(&changed);
 
         Ok(texture)
     }
 
+    fn texture_with_lod(
+        &mut self,
+        texture_handle: TextureHandle,
+        lod: LodSettings,
+    ) -> Result<TextureHandle> {
+        let mut source = match self.slot_index(texture_handle) {
+            Some(index) => index,
+            None => anyhow::bail!(
+                "the provided texture handle does not match an existing texture!"
+            ),
+        };
+        if let Some(Slot::Variant { source: root, .. }) = &self.textures[source]
+        {
+            source = *root;
+        }
+
+        let sampler_handle = self.lod_sampler(lod)?;
+        let existing = self.textures.iter().position(|slot| match slot {
+            Some(Slot::Variant {
+                source: variant_source,
+                sampler_handle: variant_sampler,
+            }) => {
+                *variant_source == source && *variant_sampler == sampler_handle
+            }
+            _ => false,
+        });
+        if let Some(index) = existing {
+            return Ok(self.handle_for(index));
+        }
+
+        let free_slot_index = self.free_slot_index()?;
+        self.textures[free_slot_index] = Some(Slot::Variant {
+            source,
+            sampler_handle,
+        });
+
+        self.mark_slots_changed(&[free_slot_index]);
+
+        Ok(self.handle_for(free_slot_index))
+    }
+
+    fn texture_source_path(
+        &self,
+        texture_handle: TextureHandle,
+    ) -> Option<&Path> {
+        match self.slot(texture_handle)? {
+            Slot::Texture(binding) => binding.texture.source_path(),
+            Slot::Variant { .. } => None,
+        }
+    }
+
     /// Build a vector of descriptor image info entries. This can be used when
     /// updating a descriptor set with specific image bindings.
+    ///
+    /// Level-of-detail variants share their source texture's image, their
+    /// sampler is picked with push constants like every other texture.
     fn build_descriptor_image_info(&self) -> Vec<vk::DescriptorImageInfo> {
-        let default_view =
-            unsafe { self.textures[0].as_ref().unwrap().texture.raw_view() };
+        let view = |index: usize| match &self.textures[index] {
+            Some(Slot::Texture(binding)) => unsafe {
+                Some(binding.texture.raw_view())
+            },
+            _ => None,
+        };
+        let default_view = view(0).unwrap();
 
         self.textures
             .iter()
-            .map(|binding_option| match binding_option {
-                Some(binding) => vk::DescriptorImageInfo {
+            .map(|slot_option| {
+                let image_view = match slot_option {
+                    Some(Slot::Texture(binding)) => unsafe {
+                        binding.texture.raw_view()
+                    },
+                    Some(Slot::Variant { source, .. }) => {
+                        view(*source).unwrap_or(default_view)
+                    }
+                    None => default_view,
+                };
+                vk::DescriptorImageInfo {
                     image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-                    image_view: unsafe { binding.texture.raw_view() },
-                    sampler: self.samplers
-                        [binding.sampler_handle.index() as usize],
-                },
+                    image_view,
+                    sampler: vk::Sampler::null(),
+                }
+            })
+            .collect()
+    }
 
-                None => vk::DescriptorImageInfo {
-                    image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-                    image_view: default_view,
-                    sampler: self.samplers[0],
-                },
+    /// Build a vector of sampler descriptor entries. Unused entries refer to
+    /// the default sampler so every element in the array is valid.
+    fn build_descriptor_sampler_info(&self) -> Vec<vk::DescriptorImageInfo> {
+        (0..self.max_samplers())
+            .map(|index| vk::DescriptorImageInfo {
+                sampler: self
+                    .samplers
+                    .get(index)
+                    .copied()
+                    .unwrap_or(self.samplers[0]),
+                ..Default::default()
             })
             .collect()
     }
@@ -196,6 +918,8 @@ impl Drop for GpuAtlas {
     fn drop(&mut self) {
         unsafe {
             for sampler in self.samplers.drain(0..) {
+                self.device
+                    .forget_vulkan_object(vk::ObjectType::SAMPLER, &sampler);
                 self.device.logical_device.destroy_sampler(sampler, None);
             }
         }
@@ -0,0 +1,119 @@
+use ash::vk;
+
+/// Level-of-detail settings used when sampling a mipmapped texture.
+///
+/// Use `TextureAtlas::texture_with_lod` to get a texture handle which samples
+/// an existing texture with these settings.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LodSettings {
+    /// Added to the computed mip level. Negative values sharpen distant
+    /// textures, positive values soften them.
+    ///
+    /// Vulkan clamps the bias to the device's `maxSamplerLodBias` limit.
+    pub mip_lod_bias: f32,
+
+    /// The lowest (most detailed) mip level which can be sampled.
+    pub min_lod: f32,
+
+    /// The highest (least detailed) mip level which can be sampled.
+    pub max_lod: f32,
+
+    /// The maximum anisotropy used when sampling, or None to disable
+    /// anisotropic filtering.
+    pub max_anisotropy: Option<f32>,
+}
+
+impl LodSettings {
+    /// Settings which only apply a bias to the computed mip level.
+    pub fn with_bias(mip_lod_bias: f32) -> Self {
+        Self {
+            mip_lod_bias,
+            ..Default::default()
+        }
+    }
+
+    /// Clamp the mip levels which can be sampled to the range
+    /// `[min_lod, max_lod]`.
+    pub fn clamped(self, min_lod: f32, max_lod: f32) -> Self {
+        Self {
+            min_lod,
+            max_lod,
+            ..self
+        }
+    }
+
+    /// Anisotropic filtering with the most anisotropy the device supports.
+    ///
+    /// Sharpens textures viewed at steep angles or with very different
+    /// horizontal and vertical scales. Devices without anisotropic filtering
+    /// fall back to regular trilinear filtering.
+    pub fn max_anisotropic() -> Self {
+        Self::default().anisotropic(f32::MAX)
+    }
+
+    /// Enable anisotropic filtering.
+    ///
+    /// The anisotropy is clamped to the device's limit when the sampler is
+    /// created.
+    pub fn anisotropic(self, max_anisotropy: f32) -> Self {
+        Self {
+            max_anisotropy: Some(max_anisotropy),
+            ..self
+        }
+    }
+
+    /// Apply these settings to a sampler create info.
+    pub fn apply(
+        &self,
+        create_info: vk::SamplerCreateInfo,
+    ) -> vk::SamplerCreateInfo {
+        vk::SamplerCreateInfo {
+            mip_lod_bias: self.mip_lod_bias,
+            min_lod: self.min_lod,
+            max_lod: self.max_lod.max(self.min_lod),
+            anisotropy_enable: self.max_anisotropy.is_some() as u32,
+            max_anisotropy: self.max_anisotropy.unwrap_or(1.0).max(1.0),
+            ..create_info
+        }
+    }
+}
+
+impl Default for LodSettings {
+    /// No bias, no clamping, and no anisotropic filtering.
+    fn default() -> Self {
+        Self {
+            mip_lod_bias: 0.0,
+            min_lod: 0.0,
+            max_lod: vk::LOD_CLAMP_NONE,
+            max_anisotropy: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_should_keep_max_lod_above_min_lod() {
+        let info = LodSettings::with_bias(-0.5)
+            .clamped(3.0, 1.0)
+            .apply(vk::SamplerCreateInfo::default());
+
+        assert_eq!(info.mip_lod_bias, -0.5);
+        assert_eq!(info.min_lod, 3.0);
+        assert_eq!(info.max_lod, 3.0);
+        assert_eq!(info.anisotropy_enable, 0);
+    }
+
+    #[test]
+    fn apply_should_enable_anisotropy() {
+        let info = LodSettings::default()
+            .anisotropic(8.0)
+            .apply(vk::SamplerCreateInfo::default());
+
+        assert_eq!(info.anisotropy_enable, 1);
+        assert_eq!(info.max_anisotropy, 8.0);
+        assert_eq!(info.max_lod, vk::LOD_CLAMP_NONE);
+    }
+}
@@ -5,36 +5,70 @@
 //! A shader can define code like:
 //!
 //! ```glsl
-//! layout(binding = 1) uniform sampler2D textures[MAX_TEXTURES];
+//! layout(set = 0, binding = 0) uniform texture2D textures[MAX_TEXTURES];
+//! layout(set = 0, binding = 2) uniform sampler samplers[MAX_SAMPLERS];
 //! ```
 //!
-//! Which describes an array of textures. Textures in the array can all have
-//! different sizes and bindings. Individual draw calls can index into the
-//! array using uniform buffers or push constants.
+//! Which describes an array of textures and an array of samplers. Textures in
+//! the array can all have different sizes. Individual draw calls can index
+//! into both arrays using uniform buffers or push constants, and combine any
+//! texture with any sampler:
+//!
+//! ```glsl
+//! texture(sampler2D(textures[texture_index], samplers[sampler_index]), uv)
+//! ```
 //!
 //! The appeal is that the entire texture array only needs to be bound once
-//! for the entire frame.
+//! for the entire frame. Keeping samplers separate means N textures and M
+//! samplers only need N + M descriptors, and changing which sampler a
+//! texture uses doesn't rewrite any descriptors at all.
+//!
+//! The arrays live in a single descriptor set owned by the atlas and shared
+//! by every frame. When the device supports update-after-bind, new textures
+//! are written while frames which use the set are still in flight. Otherwise
+//! the frames are waited on before the set is written.
 
+mod atlas_descriptor;
 mod atlas_version;
+mod cached_atlas;
+mod eviction;
 mod gpu_atlas;
+mod lod_settings;
 mod sampler_handle;
+mod sampler_preset;
 mod texture_handle;
+mod texture_ref;
 
 pub use self::{
-    atlas_version::AtlasVersion, gpu_atlas::GpuAtlas,
-    sampler_handle::SamplerHandle, texture_handle::TextureHandle,
+    atlas_descriptor::AtlasDescriptorWrites,
+    atlas_version::AtlasVersion,
+    cached_atlas::CachedAtlas,
+    eviction::{EvictionCallback, EvictionPolicy},
+    gpu_atlas::GpuAtlas,
+    lod_settings::LodSettings,
+    sampler_handle::SamplerHandle,
+    sampler_preset::SamplerPreset,
+    texture_handle::TextureHandle,
+    texture_ref::TextureRef,
 };
 
 use crate::graphics::Graphics;
 
 use anyhow::Result;
 use ash::{version::DeviceV1_0, vk};
+use std::{ops::Range, path::Path};
 
 use super::vulkan::texture::TextureImage;
 
-/// The maximum number of textures which can be managed by any given texture
-/// atlas.
-pub const MAX_SUPPORTED_TEXTURES: usize = 64;
+/// The descriptor set which holds the atlas's textures and samplers. It is
+/// shared by every frame and bound before each frame's own set.
+pub const ATLAS_DESCRIPTOR_SET: u32 = 0;
+
+/// The descriptor binding for the array of sampled texture images.
+pub const TEXTURE_BINDING: u32 = 0;
+
+/// The descriptor binding for the array of samplers.
+pub const SAMPLER_BINDING: u32 = 2;
 
 /// A type which owns a collection of texture objects that can be bound once
 /// per frame and individually accessed in calls to `vkDraw`.
@@ -42,12 +76,31 @@ pub trait TextureAtlas {
     /// The atlas's current version.
     fn version(&self) -> AtlasVersion;
 
+    /// The version of the atlas's sampler array. It changes when samplers
+    /// are added, but not when a texture's sampler binding changes.
+    fn sampler_version(&self) -> AtlasVersion;
+
+    /// The ranges of texture slots whose descriptors changed after a
+    /// version. Every slot has changed since an out of date version.
+    ///
+    /// Descriptor sets can rewrite just these slots rather than the whole
+    /// texture array.
+    fn changed_slots(&self, since: &AtlasVersion) -> Vec<Range<usize>>;
+
     /// Build the array of descriptor image info objects which can be used to
     /// write all of this atlas's textures into a descriptor set.
     fn build_descriptor_image_info(&self) -> Vec<vk::DescriptorImageInfo>;
 
+    /// Build the array of descriptor image info objects which can be used to
+    /// write all of this atlas's samplers into a descriptor set. The array
+    /// always has one entry for each of the device's supported samplers.
+    fn build_descriptor_sampler_info(&self) -> Vec<vk::DescriptorImageInfo>;
+
     /// Add a named sampler to the atlas. Samplers can be persistently bound to
     /// individual textures.
+    ///
+    /// Fails when the atlas already has as many samplers as the device can
+    /// bind, see `Device::max_supported_samplers`.
     fn add_sampler(&mut self, sampler: vk::Sampler) -> Result<SamplerHandle>;
 
     /// Add a texture to the atlas. The atlas owns the texture and will destroy
@@ -56,7 +109,7 @@ pub trait TextureAtlas {
 
     /// Take ownership of a texture owned by this atlas.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller must make sure that the texture atlas is not in use when
     ///   this method is called
@@ -67,11 +120,34 @@ pub trait TextureAtlas {
 
     /// Bind a sampler to a texture. Binding are persistent - they do not change
     /// until this method is called again.
+    ///
+    /// Level-of-detail variants are shared, so their sampler can't be
+    /// changed.
     fn bind_sampler_to_texture(
         &mut self,
         sampler_handle: SamplerHandle,
         texture_handle: TextureHandle,
     ) -> Result<()>;
+
+    /// Get a handle which samples an existing texture with different mipmap
+    /// level-of-detail settings.
+    ///
+    /// The returned handle shares the texture's image, so no texture data is
+    /// copied. Samplers and handles are cached, so requesting the same
+    /// settings for the same texture returns the same handle. Handles are
+    /// invalidated when the texture is taken from the atlas.
+    fn texture_with_lod(
+        &mut self,
+        texture_handle: TextureHandle,
+        lod: LodSettings,
+    ) -> Result<TextureHandle>;
+
+    /// The file an owned texture was read from. Level-of-detail variants
+    /// and textures which weren't read from a file have no source path.
+    fn texture_source_path(
+        &self,
+        texture_handle: TextureHandle,
+    ) -> Option<&Path>;
 }
 
 impl TextureAtlas for Graphics {
@@ -79,10 +155,22 @@ impl TextureAtlas for Graphics {
         self.texture_atlas.version()
     }
 
+    fn sampler_version(&self) -> AtlasVersion {
+        self.texture_atlas.sampler_version()
+    }
+
+    fn changed_slots(&self, since: &AtlasVersion) -> Vec<Range<usize>> {
+        self.texture_atlas.changed_slots(since)
+    }
+
     fn build_descriptor_image_info(&self) -> Vec<vk::DescriptorImageInfo> {
         self.texture_atlas.build_descriptor_image_info()
     }
 
+    fn build_descriptor_sampler_info(&self) -> Vec<vk::DescriptorImageInfo> {
+        self.texture_atlas.build_descriptor_sampler_info()
+    }
+
     fn add_sampler(&mut self, sampler: vk::Sampler) -> Result<SamplerHandle> {
         self.texture_atlas.add_sampler(sampler)
     }
@@ -96,10 +184,36 @@ impl TextureAtlas for Graphics {
             .bind_sampler_to_texture(sampler_handle, texture_handle)
     }
 
+    /// Textures are evicted first when the atlas has an eviction policy and
+    /// the new texture would go over budget. The device is idled before
+    /// anything is evicted.
     fn add_texture(&mut self, texture: TextureImage) -> Result<TextureHandle> {
+        let incoming_bytes = texture.memory_size();
+        if self.texture_atlas.needs_eviction(incoming_bytes) {
+            unsafe {
+                // SAFE: evicted textures are not in use once the device idles
+                self.device.logical_device.device_wait_idle()?;
+                self.texture_atlas.evict_for(incoming_bytes)?;
+            }
+        }
         self.texture_atlas.add_texture(texture)
     }
 
+    fn texture_with_lod(
+        &mut self,
+        texture_handle: TextureHandle,
+        lod: LodSettings,
+    ) -> Result<TextureHandle> {
+        self.texture_atlas.texture_with_lod(texture_handle, lod)
+    }
+
+    fn texture_source_path(
+        &self,
+        texture_handle: TextureHandle,
+    ) -> Option<&Path> {
+        self.texture_atlas.texture_source_path(texture_handle)
+    }
+
     /// This implementation is generally SAFE because it forces the device to
     /// idle prior to removing the texture.
     unsafe fn take_texture(
@@ -1,5 +1,5 @@
 /// A handle which can provide the texture index for a push constant.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SamplerHandle(u32);
 
 impl SamplerHandle {
@@ -0,0 +1,91 @@
+use ash::vk;
+
+/// Common sampler settings, so most textures never need a hand-written
+/// `vk::SamplerCreateInfo`.
+///
+/// Create a sampler with `SamplerFactory::create_preset_sampler`, or load a
+/// texture which uses one with `GpuAtlas::add_texture_with_sampler`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SamplerPreset {
+    /// Nearest filtering with clamped edges. Keeps pixel art crisp when it's
+    /// scaled up.
+    PixelArt,
+
+    /// Linear filtering with repeated edges. This is the atlas's default.
+    Smooth,
+
+    /// Linear filtering with clamped edges, so the opposite edge never
+    /// bleeds into interface elements.
+    Ui,
+
+    /// Smooth filtering plus the most anisotropy the device supports.
+    Anisotropic,
+}
+
+impl SamplerPreset {
+    /// The sampler create info described by this preset.
+    pub fn create_info(&self) -> vk::SamplerCreateInfo {
+        let (filter, mipmap_mode, address_mode) = match self {
+            SamplerPreset::PixelArt => (
+                vk::Filter::NEAREST,
+                vk::SamplerMipmapMode::NEAREST,
+                vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ),
+            SamplerPreset::Smooth | SamplerPreset::Anisotropic => (
+                vk::Filter::LINEAR,
+                vk::SamplerMipmapMode::LINEAR,
+                vk::SamplerAddressMode::REPEAT,
+            ),
+            SamplerPreset::Ui => (
+                vk::Filter::LINEAR,
+                vk::SamplerMipmapMode::LINEAR,
+                vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ),
+        };
+        let anisotropic = *self == SamplerPreset::Anisotropic;
+        vk::SamplerCreateInfo {
+            mag_filter: filter,
+            min_filter: filter,
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            // clamped to the device's limit when the sampler is created
+            anisotropy_enable: anisotropic as u32,
+            max_anisotropy: if anisotropic { f32::MAX } else { 1.0 },
+            border_color: vk::BorderColor::INT_OPAQUE_BLACK,
+            unnormalized_coordinates: 0,
+            compare_enable: 0,
+            compare_op: vk::CompareOp::ALWAYS,
+            mipmap_mode,
+            mip_lod_bias: 0.0,
+            min_lod: 0.0,
+            max_lod: vk::LOD_CLAMP_NONE,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pixel_art_should_use_nearest_filtering_and_clamp() {
+        let info = SamplerPreset::PixelArt.create_info();
+        assert_eq!(info.mag_filter, vk::Filter::NEAREST);
+        assert_eq!(info.min_filter, vk::Filter::NEAREST);
+        assert_eq!(info.mipmap_mode, vk::SamplerMipmapMode::NEAREST);
+        assert_eq!(info.address_mode_u, vk::SamplerAddressMode::CLAMP_TO_EDGE);
+        assert_eq!(info.anisotropy_enable, 0);
+    }
+
+    #[test]
+    fn anisotropic_should_only_add_anisotropy_to_smooth() {
+        let smooth = SamplerPreset::Smooth.create_info();
+        let anisotropic = SamplerPreset::Anisotropic.create_info();
+        assert_eq!(anisotropic.anisotropy_enable, 1);
+        assert_eq!(anisotropic.mag_filter, smooth.mag_filter);
+        assert_eq!(anisotropic.address_mode_v, smooth.address_mode_v);
+        assert_eq!(smooth.address_mode_v, vk::SamplerAddressMode::REPEAT);
+    }
+}
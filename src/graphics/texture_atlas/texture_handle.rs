@@ -1,23 +1,41 @@
-/// A unique identifier for a texture managed by the texture atlas.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct TextureHandle(u32);
+/// A handle which can be used to reference a texture owned by the atlas.
+///
+/// Handles carry the generation of the slot they were created for. When a
+/// texture is taken or evicted its slot's generation changes, so old handles
+/// stop referring to anything instead of picking up the slot's next texture.
+///
+/// Serialized handles are raw atlas indices which are only meaningful to the
+/// atlas which created them. Use a `Scene` to save textures by name.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextureHandle {
+    index: u32,
+    generation: u32,
+}
 
 impl TextureHandle {
-    pub(super) fn new(index: u32) -> Self {
-        TextureHandle(index)
+    pub(super) fn new(index: u32, generation: u32) -> Self {
+        TextureHandle { index, generation }
     }
 
     /// Return the raw index which can be passed to the shader for selecting a
     /// texture.
+    ///
+    /// The index doesn't say whether the handle is still valid, draws should
+    /// use `GpuAtlas::shader_texture_index` instead.
     pub(crate) fn texture_index(&self) -> u32 {
-        let TextureHandle(index) = self;
-        *index
+        self.index
+    }
+
+    /// The generation of the slot when this handle was created.
+    pub(super) fn generation(&self) -> u32 {
+        self.generation
     }
 }
 
 impl Default for TextureHandle {
     /// Return a texture handle which will always refer to a all-white texture
     fn default() -> Self {
-        TextureHandle(0)
+        TextureHandle::new(0, 0)
     }
 }
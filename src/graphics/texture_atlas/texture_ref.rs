@@ -0,0 +1,85 @@
+use super::TextureHandle;
+
+use std::sync::{mpsc::Sender, Arc};
+
+/// A reference counted texture handle.
+///
+/// Clones share ownership of a texture in the atlas. When the last clone is
+/// dropped the texture is queued for release, and the graphics subsystem
+/// destroys it once no frame can be using it. Create one with
+/// `GpuAtlas::add_texture_ref`.
+#[derive(Clone, Debug)]
+pub struct TextureRef {
+    handle: TextureHandle,
+    _release: Arc<ReleaseOnDrop>,
+}
+
+/// Sends the handle to the atlas's release queue when dropped.
+#[derive(Debug)]
+struct ReleaseOnDrop {
+    handle: TextureHandle,
+    sender: Sender<TextureHandle>,
+}
+
+impl TextureRef {
+    /// Create the first reference to a texture. The handle is sent when the
+    /// last reference is dropped.
+    pub(super) fn new(
+        handle: TextureHandle,
+        sender: Sender<TextureHandle>,
+    ) -> Self {
+        Self {
+            handle,
+            _release: Arc::new(ReleaseOnDrop { handle, sender }),
+        }
+    }
+
+    /// The plain handle used when drawing. It's only valid while a reference
+    /// is alive.
+    pub fn handle(&self) -> TextureHandle {
+        self.handle
+    }
+}
+
+impl From<&TextureRef> for TextureHandle {
+    fn from(texture_ref: &TextureRef) -> Self {
+        texture_ref.handle
+    }
+}
+
+impl Drop for ReleaseOnDrop {
+    fn drop(&mut self) {
+        // the atlas is already gone if the receiver is, so there's nothing
+        // left to release
+        let _ = self.sender.send(self.handle);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::mpsc;
+
+    #[test]
+    fn release_should_wait_for_the_last_clone() {
+        let (sender, receiver) = mpsc::channel();
+        let first = TextureRef::new(TextureHandle::new(3, 0), sender);
+        let second = first.clone();
+
+        drop(first);
+        assert!(receiver.try_recv().is_err());
+
+        drop(second);
+        assert_eq!(receiver.try_recv(), Ok(TextureHandle::new(3, 0)));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn dropping_after_the_atlas_should_not_panic() {
+        let (sender, receiver) = mpsc::channel();
+        let texture_ref = TextureRef::new(TextureHandle::new(1, 0), sender);
+        drop(receiver);
+        drop(texture_ref);
+    }
+}
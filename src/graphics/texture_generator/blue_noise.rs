@@ -0,0 +1,191 @@
+use crate::noise::Rng;
+
+/// The largest blue noise tile which is generated. Void and cluster is
+/// quadratic in the number of pixels, so bigger textures repeat the tile.
+pub(super) const MAX_TILE_SIZE: u32 = 64;
+
+/// The standard deviation of the gaussian used to measure clustering.
+const SIGMA: f32 = 1.5;
+
+/// Generate a square, seamlessly tiling blue noise tile with the void and
+/// cluster algorithm. Returns one gray value per pixel, row by row, where
+/// every value occurs equally often.
+pub(super) fn blue_noise_tile(size: u32, seed: u64) -> Vec<u8> {
+    let size = size as usize;
+    let count = size * size;
+    let mut random = Rng::new(seed);
+
+    // start from a random pattern where about a tenth of the pixels are set
+    let initial_ones = (count / 10).max(1);
+    let mut pattern = vec![false; count];
+    let mut placed = 0;
+    while placed < initial_ones {
+        let index = random.below(count);
+        if !pattern[index] {
+            pattern[index] = true;
+            placed += 1;
+        }
+    }
+
+    // spread the initial pattern out by swapping its tightest cluster into
+    // its largest void until that doesn't change anything
+    let mut energy = Energy::new(size, &pattern);
+    for _ in 0..count {
+        let cluster = energy.tightest_cluster(&pattern);
+        energy.toggle(&mut pattern, cluster);
+        let void = energy.largest_void(&pattern);
+        energy.toggle(&mut pattern, void);
+        if void == cluster {
+            break;
+        }
+    }
+    let prototype = pattern.clone();
+    let prototype_energy = energy.clone();
+
+    // rank the initial pattern by removing tightest clusters
+    let mut ranks = vec![0; count];
+    for rank in (0..initial_ones).rev() {
+        let cluster = energy.tightest_cluster(&pattern);
+        energy.toggle(&mut pattern, cluster);
+        ranks[cluster] = rank;
+    }
+
+    // rank everything else by filling the largest voids
+    let mut pattern = prototype;
+    let mut energy = prototype_energy;
+    for rank in initial_ones..count {
+        let void = energy.largest_void(&pattern);
+        energy.toggle(&mut pattern, void);
+        ranks[void] = rank;
+    }
+
+    ranks
+        .into_iter()
+        .map(|rank| ((rank * 256) / count) as u8)
+        .collect()
+}
+
+/// The gaussian weighted density of set pixels around every pixel, on a
+/// torus so the tile repeats seamlessly.
+#[derive(Clone)]
+struct Energy {
+    size: usize,
+    values: Vec<f32>,
+    kernel: Vec<(isize, isize, f32)>,
+}
+
+impl Energy {
+    fn new(size: usize, pattern: &[bool]) -> Self {
+        let radius = ((SIGMA * 3.0).ceil() as isize).min(size as isize / 2);
+        let mut kernel = vec![];
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let distance_squared = (dx * dx + dy * dy) as f32;
+                let weight = (-distance_squared / (2.0 * SIGMA * SIGMA)).exp();
+                kernel.push((dx, dy, weight));
+            }
+        }
+        let mut energy = Self {
+            size,
+            values: vec![0.0; size * size],
+            kernel,
+        };
+        for (index, set) in pattern.iter().enumerate() {
+            if *set {
+                energy.splat(index, 1.0);
+            }
+        }
+        energy
+    }
+
+    /// Flip a pixel and update the energy around it.
+    fn toggle(&mut self, pattern: &mut [bool], index: usize) {
+        pattern[index] = !pattern[index];
+        self.splat(index, if pattern[index] { 1.0 } else { -1.0 });
+    }
+
+    fn splat(&mut self, index: usize, sign: f32) {
+        let size = self.size as isize;
+        let (x, y) =
+            ((index % self.size) as isize, (index / self.size) as isize);
+        for (dx, dy, weight) in &self.kernel {
+            let nx = (x + dx).rem_euclid(size);
+            let ny = (y + dy).rem_euclid(size);
+            self.values[(ny * size + nx) as usize] += sign * weight;
+        }
+    }
+
+    /// The set pixel with the most set neighbors.
+    fn tightest_cluster(&self, pattern: &[bool]) -> usize {
+        self.find(pattern, true, |candidate, best| candidate > best)
+    }
+
+    /// The unset pixel with the fewest set neighbors.
+    fn largest_void(&self, pattern: &[bool]) -> usize {
+        self.find(pattern, false, |candidate, best| candidate < best)
+    }
+
+    fn find(
+        &self,
+        pattern: &[bool],
+        state: bool,
+        better: impl Fn(f32, f32) -> bool,
+    ) -> usize {
+        let mut best: Option<usize> = None;
+        for (index, set) in pattern.iter().enumerate() {
+            if *set != state {
+                continue;
+            }
+            match best {
+                Some(current)
+                    if !better(self.values[index], self.values[current]) => {}
+                _ => best = Some(index),
+            }
+        }
+        best.expect("the pattern has a pixel in the requested state")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_value_should_occur_equally_often() {
+        let tile = blue_noise_tile(16, 7);
+        let mut histogram = [0; 256];
+        for value in &tile {
+            histogram[*value as usize] += 1;
+        }
+        assert!(histogram.iter().all(|count| *count == 1));
+    }
+
+    #[test]
+    fn tiles_should_be_deterministic() {
+        assert_eq!(blue_noise_tile(8, 3), blue_noise_tile(8, 3));
+        assert_ne!(blue_noise_tile(8, 3), blue_noise_tile(8, 4));
+    }
+
+    #[test]
+    fn neighbors_should_rarely_share_extremes() {
+        // blue noise has no clumps, so dark pixels aren't next to each other
+        let size = 16;
+        let tile = blue_noise_tile(size, 11);
+        let dark = |x: usize, y: usize| {
+            tile[(y % size as usize) * size as usize + (x % size as usize)] < 32
+        };
+        let mut adjacent = 0;
+        for y in 0..size as usize {
+            for x in 0..size as usize {
+                if dark(x, y) && (dark(x + 1, y) || dark(x, y + 1)) {
+                    adjacent += 1;
+                }
+            }
+        }
+        assert!(
+            adjacent <= 2,
+            "{} dark pixels have dark neighbors",
+            adjacent
+        );
+    }
+}
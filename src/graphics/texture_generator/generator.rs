@@ -0,0 +1,212 @@
+use super::{
+    blue_noise::{blue_noise_tile, MAX_TILE_SIZE},
+    Generator,
+};
+
+use crate::noise::{Fbm, Noise2d, Rng};
+
+use ash::vk;
+
+impl Generator {
+    /// The format of the generated texture. Noise holds data rather than
+    /// colors, so it isn't srgb encoded.
+    pub fn format(&self) -> vk::Format {
+        match self {
+            Generator::WhiteNoise { .. }
+            | Generator::BlueNoise { .. }
+            | Generator::Bayer { .. }
+            | Generator::Noise { .. } => vk::Format::R8G8B8A8_UNORM,
+            _ => vk::Format::R8G8B8A8_SRGB,
+        }
+    }
+
+    /// Generate tightly packed rgba8 pixels, row by row.
+    pub fn pixels(&self, width: u32, height: u32) -> Vec<u8> {
+        let pixel_count = (width * height) as usize;
+        let mut pixels = Vec::with_capacity(pixel_count * 4);
+        match *self {
+            Generator::Checkerboard { cell_size } => {
+                let cell_size = cell_size.max(1);
+                for y in 0..height {
+                    for x in 0..width {
+                        let white =
+                            ((x / cell_size) + (y / cell_size)) % 2 == 0;
+                        pixels.extend_from_slice(&gray(if white {
+                            255
+                        } else {
+                            0
+                        }));
+                    }
+                }
+            }
+            Generator::WhiteNoise { seed } => {
+                let mut random = Rng::new(seed);
+                for _ in 0..pixel_count {
+                    pixels.extend_from_slice(&gray(random.next_u64() as u8));
+                }
+            }
+            Generator::BlueNoise { seed } => {
+                let tile_size = width.max(height).clamp(1, MAX_TILE_SIZE);
+                let tile = blue_noise_tile(tile_size, seed);
+                for y in 0..height {
+                    for x in 0..width {
+                        let index =
+                            (y % tile_size) * tile_size + (x % tile_size);
+                        pixels.extend_from_slice(&gray(tile[index as usize]));
+                    }
+                }
+            }
+            Generator::Bayer { size } => {
+                let size = bayer_size(size);
+                for y in 0..height {
+                    for x in 0..width {
+                        let threshold = bayer_threshold(size, x, y);
+                        pixels.extend_from_slice(&gray(threshold));
+                    }
+                }
+            }
+            Generator::LinearGradient { from, to } => {
+                let row: Vec<u8> = (0..width)
+                    .flat_map(|x| {
+                        let t = if width > 1 {
+                            x as f32 / (width - 1) as f32
+                        } else {
+                            0.0
+                        };
+                        (0..4).map(move |channel| {
+                            let a = from[channel] as f32;
+                            let b = to[channel] as f32;
+                            (a + (b - a) * t).round() as u8
+                        })
+                    })
+                    .collect();
+                for _ in 0..height {
+                    pixels.extend_from_slice(&row);
+                }
+            }
+            Generator::Noise {
+                kind,
+                seed,
+                period,
+                octaves,
+            } => {
+                let fbm = Fbm::new(kind.build(seed), octaves.max(1));
+                let period = period.max(1.0);
+                for y in 0..height {
+                    for x in 0..width {
+                        let value = fbm.sample(
+                            (x as f32 + 0.5) / period,
+                            (y as f32 + 0.5) / period,
+                        );
+                        let value = ((value + 1.0) * 127.5).round();
+                        pixels.extend_from_slice(&gray(value as u8));
+                    }
+                }
+            }
+        }
+        pixels
+    }
+}
+
+/// The largest Bayer matrix which is generated. 16x16 already has a
+/// threshold for every 8 bit gray value.
+const MAX_BAYER_SIZE: u32 = 16;
+
+/// Round a requested Bayer matrix size to a supported power of two.
+fn bayer_size(size: u32) -> u32 {
+    size.clamp(2, MAX_BAYER_SIZE).next_power_of_two()
+}
+
+/// The threshold for a pixel in a Bayer matrix, scaled to a gray value.
+///
+/// Each bit of the coordinates picks a quadrant of the recursive 2x2
+/// pattern `[0 2; 3 1]`, with the lowest bits being the most significant.
+fn bayer_threshold(size: u32, x: u32, y: u32) -> u8 {
+    let bits = size.trailing_zeros();
+    let mut index = 0;
+    for bit in 0..bits {
+        let (x, y) = ((x >> bit) & 1, (y >> bit) & 1);
+        let quadrant = ((x ^ y) << 1) | y;
+        index |= quadrant << (2 * (bits - 1 - bit));
+    }
+    let cells = (size * size) as f32;
+    ((index as f32 + 0.5) / cells * 255.0).round() as u8
+}
+
+/// An opaque gray pixel.
+fn gray(value: u8) -> [u8; 4] {
+    [value, value, value, 255]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::noise::NoiseKind;
+
+    #[test]
+    fn checkerboard_cells_should_alternate() {
+        let pixels = Generator::Checkerboard { cell_size: 2 }.pixels(4, 4);
+        let at = |x: usize, y: usize| pixels[(y * 4 + x) * 4];
+        assert_eq!(at(0, 0), 255);
+        assert_eq!(at(1, 1), 255);
+        assert_eq!(at(2, 0), 0);
+        assert_eq!(at(0, 2), 0);
+        assert_eq!(at(2, 2), 255);
+    }
+
+    #[test]
+    fn gradients_should_reach_both_colors() {
+        let from = [0, 0, 0, 255];
+        let to = [255, 128, 0, 255];
+        let pixels = Generator::LinearGradient { from, to }.pixels(3, 2);
+        assert_eq!(&pixels[0..4], &from);
+        assert_eq!(&pixels[4..8], &[128, 64, 0, 255]);
+        assert_eq!(&pixels[8..12], &to);
+        assert_eq!(&pixels[0..12], &pixels[12..24]);
+    }
+
+    #[test]
+    fn white_noise_should_depend_on_the_seed() {
+        let first = Generator::WhiteNoise { seed: 1 }.pixels(8, 8);
+        assert_eq!(first, Generator::WhiteNoise { seed: 1 }.pixels(8, 8));
+        assert_ne!(first, Generator::WhiteNoise { seed: 2 }.pixels(8, 8));
+    }
+
+    #[test]
+    fn noise_should_depend_on_the_seed_and_kind() {
+        let noise = |kind, seed| {
+            Generator::Noise {
+                kind,
+                seed,
+                period: 8.0,
+                octaves: 3,
+            }
+            .pixels(16, 16)
+        };
+        let first = noise(NoiseKind::Perlin, 1);
+        assert_eq!(first, noise(NoiseKind::Perlin, 1));
+        assert_ne!(first, noise(NoiseKind::Perlin, 2));
+        assert_ne!(first, noise(NoiseKind::Simplex, 1));
+        assert!(first.chunks(4).all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn bayer_thresholds_should_follow_the_recursive_pattern() {
+        let order = [0, 8, 2, 10, 12, 4, 14, 6, 3, 11, 1, 9, 15, 7, 13, 5];
+        for (cell, index) in order.iter().enumerate() {
+            let (x, y) = (cell as u32 % 4, cell as u32 / 4);
+            let expected = ((*index as f32 + 0.5) / 16.0 * 255.0).round();
+            assert_eq!(bayer_threshold(4, x, y), expected as u8);
+        }
+        let pixels = Generator::Bayer { size: 3 }.pixels(8, 1);
+        assert_eq!(&pixels[0..16], &pixels[16..32]);
+    }
+
+    #[test]
+    fn blue_noise_should_repeat_past_the_tile_size() {
+        let width = MAX_TILE_SIZE + 4;
+        let pixels = Generator::BlueNoise { seed: 5 }.pixels(width, 1);
+        let tile = MAX_TILE_SIZE as usize * 4;
+        assert_eq!(&pixels[0..16], &pixels[tile..tile + 16]);
+    }
+}
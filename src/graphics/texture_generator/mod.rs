@@ -0,0 +1,45 @@
+//! Procedural textures which are constantly needed for dithering and shader
+//! effects.
+//!
+//! Textures are generated on the cpu and uploaded with
+//! `Graphics::generate_texture`. Generation is deterministic, so the same
+//! generator and size always produce the same pixels.
+
+mod blue_noise;
+mod generator;
+
+use crate::noise::NoiseKind;
+
+/// A procedural texture.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Generator {
+    /// Alternating black and white squares, `cell_size` pixels wide.
+    Checkerboard { cell_size: u32 },
+
+    /// Independent uniformly distributed gray values.
+    WhiteNoise { seed: u64 },
+
+    /// Gray values with no low frequency structure, ideal for dithering.
+    ///
+    /// Blue noise is generated as a tile of at most 64x64 pixels which
+    /// repeats seamlessly across larger textures.
+    BlueNoise { seed: u64 },
+
+    /// A Bayer matrix of ordered dithering thresholds which repeats every
+    /// `size` pixels. The size is rounded up to a power of two, at most 16.
+    Bayer { size: u32 },
+
+    /// A horizontal blend from the `from` color on the left edge to the `to`
+    /// color on the right edge. Colors are srgb rgba values.
+    LinearGradient { from: [u8; 4], to: [u8; 4] },
+
+    /// Fractal coherent noise, with features about `period` pixels apart.
+    /// Each octave adds detail at twice the frequency and half the
+    /// amplitude.
+    Noise {
+        kind: NoiseKind,
+        seed: u64,
+        period: f32,
+        octaves: u32,
+    },
+}
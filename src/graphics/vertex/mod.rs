@@ -1,14 +1,45 @@
+mod uv_transform;
+
 use ash::vk;
 use memoffset::offset_of;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vertex2d {
     pub pos: [f32; 2],
     pub uv: [f32; 2],
     pub rgba: [f32; 4],
 }
 
+/// Flip, tile, and scroll texture coordinates without authoring them by
+/// hand.
+///
+/// Flips mirror the coordinates about the center of the range they cover,
+/// so a flipped sprite still samples its own cell of a sprite sheet. Tiling
+/// and offsets are applied after flipping. Tiling past a single copy relies
+/// on the sampler repeating the texture, like the atlas's default sampler,
+/// so it repeats the whole texture rather than one cell.
+///
+/// Transforms are resolved into the vertices when a batch is built, see
+/// `Batch::transform_uvs`. Scrolling textures are animated by building the
+/// batch again each frame with a new offset.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct UvTransform {
+    /// Mirror the texture horizontally.
+    pub flip_x: bool,
+
+    /// Mirror the texture vertically.
+    pub flip_y: bool,
+
+    /// The number of times the texture repeats along u and v.
+    pub tiling: [f32; 2],
+
+    /// Added to every coordinate after tiling, in texture coordinates.
+    pub offset: [f32; 2],
+}
+
 impl Default for Vertex2d {
     /// A complete vertex, colored white.
     fn default() -> Self {
@@ -0,0 +1,149 @@
+use super::{UvTransform, Vertex2d};
+
+impl Default for UvTransform {
+    /// Leave texture coordinates unchanged.
+    fn default() -> Self {
+        Self {
+            flip_x: false,
+            flip_y: false,
+            tiling: [1.0, 1.0],
+            offset: [0.0, 0.0],
+        }
+    }
+}
+
+impl UvTransform {
+    /// A transform which mirrors the texture horizontally and/or vertically.
+    pub fn flipped(flip_x: bool, flip_y: bool) -> Self {
+        Self {
+            flip_x,
+            flip_y,
+            ..Default::default()
+        }
+    }
+
+    /// A transform which repeats the texture along u and v.
+    pub fn tiled(u: f32, v: f32) -> Self {
+        Self {
+            tiling: [u, v],
+            ..Default::default()
+        }
+    }
+
+    /// The same transform, scrolled by a velocity in texture coordinates per
+    /// second after some number of seconds.
+    ///
+    /// The offset wraps to stay within one repeat of the texture so it
+    /// doesn't lose precision as time passes.
+    pub fn scrolled(self, velocity: [f32; 2], seconds: f32) -> Self {
+        let scroll = |offset: f32, speed: f32| {
+            let scrolled = offset + speed * seconds;
+            scrolled - scrolled.floor()
+        };
+        Self {
+            offset: [
+                scroll(self.offset[0], velocity[0]),
+                scroll(self.offset[1], velocity[1]),
+            ],
+            ..self
+        }
+    }
+
+    /// True when the transform leaves coordinates unchanged.
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Transform a single coordinate, flipping about the center of the
+    /// range being transformed.
+    pub fn apply(&self, uv: [f32; 2], center: [f32; 2]) -> [f32; 2] {
+        let flip = |value: f32, center: f32, flip: bool| {
+            if flip {
+                2.0 * center - value
+            } else {
+                value
+            }
+        };
+        [
+            flip(uv[0], center[0], self.flip_x) * self.tiling[0]
+                + self.offset[0],
+            flip(uv[1], center[1], self.flip_y) * self.tiling[1]
+                + self.offset[1],
+        ]
+    }
+
+    /// Transform every vertex's texture coordinates, flipping about the
+    /// center of the range they cover.
+    pub fn apply_to_vertices(&self, vertices: &mut [Vertex2d]) {
+        if self.is_identity() || vertices.is_empty() {
+            return;
+        }
+        let (mut min, mut max) = ([f32::INFINITY; 2], [f32::NEG_INFINITY; 2]);
+        for vertex in vertices.iter() {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(vertex.uv[axis]);
+                max[axis] = max[axis].max(vertex.uv[axis]);
+            }
+        }
+        let center = [(min[0] + max[0]) * 0.5, (min[1] + max[1]) * 0.5];
+        for vertex in vertices.iter_mut() {
+            vertex.uv = self.apply(vertex.uv, center);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sprite_cell() -> Vec<Vertex2d> {
+        [[0.25, 0.5], [0.5, 0.5], [0.5, 0.75], [0.25, 0.75]]
+            .iter()
+            .map(|uv| Vertex2d {
+                uv: *uv,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn uvs(vertices: &[Vertex2d]) -> Vec<[f32; 2]> {
+        vertices.iter().map(|vertex| vertex.uv).collect()
+    }
+
+    #[test]
+    fn flips_should_stay_within_the_sprite_cell() {
+        let mut vertices = sprite_cell();
+        UvTransform::flipped(true, false).apply_to_vertices(&mut vertices);
+        assert_eq!(
+            uvs(&vertices),
+            vec![[0.5, 0.5], [0.25, 0.5], [0.25, 0.75], [0.5, 0.75]]
+        );
+    }
+
+    #[test]
+    fn tiling_and_offset_should_follow_the_flip() {
+        let transform = UvTransform {
+            flip_y: true,
+            tiling: [4.0, 2.0],
+            offset: [0.5, 0.0],
+            ..Default::default()
+        };
+        assert_eq!(transform.apply([0.25, 0.0], [0.5, 0.5]), [1.5, 2.0]);
+    }
+
+    #[test]
+    fn scrolled_should_wrap_the_offset() {
+        let transform =
+            UvTransform::tiled(8.0, 8.0).scrolled([0.5, -0.25], 3.0);
+        assert_eq!(transform.offset, [0.5, 0.25]);
+        assert_eq!(transform.tiling, [8.0, 8.0]);
+    }
+
+    #[test]
+    fn identity_should_leave_vertices_unchanged() {
+        let mut vertices = sprite_cell();
+        UvTransform::default().apply_to_vertices(&mut vertices);
+        assert_eq!(vertices, sprite_cell());
+        assert!(!UvTransform::tiled(2.0, 1.0).is_identity());
+    }
+}
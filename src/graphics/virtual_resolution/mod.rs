@@ -0,0 +1,65 @@
+//! A fixed virtual resolution which adapts to any window.
+//!
+//! Game-like applications are usually designed for one resolution, like
+//! 320x180 for pixel art. With a virtual resolution set, see
+//! `Graphics::set_virtual_resolution`, every camera keeps the design's
+//! aspect ratio and screen space layers are measured in virtual pixels no
+//! matter the window's size. The `AspectPolicy` decides how the design is
+//! fit to a window with a different shape.
+//!
+//! Policies are applied with the layer pass's viewport and scissor, so they
+//! cost nothing extra to draw. Any part of the window outside of the design
+//! is cleared with the bar color.
+
+mod viewport;
+
+/// How a virtual resolution is fit to a window with a different aspect
+/// ratio.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum AspectPolicy {
+    /// Fill the window and distort the design to fit.
+    Stretch,
+
+    /// Show the whole design as large as possible, with bars on the sides
+    /// which don't fit.
+    Letterbox,
+
+    /// Fill the window without distortion, cutting off the edges of the
+    /// design which don't fit.
+    Crop,
+
+    /// Like letterbox, but only scale by whole numbers so pixel art stays
+    /// crisp. Windows smaller than the design fall back to letterboxing.
+    IntegerScale,
+}
+
+/// A design resolution and the policy used to fit it to the window.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct VirtualResolution {
+    /// The design's width in virtual pixels.
+    pub width: u32,
+
+    /// The design's height in virtual pixels.
+    pub height: u32,
+
+    /// How the design is fit to the window.
+    pub policy: AspectPolicy,
+
+    /// The color of any part of the window outside of the design.
+    pub bar_color: [f32; 4],
+}
+
+/// Where the design is drawn in a framebuffer.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DesignViewport {
+    /// The offset of the design's top left corner in framebuffer pixels.
+    /// Negative when the design is cropped.
+    pub x: f32,
+    pub y: f32,
+
+    /// The size of the design in framebuffer pixels.
+    pub width: f32,
+    pub height: f32,
+}
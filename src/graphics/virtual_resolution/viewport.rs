@@ -0,0 +1,246 @@
+use super::{AspectPolicy, DesignViewport, VirtualResolution};
+
+use ash::vk;
+use nalgebra as na;
+
+impl VirtualResolution {
+    /// A design resolution fit to the window with a policy. Bars are black.
+    pub fn new(width: u32, height: u32, policy: AspectPolicy) -> Self {
+        Self {
+            width,
+            height,
+            policy,
+            bar_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// Set the color of any part of the window outside of the design.
+    pub fn with_bar_color(mut self, bar_color: [f32; 4]) -> Self {
+        self.bar_color = bar_color;
+        self
+    }
+
+    /// The design's aspect ratio, which every camera is fit to.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.width.max(1) as f32 / self.height.max(1) as f32
+    }
+
+    /// Where the design is drawn in a framebuffer with the given size.
+    pub fn viewport(&self, extent: vk::Extent2D) -> DesignViewport {
+        let framebuffer_width = extent.width as f32;
+        let framebuffer_height = extent.height as f32;
+        let width_scale = framebuffer_width / self.width.max(1) as f32;
+        let height_scale = framebuffer_height / self.height.max(1) as f32;
+        let scale = match self.policy {
+            AspectPolicy::Stretch => {
+                return DesignViewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: framebuffer_width,
+                    height: framebuffer_height,
+                };
+            }
+            AspectPolicy::Letterbox => width_scale.min(height_scale),
+            AspectPolicy::Crop => width_scale.max(height_scale),
+            AspectPolicy::IntegerScale => {
+                let scale = width_scale.min(height_scale);
+                if scale >= 1.0 {
+                    scale.floor()
+                } else {
+                    scale
+                }
+            }
+        };
+        let width = self.width as f32 * scale;
+        let height = self.height as f32 * scale;
+
+        // whole pixel offsets keep integer scaled pixels aligned
+        DesignViewport {
+            x: ((framebuffer_width - width) / 2.0).floor(),
+            y: ((framebuffer_height - height) / 2.0).floor(),
+            width,
+            height,
+        }
+    }
+
+    /// The part of a framebuffer which the design covers.
+    pub fn scissor(&self, extent: vk::Extent2D) -> vk::Rect2D {
+        let viewport = self.viewport(extent);
+        let left = viewport.x.max(0.0).round() as u32;
+        let top = viewport.y.max(0.0).round() as u32;
+        let right = ((viewport.x + viewport.width).round().max(0.0) as u32)
+            .min(extent.width);
+        let bottom = ((viewport.y + viewport.height).round().max(0.0) as u32)
+            .min(extent.height);
+        vk::Rect2D {
+            offset: vk::Offset2D {
+                x: left as i32,
+                y: top as i32,
+            },
+            extent: vk::Extent2D {
+                width: right.saturating_sub(left),
+                height: bottom.saturating_sub(top),
+            },
+        }
+    }
+
+    /// The parts of a framebuffer outside of the design, which are cleared
+    /// with the bar color.
+    pub fn bars(&self, extent: vk::Extent2D) -> Vec<vk::Rect2D> {
+        let scissor = self.scissor(extent);
+        let left = scissor.offset.x as u32;
+        let top = scissor.offset.y as u32;
+        let right = left + scissor.extent.width;
+        let bottom = top + scissor.extent.height;
+        let rect = |x: u32, y: u32, width: u32, height: u32| vk::Rect2D {
+            offset: vk::Offset2D {
+                x: x as i32,
+                y: y as i32,
+            },
+            extent: vk::Extent2D { width, height },
+        };
+        let bars = vec![
+            rect(0, 0, extent.width, top),
+            rect(0, bottom, extent.width, extent.height - bottom),
+            rect(0, top, left, bottom - top),
+            rect(right, top, extent.width - right, bottom - top),
+        ];
+        bars.into_iter()
+            .filter(|bar| bar.extent.width > 0 && bar.extent.height > 0)
+            .collect()
+    }
+
+    /// Convert a point in framebuffer pixels to virtual pixels. Points
+    /// outside of the design are outside of 0..width and 0..height.
+    pub fn to_virtual(
+        &self,
+        extent: vk::Extent2D,
+        point: &na::Point2<f32>,
+    ) -> na::Point2<f32> {
+        let viewport = self.viewport(extent);
+        na::Point2::new(
+            (point.x - viewport.x) * self.width as f32
+                / viewport.width.max(1.0),
+            (point.y - viewport.y) * self.height as f32
+                / viewport.height.max(1.0),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn extent(width: u32, height: u32) -> vk::Extent2D {
+        vk::Extent2D { width, height }
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> vk::Rect2D {
+        vk::Rect2D {
+            offset: vk::Offset2D { x, y },
+            extent: vk::Extent2D { width, height },
+        }
+    }
+
+    #[test]
+    fn stretch_should_fill_the_framebuffer() {
+        let resolution =
+            VirtualResolution::new(320, 180, AspectPolicy::Stretch);
+        let viewport = resolution.viewport(extent(800, 800));
+
+        assert_eq!(
+            viewport,
+            DesignViewport {
+                x: 0.0,
+                y: 0.0,
+                width: 800.0,
+                height: 800.0
+            }
+        );
+        assert!(resolution.bars(extent(800, 800)).is_empty());
+    }
+
+    #[test]
+    fn letterbox_should_add_bars_above_and_below() {
+        let resolution =
+            VirtualResolution::new(320, 180, AspectPolicy::Letterbox);
+        let viewport = resolution.viewport(extent(640, 640));
+
+        assert_eq!(viewport.width, 640.0);
+        assert_eq!(viewport.height, 360.0);
+        assert_eq!(viewport.y, 140.0);
+        assert_eq!(
+            resolution.bars(extent(640, 640)),
+            vec![rect(0, 0, 640, 140), rect(0, 500, 640, 140)]
+        );
+    }
+
+    #[test]
+    fn letterbox_should_add_bars_on_the_sides() {
+        let resolution =
+            VirtualResolution::new(100, 100, AspectPolicy::Letterbox);
+
+        assert_eq!(
+            resolution.bars(extent(300, 100)),
+            vec![rect(0, 0, 100, 100), rect(200, 0, 100, 100)]
+        );
+    }
+
+    #[test]
+    fn crop_should_cover_the_framebuffer() {
+        let resolution = VirtualResolution::new(100, 100, AspectPolicy::Crop);
+        let viewport = resolution.viewport(extent(300, 100));
+
+        assert_eq!(viewport.x, 0.0);
+        assert_eq!(viewport.y, -100.0);
+        assert_eq!(viewport.width, 300.0);
+        assert_eq!(viewport.height, 300.0);
+        assert_eq!(resolution.scissor(extent(300, 100)), rect(0, 0, 300, 100));
+        assert!(resolution.bars(extent(300, 100)).is_empty());
+    }
+
+    #[test]
+    fn integer_scale_should_round_down() {
+        let resolution =
+            VirtualResolution::new(320, 180, AspectPolicy::IntegerScale);
+        let viewport = resolution.viewport(extent(1000, 600));
+
+        assert_eq!(viewport.width, 960.0);
+        assert_eq!(viewport.height, 540.0);
+        assert_eq!(viewport.x, 20.0);
+        assert_eq!(viewport.y, 30.0);
+        assert_eq!(resolution.bars(extent(1000, 600)).len(), 4);
+    }
+
+    #[test]
+    fn integer_scale_should_letterbox_small_windows() {
+        let resolution =
+            VirtualResolution::new(320, 180, AspectPolicy::IntegerScale);
+        let viewport = resolution.viewport(extent(160, 160));
+
+        assert_eq!(viewport.width, 160.0);
+        assert_eq!(viewport.height, 90.0);
+    }
+
+    #[test]
+    fn to_virtual_should_undo_the_viewport() {
+        let resolution =
+            VirtualResolution::new(100, 100, AspectPolicy::Letterbox);
+        let framebuffer = extent(300, 200);
+
+        assert_eq!(
+            resolution.to_virtual(framebuffer, &na::Point2::new(50.0, 0.0)),
+            na::Point2::new(0.0, 0.0)
+        );
+        assert_eq!(
+            resolution.to_virtual(framebuffer, &na::Point2::new(150.0, 100.0)),
+            na::Point2::new(50.0, 50.0)
+        );
+        assert!(
+            resolution
+                .to_virtual(framebuffer, &na::Point2::new(10.0, 0.0))
+                .x
+                < 0.0
+        );
+    }
+}
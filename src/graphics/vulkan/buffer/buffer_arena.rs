@@ -0,0 +1,270 @@
+use super::{transfer::upload_with_staging, Buffer, StaticBuffer};
+use crate::graphics::vulkan::{
+    device_allocator::{Allocation, DeviceAllocator, MemUnit, Suballocator},
+    Device,
+};
+
+use anyhow::{bail, Context, Result};
+use ash::vk;
+use std::{ptr::null_mut, sync::Arc};
+
+/// A range of bytes inside a shared buffer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct BufferSlice {
+    /// The buffer which holds the slice. Valid until the slice is freed.
+    pub buffer: vk::Buffer,
+
+    /// The offset of the slice's first byte in the buffer.
+    pub offset: u64,
+
+    /// The size of the slice in bytes, rounded up to the arena's alignment.
+    pub size: u64,
+}
+
+/// Hands out slices of a few large buffers, so many small meshes don't each
+/// need their own buffer and memory allocation.
+///
+/// Every buffer in the arena has the same usage and memory properties. A new
+/// buffer is created when no existing one has room, and buffers are destroyed
+/// when their last slice is freed.
+pub struct BufferArena {
+    device: Arc<Device>,
+    usage: vk::BufferUsageFlags,
+    properties: vk::MemoryPropertyFlags,
+    block_size: u64,
+    alignment: u64,
+    blocks: Vec<ArenaBlock>,
+}
+
+/// One buffer in the arena and the slices taken from it.
+struct ArenaBlock {
+    buffer: StaticBuffer,
+
+    /// The size of the vulkan buffer, which can be smaller than its memory.
+    size: u64,
+
+    slices: Suballocator,
+
+    /// The mapped buffer for host-visible arenas, otherwise null.
+    mapped: *mut u8,
+}
+
+impl BufferArena {
+    /// Create an arena which allocates buffers of `block_size` bytes.
+    ///
+    /// Every slice starts at a multiple of `alignment`, which should cover
+    /// things like the vertex size or minStorageBufferOffsetAlignment.
+    pub fn new(
+        device: Arc<Device>,
+        usage: vk::BufferUsageFlags,
+        properties: vk::MemoryPropertyFlags,
+        block_size: MemUnit,
+        alignment: u64,
+    ) -> Result<Self> {
+        let alignment = alignment.max(1);
+        let block_size = round_up(block_size.to_bytes(), alignment);
+        if block_size == 0 {
+            bail!("buffer arenas need a non-empty block size");
+        }
+        let usage =
+            if properties.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
+                usage
+            } else {
+                usage | vk::BufferUsageFlags::TRANSFER_DST
+            };
+        Ok(Self {
+            device,
+            usage,
+            properties,
+            block_size,
+            alignment,
+            blocks: vec![],
+        })
+    }
+
+    /// Take a slice with room for at least `size` bytes.
+    ///
+    /// Slices larger than the block size get a buffer of their own.
+    pub fn allocate(&mut self, size: u64) -> Result<BufferSlice> {
+        let size = round_up(size.max(1), self.alignment);
+        for block in &mut self.blocks {
+            if let Some(slice) = block.take(size, self.alignment) {
+                return Ok(slice);
+            }
+        }
+        let block_size = self.block_size.max(size);
+        let mut block = ArenaBlock::new(
+            StaticBuffer::create(
+                self.device.clone(),
+                self.usage,
+                self.properties,
+                block_size,
+            )?,
+            block_size,
+            self.properties,
+        )?;
+        let slice = block
+            .take(size, self.alignment)
+            .context("a new arena buffer has no room for the slice")?;
+        self.blocks.push(block);
+        Ok(slice)
+    }
+
+    /// Return a slice to the arena.
+    ///
+    /// # Safety
+    ///
+    /// - the slice must not be in use by the gpu
+    /// - the slice must have been allocated by this arena, and only freed
+    ///   once
+    pub unsafe fn free(&mut self, slice: BufferSlice) -> Result<()> {
+        let index = self
+            .blocks
+            .iter()
+            .position(|block| block.buffer.raw() == slice.buffer)
+            .context("this arena did not allocate the slice")?;
+        let block = &mut self.blocks[index];
+        block.slices.free(&slice_allocation(slice))?;
+        if block.slices.is_empty() {
+            self.blocks.swap_remove(index);
+        }
+        Ok(())
+    }
+
+    /// Copy data into the start of a slice.
+    ///
+    /// Host-visible arenas are written directly, other arenas go through a
+    /// staging buffer and wait for the copy to finish.
+    ///
+    /// # Safety
+    ///
+    /// - the slice must not be in use by the gpu
+    pub unsafe fn write<T>(&self, slice: BufferSlice, data: &[T]) -> Result<()>
+    where
+        T: Sized + Copy,
+    {
+        let byte_size = std::mem::size_of_val(data) as u64;
+        if byte_size > slice.size {
+            bail!(
+                "unable to write {} bytes into a {} byte slice",
+                byte_size,
+                slice.size
+            );
+        }
+        let block = self
+            .blocks
+            .iter()
+            .find(|block| block.buffer.raw() == slice.buffer)
+            .context("this arena did not allocate the slice")?;
+
+        if block.mapped.is_null() {
+            return upload_with_staging(
+                &self.device,
+                slice.buffer,
+                slice.offset,
+                data,
+            );
+        }
+
+        std::ptr::copy_nonoverlapping(
+            data.as_ptr() as *const u8,
+            block.mapped.add(slice.offset as usize),
+            byte_size as usize,
+        );
+        if !self
+            .properties
+            .contains(vk::MemoryPropertyFlags::HOST_COHERENT)
+        {
+            self.device.flush_allocation(
+                block.buffer.allocation(),
+                slice.offset,
+                byte_size,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The number of vulkan buffers owned by the arena.
+    pub fn buffer_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// The total size of every buffer in the arena.
+    pub fn capacity(&self) -> u64 {
+        self.blocks.iter().map(|block| block.size).sum()
+    }
+}
+
+impl ArenaBlock {
+    fn new(
+        buffer: StaticBuffer,
+        size: u64,
+        properties: vk::MemoryPropertyFlags,
+    ) -> Result<Self> {
+        let mapped =
+            if properties.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
+                unsafe { buffer.device.map_allocation(buffer.allocation())? }
+            } else {
+                null_mut()
+            };
+
+        // Slices are tracked as offsets into the buffer, not the memory.
+        let mut whole_buffer = Allocation::null();
+        whole_buffer.byte_size = size;
+
+        Ok(Self {
+            slices: Suballocator::new(whole_buffer),
+            buffer,
+            size,
+            mapped,
+        })
+    }
+
+    /// Take a slice from this block's buffer, if there is room.
+    fn take(&mut self, size: u64, alignment: u64) -> Option<BufferSlice> {
+        let allocation = unsafe {
+            self.slices.allocate(
+                vk::MemoryAllocateInfo {
+                    allocation_size: size,
+                    ..Default::default()
+                },
+                alignment,
+            )
+        }
+        .ok()?;
+        Some(BufferSlice {
+            buffer: unsafe { self.buffer.raw() },
+            offset: allocation.offset,
+            size: allocation.byte_size,
+        })
+    }
+}
+
+impl Drop for ArenaBlock {
+    /// Unmap the memory before the buffer frees it.
+    fn drop(&mut self) {
+        if !self.mapped.is_null() {
+            unsafe {
+                self.buffer
+                    .device
+                    .unmap_allocation(self.buffer.allocation());
+            }
+        }
+    }
+}
+
+/// The suballocator's view of a slice.
+fn slice_allocation(slice: BufferSlice) -> Allocation {
+    let mut allocation = Allocation::null();
+    allocation.offset = slice.offset;
+    allocation.byte_size = slice.size;
+    allocation
+}
+
+/// Round a size up to the next multiple of the alignment.
+fn round_up(size: u64, alignment: u64) -> u64 {
+    match size % alignment {
+        0 => size,
+        remainder => size + alignment - remainder,
+    }
+}
@@ -1,18 +1,36 @@
 use super::{Buffer, StaticBuffer};
 use crate::graphics::vulkan::{device_allocator::Allocation, Device};
 
-use anyhow::Result;
-use ash::{version::DeviceV1_0, vk};
-use std::sync::Arc;
+use anyhow::{bail, Result};
+use ash::vk;
+use std::{ptr::null_mut, sync::Arc};
 
 /// A CPU-accessible buffer.
 ///
 /// Data is allocated directly, so every instance of this buffer contributes
 /// to the driver-specified limit on the number of allocations supported by
 /// the device.
+///
+/// The buffer grows to the next power of two when written data doesn't fit,
+/// so buffers which are rewritten each frame settle on a size quickly rather
+/// than reallocating every time the data grows a little.
+///
+/// The memory stays mapped for the lifetime of the allocation, so writes are
+/// plain copies.
 pub struct CpuBuffer {
     buffer: StaticBuffer,
+
+    /// The first byte of the mapped buffer, null while nothing is allocated.
+    mapped: *mut u8,
+
     written_size: u64,
+    reallocations: u32,
+
+    /// False when writes need to be flushed before the GPU can see them.
+    coherent: bool,
+
+    /// The byte range written by `write_at` since the last flush.
+    unflushed: Option<(u64, u64)>,
 }
 
 impl CpuBuffer {
@@ -21,25 +39,85 @@ impl CpuBuffer {
         device: Arc<Device>,
         usage: vk::BufferUsageFlags,
     ) -> Result<Self> {
+        Self::with_properties(
+            device,
+            usage,
+            vk::MemoryPropertyFlags::HOST_VISIBLE
+                | vk::MemoryPropertyFlags::HOST_COHERENT,
+        )
+    }
+
+    /// Create an empty buffer with specific memory properties, which must
+    /// include HOST_VISIBLE.
+    ///
+    /// HOST_VISIBLE | HOST_CACHED memory is often faster to write large
+    /// amounts of data into. Without HOST_COHERENT, `write_data` flushes on
+    /// its own but writes from `write_at` need a call to `flush`.
+    pub fn with_properties(
+        device: Arc<Device>,
+        usage: vk::BufferUsageFlags,
+        properties: vk::MemoryPropertyFlags,
+    ) -> Result<Self> {
+        if !properties.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
+            bail!("cpu buffers need HOST_VISIBLE memory, not {:?}", properties);
+        }
         Ok(Self {
-            buffer: StaticBuffer::empty(
-                device.clone(),
-                usage,
-                vk::MemoryPropertyFlags::HOST_VISIBLE
-                    | vk::MemoryPropertyFlags::HOST_COHERENT,
-            )?,
+            buffer: StaticBuffer::empty(device, usage, properties)?,
+            mapped: null_mut(),
             written_size: 0,
+            reallocations: 0,
+            coherent: properties
+                .contains(vk::MemoryPropertyFlags::HOST_COHERENT),
+            unflushed: None,
         })
     }
 
+    /// The number of bytes which can be written without reallocating.
+    pub fn capacity(&self) -> u64 {
+        self.buffer.size_in_bytes()
+    }
+
+    /// How many times the underlying GPU memory has been replaced.
+    pub fn reallocation_count(&self) -> u32 {
+        self.reallocations
+    }
+
+    /// Make sure at least `byte_size` bytes can be written without
+    /// reallocating. Written data is kept.
+    ///
+    /// # Safety
+    ///
+    /// - this method can replace both the buffer and the backing memory, so
+    ///   neither resource can be in use when it is called
+    pub unsafe fn reserve(&mut self, byte_size: u64) -> Result<()> {
+        if byte_size > self.capacity() {
+            self.reallocate(byte_size, true)?;
+        }
+        Ok(())
+    }
+
+    /// Shrink the underlying GPU memory to fit the written data. Written data
+    /// is kept.
+    ///
+    /// # Safety
+    ///
+    /// - this method can replace both the buffer and the backing memory, so
+    ///   neither resource can be in use when it is called
+    pub unsafe fn shrink_to_fit(&mut self) -> Result<()> {
+        if self.written_size < self.capacity() {
+            self.reallocate(self.written_size, true)?;
+        }
+        Ok(())
+    }
+
     /// Write the provided data into the vertex buffer.
     ///
     /// Unsafe because this method can replace both the buffer and the backing
     /// memory. It is the responsibility of the application to ensure that
     /// neither resource is being used when this method is called.
     ///
-    /// No explicit flush is required because the memory is allocated as
-    /// HOST_COHERENT.
+    /// The written data is flushed before returning, so no explicit flush is
+    /// required.
     pub unsafe fn write_data<T>(&mut self, data: &[T]) -> Result<()>
     where
         T: Sized + Copy + std::fmt::Debug,
@@ -63,38 +141,136 @@ impl CpuBuffer {
 
         self.resize(total_size as u64)?;
 
-        let allocation = self.buffer.allocation();
-        let mut ptr = self.buffer.device.logical_device.map_memory(
-            allocation.memory,
-            allocation.offset,
-            self.written_size,
-            vk::MemoryMapFlags::empty(),
-        )? as *mut T;
-
+        let mut ptr = self.mapped as *mut T;
         for entry in data_arrays {
             let mapped_slice = std::slice::from_raw_parts_mut(ptr, entry.len());
             mapped_slice.copy_from_slice(entry);
-            ptr = ptr.offset(entry.len() as isize);
+            ptr = ptr.add(entry.len());
         }
 
-        self.buffer
-            .device
-            .logical_device
-            .unmap_memory(self.buffer.allocation().memory);
+        self.unflushed = None;
+        self.flush_range(0, self.written_size)
+    }
+
+    /// Write data at a byte offset without touching the rest of the buffer.
+    /// The buffer grows, keeping its data, when the write doesn't fit.
+    ///
+    /// Writes to memory which isn't HOST_COHERENT aren't visible to the GPU
+    /// until `flush` is called.
+    ///
+    /// # Safety
+    ///
+    /// - this method can replace both the buffer and the backing memory, so
+    ///   neither resource can be in use when it is called
+    /// - the written range must not be in use by the GPU
+    pub unsafe fn write_at<T>(&mut self, offset: u64, data: &[T]) -> Result<()>
+    where
+        T: Sized + Copy,
+    {
+        let byte_size = std::mem::size_of_val(data) as u64;
+        let end = offset + byte_size;
+        if end > self.capacity() {
+            self.reallocate(end.next_power_of_two(), true)?;
+        }
+        std::ptr::copy_nonoverlapping(
+            data.as_ptr() as *const u8,
+            self.mapped.add(offset as usize),
+            byte_size as usize,
+        );
+        self.written_size = self.written_size.max(end);
+        self.unflushed = Some(match self.unflushed {
+            Some((start, stop)) => (start.min(offset), stop.max(end)),
+            None => (offset, end),
+        });
+        Ok(())
+    }
 
+    /// Make every write from `write_at` since the last flush visible to the
+    /// GPU. Does nothing for HOST_COHERENT memory.
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some((start, end)) = self.unflushed.take() {
+            unsafe { self.flush_range(start, end - start)? };
+        }
         Ok(())
     }
 
+    /// Flush a range of the mapped memory when it isn't coherent.
+    unsafe fn flush_range(&self, offset: u64, size: u64) -> Result<()> {
+        if self.coherent || size == 0 {
+            return Ok(());
+        }
+        self.buffer.device.flush_allocation(
+            self.buffer.allocation(),
+            offset,
+            size,
+        )
+    }
+
     /// Update the written-size of the buffer.
     ///
-    /// Reallocate the underlying GPU memory when needed.
+    /// Reallocate the underlying GPU memory when needed. The old contents
+    /// aren't kept because they're about to be overwritten.
     fn resize(&mut self, byte_size: u64) -> Result<()> {
-        if byte_size > self.buffer.size_in_bytes() {
-            self.buffer = self.buffer.allocate(byte_size)?;
+        if byte_size > self.capacity() {
+            self.reallocate(byte_size.next_power_of_two(), false)?;
         }
         self.written_size = byte_size;
         Ok(())
     }
+
+    /// Replace the underlying GPU memory with a mapped buffer of exactly
+    /// `capacity` bytes, optionally copying the written data over.
+    ///
+    /// An empty capacity releases the memory entirely.
+    fn reallocate(&mut self, capacity: u64, keep_data: bool) -> Result<()> {
+        let mut replacement = if capacity == 0 {
+            StaticBuffer::empty(
+                self.buffer.device.clone(),
+                self.buffer.usage,
+                self.buffer.properties,
+            )?
+        } else {
+            self.buffer.allocate(capacity)?
+        };
+        let mapped = if capacity == 0 {
+            null_mut()
+        } else {
+            unsafe {
+                replacement
+                    .device
+                    .map_allocation(replacement.allocation())?
+            }
+        };
+
+        let kept = if keep_data {
+            self.written_size.min(capacity)
+        } else {
+            0
+        };
+        if kept > 0 {
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    self.mapped,
+                    mapped,
+                    kept as usize,
+                );
+            }
+        }
+
+        std::mem::swap(&mut self.buffer, &mut replacement);
+        let old_mapped = std::mem::replace(&mut self.mapped, mapped);
+        if !old_mapped.is_null() {
+            unsafe {
+                replacement
+                    .device
+                    .unmap_allocation(replacement.allocation())
+            };
+        }
+        self.reallocations += 1;
+        self.written_size = kept;
+        self.unflushed = None;
+        unsafe { self.flush_range(0, kept) }
+    }
 }
 
 impl Buffer for CpuBuffer {
@@ -118,3 +294,17 @@ impl Buffer for CpuBuffer {
         self.written_size
     }
 }
+
+impl Drop for CpuBuffer {
+    /// Unmap the memory before the buffer frees it.
+    fn drop(&mut self) {
+        if !self.mapped.is_null() {
+            unsafe {
+                self.buffer
+                    .device
+                    .unmap_allocation(self.buffer.allocation());
+            }
+            self.mapped = null_mut();
+        }
+    }
+}
@@ -1,10 +1,17 @@
+mod buffer_arena;
 mod cpu_buffer;
+mod readback_buffer;
 mod static_buffer;
 mod transfer;
+mod transient_buffer;
 
 pub use self::{
-    cpu_buffer::CpuBuffer, static_buffer::StaticBuffer,
-    transfer::copy_full_buffer,
+    buffer_arena::{BufferArena, BufferSlice},
+    cpu_buffer::CpuBuffer,
+    readback_buffer::ReadbackBuffer,
+    static_buffer::StaticBuffer,
+    transfer::{copy_full_buffer, upload_with_staging},
+    transient_buffer::TransientBuffer,
 };
 
 use ash::vk;
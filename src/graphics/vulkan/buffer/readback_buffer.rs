@@ -0,0 +1,133 @@
+use super::{Buffer, StaticBuffer};
+use crate::graphics::vulkan::{device_allocator::Allocation, Device};
+
+use anyhow::Result;
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+/// A host-visible buffer used as the destination when copying data from the
+/// GPU back to the CPU.
+pub struct ReadbackBuffer {
+    buffer: StaticBuffer,
+}
+
+impl ReadbackBuffer {
+    /// Create a readback buffer which can hold at least `size` bytes.
+    pub fn new(device: Arc<Device>, size: u64) -> Result<Self> {
+        Ok(Self {
+            buffer: StaticBuffer::create(
+                device,
+                vk::BufferUsageFlags::TRANSFER_DST,
+                vk::MemoryPropertyFlags::HOST_VISIBLE
+                    | vk::MemoryPropertyFlags::HOST_COHERENT,
+                size,
+            )?,
+        })
+    }
+
+    /// Record commands which copy the full extent of a color image into the
+    /// start of this buffer, followed by a barrier which makes the data
+    /// visible to the host.
+    ///
+    /// # Safety
+    ///
+    /// - the image must be in the `TRANSFER_SRC_OPTIMAL` layout when the
+    ///   commands execute
+    /// - the buffer must be large enough to hold the whole image
+    /// - the caller must not read the buffer until the commands have finished
+    ///   executing (e.g. by waiting on a fence)
+    pub unsafe fn record_image_copy(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        offset: vk::Offset2D,
+        extent: vk::Extent2D,
+    ) {
+        let logical_device = &self.buffer.device.logical_device;
+        let region = vk::BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_subresource: vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            },
+            image_offset: vk::Offset3D {
+                x: offset.x,
+                y: offset.y,
+                z: 0,
+            },
+            image_extent: vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            },
+        };
+        logical_device.cmd_copy_image_to_buffer(
+            command_buffer,
+            image,
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+            self.buffer.raw(),
+            &[region],
+        );
+
+        let host_barrier = vk::BufferMemoryBarrier {
+            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            dst_access_mask: vk::AccessFlags::HOST_READ,
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            buffer: self.buffer.raw(),
+            offset: 0,
+            size: vk::WHOLE_SIZE,
+            ..Default::default()
+        };
+        logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            vk::PipelineStageFlags::TRANSFER,
+            vk::PipelineStageFlags::HOST,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[host_barrier],
+            &[],
+        );
+    }
+
+    /// Copy the first `byte_count` bytes of the buffer into a new vector.
+    ///
+    /// # Safety
+    ///
+    /// - the caller must ensure that any GPU writes to the buffer have
+    ///   completed
+    pub unsafe fn read_bytes(&self, byte_count: u64) -> Result<Vec<u8>> {
+        let byte_count = byte_count.min(self.buffer.size_in_bytes());
+        let allocation = self.buffer.allocation();
+        let device = &self.buffer.device;
+        let ptr = device.map_allocation(allocation)? as *const u8;
+
+        let bytes =
+            std::slice::from_raw_parts(ptr, byte_count as usize).to_vec();
+
+        device.unmap_allocation(allocation);
+
+        Ok(bytes)
+    }
+}
+
+impl Buffer for ReadbackBuffer {
+    /// The raw buffer handle. Valid for the lifetime of this buffer.
+    unsafe fn raw(&self) -> vk::Buffer {
+        self.buffer.raw()
+    }
+
+    /// The device memory handle. Valid for the lifetime of this buffer.
+    unsafe fn allocation(&self) -> &Allocation {
+        self.buffer.allocation()
+    }
+
+    /// The size, in bytes, of the allocated device memory.
+    fn size_in_bytes(&self) -> u64 {
+        self.buffer.size_in_bytes()
+    }
+}
@@ -1,5 +1,7 @@
-use super::Buffer;
-use crate::graphics::vulkan::{device_allocator::Allocation, Device};
+use super::{transfer::upload_with_staging, Buffer};
+use crate::graphics::vulkan::{
+    device_allocator::Allocation, Device, ExternalMemoryHandle, QueueTransfer,
+};
 
 use anyhow::Result;
 use ash::{version::DeviceV1_0, vk};
@@ -11,8 +13,8 @@ pub struct StaticBuffer {
     raw: vk::Buffer,
     allocation: Allocation,
 
-    usage: vk::BufferUsageFlags,
-    properties: vk::MemoryPropertyFlags,
+    pub(super) usage: vk::BufferUsageFlags,
+    pub(super) properties: vk::MemoryPropertyFlags,
 
     /// the device used to create this buffer
     pub(super) device: Arc<Device>,
@@ -56,12 +58,100 @@ impl StaticBuffer {
         let raw =
             unsafe { device.logical_device.create_buffer(&create_info, None)? };
 
-        let buffer_memory_requirements = unsafe {
-            device.logical_device.get_buffer_memory_requirements(raw)
+        let allocation =
+            unsafe { device.allocate_buffer_memory(raw, properties)? };
+
+        unsafe {
+            device.logical_device.bind_buffer_memory(
+                raw,
+                allocation.memory,
+                allocation.offset,
+            )?;
+        }
+
+        Ok(Self {
+            raw,
+            allocation,
+            usage,
+            properties,
+            device,
+        })
+    }
+
+    /// Create a DEVICE_LOCAL buffer which holds a copy of `data`.
+    ///
+    /// The data is written to a staging buffer and copied on the graphics
+    /// queue, which this device also uses for transfers. The copy has
+    /// finished when this returns, so the buffer can be used right away.
+    /// TRANSFER_DST is added to the usage flags.
+    pub fn from_data<T>(
+        device: Arc<Device>,
+        usage: vk::BufferUsageFlags,
+        data: &[T],
+    ) -> Result<Self>
+    where
+        T: Sized + Copy,
+    {
+        let usage = usage | vk::BufferUsageFlags::TRANSFER_DST;
+        let properties = vk::MemoryPropertyFlags::DEVICE_LOCAL;
+        let size = std::mem::size_of_val(data) as u64;
+        if size == 0 {
+            return Self::empty(device, usage, properties);
+        }
+
+        let buffer = Self::create(device.clone(), usage, properties, size)?;
+        unsafe {
+            // SAFE: the buffer was just created, so nothing is using it
+            upload_with_staging(&device, buffer.raw, 0, data)?;
+        }
+        Ok(buffer)
+    }
+
+    /// Create a buffer which uses memory allocated outside of this library.
+    ///
+    /// `size` must match the size of the external buffer. The caller is
+    /// responsible for synchronizing access with whoever exported the
+    /// memory.
+    pub fn import(
+        device: Arc<Device>,
+        usage: vk::BufferUsageFlags,
+        size: u64,
+        handle: ExternalMemoryHandle,
+    ) -> Result<Self> {
+        let external_create_info = vk::ExternalMemoryBufferCreateInfo {
+            handle_types: handle.handle_type(),
+            ..Default::default()
         };
+        let create_info = vk::BufferCreateInfo {
+            p_next: &external_create_info as *const _ as *const _,
+            size,
+            usage,
+            sharing_mode: vk::SharingMode::EXCLUSIVE,
+            ..Default::default()
+        };
+
+        let raw =
+            unsafe { device.logical_device.create_buffer(&create_info, None)? };
 
         let allocation = unsafe {
-            device.allocate_memory(buffer_memory_requirements, properties)?
+            let buffer_memory_requirements =
+                device.logical_device.get_buffer_memory_requirements(raw);
+            device.import_memory(
+                buffer_memory_requirements,
+                vk::MemoryPropertyFlags::empty(),
+                handle,
+                vk::MemoryDedicatedAllocateInfo {
+                    buffer: raw,
+                    ..Default::default()
+                },
+            )
+        };
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(error) => {
+                unsafe { device.logical_device.destroy_buffer(raw, None) };
+                return Err(error);
+            }
         };
 
         unsafe {
@@ -76,10 +166,54 @@ impl StaticBuffer {
             raw,
             allocation,
             usage,
-            properties,
+            properties: vk::MemoryPropertyFlags::empty(),
             device,
         })
     }
+
+    /// Record the first half of moving the whole buffer to another queue
+    /// family, see `QueueTransfer`.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the
+    ///   transfer's source family
+    pub unsafe fn release_to(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        transfer: &QueueTransfer,
+    ) {
+        transfer.record_buffer_release(
+            &self.device,
+            command_buffer,
+            self.raw,
+            0,
+            vk::WHOLE_SIZE,
+        );
+    }
+
+    /// Record the second half of moving the whole buffer to another queue
+    /// family, see `QueueTransfer`.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the
+    ///   transfer's destination family
+    /// - the release must have been submitted, and this submission must
+    ///   wait for it
+    pub unsafe fn acquire_from(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        transfer: &QueueTransfer,
+    ) {
+        transfer.record_buffer_acquire(
+            &self.device,
+            command_buffer,
+            self.raw,
+            0,
+            vk::WHOLE_SIZE,
+        );
+    }
 }
 
 impl Buffer for StaticBuffer {
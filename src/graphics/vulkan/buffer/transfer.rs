@@ -1,8 +1,9 @@
-use super::Buffer;
-use crate::graphics::vulkan::Device;
+use super::{Buffer, CpuBuffer};
+use crate::graphics::vulkan::{Device, QueueTransfer};
 
 use anyhow::Result;
 use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
 
 /// Write commands to copy the full source buffer to the destination buffer.
 ///
@@ -43,3 +44,77 @@ where
 
     Ok(command_buffer)
 }
+
+/// Copy data into part of a buffer which the host can't write, like
+/// DEVICE_LOCAL memory.
+///
+/// The data is written to a staging buffer and copied on the transfer queue,
+/// then handed to the graphics queue. The copy has finished when this
+/// returns.
+///
+/// # Safety
+///
+/// - the destination range must not be in use by the gpu
+/// - the destination must have TRANSFER_DST usage and be large enough
+pub unsafe fn upload_with_staging<T>(
+    device: &Arc<Device>,
+    dst: vk::Buffer,
+    dst_offset: u64,
+    data: &[T],
+) -> Result<()>
+where
+    T: Sized + Copy,
+{
+    let size = std::mem::size_of_val(data) as u64;
+    if size == 0 {
+        return Ok(());
+    }
+    let mut staging =
+        CpuBuffer::new(device.clone(), vk::BufferUsageFlags::TRANSFER_SRC)?;
+    staging.write_at(0, data)?;
+    staging.flush()?;
+    // the copy is handed to the graphics queue, which may read it anywhere
+    let transfer = QueueTransfer {
+        src_family: device.transfer_queue().family_id,
+        dst_family: device.graphics_queue.family_id,
+        src_stage: vk::PipelineStageFlags::TRANSFER,
+        src_access: vk::AccessFlags::TRANSFER_WRITE,
+        dst_stage: vk::PipelineStageFlags::ALL_COMMANDS,
+        dst_access: vk::AccessFlags::MEMORY_READ,
+        old_layout: vk::ImageLayout::UNDEFINED,
+        new_layout: vk::ImageLayout::UNDEFINED,
+    };
+    device.sync_transfer_commands(|command_buffer| {
+        device.logical_device.cmd_copy_buffer(
+            command_buffer,
+            staging.raw(),
+            dst,
+            &[vk::BufferCopy {
+                src_offset: 0,
+                dst_offset,
+                size,
+            }],
+        );
+        transfer.record_buffer_release(
+            device,
+            command_buffer,
+            dst,
+            dst_offset,
+            size,
+        );
+        Ok(())
+    })?;
+    if transfer.is_ownership_transfer() {
+        device.sync_graphics_commands(|command_buffer| {
+            transfer.record_buffer_acquire(
+                device,
+                command_buffer,
+                dst,
+                dst_offset,
+                size,
+            );
+            Ok(())
+        })?;
+    }
+    Ok(())
+}
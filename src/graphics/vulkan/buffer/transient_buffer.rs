@@ -0,0 +1,198 @@
+use super::{Buffer, BufferSlice, StaticBuffer};
+use crate::graphics::vulkan::Device;
+
+use anyhow::Result;
+use ash::vk;
+use std::sync::Arc;
+
+/// The size of the first block, allocated on the first push.
+const INITIAL_BLOCK_SIZE: u64 = 64 * 1024;
+
+/// A linear allocator for data which only lives for a single frame.
+///
+/// Pushes bump a pointer through persistently mapped HOST_VISIBLE |
+/// HOST_COHERENT memory, and `reset` makes all of it available again. There
+/// is no free list: when a frame needs more room another block is chained
+/// on, and the next reset replaces the chain with a single block big enough
+/// for the whole frame.
+pub struct TransientBuffer {
+    device: Arc<Device>,
+    usage: vk::BufferUsageFlags,
+
+    /// The blocks used this frame. Pushes go into the last one.
+    blocks: Vec<TransientBlock>,
+
+    /// The offset of the next free byte in the last block.
+    cursor: u64,
+
+    /// The number of bytes pushed since the last reset, including padding.
+    used: u64,
+}
+
+/// One mapped buffer in the chain.
+struct TransientBlock {
+    buffer: StaticBuffer,
+    size: u64,
+    mapped: *mut u8,
+}
+
+impl TransientBuffer {
+    /// Create an empty transient buffer. Nothing is allocated until the
+    /// first push.
+    pub fn new(device: Arc<Device>, usage: vk::BufferUsageFlags) -> Self {
+        Self {
+            device,
+            usage,
+            blocks: vec![],
+            cursor: 0,
+            used: 0,
+        }
+    }
+
+    /// Copy data into the buffer and return where it went. The slice's offset
+    /// is a multiple of `alignment`.
+    ///
+    /// The slice is valid until the next call to `reset`. Empty data returns
+    /// an empty slice with a null buffer.
+    pub fn push<T>(&mut self, alignment: u64, data: &[T]) -> Result<BufferSlice>
+    where
+        T: Sized + Copy,
+    {
+        self.push_arrays(alignment, &[data])
+    }
+
+    /// Copy several arrays into the buffer back to back, as a single slice.
+    pub fn push_arrays<T>(
+        &mut self,
+        alignment: u64,
+        data_arrays: &[&[T]],
+    ) -> Result<BufferSlice>
+    where
+        T: Sized + Copy,
+    {
+        let size: u64 = data_arrays
+            .iter()
+            .map(|data| std::mem::size_of_val(*data) as u64)
+            .sum();
+        if size == 0 {
+            return Ok(BufferSlice::default());
+        }
+
+        let offset = self.bump(alignment.max(1), size)?;
+        let block = self.blocks.last().unwrap();
+        let mut ptr = unsafe { block.mapped.add(offset as usize) };
+        for data in data_arrays {
+            let byte_size = std::mem::size_of_val(*data);
+            // SAFE: bump reserved the range, and the memory is mapped
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    data.as_ptr() as *const u8,
+                    ptr,
+                    byte_size,
+                );
+                ptr = ptr.add(byte_size);
+            }
+        }
+
+        Ok(BufferSlice {
+            buffer: unsafe { block.buffer.raw() },
+            offset,
+            size,
+        })
+    }
+
+    /// Make the whole buffer available for the next frame.
+    ///
+    /// When the last frame needed more than one block they're replaced by a
+    /// single block which fits everything.
+    ///
+    /// # Safety
+    ///
+    /// - every slice pushed since the last reset must be finished being used
+    ///   by the gpu, usually by waiting for the frame's fence
+    pub unsafe fn reset(&mut self) -> Result<()> {
+        if self.blocks.len() > 1 {
+            let size = self.used.next_power_of_two();
+            self.blocks.clear();
+            self.blocks.push(self.create_block(size)?);
+        }
+        self.cursor = 0;
+        self.used = 0;
+        Ok(())
+    }
+
+    /// The number of bytes pushed since the last reset, including alignment
+    /// padding.
+    pub fn used_bytes(&self) -> u64 {
+        self.used
+    }
+
+    /// The total size of every block.
+    pub fn capacity(&self) -> u64 {
+        self.blocks.iter().map(|block| block.size).sum()
+    }
+
+    /// Reserve `size` bytes in the last block, chaining on a new block when
+    /// they don't fit, and return their offset.
+    fn bump(&mut self, alignment: u64, size: u64) -> Result<u64> {
+        let aligned = align_up(self.cursor, alignment);
+        let fits = self
+            .blocks
+            .last()
+            .map(|block| aligned + size <= block.size)
+            .unwrap_or(false);
+        if fits {
+            self.used += aligned + size - self.cursor;
+            self.cursor = aligned + size;
+            return Ok(aligned);
+        }
+
+        let block_size = self
+            .blocks
+            .last()
+            .map(|block| block.size * 2)
+            .unwrap_or(INITIAL_BLOCK_SIZE)
+            .max(size.next_power_of_two());
+        let block = self.create_block(block_size)?;
+        self.blocks.push(block);
+        self.used += size;
+        self.cursor = size;
+        Ok(0)
+    }
+
+    fn create_block(&self, size: u64) -> Result<TransientBlock> {
+        let buffer = StaticBuffer::create(
+            self.device.clone(),
+            self.usage,
+            vk::MemoryPropertyFlags::HOST_VISIBLE
+                | vk::MemoryPropertyFlags::HOST_COHERENT,
+            size,
+        )?;
+        let mapped =
+            unsafe { self.device.map_allocation(buffer.allocation())? };
+        Ok(TransientBlock {
+            buffer,
+            size,
+            mapped,
+        })
+    }
+}
+
+impl Drop for TransientBlock {
+    /// Unmap the memory before the buffer frees it.
+    fn drop(&mut self) {
+        unsafe {
+            self.buffer
+                .device
+                .unmap_allocation(self.buffer.allocation());
+        }
+    }
+}
+
+/// Round an offset up to the next multiple of the alignment.
+fn align_up(offset: u64, alignment: u64) -> u64 {
+    match offset % alignment {
+        0 => offset,
+        remainder => offset + alignment - remainder,
+    }
+}
@@ -0,0 +1,163 @@
+use crate::graphics::vulkan::Device;
+
+use super::ReusableCommandPool;
+
+use anyhow::Result;
+use ash::vk;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, ThreadId},
+};
+
+/// Command pools for every thread which records commands, with a separate
+/// pool for each frame in flight.
+///
+/// Vulkan command pools can't be used by more than one thread at a time, so
+/// each recording thread gets its own pool the first time it asks for a
+/// command buffer. All of a frame's pools are reset together once the frame
+/// is finished with them.
+///
+/// Pools are destroyed automatically when their thread stops recording:
+/// a pool which wasn't used since the frame's previous reset is dropped at
+/// the next one.
+///
+/// Every command buffer is handed out inside a [CommandRecorder]. A frame's
+/// pools are not reset while any of its recorders are alive, so a thread
+/// can't have its pool reset in the middle of recording.
+pub struct CommandPoolRegistry {
+    pools: Mutex<HashMap<(ThreadId, usize), RegisteredPool>>,
+    debug_name: String,
+    device: Arc<Device>,
+}
+
+/// A pool and whether it's been used since its frame was last reset.
+struct RegisteredPool {
+    pool: ReusableCommandPool,
+    used: bool,
+
+    /// The number of live recorders holding buffers from this pool.
+    recorders: Arc<AtomicUsize>,
+}
+
+/// A command buffer from one of the registry's pools.
+///
+/// The buffer's pool can't be reset while the recorder is alive. Keep it
+/// until the buffer has been submitted, frames hold submitted recorders
+/// until the gpu is done with them.
+#[derive(Debug)]
+pub struct CommandRecorder {
+    command_buffer: vk::CommandBuffer,
+    recorders: Arc<AtomicUsize>,
+}
+
+impl CommandRecorder {
+    /// The raw command buffer, which should not be used after the recorder
+    /// is dropped.
+    pub fn raw(&self) -> vk::CommandBuffer {
+        self.command_buffer
+    }
+}
+
+impl Drop for CommandRecorder {
+    fn drop(&mut self) {
+        self.recorders.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl CommandPoolRegistry {
+    /// Create an empty registry. Pools are named after the debug name, the
+    /// frame index, and the thread.
+    pub fn new(device: Arc<Device>, debug_name: impl Into<String>) -> Self {
+        Self {
+            pools: Mutex::new(HashMap::new()),
+            debug_name: debug_name.into(),
+            device,
+        }
+    }
+
+    /// Request a command buffer from the calling thread's pool for a frame,
+    /// creating the pool if needed.
+    ///
+    /// The buffer is owned by the registry, the returned recorder keeps the
+    /// frame's pools from being reset until it's dropped.
+    pub fn request_command_buffer(
+        &self,
+        frame_index: usize,
+    ) -> Result<CommandRecorder> {
+        let thread = thread::current();
+        let mut pools = self.pools.lock().unwrap();
+        let registered = match pools.entry((thread.id(), frame_index)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let name = format!(
+                    "{} Frame {} Thread {}",
+                    self.debug_name,
+                    frame_index,
+                    thread.name().unwrap_or("unnamed")
+                );
+                entry.insert(RegisteredPool {
+                    pool: ReusableCommandPool::new(self.device.clone(), name)?,
+                    used: false,
+                    recorders: Arc::new(AtomicUsize::new(0)),
+                })
+            }
+        };
+        registered.used = true;
+        let command_buffer = registered.pool.request_command_buffer()?;
+
+        // counted while the map is locked, so a concurrent reset either sees
+        // this recorder or happens before the buffer was requested
+        registered.recorders.fetch_add(1, Ordering::AcqRel);
+        Ok(CommandRecorder {
+            command_buffer,
+            recorders: registered.recorders.clone(),
+        })
+    }
+
+    /// Reset every thread's pool for a frame, and destroy the pools which
+    /// weren't used since the last reset.
+    ///
+    /// Fails without resetting anything when any of the frame's recorders
+    /// are still alive.
+    ///
+    /// # Safety
+    ///
+    /// - the gpu must be done with every command buffer requested for the
+    ///   frame, usually by waiting for the frame's fence
+    pub unsafe fn reset_frame(&self, frame_index: usize) -> Result<()> {
+        let mut pools = self.pools.lock().unwrap();
+        let recording = pools.iter().find(|(&(_, index), registered)| {
+            index == frame_index
+                && registered.recorders.load(Ordering::Acquire) > 0
+        });
+        if let Some((&(thread, _), registered)) = recording {
+            anyhow::bail!(
+                "unable to reset frame {}, {} command buffers from thread {:?} \
+                 are still being recorded",
+                frame_index,
+                registered.recorders.load(Ordering::Acquire),
+                thread
+            );
+        }
+        pools.retain(|&(_, index), registered| {
+            index != frame_index || registered.used
+        });
+        for (&(_, index), registered) in pools.iter_mut() {
+            if index == frame_index {
+                registered.pool.reset()?;
+                registered.used = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of pools owned by the registry, across every thread and
+    /// frame.
+    pub fn pool_count(&self) -> usize {
+        self.pools.lock().unwrap().len()
+    }
+}
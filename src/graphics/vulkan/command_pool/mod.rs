@@ -1,10 +1,12 @@
 //! This module provides structures for managing a collection of command
 //! buffers for a given command pool.
 
+mod command_pool_registry;
 mod owned_command_pool;
 mod reusable_command_pool;
 
 pub use self::{
+    command_pool_registry::{CommandPoolRegistry, CommandRecorder},
     owned_command_pool::OwnedCommandPool,
     reusable_command_pool::ReusableCommandPool,
 };
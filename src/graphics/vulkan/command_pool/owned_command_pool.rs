@@ -32,7 +32,7 @@ impl OwnedCommandPool {
 
     /// The raw command pool handle.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - The returned reference is still logically 'owned' by this struct and
     ///   must not be destroyed except via a call to [Self::destroy].
@@ -42,7 +42,7 @@ impl OwnedCommandPool {
 
     /// Allocate a new command buffer.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller must eventually call [Self::reset] or else resources will
     ///   be leaked
@@ -64,7 +64,7 @@ impl OwnedCommandPool {
 
     /// Free a command buffer's resources to be used by the pool again.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller is responsible for ensuring that the command buffer is
     ///   not being used anymore
@@ -82,7 +82,7 @@ impl OwnedCommandPool {
 
     /// Reset all command buffers allocated by this pool.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller is responsible for ensuring that none of the command
     ///   buffers are still in use
@@ -97,7 +97,7 @@ impl OwnedCommandPool {
 
     /// Destroy the command pool.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - the caller must ensure that the command pool is not in use when it is
     ///   destroyed.
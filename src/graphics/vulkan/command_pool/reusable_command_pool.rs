@@ -93,6 +93,10 @@ impl Drop for ReusableCommandPool {
             }
             self.available_command_buffers.clear();
             self.allocated_command_buffers.clear();
+            self.device.forget_vulkan_object(
+                vk::ObjectType::COMMAND_POOL,
+                self.command_pool.raw(),
+            );
             self.command_pool.destroy(&self.device.logical_device);
         }
     }
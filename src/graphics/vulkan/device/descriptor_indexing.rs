@@ -0,0 +1,119 @@
+//! Functions for enabling update-after-bind descriptors, which let the
+//! texture atlas write its descriptor set while frames which use it are
+//! still in flight.
+
+use crate::graphics::vulkan::Instance;
+
+use ash::{
+    version::{InstanceV1_0, InstanceV1_1},
+    vk,
+};
+
+/// The extension which provides update-after-bind descriptors.
+pub const DESCRIPTOR_INDEXING: &str = "VK_EXT_descriptor_indexing";
+
+/// Descriptor indexing depends on this extension, which is core in Vulkan
+/// 1.1.
+pub const MAINTENANCE_3: &str = "VK_KHR_maintenance3";
+
+/// Query the device's update-after-bind limits.
+///
+/// Returns None unless descriptor indexing was enabled and the device can
+/// update sampled images and samplers after they're bound, while unused
+/// descriptors in the same set are pending, and can leave unused array
+/// elements unwritten.
+pub fn update_after_bind_properties(
+    instance: &Instance,
+    physical_device: &vk::PhysicalDevice,
+    extensions: &[String],
+) -> Option<vk::PhysicalDeviceDescriptorIndexingProperties> {
+    if !extensions.iter().any(|name| name == DESCRIPTOR_INDEXING) {
+        return None;
+    }
+
+    // the features2 queries are core in 1.1, older devices go without
+    let properties = unsafe {
+        instance
+            .ash
+            .get_physical_device_properties(*physical_device)
+    };
+    if vk::version_minor(properties.api_version) < 1 {
+        return None;
+    }
+
+    let mut features = vk::PhysicalDeviceDescriptorIndexingFeatures::default();
+    let mut features2 = vk::PhysicalDeviceFeatures2 {
+        p_next: &mut features as *mut _ as *mut std::ffi::c_void,
+        ..Default::default()
+    };
+    let mut properties =
+        vk::PhysicalDeviceDescriptorIndexingProperties::default();
+    let mut properties2 = vk::PhysicalDeviceProperties2 {
+        p_next: &mut properties as *mut _ as *mut std::ffi::c_void,
+        ..Default::default()
+    };
+    unsafe {
+        instance
+            .ash
+            .get_physical_device_features2(*physical_device, &mut features2);
+        instance.ash.get_physical_device_properties2(
+            *physical_device,
+            &mut properties2,
+        );
+    }
+
+    if supports_update_after_bind(&features) {
+        properties.p_next = std::ptr::null_mut();
+        Some(properties)
+    } else {
+        None
+    }
+}
+
+/// The descriptor indexing features to enable when the device supports
+/// update-after-bind.
+pub fn update_after_bind_features(
+) -> vk::PhysicalDeviceDescriptorIndexingFeatures {
+    vk::PhysicalDeviceDescriptorIndexingFeatures {
+        descriptor_binding_sampled_image_update_after_bind: vk::TRUE,
+        descriptor_binding_update_unused_while_pending: vk::TRUE,
+        descriptor_binding_partially_bound: vk::TRUE,
+        ..Default::default()
+    }
+}
+
+/// True when the features cover every update-after-bind binding used by
+/// the atlas's descriptor set.
+///
+/// Partially bound descriptors are required because slots written while the
+/// set is pending are not valid until the write lands, so the set can't rely
+/// on every element of the array being valid when it's bound.
+fn supports_update_after_bind(
+    features: &vk::PhysicalDeviceDescriptorIndexingFeatures,
+) -> bool {
+    features.descriptor_binding_sampled_image_update_after_bind == vk::TRUE
+        && features.descriptor_binding_update_unused_while_pending == vk::TRUE
+        && features.descriptor_binding_partially_bound == vk::TRUE
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_feature_should_be_required() {
+        let mut features = update_after_bind_features();
+        assert!(supports_update_after_bind(&features));
+
+        features.descriptor_binding_update_unused_while_pending = vk::FALSE;
+        assert!(!supports_update_after_bind(&features));
+
+        features = update_after_bind_features();
+        features.descriptor_binding_sampled_image_update_after_bind = vk::FALSE;
+        assert!(!supports_update_after_bind(&features));
+
+        features = update_after_bind_features();
+        features.descriptor_binding_partially_bound = vk::FALSE;
+        assert!(!supports_update_after_bind(&features));
+    }
+}
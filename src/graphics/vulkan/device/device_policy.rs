@@ -0,0 +1,135 @@
+use ash::vk;
+
+/// Decides which kinds of physical device may be picked.
+///
+/// CPU implementations like lavapipe and SwiftShader are always available on
+/// CI machines, but they're far too slow to use without knowing it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DevicePolicy {
+    /// Only pick gpus. Device creation fails when there are none.
+    HardwareOnly,
+
+    /// Pick a gpu when there is one, otherwise fall back to a cpu
+    /// implementation with a warning.
+    #[default]
+    PreferHardware,
+
+    /// Pick a cpu implementation when there is one. Useful for tests which
+    /// need the same output on every machine.
+    PreferSoftware,
+}
+
+impl DevicePolicy {
+    /// The environment variable read by `from_env`.
+    pub const ENV_VAR: &'static str = "DRAW2D_DEVICE_POLICY";
+
+    /// Read the policy from `DRAW2D_DEVICE_POLICY`, which can be
+    /// `hardware-only`, `prefer-hardware`, or `prefer-software`. Anything
+    /// else is the default.
+    pub fn from_env() -> Self {
+        match std::env::var(Self::ENV_VAR) {
+            Ok(value) => Self::parse(&value).unwrap_or_else(|| {
+                log::warn!("ignoring unknown {} '{}'", Self::ENV_VAR, value);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Parse a policy name, ignoring case.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('_', "-").as_str() {
+            "hardware-only" | "hardware" => Some(Self::HardwareOnly),
+            "prefer-hardware" => Some(Self::PreferHardware),
+            "prefer-software" | "software" => Some(Self::PreferSoftware),
+            _ => None,
+        }
+    }
+
+    /// How strongly a device type is preferred, lowest first, or None when
+    /// the type isn't allowed.
+    pub fn rank(&self, device_type: vk::PhysicalDeviceType) -> Option<u32> {
+        let hardware_rank = match device_type {
+            vk::PhysicalDeviceType::DISCRETE_GPU => 0,
+            vk::PhysicalDeviceType::INTEGRATED_GPU => 1,
+            vk::PhysicalDeviceType::VIRTUAL_GPU => 2,
+            vk::PhysicalDeviceType::CPU => 4,
+            _ => 3,
+        };
+        match self {
+            Self::HardwareOnly if is_software(device_type) => None,
+            Self::HardwareOnly | Self::PreferHardware => Some(hardware_rank),
+            Self::PreferSoftware if is_software(device_type) => Some(0),
+            Self::PreferSoftware => Some(1 + hardware_rank),
+        }
+    }
+
+    /// The index of the best allowed device type. The first device wins
+    /// ties, so the loader's order breaks them.
+    pub fn select(
+        &self,
+        device_types: &[vk::PhysicalDeviceType],
+    ) -> Option<usize> {
+        device_types
+            .iter()
+            .enumerate()
+            .filter_map(|(index, device_type)| {
+                self.rank(*device_type).map(|rank| (rank, index))
+            })
+            .min()
+            .map(|(_, index)| index)
+    }
+}
+
+/// True for vulkan implementations which run on the cpu.
+pub fn is_software(device_type: vk::PhysicalDeviceType) -> bool {
+    device_type == vk::PhysicalDeviceType::CPU
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use vk::PhysicalDeviceType as Type;
+
+    #[test]
+    fn hardware_should_be_preferred_by_default() {
+        let types = [Type::CPU, Type::INTEGRATED_GPU, Type::DISCRETE_GPU];
+        assert_eq!(DevicePolicy::default().select(&types), Some(2));
+        assert_eq!(DevicePolicy::default().select(&[Type::CPU]), Some(0));
+    }
+
+    #[test]
+    fn hardware_only_should_skip_software() {
+        let policy = DevicePolicy::HardwareOnly;
+        assert_eq!(policy.select(&[Type::CPU]), None);
+        assert_eq!(policy.select(&[Type::CPU, Type::VIRTUAL_GPU]), Some(1));
+    }
+
+    #[test]
+    fn prefer_software_should_pick_cpu_devices_first() {
+        let policy = DevicePolicy::PreferSoftware;
+        let types = [Type::DISCRETE_GPU, Type::CPU];
+        assert_eq!(policy.select(&types), Some(1));
+        assert_eq!(policy.select(&[Type::DISCRETE_GPU]), Some(0));
+    }
+
+    #[test]
+    fn ties_should_keep_the_loader_order() {
+        let types = [Type::INTEGRATED_GPU, Type::INTEGRATED_GPU];
+        assert_eq!(DevicePolicy::default().select(&types), Some(0));
+    }
+
+    #[test]
+    fn names_should_parse_loosely() {
+        assert_eq!(
+            DevicePolicy::parse(" Prefer_Software "),
+            Some(DevicePolicy::PreferSoftware)
+        );
+        assert_eq!(
+            DevicePolicy::parse("hardware"),
+            Some(DevicePolicy::HardwareOnly)
+        );
+        assert_eq!(DevicePolicy::parse("fastest"), None);
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use ash::vk;
+use std::{collections::HashMap, ffi::c_void};
+
+/// Tracks which device memory is mapped into the application's address
+/// space.
+///
+/// Vulkan only allows a memory object to be mapped once, but the allocator
+/// puts many buffers in each memory object. The whole memory object is
+/// mapped when the first allocation in it is mapped, and unmapped when the
+/// last allocation is released.
+pub struct MemoryMappings {
+    mapped: HashMap<vk::DeviceMemory, Mapping>,
+}
+
+struct Mapping {
+    /// The start of the mapped memory object, stored as an address so the
+    /// registry can live behind a mutex on the device.
+    address: usize,
+    users: u32,
+}
+
+impl MemoryMappings {
+    pub fn new() -> Self {
+        Self {
+            mapped: HashMap::new(),
+        }
+    }
+
+    /// The address of the start of the memory object, mapping it with `map`
+    /// when this is the first user.
+    pub fn acquire<F>(
+        &mut self,
+        memory: vk::DeviceMemory,
+        map: F,
+    ) -> Result<*mut u8>
+    where
+        F: FnOnce() -> Result<*mut c_void>,
+    {
+        if let Some(mapping) = self.mapped.get_mut(&memory) {
+            mapping.users += 1;
+            return Ok(mapping.address as *mut u8);
+        }
+        let address = map()? as usize;
+        self.mapped.insert(memory, Mapping { address, users: 1 });
+        Ok(address as *mut u8)
+    }
+
+    /// Release one user of the memory object. Returns true when this was the
+    /// last user and the memory should be unmapped.
+    pub fn release(&mut self, memory: vk::DeviceMemory) -> bool {
+        match self.mapped.get_mut(&memory) {
+            Some(mapping) if mapping.users > 1 => {
+                mapping.users -= 1;
+                false
+            }
+            Some(_) => {
+                self.mapped.remove(&memory);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// True when the memory object is mapped by any user.
+    pub fn is_mapped(&self, memory: vk::DeviceMemory) -> bool {
+        self.mapped.contains_key(&memory)
+    }
+}
+
+impl Default for MemoryMappings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The range to flush or invalidate for `size` bytes at `offset`.
+///
+/// Ranges must be aligned to the device's non-coherent atom size. Ranges
+/// which would be grown past the end of the allocation flush the rest of the
+/// memory object instead, which is always valid.
+pub fn aligned_range(
+    offset: vk::DeviceSize,
+    size: vk::DeviceSize,
+    atom_size: vk::DeviceSize,
+    allocation_end: vk::DeviceSize,
+) -> (vk::DeviceSize, vk::DeviceSize) {
+    let atom_size = atom_size.max(1);
+    let start = offset - offset % atom_size;
+    let end = offset + size;
+    let aligned_end = match end % atom_size {
+        0 => end,
+        remainder => end + atom_size - remainder,
+    };
+    if aligned_end <= allocation_end {
+        (start, aligned_end - start)
+    } else {
+        (start, vk::WHOLE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use ash::vk::Handle;
+
+    #[test]
+    fn memory_should_be_mapped_once_for_every_user() {
+        let memory = vk::DeviceMemory::from_raw(1);
+        let mut mappings = MemoryMappings::new();
+        let mut backing = [0u8; 4];
+        let address = backing.as_mut_ptr() as *mut c_void;
+
+        let first = mappings.acquire(memory, || Ok(address)).unwrap();
+        let second = mappings
+            .acquire(memory, || panic!("memory should only be mapped once"))
+            .unwrap();
+        assert_eq!(first, second);
+
+        assert!(!mappings.release(memory));
+        assert!(mappings.is_mapped(memory));
+        assert!(mappings.release(memory));
+        assert!(!mappings.is_mapped(memory));
+        assert!(!mappings.release(memory));
+    }
+
+    #[test]
+    fn failed_maps_should_not_be_recorded() {
+        let memory = vk::DeviceMemory::from_raw(2);
+        let mut mappings = MemoryMappings::new();
+        assert!(mappings
+            .acquire(memory, || anyhow::bail!("out of host memory"))
+            .is_err());
+        assert!(!mappings.is_mapped(memory));
+    }
+
+    #[test]
+    fn ranges_should_be_grown_to_the_atom_size() {
+        assert_eq!(aligned_range(70, 10, 64, 1024), (64, 64));
+        assert_eq!(aligned_range(64, 64, 64, 1024), (64, 64));
+        assert_eq!(aligned_range(0, 10, 1, 1024), (0, 10));
+    }
+
+    #[test]
+    fn ranges_past_the_allocation_should_flush_the_rest() {
+        assert_eq!(aligned_range(960, 50, 64, 1000), (960, vk::WHOLE_SIZE));
+    }
+}
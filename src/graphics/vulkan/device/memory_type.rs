@@ -0,0 +1,158 @@
+//! Functions for picking the memory type used by an allocation.
+
+use ash::vk;
+
+/// True when every memory heap is device local, like on mobile and
+/// integrated GPUs where the CPU and GPU share the same memory.
+pub fn is_unified_memory(
+    memory_properties: &vk::PhysicalDeviceMemoryProperties,
+) -> bool {
+    let heaps = &memory_properties.memory_heaps
+        [..memory_properties.memory_heap_count as usize];
+    !heaps.is_empty()
+        && heaps
+            .iter()
+            .filter(|heap| heap.size > 0)
+            .all(|heap| heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL))
+}
+
+/// Allocations at least this large get their own device memory rather than
+/// tying up a page in one of the allocator's pools.
+pub const DEDICATED_ALLOCATION_SIZE: vk::DeviceSize = 32 * 1024 * 1024;
+
+/// True when a resource should get a dedicated allocation, either because
+/// the driver asks for one or because it's large.
+pub fn wants_dedicated_allocation(
+    size: vk::DeviceSize,
+    dedicated: &vk::MemoryDedicatedRequirements,
+) -> bool {
+    dedicated.requires_dedicated_allocation == vk::TRUE
+        || dedicated.prefers_dedicated_allocation == vk::TRUE
+        || size >= DEDICATED_ALLOCATION_SIZE
+}
+
+/// Pick the index of a memory type which is allowed by the memory
+/// requirements and has every requested property.
+///
+/// Unified memory devices often expose host visible types which aren't
+/// marked device local even though the memory is shared. Device local
+/// types are preferred there so host visible buffers are fast for the gpu
+/// too. Otherwise the first suitable type is used.
+pub fn select_memory_type(
+    memory_properties: &vk::PhysicalDeviceMemoryProperties,
+    memory_type_bits: u32,
+    property_flags: vk::MemoryPropertyFlags,
+) -> Option<u32> {
+    let memory_types = &memory_properties.memory_types
+        [..memory_properties.memory_type_count as usize];
+    let mut candidates = memory_types
+        .iter()
+        .enumerate()
+        .filter(|(i, memory_type)| {
+            let type_supported = memory_type_bits & (1 << i) != 0;
+            let properties_supported =
+                memory_type.property_flags.contains(property_flags);
+            type_supported && properties_supported
+        })
+        .map(|(i, memory_type)| (i as u32, memory_type.property_flags));
+
+    if is_unified_memory(memory_properties) {
+        let all: Vec<(u32, vk::MemoryPropertyFlags)> = candidates.collect();
+        all.iter()
+            .find(|(_, flags)| {
+                flags.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL)
+            })
+            .or_else(|| all.first())
+            .map(|(i, _)| *i)
+    } else {
+        candidates.next().map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn properties(
+        heaps: &[vk::MemoryHeapFlags],
+        types: &[(u32, vk::MemoryPropertyFlags)],
+    ) -> vk::PhysicalDeviceMemoryProperties {
+        let mut properties = vk::PhysicalDeviceMemoryProperties {
+            memory_heap_count: heaps.len() as u32,
+            memory_type_count: types.len() as u32,
+            ..Default::default()
+        };
+        for (i, flags) in heaps.iter().enumerate() {
+            properties.memory_heaps[i] = vk::MemoryHeap {
+                size: 1024,
+                flags: *flags,
+            };
+        }
+        for (i, (heap_index, flags)) in types.iter().enumerate() {
+            properties.memory_types[i] = vk::MemoryType {
+                heap_index: *heap_index,
+                property_flags: *flags,
+            };
+        }
+        properties
+    }
+
+    const HOST: vk::MemoryPropertyFlags = vk::MemoryPropertyFlags::HOST_VISIBLE;
+
+    #[test]
+    fn discrete_devices_should_use_the_first_suitable_type() {
+        let properties = properties(
+            &[
+                vk::MemoryHeapFlags::DEVICE_LOCAL,
+                vk::MemoryHeapFlags::empty(),
+            ],
+            &[
+                (0, vk::MemoryPropertyFlags::DEVICE_LOCAL),
+                (1, HOST),
+                (0, vk::MemoryPropertyFlags::DEVICE_LOCAL | HOST),
+            ],
+        );
+        assert!(!is_unified_memory(&properties));
+        assert_eq!(select_memory_type(&properties, !0, HOST), Some(1));
+    }
+
+    #[test]
+    fn unified_devices_should_prefer_device_local_types() {
+        let properties = properties(
+            &[vk::MemoryHeapFlags::DEVICE_LOCAL],
+            &[(0, HOST), (0, vk::MemoryPropertyFlags::DEVICE_LOCAL | HOST)],
+        );
+        assert!(is_unified_memory(&properties));
+        assert_eq!(select_memory_type(&properties, !0, HOST), Some(1));
+        // the requirements still limit which types can be used
+        assert_eq!(select_memory_type(&properties, 0b01, HOST), Some(0));
+    }
+
+    #[test]
+    fn large_or_driver_preferred_resources_should_be_dedicated() {
+        let none = vk::MemoryDedicatedRequirements::default();
+        assert!(!wants_dedicated_allocation(1024, &none));
+        assert!(wants_dedicated_allocation(DEDICATED_ALLOCATION_SIZE, &none));
+
+        let prefers = vk::MemoryDedicatedRequirements {
+            prefers_dedicated_allocation: vk::TRUE,
+            ..Default::default()
+        };
+        assert!(wants_dedicated_allocation(1024, &prefers));
+
+        let requires = vk::MemoryDedicatedRequirements {
+            requires_dedicated_allocation: vk::TRUE,
+            ..Default::default()
+        };
+        assert!(wants_dedicated_allocation(1024, &requires));
+    }
+
+    #[test]
+    fn unsupported_properties_should_not_select_a_type() {
+        let properties = properties(
+            &[vk::MemoryHeapFlags::DEVICE_LOCAL],
+            &[(0, vk::MemoryPropertyFlags::DEVICE_LOCAL)],
+        );
+        assert_eq!(select_memory_type(&properties, !0, HOST), None);
+    }
+}
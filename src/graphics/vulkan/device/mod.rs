@@ -1,18 +1,39 @@
 //! This module provides functions for picking a physical device and creating
 //! the logical device.
 
+mod descriptor_indexing;
+mod device_policy;
+mod memory_mappings;
+mod memory_type;
+mod object_registry;
+mod pending_work;
 mod physical_device;
 mod queue;
 mod queue_family_indices;
+mod queue_transfer;
+mod texture_limit;
 
-pub use self::{queue::Queue, queue_family_indices::QueueFamilyIndices};
+pub use self::{
+    device_policy::{is_software, DevicePolicy},
+    memory_mappings::MemoryMappings,
+    object_registry::ObjectRegistry,
+    pending_work::PendingWork,
+    queue::Queue,
+    queue_family_indices::QueueFamilyIndices,
+    queue_transfer::QueueTransfer,
+    texture_limit::{SAMPLER_LIMIT_CAP, TEXTURE_LIMIT_CAP},
+};
 
-use crate::graphics::vulkan::{
-    device_allocator::{self, Allocation},
-    Instance, WindowSurface,
+use crate::{
+    graphics::vulkan::{
+        device_allocator::{self, Allocation, AllocatorConfig},
+        EnabledFeatures, ExtensionRequests, ExternalMemoryHandle, Instance,
+        WindowSurface,
+    },
+    metrics::{MemoryEvent, SharedMetrics},
 };
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use ash::{version::DeviceV1_0, vk};
 use std::{
     ffi::CString,
@@ -31,55 +52,226 @@ pub struct Device {
     pub graphics_queue: Queue,
     pub present_queue: Queue,
 
+    /// The queue uploads are submitted on, see `transfer_queue`.
+    transfer_queue: Queue,
+
+    /// Entrypoints for VK_EXT_full_screen_exclusive, present only when the
+    /// extension is supported and was enabled for the logical device.
+    pub full_screen_exclusive: Option<vk::ExtFullScreenExclusiveFn>,
+
+    /// Entrypoints for VK_KHR_external_memory_fd, present only when the
+    /// extension is supported and was enabled for the logical device.
+    pub external_memory_fd: Option<vk::KhrExternalMemoryFdFn>,
+
+    /// The optional features which the device supports and were enabled.
+    pub features: vk::PhysicalDeviceFeatures,
+
+    /// The number of textures the fragment shaders' texture array holds.
+    max_supported_textures: u32,
+
+    /// The number of samplers the fragment shaders' sampler array holds.
+    max_supported_samplers: u32,
+
+    /// True when the texture atlas's descriptors can be written after its
+    /// descriptor set is bound.
+    update_after_bind: bool,
+
+    /// Every extension which was enabled for the logical device.
+    extensions: Vec<String>,
+
     shared_graphics_pool: Mutex<OwnedCommandPool>,
-    allocator: Mutex<Box<dyn DeviceAllocator>>,
+
+    /// The pool for commands submitted to the transfer queue. None when the
+    /// transfer queue is the graphics queue, which then shares the graphics
+    /// pool so submissions to the queue stay serialized by its lock.
+    shared_transfer_pool: Option<Mutex<OwnedCommandPool>>,
+
+    allocator: Mutex<Box<dyn DeviceAllocator + Send>>,
+
+    /// Receives an event for every allocation and free, when installed.
+    metrics: Mutex<Option<SharedMetrics>>,
+
+    /// Every named vulkan object which hasn't been destroyed.
+    objects: Mutex<ObjectRegistry>,
+
+    /// Device memory which is mapped for one or more allocations.
+    mappings: Mutex<MemoryMappings>,
 
     instance: Arc<Instance>,
 }
 
+/// The command pools which the device shares for one-off submissions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum SharedPool {
+    Graphics,
+    Transfer,
+}
+
 impl Device {
     /// Create a new device based on this application's required features and
     /// properties.
     pub fn new(window_surface: &dyn WindowSurface) -> Result<Arc<Device>> {
+        Self::with_extensions(window_surface, &ExtensionRequests::new())
+    }
+
+    /// Create a new device which also negotiates the application's device
+    /// extensions.
+    ///
+    /// Only physical devices which support every required extension are
+    /// considered. Optional extensions which were enabled are reported by
+    /// `enabled_features`.
+    ///
+    /// The device is picked with the policy from `DevicePolicy::from_env`.
+    pub fn with_extensions(
+        window_surface: &dyn WindowSurface,
+        requests: &ExtensionRequests,
+    ) -> Result<Arc<Device>> {
+        Self::with_policy(window_surface, requests, DevicePolicy::from_env())
+    }
+
+    /// Create a new device, picking the physical device with the policy.
+    ///
+    /// The selected device's name and type are logged, and can be checked
+    /// with `device_name` and `device_type`.
+    pub fn with_policy(
+        window_surface: &dyn WindowSurface,
+        requests: &ExtensionRequests,
+        policy: DevicePolicy,
+    ) -> Result<Arc<Device>> {
+        Self::with_allocator_config(
+            window_surface,
+            requests,
+            policy,
+            &AllocatorConfig::default(),
+        )
+    }
+
+    /// Create a new device whose memory allocator is composed as the config
+    /// describes.
+    pub fn with_allocator_config(
+        window_surface: &dyn WindowSurface,
+        requests: &ExtensionRequests,
+        policy: DevicePolicy,
+        allocator_config: &AllocatorConfig,
+    ) -> Result<Arc<Device>> {
+        allocator_config.validate()?;
         let instance = window_surface.clone_vulkan_instance();
-        let physical_device =
-            physical_device::find_optimal(&instance, window_surface)?;
+        let requests =
+            physical_device::library_extensions(&instance).merge(requests);
+        let physical_device = physical_device::find_optimal(
+            &instance,
+            window_surface,
+            &requests,
+            policy,
+        )?;
         let queue_family_indices = QueueFamilyIndices::find(
             &physical_device,
             instance.raw(),
             window_surface,
         )?;
+        let extensions =
+            requests.negotiate(&physical_device::available_extension_names(
+                &instance,
+                &physical_device,
+            ))?;
+        let features =
+            physical_device::enabled_features(&instance, &physical_device);
+        let update_after_bind_properties =
+            descriptor_indexing::update_after_bind_properties(
+                &instance,
+                &physical_device,
+                &extensions,
+            );
+        let (max_supported_textures, max_supported_samplers) =
+            match &update_after_bind_properties {
+                Some(properties) => (
+                    texture_limit::update_after_bind_texture_limit(properties),
+                    texture_limit::update_after_bind_sampler_limit(properties),
+                ),
+                None => {
+                    use ash::version::InstanceV1_0;
+                    let properties = unsafe {
+                        instance
+                            .ash
+                            .get_physical_device_properties(physical_device)
+                    };
+                    (
+                        texture_limit::texture_limit(&properties.limits),
+                        texture_limit::sampler_limit(&properties.limits),
+                    )
+                }
+            };
+        let descriptor_indexing_features = update_after_bind_properties
+            .map(|_| descriptor_indexing::update_after_bind_features());
         let logical_device = instance.create_logical_device(
             &physical_device,
-            physical_device::required_features(),
-            &physical_device::required_extensions(),
+            features,
+            descriptor_indexing_features.as_ref(),
+            &extensions,
             &queue_family_indices.as_queue_create_infos(),
         )?;
+        let full_screen_exclusive = Self::load_full_screen_exclusive(
+            &instance,
+            &logical_device,
+            &extensions,
+        );
+        let external_memory_fd = Self::load_external_memory_fd(
+            &instance,
+            &logical_device,
+            &extensions,
+        );
 
-        let (graphics_queue, present_queue) =
+        let (graphics_queue, present_queue, transfer_queue) =
             queue_family_indices.get_queues(&logical_device)?;
 
-        let allocator = device_allocator::build_standard_allocator(
+        let allocator = device_allocator::build_allocator(
             instance.ash.clone(),
             logical_device.clone(),
             physical_device,
-        );
+            allocator_config,
+        )?;
 
         let shared_graphics_pool = Mutex::new(OwnedCommandPool::new(
             &logical_device,
             graphics_queue.family_id,
         )?);
+        let shared_transfer_pool = if transfer_queue.is_same(&graphics_queue) {
+            None
+        } else {
+            Some(Mutex::new(OwnedCommandPool::new(
+                &logical_device,
+                transfer_queue.family_id,
+            )?))
+        };
 
         let device = Arc::new(Self {
             physical_device,
             logical_device,
             graphics_queue,
             present_queue,
+            transfer_queue,
+            full_screen_exclusive,
+            external_memory_fd,
+            features,
+            max_supported_textures,
+            max_supported_samplers,
+            update_after_bind: descriptor_indexing_features.is_some(),
+            extensions,
             shared_graphics_pool,
+            shared_transfer_pool,
             allocator: Mutex::new(allocator),
+            metrics: Mutex::new(None),
+            objects: Mutex::new(ObjectRegistry::new()),
+            mappings: Mutex::new(MemoryMappings::new()),
             instance,
         });
 
+        log::info!(
+            "selected {} ({:?})",
+            device.device_name(),
+            device.device_type()
+        );
+
         device.name_vulkan_object(
             "Application Logical Device",
             vk::ObjectType::DEVICE,
@@ -105,12 +297,23 @@ impl Device {
                 .name_vulkan_object("present queue", &device)?;
         }
 
+        if let Some(pool) = &device.shared_transfer_pool {
+            device.name_vulkan_object(
+                "Shared Transfer Pool",
+                vk::ObjectType::COMMAND_POOL,
+                unsafe { pool.lock().unwrap().raw() },
+            )?;
+            device
+                .transfer_queue
+                .name_vulkan_object("transfer queue", &device)?;
+        }
+
         Ok(device)
     }
 
     /// Allocate a a chunk of memory for use in a buffer or texture.
     ///
-    /// # unsafe because
+    /// # Safety
     ///
     /// - the caller is responsible for eventually calling 'free memory' before
     ///   the application quits
@@ -128,43 +331,659 @@ impl Device {
             .ash
             .get_physical_device_memory_properties(self.physical_device);
 
-        let memory_type_index = memory_properties
-            .memory_types
-            .iter()
-            .enumerate()
-            .find(|(i, memory_type)| {
-                let type_supported =
-                    memory_requirements.memory_type_bits & (1 << i) != 0;
-                let properties_supported =
-                    memory_type.property_flags.contains(property_flags);
-                type_supported & properties_supported
-            })
-            .map(|(i, _memory_type)| i as u32)
-            .with_context(|| {
-                "unable to find a suitable memory type for this allocation!"
-            })?;
-
-        self.allocator
-            .lock()
-            .unwrap()
-            .allocate(vk::MemoryAllocateInfo {
+        let memory_type_index = memory_type::select_memory_type(
+            &memory_properties,
+            memory_requirements.memory_type_bits,
+            property_flags,
+        )
+        .with_context(|| {
+            "unable to find a suitable memory type for this allocation!"
+        })?;
+
+        let allocation = self.allocator.lock().unwrap().allocate(
+            vk::MemoryAllocateInfo {
+                memory_type_index,
+                allocation_size: memory_requirements.size,
+                ..Default::default()
+            },
+            memory_requirements.alignment,
+        )?;
+        self.record_memory(MemoryEvent::Allocated {
+            memory_type_index,
+            byte_size: allocation.byte_size,
+        });
+        Ok(allocation)
+    }
+
+    /// Allocate memory for a buffer. The caller binds it.
+    ///
+    /// Buffers which the driver would rather have in their own memory, or
+    /// which are large, get a dedicated allocation instead of a piece of one
+    /// of the allocator's pools.
+    ///
+    /// # Safety
+    ///
+    /// - the caller is responsible for eventually calling 'free memory' before
+    ///   the application quits
+    ///
+    pub unsafe fn allocate_buffer_memory(
+        &self,
+        buffer: vk::Buffer,
+        property_flags: vk::MemoryPropertyFlags,
+    ) -> Result<Allocation> {
+        use ash::version::DeviceV1_1;
+
+        let (requirements, dedicated) = if self.supports_dedicated_allocation()
+        {
+            let mut dedicated = vk::MemoryDedicatedRequirements::default();
+            let mut requirements = vk::MemoryRequirements2 {
+                p_next: &mut dedicated as *mut _ as *mut std::ffi::c_void,
+                ..Default::default()
+            };
+            self.logical_device.get_buffer_memory_requirements2(
+                &vk::BufferMemoryRequirementsInfo2 {
+                    buffer,
+                    ..Default::default()
+                },
+                &mut requirements,
+            );
+            (requirements.memory_requirements, dedicated)
+        } else {
+            (
+                self.logical_device.get_buffer_memory_requirements(buffer),
+                vk::MemoryDedicatedRequirements::default(),
+            )
+        };
+
+        let allocation = if memory_type::wants_dedicated_allocation(
+            requirements.size,
+            &dedicated,
+        ) {
+            self.allocate_dedicated_memory(
+                requirements,
+                property_flags,
+                vk::MemoryDedicatedAllocateInfo {
+                    buffer,
+                    ..Default::default()
+                },
+            )?
+        } else {
+            self.allocate_memory(requirements, property_flags)?
+        };
+        Ok(allocation)
+    }
+
+    /// Allocate memory for an image. The caller binds it.
+    ///
+    /// Images which the driver would rather have in their own memory, or
+    /// which are large, get a dedicated allocation instead of a piece of one
+    /// of the allocator's pools.
+    ///
+    /// # Safety
+    ///
+    /// - the caller is responsible for eventually calling 'free memory' before
+    ///   the application quits
+    ///
+    pub unsafe fn allocate_image_memory(
+        &self,
+        image: vk::Image,
+        property_flags: vk::MemoryPropertyFlags,
+    ) -> Result<Allocation> {
+        use ash::version::DeviceV1_1;
+
+        let (requirements, dedicated) = if self.supports_dedicated_allocation()
+        {
+            let mut dedicated = vk::MemoryDedicatedRequirements::default();
+            let mut requirements = vk::MemoryRequirements2 {
+                p_next: &mut dedicated as *mut _ as *mut std::ffi::c_void,
+                ..Default::default()
+            };
+            self.logical_device.get_image_memory_requirements2(
+                &vk::ImageMemoryRequirementsInfo2 {
+                    image,
+                    ..Default::default()
+                },
+                &mut requirements,
+            );
+            (requirements.memory_requirements, dedicated)
+        } else {
+            (
+                self.logical_device.get_image_memory_requirements(image),
+                vk::MemoryDedicatedRequirements::default(),
+            )
+        };
+
+        let allocation = if memory_type::wants_dedicated_allocation(
+            requirements.size,
+            &dedicated,
+        ) {
+            self.allocate_dedicated_memory(
+                requirements,
+                property_flags,
+                vk::MemoryDedicatedAllocateInfo {
+                    image,
+                    ..Default::default()
+                },
+            )?
+        } else {
+            self.allocate_memory(requirements, property_flags)?
+        };
+        Ok(allocation)
+    }
+
+    /// Allocate device memory for a single image or buffer, bypassing the
+    /// allocator.
+    ///
+    /// The dedicated info is only passed to the driver when it's supported,
+    /// older devices still get memory of their own.
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        memory_requirements: vk::MemoryRequirements,
+        property_flags: vk::MemoryPropertyFlags,
+        dedicated: vk::MemoryDedicatedAllocateInfo,
+    ) -> Result<Allocation> {
+        use anyhow::Context;
+        use ash::version::InstanceV1_0;
+
+        let memory_properties = self
+            .instance
+            .ash
+            .get_physical_device_memory_properties(self.physical_device);
+        let memory_type_index = memory_type::select_memory_type(
+            &memory_properties,
+            memory_requirements.memory_type_bits,
+            property_flags,
+        )
+        .with_context(|| {
+            "unable to find a suitable memory type for this allocation!"
+        })?;
+
+        let p_next = if self.supports_dedicated_allocation() {
+            &dedicated as *const _ as *const std::ffi::c_void
+        } else {
+            std::ptr::null()
+        };
+        let memory = self.logical_device.allocate_memory(
+            &vk::MemoryAllocateInfo {
+                p_next,
+                memory_type_index,
+                allocation_size: memory_requirements.size,
+                ..Default::default()
+            },
+            None,
+        )?;
+        self.record_memory(MemoryEvent::Allocated {
+            memory_type_index,
+            byte_size: memory_requirements.size,
+        });
+        Ok(Allocation::dedicated(
+            memory,
+            memory_requirements.size,
+            memory_type_index,
+        ))
+    }
+
+    /// True when the device can report and honor dedicated allocation
+    /// requirements, which are core in Vulkan 1.1.
+    fn supports_dedicated_allocation(&self) -> bool {
+        self.physical_device_properties().api_version
+            >= vk::make_version(1, 1, 0)
+    }
+
+    /// True when images can be created with `vk::ImageCreateFlags::ALIAS`,
+    /// which is part of Vulkan 1.1.
+    pub fn supports_image_aliasing(&self) -> bool {
+        self.physical_device_properties().api_version
+            >= vk::make_version(1, 1, 0)
+    }
+
+    /// Send every allocation and free to a metrics sink, or stop sending
+    /// them with None.
+    pub fn set_metrics(&self, metrics: Option<SharedMetrics>) {
+        *self.metrics.lock().unwrap() = metrics;
+    }
+
+    fn record_memory(&self, event: MemoryEvent) {
+        if let Some(metrics) = &*self.metrics.lock().unwrap() {
+            metrics.lock().unwrap().record_memory(&event);
+        }
+    }
+
+    /// Import memory which was allocated outside of this library.
+    ///
+    /// The memory is always a dedicated allocation for the image or buffer
+    /// named by `dedicated`. Any memory type allowed by the requirements, and
+    /// by the handle itself for DMA-BUF imports, which has every property in
+    /// `property_flags` can be used.
+    ///
+    /// # Safety
+    ///
+    /// - the caller is responsible for eventually calling 'free memory'
+    /// - the image or buffer in `dedicated` must have been created with the
+    ///   handle's type in its external memory create info
+    /// - file descriptors are owned by the device when the import succeeds,
+    ///   the caller must not close them
+    pub unsafe fn import_memory(
+        &self,
+        memory_requirements: vk::MemoryRequirements,
+        property_flags: vk::MemoryPropertyFlags,
+        handle: ExternalMemoryHandle,
+        dedicated: vk::MemoryDedicatedAllocateInfo,
+    ) -> Result<Allocation> {
+        use anyhow::Context;
+        use ash::version::InstanceV1_0;
+
+        if !handle.is_supported_by(&self.extensions) {
+            bail!(
+                "Importing {:?} requires the {:?} device extensions!",
+                handle,
+                handle.required_extensions()
+            );
+        }
+
+        let mut memory_type_bits = memory_requirements.memory_type_bits;
+        if let ExternalMemoryHandle::DmaBuf(fd) = handle {
+            let external_memory_fd = self
+                .external_memory_fd
+                .as_ref()
+                .context("external memory fd entrypoints were not loaded")?;
+            let mut fd_properties = vk::MemoryFdPropertiesKHR::default();
+            let result = external_memory_fd.get_memory_fd_properties_khr(
+                self.logical_device.handle(),
+                handle.handle_type(),
+                fd,
+                &mut fd_properties,
+            );
+            if result != vk::Result::SUCCESS {
+                bail!("Unable to query the DMA-BUF's properties! {:?}", result);
+            }
+            memory_type_bits &= fd_properties.memory_type_bits;
+        }
+
+        let memory_properties = self
+            .instance
+            .ash
+            .get_physical_device_memory_properties(self.physical_device);
+        let memory_type_index = memory_type::select_memory_type(
+            &memory_properties,
+            memory_type_bits,
+            property_flags,
+        )
+        .with_context(|| {
+            "unable to find a suitable memory type for the imported memory!"
+        })?;
+
+        let mut import_fd_info = vk::ImportMemoryFdInfoKHR {
+            p_next: &dedicated as *const _ as *const std::ffi::c_void,
+            handle_type: handle.handle_type(),
+            ..Default::default()
+        };
+        let mut import_win32_info = vk::ImportMemoryWin32HandleInfoKHR {
+            p_next: &dedicated as *const _ as *const std::ffi::c_void,
+            handle_type: handle.handle_type(),
+            ..Default::default()
+        };
+        let p_next = match handle {
+            ExternalMemoryHandle::OpaqueFd(fd)
+            | ExternalMemoryHandle::DmaBuf(fd) => {
+                import_fd_info.fd = fd;
+                &import_fd_info as *const _ as *const std::ffi::c_void
+            }
+            ExternalMemoryHandle::OpaqueWin32(raw_handle) => {
+                import_win32_info.handle = raw_handle;
+                &import_win32_info as *const _ as *const std::ffi::c_void
+            }
+        };
+
+        let memory = self.logical_device.allocate_memory(
+            &vk::MemoryAllocateInfo {
+                p_next,
                 memory_type_index,
                 allocation_size: memory_requirements.size,
                 ..Default::default()
-            })
+            },
+            None,
+        )?;
+        Ok(Allocation::imported(
+            memory,
+            memory_requirements.size,
+            memory_type_index,
+        ))
+    }
+
+    /// True when memory with the handle's type can be imported.
+    pub fn supports_external_memory(
+        &self,
+        handle: &ExternalMemoryHandle,
+    ) -> bool {
+        handle.is_supported_by(&self.extensions)
+    }
+
+    /// True when the device's memory is shared with the CPU, like on mobile
+    /// and integrated GPUs.
+    ///
+    /// Host visible memory is as fast as any other memory on these devices,
+    /// so staging copies can be skipped.
+    pub fn has_unified_memory(&self) -> bool {
+        use ash::version::InstanceV1_0;
+
+        let memory_properties = unsafe {
+            self.instance
+                .ash
+                .get_physical_device_memory_properties(self.physical_device)
+        };
+        memory_type::is_unified_memory(&memory_properties)
+    }
+
+    /// The largest anisotropy samplers can use, or None when anisotropic
+    /// filtering isn't supported by the device.
+    pub fn max_sampler_anisotropy(&self) -> Option<f32> {
+        use ash::version::InstanceV1_0;
+
+        if self.features.sampler_anisotropy != vk::TRUE {
+            return None;
+        }
+        let properties = unsafe {
+            self.instance
+                .ash
+                .get_physical_device_properties(self.physical_device)
+        };
+        Some(properties.limits.max_sampler_anisotropy)
+    }
+
+    /// The number of textures which can be bound at once, and so the
+    /// number of textures any texture atlas can hold.
+    ///
+    /// This is queried from the device limits, up to [TEXTURE_LIMIT_CAP].
+    pub fn max_supported_textures(&self) -> u32 {
+        self.max_supported_textures
+    }
+
+    /// The number of samplers which can be bound at once, and so the
+    /// number of samplers any texture atlas can hold.
+    ///
+    /// This is queried from the device limits, up to [SAMPLER_LIMIT_CAP].
+    pub fn max_supported_samplers(&self) -> u32 {
+        self.max_supported_samplers
+    }
+
+    /// True when descriptor sets can be created for update-after-bind, so
+    /// unused descriptors can be written while command buffers which bind
+    /// the set are pending.
+    ///
+    /// Without it, descriptor sets shared between frames can only be written
+    /// once every frame which uses them has finished.
+    pub fn supports_update_after_bind(&self) -> bool {
+        self.update_after_bind
+    }
+
+    /// The physical device's name, as reported by the driver.
+    pub fn device_name(&self) -> String {
+        physical_device::device_name(&self.physical_device_properties())
+    }
+
+    /// The kind of physical device, like a discrete gpu or the cpu.
+    pub fn device_type(&self) -> vk::PhysicalDeviceType {
+        self.physical_device_properties().device_type
+    }
+
+    /// True when rendering runs on the cpu, like with lavapipe or
+    /// SwiftShader.
+    pub fn is_software(&self) -> bool {
+        is_software(self.device_type())
+    }
+
+    fn physical_device_properties(&self) -> vk::PhysicalDeviceProperties {
+        use ash::version::InstanceV1_0;
+
+        unsafe {
+            self.instance
+                .ash
+                .get_physical_device_properties(self.physical_device)
+        }
+    }
+
+    /// The nanoseconds per timestamp tick, or None when the graphics queue
+    /// can't write timestamps.
+    pub fn timestamp_period(&self) -> Option<f32> {
+        use ash::version::InstanceV1_0;
+
+        let properties = unsafe {
+            self.instance
+                .ash
+                .get_physical_device_properties(self.physical_device)
+        };
+        if properties.limits.timestamp_compute_and_graphics != vk::TRUE {
+            return None;
+        }
+        Some(properties.limits.timestamp_period)
+    }
+
+    /// Read the graphics queue's current timestamp, used to line gpu
+    /// timestamps up with the cpu's clock.
+    ///
+    /// This waits for the device to idle, so it's only suitable for one-off
+    /// calibration.
+    pub fn current_timestamp(&self) -> Result<u64> {
+        let create_info = vk::QueryPoolCreateInfo {
+            query_type: vk::QueryType::TIMESTAMP,
+            query_count: 1,
+            ..Default::default()
+        };
+        unsafe {
+            let query_pool =
+                self.logical_device.create_query_pool(&create_info, None)?;
+            let written = self.sync_graphics_commands(|command_buffer| {
+                self.logical_device.cmd_reset_query_pool(
+                    command_buffer,
+                    query_pool,
+                    0,
+                    1,
+                );
+                self.logical_device.cmd_write_timestamp(
+                    command_buffer,
+                    vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                    query_pool,
+                    0,
+                );
+                Ok(())
+            });
+            let mut timestamp = [0u64; 1];
+            let result = written.and_then(|()| {
+                self.logical_device
+                    .get_query_pool_results(
+                        query_pool,
+                        0,
+                        1,
+                        &mut timestamp,
+                        vk::QueryResultFlags::TYPE_64
+                            | vk::QueryResultFlags::WAIT,
+                    )
+                    .map_err(anyhow::Error::from)
+            });
+            self.logical_device.destroy_query_pool(query_pool, None);
+            result?;
+            Ok(timestamp[0])
+        }
+    }
+
+    /// The features supported by optimally tiled images with the format.
+    pub fn optimal_format_features(
+        &self,
+        format: vk::Format,
+    ) -> vk::FormatFeatureFlags {
+        use ash::version::InstanceV1_0;
+
+        let properties = unsafe {
+            self.instance.ash.get_physical_device_format_properties(
+                self.physical_device,
+                format,
+            )
+        };
+        properties.optimal_tiling_features
+    }
+
+    /// The largest width or height of a 2D image created on the device.
+    pub fn max_image_dimension_2d(&self) -> u32 {
+        use ash::version::InstanceV1_0;
+
+        let properties = unsafe {
+            self.instance
+                .ash
+                .get_physical_device_properties(self.physical_device)
+        };
+        properties.limits.max_image_dimension2_d
+    }
+
+    /// True when pipelines can rasterize polygons as lines.
+    pub fn supports_wireframe(&self) -> bool {
+        self.features.fill_mode_non_solid == vk::TRUE
+    }
+
+    /// The extensions and features which were enabled for this device and
+    /// its instance.
+    pub fn enabled_features(&self) -> EnabledFeatures {
+        EnabledFeatures {
+            instance_extensions: self.instance.enabled_extensions().to_vec(),
+            device_extensions: self.extensions.clone(),
+            device_features: self.features,
+        }
     }
 
     /// Free a memory allocation.
     ///
-    /// # unsafe because
+    /// # Safety
     ///
     /// - the caller is responsible for ensuring that the memory is no longer
     ///   in use by the gpu.
     ///
     pub unsafe fn free_memory(&self, allocation: &Allocation) -> Result<()> {
+        if allocation.is_imported() {
+            self.logical_device.free_memory(allocation.memory, None);
+            return Ok(());
+        }
+        if allocation.is_dedicated() {
+            self.record_memory(MemoryEvent::Freed {
+                memory_type_index: allocation.memory_type_index(),
+                byte_size: allocation.byte_size,
+            });
+            self.logical_device.free_memory(allocation.memory, None);
+            return Ok(());
+        }
+        if !allocation.is_null() {
+            self.record_memory(MemoryEvent::Freed {
+                memory_type_index: allocation.memory_type_index(),
+                byte_size: allocation.byte_size,
+            });
+        }
         self.allocator.lock().unwrap().free(allocation)
     }
 
+    /// Map an allocation into the application's address space and return a
+    /// pointer to its first byte.
+    ///
+    /// Allocations share device memory, so every mapping goes through the
+    /// device. The pointer stays valid until the matching call to
+    /// `unmap_allocation`.
+    ///
+    /// # Safety
+    ///
+    /// - the allocation must be host visible
+    /// - the caller must call `unmap_allocation` before freeing the memory
+    ///
+    pub unsafe fn map_allocation(
+        &self,
+        allocation: &Allocation,
+    ) -> Result<*mut u8> {
+        let memory = allocation.memory;
+        let start = self.mappings.lock().unwrap().acquire(memory, || {
+            Ok(self.logical_device.map_memory(
+                memory,
+                0,
+                vk::WHOLE_SIZE,
+                vk::MemoryMapFlags::empty(),
+            )?)
+        })?;
+        Ok(start.add(allocation.offset as usize))
+    }
+
+    /// Release a mapping made by `map_allocation`. The memory is unmapped
+    /// once no allocations in it are mapped.
+    ///
+    /// # Safety
+    ///
+    /// - pointers returned by `map_allocation` must not be used afterwards
+    ///
+    pub unsafe fn unmap_allocation(&self, allocation: &Allocation) {
+        if self.mappings.lock().unwrap().release(allocation.memory) {
+            self.logical_device.unmap_memory(allocation.memory);
+        }
+    }
+
+    /// Make host writes to `size` bytes at `offset` in a mapped allocation
+    /// visible to the device. Only needed for memory which isn't
+    /// HOST_COHERENT.
+    ///
+    /// # Safety
+    ///
+    /// - the allocation must be mapped with `map_allocation`
+    ///
+    pub unsafe fn flush_allocation(
+        &self,
+        allocation: &Allocation,
+        offset: vk::DeviceSize,
+        size: vk::DeviceSize,
+    ) -> Result<()> {
+        let range = self.mapped_range(allocation, offset, size);
+        self.logical_device.flush_mapped_memory_ranges(&[range])?;
+        Ok(())
+    }
+
+    /// Make device writes to `size` bytes at `offset` in a mapped allocation
+    /// visible to the host. Only needed for memory which isn't
+    /// HOST_COHERENT.
+    ///
+    /// # Safety
+    ///
+    /// - the allocation must be mapped with `map_allocation`
+    ///
+    pub unsafe fn invalidate_allocation(
+        &self,
+        allocation: &Allocation,
+        offset: vk::DeviceSize,
+        size: vk::DeviceSize,
+    ) -> Result<()> {
+        let range = self.mapped_range(allocation, offset, size);
+        self.logical_device
+            .invalidate_mapped_memory_ranges(&[range])?;
+        Ok(())
+    }
+
+    /// The atom-aligned memory range for bytes in an allocation.
+    fn mapped_range(
+        &self,
+        allocation: &Allocation,
+        offset: vk::DeviceSize,
+        size: vk::DeviceSize,
+    ) -> vk::MappedMemoryRange {
+        let atom_size = self
+            .physical_device_properties()
+            .limits
+            .non_coherent_atom_size;
+        let (offset, size) = memory_mappings::aligned_range(
+            allocation.offset + offset,
+            size,
+            atom_size,
+            allocation.offset + allocation.byte_size,
+        );
+        vk::MappedMemoryRange {
+            memory: allocation.memory,
+            offset,
+            size,
+            ..Default::default()
+        }
+    }
+
     /// Give a debug name for a vulkan object owned by this device.
     ///
     /// Whatever name is provided here will show up in the debug logs if there
@@ -179,7 +998,8 @@ impl Device {
         Handle: vk::Handle + Copy,
         Name: Into<String>,
     {
-        let cname = CString::new(name.into()).unwrap();
+        let owned_name = name.into();
+        let cname = CString::new(owned_name.clone()).unwrap();
         let name_info = vk::DebugUtilsObjectNameInfoEXT {
             object_type,
             p_object_name: cname.as_ptr(),
@@ -194,9 +1014,55 @@ impl Device {
             )?;
         }
 
+        self.objects.lock().unwrap().created(
+            object_type,
+            handle.as_raw(),
+            owned_name,
+        );
+
         Ok(())
     }
 
+    /// Stop tracking a vulkan object which is about to be destroyed.
+    ///
+    /// Every object named with `name_vulkan_object` should be forgotten when
+    /// it's destroyed, objects which are still tracked when the device is
+    /// dropped are reported as leaks. Forgetting an unnamed object does
+    /// nothing.
+    pub fn forget_vulkan_object<Handle>(
+        &self,
+        object_type: vk::ObjectType,
+        handle: &Handle,
+    ) where
+        Handle: vk::Handle + Copy,
+    {
+        self.objects
+            .lock()
+            .unwrap()
+            .destroyed(object_type, handle.as_raw());
+    }
+
+    /// Get the name given to a vulkan object with `name_vulkan_object`.
+    ///
+    /// Objects which were never named are labeled with their type and raw
+    /// handle.
+    pub fn vulkan_object_name<Handle>(
+        &self,
+        object_type: vk::ObjectType,
+        handle: &Handle,
+    ) -> String
+    where
+        Handle: vk::Handle + Copy,
+    {
+        let raw = handle.as_raw();
+        self.objects
+            .lock()
+            .unwrap()
+            .name(object_type, raw)
+            .map(str::to_owned)
+            .unwrap_or_else(|| format!("{:?} {:#x}", object_type, raw))
+    }
+
     /// Synchronously submit commands for execution on the graphics queue.
     ///
     /// This method is internally synchronized and can be called on multiple
@@ -205,7 +1071,7 @@ impl Device {
     /// This method forces the device to wait idle after submitting commands,
     /// as such it is very slow (don't do it in a loop every frame!).
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - no internal synchronization is done, any resources used by graphcis
     ///   commands must be synchronized by the caller
@@ -213,12 +1079,53 @@ impl Device {
     ///   inside this method should be in-use after the call.
     pub unsafe fn sync_graphics_commands<R, Action>(
         &self,
+        action: Action,
+    ) -> Result<R>
+    where
+        Action: FnMut(vk::CommandBuffer) -> Result<R>,
+    {
+        self.sync_commands(SharedPool::Graphics, action)
+    }
+
+    /// The queue uploads are submitted on.
+    ///
+    /// This queue comes from a dedicated transfer family when the device has
+    /// one, otherwise it's the graphics queue. Resources written on a
+    /// dedicated transfer queue must be released to the graphics family, then
+    /// acquired on the graphics queue, before they're used for rendering.
+    pub fn transfer_queue(&self) -> &Queue {
+        &self.transfer_queue
+    }
+
+    /// Synchronously submit commands for execution on the transfer queue.
+    ///
+    /// # Safety
+    ///
+    /// - the same as `sync_graphics_commands`
+    /// - the command buffer can only record transfer commands, unless the
+    ///   transfer queue is the graphics queue
+    pub unsafe fn sync_transfer_commands<R, Action>(
+        &self,
+        action: Action,
+    ) -> Result<R>
+    where
+        Action: FnMut(vk::CommandBuffer) -> Result<R>,
+    {
+        self.sync_commands(SharedPool::Transfer, action)
+    }
+
+    /// Synchronously submit commands from one of the shared pools to its
+    /// queue, then wait for the queue to idle.
+    unsafe fn sync_commands<R, Action>(
+        &self,
+        shared_pool: SharedPool,
         mut action: Action,
     ) -> Result<R>
     where
         Action: FnMut(vk::CommandBuffer) -> Result<R>,
     {
-        let pool = self.shared_graphics_pool.lock().unwrap();
+        let (pool, queue) = self.shared_pool(shared_pool);
+        let pool = pool.lock().unwrap();
         let command_buffer =
             pool.allocate_command_buffer(&self.logical_device)?;
 
@@ -232,12 +1139,167 @@ impl Device {
         let result = action(command_buffer);
 
         self.logical_device.end_command_buffer(command_buffer)?;
-        self.submit_and_wait_idle(&self.graphics_queue, command_buffer)?;
+        self.submit_and_wait_idle(queue, command_buffer)?;
         pool.free_command_buffer(&self.logical_device, command_buffer);
 
         result
     }
 
+    /// The shared pool and the queue its commands are submitted to.
+    fn shared_pool(
+        &self,
+        shared_pool: SharedPool,
+    ) -> (&Mutex<OwnedCommandPool>, &Queue) {
+        match (shared_pool, &self.shared_transfer_pool) {
+            (SharedPool::Transfer, Some(pool)) => (pool, &self.transfer_queue),
+            _ => (&self.shared_graphics_pool, &self.graphics_queue),
+        }
+    }
+
+    /// Submit commands to the graphics queue without waiting for them to
+    /// finish.
+    ///
+    /// Unlike `sync_graphics_commands`, the queue isn't idled, so other work
+    /// keeps running while the commands execute. The returned `PendingWork`
+    /// signals when they're done, and should retain anything the commands
+    /// read from, like staging buffers.
+    ///
+    /// # Safety
+    ///
+    /// - no internal synchronization is done, any resources used by the
+    ///   commands must be synchronized by the caller until the work is done
+    pub unsafe fn submit_graphics_commands_async<Action>(
+        self: &Arc<Self>,
+        action: Action,
+    ) -> Result<PendingWork>
+    where
+        Action: FnMut(vk::CommandBuffer) -> Result<()>,
+    {
+        self.submit_commands_async(SharedPool::Graphics, action)
+    }
+
+    /// Submit commands to the transfer queue without waiting for them to
+    /// finish.
+    ///
+    /// # Safety
+    ///
+    /// - the same as `submit_graphics_commands_async`
+    /// - resources written by the commands must be released to the graphics
+    ///   family, and acquired there once the work is done, see
+    ///   `QueueTransfer`
+    pub unsafe fn submit_transfer_commands_async<Action>(
+        self: &Arc<Self>,
+        action: Action,
+    ) -> Result<PendingWork>
+    where
+        Action: FnMut(vk::CommandBuffer) -> Result<()>,
+    {
+        self.submit_commands_async(SharedPool::Transfer, action)
+    }
+
+    /// Block until every command submitted to the transfer queue has
+    /// finished, including `PendingWork` which nobody has waited on yet.
+    ///
+    /// # Safety
+    ///
+    /// - when the transfer queue is the graphics queue, frames must not be
+    ///   submitted while waiting
+    pub unsafe fn wait_for_transfers(&self) -> Result<()> {
+        use anyhow::Context;
+
+        let (pool, queue) = self.shared_pool(SharedPool::Transfer);
+
+        // submissions hold the pool's lock, so nothing else uses the queue
+        // while it idles
+        let _pool = pool.lock().unwrap();
+        self.logical_device
+            .queue_wait_idle(queue.raw())
+            .context("error while waiting for the transfer queue to idle")?;
+        Ok(())
+    }
+
+    /// Submit commands from one of the shared pools to its queue without
+    /// waiting for them to finish.
+    unsafe fn submit_commands_async<Action>(
+        self: &Arc<Self>,
+        shared_pool: SharedPool,
+        mut action: Action,
+    ) -> Result<PendingWork>
+    where
+        Action: FnMut(vk::CommandBuffer) -> Result<()>,
+    {
+        let (pool, queue) = self.shared_pool(shared_pool);
+        let pool = pool.lock().unwrap();
+        let command_buffer =
+            pool.allocate_command_buffer(&self.logical_device)?;
+        let fence = match self
+            .logical_device
+            .create_fence(&vk::FenceCreateInfo::default(), None)
+        {
+            Ok(fence) => fence,
+            Err(error) => {
+                pool.free_command_buffer(&self.logical_device, command_buffer);
+                return Err(error.into());
+            }
+        };
+
+        let submitted = (|| -> Result<()> {
+            let begin_info = vk::CommandBufferBeginInfo {
+                flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+                ..Default::default()
+            };
+            self.logical_device
+                .begin_command_buffer(command_buffer, &begin_info)?;
+            let result = action(command_buffer);
+            self.logical_device.end_command_buffer(command_buffer)?;
+            result?;
+
+            let command_buffers = &[command_buffer];
+            self.logical_device.queue_submit(
+                queue.raw(),
+                &[vk::SubmitInfo {
+                    p_command_buffers: command_buffers.as_ptr(),
+                    command_buffer_count: 1,
+                    ..Default::default()
+                }],
+                fence,
+            )?;
+            Ok(())
+        })();
+        if let Err(error) = submitted {
+            // nothing was submitted, so nothing needs to be waited for
+            pool.free_command_buffer(&self.logical_device, command_buffer);
+            self.logical_device.destroy_fence(fence, None);
+            return Err(error);
+        }
+        drop(pool);
+
+        Ok(PendingWork::new(
+            self.clone(),
+            shared_pool,
+            fence,
+            command_buffer,
+        ))
+    }
+
+    /// Return a command buffer to the shared pool it was allocated from.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must have come from the shared pool and be done
+    ///   executing
+    unsafe fn free_shared_command_buffer(
+        &self,
+        shared_pool: SharedPool,
+        command_buffer: vk::CommandBuffer,
+    ) {
+        self.shared_pool(shared_pool)
+            .0
+            .lock()
+            .unwrap()
+            .free_command_buffer(&self.logical_device, command_buffer);
+    }
+
     /// Submit a command buffer to the specified queue, then wait for it to
     /// idle.
     pub unsafe fn submit_and_wait_idle(
@@ -259,6 +1321,55 @@ impl Device {
         Ok(())
     }
 
+    /// Load the full screen exclusive entrypoints if the extension was
+    /// enabled.
+    fn load_full_screen_exclusive(
+        instance: &Instance,
+        logical_device: &ash::Device,
+        enabled_extensions: &[String],
+    ) -> Option<vk::ExtFullScreenExclusiveFn> {
+        use ash::version::InstanceV1_0;
+
+        let name = vk::ExtFullScreenExclusiveFn::name().to_str().ok()?;
+        if !enabled_extensions.iter().any(|ext| ext == name) {
+            return None;
+        }
+        log::debug!("{} is enabled", name);
+
+        Some(vk::ExtFullScreenExclusiveFn::load(|name| unsafe {
+            std::mem::transmute(
+                instance.ash.get_device_proc_addr(
+                    logical_device.handle(),
+                    name.as_ptr(),
+                ),
+            )
+        }))
+    }
+
+    /// Load the external memory fd entrypoints if the extension was enabled.
+    fn load_external_memory_fd(
+        instance: &Instance,
+        logical_device: &ash::Device,
+        enabled_extensions: &[String],
+    ) -> Option<vk::KhrExternalMemoryFdFn> {
+        use ash::version::InstanceV1_0;
+
+        let name = vk::KhrExternalMemoryFdFn::name().to_str().ok()?;
+        if !enabled_extensions.iter().any(|ext| ext == name) {
+            return None;
+        }
+        log::debug!("{} is enabled", name);
+
+        Some(vk::KhrExternalMemoryFdFn::load(|name| unsafe {
+            std::mem::transmute(
+                instance.ash.get_device_proc_addr(
+                    logical_device.handle(),
+                    name.as_ptr(),
+                ),
+            )
+        }))
+    }
+
     /// Create a new swapchain loader which will be owned by the caller.
     pub fn create_swapchain_loader(&self) -> ash::extensions::khr::Swapchain {
         ash::extensions::khr::Swapchain::new(
@@ -269,16 +1380,36 @@ impl Device {
 }
 
 impl Drop for Device {
-    /// Destroy the logical device.
+    /// Destroy the logical device, then log the lifetimes of every named
+    /// vulkan object. Objects which were never destroyed are logged as a
+    /// warning.
     ///
     /// Device owns an Arc<Instance> so it's guaranteed that the instance will
     /// not be destroyed until the logical device has been dropped.
     fn drop(&mut self) {
         unsafe {
-            self.shared_graphics_pool
-                .lock()
-                .unwrap()
-                .destroy(&self.logical_device);
+            let mut pool = self.shared_graphics_pool.lock().unwrap();
+            self.forget_vulkan_object(vk::ObjectType::COMMAND_POOL, pool.raw());
+            pool.destroy(&self.logical_device);
+            drop(pool);
+
+            if let Some(pool) = &self.shared_transfer_pool {
+                let mut pool = pool.lock().unwrap();
+                self.forget_vulkan_object(
+                    vk::ObjectType::COMMAND_POOL,
+                    pool.raw(),
+                );
+                pool.destroy(&self.logical_device);
+            }
+
+            let objects = self.objects.lock().unwrap();
+            if objects.leaks().is_empty() {
+                log::debug!("{}", objects.report());
+            } else {
+                log::warn!("{}", objects.report());
+            }
+            drop(objects);
+
             self.logical_device.destroy_device(None);
         }
     }
@@ -0,0 +1,176 @@
+use ash::vk;
+use std::{collections::HashMap, fmt::Write};
+
+/// Tracks every named vulkan object from creation to destruction.
+///
+/// Objects are registered when they're named with
+/// `Device::name_vulkan_object` and removed by
+/// `Device::forget_vulkan_object`. Anything still registered when the device
+/// is dropped outlived it, and is listed in the leak report.
+#[derive(Debug, Default)]
+pub struct ObjectRegistry {
+    /// The name of every live object, keyed by the raw handle.
+    names: HashMap<(vk::ObjectType, u64), String>,
+
+    /// The number of objects of each type which were created and destroyed.
+    counts: HashMap<vk::ObjectType, ObjectCounts>,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct ObjectCounts {
+    created: u64,
+    destroyed: u64,
+}
+
+/// Objects which are destroyed along with their parent rather than on their
+/// own, so they're never reported as leaks.
+const OWNED_BY_PARENT: [vk::ObjectType; 3] = [
+    vk::ObjectType::DEVICE,
+    vk::ObjectType::QUEUE,
+    vk::ObjectType::DESCRIPTOR_SET,
+];
+
+impl ObjectRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an object's name. Naming an object which is already
+    /// registered only replaces its name.
+    pub fn created(
+        &mut self,
+        object_type: vk::ObjectType,
+        raw: u64,
+        name: String,
+    ) {
+        let key = (object_type, raw);
+        if !self.names.contains_key(&key) {
+            log::trace!("created {:?} '{}' {:#x}", object_type, name, raw);
+            if !OWNED_BY_PARENT.contains(&object_type) {
+                self.counts.entry(object_type).or_default().created += 1;
+            }
+        }
+        self.names.insert(key, name);
+    }
+
+    /// Record that an object was destroyed. Objects which were never named
+    /// are ignored.
+    pub fn destroyed(&mut self, object_type: vk::ObjectType, raw: u64) {
+        if let Some(name) = self.names.remove(&(object_type, raw)) {
+            log::trace!("destroyed {:?} '{}' {:#x}", object_type, name, raw);
+            if !OWNED_BY_PARENT.contains(&object_type) {
+                self.counts.entry(object_type).or_default().destroyed += 1;
+            }
+        }
+    }
+
+    /// The name of a live object.
+    pub fn name(&self, object_type: vk::ObjectType, raw: u64) -> Option<&str> {
+        self.names.get(&(object_type, raw)).map(String::as_str)
+    }
+
+    /// The names of objects which were never destroyed, sorted by type and
+    /// name.
+    pub fn leaks(&self) -> Vec<(vk::ObjectType, &str)> {
+        let mut leaks: Vec<(vk::ObjectType, &str)> = self
+            .names
+            .iter()
+            .filter(|((object_type, _), _)| {
+                !OWNED_BY_PARENT.contains(object_type)
+            })
+            .map(|((object_type, _), name)| (*object_type, name.as_str()))
+            .collect();
+        leaks.sort_by_key(|(object_type, name)| {
+            (format!("{:?}", object_type), name.to_string())
+        });
+        leaks
+    }
+
+    /// A markdown report with per-type counts followed by every leaked
+    /// object.
+    pub fn report(&self) -> String {
+        let mut counts: Vec<(String, ObjectCounts)> = self
+            .counts
+            .iter()
+            .map(|(object_type, counts)| {
+                (format!("{:?}", object_type), *counts)
+            })
+            .collect();
+        counts.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut report = String::from(
+            "# Vulkan Object Lifetimes\n\n\
+             | Object Type           | Created | Destroyed | Leaked |\n\
+             | --------------------- | ------- | --------- | ------ |\n",
+        );
+        for (object_type, counts) in counts {
+            writeln!(
+                report,
+                "| {:<21} | {:>7} | {:>9} | {:>6} |",
+                object_type,
+                counts.created,
+                counts.destroyed,
+                counts.created.saturating_sub(counts.destroyed),
+            )
+            .unwrap();
+        }
+
+        let leaks = self.leaks();
+        if !leaks.is_empty() {
+            report.push_str("\n## Leaked Objects\n\n");
+            for (object_type, name) in leaks {
+                writeln!(report, "- {:?} '{}'", object_type, name).unwrap();
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn destroyed_objects_should_not_leak() {
+        let mut registry = ObjectRegistry::new();
+        registry.created(vk::ObjectType::IMAGE, 1, "color".to_owned());
+        registry.created(vk::ObjectType::IMAGE, 2, "depth".to_owned());
+        registry.destroyed(vk::ObjectType::IMAGE, 1);
+
+        assert_eq!(registry.name(vk::ObjectType::IMAGE, 1), None);
+        assert_eq!(registry.leaks(), vec![(vk::ObjectType::IMAGE, "depth")]);
+    }
+
+    #[test]
+    fn renaming_should_not_count_twice() {
+        let mut registry = ObjectRegistry::new();
+        registry.created(vk::ObjectType::SAMPLER, 7, "first".to_owned());
+        registry.created(vk::ObjectType::SAMPLER, 7, "second".to_owned());
+        registry.destroyed(vk::ObjectType::SAMPLER, 7);
+
+        assert!(registry.leaks().is_empty());
+        assert!(registry.report().contains(
+            "| SAMPLER               |       1 |         1 |      0 |"
+        ));
+    }
+
+    #[test]
+    fn objects_owned_by_a_parent_should_not_leak() {
+        let mut registry = ObjectRegistry::new();
+        registry.created(vk::ObjectType::QUEUE, 3, "queue".to_owned());
+        registry.created(vk::ObjectType::DESCRIPTOR_SET, 4, "set".to_owned());
+
+        assert!(registry.leaks().is_empty());
+        assert_eq!(registry.name(vk::ObjectType::QUEUE, 3), Some("queue"));
+    }
+
+    #[test]
+    fn the_report_should_list_leaks() {
+        let mut registry = ObjectRegistry::new();
+        registry.created(vk::ObjectType::FRAMEBUFFER, 9, "target".to_owned());
+
+        let report = registry.report();
+
+        assert!(report.contains("- FRAMEBUFFER 'target'"), "{}", report);
+    }
+}
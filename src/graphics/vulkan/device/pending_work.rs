@@ -0,0 +1,103 @@
+use super::{Device, SharedPool};
+
+use anyhow::{Context, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::{any::Any, sync::Arc};
+
+/// Commands which were submitted without waiting for them to finish, see
+/// `Device::submit_graphics_commands_async` and
+/// `Device::submit_transfer_commands_async`.
+///
+/// The command buffer, and anything handed to `retain`, are kept alive until
+/// the work is done. Dropping unfinished work blocks until it finishes.
+pub struct PendingWork {
+    /// The pool the command buffer is returned to.
+    shared_pool: SharedPool,
+    fence: vk::Fence,
+    command_buffer: vk::CommandBuffer,
+
+    /// Resources the commands use, like staging buffers, dropped once the
+    /// commands finish.
+    retained: Vec<Box<dyn Any>>,
+
+    finished: bool,
+    device: Arc<Device>,
+}
+
+impl PendingWork {
+    pub(super) fn new(
+        device: Arc<Device>,
+        shared_pool: SharedPool,
+        fence: vk::Fence,
+        command_buffer: vk::CommandBuffer,
+    ) -> Self {
+        Self {
+            shared_pool,
+            fence,
+            command_buffer,
+            retained: vec![],
+            finished: false,
+            device,
+        }
+    }
+
+    /// Keep a resource alive until the commands have finished with it.
+    pub fn retain<T: 'static>(&mut self, resource: T) {
+        self.retained.push(Box::new(resource));
+    }
+
+    /// True once the gpu has finished the commands. Finished work releases
+    /// its command buffer and retained resources.
+    pub fn is_done(&mut self) -> Result<bool> {
+        if self.finished {
+            return Ok(true);
+        }
+        let signaled = unsafe {
+            self.device
+                .logical_device
+                .get_fence_status(self.fence)
+                .context("unable to check the pending work's fence")?
+        };
+        if signaled {
+            self.release();
+        }
+        Ok(signaled)
+    }
+
+    /// Block until the gpu has finished the commands.
+    pub fn wait(&mut self) -> Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        unsafe {
+            self.device
+                .logical_device
+                .wait_for_fences(&[self.fence], true, u64::MAX)
+                .context("error while waiting for pending work")?;
+        }
+        self.release();
+        Ok(())
+    }
+
+    /// Free the command buffer and retained resources once the fence has
+    /// signaled.
+    fn release(&mut self) {
+        unsafe {
+            self.device.free_shared_command_buffer(
+                self.shared_pool,
+                self.command_buffer,
+            );
+        }
+        self.retained.clear();
+        self.finished = true;
+    }
+}
+
+impl Drop for PendingWork {
+    fn drop(&mut self) {
+        self.wait().expect("error while waiting for pending work");
+        unsafe {
+            self.device.logical_device.destroy_fence(self.fence, None);
+        }
+    }
+}
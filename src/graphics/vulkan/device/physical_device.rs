@@ -1,25 +1,75 @@
 //! Functions for picking a physical device with the features required by this
 //! application.
 
+use super::{
+    descriptor_indexing::{DESCRIPTOR_INDEXING, MAINTENANCE_3},
+    device_policy::{self, DevicePolicy},
+};
+
 use crate::graphics::vulkan::{
-    device::QueueFamilyIndices, Instance, WindowSurface,
+    device::QueueFamilyIndices, ExtensionRequests, Instance, WindowSurface,
 };
 
 use anyhow::{Context, Result};
 use ash::{version::InstanceV1_0, vk};
+use std::ffi::CStr;
+
+/// Portability drivers like MoltenVK expose this extension, and it must be
+/// enabled whenever it's present.
+const PORTABILITY_SUBSET: &str = "VK_KHR_portability_subset";
 
-/// Pick a physical device based on suitability criteria.
+/// Pick the suitable physical device which the policy prefers.
 pub fn find_optimal(
     instance: &Instance,
     window_surface: &dyn WindowSurface,
+    extensions: &ExtensionRequests,
+    policy: DevicePolicy,
 ) -> Result<vk::PhysicalDevice> {
     let physical_devices =
         unsafe { instance.ash.enumerate_physical_devices()? };
-    let physical_device = physical_devices
+    let suitable: Vec<vk::PhysicalDevice> = physical_devices
+        .into_iter()
+        .filter(|device| {
+            is_device_suitable(instance, device, window_surface, extensions)
+        })
+        .collect();
+    let properties: Vec<vk::PhysicalDeviceProperties> = suitable
         .iter()
-        .find(|device| is_device_suitable(&instance, device, window_surface))
-        .context("unable to pick a suitable device")?;
-    Ok(*physical_device)
+        .map(|device| unsafe {
+            instance.ash.get_physical_device_properties(*device)
+        })
+        .collect();
+    let device_types: Vec<vk::PhysicalDeviceType> = properties
+        .iter()
+        .map(|properties| properties.device_type)
+        .collect();
+
+    let index = policy.select(&device_types).with_context(|| {
+        let names: Vec<String> = properties.iter().map(device_name).collect();
+        format!(
+            "unable to pick a suitable device with the {:?} policy, \
+             suitable devices: {:?}",
+            policy, names
+        )
+    })?;
+
+    let selected = &properties[index];
+    if device_policy::is_software(selected.device_type)
+        && policy != DevicePolicy::PreferSoftware
+    {
+        log::warn!(
+            "no suitable gpu was found, rendering on the cpu with {}",
+            device_name(selected)
+        );
+    }
+    Ok(suitable[index])
+}
+
+/// The device's name, as reported by the driver.
+pub fn device_name(properties: &vk::PhysicalDeviceProperties) -> String {
+    unsafe { CStr::from_ptr(properties.device_name.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
 }
 
 /// Return true when the device is suitable for this application.
@@ -27,6 +77,7 @@ fn is_device_suitable(
     instance: &Instance,
     physical_device: &vk::PhysicalDevice,
     window_surface: &dyn WindowSurface,
+    extensions: &ExtensionRequests,
 ) -> bool {
     let queues_supported = QueueFamilyIndices::find(
         physical_device,
@@ -35,11 +86,9 @@ fn is_device_suitable(
     )
     .is_ok();
 
-    let features =
-        unsafe { instance.ash.get_physical_device_features(*physical_device) };
-
-    let extensions_supported =
-        check_required_extensions(&instance, physical_device);
+    let extensions_supported = extensions
+        .negotiate(&available_extension_names(instance, physical_device))
+        .is_ok();
 
     let format_available = if extensions_supported {
         unsafe { !window_surface.supported_formats(physical_device).is_empty() }
@@ -61,15 +110,51 @@ fn is_device_suitable(
         && extensions_supported
         && format_available
         && presentation_mode_available
-        && features.geometry_shader == vk::TRUE
 }
 
-/// Fetch a vector of all missing device extensions based on the required
-/// extensions.
-fn check_required_extensions(
+/// Return the set of device features to enable for this application.
+///
+/// Every feature is optional. Features the device doesn't support, like
+/// anisotropic filtering or wireframe rendering on some portability drivers,
+/// are left disabled.
+pub fn enabled_features(
     instance: &Instance,
     physical_device: &vk::PhysicalDevice,
-) -> bool {
+) -> vk::PhysicalDeviceFeatures {
+    let supported =
+        unsafe { instance.ash.get_physical_device_features(*physical_device) };
+    vk::PhysicalDeviceFeatures {
+        sampler_anisotropy: supported.sampler_anisotropy,
+        fill_mode_non_solid: supported.fill_mode_non_solid,
+        ..Default::default()
+    }
+}
+
+/// Return the device extensions this library always asks for.
+///
+/// The swapchain is required. The portability subset must be enabled
+/// whenever it's present. Full screen exclusive presentation is only
+/// offered when the instance was able to enable surface capabilities 2,
+/// which the extension depends on. Descriptor indexing lets the texture atlas
+/// update its descriptors without waiting for frames in flight.
+pub fn library_extensions(instance: &Instance) -> ExtensionRequests {
+    let requests = ExtensionRequests::new()
+        .require(ash::extensions::khr::Swapchain::name().to_str().unwrap())
+        .request(PORTABILITY_SUBSET)
+        .request(MAINTENANCE_3)
+        .request(DESCRIPTOR_INDEXING);
+    if instance.is_extension_enabled(vk::KhrGetSurfaceCapabilities2Fn::name()) {
+        requests.request(vk::ExtFullScreenExclusiveFn::name().to_str().unwrap())
+    } else {
+        requests
+    }
+}
+
+/// Get the name of every extension supported by the physical device.
+pub fn available_extension_names(
+    instance: &Instance,
+    physical_device: &vk::PhysicalDevice,
+) -> Vec<String> {
     let extensions = unsafe {
         instance
             .ash
@@ -78,33 +163,9 @@ fn check_required_extensions(
     };
     extensions
         .iter()
-        .map(|extension| {
-            String::from_utf8(
-                extension.extension_name.iter().map(|c| *c as u8).collect(),
-            )
-            .unwrap()
+        .map(|extension| unsafe {
+            CStr::from_ptr(extension.extension_name.as_ptr())
         })
-        .filter(|name| required_extensions().contains(name))
-        .collect::<Vec<String>>()
-        .is_empty()
-}
-
-/// Return the set of required device features for this application.
-///
-/// `is_device_suitable` should verify that all required features are supported
-/// by the chosen physical device.
-pub fn required_features() -> vk::PhysicalDeviceFeatures {
-    vk::PhysicalDeviceFeatures {
-        geometry_shader: 1,
-        ..Default::default()
-    }
-}
-
-/// Return the set of required device extensions for this application
-pub fn required_extensions() -> Vec<String> {
-    let swapchain = ash::extensions::khr::Swapchain::name()
-        .to_owned()
-        .into_string()
-        .unwrap();
-    vec![swapchain]
+        .filter_map(|name| name.to_str().ok().map(|name| name.to_owned()))
+        .collect()
 }
@@ -18,6 +18,9 @@ pub struct QueueFamilyIndices {
 
     /// the index for the presentation queue
     present_family_index: u32,
+
+    /// the index for the queue which uploads textures and buffers
+    transfer_family_index: u32,
 }
 
 impl QueueFamilyIndices {
@@ -26,7 +29,9 @@ impl QueueFamilyIndices {
     /// Yields an Err if any of the queues cannot be found.
     ///
     /// The implementation is greedy, e.g. the same queue will be used for
-    /// multiple operations where possible.
+    /// multiple operations where possible. The exception is the transfer
+    /// queue, which comes from a dedicated transfer family when the device
+    /// has one so uploads don't wait behind rendering.
     pub fn find(
         physical_device: &vk::PhysicalDevice,
         ash: &ash::Instance,
@@ -64,9 +69,13 @@ impl QueueFamilyIndices {
         let present_family_index = present_family
             .context("unable to find a queue which supports presentation")?;
 
+        let transfer_family_index = find_transfer_family(&queue_families)
+            .unwrap_or(graphics_family_index);
+
         Ok(Self {
             graphics_family_index,
             present_family_index,
+            transfer_family_index,
         })
     }
 
@@ -76,48 +85,134 @@ impl QueueFamilyIndices {
     ///
     /// Automatically handles duplicate indices
     pub fn as_queue_create_infos(&self) -> Vec<vk::DeviceQueueCreateInfo> {
-        let mut create_infos = vec![vk::DeviceQueueCreateInfo {
-            queue_family_index: self.graphics_family_index,
-            p_queue_priorities: Self::SINGLE_QUEUE_PRIORITY.as_ptr(),
-            queue_count: 1,
-            ..Default::default()
-        }];
+        let mut families = vec![self.graphics_family_index];
+        for family in &[self.present_family_index, self.transfer_family_index] {
+            if !families.contains(family) {
+                families.push(*family);
+            }
+        }
 
-        if self.graphics_family_index != self.present_family_index {
-            create_infos.push(vk::DeviceQueueCreateInfo {
-                queue_family_index: self.present_family_index,
+        families
+            .into_iter()
+            .map(|queue_family_index| vk::DeviceQueueCreateInfo {
+                queue_family_index,
                 p_queue_priorities: Self::SINGLE_QUEUE_PRIORITY.as_ptr(),
                 queue_count: 1,
                 ..Default::default()
-            });
-        }
-
-        create_infos
+            })
+            .collect()
     }
 
-    /// Return a tuple of the actual vulkan queues.
+    /// Return a tuple of the actual vulkan queues, the graphics queue, the
+    /// present queue, and the transfer queue.
     ///
     /// Handles duplicate queue family indices automatically.
     pub fn get_queues(
         &self,
         logical_device: &ash::Device,
-    ) -> Result<(Queue, Queue)> {
+    ) -> Result<(Queue, Queue, Queue)> {
         let raw_graphics_queue = unsafe {
             logical_device.get_device_queue(self.graphics_family_index, 0)
         };
         let graphics_queue =
             Queue::from_raw(raw_graphics_queue, self.graphics_family_index, 0);
 
-        let is_same = self.graphics_family_index == self.present_family_index;
-        let present_queue = if is_same {
-            graphics_queue
-        } else {
-            let raw_present_queue = unsafe {
-                logical_device.get_device_queue(self.present_family_index, 0)
-            };
-            Queue::from_raw(raw_present_queue, self.present_family_index, 0)
+        let queue_for_family = |family_index: u32| {
+            if family_index == self.graphics_family_index {
+                graphics_queue
+            } else {
+                let raw_queue =
+                    unsafe { logical_device.get_device_queue(family_index, 0) };
+                Queue::from_raw(raw_queue, family_index, 0)
+            }
         };
+        let present_queue = queue_for_family(self.present_family_index);
+        let transfer_queue = queue_for_family(self.transfer_family_index);
+
+        Ok((graphics_queue, present_queue, transfer_queue))
+    }
+}
+
+/// Find a family which supports transfers but not graphics, preferring
+/// families which don't support compute either.
+///
+/// Families with a coarse image transfer granularity can only copy whole
+/// mipmaps, so they're skipped to keep partial texture uploads valid.
+fn find_transfer_family(
+    queue_families: &[vk::QueueFamilyProperties],
+) -> Option<u32> {
+    let is_dedicated = |family: &vk::QueueFamilyProperties| {
+        let granularity = family.min_image_transfer_granularity;
+        family.queue_flags.contains(vk::QueueFlags::TRANSFER)
+            && !family.queue_flags.contains(vk::QueueFlags::GRAPHICS)
+            && (granularity.width, granularity.height, granularity.depth)
+                == (1, 1, 1)
+    };
+    let transfer_only = queue_families.iter().position(|family| {
+        is_dedicated(family)
+            && !family.queue_flags.contains(vk::QueueFlags::COMPUTE)
+    });
+    transfer_only
+        .or_else(|| queue_families.iter().position(is_dedicated))
+        .map(|index| index as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn family(
+        queue_flags: vk::QueueFlags,
+        granularity: u32,
+    ) -> vk::QueueFamilyProperties {
+        vk::QueueFamilyProperties {
+            queue_flags,
+            queue_count: 1,
+            min_image_transfer_granularity: vk::Extent3D {
+                width: granularity,
+                height: granularity,
+                depth: granularity,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn graphics_families_should_not_be_transfer_families() {
+        let families = [family(
+            vk::QueueFlags::GRAPHICS
+                | vk::QueueFlags::COMPUTE
+                | vk::QueueFlags::TRANSFER,
+            1,
+        )];
+        assert_eq!(find_transfer_family(&families), None);
+    }
+
+    #[test]
+    fn transfer_only_families_should_be_preferred() {
+        let families = [
+            family(vk::QueueFlags::GRAPHICS | vk::QueueFlags::TRANSFER, 1),
+            family(vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER, 1),
+            family(vk::QueueFlags::TRANSFER, 1),
+        ];
+        assert_eq!(find_transfer_family(&families), Some(2));
+    }
+
+    #[test]
+    fn compute_families_should_be_used_without_transfer_only_families() {
+        let families = [
+            family(vk::QueueFlags::GRAPHICS | vk::QueueFlags::TRANSFER, 1),
+            family(vk::QueueFlags::COMPUTE | vk::QueueFlags::TRANSFER, 1),
+        ];
+        assert_eq!(find_transfer_family(&families), Some(1));
+    }
 
-        Ok((graphics_queue, present_queue))
+    #[test]
+    fn coarse_granularity_families_should_be_skipped() {
+        let families = [
+            family(vk::QueueFlags::GRAPHICS | vk::QueueFlags::TRANSFER, 1),
+            family(vk::QueueFlags::TRANSFER, 0),
+        ];
+        assert_eq!(find_transfer_family(&families), None);
     }
 }
@@ -0,0 +1,317 @@
+use super::Device;
+
+use ash::{version::DeviceV1_0, vk};
+
+/// Moves a resource from one queue family to another, for example from the
+/// queue which uploaded it to the graphics queue.
+///
+/// A transfer is recorded in two halves with the same description: a release
+/// on a queue from the source family, then an acquire on a queue from the
+/// destination family. When both families are the same no ownership changes
+/// hands, the release records an ordinary barrier and the acquire records
+/// nothing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QueueTransfer {
+    /// The family which owns the resource before the transfer.
+    pub src_family: u32,
+
+    /// The family which owns the resource after the transfer.
+    pub dst_family: u32,
+
+    /// The work on the source queue which must finish before the release.
+    pub src_stage: vk::PipelineStageFlags,
+    pub src_access: vk::AccessFlags,
+
+    /// The work on the destination queue which waits for the acquire.
+    pub dst_stage: vk::PipelineStageFlags,
+    pub dst_access: vk::AccessFlags,
+
+    /// The layout transition done by the transfer. Ignored for buffers.
+    pub old_layout: vk::ImageLayout,
+    pub new_layout: vk::ImageLayout,
+}
+
+/// The stages, access masks, and families for one half of a transfer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct TransferBarrier {
+    src_stage: vk::PipelineStageFlags,
+    dst_stage: vk::PipelineStageFlags,
+    src_access: vk::AccessFlags,
+    dst_access: vk::AccessFlags,
+    src_family: u32,
+    dst_family: u32,
+}
+
+impl QueueTransfer {
+    /// True when the resource changes queue families, so both halves need
+    /// to be recorded and submitted.
+    pub fn is_ownership_transfer(&self) -> bool {
+        self.src_family != self.dst_family
+    }
+
+    /// Record the release of an image on the source queue.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the source
+    ///   family
+    /// - the image must be in the old layout
+    pub unsafe fn record_image_release(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        subresource_range: vk::ImageSubresourceRange,
+    ) {
+        self.record_image_barrier(
+            device,
+            command_buffer,
+            self.release(),
+            image,
+            subresource_range,
+        );
+    }
+
+    /// Record the acquire of an image on the destination queue. Nothing is
+    /// recorded unless the image changes queue families.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the
+    ///   destination family
+    /// - the release must be submitted first, and the acquire's submission
+    ///   must wait for it with a semaphore or fence
+    pub unsafe fn record_image_acquire(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        image: vk::Image,
+        subresource_range: vk::ImageSubresourceRange,
+    ) {
+        if let Some(barrier) = self.acquire() {
+            self.record_image_barrier(
+                device,
+                command_buffer,
+                barrier,
+                image,
+                subresource_range,
+            );
+        }
+    }
+
+    /// Record the release of part of a buffer on the source queue.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the source
+    ///   family
+    pub unsafe fn record_buffer_release(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        buffer: vk::Buffer,
+        offset: u64,
+        size: u64,
+    ) {
+        self.record_buffer_barrier(
+            device,
+            command_buffer,
+            self.release(),
+            buffer,
+            offset,
+            size,
+        );
+    }
+
+    /// Record the acquire of part of a buffer on the destination queue.
+    /// Nothing is recorded unless the buffer changes queue families.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the
+    ///   destination family
+    /// - the release must be submitted first, and the acquire's submission
+    ///   must wait for it with a semaphore or fence
+    pub unsafe fn record_buffer_acquire(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        buffer: vk::Buffer,
+        offset: u64,
+        size: u64,
+    ) {
+        if let Some(barrier) = self.acquire() {
+            self.record_buffer_barrier(
+                device,
+                command_buffer,
+                barrier,
+                buffer,
+                offset,
+                size,
+            );
+        }
+    }
+
+    /// The barrier recorded on the source queue.
+    ///
+    /// A release doesn't make anything visible, that's the acquire's job, so
+    /// it only waits for the source work.
+    fn release(&self) -> TransferBarrier {
+        if !self.is_ownership_transfer() {
+            return TransferBarrier {
+                src_stage: self.src_stage,
+                dst_stage: self.dst_stage,
+                src_access: self.src_access,
+                dst_access: self.dst_access,
+                src_family: vk::QUEUE_FAMILY_IGNORED,
+                dst_family: vk::QUEUE_FAMILY_IGNORED,
+            };
+        }
+        TransferBarrier {
+            src_stage: self.src_stage,
+            dst_stage: vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+            src_access: self.src_access,
+            dst_access: vk::AccessFlags::empty(),
+            src_family: self.src_family,
+            dst_family: self.dst_family,
+        }
+    }
+
+    /// The barrier recorded on the destination queue, if any.
+    ///
+    /// The source work was on another queue, so the acquire relies on the
+    /// semaphore or fence between the submissions rather than its source
+    /// stage.
+    fn acquire(&self) -> Option<TransferBarrier> {
+        if !self.is_ownership_transfer() {
+            return None;
+        }
+        Some(TransferBarrier {
+            src_stage: vk::PipelineStageFlags::TOP_OF_PIPE,
+            dst_stage: self.dst_stage,
+            src_access: vk::AccessFlags::empty(),
+            dst_access: self.dst_access,
+            src_family: self.src_family,
+            dst_family: self.dst_family,
+        })
+    }
+
+    unsafe fn record_image_barrier(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        barrier: TransferBarrier,
+        image: vk::Image,
+        subresource_range: vk::ImageSubresourceRange,
+    ) {
+        device.logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            barrier.src_stage,
+            barrier.dst_stage,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[vk::ImageMemoryBarrier {
+                old_layout: self.old_layout,
+                new_layout: self.new_layout,
+                src_queue_family_index: barrier.src_family,
+                dst_queue_family_index: barrier.dst_family,
+                image,
+                subresource_range,
+                src_access_mask: barrier.src_access,
+                dst_access_mask: barrier.dst_access,
+                ..Default::default()
+            }],
+        );
+    }
+
+    unsafe fn record_buffer_barrier(
+        &self,
+        device: &Device,
+        command_buffer: vk::CommandBuffer,
+        barrier: TransferBarrier,
+        buffer: vk::Buffer,
+        offset: u64,
+        size: u64,
+    ) {
+        device.logical_device.cmd_pipeline_barrier(
+            command_buffer,
+            barrier.src_stage,
+            barrier.dst_stage,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[vk::BufferMemoryBarrier {
+                src_access_mask: barrier.src_access,
+                dst_access_mask: barrier.dst_access,
+                src_queue_family_index: barrier.src_family,
+                dst_queue_family_index: barrier.dst_family,
+                buffer,
+                offset,
+                size,
+                ..Default::default()
+            }],
+            &[],
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn upload(src_family: u32, dst_family: u32) -> QueueTransfer {
+        QueueTransfer {
+            src_family,
+            dst_family,
+            src_stage: vk::PipelineStageFlags::TRANSFER,
+            src_access: vk::AccessFlags::TRANSFER_WRITE,
+            dst_stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            dst_access: vk::AccessFlags::SHADER_READ,
+            old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        }
+    }
+
+    #[test]
+    fn staying_on_one_family_should_record_a_single_plain_barrier() {
+        let transfer = upload(0, 0);
+        assert!(!transfer.is_ownership_transfer());
+        assert_eq!(
+            transfer.release(),
+            TransferBarrier {
+                src_stage: vk::PipelineStageFlags::TRANSFER,
+                dst_stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+                src_access: vk::AccessFlags::TRANSFER_WRITE,
+                dst_access: vk::AccessFlags::SHADER_READ,
+                src_family: vk::QUEUE_FAMILY_IGNORED,
+                dst_family: vk::QUEUE_FAMILY_IGNORED,
+            }
+        );
+        assert_eq!(transfer.acquire(), None);
+    }
+
+    #[test]
+    fn the_release_should_only_wait_for_the_source_work() {
+        let release = upload(1, 0).release();
+        assert_eq!(release.src_stage, vk::PipelineStageFlags::TRANSFER);
+        assert_eq!(release.src_access, vk::AccessFlags::TRANSFER_WRITE);
+        assert_eq!(release.dst_stage, vk::PipelineStageFlags::BOTTOM_OF_PIPE);
+        assert!(release.dst_access.is_empty());
+        assert_eq!((release.src_family, release.dst_family), (1, 0));
+    }
+
+    #[test]
+    fn the_acquire_should_make_the_resource_visible_to_the_destination() {
+        let acquire = upload(1, 0).acquire().unwrap();
+        assert_eq!(acquire.src_stage, vk::PipelineStageFlags::TOP_OF_PIPE);
+        assert!(acquire.src_access.is_empty());
+        assert_eq!(acquire.dst_stage, vk::PipelineStageFlags::FRAGMENT_SHADER);
+        assert_eq!(acquire.dst_access, vk::AccessFlags::SHADER_READ);
+        assert_eq!((acquire.src_family, acquire.dst_family), (1, 0));
+    }
+
+    #[test]
+    fn external_families_should_always_transfer() {
+        assert!(upload(vk::QUEUE_FAMILY_EXTERNAL, 0).is_ownership_transfer());
+    }
+}
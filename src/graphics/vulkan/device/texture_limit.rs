@@ -0,0 +1,137 @@
+//! Functions for sizing the texture and sampler arrays bound by the 2d
+//! pipelines.
+
+use ash::vk;
+
+/// The most textures an atlas will manage, even when the device could bind
+/// more. Bigger arrays cost memory and time when the descriptor set is first
+/// written without letting anything new be drawn.
+pub const TEXTURE_LIMIT_CAP: u32 = 4096;
+
+/// The most samplers an atlas will manage, even when the device could bind
+/// more. Devices only guarantee 4000 sampler objects in total, so the atlas
+/// leaves room for samplers used elsewhere.
+pub const SAMPLER_LIMIT_CAP: u32 = 1024;
+
+/// The number of textures which can be bound in the fragment shader's
+/// texture array on a device with these limits.
+///
+/// Textures are sampled images, samplers are bound in their own array so
+/// they don't count against the texture limit.
+pub fn texture_limit(limits: &vk::PhysicalDeviceLimits) -> u32 {
+    limits
+        .max_per_stage_descriptor_sampled_images
+        .min(limits.max_descriptor_set_sampled_images)
+        .min(TEXTURE_LIMIT_CAP)
+}
+
+/// The number of textures which can be bound in the texture array when the
+/// atlas's descriptor set is created for update-after-bind, which has its
+/// own limits.
+pub fn update_after_bind_texture_limit(
+    properties: &vk::PhysicalDeviceDescriptorIndexingProperties,
+) -> u32 {
+    properties
+        .max_per_stage_descriptor_update_after_bind_sampled_images
+        .min(properties.max_descriptor_set_update_after_bind_sampled_images)
+        .min(TEXTURE_LIMIT_CAP)
+}
+
+/// The number of samplers which can be bound in the fragment shader's
+/// sampler array on a device with these limits.
+pub fn sampler_limit(limits: &vk::PhysicalDeviceLimits) -> u32 {
+    limits
+        .max_per_stage_descriptor_samplers
+        .min(limits.max_descriptor_set_samplers)
+        .min(SAMPLER_LIMIT_CAP)
+}
+
+/// The number of samplers which can be bound in the sampler array when the
+/// atlas's descriptor set is created for update-after-bind.
+pub fn update_after_bind_sampler_limit(
+    properties: &vk::PhysicalDeviceDescriptorIndexingProperties,
+) -> u32 {
+    properties
+        .max_per_stage_descriptor_update_after_bind_samplers
+        .min(properties.max_descriptor_set_update_after_bind_samplers)
+        .min(SAMPLER_LIMIT_CAP)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn limits(per_stage: u32, per_set: u32) -> vk::PhysicalDeviceLimits {
+        vk::PhysicalDeviceLimits {
+            max_per_stage_descriptor_sampled_images: per_stage,
+            max_descriptor_set_sampled_images: per_set,
+            max_per_stage_descriptor_samplers: 16,
+            max_descriptor_set_samplers: 96,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn small_devices_should_use_their_per_stage_limit() {
+        assert_eq!(texture_limit(&limits(16, 96)), 16);
+    }
+
+    #[test]
+    fn the_smallest_limit_should_win() {
+        assert_eq!(texture_limit(&limits(1000, 200)), 200);
+        assert_eq!(texture_limit(&limits(100, 200)), 100);
+    }
+
+    #[test]
+    fn sampler_limits_should_not_limit_textures() {
+        assert_eq!(texture_limit(&limits(1000, 1000)), 1000);
+    }
+
+    #[test]
+    fn huge_limits_should_be_capped() {
+        assert_eq!(texture_limit(&limits(1 << 20, 1 << 20)), TEXTURE_LIMIT_CAP);
+    }
+
+    #[test]
+    fn samplers_should_use_the_smallest_sampler_limit() {
+        assert_eq!(sampler_limit(&limits(1000, 1000)), 16);
+
+        let mut limits = limits(16, 96);
+        limits.max_per_stage_descriptor_samplers = 2000;
+        limits.max_descriptor_set_samplers = 500;
+        assert_eq!(sampler_limit(&limits), 500);
+
+        limits.max_descriptor_set_samplers = 1 << 20;
+        assert_eq!(sampler_limit(&limits), SAMPLER_LIMIT_CAP);
+    }
+
+    #[test]
+    fn update_after_bind_samplers_should_use_their_own_limits() {
+        let properties = vk::PhysicalDeviceDescriptorIndexingProperties {
+            max_per_stage_descriptor_update_after_bind_samplers: 64,
+            max_descriptor_set_update_after_bind_samplers: 200,
+            ..Default::default()
+        };
+        assert_eq!(update_after_bind_sampler_limit(&properties), 64);
+    }
+
+    #[test]
+    fn update_after_bind_should_use_its_own_limits() {
+        let properties = vk::PhysicalDeviceDescriptorIndexingProperties {
+            max_per_stage_descriptor_update_after_bind_sampled_images: 1000,
+            max_descriptor_set_update_after_bind_sampled_images: 500,
+            ..Default::default()
+        };
+        assert_eq!(update_after_bind_texture_limit(&properties), 500);
+
+        let properties = vk::PhysicalDeviceDescriptorIndexingProperties {
+            max_per_stage_descriptor_update_after_bind_sampled_images: 1 << 20,
+            max_descriptor_set_update_after_bind_sampled_images: 1 << 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            update_after_bind_texture_limit(&properties),
+            TEXTURE_LIMIT_CAP
+        );
+    }
+}
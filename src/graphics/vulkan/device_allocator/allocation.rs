@@ -11,6 +11,47 @@ impl Allocation {
             byte_size: 0,
             memory: vk::DeviceMemory::null(),
             memory_type_index: 0,
+            imported: false,
+            dedicated: false,
+        }
+    }
+
+    /// Create an allocation for memory which was imported from an external
+    /// handle.
+    ///
+    /// Imported memory is never owned by a DeviceAllocator, it's freed
+    /// directly by `Device::free_memory`.
+    pub fn imported(
+        memory: vk::DeviceMemory,
+        byte_size: vk::DeviceSize,
+        memory_type_index: u32,
+    ) -> Self {
+        Self {
+            memory,
+            offset: 0,
+            byte_size,
+            memory_type_index,
+            imported: true,
+            dedicated: false,
+        }
+    }
+
+    /// Create an allocation for memory which holds a single image or buffer.
+    ///
+    /// Dedicated memory is never owned by a DeviceAllocator, it's freed
+    /// directly by `Device::free_memory`.
+    pub fn dedicated(
+        memory: vk::DeviceMemory,
+        byte_size: vk::DeviceSize,
+        memory_type_index: u32,
+    ) -> Self {
+        Self {
+            memory,
+            offset: 0,
+            byte_size,
+            memory_type_index,
+            imported: false,
+            dedicated: true,
         }
     }
 
@@ -18,4 +59,19 @@ impl Allocation {
     pub fn is_null(&self) -> bool {
         self.memory == vk::DeviceMemory::null()
     }
+
+    /// The index of the memory type the allocation was made from.
+    pub fn memory_type_index(&self) -> u32 {
+        self.memory_type_index
+    }
+
+    /// Returns true when the memory holds a single image or buffer.
+    pub fn is_dedicated(&self) -> bool {
+        self.dedicated
+    }
+
+    /// Returns true when the memory was imported from an external handle.
+    pub fn is_imported(&self) -> bool {
+        self.imported
+    }
 }
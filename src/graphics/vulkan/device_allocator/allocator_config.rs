@@ -0,0 +1,140 @@
+use super::{
+    AllocatorConfig, DeviceAllocator, MemUnit, PoolAllocator,
+    SharedRefAllocator, SizeSelector,
+};
+
+use anyhow::{bail, Result};
+
+impl AllocatorConfig {
+    /// Set the page size which every allocation is rounded up to.
+    pub fn with_page_size(mut self, page_size: MemUnit) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Set the small pools' threshold and block size.
+    pub fn with_small_pools(
+        mut self,
+        threshold: MemUnit,
+        block_size: MemUnit,
+    ) -> Self {
+        self.small_threshold = threshold;
+        self.small_block_size = block_size;
+        self
+    }
+
+    /// Set the large pools' threshold and block size.
+    pub fn with_large_pools(
+        mut self,
+        threshold: MemUnit,
+        block_size: MemUnit,
+    ) -> Self {
+        self.large_threshold = threshold;
+        self.large_block_size = block_size;
+        self
+    }
+
+    /// Turn the allocation report on or off.
+    pub fn with_metrics(mut self, metrics: bool) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Check that every allocation below each threshold fits in that pool's
+    /// blocks.
+    pub fn validate(&self) -> Result<()> {
+        let page_size = self.page_size.to_bytes();
+        let small_threshold = self.small_threshold.to_bytes();
+        let large_threshold = self.large_threshold.to_bytes();
+        if page_size == 0 {
+            bail!("the allocator's page size must not be zero");
+        }
+        if small_threshold > large_threshold {
+            bail!(
+                "the small threshold {:?} is above the large threshold {:?}",
+                self.small_threshold,
+                self.large_threshold
+            );
+        }
+        if small_threshold > self.small_block_size.to_bytes() {
+            bail!(
+                "small pool blocks of {:?} can't hold allocations up to {:?}",
+                self.small_block_size,
+                self.small_threshold
+            );
+        }
+        if large_threshold > self.large_block_size.to_bytes() {
+            bail!(
+                "large pool blocks of {:?} can't hold allocations up to {:?}",
+                self.large_block_size,
+                self.large_threshold
+            );
+        }
+        Ok(())
+    }
+
+    /// The pools used for a single memory type, which allocate their blocks
+    /// from `device`.
+    pub fn pools<Alloc: DeviceAllocator>(
+        &self,
+        device: &SharedRefAllocator<Alloc>,
+    ) -> impl DeviceAllocator {
+        SizeSelector::new(
+            PoolAllocator::new(device.clone(), self.small_block_size),
+            self.small_threshold,
+            SizeSelector::new(
+                PoolAllocator::new(device.clone(), self.large_block_size),
+                self.large_threshold,
+                device.clone(),
+            ),
+        )
+    }
+}
+
+impl Default for AllocatorConfig {
+    fn default() -> Self {
+        Self {
+            page_size: MemUnit::KiB(1),
+            small_threshold: MemUnit::KiB(512),
+            small_block_size: MemUnit::MiB(1),
+            large_threshold: MemUnit::MiB(256),
+            large_block_size: MemUnit::MiB(512),
+            metrics: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_default_config_should_be_valid() {
+        assert!(AllocatorConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn thresholds_should_fit_in_their_blocks() {
+        let config = AllocatorConfig::default()
+            .with_small_pools(MemUnit::MiB(2), MemUnit::MiB(1));
+        assert!(config.validate().is_err());
+
+        let config = AllocatorConfig::default()
+            .with_large_pools(MemUnit::GiB(1), MemUnit::MiB(512));
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn thresholds_should_be_ordered() {
+        let config = AllocatorConfig::default()
+            .with_small_pools(MemUnit::MiB(64), MemUnit::MiB(64))
+            .with_large_pools(MemUnit::MiB(16), MemUnit::MiB(64));
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn pages_should_not_be_empty() {
+        let config = AllocatorConfig::default().with_page_size(MemUnit::B(0));
+        assert!(config.validate().is_err());
+    }
+}
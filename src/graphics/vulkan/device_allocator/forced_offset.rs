@@ -34,13 +34,22 @@ impl<Alloc: DeviceAllocator> DeviceAllocator for ForcedOffsetAllocator<Alloc> {
     unsafe fn allocate(
         &mut self,
         allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
+        if !self.offset().is_multiple_of(alignment.max(1)) {
+            anyhow::bail!(
+                "the forced offset {} isn't aligned to {}",
+                self.offset(),
+                alignment
+            );
+        }
         let expanded_allocate_info = vk::MemoryAllocateInfo {
             memory_type_index: allocate_info.memory_type_index,
             allocation_size: allocate_info.allocation_size + self.offset(),
             ..Default::default()
         };
-        let mut allocation = self.allocator.allocate(expanded_allocate_info)?;
+        let mut allocation =
+            self.allocator.allocate(expanded_allocate_info, alignment)?;
         allocation.offset += self.offset();
         allocation.byte_size = allocate_info.allocation_size;
         Ok(allocation)
@@ -0,0 +1,316 @@
+//! A test harness which drives allocators with random allocate and free
+//! sequences, checking that the results never overlap and that every block
+//! of device memory is returned once everything is freed.
+
+use super::{Allocation, DeviceAllocator};
+
+use crate::noise::Rng;
+
+use anyhow::{bail, Result};
+use ash::{vk, vk::Handle};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// Stands in for the device at the bottom of an allocator stack. Memory
+/// handles are made up, so no GPU is needed.
+#[derive(Clone)]
+pub struct FakeMemory {
+    state: Rc<RefCell<FakeMemoryState>>,
+}
+
+#[derive(Default)]
+struct FakeMemoryState {
+    next_handle: u64,
+    live: HashMap<vk::DeviceMemory, vk::DeviceSize>,
+    total_blocks: u64,
+}
+
+/// Drives an allocator with random requests and checks every result.
+pub struct FuzzedAllocator<Alloc: DeviceAllocator> {
+    allocator: Alloc,
+    rng: Rng,
+    live: Vec<Request>,
+    memory_types: u32,
+    max_size: u64,
+}
+
+/// An outstanding allocation and what was asked for.
+struct Request {
+    allocation: Allocation,
+    size: u64,
+    alignment: u64,
+    memory_type_index: u32,
+}
+
+impl FakeMemory {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(FakeMemoryState::default())),
+        }
+    }
+
+    /// The number of memory blocks which haven't been freed.
+    pub fn live_blocks(&self) -> usize {
+        self.state.borrow().live.len()
+    }
+
+    /// The number of memory blocks allocated over the fake's lifetime.
+    pub fn total_blocks(&self) -> u64 {
+        self.state.borrow().total_blocks
+    }
+}
+
+impl DeviceAllocator for FakeMemory {
+    unsafe fn allocate(
+        &mut self,
+        allocate_info: vk::MemoryAllocateInfo,
+        _alignment: vk::DeviceSize,
+    ) -> Result<Allocation> {
+        let mut state = self.state.borrow_mut();
+        state.next_handle += 1;
+        state.total_blocks += 1;
+        let memory = vk::DeviceMemory::from_raw(state.next_handle);
+        state.live.insert(memory, allocate_info.allocation_size);
+
+        let mut allocation = Allocation::null();
+        allocation.memory = memory;
+        allocation.byte_size = allocate_info.allocation_size;
+        allocation.memory_type_index = allocate_info.memory_type_index;
+        Ok(allocation)
+    }
+
+    unsafe fn free(&mut self, allocation: &Allocation) -> Result<()> {
+        if allocation.is_null() {
+            return Ok(());
+        }
+        if self
+            .state
+            .borrow_mut()
+            .live
+            .remove(&allocation.memory)
+            .is_none()
+        {
+            bail!("freed device memory which isn't allocated {:?}", allocation);
+        }
+        Ok(())
+    }
+}
+
+impl<Alloc: DeviceAllocator> FuzzedAllocator<Alloc> {
+    /// Fuzz an allocator with requests of up to `max_size` bytes spread over
+    /// a few memory types.
+    pub fn new(allocator: Alloc, seed: u64, max_size: u64) -> Self {
+        Self {
+            allocator,
+            rng: Rng::new(seed),
+            live: vec![],
+            memory_types: 3,
+            max_size,
+        }
+    }
+
+    /// Randomly allocate and free, checking the invariants after each step.
+    ///
+    /// Allocation is a little more likely than freeing so the allocator gets
+    /// fragmented before everything is released.
+    pub fn run(&mut self, steps: usize) -> Result<()> {
+        for _ in 0..steps {
+            if self.live.is_empty() || self.rng.chance(0.55) {
+                self.allocate()?;
+            } else {
+                let index = self.rng.below(self.live.len());
+                self.free(index)?;
+            }
+            self.check_invariants()?;
+        }
+        Ok(())
+    }
+
+    /// Free every outstanding allocation, in random order.
+    pub fn free_all(&mut self) -> Result<()> {
+        while !self.live.is_empty() {
+            let index = self.rng.below(self.live.len());
+            self.free(index)?;
+        }
+        Ok(())
+    }
+
+    /// The allocation at `index`, for tests which misuse it on purpose.
+    pub fn allocation(&self, index: usize) -> Allocation {
+        self.live[index].allocation.clone()
+    }
+
+    /// The allocator being fuzzed.
+    pub fn allocator(&mut self) -> &mut Alloc {
+        &mut self.allocator
+    }
+
+    fn allocate(&mut self) -> Result<()> {
+        // Favor small requests, like most buffers, with the odd big one.
+        let size = if self.rng.chance(0.9) {
+            1 + self.rng.below(4096) as u64
+        } else {
+            1 + self.rng.below(self.max_size as usize) as u64
+        };
+        let alignment = 1 << self.rng.below(9);
+        let memory_type_index = self.rng.below(self.memory_types as usize);
+        let allocation = unsafe {
+            self.allocator.allocate(
+                vk::MemoryAllocateInfo {
+                    allocation_size: size,
+                    memory_type_index: memory_type_index as u32,
+                    ..Default::default()
+                },
+                alignment,
+            )?
+        };
+        self.live.push(Request {
+            allocation,
+            size,
+            alignment,
+            memory_type_index: memory_type_index as u32,
+        });
+        Ok(())
+    }
+
+    fn free(&mut self, index: usize) -> Result<()> {
+        let request = self.live.swap_remove(index);
+        unsafe { self.allocator.free(&request.allocation) }
+    }
+
+    fn check_invariants(&self) -> Result<()> {
+        for request in &self.live {
+            let allocation = &request.allocation;
+            if allocation.offset % request.alignment != 0 {
+                bail!(
+                    "{:?} isn't aligned to {}",
+                    allocation,
+                    request.alignment
+                );
+            }
+            if allocation.byte_size < request.size {
+                bail!("{:?} is smaller than {}", allocation, request.size);
+            }
+            if allocation.memory_type_index != request.memory_type_index {
+                bail!(
+                    "{:?} should use memory type {}",
+                    allocation,
+                    request.memory_type_index
+                );
+            }
+        }
+
+        let mut by_memory: HashMap<vk::DeviceMemory, Vec<&Allocation>> =
+            HashMap::new();
+        for request in &self.live {
+            by_memory
+                .entry(request.allocation.memory)
+                .or_default()
+                .push(&request.allocation);
+        }
+        for allocations in by_memory.values_mut() {
+            allocations.sort_by_key(|allocation| allocation.offset);
+            for pair in allocations.windows(2) {
+                if pair[0].offset + pair[0].byte_size > pair[1].offset {
+                    bail!("{:?} overlaps {:?}", pair[0], pair[1]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::vulkan::device_allocator::{
+        AllocatorConfig, MemUnit, PageAllocator, SharedRefAllocator,
+        Suballocator,
+    };
+
+    /// The same composition as `build_allocator`, scaled down so the fuzzer
+    /// fills and empties pools quickly.
+    fn standard_stack(memory: FakeMemory) -> impl DeviceAllocator {
+        let config = AllocatorConfig::default()
+            .with_small_pools(MemUnit::KiB(16), MemUnit::KiB(64))
+            .with_large_pools(MemUnit::KiB(512), MemUnit::MiB(1));
+        config.validate().unwrap();
+        PageAllocator::new(
+            config.pools(&SharedRefAllocator::new(memory)),
+            config.page_size,
+        )
+    }
+
+    #[test]
+    fn the_standard_stack_should_reclaim_every_block() -> Result<()> {
+        for seed in 0..8 {
+            let memory = FakeMemory::new();
+            let mut fuzzer = FuzzedAllocator::new(
+                standard_stack(memory.clone()),
+                seed,
+                MemUnit::MiB(2).to_bytes(),
+            );
+            fuzzer.run(2000)?;
+            assert!(memory.live_blocks() > 0);
+            fuzzer.free_all()?;
+            assert_eq!(memory.live_blocks(), 0, "seed {}", seed);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn pools_should_reuse_freed_space() -> Result<()> {
+        let memory = FakeMemory::new();
+        let mut allocator = standard_stack(memory.clone());
+        let request = vk::MemoryAllocateInfo {
+            allocation_size: MemUnit::KiB(4).to_bytes(),
+            ..Default::default()
+        };
+        let mut allocations = (0..16)
+            .map(|_| unsafe { allocator.allocate(request, 256) })
+            .collect::<Result<Vec<Allocation>>>()?;
+        for allocation in allocations.drain(..8) {
+            unsafe { allocator.free(&allocation)? };
+        }
+        for _ in 0..8 {
+            allocations.push(unsafe { allocator.allocate(request, 256)? });
+        }
+        assert_eq!(memory.total_blocks(), 1);
+
+        for allocation in &allocations {
+            unsafe { allocator.free(allocation)? };
+        }
+        assert_eq!(memory.live_blocks(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn a_suballocator_should_be_empty_after_freeing_everything() -> Result<()> {
+        let mut block = Allocation::null();
+        block.byte_size = MemUnit::MiB(64).to_bytes();
+        let mut fuzzer = FuzzedAllocator::new(
+            Suballocator::new(block),
+            7,
+            MemUnit::KiB(64).to_bytes(),
+        );
+        fuzzer.memory_types = 1;
+        fuzzer.run(3000)?;
+        fuzzer.free_all()?;
+        assert!(fuzzer.allocator().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn double_frees_should_be_reported() -> Result<()> {
+        let mut fuzzer = FuzzedAllocator::new(
+            standard_stack(FakeMemory::new()),
+            3,
+            MemUnit::KiB(4).to_bytes(),
+        );
+        fuzzer.run(50)?;
+        let allocation = fuzzer.allocation(0);
+        fuzzer.free(0)?;
+        assert!(unsafe { fuzzer.allocator().free(&allocation) }.is_err());
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ use std::collections::HashMap;
 
 /// Types which implement this trait can be used by the Metrics Allocator to
 /// render a report on memory allocations.
-pub trait MetricsReport {
+pub trait MetricsReport: Send {
     /// Render the metrics report.
     ///
     /// The output is implementation-defined (console, file, format, etc..).
@@ -75,8 +75,9 @@ impl<Alloc: DeviceAllocator> DeviceAllocator for MetricsAllocator<Alloc> {
     unsafe fn allocate(
         &mut self,
         allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
-        let allocation = self.allocator.allocate(allocate_info)?;
+        let allocation = self.allocator.allocate(allocate_info, alignment)?;
         self.record_allocation(&allocation);
         Ok(allocation)
     }
@@ -11,6 +11,7 @@
 //! - pooling allocator -> something something, gpu memory pools
 
 mod allocation;
+mod allocator_config;
 mod forced_offset;
 mod mem_unit;
 mod metrics;
@@ -22,6 +23,8 @@ mod size_selector;
 mod suballocator;
 mod type_index;
 
+#[cfg(test)]
+mod fuzzed_allocator;
 #[cfg(test)]
 mod stub_allocator;
 
@@ -48,14 +51,53 @@ pub struct Allocation {
     pub offset: vk::DeviceSize,
     pub byte_size: vk::DeviceSize,
     memory_type_index: u32,
+
+    /// True when the memory was imported from an external handle rather than
+    /// allocated by a DeviceAllocator.
+    imported: bool,
+
+    /// True when the memory was allocated for a single image or buffer
+    /// rather than by a DeviceAllocator.
+    dedicated: bool,
+}
+
+/// How the device's allocators are composed.
+///
+/// Allocations are rounded up to the page size, then small allocations come
+/// from small pool blocks, medium allocations from large pool blocks, and
+/// anything bigger is allocated directly. The defaults suit a mix of buffers
+/// and textures. Apps with millions of tiny buffers might want bigger small
+/// pools, apps with a few huge textures might want a lower large threshold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllocatorConfig {
+    /// Every allocation is rounded up to a multiple of the page size.
+    pub page_size: MemUnit,
+
+    /// Allocations below this size come from the small pools.
+    pub small_threshold: MemUnit,
+
+    /// The size of each block in the small pools.
+    pub small_block_size: MemUnit,
+
+    /// Allocations below this size, and above the small threshold, come from
+    /// the large pools. Anything bigger gets its own device memory.
+    pub large_threshold: MemUnit,
+
+    /// The size of each block in the large pools.
+    pub large_block_size: MemUnit,
+
+    /// Print a report of every device allocation when the device is dropped.
+    pub metrics: bool,
 }
 
 /// The external device memory allocation interface. This is the api used by
 /// applications to allocate and free memory on the gpu.
 pub trait DeviceAllocator {
-    /// Allocate device memory with the provided type index and size.
+    /// Allocate device memory with the provided type index and size. The
+    /// allocation's offset is a multiple of `alignment`, which usually comes
+    /// from `vk::MemoryRequirements`.
     ///
-    /// # unsafe because
+    /// # Safety
     ///
     /// - it is the responsibility of the caller to free the returned memory
     ///   when it is no longer in use
@@ -65,64 +107,71 @@ pub trait DeviceAllocator {
     unsafe fn allocate(
         &mut self,
         allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation>;
 
     /// Free an allocated piece of device memory.
     ///
-    /// # unsafe because
+    /// # Safety
     ///
     /// - it is the responsibility of the caller to know when the GPU is no
     ///   longer using the allocation
     unsafe fn free(&mut self, allocation: &Allocation) -> Result<()>;
 }
 
-/// Build the standard allocator implementation.
+/// Build the allocator described by the config.
 ///
-/// The return is a boxed impl so that consumers are not dependent on the
-/// specific implementation (often the full type is unwieldy because it is a
+/// The return is boxed so that consumers are not dependent on the specific
+/// implementation (often the full type is unwieldy because it is a
 /// composition of DeviceAllocator implementations).
 ///
 /// The caller is responsible for keeping the ash instance, logical device, and
 /// physical device alive for at least as long as the allocator exists.
-pub fn build_standard_allocator(
+pub fn build_allocator(
     ash_instance: ash::Instance,
     logical_device: ash::Device,
     physical_device: ash::vk::PhysicalDevice,
-) -> Box<impl DeviceAllocator> {
-    let device_allocator = SharedRefAllocator::new(MetricsAllocator::new(
-        "Device Allocator",
-        ConsoleMarkdownReport::new(ash_instance.clone(), physical_device),
-        PassthroughAllocator::create(logical_device),
-    ));
+    config: &AllocatorConfig,
+) -> Result<Box<dyn DeviceAllocator + Send>> {
+    config.validate()?;
+
+    let passthrough = PassthroughAllocator::create(logical_device);
+    let device_allocator: SharedRefAllocator<Box<dyn DeviceAllocator + Send>> =
+        SharedRefAllocator::new(if config.metrics {
+            Box::new(MetricsAllocator::new(
+                "Device Allocator",
+                ConsoleMarkdownReport::new(
+                    ash_instance.clone(),
+                    physical_device,
+                ),
+                passthrough,
+            ))
+        } else {
+            Box::new(passthrough)
+        });
 
     let typed_allocator = PageAllocator::new(
         TypeIndexAllocator::new(
             &ash_instance,
             physical_device,
-            |_memory_type_index, _memory_type| {
-                SizeSelector::new(
-                    // For allocations below 512KiB
-                    PoolAllocator::new(
-                        device_allocator.clone(),
-                        MemUnit::MiB(1),
-                    ),
-                    MemUnit::KiB(512),
-                    // for allocations above 512KiB
-                    SizeSelector::new(
-                        // for allocations below 256MiB
-                        PoolAllocator::new(
-                            device_allocator.clone(),
-                            MemUnit::MiB(512),
-                        ),
-                        MemUnit::MiB(256),
-                        // for allocations above 256MiB
-                        device_allocator.clone(),
-                    ),
-                )
-            },
+            |_memory_type_index, _memory_type| config.pools(&device_allocator),
         ),
-        MemUnit::KiB(1),
+        config.page_size,
     );
 
-    Box::new(typed_allocator)
+    Ok(Box::new(typed_allocator))
+}
+
+impl<Alloc: DeviceAllocator + ?Sized> DeviceAllocator for Box<Alloc> {
+    unsafe fn allocate(
+        &mut self,
+        allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
+    ) -> Result<Allocation> {
+        self.as_mut().allocate(allocate_info, alignment)
+    }
+
+    unsafe fn free(&mut self, allocation: &Allocation) -> Result<()> {
+        self.as_mut().free(allocation)
+    }
 }
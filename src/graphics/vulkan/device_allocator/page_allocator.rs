@@ -30,6 +30,7 @@ impl<Allocator: DeviceAllocator> DeviceAllocator for PageAllocator<Allocator> {
     unsafe fn allocate(
         &mut self,
         memory_allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
         let aligned_memory_allocate_info = vk::MemoryAllocateInfo {
             memory_type_index: memory_allocate_info.memory_type_index,
@@ -39,8 +40,9 @@ impl<Allocator: DeviceAllocator> DeviceAllocator for PageAllocator<Allocator> {
         };
 
         // used the aligned size when actually allocating
-        let mut allocation =
-            self.parent.allocate(aligned_memory_allocate_info)?;
+        let mut allocation = self
+            .parent
+            .allocate(aligned_memory_allocate_info, alignment)?;
 
         // but tell the caller the size they expect
         allocation.byte_size = memory_allocate_info.allocation_size;
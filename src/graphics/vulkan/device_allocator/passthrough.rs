@@ -28,6 +28,7 @@ impl DeviceAllocator for PassthroughAllocator {
     unsafe fn allocate(
         &mut self,
         allocate_info: vk::MemoryAllocateInfo,
+        _alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
         Ok(Allocation {
             memory: self
@@ -36,12 +37,14 @@ impl DeviceAllocator for PassthroughAllocator {
             offset: 0,
             byte_size: allocate_info.allocation_size,
             memory_type_index: allocate_info.memory_type_index,
+            imported: false,
+            dedicated: false,
         })
     }
 
     /// Free the allocation's underlying memory.
     ///
-    /// # unsafe because
+    /// # Safety
     ///
     /// - it is the responsibility of the caller to ensure the allocation's
     ///   device memory is no longer in use
@@ -26,27 +26,32 @@ impl<Allocator: DeviceAllocator> DeviceAllocator for PoolAllocator<Allocator> {
     unsafe fn allocate(
         &mut self,
         memory_allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
         if memory_allocate_info.allocation_size > self.block_size {
             anyhow::bail!("This pool is unable to allocate a block that large!")
         }
 
         for (_, suballocator) in &mut self.blocks {
-            if let Ok(allocation) = suballocator.allocate(memory_allocate_info)
+            if let Ok(allocation) =
+                suballocator.allocate(memory_allocate_info, alignment)
             {
                 return Ok(allocation);
             }
         }
 
-        let new_block_allocation =
-            self.parent.allocate(vk::MemoryAllocateInfo {
+        let new_block_allocation = self.parent.allocate(
+            vk::MemoryAllocateInfo {
                 memory_type_index: memory_allocate_info.memory_type_index,
                 allocation_size: self.block_size,
                 ..Default::default()
-            })?;
+            },
+            alignment,
+        )?;
         let mut suballocator = Suballocator::new(new_block_allocation.clone());
 
-        let allocation = suballocator.allocate(memory_allocate_info)?;
+        let allocation =
+            suballocator.allocate(memory_allocate_info, alignment)?;
         self.blocks
             .insert(new_block_allocation.memory, suballocator);
 
@@ -2,7 +2,7 @@ use super::{Allocation, DeviceAllocator};
 
 use anyhow::Result;
 use ash::vk;
-use std::{cell::RefCell, rc::Rc};
+use std::sync::{Arc, Mutex};
 
 /// A device allocator implementation which represents a shared reference to an
 /// underlying allocator implementation.
@@ -10,14 +10,17 @@ use std::{cell::RefCell, rc::Rc};
 /// This is useful because multiple other allocators often compose over the
 /// passthrough. When this occurs, they often need to use the *same* instance
 /// of the passthrough allocator.
+///
+/// The shared allocator is behind a mutex so the composed allocator can be
+/// sent to other threads along with the device which owns it.
 pub struct SharedRefAllocator<Alloc: DeviceAllocator> {
-    allocator: Rc<RefCell<Alloc>>,
+    allocator: Arc<Mutex<Alloc>>,
 }
 
 impl<Alloc: DeviceAllocator> SharedRefAllocator<Alloc> {
     pub fn new(allocator: Alloc) -> Self {
         Self {
-            allocator: Rc::new(RefCell::new(allocator)),
+            allocator: Arc::new(Mutex::new(allocator)),
         }
     }
 }
@@ -34,15 +37,19 @@ impl<Alloc: DeviceAllocator> DeviceAllocator for SharedRefAllocator<Alloc> {
     unsafe fn allocate(
         &mut self,
         allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
-        self.allocator.borrow_mut().allocate(allocate_info)
+        self.allocator
+            .lock()
+            .unwrap()
+            .allocate(allocate_info, alignment)
     }
 
     unsafe fn free(
         &mut self,
         allocation: &super::Allocation,
     ) -> anyhow::Result<()> {
-        self.allocator.borrow_mut().free(allocation)
+        self.allocator.lock().unwrap().free(allocation)
     }
 }
 
@@ -36,11 +36,12 @@ impl<SmallAllocator: DeviceAllocator, LargeAllocator: DeviceAllocator>
     unsafe fn allocate(
         &mut self,
         allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
         if allocate_info.allocation_size < self.size {
-            self.small_allocator.allocate(allocate_info)
+            self.small_allocator.allocate(allocate_info, alignment)
         } else {
-            self.large_allocator.allocate(allocate_info)
+            self.large_allocator.allocate(allocate_info, alignment)
         }
     }
 
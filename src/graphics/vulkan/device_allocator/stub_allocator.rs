@@ -13,6 +13,7 @@ impl DeviceAllocator for StubAllocator {
     unsafe fn allocate(
         &mut self,
         _allocate_info: vk::MemoryAllocateInfo,
+        _alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
         todo!()
     }
@@ -20,6 +20,7 @@ impl DeviceAllocator for Suballocator {
     unsafe fn allocate(
         &mut self,
         memory_allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
         use anyhow::Context;
 
@@ -29,7 +30,7 @@ impl DeviceAllocator for Suballocator {
             anyhow::bail!("Attempted to allocate incompatible memory!");
         }
         let region = self
-            .allocate_region(memory_allocate_info.allocation_size)
+            .allocate_region(memory_allocate_info.allocation_size, alignment)
             .with_context(|| {
                 "not enough memory for an allocation of the requested size"
             })?;
@@ -39,6 +40,8 @@ impl DeviceAllocator for Suballocator {
             memory: self.block.memory,
             offset: region.offset + self.block.offset,
             byte_size: region.size,
+            imported: false,
+            dedicated: false,
         })
     }
 
@@ -95,7 +95,7 @@ impl Region {
 
     /// Merge this region with another region.
     ///
-    /// # Unsafe Because
+    /// # Safety
     ///
     /// - this method does not check that the regions are adjacent before
     ///   merging
@@ -9,10 +9,7 @@ use super::Suballocator;
 impl Suballocator {
     pub fn new(allocation: Allocation) -> Self {
         Self {
-            free_regions: vec![Region::new(
-                allocation.offset,
-                allocation.byte_size,
-            )],
+            free_regions: vec![Region::new(0, allocation.byte_size)],
             block: allocation,
         }
     }
@@ -28,15 +25,38 @@ impl Suballocator {
 
     /// Find and take a region with the requested size from the free regions.
     ///
+    /// The region's offset within the block's memory is a multiple of
+    /// `alignment`. Space skipped to align the region stays free.
+    ///
     /// If no region is large enough, or no regions are remaining, then None is
     /// returned.
-    pub fn allocate_region(&mut self, size: u64) -> Option<Region> {
+    pub fn allocate_region(
+        &mut self,
+        size: u64,
+        alignment: u64,
+    ) -> Option<Region> {
+        let alignment = alignment.max(1);
         for i in 0..self.free_regions.len() {
-            if size == self.free_regions[i].size {
-                return Some(self.free_regions.remove(i));
-            } else if size < self.free_regions[i].size {
-                return Some(self.free_regions[i].take_subregion(size));
+            let free = self.free_regions[i];
+            let memory_offset = self.block.offset + free.offset;
+            let padding = match memory_offset % alignment {
+                0 => 0,
+                remainder => alignment - remainder,
+            };
+            if padding + size > free.size {
+                continue;
             }
+
+            let region = Region::new(free.offset + padding, size);
+            let before = Region::new(free.offset, padding);
+            let after = Region::new(region.end(), free.end() - region.end());
+            let remaining = [before, after]
+                .iter()
+                .copied()
+                .filter(|region| region.size > 0)
+                .collect::<Vec<Region>>();
+            self.free_regions.splice(i..=i, remaining);
+            return Some(region);
         }
         None
     }
@@ -104,6 +124,8 @@ mod test {
     use super::*;
     use crate::graphics::vulkan::device_allocator::Allocation;
 
+    use ash::vk;
+
     #[test]
     pub fn test_allocate_region() {
         let allocation = fake_allocation(1024);
@@ -111,11 +133,11 @@ mod test {
 
         assert_eq!(suballocator.free_regions, vec![Region::new(0, 1024)]);
 
-        let region = suballocator.allocate_region(256);
+        let region = suballocator.allocate_region(256, 1);
         assert_eq!(region, Some(Region::new(0, 256)));
         assert_eq!(suballocator.free_regions, vec![Region::new(256, 768)]);
 
-        let remaining = suballocator.allocate_region(768);
+        let remaining = suballocator.allocate_region(768, 1);
         assert_eq!(remaining, Some(Region::new(256, 768)));
         assert_eq!(suballocator.free_regions, vec![]);
     }
@@ -124,7 +146,7 @@ mod test {
     pub fn test_free_whole_region() -> Result<()> {
         let mut sub = Suballocator::new(fake_allocation(1024));
 
-        let region = sub.allocate_region(1024).unwrap();
+        let region = sub.allocate_region(1024, 1).unwrap();
         assert_eq!(region, Region::new(0, 1024));
         assert_eq!(sub.free_regions, vec![]);
 
@@ -138,7 +160,7 @@ mod test {
     pub fn test_split_region() -> Result<()> {
         let mut sub = Suballocator::new(fake_allocation(1024));
 
-        let region = sub.allocate_region(512).unwrap();
+        let region = sub.allocate_region(512, 1).unwrap();
         assert_eq!(region, Region::new(0, 512));
         assert_eq!(sub.free_regions, vec![Region::new(512, 512)]);
 
@@ -152,9 +174,9 @@ mod test {
     pub fn test_merge_front_and_back() -> Result<()> {
         let mut sub = Suballocator::new(fake_allocation(1024));
 
-        let a = sub.allocate_region(256).unwrap();
-        let b = sub.allocate_region(512).unwrap();
-        let c = sub.allocate_region(256).unwrap();
+        let a = sub.allocate_region(256, 1).unwrap();
+        let b = sub.allocate_region(512, 1).unwrap();
+        let c = sub.allocate_region(256, 1).unwrap();
 
         assert_eq!(sub.free_regions, vec![]);
 
@@ -177,10 +199,10 @@ mod test {
     pub fn test_merge_front_with_leading() -> Result<()> {
         let mut sub = Suballocator::new(fake_allocation(1024));
 
-        let a = sub.allocate_region(256).unwrap();
-        let b = sub.allocate_region(256).unwrap();
-        let c = sub.allocate_region(256).unwrap();
-        let d = sub.allocate_region(256).unwrap();
+        let a = sub.allocate_region(256, 1).unwrap();
+        let b = sub.allocate_region(256, 1).unwrap();
+        let c = sub.allocate_region(256, 1).unwrap();
+        let d = sub.allocate_region(256, 1).unwrap();
 
         assert_eq!(sub.free_regions, vec![]);
 
@@ -209,10 +231,10 @@ mod test {
     pub fn test_merge_back_with_trailing() -> Result<()> {
         let mut sub = Suballocator::new(fake_allocation(1024));
 
-        let a = sub.allocate_region(256).unwrap();
-        let b = sub.allocate_region(256).unwrap();
-        let c = sub.allocate_region(256).unwrap();
-        let d = sub.allocate_region(256).unwrap();
+        let a = sub.allocate_region(256, 1).unwrap();
+        let b = sub.allocate_region(256, 1).unwrap();
+        let c = sub.allocate_region(256, 1).unwrap();
+        let d = sub.allocate_region(256, 1).unwrap();
 
         assert_eq!(sub.free_regions, vec![]);
 
@@ -241,13 +263,70 @@ mod test {
     #[test]
     pub fn test_double_free() {
         let mut sub = Suballocator::new(fake_allocation(1024));
-        let a = sub.allocate_region(512).unwrap();
-        let b = sub.allocate_region(512).unwrap();
+        let a = sub.allocate_region(512, 1).unwrap();
+        let b = sub.allocate_region(512, 1).unwrap();
         sub.free_region(b).unwrap();
         sub.free_region(a).unwrap();
         sub.free_region(a).unwrap();
     }
 
+    #[test]
+    pub fn test_aligned_regions_keep_the_padding_free() -> Result<()> {
+        let mut sub = Suballocator::new(fake_allocation(1024));
+
+        let a = sub.allocate_region(100, 1).unwrap();
+        let b = sub.allocate_region(64, 256).unwrap();
+        assert_eq!(b, Region::new(256, 64));
+        assert_eq!(
+            sub.free_regions,
+            vec![Region::new(100, 156), Region::new(320, 704)]
+        );
+
+        let c = sub.allocate_region(100, 4).unwrap();
+        assert_eq!(c, Region::new(100, 100));
+
+        sub.free_region(b)?;
+        sub.free_region(c)?;
+        sub.free_region(a)?;
+        assert!(sub.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_alignment_uses_the_memory_offset() -> Result<()> {
+        let mut block = fake_allocation(1024);
+        block.offset = 96;
+        let mut sub = Suballocator::new(block);
+
+        // SAFE: the allocation is never used as real memory
+        let allocation = unsafe {
+            sub.allocate(
+                vk::MemoryAllocateInfo {
+                    allocation_size: 32,
+                    ..Default::default()
+                },
+                128,
+            )
+        }?;
+        assert_eq!(allocation.offset, 128);
+        assert_eq!(allocation.byte_size, 32);
+
+        unsafe { sub.free(&allocation)? };
+        assert!(sub.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_padding_counts_against_the_region_size() {
+        let mut sub = Suballocator::new(fake_allocation(1024));
+
+        sub.allocate_region(1, 1).unwrap();
+        assert_eq!(sub.allocate_region(1000, 512), None);
+        assert_eq!(sub.allocate_region(512, 512), Some(Region::new(512, 512)));
+    }
+
     fn fake_allocation(size: u64) -> Allocation {
         let mut allocation = Allocation::null();
         allocation.byte_size = size;
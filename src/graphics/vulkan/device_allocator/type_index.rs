@@ -39,9 +39,10 @@ impl<Allocator: DeviceAllocator> DeviceAllocator
     unsafe fn allocate(
         &mut self,
         allocate_info: vk::MemoryAllocateInfo,
+        alignment: vk::DeviceSize,
     ) -> Result<Allocation> {
         self.allocators[allocate_info.memory_type_index as usize]
-            .allocate(allocate_info)
+            .allocate(allocate_info, alignment)
     }
 
     unsafe fn free(&mut self, allocation: &Allocation) -> Result<()> {
@@ -0,0 +1,89 @@
+use super::{
+    ExternalMemoryHandle, EXTERNAL_MEMORY_DMA_BUF, EXTERNAL_MEMORY_FD,
+    EXTERNAL_MEMORY_WIN32,
+};
+
+use crate::graphics::vulkan::ExtensionRequests;
+
+use ash::vk;
+
+impl ExternalMemoryHandle {
+    /// Optional requests for every extension used to import external memory.
+    ///
+    /// Use `Device::supports_external_memory` to check which handle types
+    /// were actually enabled.
+    pub fn extension_requests() -> ExtensionRequests {
+        ExtensionRequests::new()
+            .request(EXTERNAL_MEMORY_FD)
+            .request(EXTERNAL_MEMORY_DMA_BUF)
+            .request(EXTERNAL_MEMORY_WIN32)
+    }
+
+    /// The vulkan handle type used when creating and importing resources.
+    pub fn handle_type(&self) -> vk::ExternalMemoryHandleTypeFlags {
+        match self {
+            ExternalMemoryHandle::OpaqueFd(_) => {
+                vk::ExternalMemoryHandleTypeFlags::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD
+            }
+            ExternalMemoryHandle::DmaBuf(_) => {
+                vk::ExternalMemoryHandleTypeFlags::EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF
+            }
+            ExternalMemoryHandle::OpaqueWin32(_) => {
+                vk::ExternalMemoryHandleTypeFlags::EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32
+            }
+        }
+    }
+
+    /// Every device extension which must be enabled to import this handle.
+    pub fn required_extensions(&self) -> &'static [&'static str] {
+        match self {
+            ExternalMemoryHandle::OpaqueFd(_) => &[EXTERNAL_MEMORY_FD],
+            ExternalMemoryHandle::DmaBuf(_) => {
+                &[EXTERNAL_MEMORY_FD, EXTERNAL_MEMORY_DMA_BUF]
+            }
+            ExternalMemoryHandle::OpaqueWin32(_) => &[EXTERNAL_MEMORY_WIN32],
+        }
+    }
+
+    /// True when the handle can be imported with the enabled extensions.
+    pub fn is_supported_by(&self, enabled_extensions: &[String]) -> bool {
+        self.required_extensions()
+            .iter()
+            .all(|name| enabled_extensions.iter().any(|ext| ext == name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn dma_buf_should_require_the_fd_extension() {
+        let handle = ExternalMemoryHandle::DmaBuf(3);
+        assert!(!handle.is_supported_by(&names(&[EXTERNAL_MEMORY_DMA_BUF])));
+        assert!(handle.is_supported_by(&names(&[
+            EXTERNAL_MEMORY_FD,
+            EXTERNAL_MEMORY_DMA_BUF
+        ])));
+    }
+
+    #[test]
+    fn opaque_fd_should_not_require_dma_buf() {
+        let handle = ExternalMemoryHandle::OpaqueFd(3);
+        assert!(handle.is_supported_by(&names(&[EXTERNAL_MEMORY_FD])));
+        assert!(!handle.is_supported_by(&names(&[EXTERNAL_MEMORY_WIN32])));
+    }
+
+    #[test]
+    fn extension_requests_should_all_be_optional() {
+        let requests = ExternalMemoryHandle::extension_requests();
+        assert!(requests.required.is_empty());
+        let enabled =
+            requests.negotiate(&names(&[EXTERNAL_MEMORY_FD])).unwrap();
+        assert_eq!(enabled, names(&[EXTERNAL_MEMORY_FD]));
+    }
+}
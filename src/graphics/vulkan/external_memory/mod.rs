@@ -0,0 +1,43 @@
+//! Types for importing device memory which was allocated outside of this
+//! library.
+//!
+//! Video decoders, compositors, and other vulkan applications can export
+//! memory as a file descriptor or Win32 handle. Importing that memory lets
+//! Draw2D sample the same frames without a copy, see
+//! `TextureImage::import` and `StaticBuffer::import`.
+//!
+//! Each handle type needs a device extension. Merge
+//! `ExternalMemoryHandle::extension_requests()` into the device extension
+//! requests to enable whichever ones the device supports.
+
+mod external_memory_handle;
+
+use ash::vk;
+
+/// The name of the extension for importing POSIX file descriptors.
+pub const EXTERNAL_MEMORY_FD: &str = "VK_KHR_external_memory_fd";
+
+/// The name of the extension for importing Linux DMA-BUF file descriptors.
+pub const EXTERNAL_MEMORY_DMA_BUF: &str = "VK_EXT_external_memory_dma_buf";
+
+/// The name of the extension for importing Win32 handles.
+pub const EXTERNAL_MEMORY_WIN32: &str = "VK_KHR_external_memory_win32";
+
+/// A handle to memory which was allocated outside of this library.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExternalMemoryHandle {
+    /// A file descriptor exported by another vulkan device with
+    /// `vkGetMemoryFdKHR`. The device takes ownership of the descriptor when
+    /// the import succeeds.
+    OpaqueFd(i32),
+
+    /// A Linux DMA-BUF file descriptor, like the buffers produced by VA-API
+    /// and V4L2 video decoders. The device takes ownership of the descriptor
+    /// when the import succeeds.
+    DmaBuf(i32),
+
+    /// A handle exported by another vulkan device with
+    /// `vkGetMemoryWin32HandleKHR`. The caller still owns the handle and must
+    /// close it.
+    OpaqueWin32(vk::HANDLE),
+}
@@ -0,0 +1,13 @@
+use super::EnabledFeatures;
+
+impl EnabledFeatures {
+    /// True when the named instance extension was enabled.
+    pub fn has_instance_extension(&self, name: &str) -> bool {
+        self.instance_extensions.iter().any(|ext| ext == name)
+    }
+
+    /// True when the named device extension was enabled.
+    pub fn has_device_extension(&self, name: &str) -> bool {
+        self.device_extensions.iter().any(|ext| ext == name)
+    }
+}
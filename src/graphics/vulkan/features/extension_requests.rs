@@ -0,0 +1,101 @@
+use super::ExtensionRequests;
+
+use anyhow::{bail, Result};
+
+impl ExtensionRequests {
+    /// Create an empty set of requests.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an extension which must be supported.
+    pub fn require(mut self, name: impl Into<String>) -> Self {
+        push_unique(&mut self.required, name.into());
+        self
+    }
+
+    /// Add an extension which is only enabled when it's supported.
+    pub fn request(mut self, name: impl Into<String>) -> Self {
+        push_unique(&mut self.optional, name.into());
+        self
+    }
+
+    /// Combine two sets of requests. An extension required by either set is
+    /// required by the result.
+    pub fn merge(mut self, other: &ExtensionRequests) -> Self {
+        for name in &other.required {
+            push_unique(&mut self.required, name.clone());
+        }
+        for name in &other.optional {
+            push_unique(&mut self.optional, name.clone());
+        }
+        self
+    }
+
+    /// Pick the extensions to enable from the available extensions.
+    ///
+    /// Returns an `Err()` listing every required extension which isn't
+    /// available.
+    pub fn negotiate(&self, available: &[String]) -> Result<Vec<String>> {
+        let missing: Vec<&String> = self
+            .required
+            .iter()
+            .filter(|name| !available.contains(name))
+            .collect();
+        if !missing.is_empty() {
+            bail!("Some required extensions were not found!\n{:?}", missing);
+        }
+
+        let mut enabled = self.required.clone();
+        for name in &self.optional {
+            if available.contains(name) {
+                push_unique(&mut enabled, name.clone());
+            }
+        }
+        Ok(enabled)
+    }
+}
+
+/// Add the name to the list if it isn't already there.
+fn push_unique(names: &mut Vec<String>, name: String) {
+    if !names.contains(&name) {
+        names.push(name);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn negotiate_should_enable_available_optional_extensions() {
+        let requests = ExtensionRequests::new()
+            .require("a")
+            .request("b")
+            .request("c");
+        let enabled = requests.negotiate(&names(&["a", "c"])).unwrap();
+        assert_eq!(enabled, names(&["a", "c"]));
+    }
+
+    #[test]
+    fn negotiate_should_fail_when_required_extensions_are_missing() {
+        let requests = ExtensionRequests::new().require("a").require("b");
+        assert!(requests.negotiate(&names(&["a"])).is_err());
+    }
+
+    #[test]
+    fn merge_should_not_duplicate_extensions() {
+        let requests = ExtensionRequests::new()
+            .require("a")
+            .request("b")
+            .merge(&ExtensionRequests::new().require("a").request("c"));
+        assert_eq!(requests.required, names(&["a"]));
+        assert_eq!(requests.optional, names(&["b", "c"]));
+        let enabled = requests.negotiate(&names(&["a", "b", "c"])).unwrap();
+        assert_eq!(enabled, names(&["a", "b", "c"]));
+    }
+}
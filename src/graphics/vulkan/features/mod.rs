@@ -0,0 +1,30 @@
+//! Types for negotiating which instance and device extensions are enabled.
+//!
+//! Applications declare the extensions they need with `ExtensionRequests`.
+//! Required extensions must be supported or creation fails, optional
+//! extensions are only enabled when they're supported. `EnabledFeatures`
+//! reports what was actually enabled so optional code paths can be picked at
+//! runtime.
+
+mod enabled_features;
+mod extension_requests;
+
+use ash::vk;
+
+/// A set of extensions to enable when creating an instance or device.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtensionRequests {
+    /// Extensions which must be supported.
+    pub required: Vec<String>,
+
+    /// Extensions which are enabled only when they're supported.
+    pub optional: Vec<String>,
+}
+
+/// The extensions and device features which were enabled.
+#[derive(Debug, Clone)]
+pub struct EnabledFeatures {
+    pub instance_extensions: Vec<String>,
+    pub device_extensions: Vec<String>,
+    pub device_features: vk::PhysicalDeviceFeatures,
+}
@@ -1,3 +1,5 @@
+use super::{DebugMessage, DebugRouting};
+
 use anyhow::Result;
 use ash::{
     extensions::ext::DebugUtils,
@@ -8,12 +10,20 @@ use ash::{
     },
     Entry,
 };
-use std::{borrow::Cow, ffi::CStr};
+use std::{
+    borrow::Cow,
+    ffi::CStr,
+    panic::{self, AssertUnwindSafe},
+    sync::RwLock,
+};
 
 /// Create the vulkan debug callback for validation.
+///
+/// Every message is sent to the routing, which must outlive the messenger.
 pub fn create_debug_logger(
     entry: &Entry,
     instance: &ash::Instance,
+    routing: &RwLock<DebugRouting>,
 ) -> Result<(DebugUtils, DebugUtilsMessengerEXT)> {
     let debug_utils = DebugUtils::new(entry, instance);
 
@@ -26,6 +36,7 @@ pub fn create_debug_logger(
             | vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION
             | vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE,
         pfn_user_callback: Some(debug_callback),
+        p_user_data: routing as *const RwLock<DebugRouting> as *mut _,
         ..Default::default()
     };
 
@@ -40,55 +51,40 @@ unsafe extern "system" fn debug_callback(
     message_severity: DebugUtilsMessageSeverityFlagsEXT,
     message_type: DebugUtilsMessageTypeFlagsEXT,
     p_callback_data: *const DebugUtilsMessengerCallbackDataEXT,
-    _user_data: *mut std::ffi::c_void,
+    user_data: *mut std::ffi::c_void,
 ) -> vk::Bool32 {
     let callback_data = *p_callback_data;
 
-    let message = if callback_data.p_message.is_null() {
+    let text = if callback_data.p_message.is_null() {
         Cow::from("")
     } else {
         CStr::from_ptr(callback_data.p_message).to_string_lossy()
     };
 
-    let message_id_name = if callback_data.p_message_id_name.is_null() {
+    let id_name = if callback_data.p_message_id_name.is_null() {
         Cow::from("")
     } else {
         CStr::from_ptr(callback_data.p_message_id_name).to_string_lossy()
     };
 
-    let message_number = callback_data.message_id_number;
-
-    let raw_message = std::format!(
-        "Vulkan Debug Callback - {:?} :: {:?} [{} ({})]\n{}",
-        message_severity,
+    let message = DebugMessage {
+        severity: message_severity,
         message_type,
-        message_id_name,
-        message_number,
-        message
-    );
-
-    let full_message = raw_message.replace("; ", ";\n\n");
-
-    match message_severity {
-        DebugUtilsMessageSeverityFlagsEXT::VERBOSE => {
-            log::debug!("{}", full_message);
-        }
-
-        DebugUtilsMessageSeverityFlagsEXT::INFO => {
-            log::info!("{}", full_message);
-        }
-
-        DebugUtilsMessageSeverityFlagsEXT::WARNING => {
-            log::warn!("{}", full_message);
-        }
+        id_name: id_name.into_owned(),
+        id_number: callback_data.message_id_number,
+        text: text.into_owned(),
+    };
 
-        DebugUtilsMessageSeverityFlagsEXT::ERROR => {
-            log::error!("{}", full_message);
-        }
+    let routing = &*(user_data as *const RwLock<DebugRouting>);
 
-        _ => {
-            log::warn!("?? {}", full_message);
-        }
+    // Panics can't unwind into the vulkan loader. The panic message is
+    // still printed by the panic hook, then the process aborts, which is
+    // enough to fail a test run.
+    let routed = panic::catch_unwind(AssertUnwindSafe(|| {
+        routing.read().unwrap().route(&message)
+    }));
+    if routed.is_err() {
+        std::process::abort();
     }
-    return vk::FALSE;
+    vk::FALSE
 }
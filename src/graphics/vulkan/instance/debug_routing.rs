@@ -0,0 +1,206 @@
+use super::{DebugHandler, DebugMessage, DebugRouting};
+
+use ash::vk::{
+    DebugUtilsMessageSeverityFlagsEXT as Severity,
+    DebugUtilsMessageTypeFlagsEXT as MessageType,
+};
+use std::fmt;
+
+/// The environment variable which turns on `panic_on_error` by default.
+pub const PANIC_ON_ERROR_VAR: &str = "DRAW2D_PANIC_ON_VALIDATION_ERROR";
+
+impl DebugRouting {
+    /// Keep every message, log it, and panic on errors only when
+    /// `DRAW2D_PANIC_ON_VALIDATION_ERROR` is set to something other than 0.
+    pub fn new() -> Self {
+        let panic_on_error = std::env::var(PANIC_ON_ERROR_VAR)
+            .map(|value| !value.is_empty() && value != "0")
+            .unwrap_or(false);
+        Self {
+            severity: Severity::all(),
+            message_types: MessageType::all(),
+            log: true,
+            handler: None,
+            panic_on_error,
+        }
+    }
+
+    /// Only keep messages with one of these severities.
+    pub fn with_severity(self, severity: Severity) -> Self {
+        Self { severity, ..self }
+    }
+
+    /// Only keep messages with one of these types.
+    pub fn with_message_types(self, message_types: MessageType) -> Self {
+        Self {
+            message_types,
+            ..self
+        }
+    }
+
+    /// Send every kept message to a closure. Messages are still logged
+    /// unless `without_logging` is used too.
+    ///
+    /// The closure can be called from any thread which makes vulkan calls.
+    pub fn with_handler<F>(self, handler: F) -> Self
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+        let handler: DebugHandler = Box::new(handler);
+        Self {
+            handler: Some(handler),
+            ..self
+        }
+    }
+
+    /// Don't send kept messages to the logger.
+    pub fn without_logging(self) -> Self {
+        Self { log: false, ..self }
+    }
+
+    /// Panic when an error is kept, after it has been logged and handled.
+    pub fn panic_on_error(self, panic_on_error: bool) -> Self {
+        Self {
+            panic_on_error,
+            ..self
+        }
+    }
+
+    /// True when the message passes the severity and type filters.
+    pub fn keeps(&self, message: &DebugMessage) -> bool {
+        self.severity.intersects(message.severity)
+            && self.message_types.intersects(message.message_type)
+    }
+
+    /// Log, handle, and possibly panic on a message.
+    pub fn route(&self, message: &DebugMessage) {
+        if !self.keeps(message) {
+            return;
+        }
+        if self.log {
+            message.log();
+        }
+        if let Some(handler) = &self.handler {
+            handler(message);
+        }
+        if self.panic_on_error && message.severity == Severity::ERROR {
+            panic!("vulkan validation error: {}", message);
+        }
+    }
+}
+
+impl Default for DebugRouting {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for DebugRouting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugRouting")
+            .field("severity", &self.severity)
+            .field("message_types", &self.message_types)
+            .field("log", &self.log)
+            .field("handler", &self.handler.is_some())
+            .field("panic_on_error", &self.panic_on_error)
+            .finish()
+    }
+}
+
+impl DebugMessage {
+    /// Log the message at the level matching its severity.
+    pub fn log(&self) {
+        let full_message = self.to_string().replace("; ", ";\n\n");
+        match self.severity {
+            Severity::VERBOSE => log::debug!("{}", full_message),
+            Severity::INFO => log::info!("{}", full_message),
+            Severity::WARNING => log::warn!("{}", full_message),
+            Severity::ERROR => log::error!("{}", full_message),
+            _ => log::warn!("?? {}", full_message),
+        }
+    }
+}
+
+impl fmt::Display for DebugMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Vulkan Debug Callback - {:?} :: {:?} [{} ({})]\n{}",
+            self.severity,
+            self.message_type,
+            self.id_name,
+            self.id_number,
+            self.text
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn message(severity: Severity) -> DebugMessage {
+        DebugMessage {
+            severity,
+            message_type: MessageType::VALIDATION,
+            id_name: "VUID-test".to_owned(),
+            id_number: 7,
+            text: "something went wrong".to_owned(),
+        }
+    }
+
+    fn counting_routing() -> (DebugRouting, Arc<AtomicUsize>) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let handled = count.clone();
+        let routing = DebugRouting::new()
+            .without_logging()
+            .panic_on_error(false)
+            .with_handler(move |_| {
+                handled.fetch_add(1, Ordering::SeqCst);
+            });
+        (routing, count)
+    }
+
+    #[test]
+    fn handlers_should_only_see_kept_messages() {
+        let (routing, count) = counting_routing();
+        let routing =
+            routing.with_severity(Severity::WARNING | Severity::ERROR);
+
+        routing.route(&message(Severity::VERBOSE));
+        routing.route(&message(Severity::WARNING));
+        routing.route(&message(Severity::ERROR));
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn message_types_should_be_filtered() {
+        let (routing, count) = counting_routing();
+        let routing = routing.with_message_types(MessageType::PERFORMANCE);
+
+        routing.route(&message(Severity::ERROR));
+
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "VUID-test")]
+    fn errors_should_panic_when_asked() {
+        let routing =
+            DebugRouting::new().without_logging().panic_on_error(true);
+        routing.route(&message(Severity::ERROR));
+    }
+
+    #[test]
+    fn warnings_should_not_panic() {
+        let routing =
+            DebugRouting::new().without_logging().panic_on_error(true);
+        routing.route(&message(Severity::WARNING));
+    }
+}
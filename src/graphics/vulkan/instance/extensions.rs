@@ -1,47 +1,23 @@
-//! Functions to check that a set of extensions are supported by the vulkan
+//! Functions to list the extensions which are supported by the vulkan
 //! instance.
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use ash::{version::EntryV1_0, Entry};
+use std::ffi::CStr;
 
-/// Bail if any of the required extensions is not supported by the instance.
-pub fn check_extensions(
-    entry: &Entry,
-    required_extensions: &Vec<String>,
-) -> Result<()> {
-    let missing = missing_extensions(entry, required_extensions)?;
-    if !missing.is_empty() {
-        bail!("Some required extensions were not found!\n{:?}", missing);
-    }
-    Ok(())
-}
-
-/// Get a list of all extensions which are required but not available for this
-/// vulkan instance.
-fn missing_extensions(
-    entry: &Entry,
-    required_extensions: &Vec<String>,
-) -> Result<Vec<String>> {
-    let available_extensions =
-        entry.enumerate_instance_extension_properties()?;
-
-    let available_names: Vec<String> = available_extensions
+/// Get the name of every extension supported by the vulkan instance.
+pub fn available_extension_names(entry: &Entry) -> Result<Vec<String>> {
+    let available_names: Vec<String> = entry
+        .enumerate_instance_extension_properties()?
         .iter()
-        .map(|ext| {
-            String::from_utf8(
-                ext.extension_name.iter().map(|c| *c as u8).collect(),
-            )
+        .filter_map(|ext| {
+            let name = unsafe { CStr::from_ptr(ext.extension_name.as_ptr()) };
+            // only accept valid utf-8 extension names
+            name.to_str().ok().map(|name| name.to_owned())
         })
-        // only accept valid utf-8 extension names
-        .filter(|item| item.is_ok())
-        .map(|item| item.unwrap())
         .collect();
 
     log::info!("Available extensions {}", available_names.join("\n"));
 
-    Ok(required_extensions
-        .iter()
-        .cloned()
-        .filter(|name| available_names.contains(name))
-        .collect())
+    Ok(available_names)
 }
@@ -6,18 +6,36 @@
 //! to each constantly floating around.
 
 mod debug_callback;
+mod debug_routing;
 mod extensions;
 mod layers;
 
-use super::ffi::to_os_ptrs;
+pub use self::debug_routing::PANIC_ON_ERROR_VAR;
+
+use super::{ffi::to_os_ptrs, ExtensionRequests};
 
 use anyhow::Result;
 use ash::{
-    extensions::{ext::DebugUtils, khr::Surface},
+    extensions::{
+        ext::DebugUtils,
+        khr::{AndroidSurface, Surface},
+    },
     version::{EntryV1_0, InstanceV1_0},
     vk, Entry,
 };
-use std::{ffi::CString, sync::Arc};
+use std::{
+    ffi::{CStr, CString},
+    sync::{Arc, RwLock},
+};
+
+/// Lets the loader list portability drivers like MoltenVK. Newer Vulkan SDKs
+/// hide them unless this extension and flag are used.
+const PORTABILITY_ENUMERATION: &str = "VK_KHR_portability_enumeration";
+
+/// VK_INSTANCE_CREATE_ENUMERATE_PORTABILITY_BIT_KHR, which is newer than the
+/// ash bindings.
+const ENUMERATE_PORTABILITY: vk::InstanceCreateFlags =
+    vk::InstanceCreateFlags::from_raw(0x1);
 
 /// Hold all of the instance-related objects and drop them in the correct order.
 pub struct Instance {
@@ -28,10 +46,54 @@ pub struct Instance {
     pub debug: DebugUtils,
 
     layers: Vec<String>,
+    enabled_extensions: Vec<String>,
     debug_messenger: vk::DebugUtilsMessengerEXT,
+
+    /// Decides where validation messages go. Boxed so the debug callback
+    /// can hold a stable pointer to it.
+    debug_routing: Box<RwLock<DebugRouting>>,
+
     entry: Entry,
 }
 
+/// A message from the validation layers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugMessage {
+    pub severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+    pub message_type: vk::DebugUtilsMessageTypeFlagsEXT,
+
+    /// The name of the validation rule, like a VUID.
+    pub id_name: String,
+    pub id_number: i32,
+    pub text: String,
+}
+
+/// A callback which receives validation messages.
+pub type DebugHandler = Box<dyn Fn(&DebugMessage) + Send + Sync>;
+
+/// Decides which validation messages are kept and where they go.
+///
+/// By default every message is logged at the level matching its severity.
+/// Setting `DRAW2D_PANIC_ON_VALIDATION_ERROR=1` in the environment makes
+/// errors panic, so CI runs fail on validation errors without any code
+/// changes.
+pub struct DebugRouting {
+    /// Messages with any of these severities are kept.
+    severity: vk::DebugUtilsMessageSeverityFlagsEXT,
+
+    /// Messages with any of these types are kept.
+    message_types: vk::DebugUtilsMessageTypeFlagsEXT,
+
+    /// True when kept messages are sent to the logger.
+    log: bool,
+
+    /// Receives every kept message.
+    handler: Option<DebugHandler>,
+
+    /// Panic when an error is kept.
+    panic_on_error: bool,
+}
+
 impl Instance {
     fn debug_layers() -> Vec<String> {
         vec![
@@ -40,24 +102,74 @@ impl Instance {
         ]
     }
 
+    /// Extensions which this library always asks for.
+    ///
+    /// Debug utils is required for naming vulkan objects. Surface
+    /// capabilities 2 is needed by device extensions like
+    /// VK_EXT_full_screen_exclusive. Portability enumeration is needed to
+    /// find MoltenVK devices on macOS.
+    fn library_extensions() -> ExtensionRequests {
+        ExtensionRequests::new()
+            .require(DebugUtils::name().to_str().unwrap())
+            .request(vk::KhrGetSurfaceCapabilities2Fn::name().to_str().unwrap())
+            .request(PORTABILITY_ENUMERATION)
+    }
+
     /// Create a new ash instance with the required extensions.
     ///
     /// Debug and validation layers are automatically setup along with the
     /// debug callback.
     pub fn new(required_extensions: &Vec<String>) -> Result<Arc<Self>> {
-        let (instance, entry) = Self::create_instance(required_extensions)?;
-        let (debug, debug_messenger) =
-            debug_callback::create_debug_logger(&entry, &instance)?;
+        Self::with_extensions(&ExtensionRequests {
+            required: required_extensions.clone(),
+            optional: vec![],
+        })
+    }
+
+    /// Create a new ash instance, negotiating the requested extensions.
+    ///
+    /// Returns an `Err()` if any required extensions are unavailable.
+    /// Optional extensions which were enabled can be checked with
+    /// `is_extension_enabled`.
+    pub fn with_extensions(requests: &ExtensionRequests) -> Result<Arc<Self>> {
+        let (instance, entry, enabled_extensions) =
+            Self::create_instance(requests)?;
+        let debug_routing = Box::new(RwLock::new(DebugRouting::default()));
+        let (debug, debug_messenger) = debug_callback::create_debug_logger(
+            &entry,
+            &instance,
+            &debug_routing,
+        )?;
 
         Ok(Arc::new(Self {
             ash: instance,
             entry,
             debug,
             debug_messenger,
+            debug_routing,
             layers: Self::debug_layers(),
+            enabled_extensions,
         }))
     }
 
+    /// Replace the routing for validation messages. Takes effect for the
+    /// next message.
+    pub fn set_debug_routing(&self, routing: DebugRouting) {
+        *self.debug_routing.write().unwrap() = routing;
+    }
+
+    /// Returns true when the named extension was enabled for this instance.
+    pub fn is_extension_enabled(&self, name: &CStr) -> bool {
+        name.to_str()
+            .map(|name| self.enabled_extensions.iter().any(|ext| ext == name))
+            .unwrap_or(false)
+    }
+
+    /// Every extension which was enabled for this instance.
+    pub fn enabled_extensions(&self) -> &[String] {
+        &self.enabled_extensions
+    }
+
     /// A non-owning borrow of the ash library instance.
     pub fn raw(&self) -> &ash::Instance {
         &self.ash
@@ -71,12 +183,27 @@ impl Instance {
         Surface::new(&self.entry, &self.ash)
     }
 
+    /// Create a loader for VK_KHR_android_surface, which creates surfaces
+    /// from Android native windows.
+    ///
+    /// The instance must be created with the extension enabled, see
+    /// `Instance::for_android`.
+    pub fn create_android_surface_loader(&self) -> AndroidSurface {
+        AndroidSurface::new(&self.entry, &self.ash)
+    }
+
     /// Create a new logical device for use by this application. The caller is
     /// responsible for destroying the device when done.
+    ///
+    /// Descriptor indexing features are chained into the device create info
+    /// when provided, the matching extension must be in the extension list.
     pub fn create_logical_device(
         &self,
         physical_device: &vk::PhysicalDevice,
         physical_device_features: vk::PhysicalDeviceFeatures,
+        descriptor_indexing_features: Option<
+            &vk::PhysicalDeviceDescriptorIndexingFeatures,
+        >,
         physical_device_extensions: &[String],
         queue_create_infos: &[vk::DeviceQueueCreateInfo],
     ) -> Result<ash::Device> {
@@ -94,6 +221,10 @@ impl Instance {
             enabled_layer_count: layer_name_ptrs.len() as u32,
             pp_enabled_extension_names: ext_name_ptrs.as_ptr(),
             enabled_extension_count: physical_device_extensions.len() as u32,
+            p_next: descriptor_indexing_features
+                .map_or(std::ptr::null(), |features| {
+                    features as *const _ as *const std::ffi::c_void
+                }),
             ..Default::default()
         };
 
@@ -106,20 +237,20 @@ impl Instance {
         Ok(logical_device)
     }
 
-    /// Create a Vulkan instance with the required extensions.
+    /// Create a Vulkan instance with the required extensions and any optional
+    /// extensions which are available.
     /// Returns an `Err()` if any required extensions are unavailable.
     fn create_instance(
-        required_extensions: &Vec<String>,
-    ) -> Result<(ash::Instance, Entry)> {
+        requests: &ExtensionRequests,
+    ) -> Result<(ash::Instance, Entry, Vec<String>)> {
         let entry = Entry::new()?;
 
-        let mut required_with_debug = required_extensions.clone();
-        required_with_debug.push(DebugUtils::name().to_str()?.to_owned());
-
-        extensions::check_extensions(&entry, &required_with_debug)?;
+        let enabled_extensions = Self::library_extensions()
+            .merge(requests)
+            .negotiate(&extensions::available_extension_names(&entry)?)?;
         layers::check_layers(&entry, &Self::debug_layers())?;
 
-        log::debug!("Required Extensions {:?}", required_extensions);
+        log::debug!("Enabled Extensions {:?}", enabled_extensions);
 
         let app_name = CString::new("ash starter").unwrap();
         let engine_name = CString::new("no engine").unwrap();
@@ -135,10 +266,19 @@ impl Instance {
 
         let (_layer_names, layer_ptrs) =
             unsafe { to_os_ptrs(&Self::debug_layers()) };
-        let (_ext_names, ext_ptrs) =
-            unsafe { to_os_ptrs(&required_with_debug) };
+        let (_ext_names, ext_ptrs) = unsafe { to_os_ptrs(&enabled_extensions) };
+
+        let flags = if enabled_extensions
+            .iter()
+            .any(|ext| ext == PORTABILITY_ENUMERATION)
+        {
+            ENUMERATE_PORTABILITY
+        } else {
+            vk::InstanceCreateFlags::empty()
+        };
 
         let create_info = vk::InstanceCreateInfo {
+            flags,
             p_application_info: &app_info,
             pp_enabled_layer_names: layer_ptrs.as_ptr(),
             enabled_layer_count: layer_ptrs.len() as u32,
@@ -149,7 +289,7 @@ impl Instance {
 
         let instance = unsafe { entry.create_instance(&create_info, None)? };
 
-        Ok((instance, entry))
+        Ok((instance, entry, enabled_extensions))
     }
 }
 
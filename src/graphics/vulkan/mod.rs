@@ -4,15 +4,25 @@
 pub mod buffer;
 pub mod command_pool;
 pub mod device;
+pub mod device_allocator;
+pub mod external_memory;
+pub mod features;
 pub mod ffi;
 pub mod instance;
 pub mod shader_module;
 pub mod swapchain;
 pub mod texture;
 pub mod window_surface;
-pub mod device_allocator;
 
 pub use self::{
-    device::Device, instance::Instance, swapchain::Swapchain,
+    device::{
+        Device, DevicePolicy, PendingWork, QueueTransfer, TEXTURE_LIMIT_CAP,
+    },
+    external_memory::ExternalMemoryHandle,
+    features::{EnabledFeatures, ExtensionRequests},
+    instance::{DebugMessage, DebugRouting, Instance},
+    swapchain::{
+        RenderTargetProvider, Swapchain, SwapchainInfo, SwapchainOptions,
+    },
     window_surface::WindowSurface,
 };
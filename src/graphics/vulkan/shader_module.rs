@@ -26,10 +26,22 @@ impl ShaderModule {
     where
         Name: Into<String>,
     {
-        let source_u32 = ffi::copy_to_u32(source);
+        Self::from_words(device, name, &ffi::copy_to_u32(source))
+    }
+
+    /// Create a new shader module from SPIR-V words which were loaded at
+    /// runtime, like a shader which is hot reloaded from disk.
+    pub fn from_words<Name>(
+        device: &Arc<Device>,
+        name: Name,
+        words: &[u32],
+    ) -> Result<Self>
+    where
+        Name: Into<String>,
+    {
         let create_info = vk::ShaderModuleCreateInfo {
-            p_code: source_u32.as_ptr(),
-            code_size: source_u32.len() * std::mem::size_of::<u32>(),
+            p_code: words.as_ptr(),
+            code_size: std::mem::size_of_val(words),
             ..Default::default()
         };
 
@@ -56,6 +68,10 @@ impl ShaderModule {
 impl Drop for ShaderModule {
     fn drop(&mut self) {
         unsafe {
+            self.device.forget_vulkan_object(
+                vk::ObjectType::SHADER_MODULE,
+                &self.shader_module,
+            );
             self.device
                 .logical_device
                 .destroy_shader_module(self.shader_module, None);
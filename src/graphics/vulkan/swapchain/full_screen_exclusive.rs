@@ -0,0 +1,75 @@
+use super::Swapchain;
+
+use ash::vk;
+use std::sync::atomic::Ordering;
+
+impl Swapchain {
+    /// Take exclusive control of the display for this swapchain.
+    ///
+    /// This only does anything for swapchains created with application
+    /// controlled exclusive mode, which requires VK_EXT_full_screen_exclusive
+    /// and a known Win32 monitor. Call it after the window becomes
+    /// fullscreen.
+    ///
+    /// Returns true when exclusive mode is held. Failures are not errors, the
+    /// swapchain keeps presenting normally.
+    pub fn acquire_full_screen_exclusive(&self) -> bool {
+        if self.is_full_screen_exclusive_acquired() {
+            return true;
+        }
+        let functions = match self.application_controlled_functions() {
+            Some(functions) => functions,
+            None => return false,
+        };
+        let result = (functions.acquire_full_screen_exclusive_mode_ext)(
+            self.device.logical_device.handle(),
+            self.swapchain,
+        );
+        if result != vk::Result::SUCCESS {
+            log::debug!("unable to acquire full screen exclusive {:?}", result);
+            return false;
+        }
+        self.exclusive_acquired.store(true, Ordering::SeqCst);
+        true
+    }
+
+    /// Give up exclusive control of the display.
+    ///
+    /// Call this before the window leaves fullscreen. Does nothing when
+    /// exclusive mode isn't held.
+    pub fn release_full_screen_exclusive(&self) {
+        if !self.exclusive_acquired.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        if let Some(functions) = self.application_controlled_functions() {
+            let result = (functions.release_full_screen_exclusive_mode_ext)(
+                self.device.logical_device.handle(),
+                self.swapchain,
+            );
+            if result != vk::Result::SUCCESS {
+                log::debug!(
+                    "unable to release full screen exclusive {:?}",
+                    result
+                );
+            }
+        }
+    }
+
+    /// True while this swapchain holds exclusive control of the display.
+    pub fn is_full_screen_exclusive_acquired(&self) -> bool {
+        self.exclusive_acquired.load(Ordering::SeqCst)
+    }
+
+    /// The extension entrypoints, only when the application is allowed to
+    /// acquire and release exclusive mode for this swapchain.
+    fn application_controlled_functions(
+        &self,
+    ) -> Option<&vk::ExtFullScreenExclusiveFn> {
+        if self.full_screen_exclusive
+            != Some(vk::FullScreenExclusiveEXT::APPLICATION_CONTROLLED)
+        {
+            return None;
+        }
+        self.device.full_screen_exclusive.as_ref()
+    }
+}
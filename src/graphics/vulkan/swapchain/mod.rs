@@ -3,16 +3,25 @@
 //! The Swapchain is inherently tied to the display surface and the Window
 //! which provides it. As such, only the main application thread should ever
 //! directly interact with the swapchain.
+//!
+//! Applications which present with another API, like OpenXR or a projection
+//! mapping service, can implement `RenderTargetProvider` instead. The
+//! Swapchain then wraps the provider's images and the rest of the render path
+//! is unchanged.
 
+mod full_screen_exclusive;
 mod images;
+mod options;
 mod render_pass;
+mod render_targets;
 mod selection;
+mod suspended;
 
 use crate::graphics::vulkan::{Device, WindowSurface};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use ash::{extensions::khr, version::DeviceV1_0, vk};
-use std::sync::Arc;
+use std::sync::{atomic::AtomicBool, Arc};
 
 /// Manage the swapchain and all dependent resources.
 pub struct Swapchain {
@@ -20,16 +29,136 @@ pub struct Swapchain {
     pub swapchain: vk::SwapchainKHR,
 
     pub framebuffers: Vec<vk::Framebuffer>,
+    pub images: Vec<vk::Image>,
     swapchain_image_views: Vec<vk::ImageView>,
 
     pub render_pass: vk::RenderPass,
     pub extent: vk::Extent2D,
     pub format: vk::Format,
     pub color_space: vk::ColorSpaceKHR,
+    pub image_usage: vk::ImageUsageFlags,
+    pub present_mode: vk::PresentModeKHR,
+
+    /// The transform applied by the presentation engine. Rendering must
+    /// rotate clip space to match, see `pipeline2d::pre_rotation`.
+    pub pre_transform: vk::SurfaceTransformFlagsKHR,
+
+    /// The layout each image is left in when a frame finishes rendering.
+    pub final_layout: vk::ImageLayout,
+
+    /// How this swapchain was created to use full screen exclusive
+    /// presentation, None when it wasn't.
+    pub full_screen_exclusive: Option<vk::FullScreenExclusiveEXT>,
+
+    /// True while application controlled exclusive mode is acquired.
+    exclusive_acquired: AtomicBool,
+
+    /// The options used to create this swapchain, reused when rebuilding.
+    options: SwapchainOptions,
 
     device: Arc<Device>,
 }
 
+/// Controls the trade off between latency and throughput when presenting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapchainOptions {
+    /// The number of swapchain images to request, clamped to the range
+    /// supported by the surface. None requests one more than the minimum.
+    pub image_count: Option<u32>,
+
+    /// Presentation modes in order of preference. FIFO is used when none of
+    /// the modes are supported because every device supports it.
+    pub present_modes: Vec<vk::PresentModeKHR>,
+
+    /// Request full screen exclusive presentation when the device supports
+    /// VK_EXT_full_screen_exclusive. Ignored everywhere else.
+    pub full_screen_exclusive: bool,
+}
+
+/// The image picked by a `RenderTargetProvider` for the next frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AcquiredTarget {
+    /// The index of the image in the provider's `images()`.
+    pub index: u32,
+
+    /// True when the provider will signal the `image_available` semaphore
+    /// passed to `acquire`. Rendering waits on the semaphore when it's set.
+    pub signals_image_available: bool,
+}
+
+/// Images owned by another API which frames are rendered into instead of a
+/// `SwapchainKHR`.
+///
+/// Images are always rendered on the device's graphics queue. Providers which
+/// need to hand images back to another queue or process are responsible for
+/// any ownership transfers.
+pub trait RenderTargetProvider {
+    /// Every image frames can be rendered into. Each image needs
+    /// `COLOR_ATTACHMENT` usage.
+    fn images(&self) -> Vec<vk::Image>;
+
+    /// The format shared by every image.
+    fn format(&self) -> vk::Format;
+
+    /// The size shared by every image.
+    fn extent(&self) -> vk::Extent2D;
+
+    /// How the images were created. Recording frames also needs
+    /// `TRANSFER_SRC`.
+    fn image_usage(&self) -> vk::ImageUsageFlags {
+        vk::ImageUsageFlags::COLOR_ATTACHMENT
+    }
+
+    /// The layout images are left in after a frame has been rendered.
+    fn final_layout(&self) -> vk::ImageLayout {
+        vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+    }
+
+    /// Pick the image to render the next frame into.
+    ///
+    /// Errors are logged and cause the images to be queried again, like an
+    /// out of date swapchain.
+    fn acquire(
+        &mut self,
+        image_available: vk::Semaphore,
+    ) -> Result<AcquiredTarget>;
+
+    /// True when rendering should signal a semaphore which is passed to
+    /// `release`. Unwaited semaphores can't be signaled again, so only return
+    /// true when the semaphore is always waited on.
+    fn signals_render_finished(&self) -> bool {
+        false
+    }
+
+    /// Hand back an image once the frame's commands have been submitted.
+    ///
+    /// `render_finished` is null unless `signals_render_finished` returned
+    /// true.
+    fn release(
+        &mut self,
+        index: u32,
+        render_finished: vk::Semaphore,
+    ) -> Result<()>;
+
+    /// True when the images have changed and the render targets need to be
+    /// rebuilt.
+    fn needs_rebuild(&self) -> bool {
+        false
+    }
+}
+
+/// The settings actually chosen for a swapchain.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SwapchainInfo {
+    pub image_count: u32,
+    pub present_mode: vk::PresentModeKHR,
+    pub format: vk::Format,
+    pub color_space: vk::ColorSpaceKHR,
+    pub extent: vk::Extent2D,
+    pub pre_transform: vk::SurfaceTransformFlagsKHR,
+    pub full_screen_exclusive: Option<vk::FullScreenExclusiveEXT>,
+}
+
 impl Swapchain {
     /// Create a new swapchain based on the surface, physical device, and the
     /// current size of the framebuffer.
@@ -37,6 +166,7 @@ impl Swapchain {
         device: Arc<Device>,
         window_surface: &dyn WindowSurface,
         previous: Option<&Swapchain>,
+        options: SwapchainOptions,
     ) -> Result<Arc<Self>> {
         let image_format = selection::choose_surface_format(
             window_surface,
@@ -45,15 +175,37 @@ impl Swapchain {
         let present_mode = selection::choose_present_mode(
             window_surface,
             &device.physical_device,
+            &options.present_modes,
         );
         let extent = selection::choose_swap_extent(
             window_surface,
             &device.physical_device,
         )?;
+        if extent.width == 0 || extent.height == 0 {
+            bail!(
+                "unable to create a swapchain for a surface with no area {:?}",
+                extent
+            );
+        }
         let image_count = selection::choose_image_count(
             window_surface,
             &device.physical_device,
+            options.image_count,
+        )?;
+        let image_usage = selection::choose_image_usage(
+            window_surface,
+            &device.physical_device,
         )?;
+        let pre_transform = selection::choose_pre_transform(
+            window_surface,
+            &device.physical_device,
+        )?;
+        let monitor = window_surface.win32_monitor();
+        let mut full_screen_exclusive = selection::choose_full_screen_exclusive(
+            options.full_screen_exclusive,
+            device.full_screen_exclusive.is_some(),
+            monitor.is_some(),
+        );
 
         let mut create_info = vk::SwapchainCreateInfoKHR {
             surface: unsafe { window_surface.get_surface_handle() },
@@ -64,12 +216,12 @@ impl Swapchain {
             image_extent: extent,
             min_image_count: image_count,
             image_array_layers: 1,
-            image_usage: vk::ImageUsageFlags::COLOR_ATTACHMENT,
+            image_usage,
 
             // window system presentation settings
             present_mode,
             composite_alpha: vk::CompositeAlphaFlagsKHR::OPAQUE,
-            pre_transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
+            pre_transform,
             old_swapchain: if let Some(old_swapchain) = previous {
                 old_swapchain.swapchain
             } else {
@@ -93,9 +245,47 @@ impl Swapchain {
             create_info.queue_family_index_count = indices.len() as u32;
         };
 
+        let mut exclusive_win32_info =
+            vk::SurfaceFullScreenExclusiveWin32InfoEXT {
+                hmonitor: monitor.unwrap_or(std::ptr::null_mut()),
+                ..Default::default()
+            };
+        let mut exclusive_info = vk::SurfaceFullScreenExclusiveInfoEXT {
+            full_screen_exclusive: full_screen_exclusive
+                .unwrap_or(vk::FullScreenExclusiveEXT::DEFAULT),
+            ..Default::default()
+        };
+        if monitor.is_some() {
+            exclusive_info.p_next =
+                &mut exclusive_win32_info as *mut _ as *mut _;
+        }
+
         let swapchain_loader = device.create_swapchain_loader();
-        let swapchain =
-            unsafe { swapchain_loader.create_swapchain(&create_info, None)? };
+        let swapchain = if full_screen_exclusive.is_some() {
+            let exclusive_create_info = vk::SwapchainCreateInfoKHR {
+                p_next: &exclusive_info as *const _ as *const _,
+                ..create_info
+            };
+            let result = unsafe {
+                swapchain_loader.create_swapchain(&exclusive_create_info, None)
+            };
+            match result {
+                Ok(swapchain) => swapchain,
+                Err(err) => {
+                    // fall back to a regular swapchain
+                    log::debug!(
+                        "full screen exclusive swapchain unavailable {:?}",
+                        err
+                    );
+                    full_screen_exclusive = None;
+                    unsafe {
+                        swapchain_loader.create_swapchain(&create_info, None)?
+                    }
+                }
+            }
+        } else {
+            unsafe { swapchain_loader.create_swapchain(&create_info, None)? }
+        };
 
         let swapchain_images = unsafe {
             swapchain_loader
@@ -106,6 +296,7 @@ impl Swapchain {
         let render_pass = render_pass::create_render_pass(
             device.as_ref(),
             image_format.format,
+            vk::ImageLayout::PRESENT_SRC_KHR,
         )?;
 
         let swapchain_image_views = images::create_image_views(
@@ -127,45 +318,100 @@ impl Swapchain {
             render_pass,
             swapchain_image_views,
             framebuffers,
+            images: swapchain_images,
             extent,
             format: image_format.format,
             color_space: image_format.color_space,
+            image_usage,
+            present_mode,
+            pre_transform,
+            final_layout: vk::ImageLayout::PRESENT_SRC_KHR,
+            full_screen_exclusive,
+            exclusive_acquired: AtomicBool::new(false),
+            options,
             device,
         }))
     }
 
+    /// True when the window surface has a non-zero extent, so a swapchain
+    /// can be created for it.
+    ///
+    /// Minimized windows and Wayland surfaces which haven't received their
+    /// first configure event have no area.
+    pub fn surface_has_area(
+        device: &Device,
+        window_surface: &dyn WindowSurface,
+    ) -> Result<bool> {
+        let extent = selection::choose_swap_extent(
+            window_surface,
+            &device.physical_device,
+        )?;
+        Ok(extent.width > 0 && extent.height > 0)
+    }
+
     /// Rebuild a new swapchain using this swapchain as a reference.
     pub fn rebuild(
         &self,
         window_surface: &dyn WindowSurface,
     ) -> Result<Arc<Self>> {
-        Self::new(self.device.clone(), window_surface, Some(&self))
+        self.rebuild_with_options(window_surface, self.options.clone())
+    }
+
+    /// Rebuild a new swapchain with different options.
+    pub fn rebuild_with_options(
+        &self,
+        window_surface: &dyn WindowSurface,
+        options: SwapchainOptions,
+    ) -> Result<Arc<Self>> {
+        Self::new(self.device.clone(), window_surface, Some(self), options)
+    }
+
+    /// The options used to create this swapchain.
+    pub fn options(&self) -> &SwapchainOptions {
+        &self.options
+    }
+
+    /// The settings actually chosen for this swapchain.
+    pub fn info(&self) -> SwapchainInfo {
+        SwapchainInfo {
+            image_count: self.images.len() as u32,
+            present_mode: self.present_mode,
+            format: self.format,
+            color_space: self.color_space,
+            extent: self.extent,
+            pre_transform: self.pre_transform,
+            full_screen_exclusive: self.full_screen_exclusive,
+        }
     }
 }
 
 impl Drop for Swapchain {
+    /// Destroy the swapchain and its framebuffers.
+    ///
+    /// No waiting is done here. The owner must make sure that every frame
+    /// which rendered to the swapchain has finished, like the FrameContext
+    /// does, before the last reference is dropped.
+    ///
+    /// Exclusive mode is released if it's still held.
     fn drop(&mut self) {
+        self.release_full_screen_exclusive();
         unsafe {
-            self.device
-                .logical_device
-                .queue_wait_idle(self.device.graphics_queue.raw())
-                .expect("wait for graphics queue to drain");
-            self.device
-                .logical_device
-                .queue_wait_idle(self.device.present_queue.raw())
-                .expect("wait for presentation queue to drain");
-            self.device
-                .logical_device
-                .device_wait_idle()
-                .expect("wait for device to idle");
-
-            let logical_device = &self.device.logical_device;
+            let device = &self.device;
             self.framebuffers.drain(..).for_each(|framebuffer| {
-                logical_device.destroy_framebuffer(framebuffer, None);
+                device.forget_vulkan_object(
+                    vk::ObjectType::FRAMEBUFFER,
+                    &framebuffer,
+                );
+                device.logical_device.destroy_framebuffer(framebuffer, None);
             });
             self.swapchain_image_views.drain(..).for_each(|view| {
-                logical_device.destroy_image_view(view, None);
+                device.forget_vulkan_object(vk::ObjectType::IMAGE_VIEW, &view);
+                device.logical_device.destroy_image_view(view, None);
             });
+            self.device.forget_vulkan_object(
+                vk::ObjectType::RENDER_PASS,
+                &self.render_pass,
+            );
             self.device
                 .logical_device
                 .destroy_render_pass(self.render_pass, None);
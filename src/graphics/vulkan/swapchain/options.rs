@@ -0,0 +1,45 @@
+use super::SwapchainOptions;
+
+use ash::vk;
+
+impl SwapchainOptions {
+    /// Double buffering with vsync. Each frame is presented in order, so
+    /// input is never more than a frame or two behind the screen.
+    ///
+    /// Full screen exclusive presentation is used where it's supported.
+    pub fn low_latency() -> Self {
+        Self {
+            image_count: Some(2),
+            present_modes: vec![vk::PresentModeKHR::FIFO],
+            full_screen_exclusive: true,
+        }
+    }
+
+    /// Triple buffering which never waits for vsync. Rendering runs as fast as
+    /// possible and the newest finished frame is shown at each refresh.
+    pub fn throughput() -> Self {
+        Self {
+            image_count: Some(3),
+            present_modes: vec![
+                vk::PresentModeKHR::MAILBOX,
+                vk::PresentModeKHR::IMMEDIATE,
+            ],
+            full_screen_exclusive: false,
+        }
+    }
+}
+
+impl Default for SwapchainOptions {
+    /// One more image than the surface's minimum, presented with MAILBOX when
+    /// it is available.
+    fn default() -> Self {
+        Self {
+            image_count: None,
+            present_modes: vec![
+                vk::PresentModeKHR::MAILBOX,
+                vk::PresentModeKHR::IMMEDIATE,
+            ],
+            full_screen_exclusive: false,
+        }
+    }
+}
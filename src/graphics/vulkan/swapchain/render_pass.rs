@@ -7,9 +7,13 @@ use anyhow::Result;
 use ash::{version::DeviceV1_0, vk};
 
 /// Create a render pass for the graphics pipeline.
+///
+/// The color attachment is left in `final_layout`, which is PRESENT_SRC for
+/// real swapchains.
 pub fn create_render_pass(
     device: &Device,
     format: vk::Format,
+    final_layout: vk::ImageLayout,
 ) -> Result<vk::RenderPass> {
     let attachments = [vk::AttachmentDescription {
         format,
@@ -19,7 +23,7 @@ pub fn create_render_pass(
         stencil_load_op: vk::AttachmentLoadOp::DONT_CARE,
         stencil_store_op: vk::AttachmentStoreOp::DONT_CARE,
         initial_layout: vk::ImageLayout::UNDEFINED,
-        final_layout: vk::ImageLayout::PRESENT_SRC_KHR,
+        final_layout,
         ..Default::default()
     }];
 
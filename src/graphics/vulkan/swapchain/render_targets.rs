@@ -0,0 +1,75 @@
+use super::{images, render_pass, RenderTargetProvider, Swapchain};
+
+use crate::graphics::vulkan::{Device, SwapchainOptions};
+
+use anyhow::{bail, Result};
+use ash::vk;
+use std::sync::{atomic::AtomicBool, Arc};
+
+impl Swapchain {
+    /// Wrap the images owned by a render target provider.
+    ///
+    /// Views, framebuffers, and the render pass are created just like they
+    /// are for a real swapchain, but there is no `SwapchainKHR`. Frames are
+    /// acquired from and released to the provider instead of being
+    /// presented.
+    ///
+    /// The options aren't used by the render targets, they're kept so the
+    /// window's swapchain can be restored with the same options later.
+    pub fn for_render_targets(
+        device: Arc<Device>,
+        render_targets: &dyn RenderTargetProvider,
+        options: SwapchainOptions,
+    ) -> Result<Arc<Self>> {
+        let images = render_targets.images();
+        let extent = render_targets.extent();
+        let format = render_targets.format();
+        if images.is_empty() {
+            bail!("the render target provider has no images!");
+        }
+        if extent.width == 0 || extent.height == 0 {
+            bail!("unable to render to targets with no area {:?}", extent);
+        }
+
+        let final_layout = render_targets.final_layout();
+        let render_pass = render_pass::create_render_pass(
+            device.as_ref(),
+            format,
+            final_layout,
+        )?;
+        let image_views =
+            images::create_image_views(device.as_ref(), format, &images)?;
+        let framebuffers = images::create_framebuffers(
+            device.as_ref(),
+            &image_views,
+            render_pass,
+            extent,
+        )?;
+
+        Ok(Arc::new(Self {
+            swapchain_loader: device.create_swapchain_loader(),
+            swapchain: vk::SwapchainKHR::null(),
+            render_pass,
+            swapchain_image_views: image_views,
+            framebuffers,
+            images,
+            extent,
+            format,
+            color_space: vk::ColorSpaceKHR::SRGB_NONLINEAR,
+            image_usage: render_targets.image_usage(),
+            present_mode: vk::PresentModeKHR::FIFO,
+            pre_transform: vk::SurfaceTransformFlagsKHR::IDENTITY,
+            final_layout,
+            full_screen_exclusive: None,
+            exclusive_acquired: AtomicBool::new(false),
+            options,
+            device,
+        }))
+    }
+
+    /// True when this swapchain wraps a render target provider's images
+    /// rather than a `SwapchainKHR`.
+    pub fn is_render_targets(&self) -> bool {
+        self.swapchain == vk::SwapchainKHR::null() && !self.images.is_empty()
+    }
+}
@@ -7,24 +7,63 @@ use ash::vk;
 
 /// Choose the number of images to use in the swapchain based on the min and
 /// max numbers of images supported by the device.
+///
+/// When no count is requested, one more than the minimum is used.
 pub fn choose_image_count(
     window_surface: &dyn WindowSurface,
     physical_device: &vk::PhysicalDevice,
+    requested: Option<u32>,
 ) -> Result<u32> {
     //! querying surface capabilities is safe in this context because the
     //! physical device will not be selected unless it supports the swapchain
     //! extension
     let capabilities =
         unsafe { window_surface.surface_capabilities(physical_device)? };
-    let proposed_image_count = capabilities.min_image_count + 1;
-    if capabilities.max_image_count > 0 {
-        Ok(std::cmp::min(
-            proposed_image_count,
-            capabilities.max_image_count,
-        ))
+    let image_count = select_image_count(&capabilities, requested);
+    log::info!("chosen image count {}", image_count);
+    Ok(image_count)
+}
+
+/// Clamp the requested image count to the range supported by the surface.
+/// A max image count of zero means there is no maximum.
+fn select_image_count(
+    capabilities: &vk::SurfaceCapabilitiesKHR,
+    requested: Option<u32>,
+) -> u32 {
+    let proposed_image_count =
+        requested.unwrap_or(capabilities.min_image_count + 1);
+    let max_image_count = if capabilities.max_image_count > 0 {
+        capabilities.max_image_count
     } else {
-        Ok(proposed_image_count)
+        u32::MAX
+    };
+    clamp(
+        proposed_image_count,
+        capabilities.min_image_count,
+        max_image_count,
+    )
+}
+
+/// Choose the usage flags for swapchain images.
+///
+/// Images are always usable as color attachments. They are also usable as
+/// transfer sources when the surface supports it, which allows frames to be
+/// read back to the CPU.
+pub fn choose_image_usage(
+    window_surface: &dyn WindowSurface,
+    physical_device: &vk::PhysicalDevice,
+) -> Result<vk::ImageUsageFlags> {
+    // querying surface capabilities is safe in this context because the
+    // physical device will not be selected unless it supports the swapchain
+    // extension
+    let capabilities =
+        unsafe { window_surface.surface_capabilities(physical_device)? };
+    let supported = capabilities.supported_usage_flags;
+    let mut usage = vk::ImageUsageFlags::COLOR_ATTACHMENT;
+    if supported.contains(vk::ImageUsageFlags::TRANSFER_SRC) {
+        usage |= vk::ImageUsageFlags::TRANSFER_SRC;
     }
+    Ok(usage)
 }
 
 /// Choose a surface format for the swapchain based on the window and chosen
@@ -57,9 +96,11 @@ pub fn choose_surface_format(
 /// Choose a presentation mode for the swapchain based on the window and chosen
 /// physical device.
 ///
+/// The first supported mode in the preferred list is used.
 pub fn choose_present_mode(
     window_surface: &dyn WindowSurface,
     physical_device: &vk::PhysicalDevice,
+    preferred: &[vk::PresentModeKHR],
 ) -> vk::PresentModeKHR {
     //! checking presentation modes is safe because support for the swapchain
     //! extension is verified when picking a physical device
@@ -68,19 +109,32 @@ pub fn choose_present_mode(
 
     log::info!("available presentation modes {:?}", modes);
 
-    let mode = if modes.contains(&vk::PresentModeKHR::MAILBOX) {
-        vk::PresentModeKHR::MAILBOX
-    } else {
-        vk::PresentModeKHR::IMMEDIATE
-    };
+    let mode = select_present_mode(&modes, preferred);
 
     log::info!("chosen presentation mode {:?}", mode);
 
     mode
 }
 
+/// Pick the first preferred mode which is supported, falling back to FIFO
+/// which every device is required to support.
+fn select_present_mode(
+    supported: &[vk::PresentModeKHR],
+    preferred: &[vk::PresentModeKHR],
+) -> vk::PresentModeKHR {
+    preferred
+        .iter()
+        .cloned()
+        .find(|mode| supported.contains(mode))
+        .unwrap_or(vk::PresentModeKHR::FIFO)
+}
+
 /// Choose the swap extent for the swapchain based on the window's framebuffer
 /// size.
+///
+/// The extent can have zero area, like when the window is minimized or a
+/// Wayland surface hasn't been configured yet. No swapchain can be created
+/// until the window has some area.
 pub fn choose_swap_extent(
     window_surface: &dyn WindowSurface,
     physical_device: &vk::PhysicalDevice,
@@ -89,26 +143,101 @@ pub fn choose_swap_extent(
     //! extenstion is verified when picking a physical device
     let capabilities =
         unsafe { window_surface.surface_capabilities(physical_device)? };
+    let extent =
+        select_swap_extent(&capabilities, window_surface.framebuffer_size());
+    log::debug!("chosen extent {:?}", extent);
+    Ok(extent)
+}
 
-    if capabilities.current_extent.width != u32::MAX {
-        log::debug!("use current extent {:?}", capabilities.current_extent);
-        Ok(capabilities.current_extent)
+/// The surface's current extent is used when it's defined. An extent of
+/// 0xFFFFFFFF x 0xFFFFFFFF means the surface size is determined by the
+/// swapchain, like on Wayland, so the framebuffer size is used instead.
+///
+/// Either way the extent is clamped to the range supported by the surface.
+fn select_swap_extent(
+    capabilities: &vk::SurfaceCapabilitiesKHR,
+    framebuffer_size: (u32, u32),
+) -> vk::Extent2D {
+    let current = capabilities.current_extent;
+    let desired = if current.width == u32::MAX && current.height == u32::MAX {
+        let (width, height) = framebuffer_size;
+        orient_extent(
+            vk::Extent2D { width, height },
+            select_pre_transform(capabilities),
+        )
     } else {
-        let (width, height) = window_surface.framebuffer_size();
-        let extent = vk::Extent2D {
-            width: clamp(
-                width,
-                capabilities.min_image_extent.width,
-                capabilities.max_image_extent.width,
-            ),
-            height: clamp(
-                height,
-                capabilities.min_image_extent.height,
-                capabilities.max_image_extent.height,
-            ),
-        };
-        log::debug!("use computed extent {:?}", extent);
-        Ok(extent)
+        current
+    };
+    vk::Extent2D {
+        width: clamp(
+            desired.width,
+            capabilities.min_image_extent.width,
+            capabilities.max_image_extent.width,
+        ),
+        height: clamp(
+            desired.height,
+            capabilities.min_image_extent.height,
+            capabilities.max_image_extent.height,
+        ),
+    }
+}
+
+/// Choose the transform the presentation engine should apply to swapchain
+/// images.
+///
+/// The surface's current transform is used when it's a rotation, so the
+/// presentation engine doesn't need to rotate each image. The rotation is
+/// applied in the projection instead.
+pub fn choose_pre_transform(
+    window_surface: &dyn WindowSurface,
+    physical_device: &vk::PhysicalDevice,
+) -> Result<vk::SurfaceTransformFlagsKHR> {
+    // Getting surface capabilities is safe because support for the swapchain
+    // extension is verified when picking a physical device
+    let capabilities =
+        unsafe { window_surface.surface_capabilities(physical_device)? };
+    let pre_transform = select_pre_transform(&capabilities);
+    log::debug!("chosen pre transform {:?}", pre_transform);
+    Ok(pre_transform)
+}
+
+/// Use the current transform when it's a supported rotation, otherwise fall
+/// back to the identity.
+fn select_pre_transform(
+    capabilities: &vk::SurfaceCapabilitiesKHR,
+) -> vk::SurfaceTransformFlagsKHR {
+    let rotations = vk::SurfaceTransformFlagsKHR::IDENTITY
+        | vk::SurfaceTransformFlagsKHR::ROTATE_90
+        | vk::SurfaceTransformFlagsKHR::ROTATE_180
+        | vk::SurfaceTransformFlagsKHR::ROTATE_270;
+    let current = capabilities.current_transform;
+    let supported = capabilities.supported_transforms;
+    if rotations.contains(current) && supported.contains(current) {
+        current
+    } else if supported.contains(vk::SurfaceTransformFlagsKHR::IDENTITY) {
+        vk::SurfaceTransformFlagsKHR::IDENTITY
+    } else {
+        current
+    }
+}
+
+/// Swap the width and height when the pre transform is a quarter turn.
+///
+/// Swapchain images are always in the display's native orientation, but the
+/// window's framebuffer size is in the rotated orientation.
+fn orient_extent(
+    extent: vk::Extent2D,
+    pre_transform: vk::SurfaceTransformFlagsKHR,
+) -> vk::Extent2D {
+    if pre_transform == vk::SurfaceTransformFlagsKHR::ROTATE_90
+        || pre_transform == vk::SurfaceTransformFlagsKHR::ROTATE_270
+    {
+        vk::Extent2D {
+            width: extent.height,
+            height: extent.width,
+        }
+    } else {
+        extent
     }
 }
 
@@ -116,3 +245,169 @@ pub fn choose_swap_extent(
 fn clamp(x: u32, min: u32, max: u32) -> u32 {
     std::cmp::max(min, std::cmp::min(x, max))
 }
+
+/// Pick how the swapchain should use full screen exclusive presentation.
+///
+/// Returns None when exclusive presentation wasn't requested or the device
+/// doesn't support it. The application can only control exclusive mode
+/// when the surface's monitor is known, otherwise the driver decides.
+pub fn choose_full_screen_exclusive(
+    requested: bool,
+    extension_enabled: bool,
+    monitor_known: bool,
+) -> Option<vk::FullScreenExclusiveEXT> {
+    if !(requested && extension_enabled) {
+        None
+    } else if monitor_known {
+        Some(vk::FullScreenExclusiveEXT::APPLICATION_CONTROLLED)
+    } else {
+        Some(vk::FullScreenExclusiveEXT::ALLOWED)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn capabilities(min: u32, max: u32) -> vk::SurfaceCapabilitiesKHR {
+        vk::SurfaceCapabilitiesKHR {
+            min_image_count: min,
+            max_image_count: max,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn image_count_should_default_to_one_more_than_the_minimum() {
+        assert_eq!(select_image_count(&capabilities(2, 8), None), 3);
+        assert_eq!(select_image_count(&capabilities(2, 2), None), 2);
+    }
+
+    #[test]
+    fn image_count_should_clamp_requests_to_the_surface() {
+        assert_eq!(select_image_count(&capabilities(3, 8), Some(2)), 3);
+        assert_eq!(select_image_count(&capabilities(1, 2), Some(3)), 2);
+        // a max of zero means there is no limit
+        assert_eq!(select_image_count(&capabilities(1, 0), Some(5)), 5);
+    }
+
+    #[test]
+    fn present_mode_should_be_the_first_supported_preference() {
+        let supported =
+            [vk::PresentModeKHR::FIFO, vk::PresentModeKHR::IMMEDIATE];
+        let mode = select_present_mode(
+            &supported,
+            &[vk::PresentModeKHR::MAILBOX, vk::PresentModeKHR::IMMEDIATE],
+        );
+        assert_eq!(mode, vk::PresentModeKHR::IMMEDIATE);
+
+        let fallback =
+            select_present_mode(&supported, &[vk::PresentModeKHR::MAILBOX]);
+        assert_eq!(fallback, vk::PresentModeKHR::FIFO);
+    }
+
+    #[test]
+    fn full_screen_exclusive_should_require_the_extension() {
+        assert_eq!(choose_full_screen_exclusive(true, false, true), None);
+        assert_eq!(choose_full_screen_exclusive(false, true, true), None);
+    }
+
+    #[test]
+    fn full_screen_exclusive_should_be_application_controlled_with_a_monitor() {
+        assert_eq!(
+            choose_full_screen_exclusive(true, true, true),
+            Some(vk::FullScreenExclusiveEXT::APPLICATION_CONTROLLED)
+        );
+        assert_eq!(
+            choose_full_screen_exclusive(true, true, false),
+            Some(vk::FullScreenExclusiveEXT::ALLOWED)
+        );
+    }
+
+    #[test]
+    fn pre_transform_should_use_the_current_rotation() {
+        let capabilities = vk::SurfaceCapabilitiesKHR {
+            current_transform: vk::SurfaceTransformFlagsKHR::ROTATE_90,
+            supported_transforms: vk::SurfaceTransformFlagsKHR::IDENTITY
+                | vk::SurfaceTransformFlagsKHR::ROTATE_90,
+            ..Default::default()
+        };
+        assert_eq!(
+            select_pre_transform(&capabilities),
+            vk::SurfaceTransformFlagsKHR::ROTATE_90
+        );
+    }
+
+    #[test]
+    fn pre_transform_should_fall_back_to_identity_for_mirrors() {
+        let capabilities = vk::SurfaceCapabilitiesKHR {
+            current_transform: vk::SurfaceTransformFlagsKHR::HORIZONTAL_MIRROR,
+            supported_transforms: vk::SurfaceTransformFlagsKHR::IDENTITY
+                | vk::SurfaceTransformFlagsKHR::HORIZONTAL_MIRROR,
+            ..Default::default()
+        };
+        assert_eq!(
+            select_pre_transform(&capabilities),
+            vk::SurfaceTransformFlagsKHR::IDENTITY
+        );
+    }
+
+    #[test]
+    fn extent_should_swap_for_quarter_turns() {
+        let extent = vk::Extent2D {
+            width: 800,
+            height: 600,
+        };
+        let rotated =
+            orient_extent(extent, vk::SurfaceTransformFlagsKHR::ROTATE_270);
+        assert_eq!((rotated.width, rotated.height), (600, 800));
+        let flipped =
+            orient_extent(extent, vk::SurfaceTransformFlagsKHR::ROTATE_180);
+        assert_eq!((flipped.width, flipped.height), (800, 600));
+    }
+
+    fn extent_capabilities(
+        current: (u32, u32),
+        min: (u32, u32),
+        max: (u32, u32),
+    ) -> vk::SurfaceCapabilitiesKHR {
+        vk::SurfaceCapabilitiesKHR {
+            current_extent: vk::Extent2D {
+                width: current.0,
+                height: current.1,
+            },
+            min_image_extent: vk::Extent2D {
+                width: min.0,
+                height: min.1,
+            },
+            max_image_extent: vk::Extent2D {
+                width: max.0,
+                height: max.1,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn swap_extent_should_use_the_current_extent_when_defined() {
+        let capabilities =
+            extent_capabilities((800, 600), (1, 1), (4096, 4096));
+        let extent = select_swap_extent(&capabilities, (1024, 768));
+        assert_eq!((extent.width, extent.height), (800, 600));
+    }
+
+    #[test]
+    fn swap_extent_should_clamp_the_framebuffer_size_when_undefined() {
+        let capabilities =
+            extent_capabilities((u32::MAX, u32::MAX), (64, 64), (1920, 1080));
+        let extent = select_swap_extent(&capabilities, (4000, 10));
+        assert_eq!((extent.width, extent.height), (1920, 64));
+    }
+
+    #[test]
+    fn swap_extent_should_keep_zero_area_for_minimized_windows() {
+        let capabilities = extent_capabilities((0, 0), (0, 0), (1920, 1080));
+        let extent = select_swap_extent(&capabilities, (0, 0));
+        assert_eq!((extent.width, extent.height), (0, 0));
+    }
+}
@@ -0,0 +1,47 @@
+use super::{render_pass, Swapchain};
+
+use anyhow::Result;
+use ash::vk;
+use std::sync::{atomic::AtomicBool, Arc};
+
+impl Swapchain {
+    /// A stand-in for this swapchain while the application is suspended and
+    /// its window surface is gone, like when an Android activity is paused.
+    ///
+    /// There is no `SwapchainKHR` and there are no images, so nothing can be
+    /// rendered. The render pass, format, and extent are kept so systems
+    /// which are rebuilt while suspended still have something to build
+    /// against.
+    pub fn suspended(&self) -> Result<Arc<Self>> {
+        let render_pass = render_pass::create_render_pass(
+            self.device.as_ref(),
+            self.format,
+            self.final_layout,
+        )?;
+        Ok(Arc::new(Self {
+            swapchain_loader: self.device.create_swapchain_loader(),
+            swapchain: vk::SwapchainKHR::null(),
+            render_pass,
+            swapchain_image_views: vec![],
+            framebuffers: vec![],
+            images: vec![],
+            extent: self.extent,
+            format: self.format,
+            color_space: self.color_space,
+            image_usage: self.image_usage,
+            present_mode: self.present_mode,
+            pre_transform: self.pre_transform,
+            final_layout: self.final_layout,
+            full_screen_exclusive: None,
+            exclusive_acquired: AtomicBool::new(false),
+            options: self.options.clone(),
+            device: self.device.clone(),
+        }))
+    }
+
+    /// True when this is a stand-in for a suspended application's
+    /// swapchain, see `Swapchain::suspended`.
+    pub fn is_suspended(&self) -> bool {
+        self.swapchain == vk::SwapchainKHR::null() && self.images.is_empty()
+    }
+}
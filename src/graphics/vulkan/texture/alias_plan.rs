@@ -0,0 +1,178 @@
+use super::{AliasPlan, ImageLifetime};
+
+use crate::graphics::vulkan::device_allocator::{
+    Allocation, DeviceAllocator, Suballocator,
+};
+
+use anyhow::{bail, Result};
+use ash::vk;
+
+impl ImageLifetime {
+    /// An image which is only used by a single pass.
+    pub fn pass(index: u32) -> Self {
+        Self {
+            first_pass: index,
+            last_pass: index,
+        }
+    }
+
+    /// True when both images are needed during at least one pass.
+    pub fn overlaps(&self, other: &ImageLifetime) -> bool {
+        self.first_pass <= other.last_pass && other.first_pass <= self.last_pass
+    }
+}
+
+impl AliasPlan {
+    /// Pack images into as little memory as possible.
+    ///
+    /// Images are placed in the order their lifetimes begin. Before each one
+    /// is placed, the regions of images whose lifetimes have already ended
+    /// are released, so later images can reuse them. The regions are tracked
+    /// with a `Suballocator`, which keeps every offset aligned.
+    pub fn new(
+        requirements: &[(vk::MemoryRequirements, ImageLifetime)],
+    ) -> Result<Self> {
+        for (_, lifetime) in requirements {
+            if lifetime.first_pass > lifetime.last_pass {
+                bail!("{:?} ends before it begins", lifetime);
+            }
+        }
+
+        // Big enough to place every image side by side, so allocating from
+        // it can't fail.
+        let mut whole = Allocation::null();
+        whole.byte_size = requirements
+            .iter()
+            .map(|(memory, _)| memory.size + memory.alignment.max(1))
+            .sum();
+        let mut regions = Suballocator::new(whole);
+
+        let mut order: Vec<usize> = (0..requirements.len()).collect();
+        order.sort_by_key(|&index| requirements[index].1.first_pass);
+
+        let mut placed: Vec<Option<Allocation>> =
+            vec![None; requirements.len()];
+        let mut live: Vec<usize> = vec![];
+        let mut size = 0;
+        for index in order {
+            let (memory, lifetime) = requirements[index];
+            let mut still_live = vec![];
+            for other in live {
+                if requirements[other].1.last_pass < lifetime.first_pass {
+                    let region = placed[other].as_ref().unwrap();
+                    unsafe { regions.free(region)? };
+                } else {
+                    still_live.push(other);
+                }
+            }
+            live = still_live;
+
+            let region = unsafe {
+                regions.allocate(
+                    vk::MemoryAllocateInfo {
+                        allocation_size: memory.size,
+                        ..Default::default()
+                    },
+                    memory.alignment.max(1),
+                )?
+            };
+            size = size.max(region.offset + memory.size);
+            placed[index] = Some(region);
+            live.push(index);
+        }
+
+        Ok(Self {
+            offsets: placed
+                .into_iter()
+                .map(|region| region.unwrap().offset)
+                .collect(),
+            size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn image(
+        size: u64,
+        alignment: u64,
+        first_pass: u32,
+        last_pass: u32,
+    ) -> (vk::MemoryRequirements, ImageLifetime) {
+        (
+            vk::MemoryRequirements {
+                size,
+                alignment,
+                memory_type_bits: !0,
+            },
+            ImageLifetime {
+                first_pass,
+                last_pass,
+            },
+        )
+    }
+
+    #[test]
+    fn images_used_one_after_another_should_share_memory() -> Result<()> {
+        let plan = AliasPlan::new(&[
+            image(4096, 256, 0, 1),
+            image(1024, 256, 2, 3),
+            image(8192, 256, 4, 4),
+        ])?;
+        assert_eq!(plan.offsets, vec![0, 0, 0]);
+        assert_eq!(plan.size, 8192);
+        Ok(())
+    }
+
+    #[test]
+    fn overlapping_images_should_not_share_memory() -> Result<()> {
+        let plan =
+            AliasPlan::new(&[image(1000, 256, 0, 2), image(1000, 256, 1, 3)])?;
+        assert_eq!(plan.offsets, vec![0, 1024]);
+        assert_eq!(plan.size, 2024);
+        Ok(())
+    }
+
+    #[test]
+    fn a_released_region_should_be_reused_by_later_images() -> Result<()> {
+        // the first image ends before the third begins, the second is
+        // needed the whole time
+        let plan = AliasPlan::new(&[
+            image(512, 512, 0, 0),
+            image(512, 512, 0, 2),
+            image(512, 512, 1, 2),
+        ])?;
+        assert_eq!(plan.offsets, vec![0, 512, 0]);
+        assert_eq!(plan.size, 1024);
+        Ok(())
+    }
+
+    #[test]
+    fn lifetimes_should_be_placed_in_order_regardless_of_request_order(
+    ) -> Result<()> {
+        let plan =
+            AliasPlan::new(&[image(64, 64, 3, 3), image(128, 64, 0, 1)])?;
+        assert_eq!(plan.offsets, vec![0, 0]);
+        assert_eq!(plan.size, 128);
+        Ok(())
+    }
+
+    #[test]
+    fn backwards_lifetimes_should_be_rejected() {
+        assert!(AliasPlan::new(&[image(64, 64, 2, 1)]).is_err());
+    }
+
+    #[test]
+    fn lifetimes_which_share_a_pass_should_overlap() {
+        assert!(ImageLifetime::pass(2).overlaps(&ImageLifetime {
+            first_pass: 0,
+            last_pass: 2,
+        }));
+        assert!(!ImageLifetime::pass(3).overlaps(&ImageLifetime {
+            first_pass: 0,
+            last_pass: 2,
+        }));
+    }
+}
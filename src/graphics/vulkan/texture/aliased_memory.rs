@@ -0,0 +1,173 @@
+use super::{AliasPlan, AliasedMemory, ImageLifetime, TextureImage};
+
+use crate::graphics::vulkan::Device;
+
+use anyhow::{bail, Result};
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+impl AliasedMemory {
+    /// Create images which share a single block of memory.
+    ///
+    /// Each image is placed by an `AliasPlan` built from its memory
+    /// requirements and lifetime, so only images which are needed at the
+    /// same time take up separate memory. The textures are returned in the
+    /// order they were requested.
+    ///
+    /// Every image is created with `vk::ImageCreateFlags::ALIAS` when the
+    /// device supports Vulkan 1.1. The contents of an image are undefined at
+    /// the start of its lifetime, so its first use must transition it from
+    /// `UNDEFINED` and overwrite it.
+    pub fn create_images(
+        device: Arc<Device>,
+        requests: &[(vk::ImageCreateInfo, ImageLifetime)],
+        memory_property_flags: vk::MemoryPropertyFlags,
+    ) -> Result<Vec<TextureImage>> {
+        let alias_flag = if device.supports_image_aliasing() {
+            vk::ImageCreateFlags::ALIAS
+        } else {
+            vk::ImageCreateFlags::empty()
+        };
+        let create_infos: Vec<vk::ImageCreateInfo> = requests
+            .iter()
+            .map(|(create_info, _)| vk::ImageCreateInfo {
+                flags: create_info.flags | alias_flag,
+                ..*create_info
+            })
+            .collect();
+
+        let mut images = vec![];
+        for create_info in &create_infos {
+            let image = unsafe {
+                device.logical_device.create_image(create_info, None)
+            };
+            match image {
+                Ok(image) => images.push(image),
+                Err(error) => {
+                    unsafe { destroy_images(&device, &images) };
+                    return Err(error.into());
+                }
+            }
+        }
+
+        let allocated = unsafe {
+            Self::allocate_for(
+                &device,
+                &images,
+                requests,
+                memory_property_flags,
+            )
+        };
+        let (memory, offsets) = match allocated {
+            Ok(allocated) => allocated,
+            Err(error) => {
+                unsafe { destroy_images(&device, &images) };
+                return Err(error);
+            }
+        };
+
+        images
+            .into_iter()
+            .zip(create_infos.iter())
+            .zip(offsets)
+            .map(|((image, create_info), offset)| {
+                let requirements = unsafe {
+                    device.logical_device.get_image_memory_requirements(image)
+                };
+                let mut allocation = memory.allocation.clone();
+                allocation.offset += offset;
+                allocation.byte_size = requirements.size;
+                let mut texture = TextureImage::with_memory(
+                    device.clone(),
+                    image,
+                    allocation,
+                    create_info,
+                )?;
+                texture.aliased_memory = Some(memory.clone());
+                Ok(texture)
+            })
+            .collect()
+    }
+
+    /// The size of the shared block of memory.
+    pub fn size(&self) -> u64 {
+        self.allocation.byte_size
+    }
+
+    /// Allocate one block which fits every image according to the plan,
+    /// returning it with each image's offset.
+    ///
+    /// # Safety
+    ///
+    /// - the images must be valid and not bound to memory yet
+    unsafe fn allocate_for(
+        device: &Arc<Device>,
+        images: &[vk::Image],
+        requests: &[(vk::ImageCreateInfo, ImageLifetime)],
+        memory_property_flags: vk::MemoryPropertyFlags,
+    ) -> Result<(Arc<Self>, Vec<u64>)> {
+        let requirements: Vec<(vk::MemoryRequirements, ImageLifetime)> = images
+            .iter()
+            .zip(requests.iter())
+            .map(|(&image, (_, lifetime))| {
+                (
+                    device.logical_device.get_image_memory_requirements(image),
+                    *lifetime,
+                )
+            })
+            .collect();
+
+        let memory_type_bits = requirements
+            .iter()
+            .fold(!0, |bits, (memory, _)| bits & memory.memory_type_bits);
+        if memory_type_bits == 0 {
+            bail!("the aliased images have no memory type in common");
+        }
+        let alignment = requirements
+            .iter()
+            .map(|(memory, _)| memory.alignment)
+            .max()
+            .unwrap_or(1);
+
+        let plan = AliasPlan::new(&requirements)?;
+        let allocation = device.allocate_memory(
+            vk::MemoryRequirements {
+                size: plan.size,
+                alignment,
+                memory_type_bits,
+            },
+            memory_property_flags,
+        )?;
+        log::debug!(
+            "aliased {} images into {} bytes, {} bytes without aliasing",
+            images.len(),
+            plan.size,
+            requirements
+                .iter()
+                .map(|(memory, _)| memory.size)
+                .sum::<u64>()
+        );
+        Ok((
+            Arc::new(Self {
+                allocation,
+                device: device.clone(),
+            }),
+            plan.offsets,
+        ))
+    }
+}
+
+impl Drop for AliasedMemory {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.free_memory(&self.allocation).unwrap();
+        }
+    }
+}
+
+/// Destroy images which failed to get memory.
+unsafe fn destroy_images(device: &Device, images: &[vk::Image]) {
+    for &image in images {
+        device.logical_device.destroy_image(image, None);
+    }
+}
@@ -0,0 +1,91 @@
+use super::TextureImage;
+
+use crate::graphics::vulkan::{Device, ExternalMemoryHandle, QueueTransfer};
+
+use anyhow::Result;
+use ash::{version::DeviceV1_0, vk};
+use std::sync::Arc;
+
+impl TextureImage {
+    /// Create a texture which uses memory allocated outside of this library,
+    /// for example a decoded video frame.
+    ///
+    /// The image create info must describe the external image exactly. The
+    /// handle type is added to the create info automatically. DMA-BUF images
+    /// are usually linear, so use `vk::ImageTiling::LINEAR` and a
+    /// `PREINITIALIZED` initial layout to keep the existing contents.
+    ///
+    /// Ownership of the image is acquired from the external queue family and
+    /// it's transitioned for reading in the fragment shader, so the texture
+    /// can be added to an atlas right away.
+    pub fn import(
+        device: Arc<Device>,
+        image_create_info: vk::ImageCreateInfo,
+        handle: ExternalMemoryHandle,
+    ) -> Result<Self> {
+        let external_create_info = vk::ExternalMemoryImageCreateInfo {
+            p_next: image_create_info.p_next,
+            handle_types: handle.handle_type(),
+            ..Default::default()
+        };
+        let image_create_info = vk::ImageCreateInfo {
+            p_next: &external_create_info as *const _ as *const _,
+            ..image_create_info
+        };
+        let image = unsafe {
+            device
+                .logical_device
+                .create_image(&image_create_info, None)?
+        };
+
+        let allocation = unsafe {
+            let memory_requirements =
+                device.logical_device.get_image_memory_requirements(image);
+            device.import_memory(
+                memory_requirements,
+                vk::MemoryPropertyFlags::empty(),
+                handle,
+                vk::MemoryDedicatedAllocateInfo {
+                    image,
+                    ..Default::default()
+                },
+            )
+        };
+        let allocation = match allocation {
+            Ok(allocation) => allocation,
+            Err(error) => {
+                unsafe { device.logical_device.destroy_image(image, None) };
+                return Err(error);
+            }
+        };
+
+        let texture =
+            Self::with_memory(device, image, allocation, &image_create_info)?;
+        unsafe {
+            texture.acquire_external(image_create_info.initial_layout)?;
+        }
+        Ok(texture)
+    }
+
+    /// Acquire the image from the external queue family and transition every
+    /// layer and mipmap for reading in the fragment shader.
+    unsafe fn acquire_external(
+        &self,
+        old_layout: vk::ImageLayout,
+    ) -> Result<()> {
+        let transfer = QueueTransfer {
+            src_family: vk::QUEUE_FAMILY_EXTERNAL,
+            dst_family: self.device.graphics_queue.family_id,
+            src_stage: vk::PipelineStageFlags::TOP_OF_PIPE,
+            src_access: vk::AccessFlags::empty(),
+            dst_stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            dst_access: vk::AccessFlags::SHADER_READ,
+            old_layout,
+            new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        };
+        self.device.sync_graphics_commands(|command_buffer| {
+            self.acquire_from(command_buffer, &transfer);
+            Ok(())
+        })
+    }
+}
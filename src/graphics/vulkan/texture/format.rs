@@ -0,0 +1,96 @@
+use ash::vk;
+
+/// The number of bytes used by each pixel of an uncompressed color format.
+///
+/// Returns None for formats which the texture upload path doesn't know how to
+/// size, like compressed or depth formats.
+pub fn bytes_per_pixel(format: vk::Format) -> Option<u64> {
+    match format {
+        vk::Format::R8_UNORM
+        | vk::Format::R8_SNORM
+        | vk::Format::R8_UINT
+        | vk::Format::R8_SINT
+        | vk::Format::R8_SRGB => Some(1),
+
+        vk::Format::R8G8_UNORM
+        | vk::Format::R8G8_SNORM
+        | vk::Format::R8G8_UINT
+        | vk::Format::R8G8_SINT
+        | vk::Format::R8G8_SRGB
+        | vk::Format::R16_UNORM
+        | vk::Format::R16_SNORM
+        | vk::Format::R16_UINT
+        | vk::Format::R16_SINT
+        | vk::Format::R16_SFLOAT => Some(2),
+
+        vk::Format::R8G8B8A8_UNORM
+        | vk::Format::R8G8B8A8_SNORM
+        | vk::Format::R8G8B8A8_UINT
+        | vk::Format::R8G8B8A8_SINT
+        | vk::Format::R8G8B8A8_SRGB
+        | vk::Format::B8G8R8A8_UNORM
+        | vk::Format::B8G8R8A8_SRGB
+        | vk::Format::R16G16_UNORM
+        | vk::Format::R16G16_SFLOAT
+        | vk::Format::R32_UINT
+        | vk::Format::R32_SINT
+        | vk::Format::R32_SFLOAT => Some(4),
+
+        vk::Format::R16G16B16A16_UNORM
+        | vk::Format::R16G16B16A16_SFLOAT
+        | vk::Format::R32G32_UINT
+        | vk::Format::R32G32_SFLOAT => Some(8),
+
+        vk::Format::R32G32B32A32_UINT | vk::Format::R32G32B32A32_SFLOAT => {
+            Some(16)
+        }
+
+        _ => None,
+    }
+}
+
+/// True when the hardware sRGB encodes values written to the format and
+/// decodes values sampled from it.
+pub fn is_srgb(format: vk::Format) -> bool {
+    matches!(
+        format,
+        vk::Format::R8_SRGB
+            | vk::Format::R8G8_SRGB
+            | vk::Format::R8G8B8_SRGB
+            | vk::Format::B8G8R8_SRGB
+            | vk::Format::R8G8B8A8_SRGB
+            | vk::Format::B8G8R8A8_SRGB
+            | vk::Format::A8B8G8R8_SRGB_PACK32
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn srgb_formats_should_be_recognized() {
+        assert!(is_srgb(vk::Format::B8G8R8A8_SRGB));
+        assert!(!is_srgb(vk::Format::B8G8R8A8_UNORM));
+    }
+
+    #[test]
+    fn single_channel_formats_should_use_one_byte() {
+        assert_eq!(bytes_per_pixel(vk::Format::R8_UNORM), Some(1));
+        assert_eq!(bytes_per_pixel(vk::Format::R16_SFLOAT), Some(2));
+        assert_eq!(bytes_per_pixel(vk::Format::R32_UINT), Some(4));
+    }
+
+    #[test]
+    fn rgba_formats_should_use_one_unit_per_channel() {
+        assert_eq!(bytes_per_pixel(vk::Format::R8G8_UNORM), Some(2));
+        assert_eq!(bytes_per_pixel(vk::Format::R8G8B8A8_SRGB), Some(4));
+        assert_eq!(bytes_per_pixel(vk::Format::R16G16B16A16_SFLOAT), Some(8));
+    }
+
+    #[test]
+    fn compressed_formats_should_be_unknown() {
+        assert_eq!(bytes_per_pixel(vk::Format::BC1_RGBA_SRGB_BLOCK), None);
+        assert_eq!(bytes_per_pixel(vk::Format::D32_SFLOAT), None);
+    }
+}
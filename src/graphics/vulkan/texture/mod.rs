@@ -1,23 +1,49 @@
+mod alias_plan;
+mod aliased_memory;
+mod external_texture;
+mod format;
 mod mipmap_extent;
 mod texture_image;
+mod view_type;
+
+pub use self::{
+    format::{bytes_per_pixel, is_srgb},
+    view_type::{view_type_for, CUBE_FACES},
+};
 
 use crate::graphics::vulkan::Device;
 
 use ash::vk;
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use super::device_allocator::Allocation;
 
 /// The TextureImage maintains the image, view, and memory, which are required
 /// when rendering with a texture.
 pub struct TextureImage {
-    bytes_per_pixel: u64,
+    /// The size of each pixel when uploading, None when the format isn't in
+    /// the format table and the texture can't be uploaded to.
+    bytes_per_pixel: Option<u64>,
+    format: vk::Format,
     image: vk::Image,
     extent: vk::Extent3D,
     view: vk::ImageView,
 
+    /// The number of layers in the image. Cube maps have one layer per face.
+    array_layers: u32,
+
+    /// How the view interprets the image's layers.
+    view_type: vk::ImageViewType,
+
     allocation: Allocation,
 
+    /// The shared memory the image is bound to, when it's aliased. The
+    /// texture keeps the memory alive and doesn't free its allocation.
+    aliased_memory: Option<Arc<AliasedMemory>>,
+
+    /// The file this texture's data was read from, if any.
+    source_path: Option<PathBuf>,
+
     device: Arc<Device>,
 }
 
@@ -30,3 +56,35 @@ pub struct MipmapExtent {
     /// The mipmap level's height, in pixels.
     pub height: u32,
 }
+
+/// A block of device memory shared by images which are never used at the
+/// same time, like the intermediate targets of post effects.
+///
+/// The images are created with `vk::ImageCreateFlags::ALIAS` and bound to
+/// offsets picked by an `AliasPlan`. Each texture holds a reference to the
+/// memory, which is freed once the last of them is dropped.
+pub struct AliasedMemory {
+    allocation: Allocation,
+    device: Arc<Device>,
+}
+
+/// The part of a frame an aliased image's contents are needed for, as the
+/// indices of the first and last passes which use it.
+///
+/// Images with overlapping lifetimes get separate memory. Otherwise the
+/// contents of an aliased image are undefined at the start of its lifetime.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImageLifetime {
+    pub first_pass: u32,
+    pub last_pass: u32,
+}
+
+/// Where each image lives in a shared block of memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AliasPlan {
+    /// The offset of each image, in the order they were requested.
+    pub offsets: Vec<u64>,
+
+    /// The size of the block needed to hold every image.
+    pub size: u64,
+}
@@ -1,8 +1,13 @@
-use super::{MipmapExtent, TextureImage};
+use super::{bytes_per_pixel, view_type_for, MipmapExtent, TextureImage};
 
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-use crate::graphics::vulkan::{buffer::Buffer, Device};
+use crate::graphics::vulkan::{
+    buffer::Buffer, device_allocator::Allocation, Device, QueueTransfer,
+};
 
 use anyhow::{bail, Result};
 use ash::{version::DeviceV1_0, vk};
@@ -26,14 +31,17 @@ impl TextureImage {
 
     /// Create the image, allocate memory, create a view for the texture.
     ///
-    /// Bytes per pixel is used by the various `upload_*` methods when copying
-    /// data from a buffer into the image. For example, if the image format
-    /// is R8G8B8A8_SRGB then the bytes per pixel is 4.
+    /// The various `upload_*` methods compute buffer sizes from the image
+    /// format, see [bytes_per_pixel]. Images with other formats can still be
+    /// created, for example as render targets, but can't be uploaded to.
+    ///
+    /// The view type is picked from the image type, flags, and layer count,
+    /// see [view_type_for]. 1D images, 2D arrays, and cube maps are all
+    /// supported. The view always covers every layer.
     pub fn new(
         device: Arc<Device>,
         image_create_info: vk::ImageCreateInfo,
         memory_property_flags: vk::MemoryPropertyFlags,
-        bytes_per_pixel: u64,
     ) -> Result<Self> {
         let image = unsafe {
             device
@@ -42,12 +50,19 @@ impl TextureImage {
         };
 
         let allocation = unsafe {
-            let memory_requirements =
-                device.logical_device.get_image_memory_requirements(image);
-            device
-                .allocate_memory(memory_requirements, memory_property_flags)?
+            device.allocate_image_memory(image, memory_property_flags)?
         };
 
+        Self::with_memory(device, image, allocation, &image_create_info)
+    }
+
+    /// Bind the allocation to the image and create the texture's view.
+    pub(super) fn with_memory(
+        device: Arc<Device>,
+        image: vk::Image,
+        allocation: Allocation,
+        image_create_info: &vk::ImageCreateInfo,
+    ) -> Result<Self> {
         unsafe {
             device.logical_device.bind_image_memory(
                 image,
@@ -56,16 +71,18 @@ impl TextureImage {
             )?;
         }
 
+        let array_layers = image_create_info.array_layers.max(1);
+        let view_type = view_type_for(image_create_info);
         let view_create_info = vk::ImageViewCreateInfo {
             image,
-            view_type: vk::ImageViewType::TYPE_2D,
+            view_type,
             format: image_create_info.format,
             subresource_range: vk::ImageSubresourceRange {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
                 base_mip_level: 0,
                 level_count: image_create_info.mip_levels,
                 base_array_layer: 0,
-                layer_count: 1,
+                layer_count: array_layers,
             },
             components: vk::ComponentMapping {
                 r: vk::ComponentSwizzle::R,
@@ -83,15 +100,66 @@ impl TextureImage {
         };
 
         Ok(Self {
-            bytes_per_pixel,
+            bytes_per_pixel: bytes_per_pixel(image_create_info.format),
+            format: image_create_info.format,
             image,
             extent: image_create_info.extent,
             view,
+            array_layers,
+            view_type,
             allocation,
+            aliased_memory: None,
+            source_path: None,
             device,
         })
     }
 
+    /// The size of the texture's first mipmap level, in pixels.
+    pub fn extent(&self) -> MipmapExtent {
+        MipmapExtent {
+            width: self.extent.width,
+            height: self.extent.height,
+        }
+    }
+
+    /// The number of layers in the image. Cube maps have one layer per face,
+    /// in the order +X, -X, +Y, -Y, +Z, -Z.
+    pub fn array_layers(&self) -> u32 {
+        self.array_layers
+    }
+
+    /// The texture's pixel format.
+    pub fn format(&self) -> vk::Format {
+        self.format
+    }
+
+    /// How the texture's view interprets its layers.
+    pub fn view_type(&self) -> vk::ImageViewType {
+        self.view_type
+    }
+
+    /// The number of bytes of device memory allocated for the image,
+    /// including every mipmap level.
+    pub fn memory_size(&self) -> u64 {
+        self.allocation.byte_size
+    }
+
+    /// True when the image shares its memory with other images, see
+    /// `AliasedMemory`.
+    pub fn is_aliased(&self) -> bool {
+        self.aliased_memory.is_some()
+    }
+
+    /// The file this texture's data was read from, if any.
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source_path.as_deref()
+    }
+
+    /// Record the file this texture's data was read from.
+    pub fn set_source_path(&mut self, path: impl Into<PathBuf>) {
+        self.source_path = Some(path.into());
+    }
+
     /// Upload a texture's data from a buffer.
     ///
     /// This method is just an alias to [Self::upload_mipmaps_from_buffer]
@@ -110,50 +178,258 @@ impl TextureImage {
 
     /// Upload a texture's mipmaps from a buffer.
     ///
-    /// * This method assumes that each mipmap has the same `bytes_per_pixel`
-    ///   as the texture image.
+    /// * Each mipmap is tightly packed using the texture's format, so an
+    ///   R8_UNORM mask uses one byte per pixel.
     /// * Order is super important. The first entry in `mipmap_sizes`
     ///   corresponds to the first region of memory in the src bufer. The
     ///   mipmap extents are used to compute the byte offset and size of each
     ///   mipmap region.
+    /// * 3D textures upload every slice of a mipmap, one after another.
     pub unsafe fn upload_mipmaps_from_buffer(
         &mut self,
         src: &impl Buffer,
         mipmap_sizes: &[MipmapExtent],
     ) -> Result<()> {
-        let required_size: u64 = mipmap_sizes
+        self.upload_layer_mipmaps_from_buffer(src, 0, mipmap_sizes)
+    }
+
+    /// Upload the mipmaps for a single layer of an array or cube texture from
+    /// a buffer.
+    ///
+    /// The buffer is laid out exactly like it is for
+    /// [Self::upload_mipmaps_from_buffer]. Other layers are not modified, so
+    /// each frame of a sprite sheet, or each face of a cube map, can be
+    /// uploaded separately. 1D textures use mipmap extents with a height of
+    /// one.
+    ///
+    /// The copy runs on the device's transfer queue. When that queue is from
+    /// a different family the mipmaps are then handed to the graphics queue.
+    ///
+    /// # Safety
+    ///
+    /// - the texture and buffer must not be in use by the gpu, the upload
+    ///   is submitted and waited on before returning
+    pub unsafe fn upload_layer_mipmaps_from_buffer(
+        &mut self,
+        src: &impl Buffer,
+        array_layer: u32,
+        mipmap_sizes: &[MipmapExtent],
+    ) -> Result<()> {
+        self.check_upload(src, 0, array_layer, mipmap_sizes)?;
+
+        // the uploaded mipmaps are handed to the graphics queue for sampling
+        let transfer = self.upload_transfer();
+        let uploaded = upload_range(array_layer, mipmap_sizes);
+        self.device.sync_transfer_commands(|command_buffer| {
+            self.record_mipmap_copies(
+                command_buffer,
+                src.raw(),
+                0,
+                array_layer,
+                mipmap_sizes,
+            );
+            transfer.record_image_release(
+                &self.device,
+                command_buffer,
+                self.image,
+                uploaded,
+            );
+            Ok(())
+        })?;
+        if transfer.is_ownership_transfer() {
+            self.device.sync_graphics_commands(|command_buffer| {
+                transfer.record_image_acquire(
+                    &self.device,
+                    command_buffer,
+                    self.image,
+                    uploaded,
+                );
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Record the commands which upload a layer's mipmaps from part of a
+    /// buffer, without submitting them.
+    ///
+    /// The mipmaps are read from `src_offset` onwards, laid out like they are
+    /// for [Self::upload_layer_mipmaps_from_buffer], and left in the transfer
+    /// destination layout. Once every mipmap is recorded, hand the texture to
+    /// the graphics queue with `release_to` and `acquire_from` using
+    /// [Self::upload_transfer]. Recording many uploads into one command
+    /// buffer avoids a submission and wait for every texture.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for the transfer queue
+    /// - the buffer and texture must live until the commands finish
+    pub unsafe fn record_upload_from_buffer(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        src: &impl Buffer,
+        src_offset: u64,
+        array_layer: u32,
+        mipmap_sizes: &[MipmapExtent],
+    ) -> Result<()> {
+        self.check_upload(src, src_offset, array_layer, mipmap_sizes)?;
+        self.record_mipmap_copies(
+            command_buffer,
+            src.raw(),
+            src_offset,
+            array_layer,
+            mipmap_sizes,
+        );
+        Ok(())
+    }
+
+    /// The number of bytes read by an upload of a layer's mipmaps.
+    pub fn upload_size(&self, mipmap_sizes: &[MipmapExtent]) -> Result<u64> {
+        let bytes_per_pixel = match self.bytes_per_pixel {
+            Some(bytes_per_pixel) => bytes_per_pixel,
+            None => bail!(
+                "Unable to compute upload sizes for {:?} textures!",
+                self.format
+            ),
+        };
+        Ok(mipmap_sizes
             .iter()
-            .map(|mipmap_size| mipmap_size.size_in_bytes(self.bytes_per_pixel))
-            .sum();
-        if required_size > src.size_in_bytes() {
+            .enumerate()
+            .map(|(mip_level, mipmap_size)| {
+                mipmap_size.size_in_bytes(bytes_per_pixel)
+                    * self.mip_depth(mip_level as u32) as u64
+            })
+            .sum())
+    }
+
+    /// Fail when the layer doesn't exist or the buffer is too small.
+    fn check_upload(
+        &self,
+        src: &impl Buffer,
+        src_offset: u64,
+        array_layer: u32,
+        mipmap_sizes: &[MipmapExtent],
+    ) -> Result<()> {
+        if array_layer >= self.array_layers {
+            bail!(
+                "The texture has {:?} layers, unable to upload layer {:?}!",
+                self.array_layers,
+                array_layer
+            );
+        }
+        let required_size = self.upload_size(mipmap_sizes)?;
+        let available = src.size_in_bytes().saturating_sub(src_offset);
+        if required_size > available {
             bail!(
                 "The texture expects {:?} bytes, but the provided buffer includes only {:?} bytes of data!",
                 required_size,
-                src.size_in_bytes()
+                available
+            );
+        }
+        Ok(())
+    }
+
+    /// Record a copy into each of a layer's mipmaps, leaving them in the
+    /// TRANSFER_DST_OPTIMAL layout.
+    unsafe fn record_mipmap_copies(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        src_buffer: vk::Buffer,
+        src_offset: u64,
+        array_layer: u32,
+        mipmap_sizes: &[MipmapExtent],
+    ) {
+        let bytes_per_pixel = self.bytes_per_pixel.unwrap_or(0);
+        let mut offset = src_offset;
+        for (mip_level, extent) in mipmap_sizes.iter().enumerate() {
+            let mip_level = mip_level as u32;
+            self.layer_write_barrier(command_buffer, mip_level, array_layer);
+            self.copy_buffer_to_image(
+                command_buffer,
+                src_buffer,
+                offset,
+                extent,
+                mip_level,
+                array_layer,
             );
+            offset += extent.size_in_bytes(bytes_per_pixel)
+                * self.mip_depth(mip_level) as u64;
         }
+    }
 
-        self.device.sync_graphics_commands(|command_buffer| {
-            let mut mip_level = 0;
-            let mut offset: u64 = 0;
+    /// Record the first half of moving the whole image to another queue
+    /// family, see `QueueTransfer`.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the
+    ///   transfer's source family
+    /// - every layer and mipmap must be in the transfer's old layout
+    pub unsafe fn release_to(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        transfer: &QueueTransfer,
+    ) {
+        transfer.record_image_release(
+            &self.device,
+            command_buffer,
+            self.image,
+            self.whole_range(),
+        );
+    }
 
-            for extent in mipmap_sizes {
-                self.write_barrier(command_buffer, mip_level);
-                self.copy_buffer_to_image(
-                    command_buffer,
-                    src.raw(),
-                    offset,
-                    extent,
-                    mip_level,
-                );
-                self.read_barrier(command_buffer, mip_level);
+    /// Record the second half of moving the whole image to another queue
+    /// family, see `QueueTransfer`.
+    ///
+    /// # Safety
+    ///
+    /// - the command buffer must be recording for a queue from the
+    ///   transfer's destination family
+    /// - the release must have been submitted, and this submission must
+    ///   wait for it
+    pub unsafe fn acquire_from(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        transfer: &QueueTransfer,
+    ) {
+        transfer.record_image_acquire(
+            &self.device,
+            command_buffer,
+            self.image,
+            self.whole_range(),
+        );
+    }
 
-                mip_level += 1;
-                offset += extent.size_in_bytes(self.bytes_per_pixel);
-            }
+    /// Uploads copy on the transfer queue, then leave the image ready to be
+    /// sampled by the graphics queue.
+    pub fn upload_transfer(&self) -> QueueTransfer {
+        QueueTransfer {
+            src_family: self.device.transfer_queue().family_id,
+            dst_family: self.device.graphics_queue.family_id,
+            src_stage: vk::PipelineStageFlags::TRANSFER,
+            src_access: vk::AccessFlags::TRANSFER_WRITE,
+            dst_stage: vk::PipelineStageFlags::FRAGMENT_SHADER,
+            dst_access: vk::AccessFlags::SHADER_READ,
+            old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        }
+    }
 
-            Ok(())
-        })
+    /// Every layer and mipmap in the image.
+    fn whole_range(&self) -> vk::ImageSubresourceRange {
+        vk::ImageSubresourceRange {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: vk::REMAINING_MIP_LEVELS,
+            base_array_layer: 0,
+            layer_count: self.array_layers,
+        }
+    }
+
+    /// The number of slices in a mipmap level. Only 3D textures have more
+    /// than one, every slice is uploaded at once.
+    fn mip_depth(&self, mip_level: u32) -> u32 {
+        (self.extent.depth >> mip_level).max(1)
     }
 
     /// Transition the image memory layout such that it is an optimal transfer
@@ -162,6 +438,17 @@ impl TextureImage {
         &self,
         command_buffer: vk::CommandBuffer,
         mip_level: u32,
+    ) {
+        self.layer_write_barrier(command_buffer, mip_level, 0)
+    }
+
+    /// Transition a single layer's memory layout such that it is an optimal
+    /// transfer target.
+    unsafe fn layer_write_barrier(
+        &self,
+        command_buffer: vk::CommandBuffer,
+        mip_level: u32,
+        array_layer: u32,
     ) {
         let write_barrier = vk::ImageMemoryBarrier {
             old_layout: vk::ImageLayout::UNDEFINED,
@@ -171,7 +458,7 @@ impl TextureImage {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
                 base_mip_level: mip_level,
                 level_count: 1,
-                base_array_layer: 0,
+                base_array_layer: array_layer,
                 layer_count: 1,
             },
             src_access_mask: vk::AccessFlags::empty(),
@@ -189,40 +476,8 @@ impl TextureImage {
         );
     }
 
-    /// Transition the image memory layout such that is is optimal for reading
-    /// within the fragment shader.
-    unsafe fn read_barrier(
-        &self,
-        command_buffer: vk::CommandBuffer,
-        mip_level: u32,
-    ) {
-        let read_barrier = vk::ImageMemoryBarrier {
-            old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-            new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-            image: self.image,
-            subresource_range: vk::ImageSubresourceRange {
-                aspect_mask: vk::ImageAspectFlags::COLOR,
-                base_mip_level: mip_level,
-                level_count: 1,
-                base_array_layer: 0,
-                layer_count: 1,
-            },
-            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
-            dst_access_mask: vk::AccessFlags::SHADER_READ,
-            ..Default::default()
-        };
-        self.device.logical_device.cmd_pipeline_barrier(
-            command_buffer,
-            vk::PipelineStageFlags::TRANSFER,
-            vk::PipelineStageFlags::FRAGMENT_SHADER,
-            vk::DependencyFlags::empty(),
-            &[],
-            &[],
-            &[read_barrier],
-        );
-    }
-
-    /// Copy a region of the buffer's memory into the image mipmap.
+    /// Copy a region of the buffer's memory into one layer of the image
+    /// mipmap.
     unsafe fn copy_buffer_to_image(
         &self,
         command_buffer: vk::CommandBuffer,
@@ -230,6 +485,7 @@ impl TextureImage {
         offset: u64,
         mipmap_extent: &MipmapExtent,
         mip_level: u32,
+        array_layer: u32,
     ) {
         let region = vk::BufferImageCopy {
             buffer_offset: offset,
@@ -238,14 +494,14 @@ impl TextureImage {
             image_subresource: vk::ImageSubresourceLayers {
                 aspect_mask: vk::ImageAspectFlags::COLOR,
                 mip_level,
-                base_array_layer: 0,
+                base_array_layer: array_layer,
                 layer_count: 1,
             },
             image_offset: vk::Offset3D { x: 0, y: 0, z: 0 },
             image_extent: vk::Extent3D {
                 width: mipmap_extent.width,
                 height: mipmap_extent.height,
-                depth: 1,
+                depth: self.mip_depth(mip_level),
             },
         };
         self.device.logical_device.cmd_copy_buffer_to_image(
@@ -262,12 +518,32 @@ impl Drop for TextureImage {
     fn drop(&mut self) {
         log::trace!("DESTROY TEXTURE");
         unsafe {
+            self.device
+                .forget_vulkan_object(vk::ObjectType::IMAGE_VIEW, &self.view);
             self.device
                 .logical_device
                 .destroy_image_view(self.view, None);
+            self.device
+                .forget_vulkan_object(vk::ObjectType::IMAGE, &self.image);
             self.device.logical_device.destroy_image(self.image, None);
             self.image = vk::Image::null();
-            self.device.free_memory(&self.allocation).unwrap();
+            if self.aliased_memory.is_none() {
+                self.device.free_memory(&self.allocation).unwrap();
+            }
         }
     }
 }
+
+/// The mipmaps written by an upload of one layer.
+fn upload_range(
+    array_layer: u32,
+    mipmap_sizes: &[MipmapExtent],
+) -> vk::ImageSubresourceRange {
+    vk::ImageSubresourceRange {
+        aspect_mask: vk::ImageAspectFlags::COLOR,
+        base_mip_level: 0,
+        level_count: mipmap_sizes.len() as u32,
+        base_array_layer: array_layer,
+        layer_count: 1,
+    }
+}
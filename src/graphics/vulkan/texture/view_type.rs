@@ -0,0 +1,92 @@
+use ash::vk;
+
+/// The number of layers used by each cube in a cube map.
+pub const CUBE_FACES: u32 = 6;
+
+/// Pick the image view type which covers every layer of an image.
+///
+/// Cube compatible images with a multiple of six layers are viewed as cubes,
+/// other images with more than one layer are viewed as arrays.
+pub fn view_type_for(
+    image_create_info: &vk::ImageCreateInfo,
+) -> vk::ImageViewType {
+    let layers = image_create_info.array_layers.max(1);
+    let cube_compatible = image_create_info
+        .flags
+        .contains(vk::ImageCreateFlags::CUBE_COMPATIBLE);
+    match image_create_info.image_type {
+        vk::ImageType::TYPE_1D if layers > 1 => {
+            vk::ImageViewType::TYPE_1D_ARRAY
+        }
+        vk::ImageType::TYPE_1D => vk::ImageViewType::TYPE_1D,
+        vk::ImageType::TYPE_3D => vk::ImageViewType::TYPE_3D,
+        _ if cube_compatible && layers == CUBE_FACES => vk::ImageViewType::CUBE,
+        _ if cube_compatible && layers.is_multiple_of(CUBE_FACES) => {
+            vk::ImageViewType::CUBE_ARRAY
+        }
+        _ if layers > 1 => vk::ImageViewType::TYPE_2D_ARRAY,
+        _ => vk::ImageViewType::TYPE_2D,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_info(
+        image_type: vk::ImageType,
+        array_layers: u32,
+        flags: vk::ImageCreateFlags,
+    ) -> vk::ImageCreateInfo {
+        vk::ImageCreateInfo {
+            image_type,
+            array_layers,
+            flags,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn one_dimensional_images_should_use_1d_views() {
+        let none = vk::ImageCreateFlags::empty();
+        assert_eq!(
+            view_type_for(&create_info(vk::ImageType::TYPE_1D, 1, none)),
+            vk::ImageViewType::TYPE_1D
+        );
+        assert_eq!(
+            view_type_for(&create_info(vk::ImageType::TYPE_1D, 4, none)),
+            vk::ImageViewType::TYPE_1D_ARRAY
+        );
+    }
+
+    #[test]
+    fn cube_compatible_images_should_use_cube_views() {
+        let cube = vk::ImageCreateFlags::CUBE_COMPATIBLE;
+        assert_eq!(
+            view_type_for(&create_info(vk::ImageType::TYPE_2D, 6, cube)),
+            vk::ImageViewType::CUBE
+        );
+        assert_eq!(
+            view_type_for(&create_info(vk::ImageType::TYPE_2D, 12, cube)),
+            vk::ImageViewType::CUBE_ARRAY
+        );
+        // layers which don't make whole cubes are a plain array
+        assert_eq!(
+            view_type_for(&create_info(vk::ImageType::TYPE_2D, 7, cube)),
+            vk::ImageViewType::TYPE_2D_ARRAY
+        );
+    }
+
+    #[test]
+    fn two_dimensional_images_should_use_2d_views() {
+        let none = vk::ImageCreateFlags::empty();
+        assert_eq!(
+            view_type_for(&create_info(vk::ImageType::TYPE_2D, 1, none)),
+            vk::ImageViewType::TYPE_2D
+        );
+        assert_eq!(
+            view_type_for(&create_info(vk::ImageType::TYPE_2D, 6, none)),
+            vk::ImageViewType::TYPE_2D_ARRAY
+        );
+    }
+}
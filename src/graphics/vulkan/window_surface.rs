@@ -63,4 +63,13 @@ pub trait WindowSurface {
         &self,
         physical_device: &vk::PhysicalDevice,
     ) -> Result<vk::SurfaceCapabilitiesKHR>;
+
+    /// The Win32 monitor which currently contains the window.
+    ///
+    /// Full screen exclusive presentation can only be controlled by the
+    /// application when the monitor is known. Non-Windows platforms return
+    /// None.
+    fn win32_monitor(&self) -> Option<vk::HMONITOR> {
+        None
+    }
 }
@@ -0,0 +1,289 @@
+use super::{
+    adaptive_spacing, AxesStyle, CheckerboardStyle, GridStyle, GuideLayer,
+    RulerStyle,
+};
+
+use crate::{
+    camera::OrthoCamera,
+    geometry::Rect,
+    graphics::{
+        layer::{Batch, Layer},
+        vertex::Vertex2d,
+    },
+};
+
+/// Never draw more than this many lines or cells along an axis, no matter how
+/// the styles are configured.
+const MAX_DIVISIONS: f32 = 512.0;
+
+impl Default for GridStyle {
+    fn default() -> Self {
+        Self {
+            min_pixel_spacing: 16.0,
+            major_every: 5,
+            minor_color: [1.0, 1.0, 1.0, 0.05],
+            major_color: [1.0, 1.0, 1.0, 0.15],
+            line_width: 1.0,
+        }
+    }
+}
+
+impl Default for CheckerboardStyle {
+    fn default() -> Self {
+        Self {
+            colors: [[0.2, 0.2, 0.2, 1.0], [0.25, 0.25, 0.25, 1.0]],
+        }
+    }
+}
+
+impl Default for AxesStyle {
+    fn default() -> Self {
+        Self {
+            x_color: [0.9, 0.3, 0.3, 0.8],
+            y_color: [0.3, 0.9, 0.3, 0.8],
+            line_width: 2.0,
+        }
+    }
+}
+
+impl Default for RulerStyle {
+    fn default() -> Self {
+        Self {
+            background: [0.1, 0.1, 0.1, 0.9],
+            tick_color: [0.8, 0.8, 0.8, 1.0],
+            thickness: 20.0,
+        }
+    }
+}
+
+impl GuideLayer {
+    /// A guide layer with a default grid and axes.
+    pub fn grid_and_axes() -> Self {
+        Self {
+            grid: Some(GridStyle::default()),
+            axes: Some(AxesStyle::default()),
+            ..Default::default()
+        }
+    }
+
+    /// Replace a layer's contents with guides for the camera's current view.
+    ///
+    /// `window_size` is the framebuffer size in pixels and is used to keep
+    /// line widths and spacing consistent in screen space.
+    pub fn fill_layer(
+        &self,
+        layer: &mut Layer,
+        camera: &OrthoCamera,
+        window_size: (u32, u32),
+    ) {
+        layer.clear();
+        layer.set_projection(camera.as_matrix());
+        layer.push_batch(self.build_batch(camera, window_size));
+    }
+
+    /// Build a single batch containing every enabled guide.
+    pub fn build_batch(
+        &self,
+        camera: &OrthoCamera,
+        window_size: (u32, u32),
+    ) -> Batch {
+        let view = camera.bounds();
+        let pixel = camera.viewport_height() / window_size.1.max(1) as f32;
+        let spacing = self.minor_spacing(pixel, &view);
+        let major = spacing * self.major_every() as f32;
+
+        let mut vertices = vec![];
+        if let Some(style) = &self.checkerboard {
+            push_checkerboard(&mut vertices, style, &view, major);
+        }
+        if let Some(style) = &self.grid {
+            push_grid(&mut vertices, style, &view, spacing, pixel);
+        }
+        if let Some(style) = &self.axes {
+            let width = style.line_width * pixel;
+            push_rect(
+                &mut vertices,
+                vertical(0.0, width, &view),
+                style.y_color,
+            );
+            push_rect(
+                &mut vertices,
+                horizontal(0.0, width, &view),
+                style.x_color,
+            );
+        }
+        if let Some(style) = &self.rulers {
+            push_rulers(&mut vertices, style, &view, spacing, major, pixel);
+        }
+
+        Batch {
+            vertices,
+            ..Default::default()
+        }
+    }
+
+    /// The distance between minor grid lines in world units.
+    fn minor_spacing(&self, pixel: f32, view: &Rect<f32>) -> f32 {
+        let min_pixels = self
+            .grid
+            .map(|grid| grid.min_pixel_spacing)
+            .unwrap_or(GridStyle::default().min_pixel_spacing);
+        let extent = view.width().max(view.height());
+        adaptive_spacing((min_pixels * pixel).max(extent / MAX_DIVISIONS))
+    }
+
+    fn major_every(&self) -> u32 {
+        self.grid
+            .map(|grid| grid.major_every)
+            .unwrap_or(GridStyle::default().major_every)
+            .max(1)
+    }
+}
+
+fn push_grid(
+    vertices: &mut Vec<Vertex2d>,
+    style: &GridStyle,
+    view: &Rect<f32>,
+    spacing: f32,
+    pixel: f32,
+) {
+    let width = style.line_width * pixel;
+    let major_every = style.major_every.max(1) as i64;
+    let color = |index: i64| {
+        if index % major_every == 0 {
+            style.major_color
+        } else {
+            style.minor_color
+        }
+    };
+    for index in line_indices(view.left, view.right, spacing) {
+        let x = index as f32 * spacing;
+        push_rect(vertices, vertical(x, width, view), color(index));
+    }
+    for index in line_indices(view.bottom, view.top, spacing) {
+        let y = index as f32 * spacing;
+        push_rect(vertices, horizontal(y, width, view), color(index));
+    }
+}
+
+fn push_checkerboard(
+    vertices: &mut Vec<Vertex2d>,
+    style: &CheckerboardStyle,
+    view: &Rect<f32>,
+    size: f32,
+) {
+    let columns = line_indices(view.left - size, view.right, size);
+    for row in line_indices(view.bottom - size, view.top, size) {
+        for column in columns.clone() {
+            let left = column as f32 * size;
+            let bottom = row as f32 * size;
+            let cell = Rect {
+                left,
+                right: left + size,
+                bottom,
+                top: bottom + size,
+            };
+            let color = style.colors[(row + column).rem_euclid(2) as usize];
+            push_rect(vertices, cell, color);
+        }
+    }
+}
+
+fn push_rulers(
+    vertices: &mut Vec<Vertex2d>,
+    style: &RulerStyle,
+    view: &Rect<f32>,
+    spacing: f32,
+    major: f32,
+    pixel: f32,
+) {
+    let thickness = style.thickness * pixel;
+    let tick_width = pixel;
+    let bottom_strip = Rect {
+        top: view.bottom + thickness,
+        ..*view
+    };
+    let left_strip = Rect {
+        right: view.left + thickness,
+        ..*view
+    };
+    push_rect(vertices, bottom_strip, style.background);
+    push_rect(vertices, left_strip, style.background);
+
+    let tick_length = |value: f32| {
+        let is_major =
+            ((value / major).round() * major - value).abs() < spacing * 0.01;
+        if is_major {
+            thickness
+        } else {
+            thickness * 0.4
+        }
+    };
+    for index in line_indices(view.left + thickness, view.right, spacing) {
+        let x = index as f32 * spacing;
+        let tick = Rect {
+            left: x - tick_width / 2.0,
+            right: x + tick_width / 2.0,
+            bottom: view.bottom,
+            top: view.bottom + tick_length(x),
+        };
+        push_rect(vertices, tick, style.tick_color);
+    }
+    for index in line_indices(view.bottom + thickness, view.top, spacing) {
+        let y = index as f32 * spacing;
+        let tick = Rect {
+            left: view.left,
+            right: view.left + tick_length(y),
+            bottom: y - tick_width / 2.0,
+            top: y + tick_width / 2.0,
+        };
+        push_rect(vertices, tick, style.tick_color);
+    }
+}
+
+/// The indices of every multiple of `spacing` within `[min, max]`.
+fn line_indices(
+    min: f32,
+    max: f32,
+    spacing: f32,
+) -> std::ops::RangeInclusive<i64> {
+    (min / spacing).ceil() as i64..=(max / spacing).floor() as i64
+}
+
+/// A vertical line spanning the view.
+fn vertical(x: f32, width: f32, view: &Rect<f32>) -> Rect<f32> {
+    Rect {
+        left: x - width / 2.0,
+        right: x + width / 2.0,
+        ..*view
+    }
+}
+
+/// A horizontal line spanning the view.
+fn horizontal(y: f32, width: f32, view: &Rect<f32>) -> Rect<f32> {
+    Rect {
+        bottom: y - width / 2.0,
+        top: y + width / 2.0,
+        ..*view
+    }
+}
+
+fn push_rect(vertices: &mut Vec<Vertex2d>, rect: Rect<f32>, rgba: [f32; 4]) {
+    let vertex = |x: f32, y: f32| Vertex2d {
+        pos: [x, y],
+        rgba,
+        ..Default::default()
+    };
+    let top_left = vertex(rect.left, rect.top);
+    let top_right = vertex(rect.right, rect.top);
+    let bottom_right = vertex(rect.right, rect.bottom);
+    let bottom_left = vertex(rect.left, rect.bottom);
+    vertices.extend_from_slice(&[
+        top_left,
+        top_right,
+        bottom_right,
+        top_left,
+        bottom_right,
+        bottom_left,
+    ]);
+}
@@ -0,0 +1,64 @@
+//! Editor-style guides: an infinite grid, checkerboard, axes, and rulers.
+//!
+//! Guides are rebuilt from the camera each frame. Spacing adapts to the
+//! camera's zoom so grid lines stay a comfortable number of pixels apart at
+//! any scale, and line widths are specified in pixels.
+
+mod guide_layer;
+mod spacing;
+
+pub use self::spacing::adaptive_spacing;
+
+/// A world-space grid of minor and major lines.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridStyle {
+    /// The smallest distance, in pixels, allowed between minor lines.
+    pub min_pixel_spacing: f32,
+
+    /// The number of minor cells between each major line.
+    pub major_every: u32,
+
+    pub minor_color: [f32; 4],
+    pub major_color: [f32; 4],
+
+    /// Line width in pixels.
+    pub line_width: f32,
+}
+
+/// Alternating filled cells, aligned with the grid's major spacing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CheckerboardStyle {
+    pub colors: [[f32; 4]; 2],
+}
+
+/// Lines along the world's x and y axes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AxesStyle {
+    pub x_color: [f32; 4],
+    pub y_color: [f32; 4],
+
+    /// Line width in pixels.
+    pub line_width: f32,
+}
+
+/// Tick marks along the bottom and left edges of the screen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RulerStyle {
+    pub background: [f32; 4],
+    pub tick_color: [f32; 4],
+
+    /// The thickness of the ruler strips in pixels.
+    pub thickness: f32,
+}
+
+/// A dedicated layer type which draws guides behind or above a scene.
+///
+/// Every guide is optional. Use `fill_layer` each frame (or whenever the
+/// camera changes) to rebuild the guides for the current view.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct GuideLayer {
+    pub checkerboard: Option<CheckerboardStyle>,
+    pub grid: Option<GridStyle>,
+    pub axes: Option<AxesStyle>,
+    pub rulers: Option<RulerStyle>,
+}
@@ -0,0 +1,36 @@
+/// Pick a "nice" spacing (1, 2, or 5 times a power of ten) which is at least
+/// `min_spacing`.
+///
+/// Returns `min_spacing` unchanged if it isn't a positive, finite number.
+pub fn adaptive_spacing(min_spacing: f32) -> f32 {
+    if !(min_spacing.is_finite() && min_spacing > 0.0) {
+        return min_spacing;
+    }
+    let magnitude = 10f32.powf(min_spacing.log10().floor());
+    for step in &[1.0, 2.0, 5.0, 10.0] {
+        let spacing = magnitude * step;
+        // allow for rounding error in powf
+        if spacing >= min_spacing * 0.9999 {
+            return spacing;
+        }
+    }
+    magnitude * 10.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn adaptive_spacing_should_round_up_to_nice_numbers() {
+        assert_relative_eq!(adaptive_spacing(0.7), 1.0);
+        assert_relative_eq!(adaptive_spacing(1.0), 1.0);
+        assert_relative_eq!(adaptive_spacing(1.5), 2.0);
+        assert_relative_eq!(adaptive_spacing(3.0), 5.0);
+        assert_relative_eq!(adaptive_spacing(7.0), 10.0);
+        assert_relative_eq!(adaptive_spacing(0.013), 0.02);
+        assert_relative_eq!(adaptive_spacing(420.0), 500.0);
+    }
+}
@@ -2,8 +2,43 @@
 
 pub mod camera;
 pub mod geometry;
+pub mod gizmo;
 pub mod graphics;
+pub mod guides;
+pub mod metrics;
+pub mod noise;
+pub mod packing;
+pub mod params;
+pub mod profiling;
+pub mod text;
+pub mod tilemap;
 
+#[cfg(feature = "audio")]
+pub mod audio;
+
+#[cfg(feature = "control")]
+pub mod control;
+
+#[cfg(feature = "svg")]
+pub mod svg;
+
+#[cfg(not(target_os = "android"))]
+pub mod replay;
+
+#[cfg(not(target_os = "android"))]
+pub mod testing;
+
+#[cfg(target_os = "android")]
+mod android_window;
+
+#[cfg(not(target_os = "android"))]
 mod glfw_window;
 
-pub use self::glfw_window::{EventReceiver, GlfwWindow};
+#[cfg(not(target_os = "android"))]
+pub use self::glfw_window::{
+    EventHandler, EventHandlerId, EventReceiver, GlfwWindow, MonitorInfo,
+    VideoMode,
+};
+
+#[cfg(target_os = "android")]
+pub use self::android_window::AndroidWindow;
@@ -0,0 +1,149 @@
+use super::{ConsoleMetrics, FrameMetrics, MemoryEvent, Metrics};
+
+use crate::graphics::report::RenderReport;
+
+use std::time::Duration;
+
+impl ConsoleMetrics {
+    /// Create a sink which logs a summary every `interval` frames.
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            render: None,
+            frame_time: Duration::default(),
+            slowest_frame: Duration::default(),
+            allocations: 0,
+            allocated_bytes: 0,
+            peak_allocated_bytes: 0,
+        }
+    }
+
+    /// Bytes of device memory currently allocated.
+    pub fn allocated_bytes(&self) -> u64 {
+        self.allocated_bytes
+    }
+
+    /// The most device memory allocated at once.
+    pub fn peak_allocated_bytes(&self) -> u64 {
+        self.peak_allocated_bytes
+    }
+
+    /// Add a frame to the totals. Returns the summary once `interval` frames
+    /// have been recorded, then starts counting again.
+    pub fn summarize(&mut self, frame: &FrameMetrics) -> Option<String> {
+        let render = self.render.get_or_insert(RenderReport {
+            frame_number: frame.render.frame_number,
+            ..Default::default()
+        });
+        render.accumulate(&frame.render);
+        self.frame_time += frame.frame_time;
+        self.slowest_frame = self.slowest_frame.max(frame.frame_time);
+
+        if render.frames < self.interval {
+            return None;
+        }
+
+        let render = self.render.take().unwrap();
+        let mean_ms =
+            self.frame_time.as_secs_f64() * 1000.0 / render.frames as f64;
+        let summary = format!(
+            "{:.2} ms per frame ({:.2} ms slowest), {} allocations using \
+             {} bytes, {}",
+            mean_ms,
+            self.slowest_frame.as_secs_f64() * 1000.0,
+            self.allocations,
+            self.allocated_bytes,
+            render,
+        );
+        self.frame_time = Duration::default();
+        self.slowest_frame = Duration::default();
+        Some(summary)
+    }
+}
+
+impl Default for ConsoleMetrics {
+    /// Log a summary about once a second at 60 frames per second.
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+impl Metrics for ConsoleMetrics {
+    fn record_frame(&mut self, frame: &FrameMetrics) {
+        if let Some(summary) = self.summarize(frame) {
+            log::info!("{}", summary);
+        }
+    }
+
+    fn record_memory(&mut self, event: &MemoryEvent) {
+        match *event {
+            MemoryEvent::Allocated { byte_size, .. } => {
+                self.allocations += 1;
+                self.allocated_bytes += byte_size;
+                self.peak_allocated_bytes =
+                    self.peak_allocated_bytes.max(self.allocated_bytes);
+            }
+            MemoryEvent::Freed { byte_size, .. } => {
+                self.allocations = self.allocations.saturating_sub(1);
+                self.allocated_bytes =
+                    self.allocated_bytes.saturating_sub(byte_size);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frame(frame_number: u64, millis: u64) -> FrameMetrics {
+        FrameMetrics {
+            frame_number,
+            frame_time: Duration::from_millis(millis),
+            render: RenderReport {
+                draw_calls: 2,
+                ..RenderReport::for_frame(frame_number)
+            },
+        }
+    }
+
+    #[test]
+    fn summarize_should_average_every_interval() {
+        let mut metrics = ConsoleMetrics::new(2);
+
+        assert_eq!(metrics.summarize(&frame(0, 10)), None);
+        let summary = metrics.summarize(&frame(1, 20)).unwrap();
+
+        assert!(
+            summary.starts_with("15.00 ms per frame (20.00 ms slowest)"),
+            "{}",
+            summary
+        );
+        assert!(
+            summary.contains("frames 0..2: 2.0 draw calls"),
+            "{}",
+            summary
+        );
+        assert_eq!(metrics.summarize(&frame(2, 10)), None);
+    }
+
+    #[test]
+    fn memory_events_should_track_the_peak() {
+        let mut metrics = ConsoleMetrics::default();
+        let allocated = MemoryEvent::Allocated {
+            memory_type_index: 1,
+            byte_size: 256,
+        };
+        let freed = MemoryEvent::Freed {
+            memory_type_index: 1,
+            byte_size: 256,
+        };
+
+        metrics.record_memory(&allocated);
+        metrics.record_memory(&allocated);
+        metrics.record_memory(&freed);
+
+        assert_eq!(metrics.allocated_bytes(), 256);
+        assert_eq!(metrics.peak_allocated_bytes(), 512);
+    }
+}
@@ -0,0 +1,81 @@
+//! Performance counters which can be sent anywhere.
+//!
+//! # Big Idea
+//!
+//! `Metrics` is a sink for the numbers the library measures while it runs:
+//! frame times, the work recorded for each frame, and every device memory
+//! allocation. Implement it to push the numbers into Prometheus, a profiler,
+//! or a custom log. `ConsoleMetrics` is the default, it logs averages every
+//! few frames.
+//!
+//! Install a sink with `Graphics::set_metrics`. The same sink receives
+//! events from the device allocator, so memory usage shows up as it
+//! changes rather than only when the allocator is destroyed.
+
+mod console_metrics;
+
+use crate::graphics::report::RenderReport;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Receives performance counters as they're measured.
+///
+/// Every method has an empty default so sinks only implement the events
+/// they care about. Events can arrive from any thread which allocates
+/// memory.
+pub trait Metrics: Send {
+    /// Called once for each frame rendered by `Graphics::render`.
+    fn record_frame(&mut self, _frame: &FrameMetrics) {}
+
+    /// Called whenever device memory is allocated or freed.
+    fn record_memory(&mut self, _event: &MemoryEvent) {}
+}
+
+/// A metrics sink shared between the graphics and the device.
+pub type SharedMetrics = Arc<Mutex<dyn Metrics>>;
+
+/// Counters for a single rendered frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FrameMetrics {
+    pub frame_number: u64,
+
+    /// The time since the previous frame finished. Zero for the first frame
+    /// after a sink is installed.
+    pub frame_time: Duration,
+
+    /// The work recorded for the frame: draw calls, uploaded bytes, etc.
+    pub render: RenderReport,
+}
+
+/// A change in device memory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MemoryEvent {
+    Allocated {
+        memory_type_index: u32,
+        byte_size: u64,
+    },
+    Freed {
+        memory_type_index: u32,
+        byte_size: u64,
+    },
+}
+
+/// The default metrics sink, it logs a summary at the info level every
+/// `interval` frames.
+#[derive(Debug, Clone)]
+pub struct ConsoleMetrics {
+    interval: u64,
+
+    /// The totals for frames since the last summary.
+    render: Option<RenderReport>,
+    frame_time: Duration,
+    slowest_frame: Duration,
+
+    /// Device memory currently allocated.
+    allocations: u64,
+    allocated_bytes: u64,
+    peak_allocated_bytes: u64,
+}
@@ -0,0 +1,83 @@
+use super::{Fbm, Noise2d};
+
+impl<N: Noise2d> Fbm<N> {
+    /// Layer octaves of noise, doubling the frequency and halving the
+    /// amplitude each time.
+    pub fn new(noise: N, octaves: u32) -> Self {
+        Self {
+            noise,
+            octaves,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+
+    pub fn with_lacunarity(self, lacunarity: f32) -> Self {
+        Self { lacunarity, ..self }
+    }
+
+    pub fn with_gain(self, gain: f32) -> Self {
+        Self { gain, ..self }
+    }
+}
+
+impl<N: Noise2d> Noise2d for Fbm<N> {
+    /// The octaves are normalized by their total amplitude, so the result
+    /// stays in the same range as the underlying noise.
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let mut total = 0.0;
+        let mut amplitude = 1.0;
+        let mut amplitude_sum = 0.0;
+        let mut frequency = 1.0;
+        for octave in 0..self.octaves {
+            // offset each octave so their lattice points don't line up
+            let offset = octave as f32 * 17.31;
+            total += amplitude
+                * self
+                    .noise
+                    .sample(x * frequency + offset, y * frequency - offset);
+            amplitude_sum += amplitude;
+            amplitude *= self.gain;
+            frequency *= self.lacunarity;
+        }
+        if amplitude_sum > 0.0 {
+            total / amplitude_sum
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<N: Noise2d + ?Sized> Noise2d for Box<N> {
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        (**self).sample(x, y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::noise::PerlinNoise;
+
+    #[test]
+    fn one_octave_should_match_the_noise() {
+        let noise = PerlinNoise::new(3);
+        let fbm = Fbm::new(PerlinNoise::new(3), 1);
+        assert_eq!(fbm.sample(0.3, 0.7), noise.sample(0.3, 0.7));
+    }
+
+    #[test]
+    fn no_octaves_should_be_zero() {
+        let fbm = Fbm::new(PerlinNoise::new(3), 0);
+        assert_eq!(fbm.sample(0.3, 0.7), 0.0);
+    }
+
+    #[test]
+    fn samples_should_stay_in_range() {
+        let fbm = Fbm::new(PerlinNoise::new(8), 6).with_gain(0.7);
+        for i in 0..500 {
+            let value = fbm.sample(i as f32 * 0.093, i as f32 * 0.051);
+            assert!((-1.0..=1.0).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Seeded random numbers and coherent noise for sketches.
+//!
+//! # Big Idea
+//!
+//! Everything here is deterministic. The same seed always produces the same
+//! random numbers and the same noise, on every platform, so a sketch can be
+//! reproduced exactly from its seed. Pair an `Rng` with the per-frame seeds
+//! from `replay` to make interactive pieces repeatable too.
+//!
+//! Noise functions implement `Noise2d` and return values roughly between -1
+//! and 1. Any of them can be layered into fractal noise with `Fbm`, which is
+//! itself a `Noise2d`. Noise can also be baked into a texture with
+//! `texture_generator::Generator::Noise`.
+
+mod fbm;
+mod noise_kind;
+mod perlin;
+mod permutation;
+mod rng;
+mod simplex;
+mod value;
+
+/// A two dimensional noise function.
+pub trait Noise2d {
+    /// Sample the noise. Features are about one unit apart and values are
+    /// roughly between -1 and 1.
+    fn sample(&self, x: f32, y: f32) -> f32;
+}
+
+/// A small, fast, deterministic random number generator (SplitMix64).
+///
+/// It isn't suitable for cryptography, but it's plenty for placing shapes
+/// and picking colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+/// Random values at each lattice point, smoothly interpolated. Blocky
+/// compared to gradient noise, but cheap.
+#[derive(Debug, Clone)]
+pub struct ValueNoise {
+    permutation: Permutation,
+}
+
+/// Ken Perlin's improved gradient noise.
+#[derive(Debug, Clone)]
+pub struct PerlinNoise {
+    permutation: Permutation,
+}
+
+/// Gradient noise on a triangular grid. It has fewer directional artifacts
+/// than Perlin noise.
+#[derive(Debug, Clone)]
+pub struct SimplexNoise {
+    permutation: Permutation,
+}
+
+/// Fractal Brownian motion: several octaves of a noise function summed with
+/// rising frequency and falling amplitude.
+#[derive(Debug, Clone)]
+pub struct Fbm<N> {
+    /// The noise sampled by every octave.
+    pub noise: N,
+
+    /// The number of layers of noise.
+    pub octaves: u32,
+
+    /// How much the frequency is multiplied by for each octave.
+    pub lacunarity: f32,
+
+    /// How much the amplitude is multiplied by for each octave.
+    pub gain: f32,
+}
+
+/// The kinds of noise which can be picked at runtime, like when generating
+/// textures.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NoiseKind {
+    Value,
+    Perlin,
+    Simplex,
+}
+
+/// A shuffled table of the bytes 0 to 255, repeated twice so lookups never
+/// need to wrap. Hashes lattice points into pseudo-random values.
+#[derive(Clone)]
+struct Permutation {
+    table: [u8; 512],
+}
@@ -0,0 +1,12 @@
+use super::{Noise2d, NoiseKind, PerlinNoise, SimplexNoise, ValueNoise};
+
+impl NoiseKind {
+    /// Create the noise function for this kind.
+    pub fn build(self, seed: u64) -> Box<dyn Noise2d> {
+        match self {
+            NoiseKind::Value => Box::new(ValueNoise::new(seed)),
+            NoiseKind::Perlin => Box::new(PerlinNoise::new(seed)),
+            NoiseKind::Simplex => Box::new(SimplexNoise::new(seed)),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use super::{
+    permutation::{cell, fade, lerp},
+    Noise2d, PerlinNoise, Permutation,
+};
+
+/// The gradients at each lattice point. The diagonals are shortened to unit
+/// length so no direction is favored.
+const GRADIENTS: [(f32, f32); 8] = [
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, 1.0),
+    (0.0, -1.0),
+    (
+        std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    (
+        -std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    (
+        std::f32::consts::FRAC_1_SQRT_2,
+        -std::f32::consts::FRAC_1_SQRT_2,
+    ),
+    (
+        -std::f32::consts::FRAC_1_SQRT_2,
+        -std::f32::consts::FRAC_1_SQRT_2,
+    ),
+];
+
+/// 2d gradient noise peaks at sqrt(0.5), so scale it up to fill -1..1.
+const SCALE: f32 = std::f32::consts::SQRT_2;
+
+impl PerlinNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: Permutation::new(seed),
+        }
+    }
+
+    /// The dot product of a lattice point's gradient with the offset from
+    /// that point.
+    fn influence(&self, x: i32, y: i32, dx: f32, dy: f32) -> f32 {
+        let (gx, gy) = GRADIENTS[(self.permutation.hash(x, y) & 7) as usize];
+        gx * dx + gy * dy
+    }
+}
+
+impl Noise2d for PerlinNoise {
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let (x0, fx) = cell(x);
+        let (y0, fy) = cell(y);
+        let (u, v) = (fade(fx), fade(fy));
+
+        let bottom = lerp(
+            self.influence(x0, y0, fx, fy),
+            self.influence(x0 + 1, y0, fx - 1.0, fy),
+            u,
+        );
+        let top = lerp(
+            self.influence(x0, y0 + 1, fx, fy - 1.0),
+            self.influence(x0 + 1, y0 + 1, fx - 1.0, fy - 1.0),
+            u,
+        );
+        (lerp(bottom, top, v) * SCALE).clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lattice_points_should_be_zero() {
+        let noise = PerlinNoise::new(2);
+        assert_eq!(noise.sample(5.0, -7.0), 0.0);
+    }
+
+    #[test]
+    fn seeds_should_change_the_noise() {
+        let a = PerlinNoise::new(1);
+        let b = PerlinNoise::new(2);
+        let differs = (0..64).any(|i| {
+            let x = i as f32 * 0.31 + 0.5;
+            a.sample(x, 0.25) != b.sample(x, 0.25)
+        });
+        assert!(differs);
+    }
+
+    #[test]
+    fn samples_should_stay_in_range() {
+        let noise = PerlinNoise::new(11);
+        for i in 0..500 {
+            let value = noise.sample(i as f32 * 0.173, i as f32 * 0.029);
+            assert!((-1.0..=1.0).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use super::{Permutation, Rng};
+
+use std::fmt;
+
+impl Permutation {
+    /// Shuffle the table with a seed.
+    pub fn new(seed: u64) -> Self {
+        let mut bytes: Vec<u8> = (0..=255).collect();
+        Rng::new(seed).shuffle(&mut bytes);
+        let mut table = [0; 512];
+        for (index, entry) in table.iter_mut().enumerate() {
+            *entry = bytes[index % 256];
+        }
+        Self { table }
+    }
+
+    /// A pseudo-random byte for a lattice point.
+    pub fn hash(&self, x: i32, y: i32) -> u8 {
+        let x = (x & 255) as usize;
+        let y = (y & 255) as usize;
+        self.table[self.table[x] as usize + y]
+    }
+}
+
+impl fmt::Debug for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Permutation").finish()
+    }
+}
+
+/// The lattice cell containing a coordinate and the offset into it.
+pub(super) fn cell(value: f32) -> (i32, f32) {
+    let floor = value.floor();
+    (floor as i32, value - floor)
+}
+
+/// Ken Perlin's quintic ease curve, which has zero first and second
+/// derivatives at 0 and 1 so cells join smoothly.
+pub(super) fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+pub(super) fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_table_should_hold_every_byte_twice() {
+        let permutation = Permutation::new(12);
+        let mut counts = [0; 256];
+        for byte in permutation.table.iter() {
+            counts[*byte as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| *count == 2));
+        assert_eq!(permutation.table[..256], permutation.table[256..]);
+    }
+
+    #[test]
+    fn cell_should_floor_negative_coordinates() {
+        assert_eq!(cell(-0.25), (-1, 0.75));
+        assert_eq!(cell(2.5), (2, 0.5));
+    }
+}
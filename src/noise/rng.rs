@@ -0,0 +1,112 @@
+use super::Rng;
+
+impl Rng {
+    /// Create a generator. Every seed, including zero, gives a different
+    /// well mixed sequence.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// The next 32 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// A uniformly distributed value in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        // the top 24 bits fill an f32's mantissa exactly
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A uniformly distributed value in `min..max`.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// A uniformly distributed value in `0..bound`. The bound must not be
+    /// zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// True with the given probability.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// A random element of the slice, or None when it's empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.below(items.len()))
+        }
+    }
+
+    /// Put the slice in a random order.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// A new generator seeded from this one, for handing independent
+    /// sequences to different parts of a sketch.
+    pub fn fork(&mut self) -> Self {
+        Self::new(self.next_u64())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sequences_should_depend_only_on_the_seed() {
+        let first: Vec<u64> = {
+            let mut rng = Rng::new(7);
+            (0..4).map(|_| rng.next_u64()).collect()
+        };
+        let mut rng = Rng::new(7);
+        assert_eq!((0..4).map(|_| rng.next_u64()).collect::<Vec<_>>(), first);
+        assert_ne!(Rng::new(8).next_u64(), first[0]);
+    }
+
+    #[test]
+    fn floats_should_stay_in_range() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let unit = rng.next_f32();
+            assert!((0.0..1.0).contains(&unit));
+            let ranged = rng.range(-3.0, 5.0);
+            assert!((-3.0..5.0).contains(&ranged));
+        }
+    }
+
+    #[test]
+    fn shuffle_should_keep_every_element() {
+        let mut rng = Rng::new(3);
+        let mut items: Vec<u32> = (0..32).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..32).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn choose_should_return_none_for_empty_slices() {
+        let mut rng = Rng::new(0);
+        let empty: [u8; 0] = [];
+        assert_eq!(rng.choose(&empty), None);
+        assert_eq!(rng.choose(&[5]), Some(&5));
+    }
+}
@@ -0,0 +1,93 @@
+use super::{Noise2d, Permutation, SimplexNoise};
+
+/// Skews the input space onto the grid of equilateral triangles.
+const SKEW: f32 = 0.366_025_4; // (sqrt(3) - 1) / 2
+
+/// Unskews a grid cell back into the input space.
+const UNSKEW: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+
+/// Scales the summed corner contributions to fill -1..1.
+const SCALE: f32 = 70.0;
+
+const GRADIENTS: [(f32, f32); 8] = [
+    (1.0, 1.0),
+    (-1.0, 1.0),
+    (1.0, -1.0),
+    (-1.0, -1.0),
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, 1.0),
+    (0.0, -1.0),
+];
+
+impl SimplexNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: Permutation::new(seed),
+        }
+    }
+
+    /// The contribution from one corner of the triangle, which falls to zero
+    /// before reaching the neighboring triangles.
+    fn corner(&self, x: i32, y: i32, dx: f32, dy: f32) -> f32 {
+        let falloff = 0.5 - dx * dx - dy * dy;
+        if falloff <= 0.0 {
+            return 0.0;
+        }
+        let (gx, gy) = GRADIENTS[(self.permutation.hash(x, y) & 7) as usize];
+        let falloff = falloff * falloff;
+        falloff * falloff * (gx * dx + gy * dy)
+    }
+}
+
+impl Noise2d for SimplexNoise {
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let skew = (x + y) * SKEW;
+        let i = (x + skew).floor();
+        let j = (y + skew).floor();
+        let unskew = (i + j) * UNSKEW;
+        let x0 = x - (i - unskew);
+        let y0 = y - (j - unskew);
+
+        // pick the lower or upper triangle of the skewed cell
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+        let x1 = x0 - i1 as f32 + UNSKEW;
+        let y1 = y0 - j1 as f32 + UNSKEW;
+        let x2 = x0 - 1.0 + 2.0 * UNSKEW;
+        let y2 = y0 - 1.0 + 2.0 * UNSKEW;
+
+        let (i, j) = (i as i32, j as i32);
+        let total = self.corner(i, j, x0, y0)
+            + self.corner(i + i1, j + j1, x1, y1)
+            + self.corner(i + 1, j + 1, x2, y2);
+        (total * SCALE).clamp(-1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn samples_should_be_deterministic() {
+        let a = SimplexNoise::new(6);
+        let b = SimplexNoise::new(6);
+        for i in 0..32 {
+            let x = i as f32 * 0.41;
+            assert_eq!(a.sample(x, -x), b.sample(x, -x));
+        }
+    }
+
+    #[test]
+    fn samples_should_stay_in_range() {
+        let noise = SimplexNoise::new(5);
+        let mut largest: f32 = 0.0;
+        for i in 0..2000 {
+            let value = noise.sample(i as f32 * 0.0731, i as f32 * 0.0137);
+            assert!((-1.0..=1.0).contains(&value));
+            largest = largest.max(value.abs());
+        }
+        assert!(largest > 0.25);
+    }
+}
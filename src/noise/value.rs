@@ -0,0 +1,50 @@
+use super::{
+    permutation::{cell, fade, lerp},
+    Noise2d, Permutation, ValueNoise,
+};
+
+impl ValueNoise {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: Permutation::new(seed),
+        }
+    }
+
+    /// The random value at a lattice point, between -1 and 1.
+    fn lattice(&self, x: i32, y: i32) -> f32 {
+        self.permutation.hash(x, y) as f32 / 127.5 - 1.0
+    }
+}
+
+impl Noise2d for ValueNoise {
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let (x0, fx) = cell(x);
+        let (y0, fy) = cell(y);
+        let (u, v) = (fade(fx), fade(fy));
+
+        let bottom = lerp(self.lattice(x0, y0), self.lattice(x0 + 1, y0), u);
+        let top =
+            lerp(self.lattice(x0, y0 + 1), self.lattice(x0 + 1, y0 + 1), u);
+        lerp(bottom, top, v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lattice_points_should_hold_their_random_value() {
+        let noise = ValueNoise::new(4);
+        assert_eq!(noise.sample(3.0, -2.0), noise.lattice(3, -2));
+    }
+
+    #[test]
+    fn samples_should_stay_in_range() {
+        let noise = ValueNoise::new(9);
+        for i in 0..500 {
+            let value = noise.sample(i as f32 * 0.137, i as f32 * -0.071);
+            assert!((-1.0..=1.0).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Pack many small images into a single sheet without edge bleeding.
+//!
+//! Sampling a packed sub-image with linear filtering reads texels from just
+//! outside of its bounds, and every mip level doubles that distance. To keep
+//! neighbors from bleeding into each other, each sub-image is surrounded by a
+//! gutter filled with copies of its edge pixels (extrusion), and UVs are inset
+//! by half a texel so the sampler never reaches past the edge texel centers.
+//!
+//! A gutter of `g` pixels protects roughly `log2(g) + 1` mip levels.
+
+mod sheet;
+mod shelf;
+mod uv;
+
+pub use self::sheet::extrude;
+
+use image::RgbaImage;
+
+/// The location of a packed sub-image within a sheet, in pixels.
+///
+/// The rect covers only the sub-image's pixels, not its gutter.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PackedRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A rectangle in normalized texture coordinates.
+///
+/// `v` increases from the top of the image to the bottom.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UvRect {
+    pub u_min: f32,
+    pub v_min: f32,
+    pub u_max: f32,
+    pub v_max: f32,
+}
+
+/// Places rectangles into rows ("shelves") within a fixed size sheet.
+#[derive(Debug, Clone)]
+pub struct ShelfPacker {
+    width: u32,
+    height: u32,
+
+    /// Empty pixels reserved around every rectangle.
+    gutter: u32,
+
+    /// The top of the current shelf.
+    shelf_y: u32,
+
+    /// The height of the tallest rectangle on the current shelf.
+    shelf_height: u32,
+
+    /// The next free x position on the current shelf.
+    cursor_x: u32,
+}
+
+/// Builds a single image from many sub-images, extruding each sub-image's
+/// edges into its gutter.
+pub struct SheetBuilder {
+    packer: ShelfPacker,
+    gutter: u32,
+    image: RgbaImage,
+    rects: Vec<PackedRect>,
+}
@@ -0,0 +1,119 @@
+use super::{PackedRect, SheetBuilder, ShelfPacker};
+
+use anyhow::{bail, Result};
+use image::RgbaImage;
+
+impl SheetBuilder {
+    /// Create a builder for a sheet with the given size in pixels.
+    ///
+    /// Every sub-image is surrounded by `gutter` pixels of extruded edge
+    /// pixels.
+    pub fn new(width: u32, height: u32, gutter: u32) -> Self {
+        Self {
+            packer: ShelfPacker::new(width, height, gutter),
+            gutter,
+            image: RgbaImage::new(width, height),
+            rects: vec![],
+        }
+    }
+
+    /// Copy a sub-image into the sheet and extrude its edges into the gutter.
+    ///
+    /// Returns the index of the sub-image's rect in the finished sheet.
+    pub fn add(&mut self, sub_image: &RgbaImage) -> Result<usize> {
+        let rect = match self.packer.pack(sub_image.width(), sub_image.height())
+        {
+            Some(rect) => rect,
+            None => bail!(
+                "no room for a {}x{} image in the sheet",
+                sub_image.width(),
+                sub_image.height()
+            ),
+        };
+        image::imageops::replace(&mut self.image, sub_image, rect.x, rect.y);
+        extrude(&mut self.image, rect, self.gutter);
+        self.rects.push(rect);
+        Ok(self.rects.len() - 1)
+    }
+
+    /// Finish the sheet, returning the image and the rect for each added
+    /// sub-image in the order they were added.
+    pub fn build(self) -> (RgbaImage, Vec<PackedRect>) {
+        (self.image, self.rects)
+    }
+}
+
+/// Fill the `gutter` pixels surrounding a rect with copies of the rect's edge
+/// pixels, including the corners.
+///
+/// Pixels which fall outside of the image are ignored.
+pub fn extrude(image: &mut RgbaImage, rect: PackedRect, gutter: u32) {
+    if rect.width == 0 || rect.height == 0 {
+        return;
+    }
+    let (left, top) = (rect.x as i64, rect.y as i64);
+    let (right, bottom) =
+        (left + rect.width as i64 - 1, top + rect.height as i64 - 1);
+    let gutter = gutter as i64;
+
+    for y in (top - gutter)..=(bottom + gutter) {
+        for x in (left - gutter)..=(right + gutter) {
+            let inside = x >= left && x <= right && y >= top && y <= bottom;
+            let in_image = x >= 0
+                && y >= 0
+                && x < image.width() as i64
+                && y < image.height() as i64;
+            if inside || !in_image {
+                continue;
+            }
+            let source_x = x.max(left).min(right) as u32;
+            let source_y = y.max(top).min(bottom) as u32;
+            let pixel = *image.get_pixel(source_x, source_y);
+            image.put_pixel(x as u32, y as u32, pixel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use image::Rgba;
+
+    #[test]
+    fn add_should_extrude_edges_into_the_gutter() -> Result<()> {
+        let mut sub_image = RgbaImage::new(2, 1);
+        sub_image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        sub_image.put_pixel(1, 0, Rgba([0, 0, 255, 255]));
+
+        let mut builder = SheetBuilder::new(8, 8, 1);
+        let index = builder.add(&sub_image)?;
+        let (sheet, rects) = builder.build();
+
+        assert_eq!(
+            rects[index],
+            PackedRect {
+                x: 1,
+                y: 1,
+                width: 2,
+                height: 1
+            }
+        );
+        // left and right edges
+        assert_eq!(*sheet.get_pixel(0, 1), Rgba([255, 0, 0, 255]));
+        assert_eq!(*sheet.get_pixel(3, 1), Rgba([0, 0, 255, 255]));
+        // corners and top/bottom edges
+        assert_eq!(*sheet.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+        assert_eq!(*sheet.get_pixel(3, 2), Rgba([0, 0, 255, 255]));
+        assert_eq!(*sheet.get_pixel(2, 0), Rgba([0, 0, 255, 255]));
+        // outside of the gutter
+        assert_eq!(*sheet.get_pixel(4, 1), Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+
+    #[test]
+    fn add_should_fail_when_the_sheet_is_full() {
+        let mut builder = SheetBuilder::new(4, 4, 1);
+        assert!(builder.add(&RgbaImage::new(4, 4)).is_err());
+    }
+}
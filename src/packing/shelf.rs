@@ -0,0 +1,84 @@
+use super::{PackedRect, ShelfPacker};
+
+impl ShelfPacker {
+    /// Create a packer for a sheet with the given size in pixels.
+    ///
+    /// Every packed rectangle is surrounded by `gutter` empty pixels.
+    pub fn new(width: u32, height: u32, gutter: u32) -> Self {
+        Self {
+            width,
+            height,
+            gutter,
+            shelf_y: 0,
+            shelf_height: 0,
+            cursor_x: 0,
+        }
+    }
+
+    /// Reserve space for a rectangle.
+    ///
+    /// Returns None when the rectangle, plus its gutter, doesn't fit in the
+    /// remaining space.
+    pub fn pack(&mut self, width: u32, height: u32) -> Option<PackedRect> {
+        let padded_width = width + self.gutter * 2;
+        let padded_height = height + self.gutter * 2;
+        if padded_width > self.width {
+            return None;
+        }
+
+        if self.cursor_x + padded_width > self.width {
+            // start a new shelf
+            self.shelf_y += self.shelf_height;
+            self.shelf_height = 0;
+            self.cursor_x = 0;
+        }
+        if self.shelf_y + padded_height > self.height {
+            return None;
+        }
+
+        let rect = PackedRect {
+            x: self.cursor_x + self.gutter,
+            y: self.shelf_y + self.gutter,
+            width,
+            height,
+        };
+        self.cursor_x += padded_width;
+        self.shelf_height = self.shelf_height.max(padded_height);
+        Some(rect)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pack_should_offset_rects_by_the_gutter() {
+        let mut packer = ShelfPacker::new(16, 16, 2);
+
+        let first = packer.pack(4, 4).unwrap();
+        let second = packer.pack(4, 2).unwrap();
+
+        assert_eq!((first.x, first.y), (2, 2));
+        assert_eq!((second.x, second.y), (10, 2));
+    }
+
+    #[test]
+    fn pack_should_start_a_new_shelf_when_the_row_is_full() {
+        let mut packer = ShelfPacker::new(16, 16, 1);
+
+        packer.pack(10, 3).unwrap();
+        let next = packer.pack(10, 3).unwrap();
+
+        assert_eq!((next.x, next.y), (1, 6));
+    }
+
+    #[test]
+    fn pack_should_fail_when_the_sheet_is_full() {
+        let mut packer = ShelfPacker::new(8, 8, 1);
+
+        assert!(packer.pack(7, 2).is_none());
+        assert!(packer.pack(6, 6).is_some());
+        assert!(packer.pack(1, 1).is_none());
+    }
+}
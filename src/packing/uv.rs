@@ -0,0 +1,104 @@
+use super::{PackedRect, UvRect};
+
+impl PackedRect {
+    /// The rect's exact bounds in texture coordinates for a sheet of the
+    /// given size.
+    pub fn uv_rect(&self, sheet_width: u32, sheet_height: u32) -> UvRect {
+        self.inset_uv_rect(sheet_width, sheet_height, 0.0)
+    }
+
+    /// The rect's bounds in texture coordinates, inset by half a texel on
+    /// every side so linear filtering never samples outside of the rect.
+    pub fn half_texel_uv_rect(
+        &self,
+        sheet_width: u32,
+        sheet_height: u32,
+    ) -> UvRect {
+        self.inset_uv_rect(sheet_width, sheet_height, 0.5)
+    }
+
+    /// The rect's bounds in texture coordinates, inset by `texels` on every
+    /// side. The inset is limited so the result never inverts.
+    pub fn inset_uv_rect(
+        &self,
+        sheet_width: u32,
+        sheet_height: u32,
+        texels: f32,
+    ) -> UvRect {
+        let inset_x = texels.min(self.width as f32 / 2.0);
+        let inset_y = texels.min(self.height as f32 / 2.0);
+        let (sheet_width, sheet_height) =
+            (sheet_width as f32, sheet_height as f32);
+        UvRect {
+            u_min: (self.x as f32 + inset_x) / sheet_width,
+            v_min: (self.y as f32 + inset_y) / sheet_height,
+            u_max: ((self.x + self.width) as f32 - inset_x) / sheet_width,
+            v_max: ((self.y + self.height) as f32 - inset_y) / sheet_height,
+        }
+    }
+}
+
+impl UvRect {
+    /// Map a normalized coordinate within the rect, where (0, 0) is the top
+    /// left corner and (1, 1) is the bottom right, to a sheet coordinate.
+    pub fn lerp(&self, u: f32, v: f32) -> [f32; 2] {
+        [
+            self.u_min + (self.u_max - self.u_min) * u,
+            self.v_min + (self.v_max - self.v_min) * v,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RECT: PackedRect = PackedRect {
+        x: 2,
+        y: 4,
+        width: 4,
+        height: 8,
+    };
+
+    #[test]
+    fn uv_rect_should_cover_the_exact_pixels() {
+        let uv = RECT.uv_rect(16, 16);
+        assert_eq!(
+            uv,
+            UvRect {
+                u_min: 0.125,
+                v_min: 0.25,
+                u_max: 0.375,
+                v_max: 0.75,
+            }
+        );
+    }
+
+    #[test]
+    fn half_texel_uv_rect_should_inset_to_texel_centers() {
+        let uv = RECT.half_texel_uv_rect(16, 16);
+        assert_eq!(
+            uv,
+            UvRect {
+                u_min: 2.5 / 16.0,
+                v_min: 4.5 / 16.0,
+                u_max: 5.5 / 16.0,
+                v_max: 11.5 / 16.0,
+            }
+        );
+        assert_eq!(uv.lerp(0.5, 0.5), [0.25, 0.5]);
+    }
+
+    #[test]
+    fn inset_uv_rect_should_not_invert() {
+        let single = PackedRect {
+            x: 0,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+        let uv = single.inset_uv_rect(4, 4, 2.0);
+        assert_eq!(uv.u_min, uv.u_max);
+        assert_eq!(uv.v_min, uv.v_max);
+    }
+}
@@ -0,0 +1,89 @@
+//! Named values which can be tweaked at runtime with an overlay panel.
+//!
+//! Sketches register parameters every frame, immediate-mode style. The
+//! first call registers the parameter with its default and later calls
+//! return whatever value it has been changed to:
+//!
+//! ```ignore
+//! let speed = params.float("speed", 1.0, 0.0..=10.0);
+//! let tint = params.color("tint", [1.0, 0.5, 0.2, 1.0]);
+//! let show_grid = params.toggle("show grid", true);
+//! ```
+//!
+//! A `ParamsPanel` draws a slider for each parameter and adjusts them with
+//! the mouse. Values can be saved to, and loaded from, TOML files so a good
+//! configuration isn't lost when the sketch closes.
+
+mod panel;
+mod panel_mesh;
+mod param_values;
+mod params_toml;
+
+use crate::text::Labels;
+
+use std::collections::HashMap;
+
+/// A parameter's value and the constraints on it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParamValue {
+    /// A number which is always within the range min to max.
+    Float { value: f32, min: f32, max: f32 },
+
+    /// An rgba color with channels in the range 0 to 1.
+    Color([f32; 4]),
+
+    /// A checkbox.
+    Bool(bool),
+}
+
+/// A named parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub value: ParamValue,
+}
+
+/// A set of named parameters, kept in the order they were registered.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    entries: Vec<Param>,
+
+    /// Values loaded from a file for parameters which haven't been
+    /// registered yet.
+    loaded: HashMap<String, toml::Value>,
+}
+
+/// An overlay which draws a row for each parameter and adjusts them with
+/// the mouse.
+///
+/// All sizes are in screen pixels. Draw the panel's batches on a layer which
+/// uses `ParamsPanel::projection`.
+pub struct ParamsPanel {
+    /// The top left corner of the panel.
+    pub position: (f32, f32),
+
+    /// The width of the panel.
+    pub width: f32,
+
+    /// The height of each parameter's row.
+    pub row_height: f32,
+
+    /// Hidden panels ignore events and draw nothing.
+    pub visible: bool,
+
+    labels: Option<Labels>,
+    window_size: (f32, f32),
+    cursor: (f32, f32),
+    hovered: Option<PanelHit>,
+    drag: Option<PanelHit>,
+}
+
+/// A part of the panel under the cursor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct PanelHit {
+    /// The parameter's index.
+    row: usize,
+
+    /// The color channel, always 0 for other parameters.
+    channel: usize,
+}
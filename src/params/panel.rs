@@ -0,0 +1,361 @@
+use super::{Labels, PanelHit, ParamValue, Params, ParamsPanel};
+
+use nalgebra as na;
+
+/// Space around the panel's rows, in pixels.
+pub(super) const PADDING: f32 = 6.0;
+
+/// Space between the rows and inside each control, in pixels.
+pub(super) const INSET: f32 = 3.0;
+
+/// The fraction of the panel's width used for labels.
+const LABEL_FRACTION: f32 = 0.45;
+
+impl ParamsPanel {
+    /// Create a panel in the top left corner of the window.
+    ///
+    /// `window_size` is the window's size in screen coordinates, as reported
+    /// by `glfw::Window::get_size`. The panel keeps it up to date by
+    /// observing `WindowEvent::Size` events.
+    pub fn new(window_size: (i32, i32)) -> Self {
+        Self {
+            position: (10.0, 10.0),
+            width: 300.0,
+            row_height: 22.0,
+            visible: true,
+            labels: None,
+            window_size: (window_size.0 as f32, window_size.1 as f32),
+            cursor: (0.0, 0.0),
+            hovered: None,
+            drag: None,
+        }
+    }
+
+    /// Draw each parameter's name with the given labels. Panels without
+    /// labels only draw the controls.
+    pub fn set_labels(&mut self, labels: Labels) {
+        self.labels = Some(labels);
+    }
+
+    /// Borrow the panel's labels, if any.
+    pub fn labels(&self) -> Option<&Labels> {
+        self.labels.as_ref()
+    }
+
+    /// A projection which maps screen pixels, with the origin in the top
+    /// left corner, to the window. Layers which draw the panel should use
+    /// it.
+    pub fn projection(&self) -> na::Matrix4<f32> {
+        na::Matrix4::new_orthographic(
+            0.0,
+            self.window_size.0.max(1.0),
+            0.0,
+            self.window_size.1.max(1.0),
+            -1.0,
+            1.0,
+        )
+    }
+
+    /// Returns true while a slider is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Returns true when the cursor is over the visible panel.
+    ///
+    /// Applications typically use this, along with `is_dragging`, to avoid
+    /// processing the same mouse events with other controls.
+    pub fn contains_cursor(&self, params: &Params) -> bool {
+        let [left, top, right, bottom] = self.bounds(params);
+        self.visible
+            && self.cursor.0 >= left
+            && self.cursor.0 <= right
+            && self.cursor.1 >= top
+            && self.cursor.1 <= bottom
+    }
+
+    /// Update the panel, and the parameters, in response to a window event.
+    ///
+    /// The window must have cursor-position and mouse-button polling enabled
+    /// for the panel to be interactive.
+    ///
+    /// Returns `true` when the parameters or the panel's appearance changed.
+    /// Often this is used to trigger a rebuild of the panel's batches.
+    #[cfg(not(target_os = "android"))]
+    pub fn handle_event(
+        &mut self,
+        event: &glfw::WindowEvent,
+        params: &mut Params,
+    ) -> bool {
+        use glfw::{Action, MouseButton, WindowEvent};
+
+        match event {
+            WindowEvent::Size(width, height) => {
+                self.resize(*width as f32, *height as f32)
+            }
+
+            WindowEvent::CursorPos(x, y) => {
+                self.cursor_moved(*x as f32, *y as f32, params)
+            }
+
+            WindowEvent::MouseButton(
+                MouseButton::Button1,
+                Action::Press,
+                _,
+            ) => self.press(params),
+
+            WindowEvent::MouseButton(
+                MouseButton::Button1,
+                Action::Release,
+                _,
+            ) => self.release(),
+
+            _ => false,
+        }
+    }
+
+    /// Update the window size, in screen coordinates.
+    ///
+    /// `handle_event` calls this for `WindowEvent::Size`. Platforms without
+    /// glfw window events, like Android, call it directly.
+    pub fn resize(&mut self, width: f32, height: f32) -> bool {
+        self.window_size = (width, height);
+        true
+    }
+
+    /// Move the cursor, in screen coordinates, dragging the active slider if
+    /// there is one.
+    pub fn cursor_moved(
+        &mut self,
+        x: f32,
+        y: f32,
+        params: &mut Params,
+    ) -> bool {
+        self.cursor = (x, y);
+        if let Some(drag) = self.drag {
+            self.apply_cursor(drag, params);
+            true
+        } else {
+            let hovered = self.pick(params);
+            let changed = hovered != self.hovered;
+            self.hovered = hovered;
+            changed
+        }
+    }
+
+    /// Click the control under the cursor. Checkboxes toggle, and sliders
+    /// are set and start dragging.
+    pub fn press(&mut self, params: &mut Params) -> bool {
+        self.hovered = self.pick(params);
+        let hit = match self.hovered {
+            Some(hit) => hit,
+            None => return false,
+        };
+        match params.value_mut(hit.row) {
+            Some(ParamValue::Bool(value)) => *value = !*value,
+            _ => {
+                self.apply_cursor(hit, params);
+                self.drag = Some(hit);
+            }
+        }
+        true
+    }
+
+    /// Stop dragging.
+    pub fn release(&mut self) -> bool {
+        self.drag.take().is_some()
+    }
+
+    /// The control under the cursor, or the one being dragged.
+    pub(super) fn active(&self) -> Option<PanelHit> {
+        self.drag.or(self.hovered)
+    }
+
+    /// The panel's bounds as left, top, right, and bottom.
+    pub(super) fn bounds(&self, params: &Params) -> [f32; 4] {
+        let (left, top) = self.position;
+        let height = 2.0 * PADDING + params.len() as f32 * self.row_height;
+        [left, top, left + self.width, top + height]
+    }
+
+    /// The top of a parameter's row.
+    pub(super) fn row_top(&self, row: usize) -> f32 {
+        self.position.1 + PADDING + row as f32 * self.row_height
+    }
+
+    /// The left edge of the label column.
+    pub(super) fn label_left(&self) -> f32 {
+        self.position.0 + PADDING
+    }
+
+    /// The horizontal extent of the control column.
+    pub(super) fn control_span(&self) -> (f32, f32) {
+        let left = self.position.0 + self.width * LABEL_FRACTION;
+        let right = self.position.0 + self.width - PADDING;
+        (left, right.max(left))
+    }
+
+    /// The horizontal extent of a single color channel's slider.
+    pub(super) fn channel_span(&self, channel: usize) -> (f32, f32) {
+        let (left, right) = self.control_span();
+        let channel_width = ((right - left) - 3.0 * INSET) / 4.0;
+        let channel_left = left + channel as f32 * (channel_width + INSET);
+        (channel_left, channel_left + channel_width)
+    }
+
+    /// Find the control under the cursor.
+    fn pick(&self, params: &Params) -> Option<PanelHit> {
+        if !self.contains_cursor(params) {
+            return None;
+        }
+        let (x, y) = self.cursor;
+        let row = ((y - self.row_top(0)) / self.row_height).floor();
+        if row < 0.0 || row as usize >= params.len() {
+            return None;
+        }
+        let row = row as usize;
+        let (left, right) = self.control_span();
+        if x < left || x > right {
+            return None;
+        }
+        let channel = match params.entries()[row].value {
+            ParamValue::Color(_) => (0..4)
+                .find(|channel| x <= self.channel_span(*channel).1)
+                .unwrap_or(3),
+            _ => 0,
+        };
+        Some(PanelHit { row, channel })
+    }
+
+    /// Set a slider's value from the cursor's horizontal position.
+    fn apply_cursor(&self, hit: PanelHit, params: &mut Params) {
+        let span = match params.entries().get(hit.row).map(|p| p.value) {
+            Some(ParamValue::Color(_)) => self.channel_span(hit.channel),
+            _ => self.control_span(),
+        };
+        let fraction = slider_fraction(self.cursor.0, span);
+        match params.value_mut(hit.row) {
+            Some(ParamValue::Float { value, min, max }) => {
+                *value = *min + (*max - *min) * fraction;
+            }
+            Some(ParamValue::Color(rgba)) => rgba[hit.channel] = fraction,
+            _ => (),
+        }
+    }
+}
+
+/// Where x falls along a slider, in the range 0 to 1.
+pub(super) fn slider_fraction(x: f32, (left, right): (f32, f32)) -> f32 {
+    if right <= left {
+        return 0.0;
+    }
+    ((x - left) / (right - left)).clamp(0.0, 1.0)
+}
+
+#[cfg(all(test, not(target_os = "android")))]
+mod test {
+    use super::*;
+
+    use glfw::{Action, Modifiers, MouseButton, WindowEvent};
+
+    fn press() -> WindowEvent {
+        WindowEvent::MouseButton(
+            MouseButton::Button1,
+            Action::Press,
+            Modifiers::empty(),
+        )
+    }
+
+    fn release() -> WindowEvent {
+        WindowEvent::MouseButton(
+            MouseButton::Button1,
+            Action::Release,
+            Modifiers::empty(),
+        )
+    }
+
+    fn panel_and_params() -> (ParamsPanel, Params) {
+        let mut panel = ParamsPanel::new((800, 600));
+        panel.position = (0.0, 0.0);
+        panel.width = 200.0;
+        panel.row_height = 20.0;
+        let mut params = Params::new();
+        params.float("speed", 0.0, 0.0..=10.0);
+        params.toggle("grid", false);
+        params.color("tint", [0.0; 4]);
+        (panel, params)
+    }
+
+    fn move_to(
+        panel: &mut ParamsPanel,
+        params: &mut Params,
+        x: f32,
+        row: usize,
+    ) {
+        let y = panel.row_top(row) + panel.row_height / 2.0;
+        panel.handle_event(&WindowEvent::CursorPos(x as f64, y as f64), params);
+    }
+
+    #[test]
+    fn slider_fraction_is_clamped() {
+        assert_eq!(slider_fraction(-5.0, (0.0, 10.0)), 0.0);
+        assert_eq!(slider_fraction(5.0, (0.0, 10.0)), 0.5);
+        assert_eq!(slider_fraction(50.0, (0.0, 10.0)), 1.0);
+        assert_eq!(slider_fraction(5.0, (10.0, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn clicking_a_slider_sets_its_value() {
+        let (mut panel, mut params) = panel_and_params();
+        let (left, right) = panel.control_span();
+        move_to(&mut panel, &mut params, (left + right) / 2.0, 0);
+        assert!(panel.handle_event(&press(), &mut params));
+        assert!((params.float("speed", 0.0, 0.0..=10.0) - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn dragging_past_the_end_clamps_the_value() {
+        let (mut panel, mut params) = panel_and_params();
+        let (left, _) = panel.control_span();
+        move_to(&mut panel, &mut params, left, 0);
+        panel.handle_event(&press(), &mut params);
+        assert!(panel.is_dragging());
+        move_to(&mut panel, &mut params, 10_000.0, 5);
+        assert_eq!(params.float("speed", 0.0, 0.0..=10.0), 10.0);
+        assert!(panel.handle_event(&release(), &mut params));
+        assert!(!panel.is_dragging());
+    }
+
+    #[test]
+    fn clicking_a_checkbox_toggles_it() {
+        let (mut panel, mut params) = panel_and_params();
+        let (left, _) = panel.control_span();
+        move_to(&mut panel, &mut params, left + 1.0, 1);
+        panel.handle_event(&press(), &mut params);
+        assert!(params.toggle("grid", false));
+        assert!(!panel.is_dragging());
+    }
+
+    #[test]
+    fn color_channels_are_set_separately() {
+        let (mut panel, mut params) = panel_and_params();
+        let (_, right) = panel.channel_span(2);
+        move_to(&mut panel, &mut params, right, 2);
+        panel.handle_event(&press(), &mut params);
+        assert_eq!(params.color("tint", [0.0; 4]), [0.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn labels_and_hidden_panels_ignore_clicks() {
+        let (mut panel, mut params) = panel_and_params();
+        let label_left = panel.label_left();
+        move_to(&mut panel, &mut params, label_left, 1);
+        assert!(!panel.handle_event(&press(), &mut params));
+
+        panel.visible = false;
+        let (left, _) = panel.control_span();
+        move_to(&mut panel, &mut params, left + 1.0, 1);
+        assert!(!panel.handle_event(&press(), &mut params));
+        assert!(!params.toggle("grid", false));
+    }
+}
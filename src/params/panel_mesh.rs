@@ -0,0 +1,159 @@
+use super::{
+    panel::{INSET, PADDING},
+    PanelHit, ParamValue, Params, ParamsPanel,
+};
+
+use crate::graphics::{layer::Batch, vertex::Vertex2d};
+
+const BACKGROUND_COLOR: [f32; 4] = [0.08, 0.08, 0.1, 0.85];
+const TRACK_COLOR: [f32; 4] = [0.22, 0.22, 0.26, 1.0];
+const FILL_COLOR: [f32; 4] = [0.3, 0.5, 0.85, 1.0];
+const ACTIVE_COLOR: [f32; 4] = [0.45, 0.65, 1.0, 1.0];
+const LABEL_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+const CHANNEL_COLORS: [[f32; 4]; 4] = [
+    [0.85, 0.25, 0.25, 1.0],
+    [0.25, 0.75, 0.3, 1.0],
+    [0.3, 0.45, 0.95, 1.0],
+    [0.7, 0.7, 0.7, 1.0],
+];
+
+impl ParamsPanel {
+    /// Build the batches which draw the panel.
+    ///
+    /// The first batch draws the background and controls. A second batch
+    /// draws the labels, when the panel has them. Hidden panels have no
+    /// batches.
+    pub fn build_batches(&self, params: &Params) -> Vec<Batch> {
+        if !self.visible || params.is_empty() {
+            return vec![];
+        }
+
+        let mut shapes = Batch::empty();
+        push_rect(&mut shapes.vertices, self.bounds(params), BACKGROUND_COLOR);
+        for (row, param) in params.entries().iter().enumerate() {
+            let top = self.row_top(row) + INSET;
+            let bottom = self.row_top(row + 1) - INSET;
+            let active =
+                |channel| self.active() == Some(PanelHit { row, channel });
+            match param.value {
+                ParamValue::Float { value, min, max } => {
+                    let fraction = if max > min {
+                        (value - min) / (max - min)
+                    } else {
+                        0.0
+                    };
+                    let color =
+                        if active(0) { ACTIVE_COLOR } else { FILL_COLOR };
+                    self.push_slider(
+                        &mut shapes.vertices,
+                        self.control_span(),
+                        (top, bottom),
+                        fraction,
+                        color,
+                    );
+                }
+                ParamValue::Color(rgba) => {
+                    for channel in 0..4 {
+                        let mut color = CHANNEL_COLORS[channel];
+                        if active(channel) {
+                            color = ACTIVE_COLOR;
+                        }
+                        self.push_slider(
+                            &mut shapes.vertices,
+                            self.channel_span(channel),
+                            (top, bottom),
+                            rgba[channel],
+                            color,
+                        );
+                    }
+                    // a swatch at the end of the label column
+                    let (control_left, _) = self.control_span();
+                    let size = bottom - top;
+                    let swatch_right = control_left - INSET;
+                    push_rect(
+                        &mut shapes.vertices,
+                        [swatch_right - size, top, swatch_right, bottom],
+                        rgba,
+                    );
+                }
+                ParamValue::Bool(checked) => {
+                    let (left, _) = self.control_span();
+                    let size = bottom - top;
+                    let box_bounds = [left, top, left + size, bottom];
+                    push_rect(&mut shapes.vertices, box_bounds, TRACK_COLOR);
+                    if checked {
+                        let color =
+                            if active(0) { ACTIVE_COLOR } else { FILL_COLOR };
+                        push_rect(
+                            &mut shapes.vertices,
+                            [
+                                left + INSET,
+                                top + INSET,
+                                left + size - INSET,
+                                bottom - INSET,
+                            ],
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut batches = vec![shapes];
+        if let Some(labels) = &self.labels {
+            let mut text = Batch::empty();
+            text.texture_handle = labels.texture_handle();
+            let (control_left, _) = self.control_span();
+            for (row, param) in params.entries().iter().enumerate() {
+                let top = self.row_top(row)
+                    + (self.row_height - labels.line_height()) / 2.0;
+                labels.layout(
+                    &param.name,
+                    (self.label_left(), top),
+                    control_left - self.row_height - PADDING,
+                    LABEL_COLOR,
+                    &mut text.vertices,
+                );
+            }
+            batches.push(text);
+        }
+        batches
+    }
+
+    /// Push a track with a filled portion for the slider's value.
+    fn push_slider(
+        &self,
+        vertices: &mut Vec<Vertex2d>,
+        (left, right): (f32, f32),
+        (top, bottom): (f32, f32),
+        fraction: f32,
+        color: [f32; 4],
+    ) {
+        push_rect(vertices, [left, top, right, bottom], TRACK_COLOR);
+        let fill_right = left + (right - left) * fraction.clamp(0.0, 1.0);
+        if fill_right > left {
+            push_rect(vertices, [left, top, fill_right, bottom], color);
+        }
+    }
+}
+
+/// Push two triangles which cover the left, top, right, bottom bounds.
+pub(super) fn push_rect(
+    vertices: &mut Vec<Vertex2d>,
+    [left, top, right, bottom]: [f32; 4],
+    rgba: [f32; 4],
+) {
+    let corner = |x, y| Vertex2d {
+        pos: [x, y],
+        uv: [0.0, 0.0],
+        rgba,
+    };
+    vertices.extend_from_slice(&[
+        corner(left, top),
+        corner(right, top),
+        corner(right, bottom),
+        corner(left, top),
+        corner(right, bottom),
+        corner(left, bottom),
+    ]);
+}
@@ -0,0 +1,219 @@
+use super::{Param, ParamValue, Params};
+
+use std::ops::RangeInclusive;
+
+impl Params {
+    /// Create an empty set of parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a number in a range, or get its current value if it already
+    /// exists.
+    pub fn float(
+        &mut self,
+        name: &str,
+        default: f32,
+        range: RangeInclusive<f32>,
+    ) -> f32 {
+        let (min, max) = (*range.start(), *range.end());
+        let value = self.register(
+            name,
+            ParamValue::Float {
+                value: default.max(min).min(max),
+                min,
+                max,
+            },
+        );
+        match value {
+            ParamValue::Float { value, .. } => value,
+            _ => default,
+        }
+    }
+
+    /// Register a color, or get its current value if it already exists.
+    pub fn color(&mut self, name: &str, default: [f32; 4]) -> [f32; 4] {
+        match self.register(name, ParamValue::Color(default)) {
+            ParamValue::Color(rgba) => rgba,
+            _ => default,
+        }
+    }
+
+    /// Register a checkbox, or get its current value if it already exists.
+    pub fn toggle(&mut self, name: &str, default: bool) -> bool {
+        match self.register(name, ParamValue::Bool(default)) {
+            ParamValue::Bool(value) => value,
+            _ => default,
+        }
+    }
+
+    /// The current value of a parameter.
+    pub fn get(&self, name: &str) -> Option<ParamValue> {
+        self.entries
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| param.value)
+    }
+
+    /// Change a registered number. The value is clamped to the parameter's
+    /// range.
+    ///
+    /// Returns false when there's no number with the name.
+    pub fn set_float(&mut self, name: &str, new_value: f32) -> bool {
+        match self.get_mut(name) {
+            Some(ParamValue::Float { value, min, max }) => {
+                *value = new_value.max(*min).min(*max);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Change a registered color.
+    ///
+    /// Returns false when there's no color with the name.
+    pub fn set_color(&mut self, name: &str, new_rgba: [f32; 4]) -> bool {
+        match self.get_mut(name) {
+            Some(ParamValue::Color(rgba)) => {
+                *rgba = clamp_color(new_rgba);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Change a registered checkbox.
+    ///
+    /// Returns false when there's no checkbox with the name.
+    pub fn set_toggle(&mut self, name: &str, new_value: bool) -> bool {
+        match self.get_mut(name) {
+            Some(ParamValue::Bool(value)) => {
+                *value = new_value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Every parameter in the order it was registered.
+    pub fn entries(&self) -> &[Param] {
+        &self.entries
+    }
+
+    /// The number of registered parameters.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True when no parameters are registered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Mutable access to a parameter's value by index, used by the panel.
+    pub(super) fn value_mut(
+        &mut self,
+        index: usize,
+    ) -> Option<&mut ParamValue> {
+        self.entries.get_mut(index).map(|param| &mut param.value)
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut ParamValue> {
+        self.entries
+            .iter_mut()
+            .find(|param| param.name == name)
+            .map(|param| &mut param.value)
+    }
+
+    /// Add the parameter if it doesn't exist, then return its value.
+    ///
+    /// Parameters which are registered again with a different kind of value
+    /// are replaced.
+    fn register(&mut self, name: &str, default: ParamValue) -> ParamValue {
+        if let Some(existing) = self.get(name) {
+            if std::mem::discriminant(&existing)
+                == std::mem::discriminant(&default)
+            {
+                return existing;
+            }
+        }
+        let mut value = default;
+        if let Some(loaded) = self.loaded.remove(name) {
+            Self::apply_loaded(&mut value, &loaded);
+        }
+        match self.get_mut(name) {
+            Some(existing) => *existing = value,
+            None => self.entries.push(Param {
+                name: name.to_owned(),
+                value,
+            }),
+        }
+        value
+    }
+}
+
+/// Keep every channel in the range 0 to 1.
+pub(super) fn clamp_color(rgba: [f32; 4]) -> [f32; 4] {
+    let mut clamped = rgba;
+    for channel in &mut clamped {
+        *channel = channel.clamp(0.0, 1.0);
+    }
+    clamped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn first_registration_returns_the_default() {
+        let mut params = Params::new();
+        assert_eq!(params.float("speed", 2.0, 0.0..=10.0), 2.0);
+        assert_eq!(params.color("tint", [1.0; 4]), [1.0; 4]);
+        assert!(params.toggle("grid", true));
+        assert_eq!(params.len(), 3);
+    }
+
+    #[test]
+    fn later_registrations_return_the_current_value() {
+        let mut params = Params::new();
+        params.float("speed", 2.0, 0.0..=10.0);
+        params.set_float("speed", 5.0);
+        assert_eq!(params.float("speed", 2.0, 0.0..=10.0), 5.0);
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn floats_are_clamped_to_their_range() {
+        let mut params = Params::new();
+        assert_eq!(params.float("speed", 20.0, 0.0..=10.0), 10.0);
+        params.set_float("speed", -1.0);
+        assert_eq!(params.float("speed", 2.0, 0.0..=10.0), 0.0);
+    }
+
+    #[test]
+    fn setting_the_wrong_kind_of_value_fails() {
+        let mut params = Params::new();
+        params.toggle("grid", true);
+        assert!(!params.set_float("grid", 1.0));
+        assert!(!params.set_color("missing", [0.0; 4]));
+    }
+
+    #[test]
+    fn registering_a_different_kind_replaces_the_parameter() {
+        let mut params = Params::new();
+        params.toggle("value", true);
+        assert_eq!(params.float("value", 0.5, 0.0..=1.0), 0.5);
+        assert_eq!(params.len(), 1);
+    }
+
+    #[test]
+    fn parameters_keep_their_registration_order() {
+        let mut params = Params::new();
+        params.toggle("b", true);
+        params.toggle("a", true);
+        let names: Vec<&str> =
+            params.entries().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "a"]);
+    }
+}
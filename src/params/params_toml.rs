@@ -0,0 +1,167 @@
+use super::{param_values::clamp_color, ParamValue, Params};
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+impl Params {
+    /// Write every parameter's value as TOML.
+    ///
+    /// Numbers are written as floats, colors as arrays of four floats, and
+    /// checkboxes as booleans.
+    pub fn to_toml(&self) -> Result<String> {
+        let mut table = toml::value::Table::new();
+        for param in &self.entries {
+            let value = match param.value {
+                ParamValue::Float { value, .. } => {
+                    toml::Value::Float(value as f64)
+                }
+                ParamValue::Color(rgba) => toml::Value::Array(
+                    rgba.iter()
+                        .map(|channel| toml::Value::Float(*channel as f64))
+                        .collect(),
+                ),
+                ParamValue::Bool(value) => toml::Value::Boolean(value),
+            };
+            table.insert(param.name.clone(), value);
+        }
+        Ok(toml::to_string(&toml::Value::Table(table))?)
+    }
+
+    /// Set parameters from TOML written by `to_toml`.
+    ///
+    /// Values for parameters which haven't been registered yet are kept and
+    /// used when they are. Values of the wrong kind are ignored.
+    pub fn load_toml(&mut self, text: &str) -> Result<()> {
+        let document: toml::Value =
+            text.parse().context("invalid parameters toml")?;
+        let table = match document {
+            toml::Value::Table(table) => table,
+            _ => bail!("parameters toml must be a table"),
+        };
+        for (name, loaded) in table {
+            let registered =
+                self.entries.iter_mut().find(|param| param.name == name);
+            match registered {
+                Some(param) => Self::apply_loaded(&mut param.value, &loaded),
+                None => {
+                    self.loaded.insert(name, loaded);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Save every parameter to a TOML file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_toml()?)
+            .with_context(|| format!("unable to save parameters {:?}", path))
+    }
+
+    /// Load parameters from a TOML file.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to load parameters {:?}", path))?;
+        self.load_toml(&text)
+    }
+
+    /// Replace a value with one loaded from a file, when the kinds match.
+    pub(super) fn apply_loaded(value: &mut ParamValue, loaded: &toml::Value) {
+        match value {
+            ParamValue::Float { value, min, max } => {
+                if let Some(number) = as_f32(loaded) {
+                    *value = number.max(*min).min(*max);
+                }
+            }
+            ParamValue::Color(rgba) => {
+                let channels: Option<Vec<f32>> = loaded
+                    .as_array()
+                    .and_then(|array| array.iter().map(as_f32).collect());
+                if let Some(channels) = channels {
+                    if channels.len() == 4 {
+                        *rgba = clamp_color([
+                            channels[0],
+                            channels[1],
+                            channels[2],
+                            channels[3],
+                        ]);
+                    }
+                }
+            }
+            ParamValue::Bool(value) => {
+                if let Some(loaded) = loaded.as_bool() {
+                    *value = loaded;
+                }
+            }
+        }
+    }
+}
+
+/// Integers are accepted anywhere a float is, people write `speed = 2`.
+fn as_f32(value: &toml::Value) -> Option<f32> {
+    match value {
+        toml::Value::Float(number) => Some(*number as f32),
+        toml::Value::Integer(number) => Some(*number as f32),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sketch_params() -> Params {
+        let mut params = Params::new();
+        params.float("speed", 2.0, 0.0..=10.0);
+        params.color("tint", [1.0, 0.5, 0.0, 1.0]);
+        params.toggle("show grid", true);
+        params
+    }
+
+    #[test]
+    fn values_should_round_trip() -> Result<()> {
+        let mut params = sketch_params();
+        params.set_float("speed", 7.5);
+        params.set_toggle("show grid", false);
+        let text = params.to_toml()?;
+
+        let mut loaded = sketch_params();
+        loaded.load_toml(&text)?;
+        assert_eq!(loaded.float("speed", 2.0, 0.0..=10.0), 7.5);
+        assert_eq!(loaded.color("tint", [0.0; 4]), [1.0, 0.5, 0.0, 1.0]);
+        assert!(!loaded.toggle("show grid", true));
+        Ok(())
+    }
+
+    #[test]
+    fn values_loaded_early_are_used_at_registration() -> Result<()> {
+        let mut params = Params::new();
+        params.load_toml("speed = 3\n")?;
+        assert_eq!(params.float("speed", 1.0, 0.0..=10.0), 3.0);
+        Ok(())
+    }
+
+    #[test]
+    fn loaded_values_are_clamped() -> Result<()> {
+        let mut params = sketch_params();
+        params.load_toml("speed = 100.0\ntint = [2.0, -1.0, 0.5, 1.0]\n")?;
+        assert_eq!(params.float("speed", 1.0, 0.0..=10.0), 10.0);
+        assert_eq!(params.color("tint", [0.0; 4]), [1.0, 0.0, 0.5, 1.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn mismatched_values_are_ignored() -> Result<()> {
+        let mut params = sketch_params();
+        params.load_toml("speed = true\ntint = [1.0]\n")?;
+        assert_eq!(params.float("speed", 1.0, 0.0..=10.0), 2.0);
+        assert_eq!(params.color("tint", [0.0; 4]), [1.0, 0.5, 0.0, 1.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_toml_is_an_error() {
+        assert!(Params::new().load_toml("speed = ").is_err());
+    }
+}
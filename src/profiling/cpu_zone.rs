@@ -0,0 +1,34 @@
+use super::CpuZone;
+
+/// Start a cpu zone which lasts until the returned value is dropped.
+///
+/// The zone is shown at the caller's file and line.
+#[track_caller]
+pub fn zone(name: &'static str) -> CpuZone {
+    #[cfg(feature = "tracy")]
+    {
+        let location = std::panic::Location::caller();
+        CpuZone {
+            span: tracy_client::Client::start().span_alloc(
+                Some(name),
+                "",
+                location.file(),
+                location.line(),
+                0,
+            ),
+        }
+    }
+
+    #[cfg(not(feature = "tracy"))]
+    {
+        let _ = name;
+        CpuZone {}
+    }
+}
+
+/// Mark the end of a frame. Called by `Graphics::render` after the frame is
+/// presented.
+pub fn frame_mark() {
+    #[cfg(feature = "tracy")]
+    tracy_client::Client::start().frame_mark();
+}
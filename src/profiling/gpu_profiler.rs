@@ -0,0 +1,56 @@
+use super::{GpuProfiler, GpuZone};
+
+use crate::graphics::vulkan::Device;
+
+use anyhow::Result;
+
+impl GpuProfiler {
+    /// Create a profiler which is calibrated against the device's clock.
+    ///
+    /// Without the `tracy` feature, or when the device can't write
+    /// timestamps, zones are silently dropped.
+    pub fn new(device: &Device) -> Result<Self> {
+        #[cfg(feature = "tracy")]
+        {
+            let context = match device.timestamp_period() {
+                Some(period) => {
+                    let timestamp = device.current_timestamp()?;
+                    tracy_client::Client::start()
+                        .new_gpu_context(
+                            Some("Draw2D"),
+                            tracy_client::GpuContextType::Vulkan,
+                            timestamp as i64,
+                            period,
+                        )
+                        .ok()
+                }
+                None => None,
+            };
+            Ok(Self { context })
+        }
+
+        #[cfg(not(feature = "tracy"))]
+        {
+            let _ = device;
+            Ok(Self {})
+        }
+    }
+
+    /// Send a finished frame's zones to the profiler.
+    pub fn upload(&self, zones: &[GpuZone]) {
+        #[cfg(feature = "tracy")]
+        if let Some(context) = &self.context {
+            for zone in zones {
+                if let Ok(mut span) =
+                    context.span_alloc(zone.name, "", file!(), line!())
+                {
+                    span.end_zone();
+                    span.upload_timestamp(zone.start as i64, zone.end as i64);
+                }
+            }
+        }
+
+        #[cfg(not(feature = "tracy"))]
+        let _ = zones;
+    }
+}
@@ -0,0 +1,42 @@
+//! Optional integration with the Tracy profiler.
+//!
+//! # Big Idea
+//!
+//! Build with the `tracy` feature and connect the Tracy profiler, no other
+//! setup is needed. Every rendered frame is marked and split into cpu zones
+//! for its phases: poll, build, upload, record, submit, and present.
+//! Timestamp queries written into each frame's command buffer are sent as
+//! gpu zones once the frame has finished.
+//!
+//! Without the feature the zones compile to nothing and no queries are
+//! written. Applications can add their own cpu zones with `zone` either
+//! way.
+
+mod cpu_zone;
+mod gpu_profiler;
+
+pub use self::cpu_zone::{frame_mark, zone};
+
+/// True when the library was built with the `tracy` feature.
+pub const ENABLED: bool = cfg!(feature = "tracy");
+
+/// A cpu zone which ends when it's dropped.
+#[must_use = "the zone ends as soon as it's dropped"]
+pub struct CpuZone {
+    #[cfg(feature = "tracy")]
+    span: tracy_client::Span,
+}
+
+/// A span of gpu work measured by timestamp queries, in device ticks.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GpuZone {
+    pub name: &'static str,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Sends gpu zones to the profiler.
+pub struct GpuProfiler {
+    #[cfg(feature = "tracy")]
+    context: Option<tracy_client::GpuContext>,
+}
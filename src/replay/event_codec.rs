@@ -0,0 +1,205 @@
+use super::{keys::KEYS, TimedEvent};
+
+use anyhow::{bail, Context, Result};
+use glfw::{Action, Modifiers, MouseButton, WindowEvent};
+use std::str::FromStr;
+
+/// Write an event as the fields of an `event` line, or None when the event
+/// can't be replayed.
+pub(super) fn encode((time, event): &TimedEvent) -> Option<String> {
+    let fields = match event {
+        WindowEvent::Pos(x, y) => format!("pos {} {}", x, y),
+        WindowEvent::Size(width, height) => {
+            format!("size {} {}", width, height)
+        }
+        WindowEvent::Close => "close".to_owned(),
+        WindowEvent::Refresh => "refresh".to_owned(),
+        WindowEvent::Focus(focused) => format!("focus {}", focused),
+        WindowEvent::Iconify(iconified) => format!("iconify {}", iconified),
+        WindowEvent::FramebufferSize(width, height) => {
+            format!("framebuffer_size {} {}", width, height)
+        }
+        WindowEvent::MouseButton(button, action, modifiers) => format!(
+            "mouse_button {:?} {:?} {}",
+            button,
+            action,
+            modifiers.bits()
+        ),
+        WindowEvent::CursorPos(x, y) => format!("cursor_pos {} {}", x, y),
+        WindowEvent::CursorEnter(entered) => {
+            format!("cursor_enter {}", entered)
+        }
+        WindowEvent::Scroll(x, y) => format!("scroll {} {}", x, y),
+        WindowEvent::Key(key, scancode, action, modifiers) => format!(
+            "key {:?} {} {:?} {}",
+            key,
+            scancode,
+            action,
+            modifiers.bits()
+        ),
+        WindowEvent::Char(c) => format!("char {}", *c as u32),
+        WindowEvent::CharModifiers(c, modifiers) => {
+            format!("char_modifiers {} {}", *c as u32, modifiers.bits())
+        }
+        WindowEvent::FileDrop(_) => return None,
+        WindowEvent::Maximize(maximized) => {
+            format!("maximize {}", maximized)
+        }
+        WindowEvent::ContentScale(x, y) => {
+            format!("content_scale {} {}", x, y)
+        }
+    };
+    Some(format!("{} {}", time, fields))
+}
+
+/// Read the fields of an `event` line, starting with the time.
+pub(super) fn decode(fields: &[&str]) -> Result<TimedEvent> {
+    let field = |index: usize| -> Result<&str> {
+        fields
+            .get(index)
+            .copied()
+            .with_context(|| format!("expected {} fields", index + 1))
+    };
+    let time = parse::<f64>(field(0)?)?;
+    let kind = field(1)?;
+    let event = match kind {
+        "pos" => WindowEvent::Pos(parse(field(2)?)?, parse(field(3)?)?),
+        "size" => WindowEvent::Size(parse(field(2)?)?, parse(field(3)?)?),
+        "close" => WindowEvent::Close,
+        "refresh" => WindowEvent::Refresh,
+        "focus" => WindowEvent::Focus(parse(field(2)?)?),
+        "iconify" => WindowEvent::Iconify(parse(field(2)?)?),
+        "framebuffer_size" => {
+            WindowEvent::FramebufferSize(parse(field(2)?)?, parse(field(3)?)?)
+        }
+        "mouse_button" => WindowEvent::MouseButton(
+            mouse_button(field(2)?)?,
+            action(field(3)?)?,
+            modifiers(field(4)?)?,
+        ),
+        "cursor_pos" => {
+            WindowEvent::CursorPos(parse(field(2)?)?, parse(field(3)?)?)
+        }
+        "cursor_enter" => WindowEvent::CursorEnter(parse(field(2)?)?),
+        "scroll" => WindowEvent::Scroll(parse(field(2)?)?, parse(field(3)?)?),
+        "key" => WindowEvent::Key(
+            key(field(2)?)?,
+            parse(field(3)?)?,
+            action(field(4)?)?,
+            modifiers(field(5)?)?,
+        ),
+        "char" => WindowEvent::Char(character(field(2)?)?),
+        "char_modifiers" => WindowEvent::CharModifiers(
+            character(field(2)?)?,
+            modifiers(field(3)?)?,
+        ),
+        "maximize" => WindowEvent::Maximize(parse(field(2)?)?),
+        "content_scale" => {
+            WindowEvent::ContentScale(parse(field(2)?)?, parse(field(3)?)?)
+        }
+        _ => bail!("unknown event {:?}", kind),
+    };
+    Ok((time, event))
+}
+
+fn parse<T: FromStr>(field: &str) -> Result<T> {
+    field.parse::<T>().ok().with_context(|| {
+        format!("{:?} isn't a {}", field, std::any::type_name::<T>())
+    })
+}
+
+fn key(field: &str) -> Result<glfw::Key> {
+    KEYS.iter()
+        .copied()
+        .find(|key| format!("{:?}", key) == field)
+        .with_context(|| format!("{:?} isn't a key", field))
+}
+
+fn mouse_button(field: &str) -> Result<MouseButton> {
+    (0..8)
+        .filter_map(MouseButton::from_i32)
+        .find(|button| format!("{:?}", button) == field)
+        .with_context(|| format!("{:?} isn't a mouse button", field))
+}
+
+fn action(field: &str) -> Result<Action> {
+    match field {
+        "Release" => Ok(Action::Release),
+        "Press" => Ok(Action::Press),
+        "Repeat" => Ok(Action::Repeat),
+        _ => bail!("{:?} isn't an action", field),
+    }
+}
+
+fn modifiers(field: &str) -> Result<Modifiers> {
+    Ok(Modifiers::from_bits_truncate(parse(field)?))
+}
+
+fn character(field: &str) -> Result<char> {
+    std::char::from_u32(parse(field)?)
+        .with_context(|| format!("{:?} isn't a character", field))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(event: WindowEvent) -> TimedEvent {
+        let encoded = encode(&(1.25, event)).unwrap();
+        let fields: Vec<&str> = encoded.split_whitespace().collect();
+        decode(&fields).unwrap()
+    }
+
+    #[test]
+    fn events_should_survive_a_round_trip() {
+        let events = vec![
+            WindowEvent::Pos(-10, 20),
+            WindowEvent::Size(800, 600),
+            WindowEvent::Close,
+            WindowEvent::Focus(true),
+            WindowEvent::FramebufferSize(1600, 1200),
+            WindowEvent::MouseButton(
+                MouseButton::Button2,
+                Action::Press,
+                Modifiers::Shift | Modifiers::Alt,
+            ),
+            WindowEvent::CursorPos(10.125, 0.1),
+            WindowEvent::Scroll(0.0, -1.5),
+            WindowEvent::Key(
+                glfw::Key::Space,
+                57,
+                Action::Repeat,
+                Modifiers::Control,
+            ),
+            WindowEvent::Char('é'),
+            WindowEvent::CharModifiers('q', Modifiers::Super),
+            WindowEvent::ContentScale(1.5, 2.0),
+        ];
+        for event in events {
+            assert_eq!(round_trip(event.clone()), (1.25, event));
+        }
+    }
+
+    #[test]
+    fn encode_should_name_keys_and_actions() {
+        let event = WindowEvent::Key(
+            glfw::Key::Escape,
+            9,
+            Action::Press,
+            Modifiers::empty(),
+        );
+        assert_eq!(encode(&(0.5, event)).unwrap(), "0.5 key Escape 9 Press 0");
+    }
+
+    #[test]
+    fn encode_should_skip_file_drops() {
+        assert_eq!(encode(&(0.0, WindowEvent::FileDrop(vec![]))), None);
+    }
+
+    #[test]
+    fn decode_should_reject_unknown_and_incomplete_events() {
+        assert!(decode(&["0.0", "teleport"]).is_err());
+        assert!(decode(&["0.0", "cursor_pos", "1"]).is_err());
+        assert!(decode(&["0.0", "key", "NotAKey", "0", "Press", "0"]).is_err());
+    }
+}
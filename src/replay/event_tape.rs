@@ -0,0 +1,138 @@
+use super::{event_codec, EventTape, TapeFrame};
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+/// The first line of every tape.
+const HEADER: &str = "draw2d-tape 1";
+
+impl EventTape {
+    /// Read a tape written by `write_file`.
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read tape {:?}", path))?;
+        Self::parse(&text).with_context(|| format!("invalid tape {:?}", path))
+    }
+
+    /// Write the tape as text.
+    pub fn write_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_text())
+            .with_context(|| format!("unable to write tape {:?}", path))
+    }
+
+    /// Parse a tape. Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        match lines.next() {
+            Some((_, HEADER)) => (),
+            _ => bail!("expected the tape to start with {:?}", HEADER),
+        }
+
+        let mut frames: Vec<TapeFrame> = vec![];
+        for (line_number, line) in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let parsed = match fields[0] {
+                "frame" => parse_frame(&fields[1..]).map(|frame| {
+                    frames.push(frame);
+                }),
+                "event" => match frames.last_mut() {
+                    Some(frame) => event_codec::decode(&fields[1..])
+                        .map(|event| frame.events.push(event)),
+                    None => Err(anyhow::anyhow!(
+                        "events must follow the frame they belong to"
+                    )),
+                },
+                tag => Err(anyhow::anyhow!("unknown line {:?}", tag)),
+            };
+            parsed.with_context(|| format!("line {}", line_number))?;
+        }
+        Ok(Self { frames })
+    }
+
+    /// The tape as text, see `parse`.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", HEADER);
+        for frame in &self.frames {
+            text.push_str(&format!("frame {} {}\n", frame.time, frame.seed));
+            for event in frame.events.iter().filter_map(event_codec::encode) {
+                text.push_str(&format!("event {}\n", event));
+            }
+        }
+        text
+    }
+}
+
+fn parse_frame(fields: &[&str]) -> Result<TapeFrame> {
+    match fields {
+        [time, seed] => Ok(TapeFrame {
+            time: time
+                .parse()
+                .with_context(|| format!("{:?} isn't a time", time))?,
+            seed: seed
+                .parse()
+                .with_context(|| format!("{:?} isn't a seed", seed))?,
+            events: vec![],
+        }),
+        _ => bail!("expected `frame <time> <seed>`"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use glfw::WindowEvent;
+
+    fn tape() -> EventTape {
+        EventTape {
+            frames: vec![
+                TapeFrame {
+                    time: 0.0,
+                    seed: 42,
+                    events: vec![(0.0, WindowEvent::Focus(true))],
+                },
+                TapeFrame {
+                    time: 0.016,
+                    seed: u64::MAX,
+                    events: vec![
+                        (0.01, WindowEvent::CursorPos(3.5, 4.0)),
+                        (0.012, WindowEvent::Scroll(0.0, 1.0)),
+                    ],
+                },
+                TapeFrame {
+                    time: 0.033,
+                    seed: 7,
+                    events: vec![],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn tapes_should_survive_a_round_trip() {
+        let tape = tape();
+        assert_eq!(EventTape::parse(&tape.to_text()).unwrap(), tape);
+    }
+
+    #[test]
+    fn parse_should_ignore_comments_and_blank_lines() {
+        let text = "# made by hand\ndraw2d-tape 1\n\nframe 1 2\n";
+        let tape = EventTape::parse(text).unwrap();
+        assert_eq!(tape.frames.len(), 1);
+        assert_eq!(tape.frames[0].seed, 2);
+    }
+
+    #[test]
+    fn parse_should_reject_broken_tapes() {
+        assert!(EventTape::parse("frame 0 1").is_err());
+        assert!(EventTape::parse("draw2d-tape 1\nevent 0 close").is_err());
+        assert!(EventTape::parse("draw2d-tape 1\nframe 0").is_err());
+        assert!(EventTape::parse("draw2d-tape 1\nstop").is_err());
+    }
+}
@@ -0,0 +1,124 @@
+/// Every key glfw reports, used to read keys back from a tape by name.
+pub(super) const KEYS: [glfw::Key; 121] = [
+    glfw::Key::Space,
+    glfw::Key::Apostrophe,
+    glfw::Key::Comma,
+    glfw::Key::Minus,
+    glfw::Key::Period,
+    glfw::Key::Slash,
+    glfw::Key::Num0,
+    glfw::Key::Num1,
+    glfw::Key::Num2,
+    glfw::Key::Num3,
+    glfw::Key::Num4,
+    glfw::Key::Num5,
+    glfw::Key::Num6,
+    glfw::Key::Num7,
+    glfw::Key::Num8,
+    glfw::Key::Num9,
+    glfw::Key::Semicolon,
+    glfw::Key::Equal,
+    glfw::Key::A,
+    glfw::Key::B,
+    glfw::Key::C,
+    glfw::Key::D,
+    glfw::Key::E,
+    glfw::Key::F,
+    glfw::Key::G,
+    glfw::Key::H,
+    glfw::Key::I,
+    glfw::Key::J,
+    glfw::Key::K,
+    glfw::Key::L,
+    glfw::Key::M,
+    glfw::Key::N,
+    glfw::Key::O,
+    glfw::Key::P,
+    glfw::Key::Q,
+    glfw::Key::R,
+    glfw::Key::S,
+    glfw::Key::T,
+    glfw::Key::U,
+    glfw::Key::V,
+    glfw::Key::W,
+    glfw::Key::X,
+    glfw::Key::Y,
+    glfw::Key::Z,
+    glfw::Key::LeftBracket,
+    glfw::Key::Backslash,
+    glfw::Key::RightBracket,
+    glfw::Key::GraveAccent,
+    glfw::Key::World1,
+    glfw::Key::World2,
+    glfw::Key::Escape,
+    glfw::Key::Enter,
+    glfw::Key::Tab,
+    glfw::Key::Backspace,
+    glfw::Key::Insert,
+    glfw::Key::Delete,
+    glfw::Key::Right,
+    glfw::Key::Left,
+    glfw::Key::Down,
+    glfw::Key::Up,
+    glfw::Key::PageUp,
+    glfw::Key::PageDown,
+    glfw::Key::Home,
+    glfw::Key::End,
+    glfw::Key::CapsLock,
+    glfw::Key::ScrollLock,
+    glfw::Key::NumLock,
+    glfw::Key::PrintScreen,
+    glfw::Key::Pause,
+    glfw::Key::F1,
+    glfw::Key::F2,
+    glfw::Key::F3,
+    glfw::Key::F4,
+    glfw::Key::F5,
+    glfw::Key::F6,
+    glfw::Key::F7,
+    glfw::Key::F8,
+    glfw::Key::F9,
+    glfw::Key::F10,
+    glfw::Key::F11,
+    glfw::Key::F12,
+    glfw::Key::F13,
+    glfw::Key::F14,
+    glfw::Key::F15,
+    glfw::Key::F16,
+    glfw::Key::F17,
+    glfw::Key::F18,
+    glfw::Key::F19,
+    glfw::Key::F20,
+    glfw::Key::F21,
+    glfw::Key::F22,
+    glfw::Key::F23,
+    glfw::Key::F24,
+    glfw::Key::F25,
+    glfw::Key::Kp0,
+    glfw::Key::Kp1,
+    glfw::Key::Kp2,
+    glfw::Key::Kp3,
+    glfw::Key::Kp4,
+    glfw::Key::Kp5,
+    glfw::Key::Kp6,
+    glfw::Key::Kp7,
+    glfw::Key::Kp8,
+    glfw::Key::Kp9,
+    glfw::Key::KpDecimal,
+    glfw::Key::KpDivide,
+    glfw::Key::KpMultiply,
+    glfw::Key::KpSubtract,
+    glfw::Key::KpAdd,
+    glfw::Key::KpEnter,
+    glfw::Key::KpEqual,
+    glfw::Key::LeftShift,
+    glfw::Key::LeftControl,
+    glfw::Key::LeftAlt,
+    glfw::Key::LeftSuper,
+    glfw::Key::RightShift,
+    glfw::Key::RightControl,
+    glfw::Key::RightAlt,
+    glfw::Key::RightSuper,
+    glfw::Key::Menu,
+    glfw::Key::Unknown,
+];
@@ -0,0 +1,83 @@
+//! Record window events and per-frame random seeds to a tape, then play
+//! them back to reproduce an interactive piece frame for frame.
+//!
+//! # Big Idea
+//!
+//! Every frame the application hands its live events to a `Replay`, along
+//! with the current time, and gets a `TapeFrame` back. The frame holds the
+//! events, time, and random seed the application should use for that frame.
+//!
+//! While recording, the live values are returned and written to the tape.
+//! While playing, the live values are replaced by the recorded ones, so the
+//! piece sees exactly what it saw when the tape was made. Close events from
+//! the window are still passed through, so a replay can always be stopped.
+//!
+//! Tapes are plain text, one frame or event per line, so they can be read
+//! and trimmed by hand when attached to a bug report:
+//!
+//! ```text
+//! draw2d-tape 1
+//! frame 0.5 12345
+//! event 0.49 cursor_pos 10 20
+//! event 0.495 key Space 57 Press 0
+//! ```
+//!
+//! File drop events aren't recorded because the paths only make sense on
+//! the machine where the tape was made.
+//!
+//! Tapes hold glfw window events, so replay isn't built for Android.
+
+mod event_codec;
+mod event_tape;
+mod keys;
+mod playback;
+
+use std::path::PathBuf;
+
+/// A window event and the glfw time when it was received.
+pub type TimedEvent = (f64, glfw::WindowEvent);
+
+/// Everything an application needs to draw a single frame reproducibly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TapeFrame {
+    /// The time the frame started, in seconds.
+    pub time: f64,
+
+    /// A seed for any randomness used by the frame.
+    pub seed: u64,
+
+    /// The window events received since the previous frame.
+    pub events: Vec<TimedEvent>,
+}
+
+/// Recorded frames in the order they were drawn.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EventTape {
+    pub frames: Vec<TapeFrame>,
+}
+
+/// What a `Replay` does with each frame.
+#[derive(Debug)]
+enum ReplayMode {
+    /// Live values are used and nothing is kept.
+    Live,
+
+    /// Live values are used and written to the tape at the path once the
+    /// replay is finished.
+    Recording { tape: EventTape, path: PathBuf },
+
+    /// Recorded values replace the live ones until the tape runs out.
+    Playing { tape: EventTape, next_frame: usize },
+}
+
+/// Records or plays back the values which make a frame reproducible.
+#[derive(Debug)]
+pub struct Replay {
+    mode: ReplayMode,
+
+    /// Live seeds are derived from this seed and the frame number.
+    base_seed: u64,
+
+    /// The number of frames handed out so far.
+    frame_number: u64,
+}
@@ -0,0 +1,216 @@
+use super::{EventTape, Replay, ReplayMode, TapeFrame, TimedEvent};
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+impl Replay {
+    /// Use the live events and time. Seeds are derived from the base seed
+    /// and the frame number, so they're the same every run.
+    pub fn live(base_seed: u64) -> Self {
+        Self {
+            mode: ReplayMode::Live,
+            base_seed,
+            frame_number: 0,
+        }
+    }
+
+    /// Use the live events and time, and write every frame to a tape at
+    /// the path when the replay is finished or dropped.
+    pub fn record(path: impl Into<PathBuf>, base_seed: u64) -> Self {
+        Self {
+            mode: ReplayMode::Recording {
+                tape: EventTape::default(),
+                path: path.into(),
+            },
+            base_seed,
+            frame_number: 0,
+        }
+    }
+
+    /// Play back a tape. Once every recorded frame has been used, the live
+    /// events are used again.
+    pub fn play(tape: EventTape) -> Self {
+        Self {
+            mode: ReplayMode::Playing {
+                tape,
+                next_frame: 0,
+            },
+            base_seed: 0,
+            frame_number: 0,
+        }
+    }
+
+    /// Play back a tape read from a file.
+    pub fn play_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::play(EventTape::read_file(path)?))
+    }
+
+    /// The values to draw the next frame with.
+    ///
+    /// `time` is the current time in seconds, usually `glfw.get_time()`,
+    /// and `live_events` are the events polled since the previous frame.
+    pub fn next_frame(
+        &mut self,
+        time: f64,
+        live_events: Vec<TimedEvent>,
+    ) -> TapeFrame {
+        let live = TapeFrame {
+            time,
+            seed: frame_seed(self.base_seed, self.frame_number),
+            events: live_events,
+        };
+        self.frame_number += 1;
+        match &mut self.mode {
+            ReplayMode::Live => live,
+            ReplayMode::Recording { tape, .. } => {
+                tape.frames.push(live.clone());
+                live
+            }
+            ReplayMode::Playing { tape, next_frame } => {
+                let mut frame = match tape.frames.get(*next_frame) {
+                    Some(frame) => frame.clone(),
+                    None => return live,
+                };
+                *next_frame += 1;
+                // the window can still be closed while a tape is playing
+                frame.events.extend(
+                    live.events.into_iter().filter(|(_, event)| {
+                        *event == glfw::WindowEvent::Close
+                    }),
+                );
+                frame
+            }
+        }
+    }
+
+    /// True while frames are being written to a tape.
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, ReplayMode::Recording { .. })
+    }
+
+    /// True while recorded frames are being played back.
+    pub fn is_playing(&self) -> bool {
+        match &self.mode {
+            ReplayMode::Playing { tape, next_frame } => {
+                *next_frame < tape.frames.len()
+            }
+            _ => false,
+        }
+    }
+
+    /// The number of frames handed out so far.
+    pub fn frame_number(&self) -> u64 {
+        self.frame_number
+    }
+
+    /// Stop recording and write the tape. Playing and live replays have
+    /// nothing to finish.
+    pub fn finish(&mut self) -> Result<()> {
+        let mode = std::mem::replace(&mut self.mode, ReplayMode::Live);
+        match mode {
+            ReplayMode::Recording { tape, path } => tape.write_file(path),
+            mode => {
+                self.mode = mode;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        if let Err(error) = self.finish() {
+            log::error!("unable to finish the replay: {:?}", error);
+        }
+    }
+}
+
+/// A well mixed seed for a frame, using the SplitMix64 finalizer so nearby
+/// frames get unrelated seeds.
+fn frame_seed(base_seed: u64, frame_number: u64) -> u64 {
+    let mut z = base_seed.wrapping_add(
+        frame_number
+            .wrapping_add(1)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15),
+    );
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use glfw::WindowEvent;
+
+    fn events(event: WindowEvent) -> Vec<TimedEvent> {
+        vec![(0.0, event)]
+    }
+
+    #[test]
+    fn live_seeds_should_be_reproducible() {
+        let mut first = Replay::live(3);
+        let mut second = Replay::live(3);
+        let a = first.next_frame(0.0, vec![]).seed;
+        let b = first.next_frame(0.0, vec![]).seed;
+        assert_ne!(a, b);
+        assert_eq!(second.next_frame(5.0, vec![]).seed, a);
+        assert_ne!(Replay::live(4).next_frame(0.0, vec![]).seed, a);
+    }
+
+    #[test]
+    fn playing_should_replace_live_values_until_the_tape_ends() {
+        let recorded = TapeFrame {
+            time: 1.0,
+            seed: 99,
+            events: events(WindowEvent::Focus(true)),
+        };
+        let mut replay = Replay::play(EventTape {
+            frames: vec![recorded.clone()],
+        });
+        assert!(replay.is_playing());
+        let live = events(WindowEvent::CursorPos(1.0, 2.0));
+        assert_eq!(replay.next_frame(20.0, live.clone()), recorded);
+        assert!(!replay.is_playing());
+
+        let after = replay.next_frame(21.0, live.clone());
+        assert_eq!(after.time, 21.0);
+        assert_eq!(after.events, live);
+    }
+
+    #[test]
+    fn playing_should_pass_close_events_through() {
+        let mut replay = Replay::play(EventTape {
+            frames: vec![TapeFrame {
+                time: 0.0,
+                seed: 0,
+                events: vec![],
+            }],
+        });
+        let frame = replay.next_frame(0.0, events(WindowEvent::Close));
+        assert_eq!(frame.events, events(WindowEvent::Close));
+    }
+
+    #[test]
+    fn recordings_should_play_back_identically() {
+        let path = std::env::temp_dir()
+            .join(format!("draw2d-replay-test-{}.tape", std::process::id()));
+        let mut recording = Replay::record(&path, 11);
+        let recorded = vec![
+            recording.next_frame(0.5, events(WindowEvent::Scroll(0.0, 1.0))),
+            recording.next_frame(0.75, vec![]),
+        ];
+        assert!(recording.is_recording());
+        recording.finish().unwrap();
+        assert!(!recording.is_recording());
+
+        let mut playback = Replay::play_file(&path).unwrap();
+        let played = vec![
+            playback.next_frame(9.0, vec![]),
+            playback.next_frame(9.5, vec![]),
+        ];
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(played, recorded);
+    }
+}
@@ -0,0 +1,155 @@
+use super::{tessellate, SvgOptions, VectorDrawing};
+
+use crate::{
+    geometry::Transform2d,
+    graphics::{layer::Batch, texture_atlas::TextureHandle},
+};
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use usvg::NodeExt;
+
+impl VectorDrawing {
+    /// Load and tessellate an SVG file.
+    pub fn from_file<P>(path: P, options: &SvgOptions) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let data = std::fs::read(path.as_ref()).with_context(|| {
+            format!("unable to read svg file {:?}", path.as_ref())
+        })?;
+        let usvg_options = usvg::Options {
+            resources_dir: path.as_ref().parent().map(Path::to_path_buf),
+            ..Default::default()
+        };
+        let tree = usvg::Tree::from_data(&data, &usvg_options)
+            .with_context(|| format!("unable to parse {:?}", path.as_ref()))?;
+        Self::from_tree(&tree, options)
+    }
+
+    /// Parse and tessellate an SVG document.
+    pub fn from_svg_str(text: &str, options: &SvgOptions) -> Result<Self> {
+        let tree = usvg::Tree::from_str(text, &usvg::Options::default())
+            .with_context(|| "unable to parse svg document")?;
+        Self::from_tree(&tree, options)
+    }
+
+    /// Tessellate every visible path in a parsed document.
+    fn from_tree(tree: &usvg::Tree, options: &SvgOptions) -> Result<Self> {
+        let view_box = tree.svg_node().view_box.rect;
+        let (left, bottom) = (view_box.x(), view_box.y() + view_box.height());
+
+        let mut vertices = vec![];
+        for node in tree.root().descendants() {
+            if is_definition(&node) {
+                continue;
+            }
+            if let usvg::NodeKind::Path(ref path) = *node.borrow() {
+                if path.visibility != usvg::Visibility::Visible {
+                    continue;
+                }
+                let transform = node.abs_transform();
+                let to_world = |x: f64, y: f64| {
+                    let (x, y) = transform.apply(x, y);
+                    [(x - left) as f32, (bottom - y) as f32]
+                };
+                tessellate::path(path, &to_world, options, &mut vertices)?;
+            }
+        }
+
+        Ok(Self {
+            width: view_box.width() as f32,
+            height: view_box.height() as f32,
+            vertices,
+        })
+    }
+
+    /// Build a batch which draws the drawing with a transform applied to
+    /// every vertex.
+    pub fn to_batch(&self, transform: &Transform2d) -> Batch {
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let [x, y] = vertex.pos;
+                let pos = transform.transform_point(&[x, y].into());
+                crate::graphics::vertex::Vertex2d {
+                    pos: [pos.x, pos.y],
+                    ..*vertex
+                }
+            })
+            .collect();
+        Batch {
+            texture_handle: TextureHandle::default(),
+            vertices,
+            ..Batch::empty()
+        }
+    }
+}
+
+/// True when the node is only referenced by other nodes (clip paths, masks,
+/// patterns, etc...) rather than being drawn directly.
+fn is_definition(node: &usvg::Node) -> bool {
+    node.ancestors().any(|ancestor| {
+        matches!(
+            *ancestor.borrow(),
+            usvg::NodeKind::Defs
+                | usvg::NodeKind::ClipPath(_)
+                | usvg::NodeKind::Mask(_)
+                | usvg::NodeKind::Pattern(_)
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use indoc::indoc;
+
+    #[test]
+    fn from_svg_str_should_tessellate_filled_paths_with_y_up() -> Result<()> {
+        let drawing = VectorDrawing::from_svg_str(
+            indoc!(
+                r##"
+                <svg xmlns="http://www.w3.org/2000/svg"
+                     width="100" height="50" viewBox="0 0 100 50">
+                    <rect x="0" y="0" width="10" height="10" fill="#ff0000"/>
+                </svg>
+                "##
+            ),
+            &SvgOptions::default(),
+        )?;
+
+        assert_eq!(drawing.width, 100.0);
+        assert_eq!(drawing.height, 50.0);
+        assert_eq!(drawing.vertices.len(), 6);
+        for vertex in &drawing.vertices {
+            assert_eq!(vertex.rgba, [1.0, 0.0, 0.0, 1.0]);
+            assert!(vertex.pos[1] >= 40.0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn from_svg_str_should_skip_definitions() -> Result<()> {
+        let drawing = VectorDrawing::from_svg_str(
+            indoc!(
+                r##"
+                <svg xmlns="http://www.w3.org/2000/svg"
+                     width="10" height="10" viewBox="0 0 10 10">
+                    <defs>
+                        <clipPath id="clip">
+                            <rect width="5" height="5"/>
+                        </clipPath>
+                    </defs>
+                </svg>
+                "##
+            ),
+            &SvgOptions::default(),
+        )?;
+
+        assert!(drawing.vertices.is_empty());
+        Ok(())
+    }
+}
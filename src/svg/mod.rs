@@ -0,0 +1,43 @@
+//! Load SVG documents as tessellated, ready-to-draw geometry.
+//!
+//! This module is only available with the `svg` feature. Documents are
+//! parsed with `usvg` and each visible path's fill and stroke are tessellated
+//! into triangles with `lyon_tessellation`.
+//!
+//! Only solid colors are supported. Paths painted with gradients or patterns
+//! are skipped, and text must be converted to paths before loading.
+
+mod loader;
+mod tessellate;
+
+use crate::graphics::vertex::Vertex2d;
+
+/// Options which control how SVG documents are tessellated.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SvgOptions {
+    /// The maximum distance, in SVG units, between a curve and the line
+    /// segments used to approximate it.
+    pub tolerance: f32,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self { tolerance: 0.1 }
+    }
+}
+
+/// A tessellated vector drawing which can be reused to build batches.
+///
+/// Vertices use world-style coordinates: the origin is the bottom left corner
+/// of the SVG's view box and y points up. One SVG unit is one world unit.
+#[derive(Debug, Clone, Default)]
+pub struct VectorDrawing {
+    /// The width of the SVG's view box.
+    pub width: f32,
+
+    /// The height of the SVG's view box.
+    pub height: f32,
+
+    /// Triangle-list vertices for every fill and stroke, back to front.
+    pub vertices: Vec<Vertex2d>,
+}
@@ -0,0 +1,179 @@
+use super::SvgOptions;
+
+use crate::graphics::vertex::Vertex2d;
+
+use anyhow::{anyhow, Result};
+use lyon_tessellation::{
+    math::{point, Point},
+    path::Path,
+    BuffersBuilder, FillOptions, FillTessellator, LineCap, LineJoin,
+    StrokeOptions, StrokeTessellator, VertexBuffers,
+};
+
+/// Tessellate a path's fill and stroke, appending triangle-list vertices.
+///
+/// `to_world` maps a point in the path's coordinate space into the drawing's
+/// coordinate space.
+pub fn path(
+    svg_path: &usvg::Path,
+    to_world: &dyn Fn(f64, f64) -> [f32; 2],
+    options: &SvgOptions,
+    vertices: &mut Vec<Vertex2d>,
+) -> Result<()> {
+    let path = build_path(&svg_path.data, to_world);
+
+    if let Some(fill) = &svg_path.fill {
+        if let Some(rgba) = rgba(&fill.paint, fill.opacity.value()) {
+            let fill_rule = match fill.rule {
+                usvg::FillRule::NonZero => lyon_tessellation::FillRule::NonZero,
+                usvg::FillRule::EvenOdd => lyon_tessellation::FillRule::EvenOdd,
+            };
+            let fill_options = FillOptions::tolerance(options.tolerance)
+                .with_fill_rule(fill_rule);
+            let mut buffers: VertexBuffers<Point, u32> = VertexBuffers::new();
+            FillTessellator::new()
+                .tessellate(
+                    &path,
+                    &fill_options,
+                    &mut BuffersBuilder::new(
+                        &mut buffers,
+                        |vertex: lyon_tessellation::FillVertex| {
+                            vertex.position()
+                        },
+                    ),
+                )
+                .map_err(|error| anyhow!("unable to fill path {:?}", error))?;
+            append_triangles(&buffers, rgba, vertices);
+        }
+    }
+
+    if let Some(stroke) = &svg_path.stroke {
+        if let Some(rgba) = rgba(&stroke.paint, stroke.opacity.value()) {
+            let stroke_options = StrokeOptions::tolerance(options.tolerance)
+                .with_line_width(stroke.width.value() as f32 * scale(to_world))
+                .with_miter_limit(stroke.miterlimit.value() as f32)
+                .with_line_cap(match stroke.linecap {
+                    usvg::LineCap::Butt => LineCap::Butt,
+                    usvg::LineCap::Round => LineCap::Round,
+                    usvg::LineCap::Square => LineCap::Square,
+                })
+                .with_line_join(match stroke.linejoin {
+                    usvg::LineJoin::Miter => LineJoin::Miter,
+                    usvg::LineJoin::Round => LineJoin::Round,
+                    usvg::LineJoin::Bevel => LineJoin::Bevel,
+                });
+            let mut buffers: VertexBuffers<Point, u32> = VertexBuffers::new();
+            StrokeTessellator::new()
+                .tessellate(
+                    &path,
+                    &stroke_options,
+                    &mut BuffersBuilder::new(
+                        &mut buffers,
+                        |vertex: lyon_tessellation::StrokeVertex| {
+                            vertex.position()
+                        },
+                    ),
+                )
+                .map_err(|error| {
+                    anyhow!("unable to stroke path {:?}", error)
+                })?;
+            append_triangles(&buffers, rgba, vertices);
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert usvg path data into a lyon path in world coordinates.
+fn build_path(
+    data: &usvg::PathData,
+    to_world: &dyn Fn(f64, f64) -> [f32; 2],
+) -> Path {
+    let to_point = |x: f64, y: f64| {
+        let [x, y] = to_world(x, y);
+        point(x, y)
+    };
+    let mut builder = Path::builder();
+    let mut in_subpath = false;
+    for segment in data.iter() {
+        match *segment {
+            usvg::PathSegment::MoveTo { x, y } => {
+                if in_subpath {
+                    builder.end(false);
+                }
+                builder.begin(to_point(x, y));
+                in_subpath = true;
+            }
+            usvg::PathSegment::LineTo { x, y } => {
+                builder.line_to(to_point(x, y));
+            }
+            usvg::PathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                builder.cubic_bezier_to(
+                    to_point(x1, y1),
+                    to_point(x2, y2),
+                    to_point(x, y),
+                );
+            }
+            usvg::PathSegment::ClosePath => {
+                if in_subpath {
+                    builder.end(true);
+                    in_subpath = false;
+                }
+            }
+        }
+    }
+    if in_subpath {
+        builder.end(false);
+    }
+    builder.build()
+}
+
+/// The uniform scale applied by a coordinate mapping, used to convert stroke
+/// widths into world units.
+fn scale(to_world: &dyn Fn(f64, f64) -> [f32; 2]) -> f32 {
+    let [ox, oy] = to_world(0.0, 0.0);
+    let [x1, y1] = to_world(1.0, 0.0);
+    let [x2, y2] = to_world(0.0, 1.0);
+    let sx = ((x1 - ox).powi(2) + (y1 - oy).powi(2)).sqrt();
+    let sy = ((x2 - ox).powi(2) + (y2 - oy).powi(2)).sqrt();
+    (sx * sy).sqrt()
+}
+
+/// The color for a paint, or None if the paint isn't a solid color.
+fn rgba(paint: &usvg::Paint, opacity: f64) -> Option<[f32; 4]> {
+    match paint {
+        usvg::Paint::Color(color) => Some([
+            color.red as f32 / 255.0,
+            color.green as f32 / 255.0,
+            color.blue as f32 / 255.0,
+            opacity as f32,
+        ]),
+        usvg::Paint::Link(id) => {
+            log::warn!("skipping unsupported svg paint server {:?}", id);
+            None
+        }
+    }
+}
+
+/// Expand indexed triangles into colored triangle-list vertices.
+fn append_triangles(
+    buffers: &VertexBuffers<Point, u32>,
+    rgba: [f32; 4],
+    vertices: &mut Vec<Vertex2d>,
+) {
+    vertices.extend(buffers.indices.iter().map(|index| {
+        let position = buffers.vertices[*index as usize];
+        Vertex2d {
+            pos: [position.x, position.y],
+            uv: [0.0, 0.0],
+            rgba,
+        }
+    }));
+}
@@ -0,0 +1,182 @@
+use super::{Comparison, Tolerance};
+
+use anyhow::{bail, Result};
+use image::{Rgba, RgbaImage};
+
+/// The largest possible YIQ delta, between black and white.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+impl Tolerance {
+    /// Every pixel must match exactly.
+    pub fn exact() -> Self {
+        Self {
+            threshold: 0.0,
+            max_differing_pixels: 0.0,
+        }
+    }
+
+    pub fn new(threshold: f32, max_differing_pixels: f32) -> Self {
+        Self {
+            threshold,
+            max_differing_pixels,
+        }
+    }
+}
+
+impl Default for Tolerance {
+    /// Ignore differences too small to notice and allow no other changes.
+    fn default() -> Self {
+        Self::new(0.1, 0.0)
+    }
+}
+
+impl Comparison {
+    /// The fraction of pixels which are different.
+    pub fn differing_fraction(&self) -> f32 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f32 / self.total_pixels as f32
+        }
+    }
+
+    /// True when few enough pixels are different.
+    pub fn passes(&self, tolerance: &Tolerance) -> bool {
+        self.differing_fraction() <= tolerance.max_differing_pixels
+    }
+}
+
+/// Compare an image with the expected image, pixel by pixel.
+///
+/// Fails when the images aren't the same size.
+pub fn compare(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: &Tolerance,
+) -> Result<Comparison> {
+    if actual.dimensions() != expected.dimensions() {
+        bail!(
+            "the image is {:?} but the reference is {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        );
+    }
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    let mut differing_pixels = 0;
+    let mut max_delta: f32 = 0.0;
+    let pixels = actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff.pixels_mut());
+    for ((actual, expected), diff) in pixels {
+        let delta = color_delta(actual, expected);
+        max_delta = max_delta.max(delta);
+        *diff = if delta > tolerance.threshold {
+            differing_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let gray = 255 - ((255 - luma(expected)) as f32 * 0.1) as u8;
+            Rgba([gray, gray, gray, 255])
+        };
+    }
+    Ok(Comparison {
+        differing_pixels,
+        total_pixels: expected.width() as u64 * expected.height() as u64,
+        max_delta,
+        diff,
+    })
+}
+
+/// The perceptual difference between two colors, from 0 for identical
+/// colors to 1 for the most different colors. Black and white are about
+/// 0.97 apart.
+///
+/// Colors are blended over white, then compared in the YIQ color space with
+/// brightness weighted most heavily. This is the metric used by pixelmatch.
+pub fn color_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    let (ya, ia, qa) = yiq(a);
+    let (yb, ib, qb) = yiq(b);
+    let (y, i, q) = (ya - yb, ia - ib, qa - qb);
+    let delta = 0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q;
+    (delta / MAX_YIQ_DELTA).sqrt().min(1.0)
+}
+
+fn yiq(color: &Rgba<u8>) -> (f32, f32, f32) {
+    let alpha = color[3] as f32 / 255.0;
+    let blend = |channel: u8| 255.0 + (channel as f32 - 255.0) * alpha;
+    let (r, g, b) = (blend(color[0]), blend(color[1]), blend(color[2]));
+    (
+        r * 0.298_895_3 + g * 0.586_622_5 + b * 0.114_482_2,
+        r * 0.595_978 - g * 0.274_176_1 - b * 0.321_801_9,
+        r * 0.211_470_2 - g * 0.522_617_2 + b * 0.311_147,
+    )
+}
+
+fn luma(color: &Rgba<u8>) -> u8 {
+    yiq(color).0.round().clamp(0.0, 255.0) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba(color))
+    }
+
+    #[test]
+    fn color_delta_should_grow_with_contrast() {
+        let black = Rgba([0, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        assert_eq!(color_delta(&black, &black), 0.0);
+        let gray = Rgba([128, 128, 128, 255]);
+        assert!(color_delta(&black, &white) > 0.95);
+        assert!(color_delta(&black, &gray) < color_delta(&black, &white));
+    }
+
+    #[test]
+    fn transparent_colors_should_blend_over_white() {
+        let clear_black = Rgba([0, 0, 0, 0]);
+        let white = Rgba([255, 255, 255, 255]);
+        assert!(color_delta(&clear_black, &white) < 1e-3);
+    }
+
+    #[test]
+    fn small_differences_should_be_ignored() {
+        let expected = solid(4, 4, [100, 100, 100, 255]);
+        let actual = solid(4, 4, [101, 100, 99, 255]);
+
+        let comparison =
+            compare(&actual, &expected, &Tolerance::default()).unwrap();
+
+        assert_eq!(comparison.differing_pixels, 0);
+        assert!(comparison.passes(&Tolerance::default()));
+    }
+
+    #[test]
+    fn differing_pixels_should_be_red_in_the_diff() {
+        let expected = solid(2, 1, [0, 0, 0, 255]);
+        let mut actual = expected.clone();
+        actual.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+
+        let comparison =
+            compare(&actual, &expected, &Tolerance::default()).unwrap();
+
+        assert_eq!(comparison.differing_pixels, 1);
+        assert_eq!(comparison.differing_fraction(), 0.5);
+        assert!(!comparison.passes(&Tolerance::default()));
+        assert!(comparison.passes(&Tolerance::new(0.1, 0.5)));
+        assert_eq!(comparison.diff.get_pixel(1, 0), &Rgba([255, 0, 0, 255]));
+        assert_ne!(comparison.diff.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn different_sizes_should_fail() {
+        let result = compare(
+            &solid(2, 2, [0; 4]),
+            &solid(2, 3, [0; 4]),
+            &Tolerance::exact(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use super::{compare, Tolerance, UPDATE_GOLDEN_VAR};
+
+use anyhow::{bail, Context, Result};
+use image::RgbaImage;
+use std::path::{Path, PathBuf};
+
+/// Panic unless the image matches the reference PNG. See `check_golden`.
+#[track_caller]
+pub fn assert_golden(
+    actual: &RgbaImage,
+    reference: impl AsRef<Path>,
+    tolerance: &Tolerance,
+) {
+    if let Err(error) = check_golden(actual, reference, tolerance) {
+        panic!("{:#}", error);
+    }
+}
+
+/// Check an image against the reference PNG.
+///
+/// The reference is written instead when it doesn't exist yet or when
+/// `DRAW2D_UPDATE_GOLDEN` is set. When the images don't match, the actual
+/// and diff images are written beside the reference and an error describing
+/// the difference is returned.
+pub fn check_golden(
+    actual: &RgbaImage,
+    reference: impl AsRef<Path>,
+    tolerance: &Tolerance,
+) -> Result<()> {
+    let reference = reference.as_ref();
+    if !reference.exists() || should_update() {
+        if let Some(parent) = reference.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        actual.save(reference).with_context(|| {
+            format!("unable to write the golden image {:?}", reference)
+        })?;
+        log::info!("wrote golden image {:?}", reference);
+        return Ok(());
+    }
+
+    let expected = image::open(reference)
+        .with_context(|| {
+            format!("unable to read the golden image {:?}", reference)
+        })?
+        .to_rgba8();
+    let actual_path = sibling(reference, "actual");
+    if actual.dimensions() != expected.dimensions() {
+        actual.save(&actual_path)?;
+    }
+    let comparison = compare(actual, &expected, tolerance)
+        .with_context(|| format!("{:?} doesn't match", reference))?;
+    if comparison.passes(tolerance) {
+        return Ok(());
+    }
+
+    let diff_path = sibling(reference, "diff");
+    actual.save(&actual_path)?;
+    comparison.diff.save(&diff_path)?;
+    bail!(
+        "{:?} doesn't match: {} of {} pixels differ ({:.2}%, max delta \
+         {:.3}). Wrote {:?} and {:?}",
+        reference,
+        comparison.differing_pixels,
+        comparison.total_pixels,
+        comparison.differing_fraction() * 100.0,
+        comparison.max_delta,
+        actual_path,
+        diff_path,
+    )
+}
+
+fn should_update() -> bool {
+    std::env::var(UPDATE_GOLDEN_VAR)
+        .map(|value| !value.is_empty() && value != "0")
+        .unwrap_or(false)
+}
+
+/// The path for an output next to the reference, like `name.diff.png`.
+fn sibling(reference: &Path, suffix: &str) -> PathBuf {
+    let stem = reference
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    reference.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use image::Rgba;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "draw2d-golden-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn missing_references_should_be_written() {
+        let dir = scratch_dir("missing");
+        let reference = dir.join("square.png");
+        let image = RgbaImage::from_pixel(3, 3, Rgba([10, 20, 30, 255]));
+
+        check_golden(&image, &reference, &Tolerance::exact()).unwrap();
+        check_golden(&image, &reference, &Tolerance::exact()).unwrap();
+
+        assert!(reference.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn mismatches_should_write_actual_and_diff_images() {
+        let dir = scratch_dir("mismatch");
+        let reference = dir.join("square.png");
+        let expected = RgbaImage::from_pixel(3, 3, Rgba([0, 0, 0, 255]));
+        let actual = RgbaImage::from_pixel(3, 3, Rgba([255, 255, 255, 255]));
+        check_golden(&expected, &reference, &Tolerance::exact()).unwrap();
+
+        let error = check_golden(&actual, &reference, &Tolerance::default())
+            .unwrap_err();
+
+        assert!(error.to_string().contains("9 of 9 pixels"), "{}", error);
+        assert!(dir.join("square.actual.png").exists());
+        assert!(dir.join("square.diff.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn siblings_should_share_the_stem() {
+        assert_eq!(
+            sibling(Path::new("golden/quad.png"), "diff"),
+            PathBuf::from("golden/quad.diff.png")
+        );
+    }
+}
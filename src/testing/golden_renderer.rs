@@ -0,0 +1,38 @@
+use super::GoldenRenderer;
+
+use crate::{graphics::Graphics, GlfwWindow};
+
+use anyhow::Result;
+use image::RgbaImage;
+
+impl GoldenRenderer {
+    /// Create a hidden window and the graphics to render with.
+    ///
+    /// A display and a vulkan device are required, so tests which use this
+    /// should skip themselves when it fails on a machine without either.
+    pub fn new() -> Result<Self> {
+        let window = GlfwWindow::hidden(64, 64)?;
+        let graphics = Graphics::new(&window)?;
+        Ok(Self { graphics, window })
+    }
+
+    /// The graphics used for rendering. Add layers and draw to them before
+    /// calling `render`.
+    pub fn graphics(&mut self) -> &mut Graphics {
+        &mut self.graphics
+    }
+
+    /// The hidden window.
+    pub fn window(&self) -> &GlfwWindow {
+        &self.window
+    }
+
+    /// Render the layers to an image of any size.
+    ///
+    /// Shapes on the canvas are flushed into a layer by a call to
+    /// `Graphics::render` first.
+    pub fn render(&mut self, width: u32, height: u32) -> Result<RgbaImage> {
+        self.graphics.render(&self.window)?;
+        self.graphics.export_rgba(width, height)
+    }
+}
@@ -0,0 +1,66 @@
+//! Support for rendering regression tests with golden images.
+//!
+//! # Big Idea
+//!
+//! A test draws some layers with a `GoldenRenderer`, which renders offscreen
+//! through a hidden window, then checks the pixels against a reference PNG
+//! committed next to the test with `assert_golden`.
+//!
+//! Pixels are compared with a perceptual color difference, so tiny changes
+//! from driver rounding don't fail the test while visible changes do. When a
+//! comparison fails, `<name>.actual.png` and `<name>.diff.png` are written
+//! beside the reference. The diff image shows the reference faded to gray
+//! with every differing pixel in red.
+//!
+//! Missing references are written from the rendered image and the test
+//! passes. Set `DRAW2D_UPDATE_GOLDEN=1` to rewrite every reference after an
+//! intentional change.
+
+mod compare;
+mod golden;
+mod golden_renderer;
+
+pub use self::{
+    compare::{color_delta, compare},
+    golden::{assert_golden, check_golden},
+};
+
+use crate::{graphics::Graphics, GlfwWindow};
+
+use image::RgbaImage;
+
+/// The environment variable which rewrites every golden image.
+pub const UPDATE_GOLDEN_VAR: &str = "DRAW2D_UPDATE_GOLDEN";
+
+/// How different two images may be and still match.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Tolerance {
+    /// The largest color difference, from 0 to 1, which is ignored. See
+    /// `color_delta`.
+    pub threshold: f32,
+
+    /// The fraction of pixels, from 0 to 1, which may be different.
+    pub max_differing_pixels: f32,
+}
+
+/// The result of comparing two images.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    /// The number of pixels whose difference is above the threshold.
+    pub differing_pixels: u64,
+
+    pub total_pixels: u64,
+
+    /// The largest color difference between any two pixels.
+    pub max_delta: f32,
+
+    /// The expected image faded to gray, with differing pixels in red.
+    pub diff: RgbaImage,
+}
+
+/// Renders layers offscreen for golden image tests.
+pub struct GoldenRenderer {
+    // declared first so the graphics are dropped before the window
+    graphics: Graphics,
+    window: GlfwWindow,
+}
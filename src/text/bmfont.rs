@@ -0,0 +1,235 @@
+use super::{BmFont, BmFontChar, GlyphPixels};
+
+use anyhow::{bail, Context, Result};
+use image::{GenericImageView, RgbaImage};
+use std::{collections::HashMap, path::Path};
+
+impl BmFont {
+    /// Read a font from a `.fnt` file in the BMFont text format.
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| {
+            format!("unable to read bitmap font {:?}", path)
+        })?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid bitmap font {:?}", path))
+    }
+
+    /// Parse a font in the BMFont text format.
+    ///
+    /// Only the `common`, `page`, and `char` lines are used. Kerning pairs
+    /// and the font's `info` are ignored.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut common = None;
+        let mut pages = vec![];
+        let mut chars = vec![];
+        for (line_index, line) in text.lines().enumerate() {
+            let (tag, values) = parse_line(line)
+                .with_context(|| format!("line {}", line_index + 1))?;
+            let value = |key: &str| -> Result<&str> {
+                values.get(key).copied().with_context(|| {
+                    format!("line {}: missing {:?}", line_index + 1, key)
+                })
+            };
+            let number = |key: &str| -> Result<f32> {
+                value(key)?.parse::<f32>().with_context(|| {
+                    format!("line {}: {:?} isn't a number", line_index + 1, key)
+                })
+            };
+            match tag {
+                "common" => {
+                    common = Some((
+                        number("lineHeight")?,
+                        number("base")?,
+                        (number("scaleW")?, number("scaleH")?),
+                    ));
+                }
+                "page" => {
+                    let id = number("id")? as usize;
+                    if id != pages.len() {
+                        bail!(
+                            "line {}: expected page {} but found page {}",
+                            line_index + 1,
+                            pages.len(),
+                            id
+                        );
+                    }
+                    pages.push(value("file")?.into());
+                }
+                "char" => {
+                    let id = number("id")? as u32;
+                    let id = match std::char::from_u32(id) {
+                        Some(id) => id,
+                        None => bail!(
+                            "line {}: {} isn't a character",
+                            line_index + 1,
+                            id
+                        ),
+                    };
+                    chars.push(BmFontChar {
+                        id,
+                        x: number("x")?,
+                        y: number("y")?,
+                        width: number("width")?,
+                        height: number("height")?,
+                        x_offset: number("xoffset")?,
+                        y_offset: number("yoffset")?,
+                        x_advance: number("xadvance")?,
+                        page: number("page")? as usize,
+                    });
+                }
+                _ => (),
+            }
+        }
+        let (line_height, base, page_size) = match common {
+            Some(common) => common,
+            None => bail!("the font has no `common` line"),
+        };
+        if let Some(c) = chars.iter().find(|c| c.page >= pages.len()) {
+            bail!("{:?} is on page {} which doesn't exist", c.id, c.page);
+        }
+        Ok(Self {
+            line_height,
+            base,
+            page_size,
+            pages,
+            chars,
+        })
+    }
+
+    /// Copy each character's pixels out of the font's page images, in page
+    /// order, positioned the same way as rasterized glyphs.
+    pub(super) fn glyph_pixels(
+        &self,
+        pages: &[RgbaImage],
+    ) -> Result<Vec<GlyphPixels>> {
+        self.chars
+            .iter()
+            .map(|c| {
+                let page = pages.get(c.page).with_context(|| {
+                    format!("{:?} is on missing page {}", c.id, c.page)
+                })?;
+                let (x, y) = (c.x as u32, c.y as u32);
+                let (width, height) = (c.width as u32, c.height as u32);
+                if x + width > page.width() || y + height > page.height() {
+                    bail!("{:?} is outside of page {}", c.id, c.page);
+                }
+                let rgba = page.view(x, y, width, height).to_image().into_raw();
+                let top = c.y_offset - self.base;
+                Ok(GlyphPixels {
+                    c: c.id,
+                    rgba,
+                    width,
+                    height,
+                    bounds: [
+                        c.x_offset,
+                        top,
+                        c.x_offset + c.width,
+                        top + c.height,
+                    ],
+                    advance: c.x_advance,
+                    color: false,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Split a line into its tag and `key=value` pairs. Quoted values may
+/// contain spaces.
+fn parse_line(line: &str) -> Result<(&str, HashMap<&str, &str>)> {
+    let line = line.trim();
+    let (tag, mut rest) = match line.find(char::is_whitespace) {
+        Some(end) => (&line[..end], &line[end..]),
+        None => (line, ""),
+    };
+    let mut values = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let equals = match rest.find('=') {
+            Some(equals) => equals,
+            None => bail!("expected `key=value` but found {:?}", rest),
+        };
+        let key = &rest[..equals];
+        rest = &rest[equals + 1..];
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = match quoted.find('"') {
+                Some(end) => end,
+                None => bail!("{:?} is missing a closing quote", key),
+            };
+            rest = &quoted[end + 1..];
+            &quoted[..end]
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            value
+        };
+        values.insert(key, value);
+    }
+    Ok((tag, values))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const FONT: &str = r#"info face="Pixel Sans" size=16 bold=0 padding=0,0,0,0
+common lineHeight=18 base=14 scaleW=128 scaleH=64 pages=1 packed=0
+page id=0 file="pixel sans_0.png"
+chars count=2
+char id=65   x=10  y=20  width=8  height=10 xoffset=1 yoffset=4 xadvance=9  page=0 chnl=15
+char id=32   x=0   y=0   width=0  height=0  xoffset=0 yoffset=0 xadvance=5  page=0 chnl=15
+kernings count=1
+kerning first=65 second=65 amount=-1
+"#;
+
+    #[test]
+    fn parse_should_read_the_common_page_and_char_lines() {
+        let font = BmFont::parse(FONT).unwrap();
+        assert_eq!(font.line_height, 18.0);
+        assert_eq!(font.base, 14.0);
+        assert_eq!(font.page_size, (128.0, 64.0));
+        assert_eq!(font.pages, vec![Path::new("pixel sans_0.png")]);
+        assert_eq!(font.chars.len(), 2);
+        assert_eq!(font.chars[0].id, 'A');
+        assert_eq!(font.chars[0].x_advance, 9.0);
+    }
+
+    #[test]
+    fn glyph_pixels_should_be_cut_from_the_pages() {
+        let mut page = RgbaImage::new(128, 64);
+        page.put_pixel(10, 20, image::Rgba([1, 2, 3, 4]));
+        let glyphs =
+            BmFont::parse(FONT).unwrap().glyph_pixels(&[page]).unwrap();
+
+        let a = &glyphs[0];
+        assert_eq!(a.bounds, [1.0, -10.0, 9.0, 0.0]);
+        assert_eq!((a.width, a.height), (8, 10));
+        assert_eq!(a.rgba[..8], [1, 2, 3, 4, 0, 0, 0, 0]);
+        assert_eq!(glyphs[1].c, ' ');
+        assert_eq!(glyphs[1].advance, 5.0);
+    }
+
+    #[test]
+    fn glyph_pixels_should_need_every_page() {
+        let font = BmFont::parse(FONT).unwrap();
+        assert!(font.glyph_pixels(&[]).is_err());
+        assert!(font.glyph_pixels(&[RgbaImage::new(16, 16)]).is_err());
+    }
+
+    #[test]
+    fn parse_should_reject_broken_fonts() {
+        assert!(BmFont::parse("page id=0 file=\"a.png\"").is_err());
+        assert!(BmFont::parse(
+            "common lineHeight=1 base=1 scaleW=1 scaleH=1 pages=1\n\
+             char id=65 x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 \
+             xadvance=1 page=1"
+        )
+        .is_err());
+        assert!(parse_line("page file=\"unterminated").is_err());
+    }
+}
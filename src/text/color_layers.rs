@@ -0,0 +1,224 @@
+//! Reads the layered glyphs in a font's `COLR` and `CPAL` tables, and finds
+//! the glyphs which use color formats that can't be drawn.
+//!
+//! ttf-parser doesn't read these tables, so only the parts used by
+//! `EmojiFont` are parsed here.
+
+use super::ColorLayer;
+
+use anyhow::{bail, Context, Result};
+
+/// The palette index which means the text's foreground color.
+const FOREGROUND_PALETTE_INDEX: u16 = 0xFFFF;
+
+/// The layers for a glyph in a `COLR` table, with colors from the first
+/// palette in the `CPAL` table.
+///
+/// Returns None when the glyph has no version 0 layers.
+pub(super) fn color_layers(
+    colr: &[u8],
+    cpal: Option<&[u8]>,
+    glyph_id: u16,
+) -> Result<Option<Vec<ColorLayer>>> {
+    let base_glyph_count = read_u16(colr, 2)? as usize;
+    let base_glyphs_offset = read_u32(colr, 4)? as usize;
+    let layers_offset = read_u32(colr, 8)? as usize;
+
+    let mut base_glyph = None;
+    for index in 0..base_glyph_count {
+        let record = base_glyphs_offset + index * 6;
+        if read_u16(colr, record)? == glyph_id {
+            base_glyph = Some((
+                read_u16(colr, record + 2)? as usize,
+                read_u16(colr, record + 4)? as usize,
+            ));
+            break;
+        }
+    }
+    let (first_layer, layer_count) = match base_glyph {
+        Some(base_glyph) => base_glyph,
+        None => return Ok(None),
+    };
+
+    let mut layers = Vec::with_capacity(layer_count);
+    for index in first_layer..first_layer + layer_count {
+        let record = layers_offset + index * 4;
+        let palette_index = read_u16(colr, record + 2)?;
+        let rgba = if palette_index == FOREGROUND_PALETTE_INDEX {
+            None
+        } else {
+            let cpal = cpal.context("the font has COLR layers but no CPAL")?;
+            Some(palette_color(cpal, palette_index)?)
+        };
+        layers.push(ColorLayer {
+            glyph_id: read_u16(colr, record)?,
+            rgba,
+        });
+    }
+    Ok(Some(layers))
+}
+
+/// True when a version 1 `COLR` table has a paint graph for the glyph.
+pub(super) fn has_paint_graph(colr: &[u8], glyph_id: u16) -> Result<bool> {
+    if read_u16(colr, 0)? == 0 {
+        return Ok(false);
+    }
+    let list_offset = read_u32(colr, 14)? as usize;
+    if list_offset == 0 {
+        return Ok(false);
+    }
+    let record_count = read_u32(colr, list_offset)? as usize;
+    for index in 0..record_count {
+        if read_u16(colr, list_offset + 4 + index * 6)? == glyph_id {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// True when an `SVG ` table has a document for the glyph.
+pub(super) fn has_svg_document(svg: &[u8], glyph_id: u16) -> Result<bool> {
+    let list_offset = read_u32(svg, 2)? as usize;
+    let record_count = read_u16(svg, list_offset)? as usize;
+    for index in 0..record_count {
+        let record = list_offset + 2 + index * 12;
+        let first = read_u16(svg, record)?;
+        let last = read_u16(svg, record + 2)?;
+        if (first..=last).contains(&glyph_id) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// A color from the first palette in a `CPAL` table, as rgba.
+fn palette_color(cpal: &[u8], palette_index: u16) -> Result<[u8; 4]> {
+    let palette_size = read_u16(cpal, 2)?;
+    if palette_index >= palette_size {
+        bail!(
+            "palette index {} is past the palette's {} colors",
+            palette_index,
+            palette_size
+        );
+    }
+    let colors_offset = read_u32(cpal, 8)? as usize;
+    let first_color = read_u16(cpal, 12)? as usize;
+    let record = colors_offset + (first_color + palette_index as usize) * 4;
+    let bgra = cpal
+        .get(record..record + 4)
+        .context("the CPAL table is truncated")?;
+    Ok([bgra[2], bgra[1], bgra[0], bgra[3]])
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let bytes = data
+        .get(offset..offset + 2)
+        .context("the color table is truncated")?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes = data
+        .get(offset..offset + 4)
+        .context("the color table is truncated")?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Glyph 5 has two layers, glyph 7 in palette color 1 and glyph 8 in the
+    /// foreground color.
+    const COLR: &[u8] = &[
+        0, 0, // version
+        0, 1, // base glyph count
+        0, 0, 0, 14, // base glyphs offset
+        0, 0, 0, 20, // layers offset
+        0, 2, // layer count
+        0, 5, 0, 0, 0, 2, // glyph 5, layers 0 and 1
+        0, 7, 0, 1, // glyph 7, palette index 1
+        0, 8, 0xFF, 0xFF, // glyph 8, foreground
+    ];
+
+    const CPAL: &[u8] = &[
+        0, 0, // version
+        0, 2, // palette size
+        0, 1, // palette count
+        0, 2, // color count
+        0, 0, 0, 14, // colors offset
+        0, 0, // first palette's first color
+        30, 20, 10, 255, // bgra
+        3, 2, 1, 4, // bgra
+    ];
+
+    #[test]
+    fn color_layers_should_read_palette_and_foreground_colors() {
+        let layers = color_layers(COLR, Some(CPAL), 5).unwrap().unwrap();
+        assert_eq!(
+            layers,
+            vec![
+                ColorLayer {
+                    glyph_id: 7,
+                    rgba: Some([1, 2, 3, 4]),
+                },
+                ColorLayer {
+                    glyph_id: 8,
+                    rgba: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn glyphs_without_layers_should_have_none() {
+        assert_eq!(color_layers(COLR, Some(CPAL), 6).unwrap(), None);
+    }
+
+    #[test]
+    fn broken_tables_should_be_errors() {
+        assert!(color_layers(COLR, None, 5).is_err());
+        assert!(color_layers(&COLR[..24], Some(CPAL), 5).is_err());
+        assert!(palette_color(CPAL, 2).is_err());
+    }
+
+    #[test]
+    fn version_0_tables_should_have_no_paint_graphs() {
+        assert!(!has_paint_graph(COLR, 5).unwrap());
+    }
+
+    #[test]
+    fn paint_graphs_should_be_found_in_version_1_tables() {
+        let colr = [
+            0, 1, // version
+            0, 0, // base glyph count
+            0, 0, 0, 0, // base glyphs offset
+            0, 0, 0, 0, // layers offset
+            0, 0, // layer count
+            0, 0, 0, 34, // base glyph list offset
+            0, 0, 0, 0, // layer list offset
+            0, 0, 0, 0, // clip list offset
+            0, 0, 0, 0, // var index map offset
+            0, 0, 0, 0, // item variation store offset
+            0, 0, 0, 1, // paint record count
+            0, 9, 0, 0, 0, 0, // glyph 9
+        ];
+        assert!(has_paint_graph(&colr, 9).unwrap());
+        assert!(!has_paint_graph(&colr, 5).unwrap());
+    }
+
+    #[test]
+    fn svg_documents_should_cover_glyph_ranges() {
+        let svg = [
+            0, 0, // version
+            0, 0, 0, 10, // document list offset
+            0, 0, 0, 0, // reserved
+            0, 1, // document count
+            0, 3, 0, 6, // glyphs 3 to 6
+            0, 0, 0, 0, 0, 0, 0, 0, // document offset and length
+        ];
+        assert!(has_svg_document(&svg, 3).unwrap());
+        assert!(has_svg_document(&svg, 6).unwrap());
+        assert!(!has_svg_document(&svg, 7).unwrap());
+    }
+}
@@ -0,0 +1,249 @@
+use super::{color_layers, ColorLayer, EmojiFont, GlyphPixels};
+
+use ab_glyph::{Font, FontRef, PxScale};
+use anyhow::{bail, Context, Result};
+use image::{imageops::FilterType, ImageFormat};
+use std::path::Path;
+use ttf_parser::{Face, GlyphId, RasterGlyphImage, RasterImageFormat, Tag};
+
+impl EmojiFont {
+    /// Read a color font file.
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .with_context(|| format!("unable to read emoji font {:?}", path))?;
+        Self::from_vec(data)
+            .with_context(|| format!("invalid emoji font {:?}", path))
+    }
+
+    /// Use the bytes of a color font.
+    pub fn from_vec(data: Vec<u8>) -> Result<Self> {
+        Face::from_slice(&data, 0).map_err(|error| {
+            anyhow::anyhow!("unable to parse the font: {:?}", error)
+        })?;
+        FontRef::try_from_slice(&data)?;
+        Ok(Self { data })
+    }
+
+    /// True when the font has a glyph for the character.
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.face().glyph_index(c).is_some()
+    }
+
+    /// Rasterize a character at a size in pixels, or None when the font has
+    /// no glyph for it.
+    ///
+    /// The image from the strike closest to the size is scaled to fit.
+    /// Characters with `COLR` layers have each layer's outline filled with
+    /// its palette color, layers in the foreground color are white.
+    /// Characters without any color data are drawn from their outlines.
+    ///
+    /// Characters which only have a `COLR` version 1 paint graph or an `SVG`
+    /// document are an error, rather than being drawn without their colors.
+    pub(super) fn rasterize(
+        &self,
+        c: char,
+        size: f32,
+    ) -> Result<Option<GlyphPixels>> {
+        let face = self.face();
+        let id = match face.glyph_index(c) {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let image = face
+            .glyph_raster_image(id, size.round().max(1.0) as u16)
+            .filter(|image| image.format == RasterImageFormat::PNG);
+        if let Some(image) = image {
+            return self.raster_glyph(c, id, image, size).map(Some);
+        }
+
+        if let Some(colr) = face.table_data(Tag::from_bytes(b"COLR")) {
+            let cpal = face.table_data(Tag::from_bytes(b"CPAL"));
+            if let Some(layers) = color_layers::color_layers(colr, cpal, id.0)?
+            {
+                return Ok(Some(self.layered_glyph(c, id, &layers, size)));
+            }
+            if color_layers::has_paint_graph(colr, id.0)? {
+                bail!(
+                    "{:?} is a COLR version 1 glyph, which isn't supported",
+                    c
+                );
+            }
+        }
+        if let Some(svg) = face.table_data(Tag::from_bytes(b"SVG ")) {
+            if color_layers::has_svg_document(svg, id.0)? {
+                bail!("{:?} is an SVG glyph, which isn't supported", c);
+            }
+        }
+        Ok(Some(GlyphPixels::from_outline(&self.font(), c, size)))
+    }
+
+    /// Scale a glyph's pre-rendered image to the size.
+    fn raster_glyph(
+        &self,
+        c: char,
+        id: GlyphId,
+        image: RasterGlyphImage,
+        size: f32,
+    ) -> Result<GlyphPixels> {
+        let decoded =
+            image::load_from_memory_with_format(image.data, ImageFormat::Png)
+                .with_context(|| format!("unable to decode the {:?} image", c))?
+                .to_rgba8();
+        let scale = size / image.pixels_per_em.max(1) as f32;
+        let width = (decoded.width() as f32 * scale).round().max(1.0) as u32;
+        let height = (decoded.height() as f32 * scale).round().max(1.0) as u32;
+        let resized = image::imageops::resize(
+            &decoded,
+            width,
+            height,
+            FilterType::Triangle,
+        );
+        Ok(GlyphPixels {
+            c,
+            rgba: resized.into_raw(),
+            width,
+            height,
+            bounds: raster_bounds(
+                (image.x as f32, image.y as f32),
+                (width as f32, height as f32),
+                scale,
+            ),
+            advance: self.advance(id, size),
+            color: true,
+        })
+    }
+
+    /// Fill each layer's outline with its color, bottom layer first.
+    fn layered_glyph(
+        &self,
+        c: char,
+        id: GlyphId,
+        layers: &[ColorLayer],
+        size: f32,
+    ) -> GlyphPixels {
+        let font = self.font();
+        let outlines: Vec<_> = layers
+            .iter()
+            .filter_map(|layer| {
+                let glyph = ab_glyph::GlyphId(layer.glyph_id)
+                    .with_scale(PxScale::from(size));
+                let rgba = layer.rgba.unwrap_or([255; 4]);
+                font.outline_glyph(glyph).map(|outline| (outline, rgba))
+            })
+            .collect();
+        let mut pixels = GlyphPixels {
+            c,
+            rgba: vec![],
+            width: 0,
+            height: 0,
+            bounds: [0.0; 4],
+            advance: self.advance(id, size),
+            color: true,
+        };
+        let bounds = match outlines.first() {
+            Some((outline, _)) => outlines.iter().fold(
+                outline.px_bounds(),
+                |mut bounds, (outline, _)| {
+                    let layer = outline.px_bounds();
+                    bounds.min.x = bounds.min.x.min(layer.min.x);
+                    bounds.min.y = bounds.min.y.min(layer.min.y);
+                    bounds.max.x = bounds.max.x.max(layer.max.x);
+                    bounds.max.y = bounds.max.y.max(layer.max.y);
+                    bounds
+                },
+            ),
+            None => return pixels,
+        };
+        let width = bounds.width().ceil() as u32;
+        let height = bounds.height().ceil() as u32;
+
+        // straight alpha rgba, composited with the over operator
+        let mut canvas = vec![[0.0f32; 4]; (width * height) as usize];
+        for (outline, rgba) in &outlines {
+            let layer = outline.px_bounds();
+            let left = (layer.min.x - bounds.min.x) as u32;
+            let top = (layer.min.y - bounds.min.y) as u32;
+            outline.draw(|gx, gy, coverage| {
+                let (x, y) = (left + gx, top + gy);
+                if x >= width || y >= height {
+                    return;
+                }
+                let dst = &mut canvas[(y * width + x) as usize];
+                let alpha = coverage * rgba[3] as f32 / 255.0;
+                let out_alpha = alpha + dst[3] * (1.0 - alpha);
+                if out_alpha <= 0.0 {
+                    return;
+                }
+                for channel in 0..3 {
+                    let src = rgba[channel] as f32 / 255.0;
+                    dst[channel] = (src * alpha
+                        + dst[channel] * dst[3] * (1.0 - alpha))
+                        / out_alpha;
+                }
+                dst[3] = out_alpha;
+            });
+        }
+
+        pixels.width = width;
+        pixels.height = height;
+        pixels.bounds =
+            [bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y];
+        pixels.rgba = canvas
+            .iter()
+            .flat_map(|pixel| pixel.iter())
+            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect();
+        pixels
+    }
+
+    /// How far the caret moves past a glyph at the size.
+    fn advance(&self, id: GlyphId, size: f32) -> f32 {
+        let face = self.face();
+        let units_per_em = face.units_per_em().unwrap_or(1000) as f32;
+        face.glyph_hor_advance(id).unwrap_or(0) as f32 * size / units_per_em
+    }
+
+    fn font(&self) -> FontRef<'_> {
+        // SAFE: the data was checked when the font was created
+        FontRef::try_from_slice(&self.data).unwrap()
+    }
+
+    fn face(&self) -> Face<'_> {
+        // SAFE: the data was checked when the font was created
+        Face::from_slice(&self.data, 0).unwrap()
+    }
+}
+
+/// The bounds of a scaled raster image relative to the caret on the
+/// baseline, as left, top, right, and bottom with y pointing down.
+///
+/// Raster image offsets are from the caret to the image's bottom left
+/// corner with y pointing up.
+fn raster_bounds(
+    (x, y): (f32, f32),
+    (width, height): (f32, f32),
+    scale: f32,
+) -> [f32; 4] {
+    let left = x * scale;
+    let bottom = -y * scale;
+    [left, bottom - height, left + width, bottom]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn raster_bounds_should_flip_y_and_scale_the_offset() {
+        assert_eq!(
+            raster_bounds((2.0, -4.0), (32.0, 32.0), 0.25),
+            [0.5, -31.0, 32.5, 1.0]
+        );
+    }
+
+    #[test]
+    fn from_vec_should_reject_data_which_isnt_a_font() {
+        assert!(EmojiFont::from_vec(vec![0; 16]).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use super::{FontAtlas, GlyphPixels, GlyphRegion};
+
+use crate::graphics::{
+    ext::Texture2dFactory,
+    texture_atlas::{TextureAtlas, TextureHandle},
+    vulkan::buffer::CpuBuffer,
+    Graphics,
+};
+
+use anyhow::{bail, Result};
+use ash::vk;
+use std::collections::HashMap;
+
+/// The width of a font atlas's texture in pixels.
+const TEXTURE_WIDTH: u32 = 512;
+
+/// Space between glyphs in the texture, which keeps them from bleeding into
+/// each other when sampled.
+const GLYPH_PADDING: u32 = 2;
+
+impl FontAtlas {
+    /// Pack each glyph's pixels into a texture, in rows, and add it to the
+    /// texture atlas.
+    pub(super) fn new(
+        graphics: &mut Graphics,
+        name: &str,
+        glyph_pixels: Vec<GlyphPixels>,
+    ) -> Result<Self> {
+        let (texture_height, pixels, glyphs) = pack_glyphs(glyph_pixels)?;
+
+        let mut texture = graphics.create_empty_2d_texture(
+            name,
+            TEXTURE_WIDTH,
+            texture_height,
+            1,
+        )?;
+        unsafe {
+            let mut transfer_buffer = CpuBuffer::new(
+                graphics.device.clone(),
+                vk::BufferUsageFlags::TRANSFER_SRC,
+            )?;
+            transfer_buffer.write_data(&pixels)?;
+            texture.upload_from_buffer(&transfer_buffer)?;
+        }
+        let texture_handle = graphics.add_texture(texture)?;
+
+        Ok(Self {
+            texture_handle,
+            glyphs,
+        })
+    }
+
+    /// The texture which every glyph is drawn from.
+    pub fn texture_handle(&self) -> TextureHandle {
+        self.texture_handle
+    }
+
+    /// Where a character's glyph is, or None when the atlas doesn't have it.
+    pub fn glyph(&self, c: char) -> Option<&GlyphRegion> {
+        self.glyphs.get(&c)
+    }
+}
+
+/// Place each glyph in rows, left to right, and copy its pixels into an rgba
+/// texture which is `TEXTURE_WIDTH` pixels wide.
+///
+/// Returns the texture's height, its pixels, and each glyph's region.
+fn pack_glyphs(
+    glyph_pixels: Vec<GlyphPixels>,
+) -> Result<(u32, Vec<u8>, HashMap<char, GlyphRegion>)> {
+    let mut placed = vec![];
+    let (mut x, mut y, mut row_height) = (GLYPH_PADDING, GLYPH_PADDING, 0);
+    for glyph in glyph_pixels {
+        if glyph.width > TEXTURE_WIDTH - GLYPH_PADDING * 2 {
+            bail!("the glyph for {:?} is too wide for a font atlas", glyph.c);
+        }
+        if x + glyph.width + GLYPH_PADDING > TEXTURE_WIDTH {
+            x = GLYPH_PADDING;
+            y += row_height + GLYPH_PADDING;
+            row_height = 0;
+        }
+        let position = (x, y);
+        x += glyph.width + GLYPH_PADDING;
+        row_height = row_height.max(glyph.height);
+        placed.push((glyph, position));
+    }
+    let texture_height = (y + row_height + GLYPH_PADDING).next_power_of_two();
+
+    let mut pixels = vec![0u8; (TEXTURE_WIDTH * texture_height * 4) as usize];
+    let mut glyphs = HashMap::new();
+    for (glyph, (x, y)) in placed {
+        if glyph.width == 0 || glyph.height == 0 {
+            glyphs.insert(
+                glyph.c,
+                GlyphRegion {
+                    uv: [0.0; 4],
+                    bounds: [0.0; 4],
+                    advance: glyph.advance,
+                    color: glyph.color,
+                },
+            );
+            continue;
+        }
+        let row_bytes = (glyph.width * 4) as usize;
+        for (gy, row) in glyph.rgba.chunks_exact(row_bytes).enumerate() {
+            let index = (((y + gy as u32) * TEXTURE_WIDTH + x) * 4) as usize;
+            pixels[index..index + row_bytes].copy_from_slice(row);
+        }
+        let (width, height) = (glyph.width as f32, glyph.height as f32);
+        glyphs.insert(
+            glyph.c,
+            GlyphRegion {
+                uv: [
+                    x as f32 / TEXTURE_WIDTH as f32,
+                    y as f32 / texture_height as f32,
+                    (x as f32 + width) / TEXTURE_WIDTH as f32,
+                    (y as f32 + height) / texture_height as f32,
+                ],
+                bounds: glyph.bounds,
+                advance: glyph.advance,
+                color: glyph.color,
+            },
+        );
+    }
+    Ok((texture_height, pixels, glyphs))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solid(c: char, width: u32, height: u32) -> GlyphPixels {
+        GlyphPixels {
+            c,
+            rgba: vec![255; (width * height * 4) as usize],
+            width,
+            height,
+            bounds: [0.0, -(height as f32), width as f32, 0.0],
+            advance: width as f32,
+            color: false,
+        }
+    }
+
+    #[test]
+    fn glyphs_should_be_packed_in_rows() {
+        let (height, pixels, glyphs) = pack_glyphs(vec![
+            solid('a', 300, 10),
+            solid('b', 300, 20),
+            solid(' ', 0, 0),
+        ])
+        .unwrap();
+
+        // 'b' doesn't fit beside 'a' so it starts the second row at y=14
+        assert_eq!(height, 64);
+        assert_eq!(pixels.len(), (TEXTURE_WIDTH * 64 * 4) as usize);
+        assert_eq!(glyphs[&'a'].uv[..2], [2.0 / 512.0, 2.0 / 64.0]);
+        assert_eq!(glyphs[&'b'].uv[..2], [2.0 / 512.0, 14.0 / 64.0]);
+        assert_eq!(glyphs[&' '].uv, [0.0; 4]);
+        assert_eq!(glyphs[&' '].advance, 0.0);
+
+        let b_corner = ((14 * TEXTURE_WIDTH + 2) * 4) as usize;
+        assert_eq!(pixels[b_corner..b_corner + 4], [255; 4]);
+        assert_eq!(pixels[0..4], [0; 4]);
+    }
+
+    #[test]
+    fn glyphs_wider_than_the_texture_should_be_rejected() {
+        assert!(pack_glyphs(vec![solid('w', TEXTURE_WIDTH, 1)]).is_err());
+    }
+}
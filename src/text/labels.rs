@@ -0,0 +1,241 @@
+use super::{BmFont, EmojiFont, FontAtlas, GlyphPixels, Labels, TextLayout};
+
+use crate::graphics::{
+    texture_atlas::TextureHandle, vertex::Vertex2d, Graphics,
+};
+
+use ab_glyph::{Font, FontArc, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+impl Labels {
+    /// Rasterize the printable ascii characters of a font at a size in
+    /// pixels and add them to the texture atlas.
+    pub fn new(
+        graphics: &mut Graphics,
+        font: &FontArc,
+        size: f32,
+    ) -> Result<Self> {
+        let glyphs = ascii_glyphs(font, size);
+        Self::from_glyph_pixels(graphics, glyphs, font, size)
+    }
+
+    /// Rasterize the printable ascii characters of a font, along with some
+    /// emoji from a color font, at a size in pixels.
+    ///
+    /// Emoji with color images or layers are drawn untinted, while every
+    /// other character still takes the label's color. Characters which the
+    /// emoji font doesn't have are skipped, and emoji in color formats which
+    /// can't be drawn are an error, see `EmojiFont`.
+    pub fn with_emoji(
+        graphics: &mut Graphics,
+        font: &FontArc,
+        size: f32,
+        emoji_font: &EmojiFont,
+        emoji: &str,
+    ) -> Result<Self> {
+        let mut glyphs = ascii_glyphs(font, size);
+        for c in emoji.chars() {
+            if glyphs.iter().any(|glyph| glyph.c == c) {
+                continue;
+            }
+            let glyph = emoji_font.rasterize(c, size).with_context(|| {
+                format!("unable to rasterize {:?} with the emoji font", c)
+            })?;
+            match glyph {
+                Some(glyph) => glyphs.push(glyph),
+                None => log::warn!("the emoji font has no glyph for {:?}", c),
+            }
+        }
+        Self::from_glyph_pixels(graphics, glyphs, font, size)
+    }
+
+    /// Pack the glyphs into a font atlas for labels drawn with an outline
+    /// font.
+    fn from_glyph_pixels(
+        graphics: &mut Graphics,
+        glyph_pixels: Vec<GlyphPixels>,
+        font: &FontArc,
+        size: f32,
+    ) -> Result<Self> {
+        let scaled = font.as_scaled(PxScale::from(size));
+        Ok(Self {
+            atlas: FontAtlas::new(graphics, "Labels", glyph_pixels)?,
+            ascent: scaled.ascent(),
+            line_height: scaled.height(),
+        })
+    }
+
+    /// Draw labels with a pre-rendered bitmap font, read from an AngelCode
+    /// BMFont `.fnt` file and its page textures.
+    ///
+    /// The glyphs on every page are packed into one font atlas, so fonts
+    /// with many pages still draw in a single batch.
+    pub fn from_bmfont(
+        graphics: &mut Graphics,
+        path: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let font = BmFont::read_file(path)?;
+        let folder = path.parent().unwrap_or_else(|| Path::new(""));
+        let pages = font
+            .pages
+            .iter()
+            .map(|page| {
+                let page = folder.join(page);
+                image::open(&page)
+                    .map(|image| image.to_rgba8())
+                    .with_context(|| {
+                        format!("unable to load bitmap font page {:?}", page)
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let glyph_pixels = font
+            .glyph_pixels(&pages)
+            .with_context(|| format!("invalid bitmap font {:?}", path))?;
+        Ok(Self {
+            atlas: FontAtlas::new(
+                graphics,
+                "Bitmap Font Labels",
+                glyph_pixels,
+            )?,
+            ascent: font.base,
+            line_height: font.line_height,
+        })
+    }
+
+    /// The texture which labels are drawn with.
+    pub fn texture_handle(&self) -> TextureHandle {
+        self.atlas.texture_handle()
+    }
+
+    /// The font atlas which labels are drawn from.
+    pub fn atlas(&self) -> &FontAtlas {
+        &self.atlas
+    }
+
+    /// The height of a line of text in pixels.
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Measure a single line of text with its top left corner at the
+    /// position, the same way `layout` places it.
+    ///
+    /// Characters past the maximum width are left out. Characters which
+    /// aren't printable ascii take no space.
+    pub fn measure(
+        &self,
+        text: &str,
+        position: (f32, f32),
+        max_width: f32,
+    ) -> TextLayout {
+        TextLayout::new(text, position, self.line_height, max_width, |c| {
+            self.atlas.glyph(c).map(|glyph| glyph.advance)
+        })
+    }
+
+    /// Push quads for a single line of text with its top left corner at the
+    /// position. Characters past the maximum width, and characters which
+    /// aren't printable ascii, are skipped.
+    ///
+    /// Returns the text's layout, see `measure`.
+    pub fn layout(
+        &self,
+        text: &str,
+        position: (f32, f32),
+        max_width: f32,
+        rgba: [f32; 4],
+        vertices: &mut Vec<Vertex2d>,
+    ) -> TextLayout {
+        let measured = self.measure(text, position, max_width);
+        let baseline = position.1 + self.ascent;
+        for cluster in measured.clusters() {
+            let c = text[cluster.bytes.clone()].chars().next().unwrap();
+            let glyph = match self.atlas.glyph(c) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+            let caret = cluster.rect.left;
+            let rgba = if glyph.color {
+                [1.0, 1.0, 1.0, rgba[3]]
+            } else {
+                rgba
+            };
+            let [l, t, r, b] = glyph.bounds;
+            if r > l {
+                let [u0, v0, u1, v1] = glyph.uv;
+                let corner = |x: f32, y: f32, u: f32, v: f32| Vertex2d {
+                    pos: [caret + x, baseline + y],
+                    uv: [u, v],
+                    rgba,
+                };
+                vertices.extend_from_slice(&[
+                    corner(l, t, u0, v0),
+                    corner(r, t, u1, v0),
+                    corner(r, b, u1, v1),
+                    corner(l, t, u0, v0),
+                    corner(r, b, u1, v1),
+                    corner(l, b, u0, v1),
+                ]);
+            }
+        }
+        measured
+    }
+}
+
+impl GlyphPixels {
+    /// Rasterize a character's outline as white pixels with the coverage in
+    /// alpha, so the glyph can be tinted.
+    ///
+    /// Characters without an outline, like spaces, have no pixels.
+    pub(super) fn from_outline<F: Font>(font: &F, c: char, size: f32) -> Self {
+        let scaled = font.as_scaled(PxScale::from(size));
+        let glyph = scaled.scaled_glyph(c);
+        let advance = scaled.h_advance(glyph.id);
+        let mut pixels = Self {
+            c,
+            rgba: vec![],
+            width: 0,
+            height: 0,
+            bounds: [0.0; 4],
+            advance,
+            color: false,
+        };
+        let outline = match font.outline_glyph(glyph) {
+            Some(outline) => outline,
+            None => return pixels,
+        };
+        let bounds = outline.px_bounds();
+        pixels.width = bounds.width().ceil() as u32;
+        pixels.height = bounds.height().ceil() as u32;
+        pixels.bounds =
+            [bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y];
+        pixels.rgba = vec![0; (pixels.width * pixels.height * 4) as usize];
+        let width = pixels.width;
+        let rgba = &mut pixels.rgba;
+        outline.draw(|gx, gy, coverage| {
+            if gx >= width {
+                return;
+            }
+            let index = ((gy * width + gx) * 4) as usize;
+            if let Some(pixel) = rgba.get_mut(index..index + 4) {
+                pixel.copy_from_slice(&[
+                    255,
+                    255,
+                    255,
+                    (coverage * 255.0) as u8,
+                ]);
+            }
+        });
+        pixels
+    }
+}
+
+/// The printable ascii characters of a font.
+fn ascii_glyphs(font: &FontArc, size: f32) -> Vec<GlyphPixels> {
+    (32u8..127)
+        .map(char::from)
+        .map(|c| GlyphPixels::from_outline(font, c, size))
+        .collect()
+}
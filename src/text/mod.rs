@@ -0,0 +1,175 @@
+//! Fonts and the textures used to draw text.
+//!
+//! `Labels` packs a font's glyphs into a `FontAtlas`, a single texture in
+//! the texture atlas, and lays out single lines of text with them. Glyphs can
+//! come from an outline font, a color emoji font (`EmojiFont`), or the pages
+//! of a pre-rendered bitmap font (`BmFont`).
+
+mod bmfont;
+mod color_layers;
+mod emoji_font;
+mod font_atlas;
+mod labels;
+mod text_layout;
+
+use crate::{geometry::Rect, graphics::texture_atlas::TextureHandle};
+
+use std::{collections::HashMap, ops::Range, path::PathBuf};
+
+/// A texture with the printable ascii characters of a font, used to draw
+/// short runs of text like the params panel's labels. Pre-rendered bitmap
+/// fonts can be used instead, see `Labels::from_bmfont`.
+pub struct Labels {
+    atlas: FontAtlas,
+
+    /// The distance from the top of a line to the baseline.
+    ascent: f32,
+
+    /// The distance between lines of text.
+    line_height: f32,
+}
+
+/// Glyph images packed into one texture in the texture atlas, along with
+/// where each glyph is. Every glyph can be drawn in a single batch, no matter
+/// which font or bitmap font page it came from.
+pub struct FontAtlas {
+    texture_handle: TextureHandle,
+    glyphs: HashMap<char, GlyphRegion>,
+}
+
+/// A pre-rendered bitmap font read from an AngelCode BMFont `.fnt` file.
+///
+/// Only the text format is supported. Use `Labels::from_bmfont` to draw text
+/// with the font.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BmFont {
+    /// The distance between lines of text in pixels.
+    pub line_height: f32,
+
+    /// The distance from the top of a line to the baseline in pixels.
+    pub base: f32,
+
+    /// The size of each page texture in pixels.
+    pub page_size: (f32, f32),
+
+    /// The page texture files, relative to the `.fnt` file, in page order.
+    pub pages: Vec<PathBuf>,
+
+    /// Every character in the font.
+    pub chars: Vec<BmFontChar>,
+}
+
+/// Where a character is in a BMFont's page textures, all in pixels.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BmFontChar {
+    pub id: char,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+
+    /// The offset from the caret to the glyph's left edge.
+    pub x_offset: f32,
+
+    /// The offset from the top of the line to the glyph's top edge.
+    pub y_offset: f32,
+
+    /// How far the caret moves past the character.
+    pub x_advance: f32,
+
+    /// The index of the page texture with the character.
+    pub page: usize,
+}
+
+/// Where a character is in a font atlas's texture and how it's positioned
+/// relative to the caret.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GlyphRegion {
+    /// The texture coordinates as left, top, right, and bottom.
+    pub uv: [f32; 4],
+
+    /// The glyph's bounds relative to the caret on the baseline, as left,
+    /// top, right, and bottom.
+    pub bounds: [f32; 4],
+
+    /// How far the caret moves past the glyph.
+    pub advance: f32,
+
+    /// Color glyphs, like emoji, are drawn untinted.
+    pub color: bool,
+}
+
+/// A glyph's pixels before they're packed into a font atlas.
+struct GlyphPixels {
+    c: char,
+
+    /// Rgba pixels, row by row.
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+
+    /// The glyph's bounds relative to the caret on the baseline, as left,
+    /// top, right, and bottom.
+    bounds: [f32; 4],
+
+    advance: f32,
+
+    /// Color glyphs, like emoji, are drawn untinted.
+    color: bool,
+}
+
+/// A color font with pre-rendered emoji images in its `sbix` or `CBDT`
+/// tables, or layered emoji in its `COLR` table, see `Labels::with_emoji`.
+///
+/// Only version 0 `COLR` layers are drawn. Glyphs which are only described
+/// by `COLR` version 1 paint graphs or by `SVG` documents fail to rasterize
+/// with an error. Glyphs without any color data are drawn from their
+/// outlines and tinted like regular text.
+pub struct EmojiFont {
+    data: Vec<u8>,
+}
+
+/// One layer of a `COLR` glyph, an outline drawn in a single color.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct ColorLayer {
+    glyph_id: u16,
+
+    /// The layer's rgba color, or None for the text's foreground color.
+    rgba: Option<[u8; 4]>,
+}
+
+/// Where each character of a line of text was placed by `Labels::measure`.
+///
+/// Text fields use the layout to map a click to a caret index, and to find
+/// the rectangles for the caret and selection. Caret indices count
+/// characters, not bytes, and run from 0 (before the first character) to
+/// `len()` (after the last one).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    clusters: Vec<GlyphCluster>,
+
+    /// The left edge of the line, where the caret sits before the first
+    /// character.
+    left: f32,
+
+    /// The top of the line.
+    top: f32,
+
+    /// The height of the line.
+    line_height: f32,
+}
+
+/// A single character's place in a laid out line of text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphCluster {
+    /// The character's index in the text.
+    pub index: usize,
+
+    /// The character's bytes in the text.
+    pub bytes: Range<usize>,
+
+    /// The space taken by the character, from the caret before it to the
+    /// caret after it and across the full line height. Characters which the
+    /// labels can't draw take no space.
+    pub rect: Rect<f32>,
+}
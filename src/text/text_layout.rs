@@ -0,0 +1,220 @@
+use super::{GlyphCluster, TextLayout};
+
+use crate::geometry::Rect;
+
+use std::ops::Range;
+
+impl TextLayout {
+    /// Place each character of a single line of text, left to right, with
+    /// the line's top left corner at the position.
+    ///
+    /// The advance function returns how far the caret moves past a
+    /// character, or None when the character can't be drawn. Characters
+    /// which would end past the maximum width are left out of the layout,
+    /// along with everything after them.
+    pub(super) fn new(
+        text: &str,
+        (left, top): (f32, f32),
+        line_height: f32,
+        max_width: f32,
+        advance: impl Fn(char) -> Option<f32>,
+    ) -> Self {
+        let mut clusters = vec![];
+        let mut caret = left;
+        for (index, (offset, c)) in text.char_indices().enumerate() {
+            let width = advance(c).unwrap_or(0.0);
+            if caret + width > left + max_width {
+                break;
+            }
+            clusters.push(GlyphCluster {
+                index,
+                bytes: offset..offset + c.len_utf8(),
+                rect: Rect {
+                    left: caret,
+                    right: caret + width,
+                    top,
+                    bottom: top + line_height,
+                },
+            });
+            caret += width;
+        }
+        Self {
+            clusters,
+            left,
+            top,
+            line_height,
+        }
+    }
+
+    /// Every character which was laid out, in order.
+    pub fn clusters(&self) -> &[GlyphCluster] {
+        &self.clusters
+    }
+
+    /// The number of characters which were laid out.
+    pub fn len(&self) -> usize {
+        self.clusters.len()
+    }
+
+    /// True when no characters were laid out.
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+    }
+
+    /// The width of the laid out text in pixels.
+    pub fn width(&self) -> f32 {
+        self.caret_x(self.len()) - self.left
+    }
+
+    /// The byte offset in the text for a caret index, clamped to the end of
+    /// the laid out text. Use this to insert or remove text at the caret.
+    pub fn byte_offset(&self, caret: usize) -> usize {
+        match self.clusters.get(caret) {
+            Some(cluster) => cluster.bytes.start,
+            None => self
+                .clusters
+                .last()
+                .map(|cluster| cluster.bytes.end)
+                .unwrap_or(0),
+        }
+    }
+
+    /// The character under a point, if any.
+    pub fn cluster_at(&self, (x, y): (f32, f32)) -> Option<&GlyphCluster> {
+        if y < self.top || y > self.top + self.line_height {
+            return None;
+        }
+        self.clusters
+            .iter()
+            .find(|cluster| x >= cluster.rect.left && x < cluster.rect.right)
+    }
+
+    /// The caret index closest to a horizontal position, such as where the
+    /// text was clicked.
+    ///
+    /// Positions before the line give 0 and positions after it give
+    /// `len()`.
+    pub fn caret_index(&self, x: f32) -> usize {
+        self.clusters
+            .iter()
+            .position(|cluster| {
+                x < (cluster.rect.left + cluster.rect.right) * 0.5
+            })
+            .unwrap_or_else(|| self.len())
+    }
+
+    /// The rectangle for a caret of some width, centered on the caret
+    /// index's position and spanning the line height.
+    pub fn caret_rect(&self, caret: usize, width: f32) -> Rect<f32> {
+        let x = self.caret_x(caret);
+        Rect {
+            left: x - width * 0.5,
+            right: x + width * 0.5,
+            top: self.top,
+            bottom: self.top + self.line_height,
+        }
+    }
+
+    /// The rectangle covering a range of caret indices, or None when the
+    /// range is empty.
+    ///
+    /// The range can be given in either direction, which is convenient when
+    /// the selection is dragged backwards from where it started.
+    pub fn selection_rect(&self, selection: Range<usize>) -> Option<Rect<f32>> {
+        let start = selection.start.min(selection.end).min(self.len());
+        let end = selection.start.max(selection.end).min(self.len());
+        if start == end {
+            return None;
+        }
+        Some(Rect {
+            left: self.caret_x(start),
+            right: self.caret_x(end),
+            top: self.top,
+            bottom: self.top + self.line_height,
+        })
+    }
+
+    /// The horizontal position of a caret index, clamped to the end of the
+    /// laid out text.
+    fn caret_x(&self, caret: usize) -> f32 {
+        match self.clusters.get(caret) {
+            Some(cluster) => cluster.rect.left,
+            None => self
+                .clusters
+                .last()
+                .map(|cluster| cluster.rect.right)
+                .unwrap_or(self.left),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Lay out text where every ascii character is 10 pixels wide and
+    /// anything else can't be drawn.
+    fn layout(text: &str, max_width: f32) -> TextLayout {
+        TextLayout::new(text, (5.0, 20.0), 16.0, max_width, |c| {
+            if c.is_ascii() {
+                Some(10.0)
+            } else {
+                None
+            }
+        })
+    }
+
+    #[test]
+    fn new_should_stop_at_the_maximum_width() {
+        let text = layout("hello", 35.0);
+        assert_eq!(text.len(), 3);
+        assert_eq!(text.width(), 30.0);
+        assert_eq!(text.clusters()[2].rect.left, 25.0);
+    }
+
+    #[test]
+    fn byte_offset_should_account_for_multibyte_characters() {
+        let text = layout("aéb", f32::INFINITY);
+        assert_eq!(text.clusters()[1].bytes, 1..3);
+        assert_eq!(text.byte_offset(2), 3);
+        assert_eq!(text.byte_offset(3), 4);
+        assert_eq!(text.byte_offset(99), 4);
+        assert_eq!(text.caret_x(2), 15.0);
+    }
+
+    #[test]
+    fn caret_index_should_pick_the_nearest_boundary() {
+        let text = layout("abc", f32::INFINITY);
+        assert_eq!(text.caret_index(-100.0), 0);
+        assert_eq!(text.caret_index(9.0), 0);
+        assert_eq!(text.caret_index(11.0), 1);
+        assert_eq!(text.caret_index(34.0), 3);
+        assert_eq!(layout("", 10.0).caret_index(50.0), 0);
+    }
+
+    #[test]
+    fn cluster_at_should_respect_the_line_bounds() {
+        let text = layout("abc", f32::INFINITY);
+        assert_eq!(text.cluster_at((16.0, 25.0)).unwrap().index, 1);
+        assert!(text.cluster_at((16.0, 40.0)).is_none());
+        assert!(text.cluster_at((40.0, 25.0)).is_none());
+    }
+
+    #[test]
+    fn selection_rect_should_order_and_clamp_the_range() {
+        let text = layout("abcd", f32::INFINITY);
+        #[allow(clippy::reversed_empty_ranges)]
+        let rect = text.selection_rect(3..1).unwrap();
+        assert_eq!((rect.left, rect.right), (15.0, 35.0));
+        assert_eq!((rect.top, rect.bottom), (20.0, 36.0));
+        assert_eq!(text.selection_rect(2..99).unwrap().right, 45.0);
+        assert!(text.selection_rect(2..2).is_none());
+    }
+
+    #[test]
+    fn caret_rect_should_center_on_the_caret() {
+        let text = layout("ab", f32::INFINITY);
+        let rect = text.caret_rect(1, 2.0);
+        assert_eq!((rect.left, rect.right), (14.0, 16.0));
+    }
+}
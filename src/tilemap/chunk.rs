@@ -0,0 +1,30 @@
+use super::Chunk;
+
+use crate::{
+    geometry::Rect,
+    graphics::{layer::Batch, texture_atlas::TextureHandle},
+};
+
+impl Chunk {
+    /// Create a chunk which needs to be built before it is drawn.
+    pub fn new(bounds: Rect<f32>, texture_handle: TextureHandle) -> Self {
+        Self {
+            bounds,
+            batch: Batch {
+                texture_handle,
+                vertices: vec![],
+                layer: 0,
+                palette: None,
+            },
+            dirty: true,
+        }
+    }
+
+    /// True when any part of the chunk is inside of the view.
+    pub fn is_visible(&self, view: &Rect<f32>) -> bool {
+        self.bounds.left <= view.right
+            && self.bounds.right >= view.left
+            && self.bounds.bottom <= view.top
+            && self.bounds.top >= view.bottom
+    }
+}
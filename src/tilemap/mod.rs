@@ -0,0 +1,87 @@
+//! Render large grids of tiles from a tileset sprite sheet.
+//!
+//! Tile maps are split into square chunks. Each chunk keeps a prebuilt batch
+//! which is only rebuilt when one of its tiles changes, and chunks outside of
+//! the camera's view are skipped entirely. This makes very large worlds cheap
+//! to draw because only a handful of chunks are ever visible at once.
+//!
+//! Maps authored in the Tiled editor can be loaded with the `tiled` feature.
+
+mod chunk;
+mod sprite_sheet;
+mod tile_map;
+
+#[cfg(feature = "tiled")]
+mod tiled;
+
+#[cfg(feature = "tiled")]
+pub use self::tiled::load_tiled_file;
+
+use crate::{
+    geometry::Rect,
+    graphics::{layer::Batch, texture_atlas::TextureHandle},
+};
+
+use nalgebra as na;
+
+/// The width and height, in tiles, of each chunk.
+pub const CHUNK_SIZE: u32 = 32;
+
+/// A texture containing a grid of equally sized tiles.
+///
+/// Tiles are numbered left to right, top to bottom, starting at zero.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpriteSheet {
+    pub texture_handle: TextureHandle,
+
+    /// The size of the texture in pixels.
+    pub texture_width: u32,
+    pub texture_height: u32,
+
+    /// The size of each tile in pixels.
+    pub tile_width: u32,
+    pub tile_height: u32,
+
+    /// Pixels between the edge of the texture and the first tile.
+    pub margin: u32,
+
+    /// Pixels between neighboring tiles.
+    pub spacing: u32,
+}
+
+/// A rectangular grid of tiles drawn from a single sprite sheet.
+///
+/// Tile (0, 0) is the bottom-left tile of the map and y increases upwards to
+/// match world space.
+pub struct TileMap {
+    sheet: SpriteSheet,
+
+    /// The size of the map in tiles.
+    width: u32,
+    height: u32,
+
+    /// The size of each tile in world units.
+    tile_size: f32,
+
+    /// The world position of the map's bottom-left corner.
+    origin: na::Vector2<f32>,
+
+    /// The sprite sheet index for each tile, row by row from the bottom.
+    tiles: Vec<Option<u32>>,
+
+    /// Chunks, row by row from the bottom.
+    chunks: Vec<Chunk>,
+    chunk_columns: u32,
+}
+
+/// A square section of a tile map with a cached batch.
+struct Chunk {
+    /// The chunk's bounds in world space.
+    bounds: Rect<f32>,
+
+    /// Vertices for every tile in the chunk.
+    batch: Batch,
+
+    /// True when a tile has changed since the batch was built.
+    dirty: bool,
+}
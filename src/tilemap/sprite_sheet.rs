@@ -0,0 +1,113 @@
+use super::SpriteSheet;
+
+use crate::{
+    graphics::texture_atlas::TextureHandle,
+    packing::{PackedRect, UvRect},
+};
+
+impl SpriteSheet {
+    /// Create a sprite sheet where tiles are packed edge to edge.
+    pub fn new(
+        texture_handle: TextureHandle,
+        (texture_width, texture_height): (u32, u32),
+        (tile_width, tile_height): (u32, u32),
+    ) -> Self {
+        Self {
+            texture_handle,
+            texture_width,
+            texture_height,
+            tile_width,
+            tile_height,
+            margin: 0,
+            spacing: 0,
+        }
+    }
+
+    /// Set the margin around the tiles and the spacing between them.
+    pub fn with_spacing(self, margin: u32, spacing: u32) -> Self {
+        Self {
+            margin,
+            spacing,
+            ..self
+        }
+    }
+
+    /// The number of tile columns in the sheet.
+    pub fn columns(&self) -> u32 {
+        Self::count(self.texture_width, self.tile_width, self)
+    }
+
+    /// The number of tile rows in the sheet.
+    pub fn rows(&self) -> u32 {
+        Self::count(self.texture_height, self.tile_height, self)
+    }
+
+    /// The total number of tiles in the sheet.
+    pub fn tile_count(&self) -> u32 {
+        self.columns() * self.rows()
+    }
+
+    /// The pixel bounds of a tile, or None if the index is out of range.
+    pub fn tile_rect(&self, index: u32) -> Option<PackedRect> {
+        if index >= self.tile_count() {
+            return None;
+        }
+        let (column, row) = (index % self.columns(), index / self.columns());
+        Some(PackedRect {
+            x: self.margin + column * (self.tile_width + self.spacing),
+            y: self.margin + row * (self.tile_height + self.spacing),
+            width: self.tile_width,
+            height: self.tile_height,
+        })
+    }
+
+    /// Texture coordinates for a tile, inset by half a texel to prevent
+    /// neighboring tiles from bleeding in under linear filtering.
+    pub fn tile_uvs(&self, index: u32) -> Option<UvRect> {
+        self.tile_rect(index).map(|rect| {
+            rect.half_texel_uv_rect(self.texture_width, self.texture_height)
+        })
+    }
+
+    fn count(texture_size: u32, tile_size: u32, sheet: &SpriteSheet) -> u32 {
+        if tile_size == 0 || texture_size < sheet.margin * 2 + tile_size {
+            return 0;
+        }
+        (texture_size - sheet.margin * 2 + sheet.spacing)
+            / (tile_size + sheet.spacing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tiles_should_be_numbered_left_to_right_top_to_bottom() {
+        let sheet =
+            SpriteSheet::new(TextureHandle::default(), (34, 18), (8, 8))
+                .with_spacing(1, 0);
+
+        assert_eq!(sheet.columns(), 4);
+        assert_eq!(sheet.rows(), 2);
+        assert_eq!(
+            sheet.tile_rect(5),
+            Some(PackedRect {
+                x: 9,
+                y: 9,
+                width: 8,
+                height: 8
+            })
+        );
+        assert_eq!(sheet.tile_rect(8), None);
+    }
+
+    #[test]
+    fn spacing_should_separate_tiles() {
+        let sheet = SpriteSheet::new(TextureHandle::default(), (26, 8), (8, 8))
+            .with_spacing(0, 1);
+
+        assert_eq!(sheet.columns(), 3);
+        assert_eq!(sheet.tile_rect(2).unwrap().x, 18);
+    }
+}
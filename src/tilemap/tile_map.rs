@@ -0,0 +1,250 @@
+use super::{Chunk, SpriteSheet, TileMap, CHUNK_SIZE};
+
+use crate::{
+    geometry::Rect,
+    graphics::{
+        layer::{Batch, Layer},
+        vertex::Vertex2d,
+    },
+};
+
+use nalgebra as na;
+
+impl TileMap {
+    /// Create an empty map with the given size in tiles.
+    ///
+    /// Each tile is a `tile_size` square in world units and the map's bottom
+    /// left corner is at the world origin.
+    pub fn new(
+        sheet: SpriteSheet,
+        width: u32,
+        height: u32,
+        tile_size: f32,
+    ) -> Self {
+        let mut map = Self {
+            sheet,
+            width,
+            height,
+            tile_size,
+            origin: na::Vector2::new(0.0, 0.0),
+            tiles: vec![None; (width * height) as usize],
+            chunks: vec![],
+            chunk_columns: width.div_ceil(CHUNK_SIZE),
+        };
+        map.build_chunks();
+        map
+    }
+
+    /// The size of the map in tiles.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// The map's bounds in world space.
+    pub fn bounds(&self) -> Rect<f32> {
+        Rect {
+            left: self.origin.x,
+            right: self.origin.x + self.width as f32 * self.tile_size,
+            bottom: self.origin.y,
+            top: self.origin.y + self.height as f32 * self.tile_size,
+        }
+    }
+
+    /// Move the map's bottom-left corner to a position in world space.
+    pub fn set_origin(&mut self, origin: na::Vector2<f32>) {
+        self.origin = origin;
+        self.build_chunks();
+    }
+
+    /// The sprite sheet index of a tile, or None for empty and out of range
+    /// tiles.
+    pub fn tile(&self, x: u32, y: u32) -> Option<u32> {
+        self.tile_offset(x, y).and_then(|offset| self.tiles[offset])
+    }
+
+    /// Set the sprite sheet index of a tile, or None to clear it.
+    ///
+    /// Tiles outside of the map are ignored.
+    pub fn set_tile(&mut self, x: u32, y: u32, tile: Option<u32>) {
+        if let Some(offset) = self.tile_offset(x, y) {
+            if self.tiles[offset] != tile {
+                self.tiles[offset] = tile;
+                let chunk = self.chunk_index(x, y);
+                self.chunks[chunk].dirty = true;
+            }
+        }
+    }
+
+    /// The tile which contains a point in world space.
+    pub fn tile_at(&self, world: &na::Point2<f32>) -> Option<(u32, u32)> {
+        let local = (world.coords - self.origin) / self.tile_size;
+        if local.x < 0.0 || local.y < 0.0 {
+            return None;
+        }
+        let (x, y) = (local.x as u32, local.y as u32);
+        self.tile_offset(x, y).map(|_| (x, y))
+    }
+
+    /// Batches for every chunk which overlaps the view, such as the bounds
+    /// of an `OrthoCamera`.
+    ///
+    /// Chunks with modified tiles are rebuilt before being returned.
+    pub fn visible_batches(&mut self, view: &Rect<f32>) -> Vec<&Batch> {
+        for index in 0..self.chunks.len() {
+            if self.chunks[index].dirty && self.chunks[index].is_visible(view) {
+                self.rebuild_chunk(index);
+            }
+        }
+        self.chunks
+            .iter()
+            .filter(|chunk| {
+                chunk.is_visible(view) && !chunk.batch.vertices.is_empty()
+            })
+            .map(|chunk| &chunk.batch)
+            .collect()
+    }
+
+    /// Replace a layer's batches with the chunks which overlap the view.
+    pub fn fill_layer(&mut self, layer: &mut Layer, view: &Rect<f32>) {
+        layer.clear();
+        for batch in self.visible_batches(view) {
+            layer.push_batch(batch.clone());
+        }
+    }
+
+    fn tile_offset(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn chunk_index(&self, x: u32, y: u32) -> usize {
+        ((y / CHUNK_SIZE) * self.chunk_columns + x / CHUNK_SIZE) as usize
+    }
+
+    /// Create every chunk, marking them all as dirty.
+    fn build_chunks(&mut self) {
+        let chunk_rows = self.height.div_ceil(CHUNK_SIZE);
+        let chunk_extent = CHUNK_SIZE as f32 * self.tile_size;
+        let map_bounds = self.bounds();
+        self.chunks.clear();
+        for row in 0..chunk_rows {
+            for column in 0..self.chunk_columns {
+                let left = self.origin.x + column as f32 * chunk_extent;
+                let bottom = self.origin.y + row as f32 * chunk_extent;
+                let bounds = Rect {
+                    left,
+                    right: (left + chunk_extent).min(map_bounds.right),
+                    bottom,
+                    top: (bottom + chunk_extent).min(map_bounds.top),
+                };
+                self.chunks
+                    .push(Chunk::new(bounds, self.sheet.texture_handle));
+            }
+        }
+    }
+
+    /// Rebuild the vertices for every tile in a chunk.
+    fn rebuild_chunk(&mut self, index: usize) {
+        let column = index as u32 % self.chunk_columns;
+        let row = index as u32 / self.chunk_columns;
+        let x_range =
+            (column * CHUNK_SIZE)..((column + 1) * CHUNK_SIZE).min(self.width);
+        let y_range =
+            (row * CHUNK_SIZE)..((row + 1) * CHUNK_SIZE).min(self.height);
+
+        let mut vertices = vec![];
+        for y in y_range {
+            for x in x_range.clone() {
+                let uvs = match self
+                    .tile(x, y)
+                    .and_then(|tile| self.sheet.tile_uvs(tile))
+                {
+                    Some(uvs) => uvs,
+                    None => continue,
+                };
+                let left = self.origin.x + x as f32 * self.tile_size;
+                let bottom = self.origin.y + y as f32 * self.tile_size;
+                let (right, top) =
+                    (left + self.tile_size, bottom + self.tile_size);
+                let corner = |pos: [f32; 2], uv: [f32; 2]| Vertex2d {
+                    pos,
+                    uv,
+                    ..Default::default()
+                };
+                let top_left = corner([left, top], [uvs.u_min, uvs.v_min]);
+                let top_right = corner([right, top], [uvs.u_max, uvs.v_min]);
+                let bottom_left =
+                    corner([left, bottom], [uvs.u_min, uvs.v_max]);
+                let bottom_right =
+                    corner([right, bottom], [uvs.u_max, uvs.v_max]);
+                vertices.extend_from_slice(&[
+                    top_left,
+                    top_right,
+                    bottom_right,
+                    top_left,
+                    bottom_right,
+                    bottom_left,
+                ]);
+            }
+        }
+
+        let chunk = &mut self.chunks[index];
+        chunk.batch.vertices = vertices;
+        chunk.dirty = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::texture_atlas::TextureHandle;
+
+    fn map(width: u32, height: u32) -> TileMap {
+        let sheet =
+            SpriteSheet::new(TextureHandle::default(), (16, 16), (8, 8));
+        TileMap::new(sheet, width, height, 1.0)
+    }
+
+    #[test]
+    fn visible_batches_should_cull_chunks_outside_the_view() {
+        let mut map = map(CHUNK_SIZE * 4, CHUNK_SIZE * 4);
+        map.set_tile(0, 0, Some(0));
+        map.set_tile(CHUNK_SIZE * 3, CHUNK_SIZE * 3, Some(1));
+
+        let view = Rect {
+            left: -5.0,
+            right: 5.0,
+            bottom: -5.0,
+            top: 5.0,
+        };
+        let batches = map.visible_batches(&view);
+
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].vertices.len(), 6);
+    }
+
+    #[test]
+    fn set_tile_should_rebuild_only_when_changed() {
+        let mut map = map(4, 4);
+        let view = map.bounds();
+        map.set_tile(1, 1, Some(3));
+        assert_eq!(map.visible_batches(&view)[0].vertices.len(), 6);
+
+        map.set_tile(1, 1, None);
+        assert!(map.visible_batches(&view).is_empty());
+        assert_eq!(map.tile(1, 1), None);
+    }
+
+    #[test]
+    fn tile_at_should_find_the_tile_under_a_point() {
+        let mut map = map(4, 4);
+        map.set_origin(na::Vector2::new(-2.0, -2.0));
+
+        assert_eq!(map.tile_at(&na::Point2::new(-1.5, 1.5)), Some((0, 3)));
+        assert_eq!(map.tile_at(&na::Point2::new(2.5, 0.0)), None);
+    }
+}
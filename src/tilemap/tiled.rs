@@ -0,0 +1,235 @@
+use super::{SpriteSheet, TileMap};
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+/// Tiled stores flip and rotation flags in the high bits of each tile id.
+const FLIP_FLAGS: u32 = 0xE000_0000;
+
+/// A tile layer read from a Tiled map.
+struct TiledLayer {
+    name: String,
+    width: u32,
+    height: u32,
+
+    /// Global tile ids, row by row from the top. Zero is an empty tile.
+    gids: Vec<u32>,
+}
+
+/// Load every tile layer from a Tiled map saved as `.tmx` (with CSV layer
+/// data) or `.tmj` / `.json`.
+///
+/// Tile ids are resolved against the map's first tileset, which should be
+/// the tileset drawn by `sheet`. Returns each layer's name and tile map, in
+/// the order they appear in the file (back to front). Flip and rotation
+/// flags are ignored.
+pub fn load_tiled_file<P>(
+    path: P,
+    sheet: SpriteSheet,
+    tile_size: f32,
+) -> Result<Vec<(String, TileMap)>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("unable to read tiled map {:?}", path))?;
+    let (first_gid, layers) = match path.extension().and_then(|e| e.to_str()) {
+        Some("tmx") => parse_tmx(&text),
+        Some("tmj") | Some("json") => parse_tmj(&text),
+        _ => bail!("unsupported tiled map format {:?}", path),
+    }
+    .with_context(|| format!("unable to parse tiled map {:?}", path))?;
+
+    Ok(layers
+        .into_iter()
+        .map(|layer| {
+            let name = layer.name.clone();
+            (name, build_tile_map(layer, first_gid, sheet, tile_size))
+        })
+        .collect())
+}
+
+/// Build a tile map, flipping rows so the bottom row comes first.
+fn build_tile_map(
+    layer: TiledLayer,
+    first_gid: u32,
+    sheet: SpriteSheet,
+    tile_size: f32,
+) -> TileMap {
+    let mut map = TileMap::new(sheet, layer.width, layer.height, tile_size);
+    for (i, gid) in layer.gids.iter().enumerate() {
+        let gid = gid & !FLIP_FLAGS;
+        if gid < first_gid || gid == 0 {
+            continue;
+        }
+        let x = i as u32 % layer.width;
+        let y = layer.height - 1 - i as u32 / layer.width;
+        map.set_tile(x, y, Some(gid - first_gid));
+    }
+    map
+}
+
+/// Parse the JSON map format.
+fn parse_tmj(text: &str) -> Result<(u32, Vec<TiledLayer>)> {
+    let map: serde_json::Value = serde_json::from_str(text)?;
+    let first_gid = map["tilesets"][0]["firstgid"].as_u64().unwrap_or(1) as u32;
+
+    let mut layers = vec![];
+    for layer in map["layers"].as_array().context("missing layers")? {
+        if layer["type"] != "tilelayer" {
+            continue;
+        }
+        let name = layer["name"].as_str().unwrap_or_default().to_owned();
+        let data = match layer["data"].as_array() {
+            Some(data) => data,
+            None => bail!("layer {:?} must use csv or array data", name),
+        };
+        let gids = data
+            .iter()
+            .map(|gid| gid.as_u64().map(|gid| gid as u32))
+            .collect::<Option<Vec<u32>>>()
+            .with_context(|| format!("invalid tile id in layer {:?}", name))?;
+        layers.push(TiledLayer {
+            width: layer["width"].as_u64().context("missing layer width")?
+                as u32,
+            height: layer["height"].as_u64().context("missing layer height")?
+                as u32,
+            name,
+            gids,
+        });
+    }
+    validate(&layers)?;
+    Ok((first_gid, layers))
+}
+
+/// Parse the XML map format.
+fn parse_tmx(text: &str) -> Result<(u32, Vec<TiledLayer>)> {
+    let document = roxmltree::Document::parse(text)?;
+    let map = document.root_element();
+    let first_gid = map
+        .children()
+        .find(|node| node.has_tag_name("tileset"))
+        .and_then(|tileset| tileset.attribute("firstgid"))
+        .map(str::parse)
+        .transpose()?
+        .unwrap_or(1);
+
+    let mut layers = vec![];
+    for layer in map.children().filter(|node| node.has_tag_name("layer")) {
+        let name = layer.attribute("name").unwrap_or_default().to_owned();
+        let attribute = |key: &str| -> Result<u32> {
+            Ok(layer
+                .attribute(key)
+                .with_context(|| format!("layer {:?} missing {}", name, key))?
+                .parse()?)
+        };
+        let data = layer
+            .children()
+            .find(|node| node.has_tag_name("data"))
+            .with_context(|| format!("layer {:?} has no data", name))?;
+        if data.attribute("encoding") != Some("csv") {
+            bail!("layer {:?} must use csv encoding", name);
+        }
+        let gids = data
+            .text()
+            .unwrap_or_default()
+            .split(',')
+            .map(|gid| gid.trim().parse())
+            .collect::<Result<Vec<u32>, _>>()?;
+        layers.push(TiledLayer {
+            width: attribute("width")?,
+            height: attribute("height")?,
+            name,
+            gids,
+        });
+    }
+    validate(&layers)?;
+    Ok((first_gid, layers))
+}
+
+fn validate(layers: &[TiledLayer]) -> Result<()> {
+    for layer in layers {
+        if layer.gids.len() != (layer.width * layer.height) as usize {
+            bail!(
+                "layer {:?} has {} tiles but should have {}x{}",
+                layer.name,
+                layer.gids.len(),
+                layer.width,
+                layer.height
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::graphics::texture_atlas::TextureHandle;
+
+    use indoc::indoc;
+
+    #[test]
+    fn parse_tmx_should_read_csv_layers() -> Result<()> {
+        let (first_gid, layers) = parse_tmx(indoc!(
+            r#"
+            <map width="2" height="2">
+              <tileset firstgid="1" source="tiles.tsx"/>
+              <layer name="ground" width="2" height="2">
+                <data encoding="csv">
+            1,2,
+            0,3
+            </data>
+              </layer>
+            </map>
+            "#
+        ))?;
+
+        assert_eq!(first_gid, 1);
+        assert_eq!(layers[0].name, "ground");
+        assert_eq!(layers[0].gids, vec![1, 2, 0, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_tmj_should_skip_object_layers() -> Result<()> {
+        let (_, layers) = parse_tmj(indoc!(
+            r#"
+            {
+              "tilesets": [{ "firstgid": 1 }],
+              "layers": [
+                { "type": "objectgroup", "name": "spawns" },
+                {
+                  "type": "tilelayer", "name": "ground",
+                  "width": 2, "height": 1, "data": [1, 2]
+                }
+              ]
+            }
+            "#
+        ))?;
+
+        assert_eq!(layers.len(), 1);
+        assert_eq!(layers[0].gids, vec![1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn build_tile_map_should_flip_rows_and_clear_flags() {
+        let sheet =
+            SpriteSheet::new(TextureHandle::default(), (16, 16), (8, 8));
+        let layer = TiledLayer {
+            name: "ground".to_owned(),
+            width: 2,
+            height: 2,
+            gids: vec![1 | 0x8000_0000, 0, 0, 4],
+        };
+
+        let map = build_tile_map(layer, 1, sheet, 1.0);
+
+        assert_eq!(map.tile(0, 1), Some(0));
+        assert_eq!(map.tile(1, 0), Some(3));
+        assert_eq!(map.tile(0, 0), None);
+    }
+}